@@ -0,0 +1,51 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use sid_aseprite_converter::{
+    naming::AsepriteAnimationNaming,
+    sid_aseprite::{
+        self, AsepriteSheet, FromAsepriteFrameTuplesAndSidSpriteSheet, FromAsepriteSheet,
+    },
+    sidecar::AsepriteSidecar,
+};
+use sid_asset_packer::asset::{SidAnimationDefAsset, SidSpriteSheetAsset};
+
+use std::path::Path;
+
+// Parses an Aseprite JSON export, then converts it the way the converter does,
+// without writing anything.
+fuzz_target!(|data: &[u8]| {
+    let Ok(contents) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(sheet) = AsepriteSheet::from_json_str(contents) else {
+        return;
+    };
+
+    let Ok(sprite_sheet) = SidSpriteSheetAsset::from_aseprite_sheet(".", &sheet) else {
+        return;
+    };
+
+    let Ok(def) = SidAnimationDefAsset::from_aseprite_frame_tuples_and_sid_sprite_sheet(
+        sheet.frames(),
+        &sprite_sheet,
+    ) else {
+        return;
+    };
+
+    let _ = sid_aseprite::from_aseprite_sheet_to_sid_animations(
+        &sheet,
+        &def,
+        &AsepriteSidecar::new(),
+        &AsepriteAnimationNaming::new(),
+        ".",
+        Path::new(""),
+        true,
+    );
+});
@@ -0,0 +1,13 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use sid_asset_packer::resource::read;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read::decode_pak(data);
+});
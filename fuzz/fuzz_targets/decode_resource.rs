@@ -0,0 +1,34 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use sid_asset_packer::{compression, resource::read};
+
+use xxhash_rust::xxh3::xxh3_64;
+
+// Decodes a resource, possibly compressed. Its checksum is appended here, the
+// first byte telling its byte order, so that corrupted payloads get past it.
+fuzz_target!(|data: &[u8]| {
+    let Some((&order, data)) = data.split_first() else {
+        return;
+    };
+
+    let Ok(data) = compression::decompress(data) else {
+        return;
+    };
+
+    let checksum = xxh3_64(&data);
+    let mut resource = data;
+
+    if order & 1 == 0 {
+        resource.extend_from_slice(&checksum.to_le_bytes());
+    } else {
+        resource.extend_from_slice(&checksum.to_be_bytes());
+    }
+
+    let _ = read::decode_resource(&resource);
+});
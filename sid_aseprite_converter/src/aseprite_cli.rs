@@ -0,0 +1,144 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Exports of native Aseprite files through the Aseprite command line, for the
+// files relying on features the native reader does not support.
+
+use tempfile::TempDir;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::sid_aseprite::{AsepriteLayerFilter, AsepriteSheetError, AsepriteSheetResult};
+use crate::sid_debug;
+
+pub static DEFAULT_ASEPRITE_PATH: &str = "aseprite";
+
+// Frame names of the exports, matching the ones of the native reader.
+static FRAME_NAME_FORMAT: &str = "{title} ({tag}) {frame}.{extension}";
+static SPLIT_FRAME_NAME_FORMAT: &str = "{title} ({tag}) [{layer}] {frame}.{extension}";
+
+/// JSON and PNG export of an Aseprite file, in a temporary folder removed once
+/// dropped.
+#[derive(Debug)]
+pub struct AsepriteExport {
+    folder: TempDir,
+    json_path: PathBuf,
+}
+
+impl AsepriteExport {
+    pub fn folder(&self) -> &Path {
+        self.folder.path()
+    }
+
+    pub fn json_path(&self) -> &Path {
+        &self.json_path
+    }
+}
+
+/// Exports the Aseprite file at `path` with the Aseprite executable at
+/// `aseprite_path`. The visible layers accepted by `filter` are merged into a
+/// single sheet, unless `split_layers` is set: every layer (visible or not) is
+/// then exported with its frames, to be split afterwards.
+pub fn export<P1: AsRef<Path>, P2: AsRef<Path>>(
+    aseprite_path: P1,
+    path: P2,
+    filter: &AsepriteLayerFilter,
+    split_layers: bool,
+) -> AsepriteSheetResult<AsepriteExport> {
+    let aseprite_path = aseprite_path.as_ref();
+    let path = path.as_ref();
+
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| {
+            AsepriteSheetError::Malformed(format!("Invalid name for Aseprite file {:?}", path))
+        })?;
+
+    let folder = tempfile::Builder::new()
+        .prefix("sid_aseprite_")
+        .tempdir()
+        .map_err(|error| {
+            AsepriteSheetError::IO(format!("Unable to create a temporary folder: {error}"))
+        })?;
+
+    let image_path = folder.path().join(format!("{stem}.png"));
+    let json_path = folder.path().join(format!("{stem}.json"));
+
+    let mut command = Command::new(aseprite_path);
+    command.arg("--batch");
+
+    if split_layers {
+        command
+            .args(["--split-layers", "--all-layers"])
+            .args(["--filename-format", SPLIT_FRAME_NAME_FORMAT]);
+    } else {
+        if !filter.is_empty() {
+            for layer_name in list_layers(aseprite_path, path)? {
+                if !filter.matches(&layer_name) {
+                    command.arg("--ignore-layer").arg(layer_name);
+                }
+            }
+        }
+
+        command.args(["--filename-format", FRAME_NAME_FORMAT]);
+    }
+
+    command
+        .arg(path)
+        .arg("--sheet")
+        .arg(&image_path)
+        .arg("--data")
+        .arg(&json_path)
+        .args(["--format", "json-array", "--list-tags", "--list-slices"]);
+
+    run(aseprite_path, &mut command)?;
+
+    if !json_path.is_file() || !image_path.is_file() {
+        return Err(AsepriteSheetError::IO(format!(
+            "Aseprite did not export {:?}",
+            path
+        )));
+    }
+
+    Ok(AsepriteExport { folder, json_path })
+}
+
+/// Names of the visible layers of the Aseprite file at `path`.
+fn list_layers(aseprite_path: &Path, path: &Path) -> AsepriteSheetResult<Vec<String>> {
+    let mut command = Command::new(aseprite_path);
+    command.args(["--batch", "--list-layers"]).arg(path);
+
+    let output = run(aseprite_path, &mut command)?;
+
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// Runs `command`, returning its standard output.
+fn run(aseprite_path: &Path, command: &mut Command) -> AsepriteSheetResult<String> {
+    sid_debug!("Running {:?}", command);
+
+    let output = command.output().map_err(|error| {
+        AsepriteSheetError::IO(format!(
+            "Unable to run Aseprite at {:?}: {error}",
+            aseprite_path
+        ))
+    })?;
+
+    if !output.status.success() {
+        return Err(AsepriteSheetError::IO(format!(
+            "Aseprite failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,664 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Reader for the native Aseprite format (.aseprite/.ase), following the
+// specification from the Aseprite repository (docs/ase-file-specs.md).
+
+use flate2::read::ZlibDecoder;
+use image::{Rgba, RgbaImage};
+
+use std::{fs, io::Read, path::Path};
+
+use crate::sid_aseprite::{AsepriteSheetError, AsepriteSheetResult};
+use crate::sid_warning;
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const HEADER_SIZE: usize = 128;
+const FRAME_HEADER_SIZE: usize = 16;
+
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+const CHUNK_USER_DATA: u16 = 0x2020;
+const CHUNK_SLICE: u16 = 0x2022;
+
+const HEADER_FLAG_LAYER_OPACITY: u32 = 1;
+
+const LAYER_FLAG_VISIBLE: u16 = 1;
+const LAYER_TYPE_GROUP: u16 = 1;
+const LAYER_TYPE_TILEMAP: u16 = 2;
+
+const CEL_TYPE_RAW: u16 = 0;
+const CEL_TYPE_LINKED: u16 = 1;
+const CEL_TYPE_COMPRESSED: u16 = 2;
+
+const SLICE_FLAG_NINE_PATCH: u32 = 1;
+const SLICE_FLAG_PIVOT: u32 = 2;
+
+const USER_DATA_FLAG_TEXT: u32 = 1;
+
+const BLEND_MODE_NORMAL: u16 = 0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AsepriteColorDepth {
+    Indexed,
+    Grayscale,
+    Rgba,
+}
+
+impl AsepriteColorDepth {
+    fn from_bits_per_pixel(bits_per_pixel: u16) -> Option<Self> {
+        match bits_per_pixel {
+            8 => Some(AsepriteColorDepth::Indexed),
+            16 => Some(AsepriteColorDepth::Grayscale),
+            32 => Some(AsepriteColorDepth::Rgba),
+            _ => None,
+        }
+    }
+
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            AsepriteColorDepth::Indexed => 1,
+            AsepriteColorDepth::Grayscale => 2,
+            AsepriteColorDepth::Rgba => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteFileLayer {
+    pub name: String,
+    /// Visible, and so are all its parent groups.
+    pub visible: bool,
+    pub opacity: u8,
+    pub blend_mode: u16,
+    pub child_level: u16,
+    pub is_group: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteFileCel {
+    pub layer_index: u16,
+    pub x: i16,
+    pub y: i16,
+    pub opacity: u8,
+    pub width: u16,
+    pub height: u16,
+    /// Pixels, already converted to RGBA.
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteFileFrame {
+    pub duration: u16,
+    pub cels: Vec<AsepriteFileCel>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteFileTag {
+    pub name: String,
+    pub from: u16,
+    pub to: u16,
+    pub direction: u8,
+    /// Text of the user data of the tag, empty if none.
+    pub user_data: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteFileSliceKey {
+    pub frame: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub center: Option<(i32, i32, u32, u32)>,
+    pub pivot: Option<(i32, i32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteFileSlice {
+    pub name: String,
+    pub keys: Vec<AsepriteFileSliceKey>,
+}
+
+#[derive(Debug)]
+pub struct AsepriteFile {
+    pub width: u16,
+    pub height: u16,
+    pub color_depth: AsepriteColorDepth,
+    pub layers: Vec<AsepriteFileLayer>,
+    pub frames: Vec<AsepriteFileFrame>,
+    pub tags: Vec<AsepriteFileTag>,
+    pub palette: Vec<[u8; 4]>,
+    pub slices: Vec<AsepriteFileSlice>,
+    transparent_index: u8,
+    uses_layer_opacity: bool,
+    /// Tag the next user data chunk belongs to: the user data chunks following
+    /// a tags chunk belong to its tags, in order.
+    next_tag_user_data: Option<usize>,
+}
+
+struct AsepriteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> AsepriteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, count: usize) -> AsepriteSheetResult<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                AsepriteSheetError::Malformed("Unexpected end of Aseprite file".to_string())
+            })?;
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> AsepriteSheetResult<()> {
+        self.bytes(count)?;
+        Ok(())
+    }
+
+    fn byte(&mut self) -> AsepriteSheetResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn word(&mut self) -> AsepriteSheetResult<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn short(&mut self) -> AsepriteSheetResult<i16> {
+        Ok(self.word()? as i16)
+    }
+
+    fn dword(&mut self) -> AsepriteSheetResult<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn long(&mut self) -> AsepriteSheetResult<i32> {
+        Ok(self.dword()? as i32)
+    }
+
+    fn string(&mut self) -> AsepriteSheetResult<String> {
+        let length = self.word()? as usize;
+        let bytes = self.bytes(length)?;
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn remaining(&mut self) -> &'a [u8] {
+        let bytes = &self.data[self.position..];
+        self.position = self.data.len();
+        bytes
+    }
+}
+
+impl AsepriteFile {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> AsepriteSheetResult<Self> {
+        let data = fs::read(&path).map_err(|_| {
+            AsepriteSheetError::IO(format!("Failed to read file {:?}", path.as_ref()))
+        })?;
+
+        Self::from_bytes(&data).map_err(|error| match error {
+            AsepriteSheetError::Malformed(error) => {
+                AsepriteSheetError::Malformed(format!("{error} in file {:?}", path.as_ref()))
+            }
+            error => error,
+        })
+    }
+
+    pub fn from_bytes(data: &[u8]) -> AsepriteSheetResult<Self> {
+        let mut reader = AsepriteReader::new(data);
+        let header = AsepriteReader::new(reader.bytes(HEADER_SIZE)?);
+        let (mut file, frame_count) = Self::from_header(header)?;
+
+        for _ in 0..frame_count {
+            let mut frame_header = AsepriteReader::new(reader.bytes(FRAME_HEADER_SIZE)?);
+            let frame_size = frame_header.dword()? as usize;
+
+            if frame_header.word()? != FRAME_MAGIC {
+                return Err(AsepriteSheetError::Malformed(
+                    "Invalid Aseprite frame magic number".to_string(),
+                ));
+            }
+
+            let old_chunk_count = frame_header.word()? as usize;
+            let duration = frame_header.word()?;
+            frame_header.skip(2)?;
+            let chunk_count = match frame_header.dword()? as usize {
+                0 => old_chunk_count,
+                chunk_count => chunk_count,
+            };
+
+            let frame_data = reader.bytes(frame_size.saturating_sub(FRAME_HEADER_SIZE))?;
+            let mut frame_reader = AsepriteReader::new(frame_data);
+
+            file.frames.push(AsepriteFileFrame {
+                duration,
+                cels: vec![],
+            });
+
+            for _ in 0..chunk_count {
+                let chunk_size = frame_reader.dword()? as usize;
+                let chunk_type = frame_reader.word()?;
+                let chunk_data = frame_reader.bytes(chunk_size.saturating_sub(6))?;
+                file.read_chunk(chunk_type, AsepriteReader::new(chunk_data))?;
+            }
+        }
+
+        file.resolve_layer_visibility();
+        Ok(file)
+    }
+
+    fn from_header(mut header: AsepriteReader) -> AsepriteSheetResult<(Self, usize)> {
+        header.skip(4)?;
+
+        if header.word()? != HEADER_MAGIC {
+            return Err(AsepriteSheetError::Malformed(
+                "Invalid Aseprite header magic number".to_string(),
+            ));
+        }
+
+        let frame_count = header.word()? as usize;
+        let width = header.word()?;
+        let height = header.word()?;
+        let bits_per_pixel = header.word()?;
+
+        let color_depth =
+            AsepriteColorDepth::from_bits_per_pixel(bits_per_pixel).ok_or_else(|| {
+                AsepriteSheetError::Malformed(format!("Unsupported color depth {bits_per_pixel}"))
+            })?;
+
+        let flags = header.dword()?;
+        header.skip(2 + 4 + 4)?;
+        let transparent_index = header.byte()?;
+
+        let file = Self {
+            width,
+            height,
+            color_depth,
+            layers: vec![],
+            frames: Vec::with_capacity(frame_count),
+            tags: vec![],
+            palette: vec![],
+            slices: vec![],
+            transparent_index,
+            uses_layer_opacity: flags & HEADER_FLAG_LAYER_OPACITY != 0,
+            next_tag_user_data: None,
+        };
+
+        Ok((file, frame_count))
+    }
+
+    fn read_chunk(
+        &mut self,
+        chunk_type: u16,
+        mut chunk: AsepriteReader,
+    ) -> AsepriteSheetResult<()> {
+        let next_tag_user_data = self.next_tag_user_data.take();
+
+        match chunk_type {
+            CHUNK_LAYER => {
+                let flags = chunk.word()?;
+                let layer_type = chunk.word()?;
+                let child_level = chunk.word()?;
+                chunk.skip(4)?;
+                let blend_mode = chunk.word()?;
+                let opacity = chunk.byte()?;
+                chunk.skip(3)?;
+                let name = chunk.string()?;
+
+                if layer_type == LAYER_TYPE_TILEMAP {
+                    sid_warning!("Tilemap layer {:?} is not supported, ignoring it.", name);
+                }
+
+                self.layers.push(AsepriteFileLayer {
+                    name,
+                    visible: flags & LAYER_FLAG_VISIBLE != 0 && layer_type != LAYER_TYPE_TILEMAP,
+                    opacity: if self.uses_layer_opacity {
+                        opacity
+                    } else {
+                        255
+                    },
+                    blend_mode,
+                    child_level,
+                    is_group: layer_type == LAYER_TYPE_GROUP,
+                });
+            }
+            CHUNK_CEL => {
+                let layer_index = chunk.word()?;
+                let x = chunk.short()?;
+                let y = chunk.short()?;
+                let opacity = chunk.byte()?;
+                let cel_type = chunk.word()?;
+                chunk.skip(2 + 5)?;
+
+                let cel = match cel_type {
+                    CEL_TYPE_RAW | CEL_TYPE_COMPRESSED => {
+                        let width = chunk.word()?;
+                        let height = chunk.word()?;
+                        let data = chunk.remaining();
+                        let pixel_count = width as usize * height as usize;
+                        let size = pixel_count * self.color_depth.bytes_per_pixel();
+
+                        let raw = if cel_type == CEL_TYPE_RAW {
+                            data.get(..size).map(<[u8]>::to_vec)
+                        } else {
+                            let mut raw = Vec::with_capacity(size);
+
+                            ZlibDecoder::new(data)
+                                .read_to_end(&mut raw)
+                                .ok()
+                                .filter(|_| raw.len() >= size)
+                                .map(|_| raw)
+                        };
+
+                        let raw = raw.ok_or_else(|| {
+                            AsepriteSheetError::Malformed("Invalid cel pixels".to_string())
+                        })?;
+
+                        AsepriteFileCel {
+                            layer_index,
+                            x,
+                            y,
+                            opacity,
+                            width,
+                            height,
+                            pixels: self.to_rgba(&raw[..size], layer_index),
+                        }
+                    }
+                    CEL_TYPE_LINKED => {
+                        let frame_index = chunk.word()? as usize;
+
+                        let linked = self
+                            .frames
+                            .get(frame_index)
+                            .and_then(|frame| {
+                                frame.cels.iter().find(|cel| cel.layer_index == layer_index)
+                            })
+                            .ok_or_else(|| {
+                                AsepriteSheetError::Malformed("Invalid linked cel".to_string())
+                            })?;
+
+                        AsepriteFileCel {
+                            x,
+                            y,
+                            opacity,
+                            ..linked.clone()
+                        }
+                    }
+                    // Tilemap cels, only found in (ignored) tilemap layers.
+                    _ => return Ok(()),
+                };
+
+                if let Some(frame) = self.frames.last_mut() {
+                    frame.cels.push(cel);
+                }
+            }
+            CHUNK_TAGS => {
+                let tag_count = chunk.word()?;
+                chunk.skip(8)?;
+                self.next_tag_user_data = Some(self.tags.len());
+
+                for _ in 0..tag_count {
+                    let from = chunk.word()?;
+                    let to = chunk.word()?;
+                    let direction = chunk.byte()?;
+                    chunk.skip(2 + 6 + 3 + 1)?;
+                    let name = chunk.string()?;
+
+                    self.tags.push(AsepriteFileTag {
+                        name,
+                        from,
+                        to,
+                        direction,
+                        user_data: String::new(),
+                    });
+                }
+            }
+            CHUNK_USER_DATA => {
+                let Some(tag_index) = next_tag_user_data else {
+                    return Ok(());
+                };
+
+                let flags = chunk.dword()?;
+
+                if let Some(tag) = self.tags.get_mut(tag_index) {
+                    if flags & USER_DATA_FLAG_TEXT != 0 {
+                        tag.user_data = chunk.string()?;
+                    }
+
+                    self.next_tag_user_data = Some(tag_index + 1);
+                }
+            }
+            CHUNK_PALETTE => {
+                let size = chunk.dword()? as usize;
+                let first = chunk.dword()? as usize;
+                let last = chunk.dword()? as usize;
+                chunk.skip(8)?;
+
+                if self.palette.len() < size {
+                    self.palette.resize(size, [0, 0, 0, 0]);
+                }
+
+                for i in first..=last {
+                    let flags = chunk.word()?;
+                    let color = chunk.bytes(4)?;
+
+                    if flags & 1 != 0 {
+                        chunk.string()?;
+                    }
+
+                    if let Some(entry) = self.palette.get_mut(i) {
+                        entry.copy_from_slice(color);
+                    }
+                }
+            }
+            // Only found in old files, which have no new palette chunk.
+            CHUNK_OLD_PALETTE if self.palette.is_empty() => {
+                let packet_count = chunk.word()?;
+                let mut index = 0;
+
+                for _ in 0..packet_count {
+                    index += chunk.byte()? as usize;
+
+                    let color_count = match chunk.byte()? {
+                        0 => 256,
+                        color_count => color_count as usize,
+                    };
+
+                    for _ in 0..color_count {
+                        let rgb = chunk.bytes(3)?;
+
+                        if self.palette.len() <= index {
+                            self.palette.resize(index + 1, [0, 0, 0, 0]);
+                        }
+
+                        self.palette[index] = [rgb[0], rgb[1], rgb[2], 255];
+                        index += 1;
+                    }
+                }
+            }
+            CHUNK_SLICE => {
+                let key_count = chunk.dword()?;
+                let flags = chunk.dword()?;
+                chunk.skip(4)?;
+                let name = chunk.string()?;
+                let mut keys = vec![];
+
+                for _ in 0..key_count {
+                    let frame = chunk.dword()?;
+                    let x = chunk.long()?;
+                    let y = chunk.long()?;
+                    let width = chunk.dword()?;
+                    let height = chunk.dword()?;
+
+                    let center = if flags & SLICE_FLAG_NINE_PATCH != 0 {
+                        Some((chunk.long()?, chunk.long()?, chunk.dword()?, chunk.dword()?))
+                    } else {
+                        None
+                    };
+
+                    let pivot = if flags & SLICE_FLAG_PIVOT != 0 {
+                        Some((chunk.long()?, chunk.long()?))
+                    } else {
+                        None
+                    };
+
+                    keys.push(AsepriteFileSliceKey {
+                        frame,
+                        x,
+                        y,
+                        width,
+                        height,
+                        center,
+                        pivot,
+                    });
+                }
+
+                self.slices.push(AsepriteFileSlice { name, keys });
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn to_rgba(&self, raw: &[u8], layer_index: u16) -> Vec<u8> {
+        match self.color_depth {
+            AsepriteColorDepth::Rgba => raw.to_vec(),
+            AsepriteColorDepth::Grayscale => raw
+                .chunks_exact(2)
+                .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+                .collect(),
+            AsepriteColorDepth::Indexed => {
+                // The transparent index is opaque on the background layer only,
+                // which is always the first one.
+                let is_background = layer_index == 0;
+
+                raw.iter()
+                    .flat_map(|&index| {
+                        if index == self.transparent_index && !is_background {
+                            [0, 0, 0, 0]
+                        } else {
+                            self.palette
+                                .get(index as usize)
+                                .copied()
+                                .unwrap_or([0, 0, 0, 0])
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    // A layer nested in a hidden group is hidden too.
+    fn resolve_layer_visibility(&mut self) {
+        let mut hidden_level: Option<u16> = None;
+
+        for layer in &mut self.layers {
+            if let Some(level) = hidden_level {
+                if layer.child_level > level {
+                    layer.visible = false;
+                    continue;
+                }
+
+                hidden_level = None;
+            }
+
+            if !layer.visible {
+                hidden_level = Some(layer.child_level);
+            }
+        }
+    }
+
+    /// Composites the layers of a frame for which `layer_mask` is set.
+    pub fn render_frame(&self, frame_index: usize, layer_mask: &[bool]) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width as u32, self.height as u32);
+
+        let frame = match self.frames.get(frame_index) {
+            Some(frame) => frame,
+            None => return image,
+        };
+
+        let mut cels: Vec<&AsepriteFileCel> = frame.cels.iter().collect();
+        cels.sort_by_key(|cel| cel.layer_index);
+
+        for cel in cels {
+            let layer_index = cel.layer_index as usize;
+
+            let layer = match self.layers.get(layer_index) {
+                Some(layer) if layer_mask.get(layer_index) == Some(&true) => layer,
+                _ => continue,
+            };
+
+            if layer.blend_mode != BLEND_MODE_NORMAL {
+                sid_warning!(
+                    "Blend mode {} of layer {:?} is not supported, using normal.",
+                    layer.blend_mode,
+                    layer.name
+                );
+            }
+
+            let opacity = cel.opacity as u32 * layer.opacity as u32 / 255;
+
+            for cel_y in 0..cel.height as i32 {
+                let y = cel.y as i32 + cel_y;
+
+                if y < 0 || y >= self.height as i32 {
+                    continue;
+                }
+
+                for cel_x in 0..cel.width as i32 {
+                    let x = cel.x as i32 + cel_x;
+
+                    if x < 0 || x >= self.width as i32 {
+                        continue;
+                    }
+
+                    let offset = (cel_y as usize * cel.width as usize + cel_x as usize) * 4;
+                    let source = &cel.pixels[offset..offset + 4];
+                    let destination = image.get_pixel_mut(x as u32, y as u32);
+                    *destination = blend_normal(*destination, source, opacity);
+                }
+            }
+        }
+
+        image
+    }
+}
+
+fn blend_normal(destination: Rgba<u8>, source: &[u8], opacity: u32) -> Rgba<u8> {
+    let source_alpha = source[3] as u32 * opacity / 255;
+
+    if source_alpha == 0 {
+        return destination;
+    }
+
+    let destination_alpha = destination[3] as u32;
+    let alpha = source_alpha + destination_alpha * (255 - source_alpha) / 255;
+
+    let mut result = [0, 0, 0, alpha as u8];
+
+    for i in 0..3 {
+        let color = (source[i] as u32 * source_alpha
+            + destination[i] as u32 * destination_alpha * (255 - source_alpha) / 255)
+            / alpha;
+        result[i] = color as u8;
+    }
+
+    Rgba(result)
+}
@@ -0,0 +1,358 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Importer for the bitmap fonts exported by AngelCode BMFont and compatible
+// tools (e.g. Hiero, Glyph Designer), in the text or the binary format. Each
+// page image becomes a texture, and the glyphs and kernings a bitmap font
+// referencing them, as an alternative to rasterizing TrueType fonts.
+
+use sid_asset_packer::asset::{
+    SidBitmapFontAsset, SidBitmapFontGlyphAsset, SidBitmapFontKerningAsset, SidTextureAsset,
+};
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::resource::SerializeSidAsset;
+use sid_asset_packer::sid::SidStringId;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sid_aseprite::write_asset;
+
+/// Magic bytes of binary fonts, followed by their version.
+static BINARY_MAGIC: &[u8] = b"BMF";
+
+/// Only version of the binary format written by BMFont since 1.12.
+const BINARY_VERSION: u8 = 3;
+
+const BLOCK_INFO: u8 = 1;
+const BLOCK_COMMON: u8 = 2;
+const BLOCK_PAGES: u8 = 3;
+const BLOCK_CHARS: u8 = 4;
+const BLOCK_KERNING_PAIRS: u8 = 5;
+
+/// Sizes of a glyph and a kerning pair in binary fonts.
+const BINARY_CHAR_SIZE: usize = 20;
+const BINARY_KERNING_PAIR_SIZE: usize = 10;
+
+/// Font as described by a BMFont file, before its pages are named.
+#[derive(Default)]
+struct BmFont {
+    size: f32,
+    line_height: u32,
+    base: u32,
+    /// Page files by page id, relative to the font file.
+    pages: Vec<(u32, String)>,
+    glyphs: Vec<SidBitmapFontGlyphAsset>,
+    kernings: Vec<SidBitmapFontKerningAsset>,
+}
+
+fn malformed(path: &Path, error: impl std::fmt::Display) -> SidToolError {
+    SidToolError::Malformed(format!("Invalid BMFont file {:?}: {error}", path))
+}
+
+/// Pages are packed as 8-bit indices.
+fn page_index(page: u32, path: &Path) -> SidToolResult<u8> {
+    page.try_into()
+        .map_err(|_| malformed(path, format!("page {page} is out of range")))
+}
+
+/// Key-value pairs of a line of a text font, e.g.
+/// `page id=0 file="font_0.png"`, along with its tag.
+fn parse_text_line(line: &str) -> (&str, HashMap<&str, &str>) {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let mut values = HashMap::new();
+
+    loop {
+        rest = rest.trim_start();
+
+        let Some((key, value)) = rest.split_once('=') else {
+            break;
+        };
+
+        // Quoted values may hold spaces, e.g. font faces.
+        let (value, remainder) = match value.strip_prefix('"') {
+            Some(value) => value.split_once('"').unwrap_or((value, "")),
+            None => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+        };
+
+        values.insert(key.trim(), value);
+        rest = remainder;
+    }
+
+    (tag, values)
+}
+
+fn text_value<T: std::str::FromStr>(
+    values: &HashMap<&str, &str>,
+    key: &str,
+    path: &Path,
+) -> SidToolResult<T> {
+    let value = values
+        .get(key)
+        .ok_or_else(|| malformed(path, format!("missing {key:?}")))?;
+
+    value
+        .parse()
+        .map_err(|_| malformed(path, format!("invalid {key:?} {value:?}")))
+}
+
+fn parse_text_font(text: &str, path: &Path) -> SidToolResult<BmFont> {
+    let mut font = BmFont::default();
+
+    for line in text.lines() {
+        let (tag, values) = parse_text_line(line);
+
+        match tag {
+            "info" => {
+                // Negative sizes match the height of the glyphs, not of the cells.
+                font.size = text_value::<f32>(&values, "size", path)?.abs();
+            }
+            "common" => {
+                font.line_height = text_value(&values, "lineHeight", path)?;
+                font.base = text_value(&values, "base", path)?;
+            }
+            "page" => {
+                let file = values
+                    .get("file")
+                    .ok_or_else(|| malformed(path, "missing page file"))?;
+
+                font.pages
+                    .push((text_value(&values, "id", path)?, file.to_string()));
+            }
+            "char" => {
+                let page = if values.contains_key("page") {
+                    page_index(text_value(&values, "page", path)?, path)?
+                } else {
+                    0
+                };
+
+                font.glyphs.push(SidBitmapFontGlyphAsset {
+                    character: text_value(&values, "id", path)?,
+                    x: text_value(&values, "x", path)?,
+                    y: text_value(&values, "y", path)?,
+                    width: text_value(&values, "width", path)?,
+                    height: text_value(&values, "height", path)?,
+                    x_offset: text_value(&values, "xoffset", path)?,
+                    y_offset: text_value(&values, "yoffset", path)?,
+                    x_advance: text_value(&values, "xadvance", path)?,
+                    page,
+                });
+            }
+            "kerning" => font.kernings.push(SidBitmapFontKerningAsset {
+                first: text_value(&values, "first", path)?,
+                second: text_value(&values, "second", path)?,
+                amount: text_value(&values, "amount", path)?,
+            }),
+            // Counts of chars and kernings are implied by their lines.
+            _ => {}
+        }
+    }
+
+    Ok(font)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_i16(bytes: &[u8], offset: usize) -> i16 {
+    read_u16(bytes, offset) as i16
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn check_block_size(block: &[u8], size: usize, name: &str, path: &Path) -> SidToolResult<()> {
+    if block.len() < size {
+        return Err(malformed(path, format!("truncated {name} block")));
+    }
+
+    Ok(())
+}
+
+fn parse_binary_font(bytes: &[u8], path: &Path) -> SidToolResult<BmFont> {
+    let version = bytes[BINARY_MAGIC.len()];
+
+    if version != BINARY_VERSION {
+        return Err(SidToolError::UnsupportedFormat(format!(
+            "Unsupported version {version} of binary BMFont file {:?}",
+            path
+        )));
+    }
+
+    let mut font = BmFont::default();
+    let mut offset = BINARY_MAGIC.len() + 1;
+
+    while offset < bytes.len() {
+        if bytes.len() - offset < 5 {
+            return Err(malformed(path, "truncated block header"));
+        }
+
+        let block_type = bytes[offset];
+        let size = read_u32(bytes, offset + 1) as usize;
+        offset += 5;
+
+        let block = bytes
+            .get(offset..offset.saturating_add(size))
+            .ok_or_else(|| malformed(path, format!("truncated block {block_type}")))?;
+
+        offset += size;
+
+        match block_type {
+            BLOCK_INFO => {
+                check_block_size(block, 2, "info", path)?;
+                font.size = read_i16(block, 0).unsigned_abs() as f32;
+            }
+            BLOCK_COMMON => {
+                check_block_size(block, 4, "common", path)?;
+                font.line_height = read_u16(block, 0) as u32;
+                font.base = read_u16(block, 2) as u32;
+            }
+            BLOCK_PAGES => {
+                // Page file names are null-terminated, and all have the same length.
+                for (id, file) in block
+                    .split(|&byte| byte == 0)
+                    .filter(|file| !file.is_empty())
+                    .enumerate()
+                {
+                    font.pages
+                        .push((id as u32, String::from_utf8_lossy(file).into_owned()));
+                }
+            }
+            BLOCK_CHARS => {
+                for glyph in block.chunks_exact(BINARY_CHAR_SIZE) {
+                    font.glyphs.push(SidBitmapFontGlyphAsset {
+                        character: read_u32(glyph, 0),
+                        x: read_u16(glyph, 4) as u32,
+                        y: read_u16(glyph, 6) as u32,
+                        width: read_u16(glyph, 8) as u32,
+                        height: read_u16(glyph, 10) as u32,
+                        x_offset: read_i16(glyph, 12) as i32,
+                        y_offset: read_i16(glyph, 14) as i32,
+                        x_advance: read_i16(glyph, 16) as i32,
+                        page: glyph[18],
+                    });
+                }
+            }
+            BLOCK_KERNING_PAIRS => {
+                for kerning in block.chunks_exact(BINARY_KERNING_PAIR_SIZE) {
+                    font.kernings.push(SidBitmapFontKerningAsset {
+                        first: read_u32(kerning, 0),
+                        second: read_u32(kerning, 4),
+                        amount: read_i16(kerning, 8) as i32,
+                    });
+                }
+            }
+            block_type => {
+                return Err(malformed(path, format!("unknown block {block_type}")));
+            }
+        }
+    }
+
+    Ok(font)
+}
+
+/// Converts the BMFont file at `path` into a bitmap font asset named after the
+/// file, and a texture asset per page image, named after the font if it has a
+/// single page and after the font and the page id otherwise, e.g. `title_1`.
+/// They are written to `subfolder` of their namespace folders. Returns the ids
+/// of the assets.
+pub fn from_bmfont_to_sid_assets<P: AsRef<Path>>(
+    path: &Path,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let bytes = fs::read(path).map_err(|error| {
+        SidToolError::IO(format!("Unable to read BMFont file {:?}: {error}", path))
+    })?;
+
+    let mut font = if bytes.starts_with(BINARY_MAGIC) && bytes.len() > BINARY_MAGIC.len() {
+        parse_binary_font(&bytes, path)?
+    } else {
+        let text = String::from_utf8(bytes).map_err(|error| malformed(path, error))?;
+        parse_text_font(&text, path)?
+    };
+
+    let name = path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| malformed(path, "invalid font name"))
+        .map(|name| names.normalize(name))?;
+
+    if font.pages.is_empty() {
+        return Err(malformed(path, "no pages"));
+    }
+
+    // Glyphs refer to pages by id, which are packed by index.
+    font.pages.sort_by_key(|&(id, _)| id);
+
+    for glyph in &mut font.glyphs {
+        glyph.page = font
+            .pages
+            .iter()
+            .position(|&(id, _)| id == glyph.page as u32)
+            .ok_or_else(|| malformed(path, format!("unknown page {}", glyph.page)))
+            .and_then(|index| page_index(index as u32, path))?;
+    }
+
+    let font_folder = path.parent().unwrap_or(Path::new("."));
+    let single_page = font.pages.len() == 1;
+    let mut ids = vec![];
+    let mut page_names = vec![];
+
+    for (id, file) in &font.pages {
+        let image_from_path = font_folder.join(file);
+
+        if !image_from_path.is_file() {
+            return Err(SidToolError::IO(format!(
+                "Unable to find page {id} of BMFont file {:?} at {:?}",
+                path, image_from_path
+            )));
+        }
+
+        let page_name = if single_page {
+            name.clone()
+        } else {
+            names.normalize(&format!("{name}_{id}"))
+        };
+
+        // Page files of different fonts often share names, e.g. "font_0.png".
+        let mut image_path = PathBuf::from(&page_name);
+
+        if let Some(extension) = image_from_path.extension() {
+            image_path.set_extension(extension);
+        }
+
+        let texture = SidTextureAsset::with_data(page_name, image_path, image_from_path);
+        write_asset(&texture, names, &assets_output_path, subfolder, dry_run)?;
+        ids.push(texture.resource_id());
+        page_names.push(texture.name);
+    }
+
+    let font = SidBitmapFontAsset::with_data(
+        name,
+        font.size,
+        font.line_height,
+        font.base,
+        page_names,
+        font.glyphs,
+        font.kernings,
+    );
+
+    write_asset(&font, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(font.resource_id());
+
+    Ok(ids)
+}
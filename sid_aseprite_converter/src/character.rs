@@ -0,0 +1,140 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Deserialize;
+
+use sid_asset_packer::asset::{
+    SidAnimationAsset, SidCharacterAsset, SidCharacterPartAsset, SidPackedAsset,
+};
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::resource::{SerializeSidAsset, SidAssetProcessor};
+use sid_asset_packer::sid::SidStringId;
+
+use std::fs;
+use std::path::Path;
+
+use crate::sid_aseprite::write_asset;
+use crate::sid_warning;
+
+pub static CHARACTER_EXTENSION: &str = "character.toml";
+
+/// Character grouping several sheets, read from a `<name>.character.toml`
+/// file in the sheets folder, with a `[[parts]]` table per sheet.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AsepriteCharacterManifest {
+    /// Name of the character, instead of the one of the file.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub parts: Vec<AsepriteCharacterPart>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AsepriteCharacterPart {
+    pub name: String,
+    /// Name of the sprite sheet asset of the part.
+    pub sheet: String,
+}
+
+impl AsepriteCharacterManifest {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> SidToolResult<Self> {
+        let path = path.as_ref();
+
+        let contents = fs::read_to_string(path).map_err(|error| {
+            SidToolError::IO(format!(
+                "Unable to read character manifest {:?}: {error}",
+                path
+            ))
+        })?;
+
+        toml::from_str(&contents).map_err(|error| {
+            SidToolError::Malformed(format!("Invalid character manifest {:?}: {error}", path))
+        })
+    }
+}
+
+/// Whether `path` is a character manifest.
+pub fn is_character_manifest(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.to_ascii_lowercase()
+                .ends_with(&format!(".{CHARACTER_EXTENSION}"))
+        })
+}
+
+/// Converts the character manifest at `manifest_path` into a character asset.
+/// The animations of each part are the ones of `assets_output_path` playing
+/// its sheet, so sheets must be converted first.
+pub fn from_character_manifest_to_sid_character<P: AsRef<Path>>(
+    manifest_path: &Path,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let manifest = AsepriteCharacterManifest::from_file(manifest_path)?;
+
+    let name = match manifest.name {
+        Some(name) => name,
+        None => {
+            let file_name = manifest_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+
+            file_name[..file_name.len() - CHARACTER_EXTENSION.len() - 1].to_string()
+        }
+    };
+
+    let name = names.normalize(&name);
+
+    let animations_folder = assets_output_path
+        .as_ref()
+        .join(SidAnimationAsset::namespace());
+
+    let animations: Vec<SidAnimationAsset> = if animations_folder.is_dir() {
+        SidAnimationAsset::compatible_entries(&animations_folder)
+            .into_iter()
+            .filter_map(|path| SidAnimationAsset::load_asset(&path).ok())
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let parts = manifest
+        .parts
+        .into_iter()
+        .map(|part| {
+            // Sheets were normalized when converted.
+            let sheet = names.normalize(&part.sheet);
+
+            let mut part_animations: Vec<String> = animations
+                .iter()
+                .filter(|animation| animation.def_name == sheet)
+                .map(|animation| animation.name.clone())
+                .collect();
+
+            part_animations.sort();
+
+            if part_animations.is_empty() {
+                sid_warning!(
+                    "No animations found for part {:?} of character {:?}, playing sheet {:?}.",
+                    part.name,
+                    name,
+                    sheet
+                );
+            }
+
+            // Animation defs are named after their sheet.
+            SidCharacterPartAsset::with_data(part.name, sheet.clone(), sheet, part_animations)
+        })
+        .collect();
+
+    let asset = SidCharacterAsset::with_data(name, parts);
+    write_asset(&asset, names, assets_output_path, subfolder, dry_run)?;
+    Ok(vec![asset.resource_id()])
+}
@@ -0,0 +1,97 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Importer for animated GIFs, e.g. prototype or placeholder animations, each
+// file becoming a sprite sheet, its animation definition and a single
+// animation timed by the delays of the GIF.
+
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, Frame};
+
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::sid::{SidAnimationFrameDuration, SidStringId};
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::image_sequence::{self, SidFrameImage, DEFAULT_SEQUENCE_FRAME_DURATION};
+use crate::sidecar::AsepriteSidecar;
+
+/// Whether the file at `path` is a GIF.
+pub fn is_gif(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"))
+}
+
+/// Duration of `frame` in milliseconds. Delays of zero are played at the
+/// default duration, as browsers do.
+fn frame_duration(frame: &Frame) -> SidAnimationFrameDuration {
+    let (numerator, denominator) = frame.delay().numer_denom_ms();
+    let duration = numerator.checked_div(denominator).unwrap_or(0);
+
+    match duration {
+        0 => DEFAULT_SEQUENCE_FRAME_DURATION,
+        duration => {
+            duration.min(SidAnimationFrameDuration::MAX as u32) as SidAnimationFrameDuration
+        }
+    }
+}
+
+/// Packs the frames of the GIF at `path` into a sprite sheet whose image is
+/// saved in `image_folder`, and writes it along with its animation definition
+/// and an animation playing every frame, all named after the file, to
+/// `subfolder` of their namespace folders. Frames last as long as their delay
+/// unless `sidecar` sets a duration. Returns the ids of the assets.
+pub fn from_gif_to_sid_assets<P1: AsRef<Path>, P2: AsRef<Path>>(
+    path: &Path,
+    sidecar: &AsepriteSidecar,
+    names: &SidNameRules,
+    image_folder: P1,
+    assets_output_path: P2,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let file = File::open(path)
+        .map_err(|error| SidToolError::IO(format!("Unable to open GIF {:?}: {error}", path)))?;
+
+    let decoder = GifDecoder::new(BufReader::new(file))
+        .map_err(|error| SidToolError::Malformed(format!("Invalid GIF {:?}: {error}", path)))?;
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    // Frames come composited over the previous ones, as they are displayed.
+    let images = decoder.into_frames().enumerate().map(|(index, frame)| {
+        let frame = frame.map_err(|error| {
+            SidToolError::Malformed(format!(
+                "Unable to decode frame {index} of GIF {:?}: {error}",
+                path
+            ))
+        })?;
+
+        Ok(SidFrameImage {
+            label: format!("{index} of GIF {:?}", path),
+            duration: sidecar
+                .frame_duration
+                .unwrap_or_else(|| frame_duration(&frame)),
+            image: frame.into_buffer(),
+        })
+    });
+
+    image_sequence::from_frame_images_to_sid_assets(
+        &name,
+        images,
+        sidecar,
+        names,
+        image_folder,
+        assets_output_path,
+        subfolder,
+        dry_run,
+    )
+}
@@ -0,0 +1,321 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Importer for the meshes of glTF 2.0 files, text or binary, e.g. simple props
+// of 2.5D scenes. Each mesh becomes a mesh asset in its own space, a submesh
+// per triangle primitive, and the base color images of their materials become
+// textures. Nodes, skins, animations and the other material properties are
+// not imported.
+
+use ::gltf::{image::Source, mesh::Mode, Gltf};
+use base64::Engine;
+
+use sid_asset_packer::asset::{
+    SidMeshAsset, SidMeshSubmeshAsset, SidMeshTexture, SidMeshVertexAsset, SidPackedAsset,
+    SidTextureAsset,
+};
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::resource::SerializeSidAsset;
+use sid_asset_packer::sid::SidStringId;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sid_aseprite::write_asset;
+use crate::sid_warning;
+
+fn malformed(path: &Path, error: impl std::fmt::Display) -> SidToolError {
+    SidToolError::Malformed(format!("Invalid glTF file {:?}: {error}", path))
+}
+
+/// Extension of the images of `mime_type`, which glTF restricts to PNG and
+/// JPEG.
+fn image_extension(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/jpeg" => "jpg",
+        _ => "png",
+    }
+}
+
+/// Names `count` glTF elements after `name` if there is a single one, and
+/// after `name` and their own name or index otherwise.
+fn element_name(
+    name: &str,
+    element_name: Option<&str>,
+    index: usize,
+    count: usize,
+    names: &SidNameRules,
+) -> String {
+    if count == 1 {
+        return name.to_string();
+    }
+
+    match element_name {
+        Some(element_name) => names.normalize(&format!("{name}_{element_name}")),
+        None => names.normalize(&format!("{name}_{index}")),
+    }
+}
+
+/// Writes the texture of `image`, copying its file or saving its embedded
+/// data next to the texture asset.
+#[allow(clippy::too_many_arguments)]
+fn write_image_texture<P: AsRef<Path>>(
+    image: ::gltf::Image,
+    texture_name: String,
+    buffers: &[::gltf::buffer::Data],
+    path: &Path,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<SidTextureAsset> {
+    let gltf_folder = path.parent().unwrap_or(Path::new("."));
+
+    let (data, extension) = match image.source() {
+        Source::View { view, mime_type } => {
+            let buffer = &buffers[view.buffer().index()].0;
+            let data = buffer
+                .get(view.offset()..view.offset() + view.length())
+                .ok_or_else(|| malformed(path, format!("truncated image {}", image.index())))?;
+
+            (data.to_vec(), image_extension(mime_type))
+        }
+        Source::Uri { uri, mime_type } => match uri.strip_prefix("data:") {
+            Some(data_uri) => {
+                let (media_type, data) = data_uri.split_once(";base64,").ok_or_else(|| {
+                    malformed(path, format!("invalid data URI of image {}", image.index()))
+                })?;
+
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(data)
+                    .map_err(|error| malformed(path, error))?;
+
+                (data, image_extension(mime_type.unwrap_or(media_type)))
+            }
+            None => {
+                let image_from_path = gltf_folder.join(uri);
+
+                if !image_from_path.is_file() {
+                    return Err(SidToolError::IO(format!(
+                        "Unable to find image {} of glTF file {:?} at {:?}",
+                        image.index(),
+                        path,
+                        image_from_path
+                    )));
+                }
+
+                let extension = image_from_path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(|extension| extension.to_lowercase())
+                    .unwrap_or_else(|| "png".to_string());
+
+                let image_path = PathBuf::from(format!("{texture_name}.{extension}"));
+                let texture = SidTextureAsset::with_data(texture_name, image_path, image_from_path);
+                write_asset(&texture, names, &assets_output_path, subfolder, dry_run)?;
+                return Ok(texture);
+            }
+        },
+    };
+
+    let image_path = PathBuf::from(format!("{texture_name}.{extension}"));
+
+    // Embedded images are saved where the texture asset expects its image, so
+    // that it is not copied again.
+    let image_from_path = assets_output_path
+        .as_ref()
+        .join(SidTextureAsset::namespace())
+        .join(subfolder)
+        .join(&image_path);
+
+    if !dry_run {
+        let folder = image_from_path.parent().unwrap_or(Path::new("."));
+
+        fs::create_dir_all(folder).map_err(|error| {
+            SidToolError::IO(format!(
+                "Unable to create folder at path {:?}: {error}",
+                folder
+            ))
+        })?;
+
+        fs::write(&image_from_path, data).map_err(|error| {
+            SidToolError::IO(format!(
+                "Unable to write image to file {:?}: {error}",
+                image_from_path
+            ))
+        })?;
+    }
+
+    let texture = SidTextureAsset::with_data(texture_name, image_path, image_from_path);
+    write_asset(&texture, names, &assets_output_path, subfolder, dry_run)?;
+    Ok(texture)
+}
+
+/// Converts the meshes of the glTF file at `path` into mesh assets, named after
+/// the file if it holds a single mesh, and after the file and the mesh
+/// otherwise, e.g. `crate_lid`. The base color images of their materials become
+/// texture assets, named the same way. They are written to `subfolder` of their
+/// namespace folders. Returns the ids of the assets.
+pub fn from_gltf_to_sid_assets<P: AsRef<Path>>(
+    path: &Path,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let gltf = Gltf::open(path).map_err(|error| malformed(path, error))?;
+    let buffers = ::gltf::import_buffers(&gltf.document, path.parent(), gltf.blob.clone())
+        .map_err(|error| malformed(path, error))?;
+
+    let name = path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| malformed(path, "invalid glTF name"))
+        .map(|name| names.normalize(name))?;
+
+    let document = &gltf.document;
+    let mesh_count = document.meshes().len();
+    let image_count = document.images().len();
+
+    if mesh_count == 0 {
+        return Err(malformed(path, "no meshes"));
+    }
+
+    let mut ids = vec![];
+    // Textures by image index, written once however many meshes use them.
+    let mut textures: HashMap<usize, String> = HashMap::new();
+
+    for mesh in document.meshes() {
+        let mut vertices = vec![];
+        let mut indices = vec![];
+        let mut submeshes = vec![];
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                sid_warning!(
+                    "Skipping {:?} primitive {} of mesh {} of glTF file {:?}: only triangles are supported.",
+                    primitive.mode(),
+                    primitive.index(),
+                    mesh.index(),
+                    path
+                );
+                continue;
+            }
+
+            let color_texture = primitive
+                .material()
+                .pbr_metallic_roughness()
+                .base_color_texture();
+
+            let tex_coord_set = color_texture.as_ref().map_or(0, |info| info.tex_coord());
+            let reader = primitive
+                .reader(|buffer| buffers.get(buffer.index()).map(|data| data.0.as_slice()));
+
+            let positions = reader.read_positions().ok_or_else(|| {
+                malformed(
+                    path,
+                    format!(
+                        "no positions in primitive {} of mesh {}",
+                        primitive.index(),
+                        mesh.index()
+                    ),
+                )
+            })?;
+
+            let first_vertex = vertices.len() as u32;
+            let first_index = indices.len() as u32;
+
+            vertices.extend(positions.map(|position| SidMeshVertexAsset {
+                position,
+                ..Default::default()
+            }));
+
+            let primitive_vertices = &mut vertices[first_vertex as usize..];
+
+            if let Some(normals) = reader.read_normals() {
+                for (vertex, normal) in primitive_vertices.iter_mut().zip(normals) {
+                    vertex.normal = normal;
+                }
+            }
+
+            if let Some(uvs) = reader.read_tex_coords(tex_coord_set) {
+                for (vertex, uv) in primitive_vertices.iter_mut().zip(uvs.into_f32()) {
+                    vertex.uv = uv;
+                }
+            }
+
+            // Primitives without indices draw their vertices in order.
+            match reader.read_indices() {
+                Some(primitive_indices) => indices.extend(
+                    primitive_indices
+                        .into_u32()
+                        .map(|index| first_vertex + index),
+                ),
+                None => indices.extend(first_vertex..vertices.len() as u32),
+            }
+
+            let texture = match color_texture {
+                Some(info) => {
+                    let image = info.texture().source();
+
+                    let texture_name = match textures.get(&image.index()) {
+                        Some(texture_name) => texture_name.clone(),
+                        None => {
+                            let texture_name = element_name(
+                                &name,
+                                image.name(),
+                                image.index(),
+                                image_count,
+                                names,
+                            );
+
+                            let texture = write_image_texture(
+                                image.clone(),
+                                texture_name,
+                                &buffers,
+                                path,
+                                names,
+                                &assets_output_path,
+                                subfolder,
+                                dry_run,
+                            )?;
+
+                            ids.push(texture.resource_id());
+                            textures.insert(image.index(), texture.name.clone());
+                            texture.name
+                        }
+                    };
+
+                    Some(SidMeshTexture::Texture(texture_name))
+                }
+                None => None,
+            };
+
+            submeshes.push(SidMeshSubmeshAsset {
+                first_index,
+                index_count: indices.len() as u32 - first_index,
+                texture,
+            });
+        }
+
+        if submeshes.is_empty() {
+            sid_warning!(
+                "Skipping mesh {} of glTF file {:?}, which has no triangles.",
+                mesh.index(),
+                path
+            );
+            continue;
+        }
+
+        let mesh_name = element_name(&name, mesh.name(), mesh.index(), mesh_count, names);
+        let mesh = SidMeshAsset::with_data(mesh_name, vertices, indices, submeshes);
+
+        write_asset(&mesh, names, &assets_output_path, subfolder, dry_run)?;
+        ids.push(mesh.resource_id());
+    }
+
+    Ok(ids)
+}
@@ -0,0 +1,450 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Importer for numbered image sequences, e.g. run_000.png to run_023.png as
+// rendered by 3D tools, each sequence becoming a sprite sheet, its animation
+// definition and a single animation.
+
+use image::{GenericImage, GenericImageView, ImageFormat, RgbaImage};
+
+use sid_asset_packer::asset::{
+    SidAnimationAsset, SidAnimationDefAsset, SidAnimationDirection, SidAnimationFrameAsset,
+    SidAnimationFrameDims, SidAnimationFramePos, SidSpriteSheetAsset,
+};
+use sid_asset_packer::atlas::{self, SidSkylinePacker, DEFAULT_ATLAS_MAX_SIZE};
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::resource::SerializeSidAsset;
+use sid_asset_packer::sid::{self, SidAnimationFrameDuration, SidStringId};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use crate::sid_aseprite::{anchor_pivot, write_asset};
+use crate::sidecar::AsepriteSidecar;
+use crate::{sid_debug, sid_info};
+
+/// Duration of the frames of a sequence, in milliseconds, unless set
+/// otherwise.
+pub const DEFAULT_SEQUENCE_FRAME_DURATION: SidAnimationFrameDuration = 100;
+
+/// Empty pixels left between two frames of a generated sheet.
+const SEQUENCE_FRAME_PADDING: u32 = 1;
+
+/// Numbered images of a folder sharing the same prefix.
+#[derive(Debug, Clone)]
+pub struct SidImageSequence {
+    /// Prefix of the images without its trailing separator, or the name of
+    /// their folder if they have none, e.g. `run` for `run_000.png`.
+    pub name: String,
+    /// Images of the sequence, sorted by number.
+    pub frame_paths: Vec<PathBuf>,
+}
+
+impl SidImageSequence {
+    /// Path the sequence is reported and its sidecar found at, e.g.
+    /// `run.sid.toml` next to `run_000.png`.
+    pub fn path(&self) -> PathBuf {
+        let folder = self
+            .frame_paths
+            .first()
+            .and_then(|path| path.parent())
+            .unwrap_or(Path::new(""));
+
+        folder.join(&self.name)
+    }
+}
+
+/// Whether the file at `path` may be a frame of a sequence: a PNG image whose
+/// stem ends with digits.
+pub fn is_sequence_frame(path: &Path) -> bool {
+    let is_png = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+
+    is_png && split_frame_stem(path).is_some()
+}
+
+/// Prefix and number of the stem of the frame at `path`, e.g. `run_` and 12
+/// for `run_012.png`.
+fn split_frame_stem(path: &Path) -> Option<(&str, u64)> {
+    let stem = path.file_stem()?.to_str()?;
+    let prefix = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+
+    if prefix.len() == stem.len() {
+        return None;
+    }
+
+    Some((prefix, stem[prefix.len()..].parse().ok()?))
+}
+
+/// Groups the frames at `paths` into sequences, by folder and prefix.
+pub fn group_sequences(paths: Vec<PathBuf>) -> Vec<SidImageSequence> {
+    let mut groups: BTreeMap<(PathBuf, String), Vec<(u64, PathBuf)>> = BTreeMap::new();
+
+    for path in paths {
+        let Some((prefix, number)) = split_frame_stem(&path) else {
+            continue;
+        };
+
+        let folder = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let prefix = prefix.to_string();
+        groups
+            .entry((folder, prefix))
+            .or_default()
+            .push((number, path));
+    }
+
+    groups
+        .into_iter()
+        .map(|((folder, prefix), mut frames)| {
+            // Numbers may not be padded, e.g. run_9.png before run_10.png.
+            frames.sort();
+
+            let name = match prefix.trim_end_matches(['_', '-', '.', ' ']) {
+                "" => folder
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                name => name.to_string(),
+            };
+
+            SidImageSequence {
+                name,
+                frame_paths: frames.into_iter().map(|(_, path)| path).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Image of a frame, shown for `duration` milliseconds.
+pub(crate) struct SidFrameImage {
+    /// Name of the frame in errors, e.g. the path of its image.
+    pub label: String,
+    pub image: RgbaImage,
+    pub duration: SidAnimationFrameDuration,
+}
+
+/// Frame of a sequence cropped to its region, with its region in the sheet
+/// once packed.
+struct SidSequenceFrame {
+    pixels: RgbaImage,
+    frame: SidAnimationFrameAsset,
+    /// Earlier frame showing the same pixels, sharing its region.
+    original: Option<usize>,
+}
+
+fn open_frame(path: &Path, duration: SidAnimationFrameDuration) -> SidToolResult<SidFrameImage> {
+    let image = image::open(path)
+        .map(|image| image.to_rgba8())
+        .map_err(|error| SidToolError::IO(format!("Unable to open image {:?}: {error}", path)))?;
+
+    Ok(SidFrameImage {
+        label: format!("{:?}", path),
+        image,
+        duration,
+    })
+}
+
+/// Loads the frames of `images`, cropped to their opaque pixels if `trim`,
+/// and merged with the earlier ones showing the same pixels if `dedup`.
+fn load_frames(
+    images: impl IntoIterator<Item = SidToolResult<SidFrameImage>>,
+    trim: bool,
+    dedup: bool,
+) -> SidToolResult<Vec<SidSequenceFrame>> {
+    let mut frames: Vec<SidSequenceFrame> = vec![];
+
+    for image in images {
+        let SidFrameImage {
+            label,
+            image,
+            duration,
+        } = image?;
+
+        let (width, height) = match (
+            sid::SidAnimationFrameDim::try_from(image.width()),
+            sid::SidAnimationFrameDim::try_from(image.height()),
+        ) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(SidToolError::Malformed(format!(
+                    "Image {label} is too large for a frame: {}x{}",
+                    image.width(),
+                    image.height()
+                )))
+            }
+        };
+
+        let mut frame = SidAnimationFrameAsset::with_data(
+            SidAnimationFramePos::new(),
+            SidAnimationFrameDims::with_width_and_height(width, height),
+            duration,
+        );
+
+        if trim {
+            atlas::trim_frame(&mut frame, &image).map_err(|error| {
+                SidToolError::Malformed(format!("Unable to trim {label}: {error}"))
+            })?;
+        }
+
+        let pixels = image
+            .view(
+                frame.pos.x as u32,
+                frame.pos.y as u32,
+                frame.dims.width as u32,
+                frame.dims.height as u32,
+            )
+            .to_image();
+
+        let original = dedup
+            .then(|| {
+                frames.iter().position(|other| {
+                    other.original.is_none()
+                        && other.frame.trim == frame.trim
+                        && other.pixels == pixels
+                })
+            })
+            .flatten();
+
+        frames.push(SidSequenceFrame {
+            pixels,
+            frame,
+            original,
+        });
+    }
+
+    Ok(frames)
+}
+
+/// Packs the regions of `frames` with the atlas packer, tallest first, and
+/// returns the image of the sheet.
+fn pack_frames(name: &str, frames: &mut [SidSequenceFrame]) -> SidToolResult<RgbaImage> {
+    let mut order: Vec<usize> = (0..frames.len())
+        .filter(|&i| frames[i].original.is_none())
+        .collect();
+
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&frames[a].pixels, &frames[b].pixels);
+        b.height().cmp(&a.height()).then(b.width().cmp(&a.width()))
+    });
+
+    let mut packer = SidSkylinePacker::new(DEFAULT_ATLAS_MAX_SIZE, DEFAULT_ATLAS_MAX_SIZE);
+
+    for &i in &order {
+        let (width, height) = frames[i].pixels.dimensions();
+
+        let (x, y) = packer
+            .insert(
+                width + SEQUENCE_FRAME_PADDING,
+                height + SEQUENCE_FRAME_PADDING,
+            )
+            .ok_or_else(|| {
+                SidToolError::Malformed(format!(
+                    "Frames of sequence {:?} do not fit a {DEFAULT_ATLAS_MAX_SIZE}x{DEFAULT_ATLAS_MAX_SIZE} sheet",
+                    name
+                ))
+            })?;
+
+        frames[i].frame.pos = SidAnimationFramePos::with_coords(
+            x as sid::SidAnimationFrameCoord,
+            y as sid::SidAnimationFrameCoord,
+        );
+    }
+
+    // The padding after the last row and column is left out.
+    let (width, height) = packer.used_size();
+    let width = width.saturating_sub(SEQUENCE_FRAME_PADDING).max(1);
+    let height = height.saturating_sub(SEQUENCE_FRAME_PADDING).max(1);
+    let mut image = RgbaImage::new(width, height);
+
+    for frame in frames.iter().filter(|frame| frame.original.is_none()) {
+        image
+            .copy_from(
+                &frame.pixels,
+                frame.frame.pos.x as u32,
+                frame.frame.pos.y as u32,
+            )
+            .map_err(|error| SidToolError::IO(error.to_string()))?;
+    }
+
+    for i in 0..frames.len() {
+        if let Some(original) = frames[i].original {
+            frames[i].frame.pos = frames[original].frame.pos.clone();
+        }
+    }
+
+    Ok(image)
+}
+
+/// Saves `image` at `image_path`, or only logs its size on dry runs.
+fn save_image(image: &RgbaImage, image_path: &Path, dry_run: bool) -> SidToolResult<()> {
+    if dry_run {
+        let mut png = Cursor::new(vec![]);
+
+        image
+            .write_to(&mut png, ImageFormat::Png)
+            .map_err(|error| {
+                SidToolError::IO(format!("Unable to encode image {:?}: {error}", image_path))
+            })?;
+
+        sid_info!(
+            "Would write image {:?}: {} byte(s)",
+            image_path,
+            png.get_ref().len()
+        );
+
+        return Ok(());
+    }
+
+    if let Some(folder) = image_path.parent() {
+        fs::create_dir_all(folder).map_err(|error| {
+            SidToolError::IO(format!(
+                "Unable to create folder at path {:?}: {error}",
+                folder
+            ))
+        })?;
+    }
+
+    image.save(image_path).map_err(|error| {
+        SidToolError::IO(format!("Unable to save image {:?}: {error}", image_path))
+    })
+}
+
+/// Packs the frames of `sequence` into a sprite sheet whose image is saved in
+/// `image_folder`, and writes it along with its animation definition and an
+/// animation playing every frame, all named after the sequence, to
+/// `subfolder` of their namespace folders. `sidecar` sets the duration,
+/// trimming, merging and pivot of the frames, and the looping and speed of the
+/// animation. Returns the ids of the assets.
+pub fn from_image_sequence_to_sid_assets<P1: AsRef<Path>, P2: AsRef<Path>>(
+    sequence: &SidImageSequence,
+    sidecar: &AsepriteSidecar,
+    names: &SidNameRules,
+    image_folder: P1,
+    assets_output_path: P2,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let duration = sidecar
+        .frame_duration
+        .unwrap_or(DEFAULT_SEQUENCE_FRAME_DURATION);
+
+    let images = sequence
+        .frame_paths
+        .iter()
+        .map(|path| open_frame(path, duration));
+
+    from_frame_images_to_sid_assets(
+        &sequence.name,
+        images,
+        sidecar,
+        names,
+        image_folder,
+        assets_output_path,
+        subfolder,
+        dry_run,
+    )
+}
+
+/// Packs `images` into a sprite sheet whose image is saved in `image_folder`,
+/// and writes it along with its animation definition and an animation playing
+/// every frame, all named `name`, to `subfolder` of their namespace folders.
+/// `sidecar` sets the trimming, merging and pivot of the frames, and the
+/// looping and speed of the animation. Returns the ids of the assets.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn from_frame_images_to_sid_assets<P1: AsRef<Path>, P2: AsRef<Path>>(
+    name: &str,
+    images: impl IntoIterator<Item = SidToolResult<SidFrameImage>>,
+    sidecar: &AsepriteSidecar,
+    names: &SidNameRules,
+    image_folder: P1,
+    assets_output_path: P2,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let name = names.normalize(name);
+    let trim = sidecar.trim == Some(true);
+    let dedup = sidecar.dedup_frames == Some(true);
+
+    let mut frames = load_frames(images, trim, dedup)?;
+
+    if frames.is_empty() {
+        return Err(SidToolError::Malformed(format!("No frames in {:?}", name)));
+    }
+
+    // Frame indices are 16-bit.
+    if frames.len() > u16::MAX as usize {
+        return Err(SidToolError::Malformed(format!(
+            "Too many frames in {:?}: {}",
+            name,
+            frames.len()
+        )));
+    }
+
+    let image = pack_frames(&name, &mut frames)?;
+
+    let merged_count = frames
+        .iter()
+        .filter(|frame| frame.original.is_some())
+        .count();
+
+    if merged_count > 0 {
+        sid_debug!("Merged {merged_count} duplicate frame(s) of {:?}", name);
+    }
+
+    let image_name = PathBuf::from(format!("{name}.png"));
+    let image_path = image_folder.as_ref().join(&image_name);
+    save_image(&image, &image_path, dry_run)?;
+
+    let mut sheet = SidSpriteSheetAsset::with_data(
+        name.clone(),
+        image_name,
+        image_path,
+        image.width(),
+        image.height(),
+        "RGBA8888".to_string(),
+    );
+
+    // Frames may differ in size, the first one gives the anchored pivot.
+    let source_dims = frames[0].frame.source_dims();
+
+    sheet.pivot = sidecar.pivot.clone().or_else(|| {
+        Some(anchor_pivot(
+            sidecar.pivot_anchor?,
+            (source_dims.width as i32, source_dims.height as i32),
+        ))
+    });
+    sheet.atlas_group = sidecar.atlas_group.clone();
+
+    let mut ids = vec![];
+    write_asset(&sheet, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(sheet.resource_id());
+
+    let frames: Vec<_> = frames.into_iter().map(|frame| frame.frame).collect();
+    let frame_count = frames.len() as sid::SidAnimationFrameIndex;
+    let def = SidAnimationDefAsset::with_data(frame_count, frames, name.clone(), name.clone());
+
+    write_asset(&def, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(def.resource_id());
+
+    let mut animation =
+        SidAnimationAsset::from_def(&def, name, 0, frame_count, SidAnimationDirection::Forward);
+
+    if let Some(looping) = sidecar.looping {
+        animation.looping = looping;
+    }
+
+    if let Some(speed_scale) = sidecar.speed_scale {
+        animation.speed_scale = speed_scale;
+    }
+
+    write_asset(&animation, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(animation.resource_id());
+
+    Ok(ids)
+}
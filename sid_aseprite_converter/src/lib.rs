@@ -2,5 +2,20 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
-pub(crate) mod log;
+#![allow(clippy::new_without_default)]
+
+pub mod aseprite_cli;
+pub mod aseprite_file;
+pub mod bmfont;
+pub mod character;
+pub mod gif;
+pub mod gltf;
+pub mod image_sequence;
+pub mod log;
+pub mod naming;
 pub mod sid_aseprite;
+pub mod sidecar;
+pub mod spine;
+pub mod tag_data;
+pub mod texture_packer;
+pub mod tiled;
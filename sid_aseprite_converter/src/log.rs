@@ -2,45 +2,37 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! sid_debug {
-    ($($arg:tt)*) => (println!("[DEBUG] {}", format_args!($($arg)*)));
-}
-
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! sid_info {
-    ($($arg:tt)*) => (println!("[INFO] {}", format_args!($($arg)*)));
-}
-
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! sid_warning {
-    ($($arg:tt)*) => (println!("[WARNING] {}", format_args!($($arg)*)));
-}
+// Messages and counters are shared with the packer, which logs on behalf of the
+// converter.
+pub use sid_asset_packer::log::{
+    error_count, init, log, parse_filter, parse_level, record_error, record_warning, warning_count,
+    Level, LevelFilter, SidLogConfig,
+};
 
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! sid_debug {
-    ($($arg:tt)*) => {{}};
+    ($($arg:tt)*) => ($crate::log::log($crate::log::Level::Debug, module_path!(), format_args!($($arg)*)));
 }
 
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! sid_info {
-    ($($arg:tt)*) => {{}};
+    ($($arg:tt)*) => ($crate::log::log($crate::log::Level::Info, module_path!(), format_args!($($arg)*)));
 }
 
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! sid_warning {
-    ($($arg:tt)*) => {{}};
+    ($($arg:tt)*) => {{
+        $crate::log::record_warning();
+        $crate::log::log($crate::log::Level::Warn, module_path!(), format_args!($($arg)*))
+    }};
 }
 
 #[macro_export]
 macro_rules! sid_error {
-    ($($arg:tt)*) => (eprintln!("[ERROR] {}", format_args!($($arg)*)));
+    ($($arg:tt)*) => {{
+        $crate::log::record_error();
+        $crate::log::log($crate::log::Level::Error, module_path!(), format_args!($($arg)*))
+    }};
 }
 
 #[macro_export]
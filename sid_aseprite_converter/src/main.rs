@@ -2,28 +2,380 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
-use sid_aseprite_converter::sid_aseprite;
-use sid_asset_packer::asset;
+use clap::{Parser, ValueEnum};
+
+use sid_aseprite_converter::{
+    aseprite_cli,
+    log::{self, LevelFilter, SidLogConfig},
+    sid_aseprite::{self, AsepriteConvertOptions},
+    sid_error,
+};
+use sid_asset_packer::{
+    asset,
+    config::{SidNameCase, SidPivotAnchor, SidToolsConfig},
+    error::{SidToolError, SidToolResult},
+    naming::SidNameRules,
+    report::SidReport,
+    walk, watch,
+};
 
-use std::env;
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Converts Aseprite sheets into Space it, Dummy! assets"
+)]
+struct Cli {
+    /// Folder containing the Aseprite JSON + image exports or .aseprite files [default: .].
+    sheets_input_path: Option<PathBuf>,
+
+    /// Assets folder the converted assets are written to [default: assets].
+    assets_output_path: Option<PathBuf>,
+
+    /// Keep running and convert sheets again whenever the input folder changes.
+    #[arg(short, long)]
+    watch: bool,
+
+    /// Convert the sheets of the subfolders as well, mirroring them in the assets folder.
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// Walk through symbolic links (and junctions) to files and folders, instead of skipping them.
+    #[arg(long)]
+    follow_symlinks: bool,
+
+    /// Only convert the sheets whose path, relative to the input folder, matches one of these glob patterns.
+    #[arg(long, value_name = "GLOB", num_args = 1.., value_delimiter = ',')]
+    include: Vec<String>,
+
+    /// Skip the sheets whose path, relative to the input folder, matches one of these glob patterns.
+    #[arg(long, value_name = "GLOB", num_args = 1.., value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// Only keep the layers matching one of these glob patterns.
+    #[arg(long, value_name = "GLOB", num_args = 1.., value_delimiter = ',')]
+    include_layers: Vec<String>,
+
+    /// Drop the layers matching one of these glob patterns.
+    #[arg(long, value_name = "GLOB", num_args = 1.., value_delimiter = ',')]
+    exclude_layers: Vec<String>,
+
+    /// Output one sprite sheet per layer of .aseprite files.
+    #[arg(long)]
+    split_layers: bool,
+
+    /// Name of the animations, where {sheet} and {tag} are replaced by the sheet and tag names [default: {sheet}_{tag}].
+    #[arg(long, value_name = "TEMPLATE")]
+    animation_name: Option<String>,
+
+    /// Case the sheet and tag names are converted to in animation names [default: keep].
+    #[arg(long, value_name = "CASE")]
+    animation_case: Option<NameCase>,
+
+    /// Remove the first of these prefixes found at the start of tag names in animation names.
+    #[arg(long, value_name = "PREFIX", num_args = 1.., value_delimiter = ',')]
+    strip_tag_prefixes: Vec<String>,
+
+    /// Make the frames showing the same pixels share a single region of their sheet.
+    #[arg(long)]
+    dedup_frames: bool,
+
+    /// Default pivot of the frames, unless a slice with a pivot gives them one.
+    #[arg(long, value_name = "ANCHOR")]
+    pivot_anchor: Option<PivotAnchor>,
+
+    /// Convert the numbered PNG images, e.g. run_000.png to run_023.png, into a sprite sheet and an animation per sequence.
+    #[arg(long)]
+    sequences: bool,
+
+    /// Duration of the frames of image sequences and TexturePacker sheets, in milliseconds, unless their sidecar sets one [default: 100].
+    #[arg(long, value_name = "MS")]
+    sequence_frame_duration: Option<u16>,
+
+    /// Export .aseprite files with the Aseprite command line instead of reading them directly.
+    #[arg(long)]
+    aseprite_cli: bool,
+
+    /// Aseprite executable used by --aseprite-cli [default: aseprite].
+    #[arg(long, value_name = "PATH")]
+    aseprite_path: Option<PathBuf>,
+
+    /// Convert every sheet and print the assets that would be written, without writing anything.
+    #[arg(long, conflicts_with = "watch")]
+    dry_run: bool,
+
+    /// Project configuration file, instead of the closest sid_tools.toml.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Most verbose level of the printed messages [default: error, or debug in debug builds].
+    #[arg(long, value_enum)]
+    log_level: Option<LogLevel>,
+
+    /// Level of the messages of a module and its submodules, e.g. sid_aseprite_converter::tiled=debug.
+    #[arg(long, value_name = "TARGET=LEVEL", value_delimiter = ',', value_parser = log::parse_filter)]
+    log_filter: Vec<(String, LevelFilter)>,
+
+    /// File the printed messages are appended to as well, with their module.
+    #[arg(long)]
+    log_file: Option<PathBuf>,
+
+    /// Write a report of the run, with the status of every sheet.
+    #[arg(long, value_enum)]
+    report: Option<ReportFormat>,
+
+    /// File the report is written to, instead of the standard output.
+    #[arg(long, requires = "report")]
+    report_output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogLevel {
+    Off,
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warning => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NameCase {
+    /// Names are left as is.
+    Keep,
+    Lower,
+    Upper,
+    /// Lowercase words separated by underscores, e.g. run_fast.
+    Snake,
+    /// Lowercase words separated by hyphens, e.g. run-fast.
+    Kebab,
+}
+
+impl From<NameCase> for SidNameCase {
+    fn from(case: NameCase) -> Self {
+        match case {
+            NameCase::Keep => SidNameCase::Keep,
+            NameCase::Lower => SidNameCase::Lower,
+            NameCase::Upper => SidNameCase::Upper,
+            NameCase::Snake => SidNameCase::Snake,
+            NameCase::Kebab => SidNameCase::Kebab,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PivotAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl From<PivotAnchor> for SidPivotAnchor {
+    fn from(anchor: PivotAnchor) -> Self {
+        match anchor {
+            PivotAnchor::TopLeft => SidPivotAnchor::TopLeft,
+            PivotAnchor::TopCenter => SidPivotAnchor::TopCenter,
+            PivotAnchor::TopRight => SidPivotAnchor::TopRight,
+            PivotAnchor::CenterLeft => SidPivotAnchor::CenterLeft,
+            PivotAnchor::Center => SidPivotAnchor::Center,
+            PivotAnchor::CenterRight => SidPivotAnchor::CenterRight,
+            PivotAnchor::BottomLeft => SidPivotAnchor::BottomLeft,
+            PivotAnchor::BottomCenter => SidPivotAnchor::BottomCenter,
+            PivotAnchor::BottomRight => SidPivotAnchor::BottomRight,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let mut log_config = SidLogConfig::new();
+
+    if let Some(level) = cli.log_level {
+        log_config.level = level.into();
+    }
+
+    log_config.filters = cli.log_filter.clone();
+    log_config.file = cli.log_file.clone();
+
+    if let Err(error) = log::init(log_config) {
+        sid_error!("{error}");
+        return ExitCode::FAILURE;
+    }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+    let start = Instant::now();
+    let mut report = SidReport::new();
+    let report_format = cli.report;
+    let report_output = cli.report_output.clone();
 
-    let sheets_input_path = if args.len() > 1 {
-        PathBuf::from(&args[1])
+    if let Err(error) = run(cli, &mut report) {
+        sid_error!("{error}");
+    }
+
+    if let Some(ReportFormat::Json) = report_format {
+        report.finish(start.elapsed());
+
+        if let Err(error) = report.write_json(report_output.as_deref()) {
+            sid_error!("{error}");
+        }
+    }
+
+    // Errors logged without stopping the run fail it as well.
+    if log::error_count() > 0 {
+        ExitCode::FAILURE
     } else {
-        PathBuf::from(".")
+        ExitCode::SUCCESS
+    }
+}
+
+fn run(cli: Cli, report: &mut SidReport) -> SidToolResult<()> {
+    let config = SidToolsConfig::load(cli.config.as_deref())?;
+    let converter_config = &config.converter;
+
+    // Command line arguments take precedence over the configuration file.
+    let resolve = |arg: Option<PathBuf>, config_path: &Option<PathBuf>, default: &str| {
+        arg.or_else(|| config_path.as_ref().map(|path| config.resolve(path)))
+            .unwrap_or_else(|| PathBuf::from(default))
     };
 
-    let assets_output_path = if args.len() > 2 {
-        PathBuf::from(&args[2])
-    } else {
-        let mut assets_output_path = PathBuf::from(".");
-        assets_output_path.push(asset::DEFAULT_ASSETS_PATH);
-        assets_output_path
+    let sheets_input_path = resolve(cli.sheets_input_path, &converter_config.input, ".");
+    let assets_output_path = resolve(
+        cli.assets_output_path,
+        &converter_config.output,
+        asset::DEFAULT_ASSETS_PATH,
+    );
+
+    walk::set_follow_symlinks(
+        cli.follow_symlinks || converter_config.follow_symlinks.unwrap_or(false),
+    );
+
+    let mut options = AsepriteConvertOptions::new();
+    options.recursive = cli.recursive || converter_config.recursive.unwrap_or(false);
+    options.split_layers = cli.split_layers || converter_config.split_layers.unwrap_or(false);
+    options.overrides = converter_config.overrides.clone();
+    options.dedup_frames = cli.dedup_frames || converter_config.dedup_frames.unwrap_or(false);
+    options.names = SidNameRules::from_config(&config.names);
+    options.sequences = cli.sequences || converter_config.sequences.unwrap_or(false);
+
+    if let Some(duration) = cli
+        .sequence_frame_duration
+        .or(converter_config.sequence_frame_duration)
+    {
+        options.sequence_frame_duration = duration;
+    }
+    options.pivot_anchor = cli
+        .pivot_anchor
+        .map(SidPivotAnchor::from)
+        .or(converter_config.pivot_anchor);
+
+    let naming = &mut options.animation_naming;
+
+    if let Some(animation_name) = cli
+        .animation_name
+        .or_else(|| converter_config.animation_name.clone())
+    {
+        naming.template = animation_name;
+    }
+
+    if let Some(case) = cli
+        .animation_case
+        .map(SidNameCase::from)
+        .or(converter_config.animation_case)
+    {
+        naming.case = case;
+    }
+
+    naming.strip_tag_prefixes = match &converter_config.strip_tag_prefixes {
+        Some(prefixes) if cli.strip_tag_prefixes.is_empty() => prefixes.clone(),
+        _ => cli.strip_tag_prefixes,
+    };
+
+    if cli.aseprite_cli || converter_config.aseprite_cli.unwrap_or(false) {
+        let aseprite_path = match (cli.aseprite_path, &converter_config.aseprite_path) {
+            (Some(path), _) => path,
+            (None, Some(path)) if path.components().count() > 1 => config.resolve(path),
+            (None, Some(path)) => path.clone(),
+            (None, None) => PathBuf::from(aseprite_cli::DEFAULT_ASEPRITE_PATH),
+        };
+
+        options.aseprite_cli = Some(aseprite_path);
+    }
+    options.dry_run = cli.dry_run;
+
+    options.include = match &converter_config.include {
+        Some(include) if cli.include.is_empty() => include.clone(),
+        _ => cli.include,
     };
 
-    sid_aseprite::from_aseprite_sheets_to_sid_assets(sheets_input_path, assets_output_path);
+    options.exclude = match &converter_config.exclude {
+        Some(exclude) if cli.exclude.is_empty() => exclude.clone(),
+        _ => cli.exclude,
+    };
+
+    options.include_layers = match &converter_config.include_layers {
+        Some(include_layers) if cli.include_layers.is_empty() => include_layers.clone(),
+        _ => cli.include_layers,
+    };
+
+    options.exclude_layers = match &converter_config.exclude_layers {
+        Some(exclude_layers) if cli.exclude_layers.is_empty() => exclude_layers.clone(),
+        _ => cli.exclude_layers,
+    };
+
+    let result = sid_aseprite::from_aseprite_sheets_to_sid_assets_with_report(
+        &sheets_input_path,
+        &assets_output_path,
+        &options,
+        report,
+    );
+
+    if !cli.watch {
+        return result;
+    }
+
+    if let Err(error) = result {
+        sid_error!("{error}");
+    }
+
+    watch::watch(
+        &sheets_input_path,
+        std::slice::from_ref(&assets_output_path),
+        || {
+            if let Err(error) = sid_aseprite::from_aseprite_sheets_to_sid_assets_with_options(
+                &sheets_input_path,
+                &assets_output_path,
+                &options,
+            ) {
+                sid_error!("{error}");
+            }
+        },
+    )
+    .map_err(|error| SidToolError::IO(format!("Unable to watch {:?}: {error}", sheets_input_path)))
 }
@@ -0,0 +1,90 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use sid_asset_packer::config::SidNameCase;
+use sid_asset_packer::naming::SidNameRules;
+
+pub static DEFAULT_ANIMATION_NAME: &str = "{sheet}_{tag}";
+
+/// How animation names are built from the names of their sheet and tag.
+#[derive(Debug, Clone)]
+pub struct AsepriteAnimationNaming {
+    /// Name of the animations, where `{sheet}` and `{tag}` are replaced by the
+    /// names of the sheet and of the tag. Slashes put the animations in
+    /// subfolders of their namespace folder.
+    pub template: String,
+    /// Case the sheet and tag names are converted to.
+    pub case: SidNameCase,
+    /// Prefixes removed from the tag names, e.g. "anim_". Only the first
+    /// matching one is removed.
+    pub strip_tag_prefixes: Vec<String>,
+    /// Rules the animation names are normalized to.
+    pub names: SidNameRules,
+}
+
+impl AsepriteAnimationNaming {
+    pub fn new() -> Self {
+        Self {
+            template: DEFAULT_ANIMATION_NAME.to_string(),
+            case: SidNameCase::Keep,
+            strip_tag_prefixes: vec![],
+            names: SidNameRules::new(),
+        }
+    }
+
+    /// Name of the animation of `tag_name` in the sheet named `sheet_name`,
+    /// normalized after `names`.
+    pub fn animation_name(&self, sheet_name: &str, tag_name: &str) -> String {
+        let tag_name = self
+            .strip_tag_prefixes
+            .iter()
+            .find_map(|prefix| tag_name.strip_prefix(prefix.as_str()))
+            .filter(|stripped| !stripped.is_empty())
+            .unwrap_or(tag_name);
+
+        let name = self
+            .template
+            .replace("{sheet}", &to_case(sheet_name, self.case))
+            .replace("{tag}", &to_case(tag_name, self.case));
+
+        self.names.normalize(&name)
+    }
+}
+
+/// Converts `name` to `case`. Words are separated by any character that is
+/// not alphanumeric, or start with an uppercase letter following a lowercase
+/// one.
+pub fn to_case(name: &str, case: SidNameCase) -> String {
+    let separator = match case {
+        SidNameCase::Keep => return name.to_string(),
+        SidNameCase::Lower => return name.to_lowercase(),
+        SidNameCase::Upper => return name.to_uppercase(),
+        SidNameCase::Snake => "_",
+        SidNameCase::Kebab => "-",
+    };
+
+    let mut words: Vec<String> = vec![];
+    let mut previous: Option<char> = None;
+
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            previous = None;
+            continue;
+        }
+
+        let starts_word = match previous {
+            Some(previous) => previous.is_lowercase() && c.is_uppercase(),
+            None => true,
+        };
+
+        match words.last_mut() {
+            Some(word) if !starts_word => word.extend(c.to_lowercase()),
+            _ => words.push(c.to_lowercase().collect()),
+        }
+
+        previous = Some(c);
+    }
+
+    words.join(separator)
+}
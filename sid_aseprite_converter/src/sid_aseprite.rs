@@ -2,23 +2,58 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
-use serde::Deserialize;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use image::{GenericImage, GenericImageView, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use sid_asset_packer::asset::{
-    SidAnimationAsset, SidAnimationDefAsset, SidAnimationFrameAsset, SidAnimationFrameDims,
-    SidAnimationFramePos, SidPackedAsset, SidSpriteSheetAsset,
+    SidAnimationAsset, SidAnimationDefAsset, SidAnimationDirection, SidAnimationEvent,
+    SidAnimationFrameAsset, SidAnimationFrameDims, SidAnimationFramePos, SidAnimationFrameTrim,
+    SidColorSpace, SidNineSliceAsset, SidNineSliceInsets, SidPackedAsset, SidSliceAsset,
+    SidSliceKeyAsset, SidSlicePivot, SidSliceRect, SidSpriteSheetAsset, SidTextureAsset,
 };
-use sid_asset_packer::sid;
+use sid_asset_packer::atlas;
+use sid_asset_packer::config::{SidConverterOverride, SidPivotAnchor};
+use sid_asset_packer::error::{SidAssetFailure, SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::portable_path;
+use sid_asset_packer::report::{SidAssetReport, SidAssetStatus, SidReport};
+use sid_asset_packer::resource::SerializeSidAsset;
+use sid_asset_packer::sid::{self, SidAnimationFrameDuration, SidStringId};
+use sid_asset_packer::walk::{self, SidEntryKind, SidIgnoreRules, SidVisitedFolders};
 
 use std::cmp::min;
+use std::io::Cursor;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use std::{fmt, fs};
 
-use crate::{sid_error, sid_warning};
+use crate::aseprite_cli::{self, AsepriteExport};
+use crate::aseprite_file::AsepriteFile;
+use crate::bmfont;
+use crate::character;
+use crate::gif;
+use crate::gltf;
+use crate::image_sequence::{self, DEFAULT_SEQUENCE_FRAME_DURATION};
+use crate::naming::AsepriteAnimationNaming;
+use crate::sidecar::AsepriteSidecar;
+use crate::spine;
+use crate::tag_data::AsepriteTagData;
+use crate::texture_packer::{self, TexturePackerSheet};
+use crate::tiled;
+use crate::{sid_debug, sid_error, sid_info, sid_warning};
+
+/// Suffixes of the stems of the normal and emissive maps of sheet images,
+/// e.g. `hero_n.png` and `hero_e.png` for `hero.png`.
+pub static NORMAL_MAP_SUFFIX: &str = "_n";
+pub static EMISSIVE_MAP_SUFFIX: &str = "_e";
+
+// Tag given to the frames of a native Aseprite file that are not tagged.
+static DEFAULT_TAG_NAME: &str = "default";
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AsepriteSize {
     w: i16,
@@ -36,7 +71,7 @@ impl AsepriteSize {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AsepriteRect {
     x: i16,
@@ -56,7 +91,7 @@ impl AsepriteRect {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AsepriteFrameData {
     frame: AsepriteRect,
@@ -88,12 +123,36 @@ impl AsepriteFrameData {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct AsepriteFrameSlice {}
+pub struct AsepritePoint {
+    x: i16,
+    y: i16,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AsepriteSliceKey {
+    frame: u32,
+    bounds: AsepriteRect,
+    center: Option<AsepriteRect>,
+    pivot: Option<AsepritePoint>,
+}
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AsepriteFrameSlice {
+    name: String,
+    color: String,
+    #[serde(default)]
+    data: String,
+    keys: Vec<AsepriteSliceKey>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AsepriteFrameLayer {
     name: String,
@@ -102,22 +161,47 @@ pub struct AsepriteFrameLayer {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AsepriteFrameTag {
     name: String,
-    from: u8,
-    to: u8,
+    from: u16,
+    to: u16,
     direction: String,
     color: String,
+    /// Text of the user data of the tag, if any.
+    #[serde(default)]
+    data: String,
+}
+
+impl AsepriteFrameTag {
+    pub fn sid_direction(&self) -> SidAnimationDirection {
+        match self.direction.as_str() {
+            "forward" => SidAnimationDirection::Forward,
+            "reverse" => SidAnimationDirection::Reverse,
+            "pingpong" => SidAnimationDirection::PingPong,
+            "pingpong_reverse" => SidAnimationDirection::PingPongReverse,
+            direction => {
+                sid_warning!(
+                    "Unknown direction {:?} for tag {:?}, playing it forward.",
+                    direction,
+                    self.name
+                );
+
+                SidAnimationDirection::Forward
+            }
+        }
+    }
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AsepriteMeta {
     app: String,
     version: String,
+    /// Exports made on Windows may separate folders with backslashes.
+    #[serde(deserialize_with = "portable_path::deserialize")]
     image: PathBuf,
     format: String,
     size: AsepriteSize,
@@ -153,17 +237,105 @@ impl AsepriteMeta {
     }
 }
 
+impl AsepriteMeta {
+    fn from_aseprite_file(file: &AsepriteFile) -> Self {
+        let mut meta = Self::new();
+        meta.app = "sid_aseprite_converter".to_string();
+        meta.version = env!("CARGO_PKG_VERSION").to_string();
+        meta.scale = "1".to_string();
+
+        meta.frame_tags = file
+            .tags
+            .iter()
+            .map(|tag| AsepriteFrameTag {
+                name: tag.name.clone(),
+                from: tag.from,
+                to: tag.to,
+                direction: match tag.direction {
+                    1 => "reverse",
+                    2 => "pingpong",
+                    3 => "pingpong_reverse",
+                    _ => "forward",
+                }
+                .to_string(),
+                color: String::new(),
+                data: tag.user_data.clone(),
+            })
+            .collect();
+
+        meta.layers = file
+            .layers
+            .iter()
+            .map(|layer| AsepriteFrameLayer {
+                name: layer.name.clone(),
+                opacity: layer.opacity,
+                blend_mode: "normal".to_string(),
+            })
+            .collect();
+
+        meta.slices = file
+            .slices
+            .iter()
+            .map(|slice| AsepriteFrameSlice {
+                name: slice.name.clone(),
+                color: String::new(),
+                data: String::new(),
+                keys: slice
+                    .keys
+                    .iter()
+                    .map(|key| AsepriteSliceKey {
+                        frame: key.frame,
+                        bounds: AsepriteRect::with_coords_and_size(
+                            key.x as i16,
+                            key.y as i16,
+                            key.width as i16,
+                            key.height as i16,
+                        ),
+                        center: key.center.map(|(x, y, w, h)| {
+                            AsepriteRect::with_coords_and_size(
+                                x as i16, y as i16, w as i16, h as i16,
+                            )
+                        }),
+                        pivot: key.pivot.map(|(x, y)| AsepritePoint {
+                            x: x as i16,
+                            y: y as i16,
+                        }),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        meta
+    }
+}
+
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AsepriteFrameTuple {
     name: String,
     data: AsepriteFrameData,
+    /// Pivot of the frame set by the tool that packed it, e.g. TexturePacker,
+    /// used when no slice gives one.
+    pivot: Option<SidSlicePivot>,
 }
 
 impl AsepriteFrameTuple {
     pub fn new(name: String) -> Self {
         let data = AsepriteFrameData::new();
-        Self { name, data }
+        let pivot = None;
+        Self { name, data, pivot }
+    }
+
+    fn from_json(name: String, value: Value) -> AsepriteSheetResult<Self> {
+        let data = serde_json::from_value(value).map_err(|_| {
+            AsepriteSheetError::Malformed("Failed to deserialize frame data".to_string())
+        })?;
+
+        Ok(Self {
+            name,
+            data,
+            pivot: None,
+        })
     }
 }
 
@@ -184,6 +356,193 @@ impl fmt::Display for AsepriteSheetError {
 
 pub type AsepriteSheetResult<T> = Result<T, AsepriteSheetError>;
 
+impl From<AsepriteSheetError> for SidToolError {
+    fn from(error: AsepriteSheetError) -> Self {
+        match error {
+            AsepriteSheetError::Malformed(error) => SidToolError::Malformed(error),
+            AsepriteSheetError::IO(error) => SidToolError::IO(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteConvertOptions {
+    /// Convert the sheets of the subfolders of the input folder as well, each
+    /// written to the same subfolder of its namespace folders.
+    pub recursive: bool,
+    /// Glob patterns of the sheets to convert, relative to the input folder;
+    /// every sheet is converted if empty.
+    pub include: Vec<String>,
+    /// Glob patterns of the sheets to skip, applied after `include`.
+    pub exclude: Vec<String>,
+    /// Glob patterns of the layers to keep; every layer is kept if empty.
+    pub include_layers: Vec<String>,
+    /// Glob patterns of the layers to drop, applied after `include_layers`.
+    pub exclude_layers: Vec<String>,
+    /// Output one sprite sheet per layer of native Aseprite files.
+    pub split_layers: bool,
+    /// Make the frames of a sheet showing the same pixels share the region
+    /// of the first one, so that they are packed once. Sidecars may override
+    /// it.
+    pub dedup_frames: bool,
+    /// Default pivot of the frames. Sidecars may override it.
+    pub pivot_anchor: Option<SidPivotAnchor>,
+    /// How animations are named after their sheet and tag.
+    pub animation_naming: AsepriteAnimationNaming,
+    /// Rules every asset name is normalized to, and must then follow.
+    pub names: SidNameRules,
+    /// Aseprite executable exporting native Aseprite files, instead of
+    /// reading them directly.
+    pub aseprite_cli: Option<PathBuf>,
+    /// Convert the numbered PNG images of the input folder, e.g.
+    /// `run_000.png` to `run_023.png`, into a sprite sheet, an animation
+    /// definition and an animation per sequence.
+    pub sequences: bool,
+    /// Duration of the frames of image sequences and TexturePacker sheets, in
+    /// milliseconds. Sidecars may override it.
+    pub sequence_frame_duration: SidAnimationFrameDuration,
+    /// Options of the sheets under a given folder or file, relative to the
+    /// sheets folder. The last matching override wins.
+    pub overrides: Vec<SidConverterOverride>,
+    /// Convert every sheet and log the assets that would be written, without
+    /// touching the assets folder.
+    pub dry_run: bool,
+}
+
+impl AsepriteConvertOptions {
+    pub fn new() -> Self {
+        Self {
+            recursive: false,
+            include: vec![],
+            exclude: vec![],
+            include_layers: vec![],
+            exclude_layers: vec![],
+            split_layers: false,
+            dedup_frames: false,
+            pivot_anchor: None,
+            animation_naming: AsepriteAnimationNaming::new(),
+            names: SidNameRules::new(),
+            aseprite_cli: None,
+            sequences: false,
+            sequence_frame_duration: DEFAULT_SEQUENCE_FRAME_DURATION,
+            overrides: vec![],
+            dry_run: false,
+        }
+    }
+
+    /// Returns the options of the sheet at `relative_path`, if any override
+    /// matches it.
+    pub fn for_sheet(&self, relative_path: &Path) -> Option<Self> {
+        let mut overridden: Option<Self> = None;
+
+        for sheet_override in &self.overrides {
+            if !relative_path.starts_with(&sheet_override.path) {
+                continue;
+            }
+
+            let options = overridden.get_or_insert_with(|| self.clone());
+
+            if let Some(include_layers) = &sheet_override.include_layers {
+                options.include_layers = include_layers.clone();
+            }
+
+            if let Some(exclude_layers) = &sheet_override.exclude_layers {
+                options.exclude_layers = exclude_layers.clone();
+            }
+
+            if let Some(split_layers) = sheet_override.split_layers {
+                options.split_layers = split_layers;
+            }
+        }
+
+        overridden
+    }
+}
+
+#[derive(Debug)]
+pub struct AsepriteLayerFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl AsepriteLayerFilter {
+    pub fn new() -> Self {
+        Self {
+            include: None,
+            exclude: GlobSet::empty(),
+        }
+    }
+
+    pub fn from_options(options: &AsepriteConvertOptions) -> AsepriteSheetResult<Self> {
+        let include = if options.include_layers.is_empty() {
+            None
+        } else {
+            Some(build_glob_set("layer", &options.include_layers)?)
+        };
+
+        let exclude = build_glob_set("layer", &options.exclude_layers)?;
+        Ok(Self { include, exclude })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, layer_name: &str) -> bool {
+        let is_included = match &self.include {
+            Some(include) => include.is_match(layer_name),
+            None => true,
+        };
+
+        is_included && !self.exclude.is_match(layer_name)
+    }
+}
+
+/// Sheets of the input folder to convert, by path relative to it.
+#[derive(Debug)]
+pub struct AsepriteSheetFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl AsepriteSheetFilter {
+    pub fn from_options(options: &AsepriteConvertOptions) -> AsepriteSheetResult<Self> {
+        let include = if options.include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set("sheet", &options.include)?)
+        };
+
+        let exclude = build_glob_set("sheet", &options.exclude)?;
+        Ok(Self { include, exclude })
+    }
+
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        let is_included = match &self.include {
+            Some(include) => include.is_match(relative_path),
+            None => true,
+        };
+
+        is_included && !self.exclude.is_match(relative_path)
+    }
+}
+
+fn build_glob_set(kind: &str, patterns: &[String]) -> AsepriteSheetResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|error| {
+            AsepriteSheetError::Malformed(format!("Invalid {kind} pattern {:?}: {error}", pattern))
+        })?;
+
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|error| AsepriteSheetError::Malformed(error.to_string()))
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct AsepriteSheet {
@@ -198,22 +557,573 @@ impl AsepriteSheet {
         Self { frames, meta }
     }
 
+    pub fn frames(&self) -> &[AsepriteFrameTuple] {
+        &self.frames
+    }
+
+    /// Playback direction of the tag named `tag_name`, forward if the sheet
+    /// has no such tag.
+    pub fn tag_direction(&self, tag_name: &str) -> SidAnimationDirection {
+        self.meta
+            .frame_tags
+            .iter()
+            .find(|tag| tag.name == tag_name)
+            .map(|tag| tag.sid_direction())
+            .unwrap_or_default()
+    }
+
+    /// Size of the frames before trimming, i.e. of the sprite.
+    fn canvas_size(&self) -> Option<(i32, i32)> {
+        let size = &self.frames.first()?.data.source_size;
+        Some((size.w as i32, size.h as i32))
+    }
+
+    /// Pivot of every frame given by a slice with a pivot, or else by the frame
+    /// itself, relative to the top-left corner of the sprite. The slice named
+    /// "pivot" is used if several slices have a pivot.
+    fn frame_pivots(&self) -> Vec<Option<SidSlicePivot>> {
+        let mut slices = self
+            .meta
+            .slices
+            .iter()
+            .filter(|slice| slice.keys.iter().any(|key| key.pivot.is_some()));
+
+        let slice = match self.meta.slices.iter().find(|slice| slice.name == "pivot") {
+            Some(slice) => Some(slice),
+            None => {
+                let slice = slices.next();
+
+                if let (Some(slice), Some(_)) = (slice, slices.next()) {
+                    sid_warning!(
+                        "Several slices of sheet {:?} have a pivot, using {:?}.",
+                        self.meta.image,
+                        slice.name
+                    );
+                }
+
+                slice
+            }
+        };
+
+        (0..self.frames.len())
+            .map(|i| {
+                // Keys apply from their frame until the next key.
+                let key = slice.and_then(|slice| {
+                    slice
+                        .keys
+                        .iter()
+                        .take_while(|key| key.frame as usize <= i)
+                        .last()
+                });
+
+                let Some((key, pivot)) = key.and_then(|key| Some((key, key.pivot.as_ref()?)))
+                else {
+                    return self.frames[i].pivot.clone();
+                };
+
+                Some(SidSlicePivot::with_coords(
+                    key.bounds.x as i32 + pivot.x as i32,
+                    key.bounds.y as i32 + pivot.y as i32,
+                ))
+            })
+            .collect()
+    }
+
+    /// Properties written in the user data of the tag `tag_name`.
+    pub fn tag_data(&self, tag_name: &str) -> AsepriteTagData {
+        self.meta
+            .frame_tags
+            .iter()
+            .find(|tag| tag.name == tag_name)
+            .map(|tag| AsepriteTagData::parse(tag_name, &tag.data))
+            .unwrap_or_default()
+    }
+
+    /// Sets the loop flag, the speed and the events of `animation`, made from
+    /// the tag `tag_name`. The settings of the `sidecar` tag win over the ones
+    /// of the tag user data, which win over the ones of the whole `sidecar`.
+    fn apply_tag_data(
+        &self,
+        tag_name: &str,
+        sidecar: &AsepriteSidecar,
+        animation: &mut SidAnimationAsset,
+    ) {
+        let data = self.tag_data(tag_name);
+        let sidecar_tag = sidecar.tags.get(tag_name);
+
+        if let Some(looping) = sidecar_tag
+            .and_then(|tag| tag.looping)
+            .or(data.looping)
+            .or(sidecar.looping)
+        {
+            animation.looping = looping;
+        }
+
+        if let Some(speed_scale) = sidecar_tag
+            .and_then(|tag| tag.speed_scale)
+            .or(data.speed_scale)
+            .or(sidecar.speed_scale)
+        {
+            animation.speed_scale = speed_scale;
+        }
+
+        animation.events = self.tag_events(tag_name, data.events, animation.length, sidecar);
+    }
+
+    /// `events` of the tag `tag_name` followed by the ones of `sidecar`,
+    /// sorted by frame. Events past the `length` frames of the animation are
+    /// dropped with a warning.
+    fn tag_events(
+        &self,
+        tag_name: &str,
+        mut events: Vec<SidAnimationEvent>,
+        length: u16,
+        sidecar: &AsepriteSidecar,
+    ) -> Vec<SidAnimationEvent> {
+        events.extend(
+            sidecar
+                .events
+                .iter()
+                .filter(|event| event.tag == tag_name)
+                .map(|event| SidAnimationEvent::with_data(event.frame, event.name.clone())),
+        );
+
+        events.retain(|event| {
+            let is_valid = event.frame < length;
+
+            if !is_valid {
+                sid_warning!(
+                    "Ignoring event {:?} of tag {:?}: frame {} is past its {} frame(s).",
+                    event.name,
+                    tag_name,
+                    event.frame,
+                    length
+                );
+            }
+
+            is_valid
+        });
+
+        // Stable, so that events of the same frame keep their authored order.
+        events.sort_by_key(|event| event.frame);
+        events
+    }
+
+    /// Reads a native Aseprite file, rendering its frames to sheet images
+    /// saved in `image_folder`. Frames are named after their tag, the way the
+    /// JSON exports of this project are.
+    ///
+    /// The visible layers accepted by `filter` are merged into a single sheet,
+    /// unless `split_layers` is set: every layer accepted by `filter` (visible
+    /// or not) then gets its own sheet.
+    ///
+    /// On dry runs, sheet images are only logged, not saved.
+    pub fn from_aseprite_file<P1: AsRef<Path>, P2: AsRef<Path>>(
+        path: P1,
+        image_folder: P2,
+        filter: &AsepriteLayerFilter,
+        split_layers: bool,
+        dry_run: bool,
+    ) -> AsepriteSheetResult<Vec<Self>> {
+        let path = path.as_ref();
+        let file = AsepriteFile::from_path(path)?;
+
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| {
+                AsepriteSheetError::Malformed(format!("Invalid name for Aseprite file {:?}", path))
+            })?;
+
+        let meta = AsepriteMeta::from_aseprite_file(&file);
+
+        let layer_masks: Vec<Vec<bool>> = if split_layers {
+            (0..file.layers.len())
+                .filter(|&i| !file.layers[i].is_group && filter.matches(&file.layers[i].name))
+                .map(|i| (0..file.layers.len()).map(|j| i == j).collect())
+                .collect()
+        } else {
+            vec![file
+                .layers
+                .iter()
+                .map(|layer| layer.visible && filter.matches(&layer.name))
+                .collect()]
+        };
+
+        let mut sheets = Vec::with_capacity(layer_masks.len());
+
+        for (i, layer_mask) in layer_masks.iter().enumerate() {
+            let name = match layer_mask.iter().position(|&is_set| is_set) {
+                Some(layer_index) if split_layers && i > 0 => {
+                    format!("{stem}_{}", file.layers[layer_index].name)
+                }
+                _ => stem.to_string(),
+            };
+
+            let w = file.width as i16;
+            let h = file.height as i16;
+            let mut frames = Vec::with_capacity(file.frames.len());
+
+            for (frame_index, frame) in file.frames.iter().enumerate() {
+                let tag_name = file
+                    .tags
+                    .iter()
+                    .find(|tag| (tag.from as usize..=tag.to as usize).contains(&frame_index))
+                    .map(|tag| tag.name.as_str())
+                    .unwrap_or(DEFAULT_TAG_NAME);
+
+                let mut tuple =
+                    AsepriteFrameTuple::new(format!("{stem} ({tag_name}) {frame_index}.aseprite"));
+                tuple.data.frame = AsepriteRect::with_coords_and_size(0, 0, w, h);
+                tuple.data.sprite_source_size = AsepriteRect::with_coords_and_size(0, 0, w, h);
+                tuple.data.source_size = AsepriteSize::with_size(w, h);
+                tuple.data.duration = frame.duration as i32;
+
+                frames.push((tuple, file.render_frame(frame_index, layer_mask)));
+            }
+
+            sheets.push(Self::from_layer_frames(
+                &meta,
+                &name,
+                frames,
+                &image_folder,
+                dry_run,
+            )?);
+        }
+
+        Ok(sheets)
+    }
+
+    /// Reads a native Aseprite file exported by the Aseprite executable at
+    /// `aseprite_path`, the way `from_aseprite_file` would. The returned export
+    /// holds the sheet image, and is removed once dropped.
+    pub fn from_aseprite_cli<P1: AsRef<Path>, P2: AsRef<Path>>(
+        aseprite_path: P1,
+        path: P2,
+        filter: &AsepriteLayerFilter,
+        split_layers: bool,
+    ) -> AsepriteSheetResult<(AsepriteExport, Self)> {
+        let export = aseprite_cli::export(aseprite_path, path, filter, split_layers)?;
+        let mut sheet = Self::from_json(export.json_path())?;
+        let default_tag = format!("({DEFAULT_TAG_NAME})");
+
+        for tuple in &mut sheet.frames {
+            tuple.name = tuple.name.replacen("()", &default_tag, 1);
+        }
+
+        Ok((export, sheet))
+    }
+
+    /// Sheet of the frames packed by TexturePacker in `sheet`, named after
+    /// their animation the way the JSON exports of this project are. Frames
+    /// are grouped into a tag per animation, sorted by number, and last
+    /// `duration` milliseconds since TexturePacker does not time them.
+    pub fn from_texture_packer(
+        sheet: &TexturePackerSheet,
+        duration: SidAnimationFrameDuration,
+    ) -> Self {
+        let mut frames: Vec<_> = sheet
+            .frames
+            .iter()
+            .map(|frame| {
+                let (tag_name, number) =
+                    texture_packer::split_frame_name(&frame.name, DEFAULT_TAG_NAME);
+                (tag_name, number, frame)
+            })
+            .collect();
+
+        // Numbers may not be padded, e.g. run_9.png before run_10.png.
+        frames.sort_by(|(a_tag, a_number, a), (b_tag, b_number, b)| {
+            (a_tag, a_number, &a.name).cmp(&(b_tag, b_number, &b.name))
+        });
+
+        let mut descr = Self::new();
+        descr.meta.app = "TexturePacker".to_string();
+        descr.meta.image = sheet.image.clone();
+        descr.meta.format = sheet.format.clone();
+        descr.meta.size = AsepriteSize::with_size(sheet.width, sheet.height);
+        descr.meta.scale = "1".to_string();
+
+        let stem = sheet
+            .image
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        for (index, (tag_name, _, frame)) in frames.iter().enumerate() {
+            match descr.meta.frame_tags.last_mut() {
+                Some(tag) if tag.name == *tag_name => tag.to = index as u16,
+                _ => descr.meta.frame_tags.push(AsepriteFrameTag {
+                    name: tag_name.clone(),
+                    from: index as u16,
+                    to: index as u16,
+                    direction: "forward".to_string(),
+                    color: String::new(),
+                    data: String::new(),
+                }),
+            }
+
+            let mut tuple = AsepriteFrameTuple::new(format!("{stem} ({tag_name}) {index}.png"));
+            tuple.data.frame =
+                AsepriteRect::with_coords_and_size(frame.x, frame.y, frame.width, frame.height);
+            tuple.data.rotated = frame.rotated;
+            tuple.data.trimmed = frame.is_trimmed();
+            tuple.data.sprite_source_size = AsepriteRect::with_coords_and_size(
+                frame.source_x,
+                frame.source_y,
+                frame.width,
+                frame.height,
+            );
+            tuple.data.source_size =
+                AsepriteSize::with_size(frame.source_width, frame.source_height);
+            tuple.data.duration = duration as i32;
+            tuple.pivot = frame.pivot.map(|(x, y)| {
+                SidSlicePivot::with_coords(
+                    (x * frame.source_width as f32).round() as i32,
+                    (y * frame.source_height as f32).round() as i32,
+                )
+            });
+
+            descr.frames.push(tuple);
+        }
+
+        descr
+    }
+
+    /// Splits a sheet exported with split layers (frame names holding the
+    /// layer between brackets, e.g. "hero (run) [normal] 0") into one sheet
+    /// per layer accepted by `filter`. The first one keeps the name of the
+    /// sheet, the others are suffixed by their layer name.
+    pub fn split_layers<P1: AsRef<Path>, P2: AsRef<Path>>(
+        &self,
+        containing_folder: P1,
+        image_folder: P2,
+        filter: &AsepriteLayerFilter,
+        dry_run: bool,
+    ) -> AsepriteSheetResult<Vec<Self>> {
+        let mut layer_names: Vec<&str> = vec![];
+
+        for tuple in &self.frames {
+            if let Some(layer_name) = from_aseprite_frame_name_to_layer_name(&tuple.name) {
+                if !layer_names.contains(&layer_name) {
+                    layer_names.push(layer_name);
+                }
+            }
+        }
+
+        // Keep the layer order of Aseprite (bottom to top) when available.
+        layer_names.sort_by_key(|layer_name| {
+            self.meta
+                .layers
+                .iter()
+                .position(|layer| layer.name == *layer_name)
+                .unwrap_or(usize::MAX)
+        });
+
+        layer_names.retain(|layer_name| filter.matches(layer_name));
+
+        let image_path = if self.meta.image.is_absolute() {
+            self.meta.image.clone()
+        } else {
+            containing_folder.as_ref().join(&self.meta.image)
+        };
+
+        let image = image::open(&image_path)
+            .map_err(|error| {
+                AsepriteSheetError::IO(format!("Unable to open image {:?}: {error}", image_path))
+            })?
+            .to_rgba8();
+
+        let stem = image_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| {
+                AsepriteSheetError::Malformed(format!("Invalid name for image {:?}", image_path))
+            })?;
+
+        let mut sheets = Vec::with_capacity(layer_names.len());
+
+        for (i, layer_name) in layer_names.iter().enumerate() {
+            let name = if i == 0 {
+                stem.to_string()
+            } else {
+                format!("{stem}_{layer_name}")
+            };
+
+            let mut frames = vec![];
+
+            for tuple in &self.frames {
+                if from_aseprite_frame_name_to_layer_name(&tuple.name) != Some(layer_name) {
+                    continue;
+                }
+
+                let rect = &tuple.data.frame;
+
+                let (w, h) = if tuple.data.rotated {
+                    (rect.h, rect.w)
+                } else {
+                    (rect.w, rect.h)
+                };
+
+                if rect.x < 0
+                    || rect.y < 0
+                    || w < 0
+                    || h < 0
+                    || (rect.x + w) as u32 > image.width()
+                    || (rect.y + h) as u32 > image.height()
+                {
+                    return Err(AsepriteSheetError::Malformed(format!(
+                        "Frame {:?} is out of the bounds of image {:?}",
+                        tuple.name, image_path
+                    )));
+                }
+
+                let pixels = image
+                    .view(rect.x as u32, rect.y as u32, w as u32, h as u32)
+                    .to_image();
+
+                frames.push((tuple.clone(), pixels));
+            }
+
+            sheets.push(Self::from_layer_frames(
+                &self.meta,
+                &name,
+                frames,
+                &image_folder,
+                dry_run,
+            )?);
+        }
+
+        Ok(sheets)
+    }
+
+    /// Lays `frames` out on a grid, saving the resulting image as
+    /// `<name>.png` in `image_folder`, unless on a dry run.
+    fn from_layer_frames<P: AsRef<Path>>(
+        meta: &AsepriteMeta,
+        name: &str,
+        frames: Vec<(AsepriteFrameTuple, RgbaImage)>,
+        image_folder: P,
+        dry_run: bool,
+    ) -> AsepriteSheetResult<Self> {
+        let frame_count = frames.len();
+        let column_count = (frame_count as f64).sqrt().ceil().max(1.0) as usize;
+        let row_count = frame_count.div_ceil(column_count).max(1);
+
+        let cell_width = frames
+            .iter()
+            .map(|(_, pixels)| pixels.width() as usize)
+            .max()
+            .unwrap_or(0);
+
+        let cell_height = frames
+            .iter()
+            .map(|(_, pixels)| pixels.height() as usize)
+            .max()
+            .unwrap_or(0);
+
+        let (width, height) = match (
+            i16::try_from(column_count * cell_width),
+            i16::try_from(row_count * cell_height),
+        ) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => (width, height),
+            _ => {
+                return Err(AsepriteSheetError::Malformed(format!(
+                    "Invalid size for Aseprite sheet {:?} ({frame_count} frame(s) of {cell_width}x{cell_height})",
+                    name
+                )))
+            }
+        };
+
+        let mut image = RgbaImage::new(width as u32, height as u32);
+        let mut descr = Self::new();
+
+        for (i, (mut tuple, pixels)) in frames.into_iter().enumerate() {
+            let x = (i % column_count * cell_width) as i16;
+            let y = (i / column_count * cell_height) as i16;
+
+            image
+                .copy_from(&pixels, x as u32, y as u32)
+                .map_err(|error| AsepriteSheetError::IO(error.to_string()))?;
+
+            tuple.data.frame.x = x;
+            tuple.data.frame.y = y;
+            descr.frames.push(tuple);
+        }
+
+        let image_name = PathBuf::from(format!("{name}.png"));
+        let image_path = image_folder.as_ref().join(&image_name);
+
+        if dry_run {
+            let mut png = Cursor::new(vec![]);
+
+            image
+                .write_to(&mut png, ImageFormat::Png)
+                .map_err(|error| {
+                    AsepriteSheetError::IO(format!(
+                        "Unable to encode image {:?}: {error}",
+                        image_path
+                    ))
+                })?;
+
+            sid_info!(
+                "Would write image {:?}: {} byte(s)",
+                image_path,
+                png.get_ref().len()
+            );
+        } else {
+            fs::create_dir_all(&image_folder).map_err(|error| {
+                AsepriteSheetError::IO(format!(
+                    "Unable to create folder at path {:?}: {error}",
+                    image_folder.as_ref()
+                ))
+            })?;
+
+            image.save(&image_path).map_err(|error| {
+                AsepriteSheetError::IO(format!("Unable to save image {:?}: {error}", image_path))
+            })?;
+        }
+
+        descr.meta = meta.clone();
+        descr.meta.image = image_name;
+        descr.meta.format = "RGBA8888".to_string();
+        descr.meta.size = AsepriteSize::with_size(width, height);
+
+        Ok(descr)
+    }
+
+    /// Whether the sheet was exported with split layers.
+    pub fn has_split_layers(&self) -> bool {
+        self.frames
+            .iter()
+            .any(|tuple| from_aseprite_frame_name_to_layer_name(&tuple.name).is_some())
+    }
+
     pub fn from_json<P: AsRef<Path>>(path: P) -> AsepriteSheetResult<Self> {
         let contents = fs::read_to_string(&path).map_err(|_| {
             AsepriteSheetError::IO(format!("Failed to read file {:?}", &path.as_ref()))
         })?;
 
-        let aseprite_json: Value = serde_json::from_str(&contents).map_err(|_| {
-            AsepriteSheetError::Malformed(format!("Failed to parse JSON file {:?}", &path.as_ref()))
-        })?;
+        Self::from_json_str(&contents).map_err(|error| match error {
+            AsepriteSheetError::Malformed(error) => {
+                AsepriteSheetError::Malformed(format!("{error} in file {:?}", &path.as_ref()))
+            }
+            error => error,
+        })
+    }
+
+    /// Parses an Aseprite JSON export, returning an error rather than
+    /// panicking whatever `contents` hold.
+    pub fn from_json_str(contents: &str) -> AsepriteSheetResult<Self> {
+        let aseprite_json: Value = serde_json::from_str(contents)
+            .map_err(|_| AsepriteSheetError::Malformed("Failed to parse JSON".to_string()))?;
 
         let map = match aseprite_json {
             Value::Object(map) => map,
             _ => {
-                return Err(AsepriteSheetError::Malformed(format!(
-                    "Invalid JSON structure in file {:?}",
-                    &path.as_ref()
-                )))
+                return Err(AsepriteSheetError::Malformed(
+                    "Invalid JSON structure".to_string(),
+                ))
             }
         };
 
@@ -221,19 +1131,39 @@ impl AsepriteSheet {
 
         for (key, value) in map {
             match key.as_str() {
-                "frames" => {
-                    if let Value::Object(obj) = value {
+                "frames" => match value {
+                    // "Hash" layout: frames are keyed by their name.
+                    Value::Object(obj) => {
                         for (key, value) in obj {
-                            let mut tuple = AsepriteFrameTuple::new(key);
-                            tuple.data = serde_json::from_value(value).map_err(|_| {
-                                AsepriteSheetError::Malformed(
-                                    "Failed to deserialize frame data".to_string(),
-                                )
-                            })?;
-                            descr.frames.push(tuple);
+                            descr
+                                .frames
+                                .push(AsepriteFrameTuple::from_json(key, value)?);
                         }
                     }
-                }
+                    // "Array" layout (Aseprite CLI default): each frame holds
+                    // its name in a "filename" field.
+                    Value::Array(array) => {
+                        for value in array {
+                            let name = match value.get("filename") {
+                                Some(Value::String(name)) => name.clone(),
+                                _ => {
+                                    return Err(AsepriteSheetError::Malformed(
+                                        "Missing frame file name".to_string(),
+                                    ))
+                                }
+                            };
+
+                            descr
+                                .frames
+                                .push(AsepriteFrameTuple::from_json(name, value)?);
+                        }
+                    }
+                    _ => {
+                        return Err(AsepriteSheetError::Malformed(
+                            "Invalid frames structure".to_string(),
+                        ))
+                    }
+                },
                 "meta" => {
                     let meta: AsepriteMeta = serde_json::from_value(value).map_err(|_| {
                         AsepriteSheetError::Malformed("Failed to deserialize meta data".to_string())
@@ -252,25 +1182,23 @@ pub trait FromAsepriteSheet<T> {
     fn from_aseprite_sheet<P: AsRef<Path>>(
         containing_folder: P,
         sheet: &AsepriteSheet,
-    ) -> Option<T>;
+    ) -> AsepriteSheetResult<T>;
 }
 
 impl FromAsepriteSheet<SidSpriteSheetAsset> for SidSpriteSheetAsset {
     fn from_aseprite_sheet<P: AsRef<Path>>(
         containing_folder: P,
         sheet: &AsepriteSheet,
-    ) -> Option<SidSpriteSheetAsset> {
+    ) -> AsepriteSheetResult<SidSpriteSheetAsset> {
         let meta = &sheet.meta;
         let width = meta.size.w;
         let height = meta.size.h;
 
         if width <= 0 || height <= 0 {
-            sid_error!(
+            return Err(AsepriteSheetError::Malformed(format!(
                 "Invalid size ({width}x{height}) for Aseprite sheet {:?}",
-                meta.image
-            );
-
-            return None;
+                meta.image,
+            )));
         }
 
         let width = width as sid::SidSpriteSheetDim;
@@ -281,37 +1209,28 @@ impl FromAsepriteSheet<SidSpriteSheetAsset> for SidSpriteSheetAsset {
         let path = if meta.image.is_absolute() {
             meta.image.clone()
         } else {
-            let path = match std::fs::canonicalize(containing_folder.as_ref()) {
-                Ok(containing_folder_full_path) => containing_folder_full_path,
-                Err(error) => {
-                    sid_error!("Unable to retrieve the containing folder full path: {error}");
-                    return None;
-                }
-            };
+            // Generated images are not saved on dry runs, so their folder may
+            // not exist.
+            let path = std::path::absolute(containing_folder.as_ref()).map_err(|error| {
+                AsepriteSheetError::IO(format!(
+                    "Unable to retrieve the containing folder full path: {error}"
+                ))
+            })?;
 
             path.join(&meta.image)
         };
 
-        let name = match path.file_stem() {
-            Some(name) => name,
-            None => {
-                sid_error!("Invalid name for Aseprite sheet {:?}", path);
-                return None;
-            }
-        };
-
-        let name = match name.to_os_string().into_string() {
-            Ok(name) => name,
-            Err(_) => {
-                sid_error!("Invalid name for Aseprite sheet {:?}", path);
-                return None;
-            }
-        };
+        let name = path
+            .file_stem()
+            .and_then(|name| name.to_os_string().into_string().ok())
+            .ok_or_else(|| {
+                AsepriteSheetError::Malformed(format!("Invalid name for Aseprite sheet {:?}", path))
+            })?;
 
         let format = meta.format.clone();
         let sheet = SidSpriteSheetAsset::with_data(name, image_name, path, width, height, format);
 
-        Some(sheet)
+        Ok(sheet)
     }
 }
 
@@ -319,14 +1238,14 @@ pub trait FromAsepriteFrameTuplesAndSidSpriteSheet<T> {
     fn from_aseprite_frame_tuples_and_sid_sprite_sheet(
         aseprite_tuples: &[AsepriteFrameTuple],
         sheet: &SidSpriteSheetAsset,
-    ) -> Option<T>;
+    ) -> AsepriteSheetResult<T>;
 }
 
 impl FromAsepriteFrameTuplesAndSidSpriteSheet<SidAnimationDefAsset> for SidAnimationDefAsset {
     fn from_aseprite_frame_tuples_and_sid_sprite_sheet(
         aseprite_tuples: &[AsepriteFrameTuple],
         sheet: &SidSpriteSheetAsset,
-    ) -> Option<SidAnimationDefAsset> {
+    ) -> AsepriteSheetResult<SidAnimationDefAsset> {
         let frame_count = min(
             aseprite_tuples.len(),
             SidAnimationDefAsset::max_frame_count() as usize,
@@ -337,66 +1256,43 @@ impl FromAsepriteFrameTuplesAndSidSpriteSheet<SidAnimationDefAsset> for SidAnima
             let data = &tuple.data;
             let frame = &data.frame;
 
-            let x: u16 = match frame.x.try_into() {
-                Ok(x) => x,
-                Err(error) => {
-                    sid_error!(
-                        "Error while processing a frame pos (x) with {:?}: {error}",
-                        sheet.image_from_path
-                    );
-                    return None;
-                }
-            };
+            let pos = SidAnimationFramePos::with_coords(
+                to_frame_unit(frame.x, "pos (x)", sheet)?,
+                to_frame_unit(frame.y, "pos (y)", sheet)?,
+            );
 
-            let y: u16 = match frame.y.try_into() {
-                Ok(y) => y,
-                Err(error) => {
-                    sid_error!(
-                        "Error while processing a frame pos (y) with {:?}: {error}",
-                        sheet.image_from_path
-                    );
-                    return None;
-                }
-            };
+            let dim = SidAnimationFrameDims::with_width_and_height(
+                to_frame_unit(frame.w, "dimensions (width)", sheet)?,
+                to_frame_unit(frame.h, "dimensions (height)", sheet)?,
+            );
 
-            let pos = SidAnimationFramePos::with_coords(x, y);
+            let duration: u16 = data.duration.try_into().map_err(|error| {
+                AsepriteSheetError::Malformed(format!(
+                    "Error while processing a frame duration with {:?}: {error}",
+                    sheet.image_from_path
+                ))
+            })?;
 
-            let width: u16 = match frame.w.try_into() {
-                Ok(width) => width,
-                Err(error) => {
-                    sid_error!(
-                        "Error while processing a frame dimensions (width) with {:?}: {error}",
-                        sheet.image_from_path
-                    );
-                    return None;
-                }
-            };
+            let mut frame_asset = SidAnimationFrameAsset::with_data(pos, dim, duration);
+            frame_asset.rotated = data.rotated;
 
-            let height: u16 = match frame.h.try_into() {
-                Ok(height) => height,
-                Err(error) => {
-                    sid_error!(
-                        "Error while processing a frame dimensions (height) with {:?}: {error}",
-                        sheet.image_from_path
-                    );
-                    return None;
-                }
-            };
+            if data.trimmed {
+                let source_rect = &data.sprite_source_size;
+                let source_size = &data.source_size;
 
-            let dim = SidAnimationFrameDims::with_width_and_height(width, height);
+                let offset = SidAnimationFramePos::with_coords(
+                    to_frame_unit(source_rect.x, "trim offset (x)", sheet)?,
+                    to_frame_unit(source_rect.y, "trim offset (y)", sheet)?,
+                );
 
-            let duration: u16 = match data.duration.try_into() {
-                Ok(duration) => duration,
-                Err(error) => {
-                    sid_error!(
-                        "Error while processing a frame duration with {:?}: {error}",
-                        sheet.image_from_path
-                    );
-                    return None;
-                }
-            };
+                let source_dims = SidAnimationFrameDims::with_width_and_height(
+                    to_frame_unit(source_size.w, "source dimensions (width)", sheet)?,
+                    to_frame_unit(source_size.h, "source dimensions (height)", sheet)?,
+                );
+
+                frame_asset.trim = Some(SidAnimationFrameTrim::with_data(offset, source_dims));
+            }
 
-            let frame_asset = SidAnimationFrameAsset::with_data(pos, dim, duration);
             frames.push(frame_asset);
         }
 
@@ -404,10 +1300,19 @@ impl FromAsepriteFrameTuplesAndSidSpriteSheet<SidAnimationDefAsset> for SidAnima
         let name = sheet_name.clone();
         let animation_def = SidAnimationDefAsset::with_data(frame_count, frames, name, sheet_name);
 
-        Some(animation_def)
+        Ok(animation_def)
     }
 }
 
+fn to_frame_unit(value: i16, label: &str, sheet: &SidSpriteSheetAsset) -> AsepriteSheetResult<u16> {
+    value.try_into().map_err(|error| {
+        AsepriteSheetError::Malformed(format!(
+            "Error while processing a frame {label} with {:?}: {error}",
+            sheet.image_from_path
+        ))
+    })
+}
+
 pub fn from_aseprite_frame_name_to_animation_name(frame_name: &str) -> Option<&str> {
     let mut start_index = None;
     let mut end_index = None;
@@ -422,48 +1327,59 @@ pub fn from_aseprite_frame_name_to_animation_name(frame_name: &str) -> Option<&s
     }
 
     if let (Some(start), Some(end)) = (start_index, end_index) {
-        Some(&frame_name[start..end].trim())
+        Some(frame_name[start..end].trim())
     } else {
         None
     }
 }
 
+pub fn from_aseprite_frame_name_to_layer_name(frame_name: &str) -> Option<&str> {
+    let start = frame_name.find('[')? + 1;
+    let end = start + frame_name[start..].find(']')?;
+    Some(frame_name[start..end].trim())
+}
+
+/// Writes one animation asset per tag of `sheet`, named after `naming`, and
+/// returns their ids. Loop flags, speeds and events come from the tag user
+/// data and from `sidecar`.
 pub fn from_aseprite_sheet_to_sid_animations<P: AsRef<Path>>(
     sheet: &AsepriteSheet,
     def: &SidAnimationDefAsset,
+    sidecar: &AsepriteSidecar,
+    naming: &AsepriteAnimationNaming,
     assets_output_path: P,
-) {
-    if sheet.frames.len() == 0 {
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let names = &naming.names;
+    let mut ids = vec![];
+
+    if sheet.frames.is_empty() {
         sid_warning!("No animation provided from sheet {:?}", sheet.meta.image);
-        return;
+        return Ok(ids);
+    }
+
+    // Frame indices are 16-bit.
+    if sheet.frames.len() > u16::MAX as usize {
+        return Err(SidToolError::Malformed(format!(
+            "Too many animations added from sheet {:?}",
+            sheet.meta.image
+        )));
     }
 
     let mut offset = 0;
     let mut last_anim_name = "";
 
     for i in 0..sheet.frames.len() {
-        if i > u16::MAX as usize {
-            sid_error!(
-                "Too many animations added from sheet {:?}. Aborting.",
-                sheet.meta.image
-            );
-
-            return;
-        }
-
         let tuple = &sheet.frames[i];
 
-        let anim_name = match from_aseprite_frame_name_to_animation_name(&tuple.name) {
-            Some(anim_name) => anim_name,
-            None => {
-                sid_error!(
-                    "Malformed animation name from sheet {:?}. Aborting.",
+        let anim_name =
+            from_aseprite_frame_name_to_animation_name(&tuple.name).ok_or_else(|| {
+                SidToolError::Malformed(format!(
+                    "Malformed animation name from sheet {:?}",
                     sheet.meta.image
-                );
-
-                return;
-            }
-        };
+                ))
+            })?;
 
         if i == 0 {
             last_anim_name = anim_name;
@@ -474,98 +1390,990 @@ pub fn from_aseprite_sheet_to_sid_animations<P: AsRef<Path>>(
             continue;
         }
 
-        let mut final_anim_name = String::with_capacity(last_anim_name.len() + def.name.len() + 1);
-
-        final_anim_name.push_str(&def.name[..def.name.len()]);
-        final_anim_name.push('_');
-        final_anim_name.push_str(last_anim_name);
+        let final_anim_name = naming.animation_name(&def.name, last_anim_name);
 
         let i = i as u16;
-        let sid_asset = SidAnimationAsset::from_def(def, final_anim_name, offset, i - offset);
+        let mut sid_asset = SidAnimationAsset::from_def(
+            def,
+            final_anim_name,
+            offset,
+            i - offset,
+            sheet.tag_direction(last_anim_name),
+        );
+        sheet.apply_tag_data(last_anim_name, sidecar, &mut sid_asset);
         last_anim_name = anim_name;
         offset = i;
 
-        if let Err(error) = sid_asset.write_to_folder(&assets_output_path) {
-            sid_error!("{error}");
-        }
+        write_asset(&sid_asset, names, &assets_output_path, subfolder, dry_run)?;
+        ids.push(sid_asset.resource_id());
     }
 
-    let mut final_anim_name = String::with_capacity(last_anim_name.len() + def.name.len() + 1);
+    let final_anim_name = naming.animation_name(&def.name, last_anim_name);
 
-    final_anim_name.push_str(&def.name[..def.name.len()]);
-    final_anim_name.push('_');
-    final_anim_name.push_str(last_anim_name);
-
-    let sid_asset = SidAnimationAsset::from_def(
+    let mut sid_asset = SidAnimationAsset::from_def(
         def,
-        final_anim_name.to_string(),
+        final_anim_name,
         offset,
         sheet.frames.len() as u16 - offset,
+        sheet.tag_direction(last_anim_name),
     );
+    sheet.apply_tag_data(last_anim_name, sidecar, &mut sid_asset);
+
+    write_asset(&sid_asset, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(sid_asset.resource_id());
+    Ok(ids)
+}
+
+impl AsepriteRect {
+    fn to_sid_slice_rect(&self) -> Option<SidSliceRect> {
+        let width = self.w.try_into().ok()?;
+        let height = self.h.try_into().ok()?;
+
+        Some(SidSliceRect::with_coords_and_size(
+            self.x as i32,
+            self.y as i32,
+            width,
+            height,
+        ))
+    }
+}
+
+/// Writes one slice asset per slice of `sheet` and returns their ids.
+pub fn from_aseprite_sheet_to_sid_slices<P: AsRef<Path>>(
+    sheet: &AsepriteSheet,
+    sprite_sheet: &SidSpriteSheetAsset,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let mut ids = vec![];
+
+    for slice in &sheet.meta.slices {
+        let mut keys = Vec::with_capacity(slice.keys.len());
+
+        for key in &slice.keys {
+            let frame = key.frame.try_into().map_err(|error| {
+                SidToolError::Malformed(format!(
+                    "Error while processing slice {:?} (frame) with {:?}: {error}",
+                    slice.name, sheet.meta.image
+                ))
+            })?;
+
+            let bounds = key.bounds.to_sid_slice_rect();
+            let center = key.center.as_ref().map(|center| center.to_sid_slice_rect());
+
+            let (bounds, center) = match (bounds, center) {
+                (Some(bounds), None) => (bounds, None),
+                (Some(bounds), Some(Some(center))) => (bounds, Some(center)),
+                _ => {
+                    return Err(SidToolError::Malformed(format!(
+                        "Negative size for slice {:?} with {:?}",
+                        slice.name, sheet.meta.image
+                    )));
+                }
+            };
+
+            let pivot = key
+                .pivot
+                .as_ref()
+                .map(|pivot| SidSlicePivot::with_coords(pivot.x as i32, pivot.y as i32));
+
+            keys.push(SidSliceKeyAsset::with_data(frame, bounds, center, pivot));
+        }
+
+        let mut name = String::with_capacity(sprite_sheet.name.len() + slice.name.len() + 1);
+        name.push_str(&sprite_sheet.name);
+        name.push('_');
+        name.push_str(&slice.name);
+        let name = names.normalize(&name);
 
-    if let Err(error) = sid_asset.write_to_folder(&assets_output_path) {
-        sid_error!("{error}");
+        let sid_asset =
+            SidSliceAsset::with_data(name, sprite_sheet.name.clone(), keys, slice.data.clone());
+
+        write_asset(&sid_asset, names, &assets_output_path, subfolder, dry_run)?;
+        ids.push(sid_asset.resource_id());
     }
+
+    Ok(ids)
+}
+
+/// Writes one nine-slice asset per 9-slice of `sheet`, i.e. per slice with a
+/// center, and returns their ids. Nine-slices cover the bounds of the first
+/// key with a center, in the frame of that key as placed in `def`.
+pub fn from_aseprite_sheet_to_sid_nine_slices<P: AsRef<Path>>(
+    sheet: &AsepriteSheet,
+    def: &SidAnimationDefAsset,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let mut ids = vec![];
+
+    for slice in &sheet.meta.slices {
+        let Some((key, center)) = slice
+            .keys
+            .iter()
+            .find_map(|key| Some((key, key.center.as_ref()?)))
+        else {
+            continue;
+        };
+
+        let Some(frame) = def.frames.get(key.frame as usize) else {
+            sid_warning!(
+                "Skipping 9-slice {:?} of sheet {:?}: its frame {} does not exist.",
+                slice.name,
+                sheet.meta.image,
+                key.frame
+            );
+            continue;
+        };
+
+        if frame.rotated {
+            sid_warning!(
+                "Skipping 9-slice {:?} of sheet {:?}: its frame is rotated.",
+                slice.name,
+                sheet.meta.image
+            );
+            continue;
+        }
+
+        let (offset_x, offset_y) = match &frame.trim {
+            Some(trim) => (trim.offset.x as i32, trim.offset.y as i32),
+            None => (0, 0),
+        };
+
+        // Bounds relative to the part of the frame stored in the sheet.
+        let x = key.bounds.x as i32 - offset_x;
+        let y = key.bounds.y as i32 - offset_y;
+        let (width, height) = (key.bounds.w as i32, key.bounds.h as i32);
+
+        if x < 0
+            || y < 0
+            || x + width > frame.dims.width as i32
+            || y + height > frame.dims.height as i32
+        {
+            sid_warning!(
+                "Skipping 9-slice {:?} of sheet {:?}: its bounds are out of its trimmed frame.",
+                slice.name,
+                sheet.meta.image
+            );
+            continue;
+        }
+
+        let (left, top) = (center.x as i32, center.y as i32);
+        let right = width - left - center.w as i32;
+        let bottom = height - top - center.h as i32;
+
+        if left < 0 || top < 0 || right < 0 || bottom < 0 {
+            return Err(SidToolError::Malformed(format!(
+                "Center out of the bounds of 9-slice {:?} with {:?}",
+                slice.name, sheet.meta.image
+            )));
+        }
+
+        let region = SidSliceRect::with_coords_and_size(
+            frame.pos.x as i32 + x,
+            frame.pos.y as i32 + y,
+            width as u32,
+            height as u32,
+        );
+
+        let sid_asset = SidNineSliceAsset::with_data(
+            names.normalize(&format!("{}_{}", def.sheet_name, slice.name)),
+            def.sheet_name.clone(),
+            region,
+            SidNineSliceInsets::with_sizes(left as u32, top as u32, right as u32, bottom as u32),
+        );
+
+        write_asset(&sid_asset, names, &assets_output_path, subfolder, dry_run)?;
+        ids.push(sid_asset.resource_id());
+    }
+
+    Ok(ids)
 }
 
 pub fn from_aseprite_sheets_to_sid_assets<P: AsRef<Path>>(
     sheets_input_path: P,
     assets_output_path: P,
-) {
-    let entries = fs::read_dir(&sheets_input_path)
-        .unwrap_or_else(|error| panic!("Failed to read directory: {error}"));
+) -> SidToolResult<()> {
+    from_aseprite_sheets_to_sid_assets_with_options(
+        sheets_input_path,
+        assets_output_path,
+        &AsepriteConvertOptions::new(),
+    )
+}
+
+pub fn from_aseprite_sheets_to_sid_assets_with_options<P: AsRef<Path>>(
+    sheets_input_path: P,
+    assets_output_path: P,
+    options: &AsepriteConvertOptions,
+) -> SidToolResult<()> {
+    from_aseprite_sheets_to_sid_assets_with_report(
+        sheets_input_path,
+        assets_output_path,
+        options,
+        &mut SidReport::new(),
+    )
+}
+
+/// Converts every Aseprite sheet of `sheets_input_path`, and of its subfolders
+/// on recursive runs, into assets. Sheets failing to convert do not stop the
+/// others, but are all reported in a `SidToolError::AssetFailures` error. The
+/// status of every input file is added to `report`.
+pub fn from_aseprite_sheets_to_sid_assets_with_report<P: AsRef<Path>>(
+    sheets_input_path: P,
+    assets_output_path: P,
+    options: &AsepriteConvertOptions,
+    report: &mut SidReport,
+) -> SidToolResult<()> {
+    let filter = AsepriteLayerFilter::from_options(options)?;
+    let sheet_filter = AsepriteSheetFilter::from_options(options)?;
+    let sheets_input_path = sheets_input_path.as_ref();
+
+    let paths = find_sheet_paths(
+        sheets_input_path,
+        assets_output_path.as_ref(),
+        options.recursive,
+        options.sequences,
+    )?;
+
+    if !options.dry_run {
+        fs::create_dir_all(&assets_output_path).map_err(|error| {
+            SidToolError::IO(format!(
+                "Unable to create folder at path {:?}: {error}",
+                assets_output_path.as_ref()
+            ))
+        })?;
+    }
+
+    // Generated sheet images are stored with the other sprite sheet assets,
+    // so that they are only written once.
+    let image_folder = assets_output_path
+        .as_ref()
+        .join(SidSpriteSheetAsset::namespace());
+
+    let mut failures = vec![];
+
+    // Characters list the animations of their sheets, so they are converted
+    // once every sheet is.
+    let (manifest_paths, paths): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .filter(|path| sheet_filter.matches(path.strip_prefix(sheets_input_path).unwrap_or(path)))
+        .partition(|path| character::is_character_manifest(path));
+
+    let (frame_paths, paths): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .partition(|path| image_sequence::is_sequence_frame(path));
+
+    for path in paths {
+        let relative_path = path.strip_prefix(sheets_input_path).unwrap_or(&path);
+
+        // Extensions were checked when looking for sheets.
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let subfolder = relative_path.parent().unwrap_or(Path::new(""));
+        let sheet_folder = path.parent().unwrap_or(sheets_input_path);
+        let sheet_image_folder = image_folder.join(subfolder);
+        let start = Instant::now();
+
+        // Tiled maps bring their own tilesets, layer options do not apply.
+        if extension == "tmx" {
+            let (ids, result) = match tiled::from_tiled_map_to_sid_assets(
+                &path,
+                &options.names,
+                &assets_output_path,
+                subfolder,
+                options.dry_run,
+            ) {
+                Ok(ids) => (ids, Ok(())),
+                Err(error) => (vec![], Err(error)),
+            };
 
-    for entry in entries {
-        if let Err(error) = entry {
-            sid_error!("Error iterating over directory entry: {error}");
+            add_file_report(report, &mut failures, path, ids, result, start.elapsed());
             continue;
         }
 
-        let path = match entry {
-            Ok(entry) => entry.path(),
-            Err(err) => {
-                sid_error!("Error while processing an entry: {err}");
-                continue;
+        // Bitmap fonts bring their own page images.
+        if extension == "fnt" {
+            let (ids, result) = match bmfont::from_bmfont_to_sid_assets(
+                &path,
+                &options.names,
+                &assets_output_path,
+                subfolder,
+                options.dry_run,
+            ) {
+                Ok(ids) => (ids, Ok(())),
+                Err(error) => (vec![], Err(error)),
+            };
+
+            add_file_report(report, &mut failures, path, ids, result, start.elapsed());
+            continue;
+        }
+
+        // glTF files bring their own textures.
+        if extension == "gltf" || extension == "glb" {
+            let (ids, result) = match gltf::from_gltf_to_sid_assets(
+                &path,
+                &options.names,
+                &assets_output_path,
+                subfolder,
+                options.dry_run,
+            ) {
+                Ok(ids) => (ids, Ok(())),
+                Err(error) => (vec![], Err(error)),
+            };
+
+            add_file_report(report, &mut failures, path, ids, result, start.elapsed());
+            continue;
+        }
+
+        // Spine skeletons are exported as JSON too, but hold no sheet.
+        if extension == "json" && spine::is_spine_json(&path) {
+            let (ids, result) = match spine::from_spine_skeleton_to_sid_assets(
+                &path,
+                &options.names,
+                &assets_output_path,
+                subfolder,
+                options.dry_run,
+            ) {
+                Ok(ids) => (ids, Ok(())),
+                Err(error) => (vec![], Err(error)),
+            };
+
+            add_file_report(report, &mut failures, path, ids, result, start.elapsed());
+            continue;
+        }
+
+        // GIFs are packed like image sequences, the layer options do not apply
+        // either.
+        if extension == "gif" {
+            let mut ids = vec![];
+
+            let result = AsepriteSidecar::for_sheet(&path).and_then(|sidecar| {
+                let mut sidecar = sidecar.unwrap_or_default();
+                sidecar.dedup_frames.get_or_insert(options.dedup_frames);
+                sidecar.pivot_anchor = sidecar.pivot_anchor.or(options.pivot_anchor);
+
+                ids = gif::from_gif_to_sid_assets(
+                    &path,
+                    &sidecar,
+                    &options.names,
+                    &sheet_image_folder,
+                    &assets_output_path,
+                    subfolder,
+                    options.dry_run,
+                )?;
+
+                Ok(())
+            });
+
+            add_file_report(report, &mut failures, path, ids, result, start.elapsed());
+            continue;
+        }
+
+        let overridden_options = options.for_sheet(relative_path);
+        let sheet_options = overridden_options.as_ref().unwrap_or(options);
+
+        let sheet_filter = match &overridden_options {
+            Some(overridden_options) => {
+                AsepriteLayerFilter::from_options(overridden_options).map(Some)
             }
+            None => Ok(None),
+        };
+
+        // TexturePacker does not time frames, nor split layers.
+        let from_texture_packer = |sheet: TexturePackerSheet| {
+            let sheet = AsepriteSheet::from_texture_packer(&sheet, options.sequence_frame_duration);
+            (vec![sheet], sheet_folder.to_path_buf(), None)
         };
 
-        if let Some(extension) = path.extension() {
-            if extension != "json" {
+        let sheets = sheet_filter.and_then(|sheet_filter| {
+            let filter = sheet_filter.as_ref().unwrap_or(&filter);
+
+            match extension.as_str() {
+                "xml" => TexturePackerSheet::from_xml(&path).map(from_texture_packer),
+                "json" if texture_packer::is_texture_packer_json(&path) => {
+                    TexturePackerSheet::from_json(&path).map(from_texture_packer)
+                }
+                "json" => AsepriteSheet::from_json(&path).and_then(|sheet| {
+                    if sheet.has_split_layers() {
+                        let sheets = sheet.split_layers(
+                            sheet_folder,
+                            &sheet_image_folder,
+                            filter,
+                            options.dry_run,
+                        )?;
+                        Ok((sheets, sheet_image_folder.clone(), None))
+                    } else {
+                        if !filter.is_empty() {
+                            sid_warning!(
+                                "Ignoring layer filters for {:?}, not exported with split layers.",
+                                path
+                            );
+                        }
+
+                        Ok((vec![sheet], sheet_folder.to_path_buf(), None))
+                    }
+                }),
+                _ => match &options.aseprite_cli {
+                    Some(aseprite_path) => AsepriteSheet::from_aseprite_cli(
+                        aseprite_path,
+                        &path,
+                        filter,
+                        sheet_options.split_layers,
+                    )
+                    .and_then(|(export, sheet)| {
+                        if !sheet.has_split_layers() {
+                            let folder = export.folder().to_path_buf();
+                            return Ok((vec![sheet], folder, Some(export)));
+                        }
+
+                        let sheets = sheet.split_layers(
+                            export.folder(),
+                            &sheet_image_folder,
+                            filter,
+                            options.dry_run,
+                        )?;
+                        Ok((sheets, sheet_image_folder.clone(), Some(export)))
+                    }),
+                    None => AsepriteSheet::from_aseprite_file(
+                        &path,
+                        &sheet_image_folder,
+                        filter,
+                        sheet_options.split_layers,
+                        options.dry_run,
+                    )
+                    .map(|sheets| (sheets, sheet_image_folder.clone(), None)),
+                },
+            }
+        });
+
+        let mut ids = vec![];
+
+        let result = sheets
+            .map_err(SidToolError::from)
+            // Exports are kept until their sheet images are copied.
+            .and_then(|(sheets, containing_folder, _export)| {
+                // Split layers share the sidecar of their file.
+                let mut sidecar = AsepriteSidecar::for_sheet(&path)?.unwrap_or_default();
+                sidecar.dedup_frames.get_or_insert(options.dedup_frames);
+                sidecar.pivot_anchor = sidecar.pivot_anchor.or(options.pivot_anchor);
+                let mut naming = sidecar.animation_naming(&options.animation_naming);
+                naming.names = options.names.clone();
+
+                sheets.iter().try_for_each(|sheet| {
+                    ids.extend(from_aseprite_sheet_to_sid_assets(
+                        sheet,
+                        &sidecar,
+                        &naming,
+                        &containing_folder,
+                        &assets_output_path,
+                        subfolder,
+                        options.dry_run,
+                    )?);
+
+                    Ok(())
+                })
+            });
+
+        add_file_report(report, &mut failures, path, ids, result, start.elapsed());
+    }
+
+    for sequence in image_sequence::group_sequences(frame_paths) {
+        let path = sequence.path();
+        let relative_path = path.strip_prefix(sheets_input_path).unwrap_or(&path);
+        let subfolder = relative_path.parent().unwrap_or(Path::new(""));
+        let start = Instant::now();
+
+        let mut ids = vec![];
+
+        let result = AsepriteSidecar::for_sheet(&path).and_then(|sidecar| {
+            let mut sidecar = sidecar.unwrap_or_default();
+            sidecar.dedup_frames.get_or_insert(options.dedup_frames);
+            sidecar.pivot_anchor = sidecar.pivot_anchor.or(options.pivot_anchor);
+            sidecar
+                .frame_duration
+                .get_or_insert(options.sequence_frame_duration);
+
+            ids = image_sequence::from_image_sequence_to_sid_assets(
+                &sequence,
+                &sidecar,
+                &options.names,
+                image_folder.join(subfolder),
+                &assets_output_path,
+                subfolder,
+                options.dry_run,
+            )?;
+
+            Ok(())
+        });
+
+        add_file_report(report, &mut failures, path, ids, result, start.elapsed());
+    }
+
+    for path in manifest_paths {
+        let relative_path = path.strip_prefix(sheets_input_path).unwrap_or(&path);
+        let subfolder = relative_path.parent().unwrap_or(Path::new(""));
+        let start = Instant::now();
+
+        let (ids, result) = match character::from_character_manifest_to_sid_character(
+            &path,
+            &options.names,
+            &assets_output_path,
+            subfolder,
+            options.dry_run,
+        ) {
+            Ok(ids) => (ids, Ok(())),
+            Err(error) => (vec![], Err(error)),
+        };
+
+        add_file_report(report, &mut failures, path, ids, result, start.elapsed());
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
+
+/// Paths of the sheets, TexturePacker exports, Spine skeletons, maps, GIFs,
+/// BMFont files, glTF files and character manifests of `sheets_input_path`,
+/// along with the frames of image sequences if `sequences` is set, and of its
+/// subfolders if `recursive` is set, sorted.
+/// Hidden folders are skipped, as well as `assets_output_path` when it is inside
+/// the input folder, links unless `walk::follows_symlinks()`, and the entries
+/// matching a `.sidignore` file.
+fn find_sheet_paths(
+    sheets_input_path: &Path,
+    assets_output_path: &Path,
+    recursive: bool,
+    sequences: bool,
+) -> SidToolResult<Vec<PathBuf>> {
+    // The assets folder may not exist yet on dry runs.
+    let assets_output_path = fs::canonicalize(assets_output_path).ok();
+
+    let mut paths = vec![];
+    let mut folders = vec![sheets_input_path.to_path_buf()];
+    let mut visited_folders = SidVisitedFolders::new();
+    let mut ignore_rules = SidIgnoreRules::for_root(sheets_input_path);
+
+    while let Some(folder) = folders.pop() {
+        if !visited_folders.visit(&folder) {
+            continue;
+        }
+
+        ignore_rules.load(&folder);
+
+        let entries = match fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(error) if folder == sheets_input_path => {
+                return Err(SidToolError::IO(format!(
+                    "Failed to read directory {:?}: {error}",
+                    folder
+                )));
+            }
+            Err(error) => {
+                sid_error!("Failed to read directory {:?}: {error}", folder);
                 continue;
             }
+        };
 
-            let sheet = match AsepriteSheet::from_json(&path) {
-                Ok(sheet) => sheet,
+        for entry in entries {
+            let (path, kind) = match entry {
+                Ok(entry) => (entry.path(), walk::entry_kind(&entry)),
                 Err(error) => {
-                    sid_error!("{error}");
+                    sid_error!("Error iterating over directory entry: {error}");
                     continue;
                 }
             };
 
-            let sid_asset =
-                match SidSpriteSheetAsset::from_aseprite_sheet(&sheets_input_path, &sheet) {
-                    Some(sid_asset) => sid_asset,
-                    None => continue,
-                };
+            if kind == Some(SidEntryKind::Folder) {
+                let is_hidden = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with('.'));
+
+                let is_assets_output = assets_output_path.is_some()
+                    && fs::canonicalize(&path).ok() == assets_output_path;
+
+                if recursive
+                    && !is_hidden
+                    && !is_assets_output
+                    && !ignore_rules.is_ignored(&path, true)
+                {
+                    folders.push(path);
+                }
 
-            if let Err(error) = sid_asset.write_to_folder(&assets_output_path) {
-                sid_error!("{error}");
+                continue;
             }
 
-            let sid_asset =
-                match SidAnimationDefAsset::from_aseprite_frame_tuples_and_sid_sprite_sheet(
-                    &sheet.frames,
-                    &sid_asset,
-                ) {
-                    Some(sid_asset) => sid_asset,
-                    None => continue,
-                };
+            if kind != Some(SidEntryKind::File) {
+                continue;
+            }
 
-            if let Err(error) = sid_asset.write_to_folder(&assets_output_path) {
-                sid_error!("{error}");
+            let is_sheet = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    matches!(
+                        extension.to_lowercase().as_str(),
+                        "json" | "aseprite" | "ase" | "tmx" | "gif" | "fnt" | "gltf" | "glb"
+                    )
+                });
+
+            // Other XML files may live next to the sheets, e.g. Tiled tilesets.
+            let is_texture_packer_xml = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"))
+                && texture_packer::is_texture_packer_xml(&path);
+
+            let is_ignored = ignore_rules.is_ignored(&path, false);
+            let is_frame = sequences && image_sequence::is_sequence_frame(&path);
+
+            if (is_sheet
+                || is_texture_packer_xml
+                || is_frame
+                || character::is_character_manifest(&path))
+                && !is_ignored
+            {
+                paths.push(path);
             }
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+fn add_file_report(
+    report: &mut SidReport,
+    failures: &mut Vec<SidAssetFailure>,
+    path: PathBuf,
+    ids: Vec<SidStringId>,
+    result: SidToolResult<()>,
+    duration: Duration,
+) {
+    let status = if result.is_ok() {
+        SidAssetStatus::Ok
+    } else {
+        SidAssetStatus::Failed
+    };
 
-            from_aseprite_sheet_to_sid_animations(&sheet, &sid_asset, &assets_output_path);
+    let mut asset_report = SidAssetReport::new(path.clone(), status, duration);
+    asset_report.ids = ids;
+
+    if let Err(error) = result {
+        asset_report.error = Some(error.to_string());
+        failures.push(SidAssetFailure { path, error });
+    }
+
+    report.add(asset_report);
+}
+
+/// Writes `asset` to `subfolder` of its namespace folder in
+/// `assets_output_path`, or only logs it on dry runs. Its name must follow
+/// `names`.
+pub(crate) fn write_asset<T, P>(
+    asset: &T,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<()>
+where
+    T: SidPackedAsset + SerializeSidAsset + Serialize,
+    P: AsRef<Path>,
+{
+    // The packer would reject the asset anyway, so fail its file now.
+    names.check(asset.name()).map_err(|violation| {
+        SidToolError::Malformed(format!(
+            "Invalid name {:?} of {} asset: {violation}",
+            asset.name(),
+            T::namespace()
+        ))
+    })?;
+
+    if !dry_run {
+        return Ok(asset.write_to_subfolder(assets_output_path, subfolder)?);
+    }
+
+    let json = serde_json::to_string_pretty(asset).map_err(|error| {
+        SidToolError::Malformed(format!(
+            "Unable to create asset {:?}: {error}",
+            asset.name()
+        ))
+    })?;
+
+    sid_info!(
+        "Would write {} {:?} ({}): {} byte(s)",
+        T::namespace(),
+        asset.name(),
+        asset.resource_id(),
+        json.len()
+    );
+
+    Ok(())
+}
+
+fn from_aseprite_sheet_to_sid_assets<P1: AsRef<Path>, P2: AsRef<Path>>(
+    sheet: &AsepriteSheet,
+    sidecar: &AsepriteSidecar,
+    naming: &AsepriteAnimationNaming,
+    containing_folder: P1,
+    assets_output_path: P2,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let names = &naming.names;
+    let mut sid_asset = SidSpriteSheetAsset::from_aseprite_sheet(&containing_folder, sheet)?;
+    // The other assets of the sheet are named after it.
+    sid_asset.name = names.normalize(&sid_asset.name);
+    sid_asset.pivot = sidecar
+        .pivot
+        .clone()
+        .or_else(|| Some(anchor_pivot(sidecar.pivot_anchor?, sheet.canvas_size()?)));
+    sid_asset.atlas_group = sidecar.atlas_group.clone();
+
+    let normal_map = from_companion_image_to_sid_texture(
+        &sid_asset,
+        NORMAL_MAP_SUFFIX,
+        "normal",
+        SidColorSpace::Linear,
+    )?;
+    let emissive_map = from_companion_image_to_sid_texture(
+        &sid_asset,
+        EMISSIVE_MAP_SUFFIX,
+        "emissive",
+        SidColorSpace::Srgb,
+    )?;
+
+    let mut ids = vec![];
+
+    for texture in normal_map.iter().chain(&emissive_map) {
+        write_asset(texture, names, &assets_output_path, subfolder, dry_run)?;
+        ids.push(texture.resource_id());
+    }
+
+    sid_asset.normal_map = normal_map.map(|texture| texture.name);
+    sid_asset.emissive_map = emissive_map.map(|texture| texture.name);
+    write_asset(&sid_asset, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(sid_asset.resource_id());
+    ids.extend(from_aseprite_sheet_to_sid_slices(
+        sheet,
+        &sid_asset,
+        names,
+        &assets_output_path,
+        subfolder,
+        dry_run,
+    )?);
+
+    let image_path = sid_asset.image_from_path.clone();
+    let mut sid_asset = SidAnimationDefAsset::from_aseprite_frame_tuples_and_sid_sprite_sheet(
+        &sheet.frames,
+        &sid_asset,
+    )?;
+
+    if let Some(duration) = sidecar.frame_duration {
+        for frame in &mut sid_asset.frames {
+            frame.duration = duration;
+        }
+    }
+
+    for (frame, pivot) in sid_asset.frames.iter_mut().zip(sheet.frame_pivots()) {
+        frame.pivot = pivot;
+    }
+
+    let trim = sidecar.trim == Some(true);
+    let dedup = sidecar.dedup_frames == Some(true);
+
+    // Images generated from native Aseprite files are not saved on dry runs.
+    if (trim || dedup) && dry_run && !image_path.is_file() {
+        sid_info!("Would process the frames of {:?}", sid_asset.name);
+    } else if trim || dedup {
+        let image = open_sheet_image(&image_path)?;
+
+        if trim {
+            trim_frames(&mut sid_asset, &image, &image_path)?;
+        }
+
+        if dedup {
+            let count = dedup_frames(&mut sid_asset, &image);
+
+            if count > 0 {
+                sid_debug!("Merged {count} duplicate frame(s) of {:?}", sid_asset.name);
+            }
+        }
+    }
+
+    write_asset(&sid_asset, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(sid_asset.resource_id());
+
+    ids.extend(from_aseprite_sheet_to_sid_nine_slices(
+        sheet,
+        &sid_asset,
+        names,
+        &assets_output_path,
+        subfolder,
+        dry_run,
+    )?);
+
+    ids.extend(from_aseprite_sheet_to_sid_animations(
+        sheet,
+        &sid_asset,
+        sidecar,
+        naming,
+        &assets_output_path,
+        subfolder,
+        dry_run,
+    )?);
+
+    Ok(ids)
+}
+
+/// Texture of the companion image of the image of `sheet`, if any, named after
+/// `suffix`, e.g. `hero_n.png` next to `hero.png`. Companion images must have
+/// the layout, and so the size, of the sheet image.
+fn from_companion_image_to_sid_texture(
+    sheet: &SidSpriteSheetAsset,
+    suffix: &str,
+    kind: &str,
+    color_space: SidColorSpace,
+) -> SidToolResult<Option<SidTextureAsset>> {
+    let image_path = &sheet.image_from_path;
+
+    let (Some(stem), Some(extension)) = (image_path.file_stem(), image_path.extension()) else {
+        return Ok(None);
+    };
+
+    let file_name = format!(
+        "{}{suffix}.{}",
+        stem.to_string_lossy(),
+        extension.to_string_lossy()
+    );
+    let path = image_path.with_file_name(&file_name);
+
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let (width, height) = image::image_dimensions(&path)
+        .map_err(|error| SidToolError::IO(format!("Unable to read image {:?}: {error}", path)))?;
+
+    if (width, height) != (sheet.width, sheet.height) {
+        sid_warning!(
+            "Ignoring {kind} map {:?} of {:?}: it is {width}x{height}, not {}x{} like its sheet.",
+            path,
+            sheet.name,
+            sheet.width,
+            sheet.height
+        );
+        return Ok(None);
+    }
+
+    let mut texture =
+        SidTextureAsset::with_data(format!("{}_{kind}", sheet.name), file_name.into(), path);
+    texture.color_space = color_space;
+
+    Ok(Some(texture))
+}
+
+/// Position of `anchor` in frames of `width` by `height` pixels.
+pub(crate) fn anchor_pivot(anchor: SidPivotAnchor, (width, height): (i32, i32)) -> SidSlicePivot {
+    let (x, y) = match anchor {
+        SidPivotAnchor::TopLeft => (0, 0),
+        SidPivotAnchor::TopCenter => (width / 2, 0),
+        SidPivotAnchor::TopRight => (width, 0),
+        SidPivotAnchor::CenterLeft => (0, height / 2),
+        SidPivotAnchor::Center => (width / 2, height / 2),
+        SidPivotAnchor::CenterRight => (width, height / 2),
+        SidPivotAnchor::BottomLeft => (0, height),
+        SidPivotAnchor::BottomCenter => (width / 2, height),
+        SidPivotAnchor::BottomRight => (width, height),
+    };
+
+    SidSlicePivot::with_coords(x, y)
+}
+
+fn open_sheet_image(image_path: &Path) -> SidToolResult<RgbaImage> {
+    image::open(image_path)
+        .map(|image| image.to_rgba8())
+        .map_err(|error| {
+            SidToolError::IO(format!("Unable to open image {:?}: {error}", image_path))
+        })
+}
+
+/// Crops the frames of `def` to their opaque pixels in the sheet `image` at
+/// `image_path`. Rotated and empty frames are left as is.
+fn trim_frames(
+    def: &mut SidAnimationDefAsset,
+    image: &RgbaImage,
+    image_path: &Path,
+) -> SidToolResult<()> {
+    for frame in &mut def.frames {
+        atlas::trim_frame(frame, image).map_err(|error| {
+            SidToolError::Malformed(format!("Unable to trim {:?}: {error}", image_path))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Moves the frames of `def` showing the same pixels as an earlier frame of
+/// the sheet `image` to the region of that frame, so that they are packed
+/// once. Returns the number of moved frames.
+fn dedup_frames(def: &mut SidAnimationDefAsset, image: &RgbaImage) -> usize {
+    let mut count = 0;
+
+    for i in 1..def.frames.len() {
+        let (previous_frames, frames) = def.frames.split_at_mut(i);
+        let frame = &mut frames[0];
+
+        let Some(region) = frame_pixels(frame, image) else {
+            continue;
+        };
+
+        let original = previous_frames.iter().find(|other| {
+            other.rotated == frame.rotated
+                && other.dims == frame.dims
+                && other.trim == frame.trim
+                && frame_pixels(other, image)
+                    .is_some_and(|other_region| other_region.pixels().eq(region.pixels()))
+        });
+
+        let Some(original) = original else {
+            continue;
+        };
+
+        if original.pos != frame.pos {
+            frame.pos = original.pos.clone();
+            count += 1;
         }
     }
+
+    count
+}
+
+// Pixels the frame covers in the sheet image, if within its bounds.
+fn frame_pixels<'a>(
+    frame: &SidAnimationFrameAsset,
+    image: &'a RgbaImage,
+) -> Option<image::SubImage<&'a RgbaImage>> {
+    let dims = frame.sheet_dims();
+    let x = frame.pos.x as u32;
+    let y = frame.pos.y as u32;
+    let width = dims.width as u32;
+    let height = dims.height as u32;
+
+    if x + width > image.width() || y + height > image.height() {
+        return None;
+    }
+
+    Some(image.view(x, y, width, height))
 }
@@ -0,0 +1,113 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Deserialize;
+
+use sid_asset_packer::asset::SidSlicePivot;
+use sid_asset_packer::config::SidPivotAnchor;
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::sid::SidAnimationFrameIndex;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::naming::AsepriteAnimationNaming;
+
+pub static SIDECAR_EXTENSION: &str = "sid.toml";
+
+/// Conversion options of a single sheet, read from a `<name>.sid.toml` file
+/// next to it, e.g. `hero.sid.toml` for `hero.json` or `hero.aseprite`.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AsepriteSidecar {
+    /// Name of the animations, where `{sheet}` and `{tag}` are replaced by the
+    /// names of the sheet and of the tag, instead of the converter one.
+    pub animation_name: Option<String>,
+    /// Duration of every frame in milliseconds, replacing the exported ones.
+    pub frame_duration: Option<u16>,
+    /// Default pivot of the frames, in pixels from their top-left corner.
+    pub pivot: Option<SidSlicePivot>,
+    /// Default pivot of the frames, used when `pivot` is not set.
+    pub pivot_anchor: Option<SidPivotAnchor>,
+    /// Crop the frames to their opaque pixels.
+    pub trim: Option<bool>,
+    /// Make the frames showing the same pixels share the region of the first
+    /// one, so that they are packed once.
+    pub dedup_frames: Option<bool>,
+    /// Atlas the sprite sheet is packed into by the atlas command, instead of
+    /// the default one.
+    pub atlas_group: Option<String>,
+    /// Play the animations again once they end, unless their tag says
+    /// otherwise.
+    #[serde(rename = "loop")]
+    pub looping: Option<bool>,
+    /// Default playback speed of the animations, unless their tag says
+    /// otherwise.
+    pub speed_scale: Option<f32>,
+    /// Settings of the animation of each tag, written as `[tags.<name>]`
+    /// tables and replacing the ones of the tag user data.
+    pub tags: HashMap<String, AsepriteSidecarTag>,
+    /// Events of the animations, added to the ones of the tag user data.
+    pub events: Vec<AsepriteSidecarEvent>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AsepriteSidecarTag {
+    #[serde(rename = "loop")]
+    pub looping: Option<bool>,
+    pub speed_scale: Option<f32>,
+}
+
+/// Event of the animation of a tag, written as an `[[events]]` table.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AsepriteSidecarEvent {
+    pub tag: String,
+    /// Frame of the event, relative to the start of the tag.
+    pub frame: SidAnimationFrameIndex,
+    pub name: String,
+}
+
+impl AsepriteSidecar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the sidecar of the sheet at `sheet_path`, if any.
+    pub fn for_sheet<P: AsRef<Path>>(sheet_path: P) -> SidToolResult<Option<Self>> {
+        let path = sidecar_path(sheet_path);
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|error| {
+            SidToolError::IO(format!("Unable to read sidecar {:?}: {error}", path))
+        })?;
+
+        toml::from_str(&contents).map(Some).map_err(|error| {
+            SidToolError::Malformed(format!("Invalid sidecar {:?}: {error}", path))
+        })
+    }
+
+    /// `naming`, with the animation name template of the sidecar if any.
+    pub fn animation_naming(&self, naming: &AsepriteAnimationNaming) -> AsepriteAnimationNaming {
+        let mut naming = naming.clone();
+
+        if let Some(animation_name) = &self.animation_name {
+            naming.template = animation_name.clone();
+        }
+
+        naming
+    }
+}
+
+/// Path of the sidecar of the sheet at `sheet_path`.
+pub fn sidecar_path<P: AsRef<Path>>(sheet_path: P) -> PathBuf {
+    let sheet_path = sheet_path.as_ref();
+    let stem = sheet_path.file_stem().unwrap_or_default().to_string_lossy();
+    sheet_path.with_file_name(format!("{stem}.{SIDECAR_EXTENSION}"))
+}
@@ -0,0 +1,530 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Importer for the JSON skeletons exported by Spine 4.x, and 3.8 as far as
+// its format matches. Region attachments of the default skin are imported,
+// along with the rotate, translate and scale timelines of the bones and the
+// attachment timelines of the slots. Meshes, constraints, events and the
+// other timelines are skipped with a warning.
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use sid_asset_packer::asset::{
+    SidBoneKeyAsset, SidBoneTimelineAsset, SidBoneTimelineProperty, SidBoneTransform, SidKeyCurve,
+    SidSkeletalAnimationAsset, SidSkeletonAsset, SidSkeletonAttachmentAsset, SidSkeletonBoneAsset,
+    SidSkeletonSlotAsset, SidSlotKeyAsset, SidSlotTimelineAsset,
+};
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::resource::SerializeSidAsset;
+use sid_asset_packer::sid::SidStringId;
+
+use std::fs;
+use std::path::Path;
+
+use crate::sid_aseprite::write_asset;
+use crate::sid_warning;
+
+/// Skin whose attachments are imported, the one every Spine skeleton has.
+static DEFAULT_SKIN_NAME: &str = "default";
+
+/// Attachment type of the attachments without one.
+static REGION_ATTACHMENT_TYPE: &str = "region";
+
+#[derive(Deserialize)]
+struct SpineSkeletonInfo {
+    /// Version of Spine the skeleton was exported with, e.g. "4.1.23".
+    spine: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpineBone {
+    name: String,
+    parent: Option<String>,
+    #[serde(default)]
+    length: f32,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "default_spine_scale")]
+    scale_x: f32,
+    #[serde(default = "default_spine_scale")]
+    scale_y: f32,
+}
+
+#[derive(Deserialize)]
+struct SpineSlot {
+    name: String,
+    bone: String,
+    attachment: Option<String>,
+    /// RGBA color, as 8 hexadecimal digits.
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpineAttachment {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    /// Region of the atlas, when not the name of the attachment.
+    path: Option<String>,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "default_spine_scale")]
+    scale_x: f32,
+    #[serde(default = "default_spine_scale")]
+    scale_y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+}
+
+fn default_spine_scale() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct SpineSkeleton {
+    skeleton: SpineSkeletonInfo,
+    bones: Vec<SpineBone>,
+    #[serde(default)]
+    slots: Vec<SpineSlot>,
+    /// Array of named skins since Spine 4, object keyed by skin name before.
+    #[serde(default)]
+    skins: Value,
+    #[serde(default)]
+    animations: Map<String, Value>,
+}
+
+fn malformed(path: &Path, error: impl std::fmt::Display) -> SidToolError {
+    SidToolError::Malformed(format!("Invalid Spine skeleton {:?}: {error}", path))
+}
+
+/// Whether the JSON file at `path` is a Spine skeleton rather than a sheet.
+pub fn is_spine_json(path: &Path) -> bool {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return false;
+    };
+
+    let Ok(json) = serde_json::from_str::<Value>(&contents) else {
+        return false;
+    };
+
+    json.pointer("/skeleton/spine")
+        .is_some_and(Value::is_string)
+}
+
+/// Parses a slot color such as "ff8000ff".
+fn parse_color(color: &str) -> Option<[u8; 4]> {
+    if color.len() != 8 {
+        return None;
+    }
+
+    let mut rgba = [0; 4];
+
+    for (i, channel) in rgba.iter_mut().enumerate() {
+        *channel = u8::from_str_radix(color.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+
+    Some(rgba)
+}
+
+/// Attachments of the default skin, by slot then attachment name.
+fn default_skin_attachments(skins: &Value) -> Option<&Map<String, Value>> {
+    match skins {
+        Value::Array(skins) => skins
+            .iter()
+            .find(|skin| skin.get("name").and_then(Value::as_str) == Some(DEFAULT_SKIN_NAME))
+            .and_then(|skin| skin.get("attachments")),
+        Value::Object(skins) => skins.get(DEFAULT_SKIN_NAME),
+        _ => None,
+    }
+    .and_then(Value::as_object)
+}
+
+fn read_attachments(
+    json: &SpineSkeleton,
+    path: &Path,
+) -> SidToolResult<Vec<SidSkeletonAttachmentAsset>> {
+    let mut attachments = vec![];
+
+    let Some(slots) = default_skin_attachments(&json.skins) else {
+        return Ok(attachments);
+    };
+
+    for (slot, slot_attachments) in slots {
+        let Some(slot_attachments) = slot_attachments.as_object() else {
+            return Err(malformed(
+                path,
+                format!("invalid attachments of slot {slot:?}"),
+            ));
+        };
+
+        for (name, attachment) in slot_attachments {
+            let attachment = SpineAttachment::deserialize(attachment).map_err(|error| {
+                malformed(path, format!("invalid attachment {name:?}: {error}"))
+            })?;
+
+            let kind = attachment.kind.as_deref().unwrap_or(REGION_ATTACHMENT_TYPE);
+
+            if kind != REGION_ATTACHMENT_TYPE {
+                sid_warning!(
+                    "Skipping {kind} attachment {:?} of slot {:?} in {:?}, only regions are supported.",
+                    name,
+                    slot,
+                    path
+                );
+                continue;
+            }
+
+            attachments.push(SidSkeletonAttachmentAsset::with_data(
+                slot.clone(),
+                name.clone(),
+                attachment.path.unwrap_or_else(|| name.clone()),
+                SidBoneTransform {
+                    x: attachment.x,
+                    y: attachment.y,
+                    rotation: attachment.rotation,
+                    scale_x: attachment.scale_x,
+                    scale_y: attachment.scale_y,
+                },
+                attachment.width,
+                attachment.height,
+            ));
+        }
+    }
+
+    Ok(attachments)
+}
+
+fn number(key: &Value, name: &str) -> Option<f32> {
+    key.get(name)
+        .and_then(Value::as_f64)
+        .map(|value| value as f32)
+}
+
+/// Whether the skeleton was exported by Spine 3, whose keys differ from the
+/// ones of Spine 4.
+fn is_spine_3(version: &str) -> bool {
+    version.starts_with("3.")
+}
+
+/// Values of a bone key, defaulting to the setup pose.
+fn key_values(key: &Value, property: SidBoneTimelineProperty, version: &str) -> [f32; 2] {
+    match property {
+        SidBoneTimelineProperty::Rotate => {
+            let field = if is_spine_3(version) {
+                "angle"
+            } else {
+                "value"
+            };
+            [number(key, field).unwrap_or(0.0), 0.0]
+        }
+        SidBoneTimelineProperty::Translate => [
+            number(key, "x").unwrap_or(0.0),
+            number(key, "y").unwrap_or(0.0),
+        ],
+        SidBoneTimelineProperty::Scale => [
+            number(key, "x").unwrap_or(1.0),
+            number(key, "y").unwrap_or(1.0),
+        ],
+    }
+}
+
+/// Curve from `key`, at `time` and of first value `value`, to `next`. Spine 3
+/// stores Bézier control points relative to the keys, Spine 4 stores them as
+/// times and values for each value of the key, of which the first is used.
+fn key_curve(
+    key: &Value,
+    next: Option<(f32, [f32; 2])>,
+    time: f32,
+    value: f32,
+    version: &str,
+) -> SidKeyCurve {
+    let points: Vec<Option<&Value>> = match key.get("curve") {
+        Some(Value::String(curve)) if curve == "stepped" => return SidKeyCurve::Stepped,
+        Some(Value::Array(points)) => points.iter().map(Some).collect(),
+        // Spine 3.8 splits the control points into 4 fields.
+        Some(curve @ Value::Number(_)) => {
+            vec![Some(curve), key.get("c2"), key.get("c3"), key.get("c4")]
+        }
+        _ => vec![],
+    };
+
+    let points = points
+        .into_iter()
+        .map(|point| point.and_then(Value::as_f64).unwrap_or(0.0) as f32)
+        .collect::<Vec<_>>();
+
+    let &[x1, y1, x2, y2, ..] = points.as_slice() else {
+        return SidKeyCurve::Linear;
+    };
+
+    if is_spine_3(version) {
+        return SidKeyCurve::Bezier {
+            points: [x1, y1, x2, y2],
+        };
+    }
+
+    let Some((next_time, [next_value, _])) = next else {
+        return SidKeyCurve::Linear;
+    };
+
+    if next_time <= time {
+        return SidKeyCurve::Linear;
+    }
+
+    let time_span = next_time - time;
+    let value_span = next_value - value;
+
+    // Keys of the same value can not be eased, the curve is only timing then.
+    let relative_value = |point: f32, relative_time: f32| match value_span {
+        0.0 => relative_time,
+        span => (point - value) / span,
+    };
+
+    let (x1, x2) = ((x1 - time) / time_span, (x2 - time) / time_span);
+
+    SidKeyCurve::Bezier {
+        points: [x1, relative_value(y1, x1), x2, relative_value(y2, x2)],
+    }
+}
+
+fn read_bone_timeline(
+    bone: &str,
+    property: SidBoneTimelineProperty,
+    keys: &Value,
+    version: &str,
+    path: &Path,
+) -> SidToolResult<SidBoneTimelineAsset> {
+    let Some(keys) = keys.as_array() else {
+        return Err(malformed(
+            path,
+            format!("invalid timeline of bone {bone:?}"),
+        ));
+    };
+
+    let times_and_values = keys
+        .iter()
+        .map(|key| {
+            (
+                number(key, "time").unwrap_or(0.0),
+                key_values(key, property, version),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let keys = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let (time, values) = times_and_values[i];
+
+            SidBoneKeyAsset {
+                time,
+                values,
+                curve: key_curve(
+                    key,
+                    times_and_values.get(i + 1).copied(),
+                    time,
+                    values[0],
+                    version,
+                ),
+            }
+        })
+        .collect();
+
+    Ok(SidBoneTimelineAsset {
+        bone: bone.to_string(),
+        property,
+        keys,
+    })
+}
+
+fn read_slot_timeline(
+    slot: &str,
+    keys: &Value,
+    path: &Path,
+) -> SidToolResult<SidSlotTimelineAsset> {
+    let Some(keys) = keys.as_array() else {
+        return Err(malformed(
+            path,
+            format!("invalid timeline of slot {slot:?}"),
+        ));
+    };
+
+    let keys = keys
+        .iter()
+        .map(|key| SidSlotKeyAsset {
+            time: number(key, "time").unwrap_or(0.0),
+            attachment: key.get("name").and_then(Value::as_str).map(str::to_string),
+        })
+        .collect();
+
+    Ok(SidSlotTimelineAsset {
+        slot: slot.to_string(),
+        keys,
+    })
+}
+
+fn read_animation(
+    name: &str,
+    skeleton_name: &str,
+    animation: &Value,
+    version: &str,
+    path: &Path,
+) -> SidToolResult<SidSkeletalAnimationAsset> {
+    let mut bones = vec![];
+    let mut slots = vec![];
+    let mut skipped = vec![];
+
+    for (kind, timelines) in animation.as_object().into_iter().flatten() {
+        if kind != "bones" && kind != "slots" {
+            skipped.push(kind.clone());
+            continue;
+        }
+
+        for (target, target_timelines) in timelines.as_object().into_iter().flatten() {
+            for (timeline, keys) in target_timelines.as_object().into_iter().flatten() {
+                let property = match (kind.as_str(), timeline.as_str()) {
+                    ("bones", "rotate") => Some(SidBoneTimelineProperty::Rotate),
+                    ("bones", "translate") => Some(SidBoneTimelineProperty::Translate),
+                    ("bones", "scale") => Some(SidBoneTimelineProperty::Scale),
+                    ("slots", "attachment") => {
+                        slots.push(read_slot_timeline(target, keys, path)?);
+                        continue;
+                    }
+                    _ => None,
+                };
+
+                match property {
+                    Some(property) => {
+                        bones.push(read_bone_timeline(target, property, keys, version, path)?)
+                    }
+                    None => skipped.push(format!("{timeline} of {target:?}")),
+                }
+            }
+        }
+    }
+
+    if !skipped.is_empty() {
+        sid_warning!(
+            "Skipping unsupported timelines of animation {:?} in {:?}: {}.",
+            name,
+            path,
+            skipped.join(", ")
+        );
+    }
+
+    // Spine animations last until their last key.
+    let duration = bones
+        .iter()
+        .flat_map(|timeline| timeline.keys.iter().map(|key| key.time))
+        .chain(
+            slots
+                .iter()
+                .flat_map(|timeline| timeline.keys.iter().map(|key| key.time)),
+        )
+        .fold(0.0, f32::max);
+
+    Ok(SidSkeletalAnimationAsset::with_data(
+        name.to_string(),
+        skeleton_name.to_string(),
+        duration,
+        bones,
+        slots,
+    ))
+}
+
+/// Converts the Spine skeleton at `path` into a skeleton asset named after the
+/// file, and a skeletal animation asset per animation, named after the
+/// skeleton and the animation, e.g. `hero_walk`. They are written to
+/// `subfolder` of their namespace folders. Returns the ids of the assets.
+pub fn from_spine_skeleton_to_sid_assets<P: AsRef<Path>>(
+    path: &Path,
+    names: &SidNameRules,
+    assets_output_path: P,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let contents = fs::read_to_string(path).map_err(|error| {
+        SidToolError::IO(format!("Unable to read Spine skeleton {:?}: {error}", path))
+    })?;
+
+    let json: SpineSkeleton =
+        serde_json::from_str(&contents).map_err(|error| malformed(path, error))?;
+
+    let version = json.skeleton.spine.as_str();
+
+    let name = path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| malformed(path, "invalid skeleton name"))
+        .map(|name| names.normalize(name))?;
+
+    let bones = json
+        .bones
+        .iter()
+        .map(|bone| {
+            SidSkeletonBoneAsset::with_data(
+                bone.name.clone(),
+                bone.parent.clone(),
+                bone.length,
+                SidBoneTransform {
+                    x: bone.x,
+                    y: bone.y,
+                    rotation: bone.rotation,
+                    scale_x: bone.scale_x,
+                    scale_y: bone.scale_y,
+                },
+            )
+        })
+        .collect();
+
+    let slots = json
+        .slots
+        .iter()
+        .map(|slot| {
+            let color = match &slot.color {
+                Some(color) => parse_color(color)
+                    .ok_or_else(|| malformed(path, format!("invalid color {color:?}")))?,
+                None => [255; 4],
+            };
+
+            Ok(SidSkeletonSlotAsset::with_data(
+                slot.name.clone(),
+                slot.bone.clone(),
+                slot.attachment.clone(),
+                color,
+            ))
+        })
+        .collect::<SidToolResult<_>>()?;
+
+    let attachments = read_attachments(&json, path)?;
+    let skeleton = SidSkeletonAsset::with_data(name, bones, slots, attachments);
+
+    let mut ids = vec![];
+    write_asset(&skeleton, names, &assets_output_path, subfolder, dry_run)?;
+    ids.push(skeleton.resource_id());
+
+    for (animation_name, animation) in &json.animations {
+        let animation_name = names.normalize(&format!("{}_{}", skeleton.name, animation_name));
+        let animation = read_animation(&animation_name, &skeleton.name, animation, version, path)?;
+
+        write_asset(&animation, names, &assets_output_path, subfolder, dry_run)?;
+        ids.push(animation.resource_id());
+    }
+
+    Ok(ids)
+}
@@ -0,0 +1,123 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Properties of an animation, written in the user data of its Aseprite tag as
+// `key=value` entries separated by semicolons or new lines, e.g.
+// "loop=false; speed_scale=1.5; events=footstep@1,land@3".
+
+use sid_asset_packer::asset::SidAnimationEvent;
+use sid_asset_packer::sid::SidAnimationFrameIndex;
+
+use std::str::FromStr;
+
+use crate::sid_warning;
+
+#[derive(Debug, Default, Clone)]
+pub struct AsepriteTagData {
+    /// Play the animation again once it ends.
+    pub looping: Option<bool>,
+    /// Default playback speed of the animation.
+    pub speed_scale: Option<f32>,
+    /// Events of the animation, at frames relative to the start of the tag.
+    pub events: Vec<SidAnimationEvent>,
+}
+
+impl AsepriteTagData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the user data `text` of the tag `tag_name`. Malformed entries
+    /// are skipped with a warning.
+    pub fn parse(tag_name: &str, text: &str) -> Self {
+        let mut data = Self::new();
+
+        for entry in text.split([';', '\n']) {
+            let entry = entry.trim();
+
+            if entry.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = entry.split_once('=') else {
+                sid_warning!(
+                    "Ignoring malformed user data entry {:?} of tag {:?}, expected key=value.",
+                    entry,
+                    tag_name
+                );
+                continue;
+            };
+
+            let value = value.trim();
+
+            match key.trim() {
+                "loop" => data.looping = parse_value(tag_name, "loop", value),
+                "speed_scale" => {
+                    data.speed_scale =
+                        parse_value(tag_name, "speed_scale", value).filter(|speed_scale: &f32| {
+                            let is_valid = speed_scale.is_finite() && *speed_scale > 0.0;
+
+                            if !is_valid {
+                                sid_warning!(
+                                    "Ignoring speed_scale {} of tag {:?}, it must be positive.",
+                                    speed_scale,
+                                    tag_name
+                                );
+                            }
+
+                            is_valid
+                        })
+                }
+                "events" => data.parse_events(tag_name, value),
+                key => sid_warning!(
+                    "Ignoring unknown user data key {:?} of tag {:?}.",
+                    key,
+                    tag_name
+                ),
+            }
+        }
+
+        data
+    }
+
+    // Events are written as `name@frame`, separated by commas.
+    fn parse_events(&mut self, tag_name: &str, value: &str) {
+        for event in value.split(',') {
+            let event = event.trim();
+
+            if event.is_empty() {
+                continue;
+            }
+
+            let parsed = event.rsplit_once('@').and_then(|(name, frame)| {
+                let frame = frame.trim().parse::<SidAnimationFrameIndex>().ok()?;
+                let name = name.trim();
+                (!name.is_empty()).then(|| SidAnimationEvent::with_data(frame, name.to_string()))
+            });
+
+            match parsed {
+                Some(event) => self.events.push(event),
+                None => sid_warning!(
+                    "Ignoring malformed event {:?} of tag {:?}, expected name@frame.",
+                    event,
+                    tag_name
+                ),
+            }
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(tag_name: &str, key: &str, value: &str) -> Option<T> {
+    let parsed = value.parse().ok();
+
+    if parsed.is_none() {
+        sid_warning!(
+            "Ignoring malformed {key} {:?} of tag {:?}.",
+            value,
+            tag_name
+        );
+    }
+
+    parsed
+}
@@ -0,0 +1,346 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Reader for the sheets packed by TexturePacker, in its JSON (array or hash)
+// and generic XML data formats. Frames are grouped into animations by name,
+// e.g. run_00.png to run_07.png into run.
+
+use roxmltree::{Document, Node};
+use serde::Deserialize;
+use serde_json::Value;
+
+use sid_asset_packer::portable_path;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::sid_aseprite::{AsepriteSheetError, AsepriteSheetResult};
+
+/// Name of the application TexturePacker writes in its JSON exports.
+static TEXTURE_PACKER_APP: &str = "texturepacker";
+
+/// Root element of the generic XML exports of TexturePacker.
+static TEXTURE_PACKER_XML_ROOT: &str = "TextureAtlas";
+
+/// Frame of a TexturePacker sheet. Rotated frames are stored rotated by 90
+/// degrees clockwise, `width` and `height` being their unrotated size.
+#[derive(Debug, Clone)]
+pub struct TexturePackerFrame {
+    pub name: String,
+    pub x: i16,
+    pub y: i16,
+    pub width: i16,
+    pub height: i16,
+    pub rotated: bool,
+    /// Position of the frame in its sprite, and size of the sprite before
+    /// trimming.
+    pub source_x: i16,
+    pub source_y: i16,
+    pub source_width: i16,
+    pub source_height: i16,
+    /// Pivot of the sprite, relative to its size, e.g. (0.5, 0.5) for its
+    /// center.
+    pub pivot: Option<(f32, f32)>,
+}
+
+impl TexturePackerFrame {
+    /// Whether the frame is smaller than its sprite.
+    pub fn is_trimmed(&self) -> bool {
+        (self.source_x, self.source_y) != (0, 0)
+            || (self.width, self.height) != (self.source_width, self.source_height)
+    }
+}
+
+/// Sheet packed by TexturePacker.
+#[derive(Debug, Clone)]
+pub struct TexturePackerSheet {
+    /// Image of the sheet, relative to the data file.
+    pub image: PathBuf,
+    pub width: i16,
+    pub height: i16,
+    pub format: String,
+    pub frames: Vec<TexturePackerFrame>,
+}
+
+#[derive(Deserialize)]
+struct TexturePackerJsonRect {
+    x: i16,
+    y: i16,
+    w: i16,
+    h: i16,
+}
+
+#[derive(Deserialize)]
+struct TexturePackerJsonSize {
+    w: i16,
+    h: i16,
+}
+
+#[derive(Deserialize)]
+struct TexturePackerJsonPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TexturePackerJsonFrame {
+    /// Only set in the array layout, the hash one keys frames by name.
+    #[serde(default)]
+    filename: String,
+    frame: TexturePackerJsonRect,
+    #[serde(default)]
+    rotated: bool,
+    sprite_source_size: TexturePackerJsonRect,
+    source_size: TexturePackerJsonSize,
+    pivot: Option<TexturePackerJsonPoint>,
+}
+
+impl TexturePackerJsonFrame {
+    fn into_frame(self, name: String) -> TexturePackerFrame {
+        TexturePackerFrame {
+            name,
+            x: self.frame.x,
+            y: self.frame.y,
+            width: self.frame.w,
+            height: self.frame.h,
+            rotated: self.rotated,
+            source_x: self.sprite_source_size.x,
+            source_y: self.sprite_source_size.y,
+            source_width: self.source_size.w,
+            source_height: self.source_size.h,
+            pivot: self.pivot.map(|pivot| (pivot.x, pivot.y)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TexturePackerJsonFrames {
+    Array(Vec<TexturePackerJsonFrame>),
+    Hash(BTreeMap<String, TexturePackerJsonFrame>),
+}
+
+#[derive(Deserialize)]
+struct TexturePackerJsonMeta {
+    /// Exports made on Windows may separate folders with backslashes.
+    #[serde(deserialize_with = "portable_path::deserialize")]
+    image: PathBuf,
+    #[serde(default)]
+    format: String,
+    size: TexturePackerJsonSize,
+}
+
+#[derive(Deserialize)]
+struct TexturePackerJsonSheet {
+    frames: TexturePackerJsonFrames,
+    meta: TexturePackerJsonMeta,
+}
+
+fn read_file(path: &Path) -> AsepriteSheetResult<String> {
+    fs::read_to_string(path)
+        .map_err(|_| AsepriteSheetError::IO(format!("Failed to read file {:?}", path)))
+}
+
+fn malformed(path: &Path, error: impl std::fmt::Display) -> AsepriteSheetError {
+    AsepriteSheetError::Malformed(format!("Invalid TexturePacker file {:?}: {error}", path))
+}
+
+/// Whether the JSON file at `path` was exported by TexturePacker rather than
+/// Aseprite.
+pub fn is_texture_packer_json(path: &Path) -> bool {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return false;
+    };
+
+    let Ok(json) = serde_json::from_str::<Value>(&contents) else {
+        return false;
+    };
+
+    json.pointer("/meta/app")
+        .and_then(Value::as_str)
+        .is_some_and(|app| app.to_lowercase().contains(TEXTURE_PACKER_APP))
+}
+
+/// Whether the XML file at `path` is a TexturePacker generic XML export.
+pub fn is_texture_packer_xml(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| {
+        Document::parse(&contents).is_ok_and(|document| {
+            document
+                .root_element()
+                .has_tag_name(TEXTURE_PACKER_XML_ROOT)
+        })
+    })
+}
+
+impl TexturePackerSheet {
+    /// Reads a TexturePacker JSON export, of the array or hash layout.
+    pub fn from_json(path: &Path) -> AsepriteSheetResult<Self> {
+        let contents = read_file(path)?;
+        let json: TexturePackerJsonSheet =
+            serde_json::from_str(&contents).map_err(|error| malformed(path, error))?;
+
+        let frames = match json.frames {
+            TexturePackerJsonFrames::Array(frames) => frames
+                .into_iter()
+                .map(|frame| {
+                    let name = frame.filename.clone();
+                    frame.into_frame(name)
+                })
+                .collect(),
+            TexturePackerJsonFrames::Hash(frames) => frames
+                .into_iter()
+                .map(|(name, frame)| frame.into_frame(name))
+                .collect(),
+        };
+
+        Ok(Self {
+            image: json.meta.image,
+            width: json.meta.size.w,
+            height: json.meta.size.h,
+            format: json.meta.format,
+            frames,
+        })
+    }
+
+    /// Reads a TexturePacker generic XML export. Sheets without a size take the
+    /// one of their image.
+    pub fn from_xml(path: &Path) -> AsepriteSheetResult<Self> {
+        let contents = read_file(path)?;
+        let document = Document::parse(&contents).map_err(|error| malformed(path, error))?;
+        let root = document.root_element();
+
+        if !root.has_tag_name(TEXTURE_PACKER_XML_ROOT) {
+            return Err(malformed(
+                path,
+                format!("expected <{TEXTURE_PACKER_XML_ROOT}> as root element"),
+            ));
+        }
+
+        let image = portable_path::from_portable(attribute(root, "imagePath", path)?);
+
+        let (width, height) = match (root.attribute("width"), root.attribute("height")) {
+            (Some(width), Some(height)) => (
+                parse_number(width, "width", path)?,
+                parse_number(height, "height", path)?,
+            ),
+            _ => {
+                let image_path = path.parent().unwrap_or(Path::new("")).join(&image);
+                let (width, height) = image::image_dimensions(&image_path).map_err(|error| {
+                    AsepriteSheetError::IO(format!(
+                        "Unable to read the size of image {:?}: {error}",
+                        image_path
+                    ))
+                })?;
+
+                match (i16::try_from(width), i16::try_from(height)) {
+                    (Ok(width), Ok(height)) => (width, height),
+                    _ => {
+                        return Err(malformed(
+                            path,
+                            format!("image too large: {width}x{height}"),
+                        ))
+                    }
+                }
+            }
+        };
+
+        let frames = root
+            .children()
+            .filter(|node| node.has_tag_name("sprite"))
+            .map(|node| read_xml_sprite(node, path))
+            .collect::<AsepriteSheetResult<_>>()?;
+
+        Ok(Self {
+            image,
+            width,
+            height,
+            format: root.attribute("format").unwrap_or_default().to_string(),
+            frames,
+        })
+    }
+}
+
+fn attribute<'a>(node: Node<'a, '_>, name: &str, path: &Path) -> AsepriteSheetResult<&'a str> {
+    node.attribute(name).ok_or_else(|| {
+        malformed(
+            path,
+            format!(
+                "missing {:?} attribute on <{}>",
+                name,
+                node.tag_name().name()
+            ),
+        )
+    })
+}
+
+fn parse_number<T: FromStr>(value: &str, name: &str, path: &Path) -> AsepriteSheetResult<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| malformed(path, format!("invalid {:?} value {:?}", name, value)))
+}
+
+fn optional_number_attribute<T: FromStr>(
+    node: Node,
+    name: &str,
+    path: &Path,
+) -> AsepriteSheetResult<Option<T>> {
+    node.attribute(name)
+        .map(|value| parse_number(value, name, path))
+        .transpose()
+}
+
+/// Reads a <sprite> element. Untrimmed sprites may leave out their offset and
+/// source size.
+fn read_xml_sprite(node: Node, path: &Path) -> AsepriteSheetResult<TexturePackerFrame> {
+    let width = parse_number(attribute(node, "w", path)?, "w", path)?;
+    let height = parse_number(attribute(node, "h", path)?, "h", path)?;
+
+    let pivot = match (
+        optional_number_attribute(node, "pX", path)?,
+        optional_number_attribute(node, "pY", path)?,
+    ) {
+        (Some(x), Some(y)) => Some((x, y)),
+        _ => None,
+    };
+
+    Ok(TexturePackerFrame {
+        name: attribute(node, "n", path)?.to_string(),
+        x: parse_number(attribute(node, "x", path)?, "x", path)?,
+        y: parse_number(attribute(node, "y", path)?, "y", path)?,
+        width,
+        height,
+        rotated: node.attribute("r") == Some("y"),
+        source_x: optional_number_attribute(node, "oX", path)?.unwrap_or(0),
+        source_y: optional_number_attribute(node, "oY", path)?.unwrap_or(0),
+        source_width: optional_number_attribute(node, "oW", path)?.unwrap_or(width),
+        source_height: optional_number_attribute(node, "oH", path)?.unwrap_or(height),
+        pivot,
+    })
+}
+
+/// Animation and number of the frame named `name`, e.g. `run` and 3 for
+/// `run_03.png`. Frames without a number are animations of their own, and
+/// numbered frames without a name, e.g. `hero/0001.png`, belong to
+/// `default_name`.
+pub fn split_frame_name(name: &str, default_name: &str) -> (String, Option<u64>) {
+    let stem = match name.rsplit_once('.') {
+        Some((stem, extension)) if extension.chars().all(|c| c.is_ascii_alphanumeric()) => stem,
+        _ => name,
+    };
+
+    let prefix = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    let number = stem[prefix.len()..].parse().ok();
+
+    let animation_name = match prefix.trim_end_matches(['_', '-', '.', ' ', '/']) {
+        "" => default_name.to_string(),
+        // Folders of the frame name are kept in the animation name.
+        prefix => prefix.replace(['/', '\\'], "_"),
+    };
+
+    (animation_name, number)
+}
@@ -0,0 +1,372 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Reader for Tiled maps (.tmx) and external tilesets (.tsx), following the TMX
+// map format documentation. Only orthogonal, finite maps are supported.
+
+use base64::Engine;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use roxmltree::{Document, Node};
+
+use sid_asset_packer::asset::{
+    SidSpriteSheetAsset, SidTilemapAsset, SidTilemapLayerAsset, SidTilemapTilesetAsset,
+    SidTilesetAsset,
+};
+use sid_asset_packer::error::{SidToolError, SidToolResult};
+use sid_asset_packer::naming::SidNameRules;
+use sid_asset_packer::resource::SerializeSidAsset;
+use sid_asset_packer::sid::{self, SidStringId, SidTileGid};
+
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::sid_aseprite::write_asset;
+use crate::sid_warning;
+
+fn malformed<E: fmt::Display>(path: &Path, error: E) -> SidToolError {
+    SidToolError::Malformed(format!("Invalid Tiled file {:?}: {error}", path))
+}
+
+fn attribute<'a>(node: Node<'a, '_>, name: &str, path: &Path) -> SidToolResult<&'a str> {
+    node.attribute(name).ok_or_else(|| {
+        malformed(
+            path,
+            format!(
+                "missing {:?} attribute on <{}>",
+                name,
+                node.tag_name().name()
+            ),
+        )
+    })
+}
+
+fn parse_number<T: FromStr>(value: &str, name: &str, path: &Path) -> SidToolResult<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| malformed(path, format!("invalid {:?} value {:?}", name, value)))
+}
+
+fn number_attribute<T: FromStr>(node: Node, name: &str, path: &Path) -> SidToolResult<T> {
+    parse_number(attribute(node, name, path)?, name, path)
+}
+
+fn optional_number_attribute<T: FromStr>(
+    node: Node,
+    name: &str,
+    path: &Path,
+    default: T,
+) -> SidToolResult<T> {
+    match node.attribute(name) {
+        Some(value) => parse_number(value, name, path),
+        None => Ok(default),
+    }
+}
+
+fn read_document(path: &Path) -> SidToolResult<String> {
+    fs::read_to_string(path)
+        .map_err(|error| SidToolError::IO(format!("Unable to read Tiled file {:?}: {error}", path)))
+}
+
+/// Reads a <tileset> element, from a map or a .tsx file in `folder`, along with
+/// the sprite sheet of its image.
+fn read_tileset(
+    node: Node,
+    folder: &Path,
+    path: &Path,
+) -> SidToolResult<(SidTilesetAsset, SidSpriteSheetAsset)> {
+    let name = attribute(node, "name", path)?.to_string();
+
+    let image = node
+        .children()
+        .find(|child| child.has_tag_name("image"))
+        .ok_or_else(|| {
+            SidToolError::UnsupportedFormat(format!(
+                "Tileset {:?} of {:?} has no image, image collections are not supported",
+                name, path
+            ))
+        })?;
+
+    let image_from_path = folder.join(attribute(image, "source", path)?);
+
+    let image_name = image_from_path
+        .file_name()
+        .map(PathBuf::from)
+        .ok_or_else(|| malformed(path, format!("invalid image path {:?}", image_from_path)))?;
+
+    let (width, height) = match (image.attribute("width"), image.attribute("height")) {
+        (Some(width), Some(height)) => (
+            parse_number(width, "width", path)?,
+            parse_number(height, "height", path)?,
+        ),
+        _ => image::image_dimensions(&image_from_path).map_err(|error| {
+            SidToolError::IO(format!(
+                "Unable to read image {:?}: {error}",
+                image_from_path
+            ))
+        })?,
+    };
+
+    let sheet = SidSpriteSheetAsset::with_data(
+        name.clone(),
+        image_name,
+        image_from_path,
+        width,
+        height,
+        "RGBA8888".to_string(),
+    );
+
+    let mut tileset = SidTilesetAsset::with_data(
+        name.clone(),
+        name,
+        number_attribute(node, "tilewidth", path)?,
+        number_attribute(node, "tileheight", path)?,
+        number_attribute(node, "tilecount", path)?,
+        number_attribute(node, "columns", path)?,
+    );
+
+    tileset.spacing = optional_number_attribute(node, "spacing", path, 0)?;
+    tileset.margin = optional_number_attribute(node, "margin", path, 0)?;
+
+    Ok((tileset, sheet))
+}
+
+/// Reads a <tileset> element of a map, which may reference a .tsx file.
+fn load_tileset(
+    node: Node,
+    map_folder: &Path,
+    map_path: &Path,
+) -> SidToolResult<(SidTileGid, SidTilesetAsset, SidSpriteSheetAsset)> {
+    let first_gid = number_attribute(node, "firstgid", map_path)?;
+
+    let (tileset, sheet) = match node.attribute("source") {
+        Some(source) => {
+            let tileset_path = map_folder.join(source);
+            let text = read_document(&tileset_path)?;
+            let document =
+                Document::parse(&text).map_err(|error| malformed(&tileset_path, error))?;
+            let root = document.root_element();
+
+            if !root.has_tag_name("tileset") {
+                return Err(malformed(
+                    &tileset_path,
+                    "expected a <tileset> root element",
+                ));
+            }
+
+            let tileset_folder = tileset_path.parent().unwrap_or(map_folder).to_path_buf();
+            read_tileset(root, &tileset_folder, &tileset_path)?
+        }
+        None => read_tileset(node, map_folder, map_path)?,
+    };
+
+    Ok((first_gid, tileset, sheet))
+}
+
+fn decompress(data: Vec<u8>, compression: Option<&str>, path: &Path) -> SidToolResult<Vec<u8>> {
+    let mut out = vec![];
+
+    let result = match compression {
+        None => return Ok(data),
+        Some("zlib") => ZlibDecoder::new(data.as_slice()).read_to_end(&mut out),
+        Some("gzip") => GzDecoder::new(data.as_slice()).read_to_end(&mut out),
+        Some("zstd") => zstd::stream::read::Decoder::new(data.as_slice())
+            .and_then(|mut decoder| decoder.read_to_end(&mut out)),
+        Some(compression) => {
+            return Err(SidToolError::UnsupportedFormat(format!(
+                "Unsupported layer compression {:?} in {:?}",
+                compression, path
+            )))
+        }
+    };
+
+    result.map_err(|error| malformed(path, format!("unable to decompress layer: {error}")))?;
+    Ok(out)
+}
+
+/// Reads the global tile ids of a layer <data> element, whatever its encoding.
+fn read_layer_data(data: Node, tile_count: usize, path: &Path) -> SidToolResult<Vec<SidTileGid>> {
+    if data.children().any(|child| child.has_tag_name("chunk")) {
+        return Err(SidToolError::UnsupportedFormat(format!(
+            "Infinite Tiled maps are not supported: {:?}",
+            path
+        )));
+    }
+
+    let text = data.text().unwrap_or_default();
+
+    let tiles: Vec<SidTileGid> = match data.attribute("encoding") {
+        None => data
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .map(|tile| optional_number_attribute(tile, "gid", path, 0))
+            .collect::<SidToolResult<_>>()?,
+        Some("csv") => text
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| parse_number(value, "gid", path))
+            .collect::<SidToolResult<_>>()?,
+        Some("base64") => {
+            let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .map_err(|error| malformed(path, format!("invalid base64 layer: {error}")))?;
+
+            decompress(bytes, data.attribute("compression"), path)?
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes(gid.try_into().unwrap()))
+                .collect()
+        }
+        Some(encoding) => {
+            return Err(SidToolError::UnsupportedFormat(format!(
+                "Unsupported layer encoding {:?} in {:?}",
+                encoding, path
+            )))
+        }
+    };
+
+    if tiles.len() != tile_count {
+        return Err(malformed(
+            path,
+            format!("layer has {} tile(s), expected {tile_count}", tiles.len()),
+        ));
+    }
+
+    Ok(tiles)
+}
+
+/// Collects the tile layers of `parent`, flattening groups. A layer is only
+/// visible if all its groups are.
+fn read_layers(
+    parent: Node,
+    visible: bool,
+    tile_count: usize,
+    path: &Path,
+    layers: &mut Vec<SidTilemapLayerAsset>,
+) -> SidToolResult<()> {
+    for node in parent.children().filter(Node::is_element) {
+        let is_visible = visible && node.attribute("visible") != Some("0");
+        let name = node.attribute("name").unwrap_or_default();
+
+        match node.tag_name().name() {
+            "layer" => {
+                let data = node
+                    .children()
+                    .find(|child| child.has_tag_name("data"))
+                    .ok_or_else(|| malformed(path, format!("layer {:?} has no data", name)))?;
+
+                let tiles = read_layer_data(data, tile_count, path)?;
+                layers.push(SidTilemapLayerAsset::with_data(
+                    name.to_string(),
+                    is_visible,
+                    tiles,
+                ));
+            }
+            "group" => read_layers(node, is_visible, tile_count, path, layers)?,
+            tag @ ("objectgroup" | "imagelayer") => sid_warning!(
+                "Ignoring {tag} {:?} of {:?}, only tile layers are converted.",
+                name,
+                path
+            ),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a Tiled map into a tilemap asset, along with a tileset and a sprite
+/// sheet asset for each of its tilesets, written to `subfolder` of their
+/// namespace folders. Returns the ids of every written asset, which are only
+/// logged on dry runs.
+pub fn from_tiled_map_to_sid_assets<P1: AsRef<Path>, P2: AsRef<Path>>(
+    map_path: P1,
+    names: &SidNameRules,
+    assets_output_path: P2,
+    subfolder: &Path,
+    dry_run: bool,
+) -> SidToolResult<Vec<SidStringId>> {
+    let map_path = map_path.as_ref();
+    let assets_output_path = assets_output_path.as_ref();
+    let map_folder = map_path.parent().unwrap_or(Path::new("."));
+
+    let text = read_document(map_path)?;
+    let document = Document::parse(&text).map_err(|error| malformed(map_path, error))?;
+    let map = document.root_element();
+
+    if !map.has_tag_name("map") {
+        return Err(malformed(map_path, "expected a <map> root element"));
+    }
+
+    let orientation = map.attribute("orientation").unwrap_or("orthogonal");
+
+    if orientation != "orthogonal" {
+        return Err(SidToolError::UnsupportedFormat(format!(
+            "Unsupported {orientation} orientation of Tiled map {:?}",
+            map_path
+        )));
+    }
+
+    if map.attribute("infinite") == Some("1") {
+        return Err(SidToolError::UnsupportedFormat(format!(
+            "Infinite Tiled maps are not supported: {:?}",
+            map_path
+        )));
+    }
+
+    let name = map_path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| malformed(map_path, "invalid map name"))
+        .map(|name| names.normalize(name))?;
+
+    let width: u32 = number_attribute(map, "width", map_path)?;
+    let height: u32 = number_attribute(map, "height", map_path)?;
+    let tile_width: sid::SidTileDim = number_attribute(map, "tilewidth", map_path)?;
+    let tile_height: sid::SidTileDim = number_attribute(map, "tileheight", map_path)?;
+
+    let mut ids = vec![];
+    let mut tilesets = vec![];
+
+    for node in map.children().filter(|child| child.has_tag_name("tileset")) {
+        let (first_gid, mut tileset, mut sheet) = load_tileset(node, map_folder, map_path)?;
+
+        // Tilesets play the sheet of the same name.
+        tileset.name = names.normalize(&tileset.name);
+        tileset.sheet_name = tileset.name.clone();
+        sheet.name = tileset.name.clone();
+
+        write_asset(&sheet, names, assets_output_path, subfolder, dry_run)?;
+        ids.push(sheet.resource_id());
+
+        write_asset(&tileset, names, assets_output_path, subfolder, dry_run)?;
+        ids.push(tileset.resource_id());
+
+        tilesets.push(SidTilemapTilesetAsset::with_data(first_gid, tileset.name));
+    }
+
+    let mut layers = vec![];
+    let tile_count = width as usize * height as usize;
+    read_layers(map, true, tile_count, map_path, &mut layers)?;
+
+    let tilemap = SidTilemapAsset::with_data(
+        name,
+        width,
+        height,
+        tile_width,
+        tile_height,
+        tilesets,
+        layers,
+    );
+
+    write_asset(&tilemap, names, assets_output_path, subfolder, dry_run)?;
+    ids.push(tilemap.resource_id());
+
+    Ok(ids)
+}
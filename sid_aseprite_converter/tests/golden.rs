@@ -0,0 +1,159 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Converts the Aseprite exports of tests/fixtures/sheets, packs them with the
+// hand-written assets of tests/fixtures/assets, and compares the assets and
+// the resources produced with the ones of tests/fixtures/golden, byte for
+// byte. Ids come from the test backend, so that no sid_lib is needed.
+//
+// Run with SID_UPDATE_GOLDEN=1 to write the produced files as the new golden
+// ones, then review their diff.
+
+use sid_aseprite_converter::sid_aseprite;
+use sid_asset_packer::{
+    report::SidReport,
+    resource::{self, read, SidPackOptions},
+    sid::{self, SidTestBackend},
+};
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+fn fixtures_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Contents of every file under `root`, by path relative to it.
+fn read_tree(root: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    let mut files = BTreeMap::new();
+    let mut folders = vec![root.to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        let Ok(entries) = fs::read_dir(&folder) else {
+            continue;
+        };
+
+        for entry in entries {
+            let path = entry.expect("Unable to read folder entry").path();
+
+            if path.is_dir() {
+                folders.push(path);
+                continue;
+            }
+
+            let contents = fs::read(&path).expect("Unable to read file");
+            files.insert(path.strip_prefix(root).unwrap().to_path_buf(), contents);
+        }
+    }
+
+    files
+}
+
+fn update_golden(produced_path: &Path, golden_path: &Path) {
+    let _ = fs::remove_dir_all(golden_path);
+
+    for (path, contents) in read_tree(produced_path) {
+        let path = golden_path.join(path);
+        fs::create_dir_all(path.parent().unwrap()).expect("Unable to create golden folder");
+        fs::write(&path, contents).expect("Unable to write golden file");
+    }
+}
+
+fn assert_matches_golden(produced_path: &Path, golden_path: &Path) {
+    if env::var_os("SID_UPDATE_GOLDEN").is_some() {
+        update_golden(produced_path, golden_path);
+        return;
+    }
+
+    let produced = read_tree(produced_path);
+    let golden = read_tree(golden_path);
+
+    let produced_paths: Vec<&PathBuf> = produced.keys().collect();
+    let golden_paths: Vec<&PathBuf> = golden.keys().collect();
+    assert_eq!(
+        produced_paths, golden_paths,
+        "Files of {:?} differ from the golden ones",
+        produced_path
+    );
+
+    for (path, contents) in &produced {
+        assert!(
+            contents == &golden[path],
+            "{:?} differs from its golden file, run with SID_UPDATE_GOLDEN=1 to update it",
+            path
+        );
+    }
+}
+
+#[test]
+fn sheets_and_assets_pack_to_golden_resources() {
+    // The golden resources hold 32-bit ids.
+    if sid::STRING_ID_BITS != 32 {
+        return;
+    }
+
+    sid::set_backend(Arc::new(SidTestBackend::new()));
+
+    let fixtures_path = fixtures_path();
+    let out = tempfile::tempdir().expect("Unable to create temporary folder");
+    let assets_path = out.path().join("assets");
+    let resources_path = out.path().join("resources");
+
+    sid_aseprite::from_aseprite_sheets_to_sid_assets(
+        fixtures_path.join("sheets"),
+        assets_path.clone(),
+    )
+    .expect("Unable to convert the sheets");
+
+    let mut options = SidPackOptions::new();
+    options.deterministic = true;
+    options.jobs = 1;
+
+    resource::from_asset_folders_to_resources_with_report(
+        &[assets_path.clone(), fixtures_path.join("assets")],
+        &resources_path,
+        &options,
+        &mut SidReport::new(),
+    )
+    .expect("Unable to pack the assets");
+
+    let golden_path = fixtures_path.join("golden");
+    assert_matches_golden(&assets_path, &golden_path.join("assets"));
+    assert_matches_golden(&resources_path, &golden_path.join("resources"));
+}
+
+#[test]
+fn golden_resources_read_back() {
+    if sid::STRING_ID_BITS != 32 {
+        return;
+    }
+
+    sid::set_backend(Arc::new(SidTestBackend::new()));
+
+    let resources_path = fixtures_path().join("golden/resources");
+    let read_resource = |id: sid::SidStringId| {
+        fs::read(resources_path.join(id.to_string())).expect("Unable to read golden resource")
+    };
+
+    let sheet_id = sid::generate_sprite_sheet_id("hero");
+    let sheet = read::read_sprite_sheet(&read_resource(sheet_id)).expect("Invalid sprite sheet");
+    assert_eq!(sheet.id, sheet_id);
+    assert_eq!((sheet.image.width, sheet.image.height), (4, 2));
+
+    let def_id = sid::generate_animation_def_id("hero");
+    let def = read::read_animation_def(&read_resource(def_id)).expect("Invalid animation def");
+    assert_eq!(def.sheet_id, sheet_id);
+    assert_eq!(def.frames.len(), 2);
+
+    let animation_id = sid::generate_animation_id("hero_idle");
+    let animation = read::read_animation(&read_resource(animation_id)).expect("Invalid animation");
+    assert_eq!(animation.def_id, def_id);
+    assert_eq!((animation.offset, animation.length), (0, 2));
+
+    assert!(read::read_animation(&read_resource(def_id)).is_err());
+}
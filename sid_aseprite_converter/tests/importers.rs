@@ -0,0 +1,819 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Builds small files of each format the converter imports, and checks what it
+// reads from them, or the assets it converts them into, read back from their
+// JSON files.
+
+use base64::Engine;
+use flate2::{write::ZlibEncoder, Compression};
+use image::{codecs::gif::GifEncoder, Delay, Frame, Rgba, RgbaImage};
+use serde::de::DeserializeOwned;
+
+use sid_aseprite_converter::{
+    aseprite_file::{AsepriteColorDepth, AsepriteFile},
+    gif, image_sequence, sid_aseprite,
+    sidecar::AsepriteSidecar,
+    tiled,
+};
+use sid_asset_packer::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidBitmapFontAsset, SidBoneTimelineProperty,
+        SidKeyCurve, SidMeshAsset, SidMeshTexture, SidPackedAsset, SidSkeletalAnimationAsset,
+        SidSkeletonAsset, SidSlicePivot, SidSpriteSheetAsset, SidTextureAsset, SidTilemapAsset,
+        SidTilesetAsset,
+    },
+    naming::SidNameRules,
+};
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Asset `name` written to `assets_path`, read back from its JSON file.
+fn read_asset<A: SidPackedAsset + DeserializeOwned>(assets_path: &Path, name: &str) -> A {
+    let path = assets_path
+        .join(A::namespace())
+        .join(format!("{name}.json"));
+
+    let contents = fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("Unable to read {:?}: {error}", path));
+    serde_json::from_str(&contents)
+        .unwrap_or_else(|error| panic!("Invalid asset {:?}: {error}", path))
+}
+
+/// Writes `contents` to `name` in `folder` and returns its path.
+fn write_file(folder: &Path, name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+    let path = folder.join(name);
+    fs::write(&path, contents).expect("Unable to write file");
+    path
+}
+
+/// Saves a blank image of `width` by `height` as `name` in `folder`.
+fn save_image(folder: &Path, name: &str, width: u32, height: u32) {
+    RgbaImage::new(width, height)
+        .save(folder.join(name))
+        .expect("Unable to save the image");
+}
+
+/// Chunk of an Aseprite frame, of type `chunk_type`.
+fn aseprite_chunk(chunk_type: u16, data: &[u8]) -> Vec<u8> {
+    let mut chunk = ((data.len() + 6) as u32).to_le_bytes().to_vec();
+    chunk.extend(chunk_type.to_le_bytes());
+    chunk.extend(data);
+    chunk
+}
+
+fn aseprite_string(text: &str) -> Vec<u8> {
+    let mut bytes = (text.len() as u16).to_le_bytes().to_vec();
+    bytes.extend(text.as_bytes());
+    bytes
+}
+
+/// Aseprite file of `width` by `height` RGBA pixels, of a frame per duration
+/// and chunks.
+fn aseprite_file(width: u16, height: u16, frames: &[(u16, Vec<Vec<u8>>)]) -> Vec<u8> {
+    let mut data = vec![0; 128];
+    data[4..6].copy_from_slice(&0xA5E0u16.to_le_bytes());
+    data[6..8].copy_from_slice(&(frames.len() as u16).to_le_bytes());
+    data[8..10].copy_from_slice(&width.to_le_bytes());
+    data[10..12].copy_from_slice(&height.to_le_bytes());
+    data[12..14].copy_from_slice(&32u16.to_le_bytes());
+    // Layer opacity is valid.
+    data[14..18].copy_from_slice(&1u32.to_le_bytes());
+
+    for (duration, chunks) in frames {
+        let chunks_data: Vec<u8> = chunks.concat();
+        data.extend(((chunks_data.len() + 16) as u32).to_le_bytes());
+        data.extend(0xF1FAu16.to_le_bytes());
+        data.extend((chunks.len() as u16).to_le_bytes());
+        data.extend(duration.to_le_bytes());
+        data.extend([0; 2]);
+        data.extend((chunks.len() as u32).to_le_bytes());
+        data.extend(chunks_data);
+    }
+
+    let size = data.len() as u32;
+    data[..4].copy_from_slice(&size.to_le_bytes());
+    data
+}
+
+#[test]
+fn aseprite_files_read_layers_cels_tags_and_slices() {
+    let red = [255, 0, 0, 255];
+    let blue = [0, 0, 255, 255];
+
+    let mut layer = vec![];
+    layer.extend(1u16.to_le_bytes()); // Visible.
+    layer.extend([0; 2 + 2 + 4 + 2]); // Image layer at the root, normal blend.
+    layer.push(255);
+    layer.extend([0; 3]);
+    layer.extend(aseprite_string("body"));
+
+    let mut raw_cel = vec![0; 2 + 2 + 2];
+    raw_cel.push(255);
+    raw_cel.extend([0; 2 + 2 + 5]); // Raw cel.
+    raw_cel.extend(2u16.to_le_bytes());
+    raw_cel.extend(1u16.to_le_bytes());
+    raw_cel.extend(red);
+    raw_cel.extend(blue);
+
+    let mut tags = 1u16.to_le_bytes().to_vec();
+    tags.extend([0; 8]);
+    tags.extend(0u16.to_le_bytes());
+    tags.extend(1u16.to_le_bytes());
+    tags.push(2); // Ping-pong.
+    tags.extend([0; 12]);
+    tags.extend(aseprite_string("idle"));
+
+    let mut user_data = 1u32.to_le_bytes().to_vec();
+    user_data.extend(aseprite_string("loop=false"));
+
+    let mut slice = 1u32.to_le_bytes().to_vec();
+    slice.extend(2u32.to_le_bytes()); // Pivot.
+    slice.extend([0; 4]);
+    slice.extend(aseprite_string("hitbox"));
+    slice.extend(0u32.to_le_bytes());
+    slice.extend(0i32.to_le_bytes());
+    slice.extend(0i32.to_le_bytes());
+    slice.extend(2u32.to_le_bytes());
+    slice.extend(1u32.to_le_bytes());
+    slice.extend(1i32.to_le_bytes());
+    slice.extend(1i32.to_le_bytes());
+
+    // The cel of the second frame is the one of the first, moved right.
+    let mut linked_cel = vec![0; 2];
+    linked_cel.extend(1i16.to_le_bytes());
+    linked_cel.extend(0i16.to_le_bytes());
+    linked_cel.push(255);
+    linked_cel.extend(1u16.to_le_bytes());
+    linked_cel.extend([0; 2 + 5]);
+    linked_cel.extend(0u16.to_le_bytes());
+
+    let data = aseprite_file(
+        2,
+        1,
+        &[
+            (
+                100,
+                vec![
+                    aseprite_chunk(0x2004, &layer),
+                    aseprite_chunk(0x2005, &raw_cel),
+                    aseprite_chunk(0x2018, &tags),
+                    aseprite_chunk(0x2020, &user_data),
+                    aseprite_chunk(0x2022, &slice),
+                ],
+            ),
+            (150, vec![aseprite_chunk(0x2005, &linked_cel)]),
+        ],
+    );
+
+    let file = AsepriteFile::from_bytes(&data).expect("Unable to read the Aseprite file");
+    assert_eq!((file.width, file.height), (2, 1));
+    assert_eq!(file.color_depth, AsepriteColorDepth::Rgba);
+
+    let [layer] = &file.layers[..] else {
+        panic!("expected a layer");
+    };
+    assert_eq!(layer.name, "body");
+    assert!(layer.visible);
+    assert_eq!(layer.opacity, 255);
+
+    let durations: Vec<_> = file.frames.iter().map(|frame| frame.duration).collect();
+    assert_eq!(durations, [100, 150]);
+
+    let linked = &file.frames[1].cels[0];
+    assert_eq!((linked.x, linked.y), (1, 0));
+    assert_eq!(linked.pixels, file.frames[0].cels[0].pixels);
+
+    let tag = &file.tags[0];
+    assert_eq!((tag.name.as_str(), tag.from, tag.to), ("idle", 0, 1));
+    assert_eq!((tag.direction, tag.user_data.as_str()), (2, "loop=false"));
+
+    let key = &file.slices[0].keys[0];
+    assert_eq!(file.slices[0].name, "hitbox");
+    assert_eq!((key.x, key.y, key.width, key.height), (0, 0, 2, 1));
+    assert_eq!((key.center, key.pivot), (None, Some((1, 1))));
+
+    let first = file.render_frame(0, &[true]);
+    assert_eq!(first.as_raw(), &[red, blue].concat());
+
+    // The blue pixel of the moved cel falls out of the canvas.
+    let second = file.render_frame(1, &[true]);
+    assert_eq!(second.as_raw(), &[[0; 4], red].concat());
+}
+
+#[test]
+fn aseprite_files_of_invalid_magic_are_rejected() {
+    let mut data = aseprite_file(1, 1, &[]);
+    data[4] = 0;
+
+    assert!(AsepriteFile::from_bytes(&data).is_err());
+    assert!(AsepriteFile::from_bytes(&data[..64]).is_err());
+}
+
+/// Global tile ids of a layer, as the base64 of their zlib-compressed bytes.
+fn compressed_layer(gids: &[u32]) -> String {
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+
+    for gid in gids {
+        encoder.write_all(&gid.to_le_bytes()).unwrap();
+    }
+
+    base64::engine::general_purpose::STANDARD.encode(encoder.finish().unwrap())
+}
+
+#[test]
+fn tiled_maps_convert_with_their_tilesets_and_layers() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    save_image(folder.path(), "terrain.png", 32, 16);
+    save_image(folder.path(), "props.png", 33, 16);
+
+    write_file(
+        folder.path(),
+        "props.tsx",
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset name="props" tilewidth="16" tileheight="16" spacing="1" tilecount="2" columns="2">
+    <image source="props.png" width="33" height="16"/>
+</tileset>"#,
+    );
+
+    let path = write_file(
+        folder.path(),
+        "cave.tmx",
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+    <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="2" columns="2">
+        <image source="terrain.png" width="32" height="16"/>
+    </tileset>
+    <tileset firstgid="3" source="props.tsx"/>
+    <layer name="ground" width="2" height="2">
+        <data encoding="csv">
+1,2,
+2,1
+        </data>
+    </layer>
+    <group name="hidden" visible="0">
+        <layer name="decor" width="2" height="2">
+            <data encoding="base64" compression="zlib">{}</data>
+        </layer>
+    </group>
+    <objectgroup name="spawns"/>
+</map>"#,
+            compressed_layer(&[0, 3, 0, 4])
+        ),
+    );
+
+    let ids = tiled::from_tiled_map_to_sid_assets(
+        &path,
+        &SidNameRules::new(),
+        &assets_path,
+        Path::new(""),
+        false,
+    )
+    .expect("Unable to convert the map");
+    assert_eq!(ids.len(), 5);
+
+    let tilemap: SidTilemapAsset = read_asset(&assets_path, "cave");
+    assert_eq!((tilemap.width, tilemap.height), (2, 2));
+    assert_eq!((tilemap.tile_width, tilemap.tile_height), (16, 16));
+
+    let tilesets: Vec<_> = tilemap
+        .tilesets
+        .iter()
+        .map(|tileset| (tileset.first_gid, tileset.tileset_name.as_str()))
+        .collect();
+    assert_eq!(tilesets, [(1, "terrain"), (3, "props")]);
+
+    let [ground, decor] = &tilemap.layers[..] else {
+        panic!("expected two layers");
+    };
+    assert_eq!((ground.name.as_str(), ground.visible), ("ground", true));
+    assert_eq!(ground.tiles, [1, 2, 2, 1]);
+    assert_eq!((decor.name.as_str(), decor.visible), ("decor", false));
+    assert_eq!(decor.tiles, [0, 3, 0, 4]);
+
+    let props: SidTilesetAsset = read_asset(&assets_path, "props");
+    assert_eq!(props.sheet_name, "props");
+    assert_eq!((props.tile_count, props.columns), (2, 2));
+    assert_eq!((props.spacing, props.margin), (1, 0));
+
+    let sheet: SidSpriteSheetAsset = read_asset(&assets_path, "props");
+    assert_eq!((sheet.width, sheet.height), (33, 16));
+}
+
+#[test]
+fn tiled_layers_of_the_wrong_size_are_rejected() {
+    let folder = tempfile::tempdir().unwrap();
+    save_image(folder.path(), "terrain.png", 16, 16);
+
+    let path = write_file(
+        folder.path(),
+        "short.tmx",
+        r#"<map width="2" height="2" tilewidth="16" tileheight="16">
+    <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="1" columns="1">
+        <image source="terrain.png" width="16" height="16"/>
+    </tileset>
+    <layer name="ground"><data encoding="csv">1,1,1</data></layer>
+</map>"#,
+    );
+
+    assert!(tiled::from_tiled_map_to_sid_assets(
+        &path,
+        &SidNameRules::new(),
+        folder.path().join("assets"),
+        Path::new(""),
+        true
+    )
+    .is_err());
+}
+
+#[test]
+fn image_sequences_pack_into_a_sheet_and_an_animation() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let red = Rgba([255, 0, 0, 255]);
+    let blue = Rgba([0, 0, 255, 255]);
+
+    // Numbers are not padded, run_10.png comes after run_9.png.
+    for (name, color) in [
+        ("run_9.png", red),
+        ("run_10.png", blue),
+        ("run_11.png", red),
+    ] {
+        RgbaImage::from_pixel(2, 3, color)
+            .save(folder.path().join(name))
+            .expect("Unable to save the image");
+    }
+
+    let paths = ["run_10.png", "run_11.png", "run_9.png", "idle.png"]
+        .map(|name| folder.path().join(name))
+        .into_iter()
+        .filter(|path| image_sequence::is_sequence_frame(path))
+        .collect();
+
+    let [sequence] = &image_sequence::group_sequences(paths)[..] else {
+        panic!("expected a sequence");
+    };
+    assert_eq!(sequence.name, "run");
+    assert_eq!(sequence.frame_paths[0], folder.path().join("run_9.png"));
+
+    let sidecar = AsepriteSidecar {
+        frame_duration: Some(80),
+        dedup_frames: Some(true),
+        looping: Some(false),
+        ..Default::default()
+    };
+
+    let ids = image_sequence::from_image_sequence_to_sid_assets(
+        sequence,
+        &sidecar,
+        &SidNameRules::new(),
+        folder.path().join("images"),
+        &assets_path,
+        Path::new(""),
+        false,
+    )
+    .expect("Unable to convert the sequence");
+    assert_eq!(ids.len(), 3);
+
+    let sheet: SidSpriteSheetAsset = read_asset(&assets_path, "run");
+    assert!(folder.path().join("images").join("run.png").is_file());
+    // The last frame shows the pixels of the first one, and shares its region.
+    assert_eq!((sheet.width, sheet.height), (5, 3));
+
+    let def: SidAnimationDefAsset = read_asset(&assets_path, "run");
+    assert_eq!((def.frame_count, def.sheet_name.as_str()), (3, "run"));
+    assert!(def.frames.iter().all(|frame| frame.duration == 80));
+    assert_eq!(def.frames[0].pos, def.frames[2].pos);
+    assert_ne!(def.frames[0].pos, def.frames[1].pos);
+
+    let animation: SidAnimationAsset = read_asset(&assets_path, "run");
+    assert_eq!((animation.offset, animation.length), (0, 3));
+    assert!(!animation.looping);
+}
+
+#[test]
+fn gifs_convert_with_the_durations_of_their_delays() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let path = folder.path().join("blink.gif");
+
+    let mut encoder = GifEncoder::new(fs::File::create(&path).unwrap());
+
+    for (color, delay) in [([255, 0, 0, 255], 50), ([0, 0, 255, 255], 0)] {
+        let image = RgbaImage::from_pixel(3, 2, Rgba(color));
+        let delay = Delay::from_numer_denom_ms(delay, 1);
+        encoder
+            .encode_frame(Frame::from_parts(image, 0, 0, delay))
+            .expect("Unable to encode the frame");
+    }
+
+    drop(encoder);
+    assert!(gif::is_gif(&path));
+
+    let ids = gif::from_gif_to_sid_assets(
+        &path,
+        &AsepriteSidecar::default(),
+        &SidNameRules::new(),
+        folder.path().join("images"),
+        &assets_path,
+        Path::new(""),
+        false,
+    )
+    .expect("Unable to convert the GIF");
+    assert_eq!(ids.len(), 3);
+
+    let sheet: SidSpriteSheetAsset = read_asset(&assets_path, "blink");
+    assert_eq!((sheet.width, sheet.height), (7, 2));
+
+    // Delays of zero play at the default duration.
+    let def: SidAnimationDefAsset = read_asset(&assets_path, "blink");
+    let durations: Vec<_> = def.frames.iter().map(|frame| frame.duration).collect();
+    assert_eq!(durations, [50, 100]);
+
+    let animation: SidAnimationAsset = read_asset(&assets_path, "blink");
+    assert_eq!((animation.offset, animation.length), (0, 2));
+}
+
+#[test]
+fn texture_packer_sheets_convert_with_an_animation_per_frame_name() {
+    let folder = tempfile::tempdir().unwrap();
+    let sheets_path = folder.path().join("sheets");
+    let assets_path = folder.path().join("assets");
+    fs::create_dir(&sheets_path).unwrap();
+    save_image(&sheets_path, "ui.png", 64, 32);
+    save_image(&sheets_path, "items.png", 32, 16);
+
+    // Hash layout, whose frames are keyed by name.
+    write_file(
+        &sheets_path,
+        "ui.json",
+        r#"{
+    "frames": {
+        "run_10.png": {
+            "frame": {"x": 16, "y": 0, "w": 16, "h": 16},
+            "rotated": false,
+            "trimmed": false,
+            "spriteSourceSize": {"x": 0, "y": 0, "w": 16, "h": 16},
+            "sourceSize": {"w": 16, "h": 16}
+        },
+        "run_9.png": {
+            "frame": {"x": 0, "y": 0, "w": 16, "h": 16},
+            "rotated": false,
+            "trimmed": false,
+            "spriteSourceSize": {"x": 0, "y": 0, "w": 16, "h": 16},
+            "sourceSize": {"w": 16, "h": 16}
+        },
+        "idle.png": {
+            "frame": {"x": 32, "y": 0, "w": 8, "h": 12},
+            "rotated": true,
+            "trimmed": true,
+            "spriteSourceSize": {"x": 4, "y": 2, "w": 8, "h": 12},
+            "sourceSize": {"w": 16, "h": 16},
+            "pivot": {"x": 0.5, "y": 1}
+        }
+    },
+    "meta": {
+        "app": "https://www.codeandweb.com/texturepacker",
+        "version": "1.0",
+        "image": "ui.png",
+        "format": "RGBA8888",
+        "size": {"w": 64, "h": 32},
+        "scale": "1"
+    }
+}"#,
+    );
+
+    write_file(
+        &sheets_path,
+        "items.xml",
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<TextureAtlas imagePath="items.png" width="32" height="16">
+    <sprite n="coin_1.png" x="0" y="0" w="16" h="16"/>
+    <sprite n="coin_2.png" x="16" y="0" w="12" h="14" oX="2" oY="1" oW="16" oH="16" r="y"/>
+</TextureAtlas>"#,
+    );
+
+    // Unrelated XML files are left alone.
+    write_file(&sheets_path, "notes.xml", "<notes/>");
+
+    sid_aseprite::from_aseprite_sheets_to_sid_assets(&sheets_path, &assets_path)
+        .expect("Unable to convert the sheets");
+
+    let sheet: SidSpriteSheetAsset = read_asset(&assets_path, "ui");
+    assert_eq!((sheet.width, sheet.height), (64, 32));
+
+    let def: SidAnimationDefAsset = read_asset(&assets_path, "ui");
+    let positions: Vec<_> = def.frames.iter().map(|frame| frame.pos.x).collect();
+    assert_eq!(positions, [32, 0, 16]);
+    assert!(def.frames.iter().all(|frame| frame.duration == 100));
+
+    let idle = &def.frames[0];
+    assert!(idle.rotated);
+    assert_eq!((idle.dims.width, idle.dims.height), (8, 12));
+    assert_eq!(idle.source_dims().height, 16);
+    assert_eq!(idle.pivot, Some(SidSlicePivot::with_coords(8, 16)));
+
+    let idle: SidAnimationAsset = read_asset(&assets_path, "ui_idle");
+    assert_eq!((idle.offset, idle.length), (0, 1));
+    let run: SidAnimationAsset = read_asset(&assets_path, "ui_run");
+    assert_eq!((run.offset, run.length), (1, 2));
+
+    let def: SidAnimationDefAsset = read_asset(&assets_path, "items");
+    assert_eq!(def.frame_count, 2);
+    assert!(def.frames[0].trim.is_none());
+    assert!(def.frames[1].rotated && def.frames[1].trim.is_some());
+
+    let coin: SidAnimationAsset = read_asset(&assets_path, "items_coin");
+    assert_eq!((coin.offset, coin.length), (0, 2));
+}
+
+#[test]
+fn spine_skeletons_convert_with_an_animation_each() {
+    let folder = tempfile::tempdir().unwrap();
+    let sheets_path = folder.path().join("sheets");
+    let assets_path = folder.path().join("assets");
+    fs::create_dir(&sheets_path).unwrap();
+
+    write_file(
+        &sheets_path,
+        "villager.json",
+        r#"{
+    "skeleton": {"hash": "abc", "spine": "4.1.23", "width": 32, "height": 64},
+    "bones": [
+        {"name": "root"},
+        {"name": "arm", "parent": "root", "length": 12, "x": 3, "rotation": 90}
+    ],
+    "slots": [
+        {"name": "arm", "bone": "arm", "color": "ff8000ff", "attachment": "arm"}
+    ],
+    "skins": [
+        {
+            "name": "default",
+            "attachments": {
+                "arm": {
+                    "arm": {"path": "villager/arm", "width": 8, "height": 16},
+                    "arm_bounds": {"type": "boundingbox", "vertexCount": 0, "vertices": []}
+                }
+            }
+        }
+    ],
+    "animations": {
+        "wave": {
+            "bones": {
+                "arm": {
+                    "rotate": [
+                        {"value": 0, "curve": [0.25, 0, 0.75, 45]},
+                        {"time": 1, "value": 45}
+                    ],
+                    "shear": [{"x": 10}]
+                }
+            },
+            "slots": {
+                "arm": {"attachment": [{"time": 0.5, "name": null}]}
+            }
+        }
+    }
+}"#,
+    );
+
+    sid_aseprite::from_aseprite_sheets_to_sid_assets(&sheets_path, &assets_path)
+        .expect("Unable to convert the skeleton");
+
+    let skeleton: SidSkeletonAsset = read_asset(&assets_path, "villager");
+    assert_eq!(skeleton.bones.len(), 2);
+    assert_eq!(skeleton.bones[1].parent.as_deref(), Some("root"));
+    assert_eq!(skeleton.bones[1].transform.rotation, 90.0);
+    assert_eq!(skeleton.slots[0].color, [255, 128, 0, 255]);
+
+    // Only region attachments are imported.
+    assert_eq!(skeleton.attachments.len(), 1);
+    assert_eq!(skeleton.attachments[0].region, "villager/arm");
+
+    let wave: SidSkeletalAnimationAsset = read_asset(&assets_path, "villager_wave");
+    assert_eq!(wave.skeleton_name, "villager");
+    assert_eq!(wave.duration, 1.0);
+    assert_eq!(wave.bones.len(), 1);
+
+    let keys = &wave.bones[0].keys;
+    assert_eq!(wave.bones[0].property, SidBoneTimelineProperty::Rotate);
+    assert_eq!(keys[1].values, [45.0, 0.0]);
+    assert_eq!(
+        keys[0].curve,
+        SidKeyCurve::Bezier {
+            points: [0.25, 0.0, 0.75, 1.0]
+        }
+    );
+
+    assert_eq!(wave.slots[0].keys[0].attachment, None);
+    assert!(!assets_path.join(SidSpriteSheetAsset::namespace()).exists());
+}
+
+#[test]
+fn bmfont_text_files_convert_with_a_texture_per_page() {
+    let folder = tempfile::tempdir().unwrap();
+    let sheets_path = folder.path().join("sheets");
+    let assets_path = folder.path().join("assets");
+    fs::create_dir(&sheets_path).unwrap();
+
+    save_image(&sheets_path, "title_0.png", 64, 64);
+    save_image(&sheets_path, "title_1.png", 64, 64);
+
+    write_file(
+        &sheets_path,
+        "title.fnt",
+        r#"info face="Pixel Sans" size=-16 bold=0 italic=0 charset="" unicode=1
+common lineHeight=18 base=14 scaleW=64 scaleH=64 pages=2 packed=0
+page id=0 file="title_0.png"
+page id=1 file="title_1.png"
+chars count=2
+char id=65   x=0     y=0     width=7     height=12    xoffset=0     yoffset=2     xadvance=8     page=0  chnl=15
+char id=66   x=8     y=0     width=7     height=12    xoffset=-1    yoffset=2     xadvance=8     page=1  chnl=15
+kernings count=1
+kerning first=65  second=66  amount=-1
+"#,
+    );
+
+    sid_aseprite::from_aseprite_sheets_to_sid_assets(&sheets_path, &assets_path)
+        .expect("Unable to convert the font");
+
+    let font: SidBitmapFontAsset = read_asset(&assets_path, "title");
+    assert_eq!(font.size, 16.0);
+    assert_eq!((font.line_height, font.base), (18, 14));
+    assert_eq!(font.pages, ["title_0", "title_1"]);
+    assert_eq!(font.glyphs.len(), 2);
+    assert_eq!(font.glyphs[1].x_offset, -1);
+    assert_eq!(font.glyphs[1].page, 1);
+    assert_eq!(font.kernings[0].amount, -1);
+
+    // Pages are copied next to their texture assets.
+    let page: SidTextureAsset = read_asset(&assets_path, "title_1");
+    assert_eq!(page.image_path, Path::new("title_1.png"));
+    assert!(assets_path
+        .join(SidTextureAsset::namespace())
+        .join("title_1.png")
+        .is_file());
+}
+
+/// Binary block of `block_type` holding `data`.
+fn bmfont_block(block_type: u8, data: &[u8]) -> Vec<u8> {
+    let mut block = vec![block_type];
+    block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    block.extend_from_slice(data);
+    block
+}
+
+#[test]
+fn bmfont_binary_files_convert_with_their_single_page() {
+    let folder = tempfile::tempdir().unwrap();
+    let sheets_path = folder.path().join("sheets");
+    let assets_path = folder.path().join("assets");
+    fs::create_dir(&sheets_path).unwrap();
+
+    save_image(&sheets_path, "body_0.png", 32, 32);
+
+    let mut info = 12i16.to_le_bytes().to_vec();
+    info.extend_from_slice(&[0; 12]);
+    info.extend_from_slice(b"Body\0");
+
+    let mut common = vec![];
+
+    for value in [14u16, 11, 32, 32, 1] {
+        common.extend_from_slice(&value.to_le_bytes());
+    }
+
+    common.extend_from_slice(&[0; 5]);
+
+    let mut chars = 97u32.to_le_bytes().to_vec();
+
+    for value in [4u16, 2, 5, 8] {
+        chars.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for value in [1i16, 3, 6] {
+        chars.extend_from_slice(&value.to_le_bytes());
+    }
+
+    chars.extend_from_slice(&[0, 15]);
+
+    let mut file = b"BMF\x03".to_vec();
+    file.extend(bmfont_block(1, &info));
+    file.extend(bmfont_block(2, &common));
+    file.extend(bmfont_block(3, b"body_0.png\0"));
+    file.extend(bmfont_block(4, &chars));
+    write_file(&sheets_path, "body.fnt", file);
+
+    sid_aseprite::from_aseprite_sheets_to_sid_assets(&sheets_path, &assets_path)
+        .expect("Unable to convert the font");
+
+    // Single pages are named after their font.
+    let font: SidBitmapFontAsset = read_asset(&assets_path, "body");
+    assert_eq!(font.size, 12.0);
+    assert_eq!((font.line_height, font.base), (14, 11));
+    assert_eq!(font.pages, ["body"]);
+    assert!(font.kernings.is_empty());
+
+    let glyph = &font.glyphs[0];
+    assert_eq!(glyph.character, 97);
+    assert_eq!((glyph.x, glyph.y, glyph.width, glyph.height), (4, 2, 5, 8));
+    assert_eq!((glyph.x_offset, glyph.y_offset, glyph.x_advance), (1, 3, 6));
+
+    let page: SidTextureAsset = read_asset(&assets_path, "body");
+    assert_eq!(page.image_path, Path::new("body.png"));
+}
+
+/// Buffer of a triangle: positions, texture coordinates and 16-bit indices.
+fn gltf_triangle_buffer() -> Vec<u8> {
+    let mut buffer = vec![];
+
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for value in [0.0f32, 1.0, 1.0, 1.0, 0.0, 0.0] {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for index in [0u16, 1, 2] {
+        buffer.extend_from_slice(&index.to_le_bytes());
+    }
+
+    buffer
+}
+
+#[test]
+fn gltf_meshes_convert_with_their_base_color_textures() {
+    let folder = tempfile::tempdir().unwrap();
+    let sheets_path = folder.path().join("sheets");
+    let assets_path = folder.path().join("assets");
+    fs::create_dir(&sheets_path).unwrap();
+
+    save_image(&sheets_path, "wood.png", 8, 8);
+
+    let buffer = gltf_triangle_buffer();
+    let data = base64::engine::general_purpose::STANDARD.encode(&buffer);
+
+    write_file(
+        &sheets_path,
+        "barrel.gltf",
+        format!(
+            r#"{{
+    "asset": {{"version": "2.0"}},
+    "buffers": [{{"byteLength": {}, "uri": "data:application/octet-stream;base64,{data}"}}],
+    "bufferViews": [
+        {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+        {{"buffer": 0, "byteOffset": 36, "byteLength": 24}},
+        {{"buffer": 0, "byteOffset": 60, "byteLength": 6}}
+    ],
+    "accessors": [
+        {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]}},
+        {{"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2"}},
+        {{"bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR"}}
+    ],
+    "images": [{{"uri": "wood.png"}}],
+    "textures": [{{"source": 0}}],
+    "materials": [{{"pbrMetallicRoughness": {{"baseColorTexture": {{"index": 0}}}}}}],
+    "meshes": [
+        {{
+            "name": "body",
+            "primitives": [
+                {{"attributes": {{"POSITION": 0, "TEXCOORD_0": 1}}, "indices": 2, "material": 0}},
+                {{"attributes": {{"POSITION": 0}}, "mode": 1}}
+            ]
+        }}
+    ]
+}}"#,
+            buffer.len()
+        ),
+    );
+
+    sid_aseprite::from_aseprite_sheets_to_sid_assets(&sheets_path, &assets_path)
+        .expect("Unable to convert the glTF file");
+
+    // Single meshes and images are named after their file.
+    let mesh: SidMeshAsset = read_asset(&assets_path, "barrel");
+    assert_eq!(mesh.vertices.len(), 3);
+    assert_eq!(mesh.vertices[1].position, [1.0, 0.0, 0.0]);
+    assert_eq!(mesh.vertices[1].uv, [1.0, 1.0]);
+    assert_eq!(mesh.indices, [0, 1, 2]);
+
+    // Line primitives are skipped.
+    assert_eq!(mesh.submeshes.len(), 1);
+    assert_eq!(
+        mesh.submeshes[0].texture,
+        Some(SidMeshTexture::Texture("barrel".to_string()))
+    );
+
+    let texture: SidTextureAsset = read_asset(&assets_path, "barrel");
+    assert_eq!(texture.image_path, Path::new("barrel.png"));
+    assert!(assets_path
+        .join(SidTextureAsset::namespace())
+        .join("barrel.png")
+        .is_file());
+}
@@ -5,6 +5,10 @@
 use std::{env, path::Path};
 
 fn main() {
+    if env::var_os("CARGO_FEATURE_PURE_RUST").is_some() {
+        return;
+    }
+
     let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
 
     println!(
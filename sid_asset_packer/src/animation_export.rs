@@ -0,0 +1,377 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::{self, FilterType},
+    Delay, Frame, RgbaImage,
+};
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAnimationDirection, SidPackedAsset,
+        SidSpriteSheetAsset,
+    },
+    error::{SidToolError, SidToolResult},
+    preview::{self, frame_order},
+    resource::SidAssetProcessor,
+    sid::{self, SidAnimationFrameDuration},
+    sid_info, sid_warning, texture,
+};
+
+/// Shortest frame delay most GIF viewers play as is, in milliseconds; shorter
+/// ones are slowed down, usually to 100 ms.
+const MIN_GIF_FRAME_DELAY: u32 = 20;
+
+/// Container the frames are written to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SidAnimationExportFormat {
+    /// Animated GIF, with delays rounded to hundredths of a second and 1-bit
+    /// transparency.
+    Gif,
+    /// Animated PNG, with exact delays and full alpha.
+    Apng,
+}
+
+impl SidAnimationExportFormat {
+    /// Format matching the extension of `path`: APNG for .png and .apng, and
+    /// GIF otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension)
+                if extension.eq_ignore_ascii_case("png")
+                    || extension.eq_ignore_ascii_case("apng") =>
+            {
+                SidAnimationExportFormat::Apng
+            }
+            _ => SidAnimationExportFormat::Gif,
+        }
+    }
+}
+
+/// Frames of an animation in play order, each with its duration in
+/// milliseconds, already scaled by the speed of the animation.
+pub struct SidAnimationFrames {
+    pub frames: Vec<(RgbaImage, u32)>,
+    /// Whether the animation plays again once it ends.
+    pub looping: bool,
+    speed_scale: f32,
+}
+
+impl SidAnimationFrames {
+    pub fn new(looping: bool, speed_scale: f32) -> Self {
+        Self {
+            frames: vec![],
+            looping,
+            speed_scale,
+        }
+    }
+
+    pub fn push(&mut self, image: RgbaImage, duration: SidAnimationFrameDuration) {
+        let duration = if self.speed_scale > 0.0 {
+            (duration as f32 / self.speed_scale).round() as u32
+        } else {
+            duration as u32
+        };
+
+        self.frames.push((image, duration));
+    }
+
+    /// Size every frame is drawn on, the one of the largest frame.
+    fn canvas_size(&self) -> (u32, u32) {
+        self.frames
+            .iter()
+            .fold((0, 0), |(width, height), (image, _)| {
+                (width.max(image.width()), height.max(image.height()))
+            })
+    }
+
+    /// Frames drawn at the top-left corner of the canvas, like in the engine,
+    /// and scaled `scale` times.
+    fn canvas_frames(&self, scale: u32) -> Vec<(RgbaImage, u32)> {
+        let (width, height) = self.canvas_size();
+        let scale = scale.max(1);
+
+        self.frames
+            .iter()
+            .map(|(image, duration)| {
+                let mut canvas = RgbaImage::new(width, height);
+                imageops::replace(&mut canvas, image, 0, 0);
+
+                if scale > 1 {
+                    canvas = imageops::resize(
+                        &canvas,
+                        width * scale,
+                        height * scale,
+                        FilterType::Nearest,
+                    );
+                }
+
+                (canvas, *duration)
+            })
+            .collect()
+    }
+}
+
+/// Namespace folder of the asset at `path` and the assets folder holding it.
+fn namespace_folder(path: &Path) -> Option<(&str, &Path)> {
+    path.ancestors().skip(1).find_map(|folder| {
+        let namespace = folder.file_name()?.to_str()?;
+
+        if namespace == SidAnimationAsset::namespace()
+            || namespace == SidAnimationDefAsset::namespace()
+        {
+            Some((namespace, folder.parent()?))
+        } else {
+            None
+        }
+    })
+}
+
+/// Finds the asset of type `T` named `name` in `assets_path`, returning its
+/// path along with it.
+fn find_asset<T: SidAssetProcessor>(assets_path: &Path, name: &str) -> SidToolResult<(PathBuf, T)> {
+    let folder = assets_path.join(T::namespace());
+
+    if folder.is_dir() {
+        for path in T::compatible_entries(&folder) {
+            let asset = T::load_asset(&path)?;
+
+            if asset.name() == name {
+                return Ok((path, asset));
+            }
+        }
+    }
+
+    Err(SidToolError::Malformed(format!(
+        "Unknown {} asset {:?} in {:?}",
+        T::namespace(),
+        name,
+        assets_path
+    )))
+}
+
+/// Image of the sprite sheet `name`, with its color key cleared.
+fn load_sheet_image(assets_path: &Path, name: &str) -> SidToolResult<RgbaImage> {
+    let (path, sheet) = find_asset::<SidSpriteSheetAsset>(assets_path, name)?;
+    let texture_path = sheet.texture_path(&path)?;
+
+    let image = image::open(&texture_path).map_err(|error| {
+        SidToolError::IO(format!("Unable to open image {:?}: {error}", texture_path))
+    })?;
+
+    Ok(match sheet.color_key {
+        Some(color_key) => texture::apply_color_key(image, color_key).into_rgba8(),
+        None => image.into_rgba8(),
+    })
+}
+
+/// Frames of the animation definition or animation asset at `path`, in play
+/// order, cut from the images of their sprite sheets.
+fn asset_animation_frames(path: &Path) -> SidToolResult<SidAnimationFrames> {
+    let (namespace, assets_path) = namespace_folder(path).ok_or_else(|| {
+        SidToolError::UnsupportedFormat(format!(
+            "Asset {:?} is neither in a {} nor in a {} folder, only animations can be exported",
+            path,
+            SidAnimationAsset::namespace(),
+            SidAnimationDefAsset::namespace()
+        ))
+    })?;
+
+    let (def, order, mut out) = if namespace == SidAnimationAsset::namespace() {
+        let animation = SidAnimationAsset::load_asset(path)?;
+        let (_, def) = find_asset::<SidAnimationDefAsset>(assets_path, &animation.def_name)?;
+
+        let order = frame_order(
+            def.frames.len(),
+            animation.offset as usize,
+            animation.length as usize,
+            animation.direction,
+        )?;
+
+        let out = SidAnimationFrames::new(animation.looping, animation.speed_scale);
+        (def, order, out)
+    } else {
+        let def = SidAnimationDefAsset::load_asset(path)?;
+        let frame_count = def.frames.len();
+        let order = frame_order(frame_count, 0, frame_count, SidAnimationDirection::Forward)?;
+        (def, order, SidAnimationFrames::new(true, 1.0))
+    };
+
+    let mut sheets = vec![load_sheet_image(assets_path, &def.sheet_name)?];
+
+    for page in &def.pages {
+        sheets.push(load_sheet_image(assets_path, page)?);
+    }
+
+    for index in order {
+        let frame = &def.frames[index];
+
+        let sheet = sheets.get(frame.page as usize).ok_or_else(|| {
+            SidToolError::Malformed(format!("Frame on unknown page {}", frame.page))
+        })?;
+
+        let sheet_dims = frame.sheet_dims();
+        let (x, y) = (frame.pos.x as u32, frame.pos.y as u32);
+
+        if x + sheet_dims.width as u32 > sheet.width()
+            || y + sheet_dims.height as u32 > sheet.height()
+        {
+            return Err(SidToolError::Malformed(format!(
+                "Frame at ({x}, {y}) is out of the bounds of its {}x{} sheet",
+                sheet.width(),
+                sheet.height()
+            )));
+        }
+
+        let (width, height) = (frame.dims.width as u32, frame.dims.height as u32);
+        let source_dims = frame.source_dims();
+        let (offset_x, offset_y) = match &frame.trim {
+            Some(trim) => (trim.offset.x as u32, trim.offset.y as u32),
+            None => (0, 0),
+        };
+
+        let mut image = RgbaImage::new(source_dims.width as u32, source_dims.height as u32);
+
+        for j in 0..height {
+            for i in 0..width {
+                // Rotated frames are stored turned clockwise.
+                let pixel = if frame.rotated {
+                    *sheet.get_pixel(x + height - 1 - j, y + i)
+                } else {
+                    *sheet.get_pixel(x + i, y + j)
+                };
+
+                let (out_x, out_y) = (offset_x + i, offset_y + j);
+
+                if out_x < image.width() && out_y < image.height() {
+                    image.put_pixel(out_x, out_y, pixel);
+                }
+            }
+        }
+
+        out.push(image, frame.duration);
+    }
+
+    Ok(out)
+}
+
+fn create_output(output: &Path) -> SidToolResult<BufWriter<File>> {
+    File::create(output)
+        .map(BufWriter::new)
+        .map_err(|error| SidToolError::IO(format!("Unable to create file {:?}: {error}", output)))
+}
+
+fn write_gif(frames: Vec<(RgbaImage, u32)>, looping: bool, output: &Path) -> SidToolResult<()> {
+    let encode_error = |error: image::ImageError| {
+        SidToolError::IO(format!("Unable to write GIF {:?}: {error}", output))
+    };
+
+    if frames
+        .iter()
+        .any(|(_, duration)| *duration < MIN_GIF_FRAME_DELAY)
+    {
+        sid_warning!(
+            "Some frames of {:?} last less than {MIN_GIF_FRAME_DELAY} ms, which most GIF viewers slow down, export an APNG for exact timings.",
+            output
+        );
+    }
+
+    let mut encoder = GifEncoder::new(create_output(output)?);
+    let repeat = if looping {
+        Repeat::Infinite
+    } else {
+        Repeat::Finite(0)
+    };
+
+    encoder.set_repeat(repeat).map_err(encode_error)?;
+
+    encoder
+        .encode_frames(frames.into_iter().map(|(image, duration)| {
+            Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(duration, 1))
+        }))
+        .map_err(encode_error)
+}
+
+fn write_apng(frames: Vec<(RgbaImage, u32)>, looping: bool, output: &Path) -> SidToolResult<()> {
+    let encode_error = |error: png::EncodingError| {
+        SidToolError::IO(format!("Unable to write APNG {:?}: {error}", output))
+    };
+
+    let (width, height) = frames
+        .first()
+        .map_or((0, 0), |(image, _)| image.dimensions());
+
+    let mut encoder = png::Encoder::new(create_output(output)?, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    // Zero plays loops forever.
+    encoder
+        .set_animated(frames.len() as u32, if looping { 0 } else { 1 })
+        .map_err(encode_error)?;
+
+    let mut writer = encoder.write_header().map_err(encode_error)?;
+
+    for (image, duration) in &frames {
+        let delay = (*duration).min(u16::MAX as u32) as u16;
+        writer.set_frame_delay(delay, 1000).map_err(encode_error)?;
+        writer
+            .write_image_data(image.as_raw())
+            .map_err(encode_error)?;
+    }
+
+    writer.finish().map_err(encode_error)
+}
+
+/// Renders the frames of an animation or an animation definition, with their
+/// durations, into an animated GIF or APNG at `output`, depending on its
+/// extension, for review. `path` is either a JSON asset, whose sprite sheets
+/// are found in the same assets folder, or a packed resource, in which case
+/// `id` picks the resource of a .sidpak archive. Frames are scaled `scale`
+/// times, without filtering.
+pub fn export_animation<P1: AsRef<Path>, P2: AsRef<Path>>(
+    path: P1,
+    id: Option<sid::SidStringId>,
+    output: P2,
+    scale: u32,
+) -> SidToolResult<()> {
+    let path = path.as_ref();
+    let output = output.as_ref();
+
+    let frames = if SidAnimationAsset::compatible(path) {
+        asset_animation_frames(path)?
+    } else {
+        preview::animation_frames(path, id)?
+    };
+
+    if frames.frames.is_empty() {
+        return Err(SidToolError::Malformed(format!(
+            "Animation {:?} has no frame to export",
+            path
+        )));
+    }
+
+    let canvas_frames = frames.canvas_frames(scale);
+
+    match SidAnimationExportFormat::from_path(output) {
+        SidAnimationExportFormat::Gif => write_gif(canvas_frames, frames.looping, output)?,
+        SidAnimationExportFormat::Apng => write_apng(canvas_frames, frames.looping, output)?,
+    }
+
+    sid_info!(
+        "Exported {} frame(s) of {:?} to {:?}.",
+        frames.frames.len(),
+        path,
+        output
+    );
+
+    Ok(())
+}
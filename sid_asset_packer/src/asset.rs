@@ -3,15 +3,18 @@
 // license that can be found in the LICENSE file.
 
 use std::{
+    collections::BTreeMap,
     fmt, fs,
     path::{Path, PathBuf},
 };
 
 use serde::{Deserialize, Serialize};
 
+use crate::portable_path;
 use crate::sid::{
     self, SidAnimationFrameCoord, SidAnimationFrameDim, SidAnimationFrameDuration,
-    SidAnimationFrameIndex, SidSpriteSheetDim,
+    SidAnimationFrameIndex, SidAnimationPageIndex, SidFontGlyphCoord, SidFontGlyphDim,
+    SidSliceCoord, SidSliceDim, SidSpriteSheetDim, SidTileDim, SidTileGid,
 };
 
 pub static DEFAULT_ASSETS_PATH: &str = "./assets";
@@ -35,18 +38,89 @@ pub type SidAssetResult<T> = Result<T, SidAssetError>;
 
 pub trait SidPackedAsset {
     fn namespace() -> &'static str;
-    fn write_to_folder<P: AsRef<Path>>(&self, folder_path: P) -> SidAssetResult<()>;
+    fn name(&self) -> &str;
+
+    /// Writes the asset to its namespace folder in `folder_path`.
+    fn write_to_folder<P: AsRef<Path>>(&self, folder_path: P) -> SidAssetResult<()> {
+        self.write_to_subfolder(folder_path, Path::new(""))
+    }
+
+    /// Writes the asset to `subfolder` of its namespace folder in
+    /// `folder_path`, e.g. to mirror the layout of its source files.
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()>;
+}
+
+/// How the color channels of an image are encoded. Alpha is always linear.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidColorSpace {
+    /// Gamma-encoded colors, as painted by artists.
+    #[default]
+    Srgb,
+    /// Data read as-is by shaders (e.g. normal maps and masks).
+    Linear,
+}
+
+/// Pixel layout a raw image is packed with.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidPixelFormat {
+    /// The layout of the image if supported, or else RGBA16 for 16-bit and
+    /// floating-point images, and RGBA8 for the others.
+    #[default]
+    Source,
+    /// 8-bit grayscale.
+    L8,
+    /// 8-bit grayscale with alpha.
+    La8,
+    Rgb8,
+    Rgba8,
+    Rgba16,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SidSpriteSheetAsset {
     pub name: String,
+    #[serde(with = "portable_path")]
     pub image_path: PathBuf,
     #[serde(skip_serializing, skip_deserializing)]
     pub image_from_path: PathBuf,
     pub width: SidSpriteSheetDim,
     pub height: SidSpriteSheetDim,
     pub format: String,
+    /// Color space of the image.
+    #[serde(default)]
+    pub color_space: SidColorSpace,
+    /// Pixel layout of the packed image.
+    #[serde(default)]
+    pub pixel_format: SidPixelFormat,
+    /// Name of the shared palette the image is mapped to, packing it as 8-bit
+    /// indices.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+    /// Color made transparent, e.g. magenta in legacy art, as red, green and
+    /// blue values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_key: Option<[u8; 3]>,
+    /// Default pivot of the frames, in pixels from their top-left corner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<SidSlicePivot>,
+    /// Atlas the sheet is packed into by the atlas command, instead of the
+    /// default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atlas_group: Option<String>,
+    /// Name of the texture holding the normals of the image, with the same
+    /// layout, for lit rendering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normal_map: Option<String>,
+    /// Name of the texture holding the light emitted by the image, with the
+    /// same layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emissive_map: Option<String>,
 }
 
 impl SidSpriteSheetAsset {
@@ -76,16 +150,32 @@ impl SidSpriteSheetAsset {
             width,
             height,
             format,
+            color_space: SidColorSpace::default(),
+            pixel_format: SidPixelFormat::default(),
+            palette: None,
+            color_key: None,
+            pivot: None,
+            atlas_group: None,
+            normal_map: None,
+            emissive_map: None,
         }
     }
 }
 
 impl SidPackedAsset for SidSpriteSheetAsset {
     fn namespace() -> &'static str {
-        return sid::get_sprite_sheet_namespace();
+        sid::get_sprite_sheet_namespace()
     }
 
-    fn write_to_folder<P: AsRef<Path>>(&self, folder_path: P) -> SidAssetResult<()> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
         let out_json = serde_json::to_string_pretty(self).map_err(|error| {
             SidAssetError::Malformed(format!(
                 "Unable to create sprite sheet asset {:?}: {error}",
@@ -96,7 +186,7 @@ impl SidPackedAsset for SidSpriteSheetAsset {
         let folder_path = folder_path.as_ref();
 
         let out_path = if folder_path.is_absolute() {
-            folder_path.join(Self::namespace())
+            folder_path.join(Self::namespace()).join(subfolder)
         } else {
             let out_path = match std::fs::canonicalize(folder_path) {
                 Ok(containing_folder_full_path) => containing_folder_full_path,
@@ -107,7 +197,7 @@ impl SidPackedAsset for SidSpriteSheetAsset {
                 }
             };
 
-            out_path.join(Self::namespace())
+            out_path.join(Self::namespace()).join(subfolder)
         };
 
         fs::create_dir_all(&out_path).map_err(|error| {
@@ -120,12 +210,23 @@ impl SidPackedAsset for SidSpriteSheetAsset {
         let in_sheet_path = self.image_from_path.clone();
         let out_sheet_path = out_path.join(&self.image_path);
 
-        fs::copy(&in_sheet_path, &out_sheet_path).map_err(|error| {
-            SidAssetError::IO(format!(
-                "Unable to copy file from {:?} to {:?}: {}",
-                in_sheet_path, out_sheet_path, error
-            ))
-        })?;
+        // Generated images (e.g. atlases) may already be at their final place.
+        let is_same_file = match (
+            fs::canonicalize(&in_sheet_path),
+            fs::canonicalize(&out_sheet_path),
+        ) {
+            (Ok(in_sheet_path), Ok(out_sheet_path)) => in_sheet_path == out_sheet_path,
+            _ => false,
+        };
+
+        if !is_same_file {
+            fs::copy(&in_sheet_path, &out_sheet_path).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to copy file from {:?} to {:?}: {}",
+                    in_sheet_path, out_sheet_path, error
+                ))
+            })?;
+        }
 
         let out_json_path = out_path.join(format!("{}.json", self.name));
 
@@ -140,7 +241,7 @@ impl SidPackedAsset for SidSpriteSheetAsset {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct SidAnimationFramePos {
     pub x: SidAnimationFrameCoord,
     pub y: SidAnimationFrameCoord,
@@ -165,7 +266,7 @@ impl Clone for SidAnimationFramePos {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct SidAnimationFrameDims {
     pub width: SidAnimationFrameDim,
     pub height: SidAnimationFrameDim,
@@ -193,11 +294,51 @@ impl Clone for SidAnimationFrameDims {
     }
 }
 
+/// Transparent borders removed from a frame when it was exported.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SidAnimationFrameTrim {
+    /// Position of the trimmed frame within the original one.
+    pub offset: SidAnimationFramePos,
+    /// Size of the original frame, before trimming.
+    pub source_dims: SidAnimationFrameDims,
+}
+
+impl SidAnimationFrameTrim {
+    pub fn new() -> Self {
+        Self::with_data(SidAnimationFramePos::new(), SidAnimationFrameDims::new())
+    }
+
+    pub fn with_data(offset: SidAnimationFramePos, source_dims: SidAnimationFrameDims) -> Self {
+        Self {
+            offset,
+            source_dims,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SidAnimationFrameAsset {
     pub pos: SidAnimationFramePos,
     pub dims: SidAnimationFrameDims,
     pub duration: SidAnimationFrameDuration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim: Option<SidAnimationFrameTrim>,
+    /// Whether the frame is stored rotated by 90 degrees clockwise in the sheet,
+    /// in which case `dims` is the unrotated size.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rotated: bool,
+    /// Point the frame is drawn around, in pixels from the top-left corner of
+    /// its source size, replacing the pivot of the sprite sheet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<SidSlicePivot>,
+    /// Sheet holding the frame: 0 for the sheet of its animation definition,
+    /// or else the index of the sheet in its `pages`, plus one.
+    #[serde(default, skip_serializing_if = "is_first_page")]
+    pub page: SidAnimationPageIndex,
+}
+
+fn is_first_page(page: &SidAnimationPageIndex) -> bool {
+    *page == 0
 }
 
 impl SidAnimationFrameAsset {
@@ -214,6 +355,27 @@ impl SidAnimationFrameAsset {
             pos,
             dims: dim,
             duration,
+            trim: None,
+            rotated: false,
+            pivot: None,
+            page: 0,
+        }
+    }
+
+    /// Size of the frame once drawn, trimmed borders included.
+    pub fn source_dims(&self) -> SidAnimationFrameDims {
+        match &self.trim {
+            Some(trim) => trim.source_dims.clone(),
+            None => self.dims.clone(),
+        }
+    }
+
+    /// Size of the region the frame covers in the sheet.
+    pub fn sheet_dims(&self) -> SidAnimationFrameDims {
+        if self.rotated {
+            SidAnimationFrameDims::with_width_and_height(self.dims.height, self.dims.width)
+        } else {
+            self.dims.clone()
         }
     }
 }
@@ -224,6 +386,10 @@ pub struct SidAnimationDefAsset {
     pub frames: Vec<SidAnimationFrameAsset>,
     pub name: String,
     pub sheet_name: String,
+    /// Sheets holding the frames that did not fit in `sheet_name`, e.g. once
+    /// split into atlas pages, referenced by the `page` of the frames.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<String>,
 }
 
 impl SidAnimationDefAsset {
@@ -251,16 +417,25 @@ impl SidAnimationDefAsset {
             frames,
             name,
             sheet_name,
+            pages: vec![],
         }
     }
 }
 
 impl SidPackedAsset for SidAnimationDefAsset {
     fn namespace() -> &'static str {
-        return sid::get_animation_def_namespace();
+        sid::get_animation_def_namespace()
     }
 
-    fn write_to_folder<P: AsRef<Path>>(&self, folder_path: P) -> SidAssetResult<()> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
         let out_json = serde_json::to_string_pretty(self).map_err(|error| {
             SidAssetError::Malformed(format!(
                 "Unable to create animation definition asset {:?}: {error}",
@@ -268,7 +443,7 @@ impl SidPackedAsset for SidAnimationDefAsset {
             ))
         })?;
 
-        let out_path = folder_path.as_ref().join(Self::namespace());
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
 
         fs::create_dir_all(&out_path).map_err(|error| {
             SidAssetError::IO(format!(
@@ -290,12 +465,50 @@ impl SidPackedAsset for SidAnimationDefAsset {
     }
 }
 
+#[repr(u8)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidAnimationDirection {
+    #[default]
+    Forward = 0,
+    Reverse = 1,
+    // Same spelling as Aseprite.
+    #[serde(rename = "pingpong")]
+    PingPong = 2,
+    #[serde(rename = "pingpong_reverse")]
+    PingPongReverse = 3,
+}
+
+impl SidAnimationDirection {
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(SidAnimationDirection::Forward),
+            1 => Some(SidAnimationDirection::Reverse),
+            2 => Some(SidAnimationDirection::PingPong),
+            3 => Some(SidAnimationDirection::PingPongReverse),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SidAnimationAsset {
     pub offset: SidAnimationFrameIndex,
     pub length: SidAnimationFrameIndex,
     pub name: String,
     pub def_name: String,
+    #[serde(default)]
+    pub direction: SidAnimationDirection,
+    /// Play the animation again once it ends, instead of holding its last
+    /// frame.
+    #[serde(rename = "loop", default = "default_animation_loop")]
+    pub looping: bool,
+    /// Default playback speed, 1 being the speed of the frame durations.
+    #[serde(default = "default_animation_speed_scale")]
+    pub speed_scale: f32,
+    /// Events fired when the animation reaches their frame, sorted by frame.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<SidAnimationEvent>,
 }
 
 impl SidAnimationAsset {
@@ -304,8 +517,9 @@ impl SidAnimationAsset {
         let length = 0;
         let name = String::new();
         let def_name = String::new();
+        let direction = SidAnimationDirection::Forward;
 
-        Self::with_data(offset, length, name, def_name)
+        Self::with_data(offset, length, name, def_name, direction)
     }
 
     pub fn with_data(
@@ -313,12 +527,17 @@ impl SidAnimationAsset {
         length: SidAnimationFrameIndex,
         name: String,
         def_name: String,
+        direction: SidAnimationDirection,
     ) -> Self {
         Self {
             offset,
             length,
             name,
             def_name,
+            direction,
+            looping: default_animation_loop(),
+            speed_scale: default_animation_speed_scale(),
+            events: vec![],
         }
     }
 
@@ -327,18 +546,53 @@ impl SidAnimationAsset {
         name: String,
         offset: SidAnimationFrameIndex,
         length: SidAnimationFrameIndex,
+        direction: SidAnimationDirection,
     ) -> Self {
         let def_name = def.name.clone();
-        SidAnimationAsset::with_data(offset, length, name, def_name)
+        SidAnimationAsset::with_data(offset, length, name, def_name, direction)
+    }
+}
+
+fn default_animation_loop() -> bool {
+    true
+}
+
+fn default_animation_speed_scale() -> f32 {
+    1.0
+}
+
+/// Named event of an animation, e.g. a footstep.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SidAnimationEvent {
+    /// Frame of the event, relative to the first frame of the animation.
+    pub frame: SidAnimationFrameIndex,
+    pub name: String,
+}
+
+impl SidAnimationEvent {
+    pub fn new() -> Self {
+        Self::with_data(0, String::new())
+    }
+
+    pub fn with_data(frame: SidAnimationFrameIndex, name: String) -> Self {
+        Self { frame, name }
     }
 }
 
 impl SidPackedAsset for SidAnimationAsset {
     fn namespace() -> &'static str {
-        return sid::get_animation_namespace();
+        sid::get_animation_namespace()
     }
 
-    fn write_to_folder<P: AsRef<Path>>(&self, folder_path: P) -> SidAssetResult<()> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
         let out_json = serde_json::to_string_pretty(self).map_err(|error| {
             SidAssetError::Malformed(format!(
                 "Unable to create animation definition asset {:?}: {error}",
@@ -346,7 +600,275 @@ impl SidPackedAsset for SidAnimationAsset {
             ))
         })?;
 
-        let out_path = folder_path.as_ref().join(Self::namespace());
+        // Names may hold slashes, e.g. "hero/run", nesting them in folders.
+        let out_path = folder_path
+            .as_ref()
+            .join(Self::namespace())
+            .join(subfolder)
+            .join(format!("{}.json", self.name));
+
+        if let Some(out_folder) = out_path.parent() {
+            fs::create_dir_all(out_folder).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to create folder at path {:?}: {}",
+                    out_folder, error
+                ))
+            })?;
+        }
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidSliceRect {
+    pub x: SidSliceCoord,
+    pub y: SidSliceCoord,
+    pub width: SidSliceDim,
+    pub height: SidSliceDim,
+}
+
+impl SidSliceRect {
+    pub fn new() -> Self {
+        Self::with_coords_and_size(0, 0, 0, 0)
+    }
+
+    pub fn with_coords_and_size(
+        x: SidSliceCoord,
+        y: SidSliceCoord,
+        width: SidSliceDim,
+        height: SidSliceDim,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SidSlicePivot {
+    pub x: SidSliceCoord,
+    pub y: SidSliceCoord,
+}
+
+impl SidSlicePivot {
+    pub fn new() -> Self {
+        Self::with_coords(0, 0)
+    }
+
+    pub fn with_coords(x: SidSliceCoord, y: SidSliceCoord) -> Self {
+        Self { x, y }
+    }
+}
+
+/// State of a slice from `frame` onwards, until the next key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidSliceKeyAsset {
+    pub frame: SidAnimationFrameIndex,
+    pub bounds: SidSliceRect,
+    /// Center part of a 9-slice, relative to the bounds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<SidSliceRect>,
+    /// Pivot point, relative to the bounds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<SidSlicePivot>,
+}
+
+impl SidSliceKeyAsset {
+    pub fn new() -> Self {
+        Self::with_data(0, SidSliceRect::new(), None, None)
+    }
+
+    pub fn with_data(
+        frame: SidAnimationFrameIndex,
+        bounds: SidSliceRect,
+        center: Option<SidSliceRect>,
+        pivot: Option<SidSlicePivot>,
+    ) -> Self {
+        Self {
+            frame,
+            bounds,
+            center,
+            pivot,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidSliceAsset {
+    pub name: String,
+    pub sheet_name: String,
+    pub keys: Vec<SidSliceKeyAsset>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_data: String,
+}
+
+impl SidSliceAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let sheet_name = String::new();
+        let keys = vec![];
+        let user_data = String::new();
+
+        Self::with_data(name, sheet_name, keys, user_data)
+    }
+
+    pub fn with_data(
+        name: String,
+        sheet_name: String,
+        keys: Vec<SidSliceKeyAsset>,
+        user_data: String,
+    ) -> Self {
+        Self {
+            name,
+            sheet_name,
+            keys,
+            user_data,
+        }
+    }
+}
+
+impl SidPackedAsset for SidSliceAsset {
+    fn namespace() -> &'static str {
+        sid::get_slice_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create slice asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Region of a sprite sheet drawn stretched by the UI, e.g. a panel: its
+/// borders keep their size, while its center and edges are stretched.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidNineSliceAsset {
+    pub name: String,
+    pub sheet_name: String,
+    /// Region of the sprite sheet, in pixels.
+    pub region: SidSliceRect,
+    /// Size of the borders that are not stretched, in pixels from the edges
+    /// of the region.
+    pub insets: SidNineSliceInsets,
+}
+
+impl SidNineSliceAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let sheet_name = String::new();
+        let region = SidSliceRect::new();
+        let insets = SidNineSliceInsets::new();
+
+        Self::with_data(name, sheet_name, region, insets)
+    }
+
+    pub fn with_data(
+        name: String,
+        sheet_name: String,
+        region: SidSliceRect,
+        insets: SidNineSliceInsets,
+    ) -> Self {
+        Self {
+            name,
+            sheet_name,
+            region,
+            insets,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidNineSliceInsets {
+    pub left: SidSliceDim,
+    pub top: SidSliceDim,
+    pub right: SidSliceDim,
+    pub bottom: SidSliceDim,
+}
+
+impl SidNineSliceInsets {
+    pub fn new() -> Self {
+        Self::with_sizes(0, 0, 0, 0)
+    }
+
+    pub fn with_sizes(
+        left: SidSliceDim,
+        top: SidSliceDim,
+        right: SidSliceDim,
+        bottom: SidSliceDim,
+    ) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+}
+
+impl SidPackedAsset for SidNineSliceAsset {
+    fn namespace() -> &'static str {
+        sid::get_nine_slice_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create nine-slice asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
 
         fs::create_dir_all(&out_path).map_err(|error| {
             SidAssetError::IO(format!(
@@ -367,3 +889,2026 @@ impl SidPackedAsset for SidAnimationAsset {
         Ok(())
     }
 }
+
+/// Character made of several sprite sheets drawn together, e.g. a body, a
+/// weapon and effects, each with its own animations.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidCharacterAsset {
+    pub name: String,
+    pub parts: Vec<SidCharacterPartAsset>,
+}
+
+impl SidCharacterAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), vec![])
+    }
+
+    pub fn with_data(name: String, parts: Vec<SidCharacterPartAsset>) -> Self {
+        Self { name, parts }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidCharacterPartAsset {
+    /// Name of the part within its character, e.g. "weapon".
+    pub name: String,
+    pub sheet_name: String,
+    pub def_name: String,
+    pub animations: Vec<String>,
+}
+
+impl SidCharacterPartAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), String::new(), String::new(), vec![])
+    }
+
+    pub fn with_data(
+        name: String,
+        sheet_name: String,
+        def_name: String,
+        animations: Vec<String>,
+    ) -> Self {
+        Self {
+            name,
+            sheet_name,
+            def_name,
+            animations,
+        }
+    }
+}
+
+impl SidPackedAsset for SidCharacterAsset {
+    fn namespace() -> &'static str {
+        sid::get_character_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create character asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Position, rotation (in degrees, counterclockwise) and scale of a bone or an
+/// attachment, relative to its parent bone, or of a scene entity, relative to
+/// the scene origin.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct SidBoneTransform {
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default = "default_bone_scale")]
+    pub scale_x: f32,
+    #[serde(default = "default_bone_scale")]
+    pub scale_y: f32,
+}
+
+fn default_bone_scale() -> f32 {
+    1.0
+}
+
+impl Default for SidBoneTransform {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidSkeletonBoneAsset {
+    pub name: String,
+    /// Bone this one is attached to, none for the root bone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub length: f32,
+    #[serde(flatten)]
+    pub transform: SidBoneTransform,
+}
+
+impl SidSkeletonBoneAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), None, 0.0, SidBoneTransform::default())
+    }
+
+    pub fn with_data(
+        name: String,
+        parent: Option<String>,
+        length: f32,
+        transform: SidBoneTransform,
+    ) -> Self {
+        Self {
+            name,
+            parent,
+            length,
+            transform,
+        }
+    }
+}
+
+/// Draw slot of a skeleton, following a bone. Slots are drawn in order.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidSkeletonSlotAsset {
+    pub name: String,
+    pub bone: String,
+    /// Attachment shown in the setup pose, none to show nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<String>,
+    /// RGBA tint of the slot.
+    #[serde(default = "default_slot_color")]
+    pub color: [u8; 4],
+}
+
+fn default_slot_color() -> [u8; 4] {
+    [255; 4]
+}
+
+impl SidSkeletonSlotAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), String::new(), None, default_slot_color())
+    }
+
+    pub fn with_data(
+        name: String,
+        bone: String,
+        attachment: Option<String>,
+        color: [u8; 4],
+    ) -> Self {
+        Self {
+            name,
+            bone,
+            attachment,
+            color,
+        }
+    }
+}
+
+/// Image drawn in a slot, e.g. an arm, from a region of the atlas of the
+/// skeleton.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidSkeletonAttachmentAsset {
+    pub slot: String,
+    pub name: String,
+    pub region: String,
+    #[serde(flatten)]
+    pub transform: SidBoneTransform,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl SidSkeletonAttachmentAsset {
+    pub fn new() -> Self {
+        Self::with_data(
+            String::new(),
+            String::new(),
+            String::new(),
+            SidBoneTransform::default(),
+            0.0,
+            0.0,
+        )
+    }
+
+    pub fn with_data(
+        slot: String,
+        name: String,
+        region: String,
+        transform: SidBoneTransform,
+        width: f32,
+        height: f32,
+    ) -> Self {
+        Self {
+            slot,
+            name,
+            region,
+            transform,
+            width,
+            height,
+        }
+    }
+}
+
+/// Rig of a skeletal animation: bones, the slots they carry and what can be
+/// drawn in them. Bones come after their parent.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidSkeletonAsset {
+    pub name: String,
+    pub bones: Vec<SidSkeletonBoneAsset>,
+    #[serde(default)]
+    pub slots: Vec<SidSkeletonSlotAsset>,
+    #[serde(default)]
+    pub attachments: Vec<SidSkeletonAttachmentAsset>,
+}
+
+impl SidSkeletonAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), vec![], vec![], vec![])
+    }
+
+    pub fn with_data(
+        name: String,
+        bones: Vec<SidSkeletonBoneAsset>,
+        slots: Vec<SidSkeletonSlotAsset>,
+        attachments: Vec<SidSkeletonAttachmentAsset>,
+    ) -> Self {
+        Self {
+            name,
+            bones,
+            slots,
+            attachments,
+        }
+    }
+}
+
+impl SidPackedAsset for SidSkeletonAsset {
+    fn namespace() -> &'static str {
+        sid::get_skeleton_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create skeleton asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Bone property animated by a timeline.
+#[repr(u8)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SidBoneTimelineProperty {
+    /// Rotation in degrees, added to the setup pose. Keys only use their
+    /// first value.
+    Rotate = 0,
+    /// Offset added to the setup pose.
+    Translate = 1,
+    /// Factors multiplying the scale of the setup pose.
+    Scale = 2,
+}
+
+impl SidBoneTimelineProperty {
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(SidBoneTimelineProperty::Rotate),
+            1 => Some(SidBoneTimelineProperty::Translate),
+            2 => Some(SidBoneTimelineProperty::Scale),
+            _ => None,
+        }
+    }
+}
+
+/// Interpolation from a key to the next one.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SidKeyCurve {
+    #[default]
+    Linear,
+    /// Holds the value of the key until the next one.
+    Stepped,
+    /// Cubic Bézier easing between (0, 0) and (1, 1), through its two control
+    /// points `[x1, y1, x2, y2]`.
+    Bezier { points: [f32; 4] },
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct SidBoneKeyAsset {
+    /// Time of the key, in seconds.
+    pub time: f32,
+    pub values: [f32; 2],
+    #[serde(default)]
+    pub curve: SidKeyCurve,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidBoneTimelineAsset {
+    pub bone: String,
+    pub property: SidBoneTimelineProperty,
+    pub keys: Vec<SidBoneKeyAsset>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SidSlotKeyAsset {
+    /// Time of the key, in seconds.
+    pub time: f32,
+    /// Attachment shown from then on, none to hide the slot.
+    pub attachment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidSlotTimelineAsset {
+    pub slot: String,
+    pub keys: Vec<SidSlotKeyAsset>,
+}
+
+/// Animation of the bones and slots of a skeleton, keyed in seconds.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidSkeletalAnimationAsset {
+    pub name: String,
+    pub skeleton_name: String,
+    /// Length of the animation, in seconds.
+    pub duration: f32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bones: Vec<SidBoneTimelineAsset>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slots: Vec<SidSlotTimelineAsset>,
+}
+
+impl SidSkeletalAnimationAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), String::new(), 0.0, vec![], vec![])
+    }
+
+    pub fn with_data(
+        name: String,
+        skeleton_name: String,
+        duration: f32,
+        bones: Vec<SidBoneTimelineAsset>,
+        slots: Vec<SidSlotTimelineAsset>,
+    ) -> Self {
+        Self {
+            name,
+            skeleton_name,
+            duration,
+            bones,
+            slots,
+        }
+    }
+}
+
+impl SidPackedAsset for SidSkeletalAnimationAsset {
+    fn namespace() -> &'static str {
+        sid::get_skeletal_animation_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create skeletal animation asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Sound played by the engine, from a WAV or Ogg Vorbis file next to the asset.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidAudioAsset {
+    pub name: String,
+    #[serde(with = "portable_path")]
+    pub audio_path: PathBuf,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub audio_from_path: PathBuf,
+}
+
+impl SidAudioAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let audio_path = PathBuf::new();
+        let audio_from_path = PathBuf::new();
+
+        Self::with_data(name, audio_path, audio_from_path)
+    }
+
+    pub fn with_data(name: String, audio_path: PathBuf, audio_from_path: PathBuf) -> Self {
+        Self {
+            name,
+            audio_path,
+            audio_from_path,
+        }
+    }
+}
+
+impl SidPackedAsset for SidAudioAsset {
+    fn namespace() -> &'static str {
+        sid::get_audio_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create audio asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_audio_path = out_path.join(&self.audio_path);
+
+        if self.audio_from_path != out_audio_path {
+            fs::copy(&self.audio_from_path, &out_audio_path).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to copy file from {:?} to {:?}: {}",
+                    self.audio_from_path, out_audio_path, error
+                ))
+            })?;
+        }
+
+        let out_json_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_json_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_json_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Bitmap font rasterized from a TrueType font next to the asset.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidFontAsset {
+    pub name: String,
+    #[serde(with = "portable_path")]
+    pub font_path: PathBuf,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub font_from_path: PathBuf,
+    /// Height of the glyphs, in pixels.
+    pub size: f32,
+    /// Characters to rasterize; printable ASCII characters if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub characters: String,
+    /// Empty pixels left between two glyphs of the atlas.
+    #[serde(default = "default_font_padding")]
+    pub padding: u32,
+}
+
+fn default_font_padding() -> u32 {
+    1
+}
+
+impl SidFontAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let font_path = PathBuf::new();
+        let font_from_path = PathBuf::new();
+        let size = 0.0;
+        let characters = String::new();
+
+        Self::with_data(name, font_path, font_from_path, size, characters)
+    }
+
+    pub fn with_data(
+        name: String,
+        font_path: PathBuf,
+        font_from_path: PathBuf,
+        size: f32,
+        characters: String,
+    ) -> Self {
+        Self {
+            name,
+            font_path,
+            font_from_path,
+            size,
+            characters,
+            padding: default_font_padding(),
+        }
+    }
+}
+
+impl SidPackedAsset for SidFontAsset {
+    fn namespace() -> &'static str {
+        sid::get_font_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create font asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_font_path = out_path.join(&self.font_path);
+
+        if self.font_from_path != out_font_path {
+            fs::copy(&self.font_from_path, &out_font_path).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to copy file from {:?} to {:?}: {}",
+                    self.font_from_path, out_font_path, error
+                ))
+            })?;
+        }
+
+        let out_json_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_json_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_json_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Glyph of a bitmap font, as placed in its page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SidBitmapFontGlyphAsset {
+    /// Unicode code point of the glyph.
+    pub character: u32,
+    pub x: SidFontGlyphCoord,
+    pub y: SidFontGlyphCoord,
+    pub width: SidFontGlyphDim,
+    pub height: SidFontGlyphDim,
+    /// Offset from the cursor to the top left corner of the glyph.
+    pub x_offset: i32,
+    pub y_offset: i32,
+    /// Distance the cursor moves after the glyph.
+    pub x_advance: i32,
+    /// Index of the page holding the glyph.
+    #[serde(default)]
+    pub page: u8,
+}
+
+/// Extra distance between two characters drawn one after the other.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SidBitmapFontKerningAsset {
+    pub first: u32,
+    pub second: u32,
+    pub amount: i32,
+}
+
+/// Font drawn from glyphs prerendered in textures, e.g. exported by BMFont,
+/// instead of rasterized from a TrueType font.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidBitmapFontAsset {
+    pub name: String,
+    /// Size the glyphs were rendered at, in pixels.
+    pub size: f32,
+    /// Distance between two lines, and from the top of a line to the baseline.
+    pub line_height: u32,
+    pub base: u32,
+    /// Names of the textures of the pages.
+    pub pages: Vec<String>,
+    pub glyphs: Vec<SidBitmapFontGlyphAsset>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kernings: Vec<SidBitmapFontKerningAsset>,
+}
+
+impl SidBitmapFontAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), 0.0, 0, 0, vec![], vec![], vec![])
+    }
+
+    pub fn with_data(
+        name: String,
+        size: f32,
+        line_height: u32,
+        base: u32,
+        pages: Vec<String>,
+        glyphs: Vec<SidBitmapFontGlyphAsset>,
+        kernings: Vec<SidBitmapFontKerningAsset>,
+    ) -> Self {
+        Self {
+            name,
+            size,
+            line_height,
+            base,
+            pages,
+            glyphs,
+            kernings,
+        }
+    }
+}
+
+impl SidPackedAsset for SidBitmapFontAsset {
+    fn namespace() -> &'static str {
+        sid::get_bitmap_font_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create bitmap font asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Vertex of a mesh, interleaved with the others when packed.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+pub struct SidMeshVertexAsset {
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub normal: [f32; 3],
+    /// Texture coordinates, from the top left corner of the texture.
+    #[serde(default)]
+    pub uv: [f32; 2],
+}
+
+/// Texture sampled by a submesh, which is either a texture or a whole sprite
+/// sheet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum SidMeshTexture {
+    Texture(String),
+    SpriteSheet(String),
+}
+
+/// Range of the indices of a mesh drawn with the same material.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SidMeshSubmeshAsset {
+    pub first_index: u32,
+    pub index_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<SidMeshTexture>,
+}
+
+/// Triangle mesh of a simple 3D prop, e.g. imported from glTF.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidMeshAsset {
+    pub name: String,
+    pub vertices: Vec<SidMeshVertexAsset>,
+    /// Vertices of the triangles, three per triangle.
+    pub indices: Vec<u32>,
+    pub submeshes: Vec<SidMeshSubmeshAsset>,
+}
+
+impl SidMeshAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), vec![], vec![], vec![])
+    }
+
+    pub fn with_data(
+        name: String,
+        vertices: Vec<SidMeshVertexAsset>,
+        indices: Vec<u32>,
+        submeshes: Vec<SidMeshSubmeshAsset>,
+    ) -> Self {
+        Self {
+            name,
+            vertices,
+            indices,
+            submeshes,
+        }
+    }
+}
+
+impl SidPackedAsset for SidMeshAsset {
+    fn namespace() -> &'static str {
+        sid::get_mesh_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create mesh asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Interpolation between the keys of a curve.
+#[repr(u8)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidCurveInterpolation {
+    /// Holds the values of a key until the next one.
+    Stepped = 0,
+    #[default]
+    Linear = 1,
+    /// Catmull-Rom spline through the keys, e.g. for smooth camera paths.
+    CatmullRom = 2,
+}
+
+impl SidCurveInterpolation {
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(SidCurveInterpolation::Stepped),
+            1 => Some(SidCurveInterpolation::Linear),
+            2 => Some(SidCurveInterpolation::CatmullRom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SidCurveKeyAsset {
+    /// Time of the key, in seconds.
+    pub time: f32,
+    /// Values of the key, e.g. a position for camera paths or a single factor
+    /// for tweens. Every key of a curve has as many.
+    pub values: Vec<f32>,
+}
+
+/// Values interpolated over time between keys, e.g. camera paths and UI
+/// tweens.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidCurveAsset {
+    pub name: String,
+    #[serde(default)]
+    pub interpolation: SidCurveInterpolation,
+    /// Keys, sorted by time.
+    pub keys: Vec<SidCurveKeyAsset>,
+    /// Samples per second the curve is packed at, instead of its keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f32>,
+}
+
+impl SidCurveAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), SidCurveInterpolation::default(), vec![])
+    }
+
+    pub fn with_data(
+        name: String,
+        interpolation: SidCurveInterpolation,
+        keys: Vec<SidCurveKeyAsset>,
+    ) -> Self {
+        Self {
+            name,
+            interpolation,
+            keys,
+            sample_rate: None,
+        }
+    }
+
+    /// Values of the curve at `time`, which hold the ones of the first and the
+    /// last keys out of their range.
+    pub fn evaluate(&self, time: f32) -> Vec<f32> {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return vec![];
+        };
+
+        if time <= first.time {
+            return first.values.clone();
+        }
+
+        if time >= last.time {
+            return last.values.clone();
+        }
+
+        let next = self.keys.partition_point(|key| key.time <= time);
+        let (from, to) = (&self.keys[next - 1], &self.keys[next]);
+        let t = (time - from.time) / (to.time - from.time);
+
+        match self.interpolation {
+            SidCurveInterpolation::Stepped => from.values.clone(),
+            SidCurveInterpolation::Linear => from
+                .values
+                .iter()
+                .zip(&to.values)
+                .map(|(from, to)| from + (to - from) * t)
+                .collect(),
+            SidCurveInterpolation::CatmullRom => {
+                // The first and last keys stand for the keys out of range.
+                let before = &self.keys[next.saturating_sub(2)];
+                let after = self.keys.get(next + 1).unwrap_or(to);
+
+                (0..from.values.len().min(to.values.len()))
+                    .map(|i| {
+                        let p0 = before.values.get(i).copied().unwrap_or(from.values[i]);
+                        let p1 = from.values[i];
+                        let p2 = to.values[i];
+                        let p3 = after.values.get(i).copied().unwrap_or(p2);
+
+                        0.5 * (2.0 * p1
+                            + (p2 - p0) * t
+                            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl SidPackedAsset for SidCurveAsset {
+    fn namespace() -> &'static str {
+        sid::get_curve_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create curve asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Grid of tiles cut from a sprite sheet.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidTilesetAsset {
+    pub name: String,
+    pub sheet_name: String,
+    pub tile_width: SidTileDim,
+    pub tile_height: SidTileDim,
+    pub tile_count: u32,
+    pub columns: u32,
+    /// Empty pixels between two tiles of the sheet.
+    #[serde(default)]
+    pub spacing: u32,
+    /// Empty pixels around the tiles of the sheet.
+    #[serde(default)]
+    pub margin: u32,
+}
+
+impl SidTilesetAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), String::new(), 0, 0, 0, 0)
+    }
+
+    pub fn with_data(
+        name: String,
+        sheet_name: String,
+        tile_width: SidTileDim,
+        tile_height: SidTileDim,
+        tile_count: u32,
+        columns: u32,
+    ) -> Self {
+        Self {
+            name,
+            sheet_name,
+            tile_width,
+            tile_height,
+            tile_count,
+            columns,
+            spacing: 0,
+            margin: 0,
+        }
+    }
+}
+
+impl SidPackedAsset for SidTilesetAsset {
+    fn namespace() -> &'static str {
+        sid::get_tileset_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create tileset asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Tileset used by a map, whose tiles start at `first_gid`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidTilemapTilesetAsset {
+    pub first_gid: SidTileGid,
+    pub tileset_name: String,
+}
+
+impl SidTilemapTilesetAsset {
+    pub fn new() -> Self {
+        Self::with_data(0, String::new())
+    }
+
+    pub fn with_data(first_gid: SidTileGid, tileset_name: String) -> Self {
+        Self {
+            first_gid,
+            tileset_name,
+        }
+    }
+}
+
+/// Row-major tile ids of a map layer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidTilemapLayerAsset {
+    pub name: String,
+    #[serde(default = "default_layer_visible")]
+    pub visible: bool,
+    pub tiles: Vec<SidTileGid>,
+}
+
+fn default_layer_visible() -> bool {
+    true
+}
+
+impl SidTilemapLayerAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), true, vec![])
+    }
+
+    pub fn with_data(name: String, visible: bool, tiles: Vec<SidTileGid>) -> Self {
+        Self {
+            name,
+            visible,
+            tiles,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidTilemapAsset {
+    pub name: String,
+    /// Size of the map, in tiles.
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: SidTileDim,
+    pub tile_height: SidTileDim,
+    pub tilesets: Vec<SidTilemapTilesetAsset>,
+    pub layers: Vec<SidTilemapLayerAsset>,
+}
+
+impl SidTilemapAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), 0, 0, 0, 0, vec![], vec![])
+    }
+
+    pub fn with_data(
+        name: String,
+        width: u32,
+        height: u32,
+        tile_width: SidTileDim,
+        tile_height: SidTileDim,
+        tilesets: Vec<SidTilemapTilesetAsset>,
+        layers: Vec<SidTilemapLayerAsset>,
+    ) -> Self {
+        Self {
+            name,
+            width,
+            height,
+            tile_width,
+            tile_height,
+            tilesets,
+            layers,
+        }
+    }
+}
+
+impl SidPackedAsset for SidTilemapAsset {
+    fn namespace() -> &'static str {
+        sid::get_tilemap_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create tilemap asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidShaderTarget {
+    /// GLSL sources, packed as-is.
+    #[default]
+    Glsl,
+    /// SPIR-V modules, compiled at pack time.
+    Spirv,
+}
+
+/// Shader program made of a vertex and a fragment stage, from GLSL sources next
+/// to the asset.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidShaderAsset {
+    pub name: String,
+    #[serde(with = "portable_path")]
+    pub vertex_path: PathBuf,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub vertex_from_path: PathBuf,
+    #[serde(with = "portable_path")]
+    pub fragment_path: PathBuf,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub fragment_from_path: PathBuf,
+    #[serde(default)]
+    pub target: SidShaderTarget,
+    /// Parses and validates both stages when packing, so that broken shaders
+    /// are caught before the engine loads them. Always done for SPIR-V.
+    #[serde(default = "default_shader_validate")]
+    pub validate: bool,
+}
+
+fn default_shader_validate() -> bool {
+    true
+}
+
+impl SidShaderAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let vertex_path = PathBuf::new();
+        let vertex_from_path = PathBuf::new();
+        let fragment_path = PathBuf::new();
+        let fragment_from_path = PathBuf::new();
+
+        Self::with_data(
+            name,
+            vertex_path,
+            vertex_from_path,
+            fragment_path,
+            fragment_from_path,
+        )
+    }
+
+    pub fn with_data(
+        name: String,
+        vertex_path: PathBuf,
+        vertex_from_path: PathBuf,
+        fragment_path: PathBuf,
+        fragment_from_path: PathBuf,
+    ) -> Self {
+        Self {
+            name,
+            vertex_path,
+            vertex_from_path,
+            fragment_path,
+            fragment_from_path,
+            target: SidShaderTarget::default(),
+            validate: default_shader_validate(),
+        }
+    }
+}
+
+impl SidPackedAsset for SidShaderAsset {
+    fn namespace() -> &'static str {
+        sid::get_shader_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create shader asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        for (from_path, path) in [
+            (&self.vertex_from_path, &self.vertex_path),
+            (&self.fragment_from_path, &self.fragment_path),
+        ] {
+            let out_source_path = out_path.join(path);
+
+            if *from_path != out_source_path {
+                fs::copy(from_path, &out_source_path).map_err(|error| {
+                    SidAssetError::IO(format!(
+                        "Unable to copy file from {:?} to {:?}: {}",
+                        from_path, out_source_path, error
+                    ))
+                })?;
+            }
+        }
+
+        let out_json_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_json_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_json_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Blending of what a material draws over what is already drawn.
+#[repr(u8)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidBlendMode {
+    Opaque = 0,
+    /// Blends by the alpha of the source, e.g. for sprites.
+    #[default]
+    Alpha = 1,
+    /// Blends colors whose alpha is already multiplied in.
+    Premultiplied = 2,
+    Additive = 3,
+    Multiply = 4,
+}
+
+impl SidBlendMode {
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(SidBlendMode::Opaque),
+            1 => Some(SidBlendMode::Alpha),
+            2 => Some(SidBlendMode::Premultiplied),
+            3 => Some(SidBlendMode::Additive),
+            4 => Some(SidBlendMode::Multiply),
+            _ => None,
+        }
+    }
+}
+
+/// Default value of a float uniform, or of a vector of up to four floats.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SidUniformValue {
+    Float(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+}
+
+impl SidUniformValue {
+    pub fn components(&self) -> &[f32] {
+        match self {
+            SidUniformValue::Float(value) => std::slice::from_ref(value),
+            SidUniformValue::Vec2(values) => values,
+            SidUniformValue::Vec3(values) => values,
+            SidUniformValue::Vec4(values) => values,
+        }
+    }
+
+    pub fn from_components(components: &[f32]) -> Option<Self> {
+        match *components {
+            [x] => Some(SidUniformValue::Float(x)),
+            [x, y] => Some(SidUniformValue::Vec2([x, y])),
+            [x, y, z] => Some(SidUniformValue::Vec3([x, y, z])),
+            [x, y, z, w] => Some(SidUniformValue::Vec4([x, y, z, w])),
+            _ => None,
+        }
+    }
+}
+
+/// Shader along with the textures and uniform values it draws with. Other
+/// assets are referenced by name, and resolved to ids when packing.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidMaterialAsset {
+    pub name: String,
+    pub shader_name: String,
+    #[serde(default)]
+    pub blend_mode: SidBlendMode,
+    /// Names of the textures bound to the samplers of the shader, by sampler
+    /// name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub textures: BTreeMap<String, String>,
+    /// Values of the uniforms of the shader, by uniform name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub uniforms: BTreeMap<String, SidUniformValue>,
+}
+
+impl SidMaterialAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), String::new())
+    }
+
+    pub fn with_data(name: String, shader_name: String) -> Self {
+        Self {
+            name,
+            shader_name,
+            blend_mode: SidBlendMode::default(),
+            textures: BTreeMap::new(),
+            uniforms: BTreeMap::new(),
+        }
+    }
+}
+
+impl SidPackedAsset for SidMaterialAsset {
+    fn namespace() -> &'static str {
+        sid::get_material_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create material asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// File packed verbatim, for data the engine loads as-is (lookup tables, binary
+/// configurations, etc.).
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidBlobAsset {
+    pub name: String,
+    #[serde(with = "portable_path")]
+    pub blob_path: PathBuf,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub blob_from_path: PathBuf,
+}
+
+impl SidBlobAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let blob_path = PathBuf::new();
+        let blob_from_path = PathBuf::new();
+
+        Self::with_data(name, blob_path, blob_from_path)
+    }
+
+    pub fn with_data(name: String, blob_path: PathBuf, blob_from_path: PathBuf) -> Self {
+        Self {
+            name,
+            blob_path,
+            blob_from_path,
+        }
+    }
+}
+
+impl SidPackedAsset for SidBlobAsset {
+    fn namespace() -> &'static str {
+        sid::get_blob_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create blob asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_blob_path = out_path.join(&self.blob_path);
+
+        if let Some(folder) = out_blob_path.parent() {
+            fs::create_dir_all(folder).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to create folder at path {:?}: {}",
+                    folder, error
+                ))
+            })?;
+        }
+
+        if self.blob_from_path != out_blob_path {
+            fs::copy(&self.blob_from_path, &out_blob_path).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to copy file from {:?} to {:?}: {}",
+                    self.blob_from_path, out_blob_path, error
+                ))
+            })?;
+        }
+
+        let out_json_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_json_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_json_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Component of a prefab. Other assets are referenced by name, and resolved to
+/// ids when packing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SidPrefabComponentAsset {
+    Sprite {
+        sheet_name: String,
+    },
+    Animation {
+        animation_name: String,
+    },
+    /// Box collider, relative to the entity origin.
+    Collider {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(default)]
+        is_trigger: bool,
+    },
+    /// Game-specific values, read by the engine as strings.
+    Custom {
+        values: BTreeMap<String, String>,
+    },
+}
+
+/// Entity definition, made of components.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidPrefabAsset {
+    pub name: String,
+    pub components: Vec<SidPrefabComponentAsset>,
+}
+
+impl SidPrefabAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let components = vec![];
+
+        Self::with_data(name, components)
+    }
+
+    pub fn with_data(name: String, components: Vec<SidPrefabComponentAsset>) -> Self {
+        Self { name, components }
+    }
+}
+
+impl SidPackedAsset for SidPrefabAsset {
+    fn namespace() -> &'static str {
+        sid::get_prefab_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create prefab asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Prefab instance placed in a scene.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SidSceneEntityAsset {
+    /// Name the engine finds the entity by, if any, e.g. a spawn point.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub prefab_name: String,
+    #[serde(flatten)]
+    pub transform: SidBoneTransform,
+    /// Draw order, entities of higher layers being drawn on top.
+    #[serde(default)]
+    pub layer: i32,
+    /// Components replacing those of the same type in the prefab, or added to
+    /// them if it has none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<SidPrefabComponentAsset>,
+}
+
+impl SidSceneEntityAsset {
+    pub fn new() -> Self {
+        Self::with_data(String::new(), SidBoneTransform::default())
+    }
+
+    pub fn with_data(prefab_name: String, transform: SidBoneTransform) -> Self {
+        Self {
+            name: None,
+            prefab_name,
+            transform,
+            layer: 0,
+            overrides: vec![],
+        }
+    }
+}
+
+/// Level or screen, made of prefab instances.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidSceneAsset {
+    pub name: String,
+    pub entities: Vec<SidSceneEntityAsset>,
+}
+
+impl SidSceneAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let entities = vec![];
+
+        Self::with_data(name, entities)
+    }
+
+    pub fn with_data(name: String, entities: Vec<SidSceneEntityAsset>) -> Self {
+        Self { name, entities }
+    }
+}
+
+impl SidPackedAsset for SidSceneAsset {
+    fn namespace() -> &'static str {
+        sid::get_scene_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create scene asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Localized strings, from one CSV or Fluent file per locale next to the asset.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidStringTableAsset {
+    pub name: String,
+    /// Path of the strings file of every locale, by locale name (e.g. "en").
+    #[serde(with = "portable_path::map")]
+    pub locales: BTreeMap<String, PathBuf>,
+}
+
+impl SidStringTableAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let locales = BTreeMap::new();
+
+        Self::with_data(name, locales)
+    }
+
+    pub fn with_data(name: String, locales: BTreeMap<String, PathBuf>) -> Self {
+        Self { name, locales }
+    }
+}
+
+impl SidPackedAsset for SidStringTableAsset {
+    fn namespace() -> &'static str {
+        sid::get_string_table_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create string table asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Image packed on its own, without the metadata of a sprite sheet (e.g.
+/// backgrounds and UI panels).
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidTextureAsset {
+    pub name: String,
+    #[serde(with = "portable_path")]
+    pub image_path: PathBuf,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub image_from_path: PathBuf,
+    /// Color space of the image.
+    #[serde(default)]
+    pub color_space: SidColorSpace,
+    /// Pixel layout of the packed image.
+    #[serde(default)]
+    pub pixel_format: SidPixelFormat,
+    /// Name of the shared palette the image is mapped to, packing it as 8-bit
+    /// indices.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+    /// Color made transparent, e.g. magenta in legacy art, as red, green and
+    /// blue values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_key: Option<[u8; 3]>,
+}
+
+impl SidTextureAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let image_path = PathBuf::new();
+        let image_from_path = PathBuf::new();
+
+        Self::with_data(name, image_path, image_from_path)
+    }
+
+    pub fn with_data(name: String, image_path: PathBuf, image_from_path: PathBuf) -> Self {
+        Self {
+            name,
+            image_path,
+            image_from_path,
+            color_space: SidColorSpace::default(),
+            pixel_format: SidPixelFormat::default(),
+            palette: None,
+            color_key: None,
+        }
+    }
+}
+
+impl SidPackedAsset for SidTextureAsset {
+    fn namespace() -> &'static str {
+        sid::get_texture_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create texture asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_image_path = out_path.join(&self.image_path);
+
+        if self.image_from_path != out_image_path {
+            fs::copy(&self.image_from_path, &out_image_path).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to copy file from {:?} to {:?}: {}",
+                    self.image_from_path, out_image_path, error
+                ))
+            })?;
+        }
+
+        let out_json_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_json_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_json_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Colors shared by indexed sprite sheets and textures, read in order from an
+/// image (e.g. a strip with one pixel per color).
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidPaletteAsset {
+    pub name: String,
+    #[serde(with = "portable_path")]
+    pub image_path: PathBuf,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub image_from_path: PathBuf,
+}
+
+impl SidPaletteAsset {
+    pub fn new() -> Self {
+        let name = String::new();
+        let image_path = PathBuf::new();
+        let image_from_path = PathBuf::new();
+
+        Self::with_data(name, image_path, image_from_path)
+    }
+
+    pub fn with_data(name: String, image_path: PathBuf, image_from_path: PathBuf) -> Self {
+        Self {
+            name,
+            image_path,
+            image_from_path,
+        }
+    }
+}
+
+impl SidPackedAsset for SidPaletteAsset {
+    fn namespace() -> &'static str {
+        sid::get_palette_namespace()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_to_subfolder<P: AsRef<Path>>(
+        &self,
+        folder_path: P,
+        subfolder: &Path,
+    ) -> SidAssetResult<()> {
+        let out_json = serde_json::to_string_pretty(self).map_err(|error| {
+            SidAssetError::Malformed(format!(
+                "Unable to create palette asset {:?}: {error}",
+                self.name
+            ))
+        })?;
+
+        let out_path = folder_path.as_ref().join(Self::namespace()).join(subfolder);
+
+        fs::create_dir_all(&out_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to create folder at path {:?}: {}",
+                &out_path, error
+            ))
+        })?;
+
+        let out_image_path = out_path.join(&self.image_path);
+
+        if self.image_from_path != out_image_path {
+            fs::copy(&self.image_from_path, &out_image_path).map_err(|error| {
+                SidAssetError::IO(format!(
+                    "Unable to copy file from {:?} to {:?}: {}",
+                    self.image_from_path, out_image_path, error
+                ))
+            })?;
+        }
+
+        let out_json_path = out_path.join(format!("{}.json", self.name));
+
+        fs::write(&out_json_path, out_json).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to write JSON to file {:?}: {}",
+                out_json_path, error
+            ))
+        })?;
+
+        Ok(())
+    }
+}
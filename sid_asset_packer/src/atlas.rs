@@ -0,0 +1,1045 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use image::{GenericImage, GenericImageView, Rgba, RgbaImage};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAnimationFrameAsset, SidAnimationFrameDims,
+        SidAnimationFramePos, SidAnimationFrameTrim, SidAssetError, SidAssetResult, SidColorSpace,
+        SidNineSliceAsset, SidPackedAsset, SidSliceAsset, SidSlicePivot, SidSliceRect,
+        SidSpriteSheetAsset, SidTextureAsset,
+    },
+    error::{SidToolError, SidToolResult},
+    resource::SidAssetProcessor,
+    sid::{self, SidAnimationFrameCoord},
+    sid_error, sid_info, sid_warning, texture,
+};
+
+pub const DEFAULT_ATLAS_MAX_SIZE: u32 = 2048;
+pub static DEFAULT_ATLAS_NAME: &str = "atlas";
+
+/// Normal pointing out of the screen, filling the normal map pages where
+/// sheets without normal map are packed.
+const FLAT_NORMAL: Rgba<u8> = Rgba([128, 128, 255, 255]);
+
+#[derive(Debug)]
+pub struct SidAtlasOptions {
+    /// Prefix of the generated atlas pages, suffixed by their atlas group, if
+    /// any, and by the page index.
+    pub name: String,
+    /// Maximum width and height of an atlas page.
+    pub max_size: u32,
+    /// Empty pixels left between two packed regions.
+    pub padding: u32,
+    /// Pixels the edges of each packed region are repeated over, so that
+    /// filtered sampling does not blend in their neighbors.
+    pub extrude: u32,
+    /// Crop the frames to their opaque pixels before packing them.
+    pub trim: bool,
+}
+
+impl SidAtlasOptions {
+    pub fn new() -> Self {
+        Self {
+            name: DEFAULT_ATLAS_NAME.to_string(),
+            max_size: DEFAULT_ATLAS_MAX_SIZE,
+            padding: 1,
+            extrude: 0,
+            trim: false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SidAtlasRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl SidAtlasRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Whether `rect` lies within this region.
+    pub fn contains(&self, rect: &SidSliceRect) -> bool {
+        let (x, y) = (i64::from(self.x), i64::from(self.y));
+
+        i64::from(rect.x) >= x
+            && i64::from(rect.y) >= y
+            && i64::from(rect.x) + i64::from(rect.width) <= x + i64::from(self.width)
+            && i64::from(rect.y) + i64::from(rect.height) <= y + i64::from(self.height)
+    }
+
+    /// Region of the sheet covered by `frame`.
+    pub fn from_frame(frame: &SidAnimationFrameAsset) -> Self {
+        let dims = frame.sheet_dims();
+
+        Self::new(
+            frame.pos.x as u32,
+            frame.pos.y as u32,
+            dims.width as u32,
+            dims.height as u32,
+        )
+    }
+}
+
+/// Crops `frame` to the opaque pixels of its region of the sheet `image`,
+/// keeping track of the removed borders in its trim. Returns whether the frame
+/// changed. Rotated frames and fully transparent ones are left as is.
+pub fn trim_frame(frame: &mut SidAnimationFrameAsset, image: &RgbaImage) -> SidAssetResult<bool> {
+    if frame.rotated {
+        return Ok(false);
+    }
+
+    let x = frame.pos.x as u32;
+    let y = frame.pos.y as u32;
+    let width = frame.dims.width as u32;
+    let height = frame.dims.height as u32;
+
+    if x + width > image.width() || y + height > image.height() {
+        return Err(SidAssetError::Malformed(format!(
+            "Frame at ({x}, {y}) is out of the bounds of its {}x{} sheet",
+            image.width(),
+            image.height()
+        )));
+    }
+
+    // Opaque pixel bounds, as (min x, min y, max x, max y).
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+
+    for j in 0..height {
+        for i in 0..width {
+            if image.get_pixel(x + i, y + j)[3] == 0 {
+                continue;
+            }
+
+            bounds = Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(i), min_y.min(j), max_x.max(i), max_y.max(j))
+                }
+                None => (i, j, i, j),
+            });
+        }
+    }
+
+    let Some((min_x, min_y, max_x, max_y)) = bounds else {
+        return Ok(false);
+    };
+
+    if (min_x, min_y, max_x, max_y) == (0, 0, width - 1, height - 1) {
+        return Ok(false);
+    }
+
+    // Frames trimmed on export keep their source size.
+    let trim = frame.trim.take().unwrap_or_else(|| {
+        SidAnimationFrameTrim::with_data(SidAnimationFramePos::new(), frame.dims.clone())
+    });
+
+    let offset = SidAnimationFramePos::with_coords(
+        trim.offset.x + min_x as SidAnimationFrameCoord,
+        trim.offset.y + min_y as SidAnimationFrameCoord,
+    );
+
+    frame.trim = Some(SidAnimationFrameTrim::with_data(offset, trim.source_dims));
+    frame.pos = SidAnimationFramePos::with_coords(
+        (x + min_x) as SidAnimationFrameCoord,
+        (y + min_y) as SidAnimationFrameCoord,
+    );
+    frame.dims = SidAnimationFrameDims::with_width_and_height(
+        (max_x - min_x + 1) as sid::SidAnimationFrameDim,
+        (max_y - min_y + 1) as sid::SidAnimationFrameDim,
+    );
+
+    Ok(true)
+}
+
+#[derive(Debug, Clone)]
+struct SidSkylineSegment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Bottom-left skyline bin packer: the top edge of the packed rectangles is
+/// tracked as a list of horizontal segments, and each new rectangle is placed
+/// where it ends up the lowest.
+#[derive(Debug, Clone)]
+pub struct SidSkylinePacker {
+    width: u32,
+    height: u32,
+    skyline: Vec<SidSkylineSegment>,
+    used_width: u32,
+    used_height: u32,
+}
+
+impl SidSkylinePacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            skyline: vec![SidSkylineSegment { x: 0, y: 0, width }],
+            used_width: 0,
+            used_height: 0,
+        }
+    }
+
+    /// Width and height actually covered by the packed rectangles.
+    pub fn used_size(&self) -> (u32, u32) {
+        (self.used_width, self.used_height)
+    }
+
+    pub fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let mut best: Option<(usize, u32, u32)> = None;
+
+        for i in 0..self.skyline.len() {
+            let x = self.skyline[i].x;
+
+            if let Some(y) = self.fit(i, width, height) {
+                let is_better = match best {
+                    Some((_, best_x, best_y)) => y < best_y || (y == best_y && x < best_x),
+                    None => true,
+                };
+
+                if is_better {
+                    best = Some((i, x, y));
+                }
+            }
+        }
+
+        let (i, x, y) = best?;
+        self.add_segment(i, x, y + height, width);
+        self.used_width = self.used_width.max(x + width);
+        self.used_height = self.used_height.max(y + height);
+        Some((x, y))
+    }
+
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[index].x;
+
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut remaining = width as i64;
+        let mut y = 0;
+
+        for segment in &self.skyline[index..] {
+            if remaining <= 0 {
+                break;
+            }
+
+            y = y.max(segment.y);
+
+            if y + height > self.height {
+                return None;
+            }
+
+            remaining -= segment.width as i64;
+        }
+
+        Some(y)
+    }
+
+    fn add_segment(&mut self, index: usize, x: u32, y: u32, width: u32) {
+        self.skyline
+            .insert(index, SidSkylineSegment { x, y, width });
+
+        let right = x + width;
+        let i = index + 1;
+
+        // Shrink or remove the segments now covered by the new one.
+        while i < self.skyline.len() {
+            let segment = &mut self.skyline[i];
+
+            if segment.x >= right {
+                break;
+            }
+
+            let segment_right = segment.x + segment.width;
+
+            if segment_right <= right {
+                self.skyline.remove(i);
+                continue;
+            }
+
+            segment.width = segment_right - right;
+            segment.x = right;
+            break;
+        }
+
+        // Merge neighbors at the same height.
+        let mut i = 0;
+
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].y == self.skyline[i + 1].y {
+                self.skyline[i].width += self.skyline[i + 1].width;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+struct SidAtlasSource {
+    image: RgbaImage,
+    normal_map: Option<RgbaImage>,
+    emissive_map: Option<RgbaImage>,
+    atlas_group: Option<String>,
+    pivot: Option<SidSlicePivot>,
+}
+
+struct SidAtlasGroup {
+    // Sprite sheet the regions are cut from.
+    sheet_name: String,
+    // Atlas group of the sprite sheet, whose pages no other group shares.
+    atlas_group: Option<String>,
+    // Animation definition to rewrite, if any; a sheet without definition is
+    // packed as a single region.
+    def: Option<SidAnimationDefAsset>,
+    regions: Vec<SidAtlasRect>,
+}
+
+// Region index and atlas position of a packed region.
+type SidRegionPlacement = (usize, u32, u32);
+
+struct SidAtlasPage {
+    atlas_group: Option<String>,
+    packer: SidSkylinePacker,
+    placements: Vec<(usize, usize, u32, u32)>,
+}
+
+fn load_sources<P: AsRef<Path>>(
+    assets_input_path: P,
+) -> SidAssetResult<BTreeMap<String, SidAtlasSource>> {
+    let folder = assets_input_path
+        .as_ref()
+        .join(SidSpriteSheetAsset::namespace());
+
+    let mut sources = BTreeMap::new();
+    let textures = load_texture_paths(&assets_input_path)?;
+
+    for path in SidSpriteSheetAsset::compatible_entries(&folder) {
+        let sheet = SidSpriteSheetAsset::load_asset(&path)
+            .map_err(|error| SidAssetError::Malformed(error.to_string()))?;
+
+        let texture_path = sheet
+            .texture_path(&path)
+            .map_err(|error| SidAssetError::IO(error.to_string()))?;
+
+        let mut image = open_image(&texture_path)?;
+
+        // Pages have no color key, so keyed pixels are cleared beforehand.
+        if let Some(color_key) = sheet.color_key {
+            image = texture::apply_color_key(image.into(), color_key).into_rgba8();
+        }
+
+        let normal_map = load_map(&textures, &sheet, sheet.normal_map.as_deref(), &image)?;
+        let emissive_map = load_map(&textures, &sheet, sheet.emissive_map.as_deref(), &image)?;
+
+        sources.insert(
+            sheet.name.clone(),
+            SidAtlasSource {
+                image,
+                normal_map,
+                emissive_map,
+                atlas_group: sheet.atlas_group,
+                pivot: sheet.pivot,
+            },
+        );
+    }
+
+    Ok(sources)
+}
+
+fn open_image(path: &Path) -> SidAssetResult<RgbaImage> {
+    image::open(path)
+        .map(|image| image.to_rgba8())
+        .map_err(|error| SidAssetError::IO(format!("Unable to open image {:?}: {error}", path)))
+}
+
+/// Image paths of the textures of `assets_input_path`, by name.
+fn load_texture_paths<P: AsRef<Path>>(
+    assets_input_path: P,
+) -> SidAssetResult<HashMap<String, PathBuf>> {
+    let folder = assets_input_path
+        .as_ref()
+        .join(SidTextureAsset::namespace());
+    let mut paths = HashMap::new();
+
+    if !folder.is_dir() {
+        return Ok(paths);
+    }
+
+    for path in SidTextureAsset::compatible_entries(&folder) {
+        let texture = SidTextureAsset::load_asset(&path)
+            .map_err(|error| SidAssetError::Malformed(error.to_string()))?;
+
+        let texture_path = texture
+            .texture_path(&path)
+            .map_err(|error| SidAssetError::IO(error.to_string()))?;
+
+        paths.insert(texture.name, texture_path);
+    }
+
+    Ok(paths)
+}
+
+/// Image of the map `name` of `sheet`, if it has one matching its image.
+fn load_map(
+    textures: &HashMap<String, PathBuf>,
+    sheet: &SidSpriteSheetAsset,
+    name: Option<&str>,
+    image: &RgbaImage,
+) -> SidAssetResult<Option<RgbaImage>> {
+    let Some(name) = name else {
+        return Ok(None);
+    };
+
+    let Some(path) = textures.get(name) else {
+        sid_warning!(
+            "Sprite sheet {:?} references unknown texture {:?}, leaving it out of the atlas.",
+            sheet.name,
+            name
+        );
+        return Ok(None);
+    };
+
+    let map = open_image(path)?;
+
+    if map.dimensions() != image.dimensions() {
+        sid_warning!(
+            "Texture {:?} of sprite sheet {:?} is {}x{}, not the size of the sheet, leaving it out of the atlas.",
+            name,
+            sheet.name,
+            map.width(),
+            map.height()
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(map))
+}
+
+/// Copies `region` of `source` to `x`, `y` in `image`, repeating its edge
+/// pixels `extrude` times around it.
+fn copy_region(
+    image: &mut RgbaImage,
+    source: &RgbaImage,
+    region: &SidAtlasRect,
+    (x, y): (u32, u32),
+    extrude: u32,
+) -> SidAssetResult<()> {
+    let view = source.view(region.x, region.y, region.width, region.height);
+
+    image
+        .copy_from(&*view, x, y)
+        .map_err(|error| SidAssetError::IO(format!("Unable to copy frame to atlas: {error}")))?;
+
+    if extrude == 0 || region.width == 0 || region.height == 0 {
+        return Ok(());
+    }
+
+    let right = x + region.width - 1;
+    let bottom = y + region.height - 1;
+
+    for j in y..=bottom {
+        let (left_pixel, right_pixel) = (*image.get_pixel(x, j), *image.get_pixel(right, j));
+
+        for i in 1..=extrude {
+            image.put_pixel(x - i, j, left_pixel);
+            image.put_pixel(right + i, j, right_pixel);
+        }
+    }
+
+    // Rows are extruded from the extruded columns, filling the corners.
+    for i in x - extrude..=right + extrude {
+        let (top_pixel, bottom_pixel) = (*image.get_pixel(i, y), *image.get_pixel(i, bottom));
+
+        for j in 1..=extrude {
+            image.put_pixel(i, y - j, top_pixel);
+            image.put_pixel(i, bottom + j, bottom_pixel);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes `image`, the `suffix` map of the atlas page `page_name`, as a
+/// texture, returning the name of the texture.
+fn write_map_page(
+    image: Option<RgbaImage>,
+    page_name: &str,
+    suffix: &str,
+    color_space: SidColorSpace,
+    assets_output_path: &Path,
+) -> SidToolResult<Option<String>> {
+    let Some(image) = image else {
+        return Ok(None);
+    };
+
+    let name = format!("{page_name}_{suffix}");
+    let textures_path = assets_output_path.join(SidTextureAsset::namespace());
+
+    fs::create_dir_all(&textures_path).map_err(|error| {
+        SidAssetError::IO(format!(
+            "Unable to create folder at path {:?}: {error}",
+            textures_path
+        ))
+    })?;
+
+    let image_path = PathBuf::from(format!("{name}.png"));
+    let image_from_path = textures_path.join(&image_path);
+
+    image.save(&image_from_path).map_err(|error| {
+        SidAssetError::IO(format!(
+            "Unable to save atlas {:?}: {error}",
+            image_from_path
+        ))
+    })?;
+
+    let mut texture = SidTextureAsset::with_data(name.clone(), image_path, image_from_path);
+    texture.color_space = color_space;
+    texture.write_to_folder(assets_output_path)?;
+
+    Ok(Some(name))
+}
+
+fn load_all<T: SidAssetProcessor, P: AsRef<Path>>(assets_input_path: P) -> SidAssetResult<Vec<T>> {
+    let folder = assets_input_path.as_ref().join(T::namespace());
+    let mut assets = vec![];
+
+    // Assets folders only hold the namespaces they use.
+    if !folder.is_dir() {
+        return Ok(assets);
+    }
+
+    for path in T::compatible_entries(&folder) {
+        let asset =
+            T::load_asset(&path).map_err(|error| SidAssetError::Malformed(error.to_string()))?;
+        assets.push(asset);
+    }
+
+    Ok(assets)
+}
+
+/// Crops the frames of `defs` to their opaque pixels, except the ones holding
+/// a nine-slice, whose region must stay whole. Returns the number of cropped
+/// frames.
+fn trim_defs(
+    defs: &mut [SidAnimationDefAsset],
+    sources: &BTreeMap<String, SidAtlasSource>,
+    nine_slices: &[SidNineSliceAsset],
+) -> SidAssetResult<usize> {
+    let mut count = 0;
+
+    for def in defs {
+        let Some(source) = sources.get(&def.sheet_name) else {
+            continue;
+        };
+
+        for frame in &mut def.frames {
+            let region = SidAtlasRect::from_frame(frame);
+
+            let holds_nine_slice = nine_slices.iter().any(|nine_slice| {
+                nine_slice.sheet_name == def.sheet_name && region.contains(&nine_slice.region)
+            });
+
+            if !holds_nine_slice && trim_frame(frame, &source.image)? {
+                count += 1;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+fn build_groups(
+    sources: &BTreeMap<String, SidAtlasSource>,
+    mut defs: Vec<SidAnimationDefAsset>,
+) -> Vec<SidAtlasGroup> {
+    defs.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut groups = vec![];
+    let mut sheets_with_def = vec![];
+
+    for def in defs {
+        // Regions are cut from a single sheet.
+        if !def.pages.is_empty() {
+            sid_error!(
+                "Animation definition {:?} is already split over several sprite sheets, skipping it.",
+                def.name
+            );
+            continue;
+        }
+
+        if !sources.contains_key(&def.sheet_name) {
+            sid_error!(
+                "Animation definition {:?} references unknown sprite sheet {:?}, skipping it.",
+                def.name,
+                def.sheet_name
+            );
+            continue;
+        }
+
+        let mut regions = vec![];
+
+        for frame in &def.frames {
+            let region = SidAtlasRect::from_frame(frame);
+
+            if !regions.contains(&region) {
+                regions.push(region);
+            }
+        }
+
+        sheets_with_def.push(def.sheet_name.clone());
+
+        groups.push(SidAtlasGroup {
+            sheet_name: def.sheet_name.clone(),
+            atlas_group: sources[&def.sheet_name].atlas_group.clone(),
+            def: Some(def),
+            regions,
+        });
+    }
+
+    for (sheet_name, source) in sources {
+        if sheets_with_def.contains(sheet_name) {
+            continue;
+        }
+
+        groups.push(SidAtlasGroup {
+            sheet_name: sheet_name.clone(),
+            atlas_group: source.atlas_group.clone(),
+            def: None,
+            regions: vec![SidAtlasRect::new(
+                0,
+                0,
+                source.image.width(),
+                source.image.height(),
+            )],
+        });
+    }
+
+    groups
+}
+
+/// Regions of `group`, taller ones first, which give the skyline packer much
+/// better results.
+fn insertion_order(group: &SidAtlasGroup) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..group.regions.len()).collect();
+
+    order.sort_by(|&a, &b| {
+        let a = &group.regions[a];
+        let b = &group.regions[b];
+        b.height.cmp(&a.height).then(b.width.cmp(&a.width))
+    });
+
+    order
+}
+
+/// Packs `region` in `packer`, returning its position within its extruded
+/// borders.
+fn try_insert_region(
+    packer: &mut SidSkylinePacker,
+    region: &SidAtlasRect,
+    options: &SidAtlasOptions,
+) -> Option<(u32, u32)> {
+    let border = 2 * options.extrude + options.padding;
+    let (x, y) = packer.insert(region.width + border, region.height + border)?;
+    Some((x + options.extrude, y + options.extrude))
+}
+
+/// Packs every region of `group` in `packer`.
+fn try_insert_group(
+    packer: &SidSkylinePacker,
+    group: &SidAtlasGroup,
+    options: &SidAtlasOptions,
+) -> Option<(SidSkylinePacker, Vec<SidRegionPlacement>)> {
+    let mut packer = packer.clone();
+    let order = insertion_order(group);
+    let mut placements = Vec::with_capacity(order.len());
+
+    for region_index in order {
+        let (x, y) = try_insert_region(&mut packer, &group.regions[region_index], options)?;
+        placements.push((region_index, x, y));
+    }
+
+    Some((packer, placements))
+}
+
+/// Packs the regions of `group`, too large for a single page, over as many
+/// pages as needed.
+fn split_group(
+    pages: &mut Vec<SidAtlasPage>,
+    group_index: usize,
+    group: &SidAtlasGroup,
+    options: &SidAtlasOptions,
+) -> SidAssetResult<()> {
+    sid_info!(
+        "Sprite sheet {:?} does not fit in a {}x{} atlas page, splitting it.",
+        group.sheet_name,
+        options.max_size,
+        options.max_size
+    );
+
+    for region_index in insertion_order(group) {
+        let region = &group.regions[region_index];
+
+        let placement = pages
+            .iter_mut()
+            .filter(|page| page.atlas_group == group.atlas_group)
+            .find_map(|page| {
+                let (x, y) = try_insert_region(&mut page.packer, region, options)?;
+                Some((page, x, y))
+            });
+
+        if let Some((page, x, y)) = placement {
+            page.placements.push((group_index, region_index, x, y));
+            continue;
+        }
+
+        let mut packer = SidSkylinePacker::new(options.max_size, options.max_size);
+
+        let Some((x, y)) = try_insert_region(&mut packer, region, options) else {
+            return Err(SidAssetError::Malformed(format!(
+                "Frame {:?} of sprite sheet {:?} does not fit in a {}x{} atlas page",
+                region, group.sheet_name, options.max_size, options.max_size
+            )));
+        };
+
+        pages.push(SidAtlasPage {
+            atlas_group: group.atlas_group.clone(),
+            packer,
+            placements: vec![(group_index, region_index, x, y)],
+        });
+    }
+
+    Ok(())
+}
+
+fn pack_groups(
+    groups: &[SidAtlasGroup],
+    options: &SidAtlasOptions,
+) -> SidAssetResult<Vec<SidAtlasPage>> {
+    let mut pages: Vec<SidAtlasPage> = vec![];
+
+    for (group_index, group) in groups.iter().enumerate() {
+        let mut is_packed = false;
+
+        // The regions of a group land on the same page when they fit one, so
+        // that its animation definition references a single sprite sheet.
+        for page in pages
+            .iter_mut()
+            .filter(|page| page.atlas_group == group.atlas_group)
+        {
+            if let Some((packer, placements)) = try_insert_group(&page.packer, group, options) {
+                page.packer = packer;
+                page.placements.extend(
+                    placements
+                        .into_iter()
+                        .map(|(region_index, x, y)| (group_index, region_index, x, y)),
+                );
+                is_packed = true;
+                break;
+            }
+        }
+
+        if is_packed {
+            continue;
+        }
+
+        let packer = SidSkylinePacker::new(options.max_size, options.max_size);
+
+        match try_insert_group(&packer, group, options) {
+            Some((packer, placements)) => pages.push(SidAtlasPage {
+                atlas_group: group.atlas_group.clone(),
+                packer,
+                placements: placements
+                    .into_iter()
+                    .map(|(region_index, x, y)| (group_index, region_index, x, y))
+                    .collect(),
+            }),
+            None => split_group(&mut pages, group_index, group, options)?,
+        }
+    }
+
+    Ok(pages)
+}
+
+/// Packs every sprite sheet of `assets_input_path` into shared atlas pages,
+/// one set of pages per atlas group, and writes the resulting assets to
+/// `assets_output_path`: the atlas sprite sheets with their normal and
+/// emissive maps, the animation definitions with their frames moved to the
+/// atlas, and the animations unchanged. Sheets too large for a page are split
+/// over several ones.
+pub fn build_atlases<P1: AsRef<Path>, P2: AsRef<Path>>(
+    assets_input_path: P1,
+    assets_output_path: P2,
+    options: &SidAtlasOptions,
+) -> SidToolResult<()> {
+    if options.max_size == 0 || options.max_size > SidAnimationFrameCoord::MAX as u32 {
+        return Err(SidToolError::Malformed(format!(
+            "Invalid atlas size: {}",
+            options.max_size
+        )));
+    }
+
+    let assets_input_path = assets_input_path.as_ref();
+    let assets_output_path = assets_output_path.as_ref();
+
+    let sources = load_sources(assets_input_path)?;
+    let mut defs = load_all::<SidAnimationDefAsset, _>(assets_input_path)?;
+    let animations = load_all::<SidAnimationAsset, _>(assets_input_path)?;
+    let slices = load_all::<SidSliceAsset, _>(assets_input_path)?;
+    let nine_slices = load_all::<SidNineSliceAsset, _>(assets_input_path)?;
+
+    if options.trim {
+        let count = trim_defs(&mut defs, &sources, &nine_slices)?;
+        sid_info!("Trimmed {count} frame(s).");
+    }
+
+    let mut groups = build_groups(&sources, defs);
+    let pages = pack_groups(&groups, options)?;
+
+    let sheets_path = assets_output_path.join(SidSpriteSheetAsset::namespace());
+
+    fs::create_dir_all(&sheets_path).map_err(|error| {
+        SidAssetError::IO(format!(
+            "Unable to create folder at path {:?}: {error}",
+            sheets_path
+        ))
+    })?;
+
+    // Positions of every region in the atlas, per group.
+    let mut moved_regions: HashMap<(usize, usize), (String, u32, u32)> = HashMap::new();
+
+    // Pages are numbered per atlas group.
+    let mut page_counts: HashMap<Option<&str>, usize> = HashMap::new();
+
+    for page in &pages {
+        let page_index = page_counts.entry(page.atlas_group.as_deref()).or_default();
+
+        let page_name = match &page.atlas_group {
+            Some(atlas_group) => format!("{}_{atlas_group}_{page_index}", options.name),
+            None => format!("{}_{page_index}", options.name),
+        };
+
+        *page_index += 1;
+        let (width, height) = page.packer.used_size();
+        let mut image = RgbaImage::new(width, height);
+
+        // Pages get maps as soon as one of their sheets has some.
+        let page_sources: Vec<_> = page
+            .placements
+            .iter()
+            .map(|&(group_index, ..)| &sources[&groups[group_index].sheet_name])
+            .collect();
+
+        let mut normal_image = page_sources
+            .iter()
+            .any(|source| source.normal_map.is_some())
+            .then(|| RgbaImage::from_pixel(width, height, FLAT_NORMAL));
+
+        let mut emissive_image = page_sources
+            .iter()
+            .any(|source| source.emissive_map.is_some())
+            .then(|| RgbaImage::new(width, height));
+
+        for &(group_index, region_index, x, y) in &page.placements {
+            let group = &groups[group_index];
+            let region = &group.regions[region_index];
+            let source = &sources[&group.sheet_name];
+
+            if region.x + region.width > source.image.width()
+                || region.y + region.height > source.image.height()
+            {
+                return Err(SidToolError::Malformed(format!(
+                    "Frame {:?} is out of the bounds of sprite sheet {:?}",
+                    region, group.sheet_name
+                )));
+            }
+
+            copy_region(&mut image, &source.image, region, (x, y), options.extrude)?;
+
+            // Maps have the size of their sheet.
+            if let (Some(page_map), Some(map)) = (&mut normal_image, &source.normal_map) {
+                copy_region(page_map, map, region, (x, y), options.extrude)?;
+            }
+
+            if let (Some(page_map), Some(map)) = (&mut emissive_image, &source.emissive_map) {
+                copy_region(page_map, map, region, (x, y), options.extrude)?;
+            }
+
+            moved_regions.insert((group_index, region_index), (page_name.clone(), x, y));
+        }
+
+        let image_path = PathBuf::from(format!("{page_name}.png"));
+        let image_from_path = sheets_path.join(&image_path);
+
+        image.save(&image_from_path).map_err(|error| {
+            SidAssetError::IO(format!(
+                "Unable to save atlas {:?}: {error}",
+                image_from_path
+            ))
+        })?;
+
+        let mut sheet = SidSpriteSheetAsset::with_data(
+            page_name,
+            image_path,
+            image_from_path,
+            width as sid::SidSpriteSheetDim,
+            height as sid::SidSpriteSheetDim,
+            "RGBA8888".to_string(),
+        );
+
+        sheet.atlas_group = page.atlas_group.clone();
+        sheet.normal_map = write_map_page(
+            normal_image,
+            &sheet.name,
+            "normal",
+            SidColorSpace::Linear,
+            assets_output_path,
+        )?;
+        sheet.emissive_map = write_map_page(
+            emissive_image,
+            &sheet.name,
+            "emissive",
+            SidColorSpace::Srgb,
+            assets_output_path,
+        )?;
+        sheet.write_to_folder(assets_output_path)?;
+    }
+
+    for (group_index, group) in groups.iter_mut().enumerate() {
+        let def = match &mut group.def {
+            Some(def) => def,
+            None => continue,
+        };
+
+        // Pages mix sheets, so the frames keep the pivot of their sheet.
+        let sheet_pivot = &sources[&group.sheet_name].pivot;
+
+        // Pages holding the frames, in order of first use.
+        let mut page_names: Vec<String> = vec![];
+
+        for frame in &mut def.frames {
+            if frame.pivot.is_none() {
+                frame.pivot = sheet_pivot.clone();
+            }
+
+            let region = SidAtlasRect::from_frame(frame);
+
+            let region_index = group
+                .regions
+                .iter()
+                .position(|other| *other == region)
+                .expect("Every frame has a region");
+
+            let (page_name, x, y) = &moved_regions[&(group_index, region_index)];
+
+            let page = match page_names.iter().position(|name| name == page_name) {
+                Some(page) => page,
+                None => {
+                    page_names.push(page_name.clone());
+                    page_names.len() - 1
+                }
+            };
+
+            frame.page = page as sid::SidAnimationPageIndex;
+            frame.pos.x = *x as SidAnimationFrameCoord;
+            frame.pos.y = *y as SidAnimationFrameCoord;
+        }
+
+        let mut page_names = page_names.into_iter();
+
+        if let Some(sheet_name) = page_names.next() {
+            def.sheet_name = sheet_name;
+        }
+
+        def.pages = page_names.collect();
+        def.write_to_folder(assets_output_path)?;
+    }
+
+    for animation in animations {
+        animation.write_to_folder(assets_output_path)?;
+    }
+
+    // Slices are relative to the frames, so only their sheet changes.
+    for mut slice in slices {
+        let page_name = moved_regions
+            .iter()
+            .find(|((group_index, _), _)| groups[*group_index].sheet_name == slice.sheet_name)
+            .map(|(_, (page_name, _, _))| page_name.clone());
+
+        if let Some(page_name) = page_name {
+            slice.sheet_name = page_name;
+        }
+
+        slice.write_to_folder(assets_output_path)?;
+    }
+
+    // Nine-slices are regions of their sheet, so they move with the region
+    // holding them.
+    for mut nine_slice in nine_slices {
+        let rect = &nine_slice.region;
+
+        let moved = groups.iter().enumerate().find_map(|(group_index, group)| {
+            if group.sheet_name != nine_slice.sheet_name {
+                return None;
+            }
+
+            group
+                .regions
+                .iter()
+                .enumerate()
+                .find(|(_, region)| region.contains(rect))
+                .and_then(|(region_index, region)| {
+                    let (page_name, x, y) = moved_regions.get(&(group_index, region_index))?;
+
+                    Some((
+                        page_name.clone(),
+                        *x as sid::SidSliceCoord + rect.x - region.x as sid::SidSliceCoord,
+                        *y as sid::SidSliceCoord + rect.y - region.y as sid::SidSliceCoord,
+                    ))
+                })
+        });
+
+        match moved {
+            Some((page_name, x, y)) => {
+                nine_slice.sheet_name = page_name;
+                nine_slice.region.x = x;
+                nine_slice.region.y = y;
+            }
+            None => sid_warning!(
+                "Nine-slice {:?} is not within a frame of sprite sheet {:?}, leaving it as is.",
+                nine_slice.name,
+                nine_slice.sheet_name
+            ),
+        }
+
+        nine_slice.write_to_folder(assets_output_path)?;
+    }
+
+    sid_info!(
+        "Packed {} sprite sheet(s) into {} atlas page(s).",
+        sources.len(),
+        pages.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,419 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compression,
+    error::{SidToolError, SidToolResult},
+    pak::{self, DEFAULT_PAK_FILE_NAME},
+    report,
+    resource::{self, SidResourceHeader, SidResourceType},
+    schema::SidAssetReference,
+    sid,
+};
+
+/// Rows skipped by page up and page down.
+const PAGE_ROW_COUNT: usize = 20;
+
+/// Packed resource with the id of an asset.
+#[derive(Debug, Clone)]
+pub struct SidBrowseResource {
+    /// Name of the loose resource file, or of the .sidpak archive holding it.
+    pub file_name: String,
+    /// Size of the resource as stored, in bytes.
+    pub size: u64,
+    /// Type read from the resource, if valid.
+    pub resource_type: Option<SidResourceType>,
+}
+
+/// Row of the browser: an asset and its resource, or a resource no asset of
+/// the assets folder has the id of.
+#[derive(Debug, Clone)]
+pub struct SidBrowseEntry {
+    /// Namespace of the asset, empty without one.
+    pub namespace: &'static str,
+    /// Name of the asset, empty without one.
+    pub name: String,
+    pub id: sid::SidStringId,
+    pub asset_path: Option<PathBuf>,
+    pub resource: Option<SidBrowseResource>,
+    /// Assets the asset refers to by name.
+    pub references: Vec<SidAssetReference>,
+    /// Assets referring to the asset, as namespace/name.
+    pub referenced_by: Vec<String>,
+}
+
+impl SidBrowseEntry {
+    /// Whether the entry matches `query`: part of its namespace/name, ignoring
+    /// case, or the start of its id.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim();
+
+        if query.is_empty() || self.id.to_string().starts_with(query) {
+            return true;
+        }
+
+        format!("{}/{}", self.namespace, self.name)
+            .to_lowercase()
+            .contains(&query.to_lowercase())
+    }
+}
+
+fn read_resource_type(data: &[u8]) -> Option<SidResourceType> {
+    let data = compression::decompress(data).ok()?;
+    SidResourceHeader::check(&data)
+        .ok()
+        .map(|header| header.resource_type)
+}
+
+/// Resources of `resources_path` by id: the loose resource files, named after
+/// their id, and the resources of its .sidpak archive, if any.
+fn load_resources(
+    resources_path: &Path,
+) -> SidToolResult<HashMap<sid::SidStringId, SidBrowseResource>> {
+    let mut resources = HashMap::new();
+
+    if !resources_path.is_dir() {
+        return Ok(resources);
+    }
+
+    let pak_path = resources_path.join(DEFAULT_PAK_FILE_NAME);
+
+    if pak_path.is_file() {
+        let data = fs::read(&pak_path)
+            .map_err(|error| SidToolError::IO(format!("Unable to read {:?}: {error}", pak_path)))?;
+
+        for entry in pak::read_pak(&data)?.1 {
+            resources.insert(
+                entry.id,
+                SidBrowseResource {
+                    file_name: DEFAULT_PAK_FILE_NAME.to_string(),
+                    size: entry.data.len() as u64,
+                    resource_type: Some(entry.resource_type),
+                },
+            );
+        }
+    }
+
+    let entries = fs::read_dir(resources_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Failed to read directory {:?}: {error}",
+            resources_path
+        ))
+    })?;
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) else {
+            continue;
+        };
+
+        let Some(id) = resource::resource_file_id(file_name) else {
+            continue;
+        };
+
+        let data = fs::read(&path)
+            .map_err(|error| SidToolError::IO(format!("Unable to read {:?}: {error}", path)))?;
+
+        resources.insert(
+            id,
+            SidBrowseResource {
+                file_name: file_name.to_string(),
+                size: data.len() as u64,
+                resource_type: read_resource_type(&data),
+            },
+        );
+    }
+
+    Ok(resources)
+}
+
+/// Every asset of `assets_input_path` with its resource in `resources_path`,
+/// then every resource left, sorted by namespace and name, then id.
+pub fn load_entries<P: AsRef<Path>, Q: AsRef<Path>>(
+    assets_input_path: P,
+    resources_path: Q,
+) -> SidToolResult<Vec<SidBrowseEntry>> {
+    let mut resources = load_resources(resources_path.as_ref())?;
+    let graph = resource::build_asset_graph(assets_input_path)?;
+
+    let mut referenced_by: BTreeMap<(&'static str, &str), Vec<String>> = BTreeMap::new();
+
+    for asset in graph.assets() {
+        for reference in &asset.references {
+            referenced_by
+                .entry((reference.namespace, &reference.name))
+                .or_default()
+                .push(format!("{}/{}", asset.namespace, asset.name));
+        }
+    }
+
+    let mut entries: Vec<SidBrowseEntry> = graph
+        .assets()
+        .iter()
+        .map(|asset| SidBrowseEntry {
+            namespace: asset.namespace,
+            name: asset.name.clone(),
+            id: asset.id,
+            asset_path: Some(asset.path.clone()),
+            resource: resources.remove(&asset.id),
+            references: asset.references.clone(),
+            referenced_by: referenced_by
+                .get(&(asset.namespace, asset.name.as_str()))
+                .cloned()
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    entries.extend(resources.into_iter().map(|(id, resource)| SidBrowseEntry {
+        namespace: "",
+        name: String::new(),
+        id,
+        asset_path: None,
+        resource: Some(resource),
+        references: vec![],
+        referenced_by: vec![],
+    }));
+
+    entries.sort_by(|a, b| {
+        (a.namespace.is_empty(), a.namespace, &a.name, a.id).cmp(&(
+            b.namespace.is_empty(),
+            b.namespace,
+            &b.name,
+            b.id,
+        ))
+    });
+
+    Ok(entries)
+}
+
+/// State of the browser: the entries, the search query, and the selected
+/// entry among the ones matching it.
+struct SidBrowser {
+    entries: Vec<SidBrowseEntry>,
+    query: String,
+    /// Indices of the entries matching the query.
+    matches: Vec<usize>,
+    table: TableState,
+}
+
+impl SidBrowser {
+    fn new(entries: Vec<SidBrowseEntry>) -> Self {
+        let mut browser = Self {
+            entries,
+            query: String::new(),
+            matches: vec![],
+            table: TableState::default(),
+        };
+
+        browser.search();
+        browser
+    }
+
+    fn search(&mut self) {
+        self.matches = (0..self.entries.len())
+            .filter(|&index| self.entries[index].matches(&self.query))
+            .collect();
+
+        self.table.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    /// Moves the selection by `offset` rows, staying within the matches.
+    fn move_selection(&mut self, offset: isize) {
+        let Some(selected) = self.table.selected() else {
+            return;
+        };
+
+        let last = self.matches.len() - 1;
+        let selected = selected.saturating_add_signed(offset).min(last);
+        self.table.select(Some(selected));
+    }
+
+    fn selected_entry(&self) -> Option<&SidBrowseEntry> {
+        self.table
+            .selected()
+            .map(|selected| &self.entries[self.matches[selected]])
+    }
+
+    /// Handles the next key press. Returns whether to quit.
+    fn handle_event(&mut self) -> SidToolResult<bool> {
+        let Event::Key(key) = event::read()? else {
+            return Ok(false);
+        };
+
+        if key.kind != KeyEventKind::Press {
+            return Ok(false);
+        }
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(true),
+            KeyCode::Esc if self.query.is_empty() => return Ok(true),
+            KeyCode::Esc => {
+                self.query.clear();
+                self.search();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.search();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.search();
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-(PAGE_ROW_COUNT as isize)),
+            KeyCode::PageDown => self.move_selection(PAGE_ROW_COUNT as isize),
+            KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::End => self.move_selection(isize::MAX),
+            _ => {}
+        }
+
+        Ok(false)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search_area, table_area, details_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(9),
+        ])
+        .areas(frame.area());
+
+        let search =
+            Paragraph::new(format!("{}_", self.query)).block(Block::bordered().title(format!(
+                " Search by name or id ({}/{}), Esc to clear or quit ",
+                self.matches.len(),
+                self.entries.len()
+            )));
+        frame.render_widget(search, search_area);
+
+        let rows = self.matches.iter().map(|&index| {
+            let entry = &self.entries[index];
+            let resource = entry.resource.as_ref();
+
+            Row::new([
+                entry.namespace.to_string(),
+                entry.name.clone(),
+                entry.id.to_string(),
+                resource.map_or("-".to_string(), |resource| resource.file_name.clone()),
+                resource
+                    .and_then(|resource| resource.resource_type)
+                    .map_or("-".to_string(), |resource_type| {
+                        format!("{resource_type:?}")
+                    }),
+                resource.map_or("-".to_string(), |resource| {
+                    report::format_size(resource.size)
+                }),
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Fill(2),
+                Constraint::Length(20),
+                Constraint::Fill(1),
+                Constraint::Length(14),
+                Constraint::Length(10),
+            ],
+        )
+        .header(
+            Row::new(["Namespace", "Name", "Id", "Resource", "Type", "Size"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered());
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let details = self.selected_entry().map_or(vec![], describe);
+        frame.render_widget(
+            Paragraph::new(details).block(Block::bordered().title(" Details ")),
+            details_area,
+        );
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> SidToolResult<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if self.handle_event()? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn describe(entry: &SidBrowseEntry) -> Vec<Line<'static>> {
+    let asset_path = entry
+        .asset_path
+        .as_ref()
+        .map_or("-".to_string(), |path| path.display().to_string());
+
+    let references = if entry.references.is_empty() {
+        "-".to_string()
+    } else {
+        entry
+            .references
+            .iter()
+            .map(|reference| {
+                format!(
+                    "{} -> {}/{}",
+                    reference.field, reference.namespace, reference.name
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let referenced_by = if entry.referenced_by.is_empty() {
+        "-".to_string()
+    } else {
+        entry.referenced_by.join(", ")
+    };
+
+    vec![
+        Line::from(format!("Asset: {asset_path}")),
+        Line::from(format!(
+            "Resource: {}",
+            entry
+                .resource
+                .as_ref()
+                .map_or("not packed".to_string(), |resource| {
+                    format!("{} ({} byte(s))", resource.file_name, resource.size)
+                })
+        )),
+        Line::from(format!("References: {references}")),
+        Line::from(format!("Referenced by: {referenced_by}")),
+    ]
+}
+
+/// Browses the assets of `assets_input_path` and their resources in
+/// `resources_path` in the terminal, until Esc or Ctrl+C is pressed.
+pub fn browse<P: AsRef<Path>, Q: AsRef<Path>>(
+    assets_input_path: P,
+    resources_path: Q,
+) -> SidToolResult<()> {
+    let mut browser = SidBrowser::new(load_entries(assets_input_path, resources_path)?);
+
+    let mut terminal = ratatui::try_init()?;
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+
+    result
+}
@@ -0,0 +1,372 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    mem::size_of,
+    path::Path,
+};
+
+use crate::{
+    asset::SidAnimationDirection,
+    compression::{SidCompression, COMPRESSED_RESOURCE_HEADER_SIZE, COMPRESSED_RESOURCE_MAGIC},
+    error::{SidToolError, SidToolResult},
+    pak::{PAK_ALIGNMENT, PAK_HEADER_SIZE, PAK_MAGIC, PAK_TABLE_ENTRY_SIZE, PAK_VERSION},
+    resource::{
+        SidResourceType, RESOURCE_CHECKSUM_SIZE, RESOURCE_FORMAT_VERSION, RESOURCE_HEADER_SIZE,
+        RESOURCE_MAGIC, RESOURCE_WIDE_IDS_FLAG, TILEMAP_LAYER_FLAG_VISIBLE,
+    },
+    sid,
+};
+
+// The header is generated from the constants and the types the resources are
+// written with, so that the engine reader fails to build rather than misread
+// resources when they change.
+
+/// C type of the numbers of a given Rust type.
+trait SidCType {
+    const C_TYPE: &'static str;
+}
+
+macro_rules! c_type {
+    ($($type:ty => $c_type:literal),* $(,)?) => {
+        $(impl SidCType for $type {
+            const C_TYPE: &'static str = $c_type;
+        })*
+    };
+}
+
+c_type! {
+    u8 => "uint8_t",
+    u16 => "uint16_t",
+    u32 => "uint32_t",
+    u64 => "uint64_t",
+    i32 => "int32_t",
+    f32 => "float",
+}
+
+/// Field of a packed struct: its C type, name and size in bytes.
+type SidCField = (&'static str, &'static str, usize);
+
+fn field<T: SidCType>(name: &'static str) -> SidCField {
+    (T::C_TYPE, name, size_of::<T>())
+}
+
+fn fields_size(fields: &[SidCField]) -> usize {
+    fields.iter().map(|(_, _, size)| size).sum()
+}
+
+fn id_field(name: &'static str) -> SidCField {
+    ("sid_string_id", name, size_of::<sid::SidStringId>())
+}
+
+fn magic_field() -> SidCField {
+    ("char", "magic[4]", RESOURCE_MAGIC.len())
+}
+
+/// Name of a Rust variant in C, e.g. `SPRITE_SHEET` for `SpriteSheet`.
+fn to_upper_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+
+        out.push(c.to_ascii_uppercase());
+    }
+
+    out
+}
+
+fn write_define(out: &mut String, name: &str, value: impl std::fmt::Display) {
+    writeln!(out, "#define {name} {value}").unwrap();
+}
+
+fn write_magic(out: &mut String, name: &str, magic: &[u8; 4]) {
+    write_define(out, name, format!("\"{}\"", magic.escape_ascii()));
+}
+
+fn write_enum<T: std::fmt::Debug>(
+    out: &mut String,
+    name: &str,
+    prefix: &str,
+    variants: impl IntoIterator<Item = (T, u32)>,
+) {
+    writeln!(out, "typedef enum {name} {{").unwrap();
+
+    for (variant, value) in variants {
+        let variant = to_upper_snake_case(&format!("{variant:?}"));
+        writeln!(out, "  {prefix}_{variant} = {value},").unwrap();
+    }
+
+    writeln!(out, "}} {name};\n").unwrap();
+}
+
+fn write_struct(out: &mut String, name: &str, doc: &str, fields: &[SidCField], size: usize) {
+    assert_eq!(fields_size(fields), size, "Layout of {name} out of sync");
+
+    writeln!(out, "/* {doc} */").unwrap();
+    writeln!(out, "typedef struct {name} {{").unwrap();
+
+    for (c_type, field_name, _) in fields {
+        writeln!(out, "  {c_type} {field_name};").unwrap();
+    }
+
+    writeln!(out, "}} {name};").unwrap();
+    writeln!(
+        out,
+        "SID_STATIC_ASSERT(sizeof({name}) == {size}, {name});\n"
+    )
+    .unwrap();
+}
+
+/// C header describing the layout of the resources and paks written by this
+/// build: format versions, magics, enum values and fixed-size records.
+pub fn generate_c_header() -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "/* Generated by sid_asset_packer {}, do not edit. */\n",
+        env!("CARGO_PKG_VERSION")
+    )
+    .unwrap();
+
+    out.push_str(
+        "#ifndef SID_RESOURCE_FORMAT_H\n\
+         #define SID_RESOURCE_FORMAT_H\n\n\
+         #include <stdint.h>\n\n\
+         #ifdef __cplusplus\n\
+         #define SID_STATIC_ASSERT(condition, name) static_assert(condition, #name)\n\
+         #else\n\
+         #define SID_STATIC_ASSERT(condition, name) _Static_assert(condition, #name)\n\
+         #endif\n\n",
+    );
+
+    write_magic(&mut out, "SID_RESOURCE_MAGIC", RESOURCE_MAGIC);
+    write_define(
+        &mut out,
+        "SID_RESOURCE_FORMAT_VERSION",
+        RESOURCE_FORMAT_VERSION,
+    );
+    write_define(&mut out, "SID_RESOURCE_HEADER_SIZE", RESOURCE_HEADER_SIZE);
+    write_define(
+        &mut out,
+        "SID_RESOURCE_CHECKSUM_SIZE",
+        RESOURCE_CHECKSUM_SIZE,
+    );
+    write_define(
+        &mut out,
+        "SID_RESOURCE_WIDE_IDS_FLAG",
+        format!("0x{RESOURCE_WIDE_IDS_FLAG:08x}u"),
+    );
+    write_magic(
+        &mut out,
+        "SID_COMPRESSED_RESOURCE_MAGIC",
+        COMPRESSED_RESOURCE_MAGIC,
+    );
+    write_define(
+        &mut out,
+        "SID_COMPRESSED_RESOURCE_HEADER_SIZE",
+        COMPRESSED_RESOURCE_HEADER_SIZE,
+    );
+    write_magic(&mut out, "SID_PAK_MAGIC", PAK_MAGIC);
+    write_define(&mut out, "SID_PAK_VERSION", PAK_VERSION);
+    write_define(&mut out, "SID_PAK_ALIGNMENT", PAK_ALIGNMENT);
+    write_define(&mut out, "SID_STRING_ID_BITS", sid::STRING_ID_BITS);
+    write_define(
+        &mut out,
+        "SID_TILEMAP_LAYER_FLAG_VISIBLE",
+        TILEMAP_LAYER_FLAG_VISIBLE,
+    );
+
+    writeln!(
+        out,
+        "\ntypedef {} sid_string_id;\n",
+        <sid::SidStringId as SidCType>::C_TYPE
+    )
+    .unwrap();
+
+    write_enum(
+        &mut out,
+        "sid_resource_type",
+        "SID_RESOURCE_TYPE",
+        (1..)
+            .map_while(|raw| SidResourceType::from_raw(raw).map(|value| (value, raw)))
+            .collect::<Vec<_>>(),
+    );
+    write_enum(
+        &mut out,
+        "sid_compression",
+        "SID_COMPRESSION",
+        (0..)
+            .map_while(|raw| SidCompression::from_raw(raw).map(|value| (value, raw)))
+            .collect::<Vec<_>>(),
+    );
+    write_enum(
+        &mut out,
+        "sid_animation_direction",
+        "SID_ANIMATION_DIRECTION",
+        (0..=u8::MAX)
+            .map_while(|raw| SidAnimationDirection::from_raw(raw).map(|value| (value, raw.into())))
+            .collect::<Vec<_>>(),
+    );
+
+    out.push_str("#pragma pack(push, 1)\n\n");
+
+    write_struct(
+        &mut out,
+        "sid_resource_header",
+        "Start of every resource, in its byte order. The type holds \
+         SID_RESOURCE_WIDE_IDS_FLAG with 64-bit ids. An XXH3 checksum of the \
+         resource ends it.",
+        &[magic_field(), field::<u32>("version"), field::<u32>("type")],
+        RESOURCE_HEADER_SIZE,
+    );
+    write_struct(
+        &mut out,
+        "sid_compressed_resource_header",
+        "Start of compressed resources, little endian, followed by the payload.",
+        &[
+            magic_field(),
+            field::<u32>("compression"),
+            field::<u64>("uncompressed_size"),
+        ],
+        COMPRESSED_RESOURCE_HEADER_SIZE,
+    );
+    write_struct(
+        &mut out,
+        "sid_pak_header",
+        "Start of a .sidpak archive, little endian. Id bits are 0 for 32.",
+        &[
+            magic_field(),
+            field::<u32>("version"),
+            field::<u32>("entry_count"),
+            field::<u32>("id_bits"),
+        ],
+        PAK_HEADER_SIZE as usize,
+    );
+
+    let mut pak_entry_fields = vec![id_field("id"), field::<u32>("type")];
+
+    if sid::STRING_ID_BITS == 64 {
+        pak_entry_fields.push(field::<u32>("reserved"));
+    }
+
+    pak_entry_fields.extend([field::<u64>("offset"), field::<u64>("size")]);
+
+    write_struct(
+        &mut out,
+        "sid_pak_entry",
+        "Entry of the table of a pak, sorted by id, after its header.",
+        &pak_entry_fields,
+        PAK_TABLE_ENTRY_SIZE as usize,
+    );
+
+    let pivot_fields = [
+        field::<u8>("has_pivot"),
+        field::<sid::SidSliceCoord>("x"),
+        field::<sid::SidSliceCoord>("y"),
+    ];
+    let pivot_size = fields_size(&pivot_fields);
+
+    write_struct(
+        &mut out,
+        "sid_pivot",
+        "Pivot of sprite sheets and frames, zeros if there is none.",
+        &pivot_fields,
+        pivot_size,
+    );
+
+    let texture_ref_fields = [field::<u8>("has_texture"), id_field("texture_id")];
+    let texture_ref_size = fields_size(&texture_ref_fields);
+
+    write_struct(
+        &mut out,
+        "sid_texture_ref",
+        "Optional texture of a sprite sheet, e.g. its normal map.",
+        &texture_ref_fields,
+        texture_ref_size,
+    );
+
+    let region_fields = [
+        field::<sid::SidAnimationFrameCoord>("x"),
+        field::<sid::SidAnimationFrameCoord>("y"),
+        field::<sid::SidAnimationFrameDim>("width"),
+        field::<sid::SidAnimationFrameDim>("height"),
+        field::<sid::SidAnimationFrameCoord>("offset_x"),
+        field::<sid::SidAnimationFrameCoord>("offset_y"),
+        field::<sid::SidAnimationFrameDim>("source_width"),
+        field::<sid::SidAnimationFrameDim>("source_height"),
+        field::<u8>("rotated"),
+        field::<sid::SidAnimationPageIndex>("page"),
+    ];
+    let region_size = fields_size(&region_fields);
+
+    write_struct(
+        &mut out,
+        "sid_animation_region",
+        "Region of a sheet shown by frames of an animation def.",
+        &region_fields,
+        region_size,
+    );
+
+    let frame_fields = [
+        field::<sid::SidAnimationFrameIndex>("region_index"),
+        field::<sid::SidAnimationFrameDuration>("duration"),
+        ("sid_pivot", "pivot", pivot_size),
+    ];
+    let frame_size = fields_size(&frame_fields);
+
+    write_struct(
+        &mut out,
+        "sid_animation_frame",
+        "Frame of an animation def, after its regions.",
+        &frame_fields,
+        frame_size,
+    );
+
+    let animation_fields = [
+        id_field("id"),
+        id_field("def_id"),
+        field::<sid::SidAnimationFrameIndex>("offset"),
+        field::<sid::SidAnimationFrameIndex>("length"),
+        field::<u8>("direction"),
+        field::<u8>("looping"),
+        field::<f32>("speed_scale"),
+        field::<u16>("event_count"),
+    ];
+    let animation_size = fields_size(&animation_fields);
+
+    write_struct(
+        &mut out,
+        "sid_animation_header",
+        "Start of the payload of an animation, followed by its events: frame \
+         (uint16_t) and name (uint16_t length, then UTF-8 bytes).",
+        &animation_fields,
+        animation_size,
+    );
+
+    out.push_str("#pragma pack(pop)\n\n#endif /* SID_RESOURCE_FORMAT_H */\n");
+    out
+}
+
+/// Writes the C header to `path`, or to the standard output.
+pub fn write_c_header(path: Option<&Path>) -> SidToolResult<()> {
+    let header = generate_c_header();
+
+    match path {
+        Some(path) => fs::write(path, header).map_err(|error| {
+            SidToolError::IO(format!("Unable to write C header {:?}: {error}", path))
+        }),
+        None => {
+            let mut stdout = io::stdout().lock();
+            write!(stdout, "{header}")?;
+            Ok(())
+        }
+    }
+}
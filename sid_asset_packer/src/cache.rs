@@ -0,0 +1,195 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::Xxh3;
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{sid, sid_warning};
+
+pub static CACHE_FILE_NAME: &str = ".sid_cache.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SidSourceFingerprint {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified_secs: u64,
+    pub modified_nanos: u32,
+    pub hash: u64,
+}
+
+impl SidSourceFingerprint {
+    /// Fingerprints the file at `path`. The content hash of `previous` is
+    /// reused when the size and modification time did not change, so unchanged
+    /// files are never read.
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+        previous: Option<&SidSourceFingerprint>,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let metadata = fs::metadata(path)?;
+        let size = metadata.len();
+
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let modified_secs = modified.as_secs();
+        let modified_nanos = modified.subsec_nanos();
+
+        let hash = match previous {
+            Some(previous)
+                if previous.size == size
+                    && previous.modified_secs == modified_secs
+                    && previous.modified_nanos == modified_nanos =>
+            {
+                previous.hash
+            }
+            _ => hash_file(path)?,
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            size,
+            modified_secs,
+            modified_nanos,
+            hash,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidCacheEntry {
+    pub resource_id: sid::SidStringId,
+    pub sources: Vec<SidSourceFingerprint>,
+    /// Name of the resource file, when packed with content-hash names.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidBuildCache {
+    pub version: String,
+    /// Pack options affecting the resource contents. Changing them invalidates
+    /// every entry.
+    #[serde(default)]
+    pub options: String,
+    pub entries: BTreeMap<PathBuf, SidCacheEntry>,
+}
+
+impl SidBuildCache {
+    pub fn new(options: String) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            options,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the cache stored in the resources folder. A missing, unreadable or
+    /// outdated cache yields an empty one, which forces a full repack.
+    pub fn load<P: AsRef<Path>>(resources_path: P, options: String) -> Self {
+        let path = resources_path.as_ref().join(CACHE_FILE_NAME);
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Self::new(options),
+        };
+
+        match serde_json::from_str::<Self>(&contents) {
+            Ok(cache) if cache.version == env!("CARGO_PKG_VERSION") && cache.options == options => {
+                cache
+            }
+            Ok(_) => Self::new(options),
+            Err(error) => {
+                sid_warning!("Ignoring malformed build cache {:?}: {error}", path);
+                Self::new(options)
+            }
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, resources_path: P) -> io::Result<()> {
+        let path = resources_path.as_ref().join(CACHE_FILE_NAME);
+        let out_json = serde_json::to_string_pretty(self)?;
+        fs::write(path, out_json)
+    }
+
+    /// Name of the resource file last packed from the asset at `asset_path`
+    /// with content-hash names.
+    pub fn file_name(&self, asset_path: &Path) -> Option<&str> {
+        self.entries.get(asset_path)?.file_name.as_deref()
+    }
+
+    /// Fingerprints the sources of an asset and tells whether its resource is
+    /// still up to date.
+    pub fn check(
+        &self,
+        asset_path: &Path,
+        source_paths: &[PathBuf],
+        resource_path: &Path,
+        resource_id: sid::SidStringId,
+    ) -> io::Result<(SidCacheEntry, bool)> {
+        let previous = self.entries.get(asset_path);
+        let mut sources = Vec::with_capacity(source_paths.len());
+
+        for (i, source_path) in source_paths.iter().enumerate() {
+            let previous_source = previous
+                .and_then(|previous| previous.sources.get(i))
+                .filter(|previous_source| &previous_source.path == source_path);
+
+            sources.push(SidSourceFingerprint::from_path(
+                source_path,
+                previous_source,
+            )?);
+        }
+
+        let fresh = match previous {
+            Some(previous) => {
+                previous.resource_id == resource_id
+                    && previous.sources.len() == sources.len()
+                    && previous
+                        .sources
+                        .iter()
+                        .zip(&sources)
+                        .all(|(a, b)| a.path == b.path && a.hash == b.hash)
+                    && resource_path.is_file()
+            }
+            None => false,
+        };
+
+        let entry = SidCacheEntry {
+            resource_id,
+            sources,
+            file_name: None,
+        };
+
+        Ok((entry, fresh))
+    }
+}
+
+pub(crate) fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = Xxh3::new();
+    let mut buffer = [0; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.digest())
+}
@@ -0,0 +1,189 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cache::CACHE_FILE_NAME,
+    error::{SidToolError, SidToolResult},
+    manifest::{SidManifest, MANIFEST_FILE_NAME},
+    pak::DEFAULT_PAK_FILE_NAME,
+    resource, sid_debug, sid_info,
+};
+
+/// Lists the resource files of `resources_path` missing from its manifest,
+/// e.g. those left behind by renamed or deleted assets, or by resources with
+/// content-hash names that changed. Only files named after a resource id are
+/// considered, so other files are never touched.
+pub fn find_stale_resources<P: AsRef<Path>>(resources_path: P) -> SidToolResult<Vec<PathBuf>> {
+    let resources_path = resources_path.as_ref();
+
+    let manifest = SidManifest::load(resources_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Unable to load the manifest of {:?}: {error}",
+            resources_path
+        ))
+    })?;
+
+    let file_names: HashSet<String> = manifest
+        .entries
+        .iter()
+        .map(|entry| entry.file_name())
+        .collect();
+
+    let entries = fs::read_dir(resources_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Failed to read directory {:?}: {error}",
+            resources_path
+        ))
+    })?;
+
+    let mut stale_paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|file_name| file_name.to_str())
+                .is_some_and(|file_name| {
+                    resource::resource_file_id(file_name).is_some()
+                        && !file_names.contains(file_name)
+                })
+        })
+        .collect();
+
+    stale_paths.sort();
+    Ok(stale_paths)
+}
+
+/// Deletes the stale resource files of `resources_path`, or only lists them
+/// on dry runs.
+pub fn clean_resources<P: AsRef<Path>>(resources_path: P, dry_run: bool) -> SidToolResult<()> {
+    let resources_path = resources_path.as_ref();
+    let stale_paths = find_stale_resources(resources_path)?;
+
+    for path in &stale_paths {
+        if dry_run {
+            sid_info!("Would remove {:?}", path);
+            continue;
+        }
+
+        fs::remove_file(path)
+            .map_err(|error| SidToolError::IO(format!("Unable to remove {:?}: {error}", path)))?;
+
+        sid_info!("Removed {:?}", path);
+    }
+
+    if dry_run {
+        sid_info!(
+            "Dry run: would remove {} stale resource(s) from {:?}.",
+            stale_paths.len(),
+            resources_path
+        );
+    } else {
+        sid_info!(
+            "Removed {} stale resource(s) from {:?}.",
+            stale_paths.len(),
+            resources_path
+        );
+    }
+
+    Ok(())
+}
+
+/// Deletes the resource files of `resources_path` with the id of an entry of
+/// `manifest` but another file name, e.g. the previous content-hash names of
+/// repacked resources.
+pub(crate) fn remove_replaced_resources(
+    resources_path: &Path,
+    manifest: &SidManifest,
+) -> SidToolResult<()> {
+    let file_names: HashMap<_, _> = manifest
+        .entries
+        .iter()
+        .map(|entry| (entry.id, entry.file_name()))
+        .collect();
+
+    let entries = fs::read_dir(resources_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Failed to read directory {:?}: {error}",
+            resources_path
+        ))
+    })?;
+
+    let replaced_paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|file_name| file_name.to_str())
+                .is_some_and(|file_name| {
+                    resource::resource_file_id(file_name)
+                        .and_then(|id| file_names.get(&id))
+                        .is_some_and(|kept_file_name| kept_file_name != file_name)
+                })
+        })
+        .collect();
+
+    for path in &replaced_paths {
+        fs::remove_file(path)
+            .map_err(|error| SidToolError::IO(format!("Unable to remove {:?}: {error}", path)))?;
+
+        sid_debug!("Removed replaced resource {:?}", path);
+    }
+
+    Ok(())
+}
+
+/// Deletes everything a pack writes to `resources_path`: its resource files,
+/// pak, manifest and build cache, or only lists them on dry runs. Other files
+/// are never touched.
+pub fn wipe_resources<P: AsRef<Path>>(resources_path: P, dry_run: bool) -> SidToolResult<()> {
+    let resources_path = resources_path.as_ref();
+
+    // A missing folder has nothing to wipe.
+    let Ok(entries) = fs::read_dir(resources_path) else {
+        return Ok(());
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|file_name| file_name.to_str())
+                .is_some_and(|file_name| {
+                    file_name == DEFAULT_PAK_FILE_NAME
+                        || file_name == MANIFEST_FILE_NAME
+                        || file_name == CACHE_FILE_NAME
+                        || resource::resource_file_id(file_name).is_some()
+                })
+        })
+        .collect();
+
+    paths.sort();
+
+    if dry_run {
+        sid_info!(
+            "Dry run: would wipe {} file(s) from {:?}.",
+            paths.len(),
+            resources_path
+        );
+        return Ok(());
+    }
+
+    for path in &paths {
+        fs::remove_file(path)
+            .map_err(|error| SidToolError::IO(format!("Unable to remove {:?}: {error}", path)))?;
+    }
+
+    sid_info!("Wiped {} file(s) from {:?}.", paths.len(), resources_path);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Deserialize;
+
+use std::io;
+
+// Layout of a compressed resource (little endian): magic (4 bytes),
+// compression (u32), uncompressed size (u64), then the compressed payload.
+// Uncompressed resources are written as-is, without this header.
+pub static COMPRESSED_RESOURCE_MAGIC: &[u8; 4] = b"SIDZ";
+pub const COMPRESSED_RESOURCE_HEADER_SIZE: usize = 16;
+pub const DEFAULT_ZSTD_LEVEL: i32 = 19;
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidCompression {
+    None = 0,
+    Lz4 = 1,
+    Zstd = 2,
+}
+
+impl SidCompression {
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        match raw {
+            0 => Some(SidCompression::None),
+            1 => Some(SidCompression::Lz4),
+            2 => Some(SidCompression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Most bytes a compressed byte expands to, so that corrupted sizes are
+    /// rejected before being allocated.
+    fn max_ratio(self) -> usize {
+        match self {
+            SidCompression::None => 1,
+            SidCompression::Lz4 => 255,
+            // RLE blocks expand 4 bytes to up to 128 KiB.
+            SidCompression::Zstd => 1 << 15,
+        }
+    }
+}
+
+pub fn compress(data: &[u8], compression: SidCompression) -> io::Result<Vec<u8>> {
+    let payload = match compression {
+        SidCompression::None => return Ok(data.to_vec()),
+        SidCompression::Lz4 => lz4_flex::block::compress(data),
+        SidCompression::Zstd => zstd::bulk::compress(data, DEFAULT_ZSTD_LEVEL)?,
+    };
+
+    let mut out = Vec::with_capacity(COMPRESSED_RESOURCE_HEADER_SIZE + payload.len());
+    out.extend_from_slice(COMPRESSED_RESOURCE_MAGIC);
+    out.extend_from_slice(&(compression as u32).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+/// Returns the uncompressed resource, whether `data` was compressed or not.
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() < COMPRESSED_RESOURCE_HEADER_SIZE || &data[..4] != COMPRESSED_RESOURCE_MAGIC {
+        return Ok(data.to_vec());
+    }
+
+    let invalid = |error: String| io::Error::new(io::ErrorKind::InvalidData, error);
+
+    let raw_compression = u32::from_le_bytes(data[4..8].try_into().unwrap());
+    let size = u64::from_le_bytes(data[8..16].try_into().unwrap()) as usize;
+    let payload = &data[COMPRESSED_RESOURCE_HEADER_SIZE..];
+
+    let compression = SidCompression::from_raw(raw_compression)
+        .ok_or_else(|| invalid(format!("Unknown compression {raw_compression}")))?;
+
+    if size > payload.len().saturating_mul(compression.max_ratio()) {
+        return Err(invalid(format!(
+            "Uncompressed size {size} out of reach of {} compressed bytes",
+            payload.len()
+        )));
+    }
+
+    let out = match compression {
+        SidCompression::None => payload.to_vec(),
+        SidCompression::Lz4 => lz4_flex::block::decompress(payload, size)
+            .map_err(|error| invalid(error.to_string()))?,
+        SidCompression::Zstd => zstd::bulk::decompress(payload, size)?,
+    };
+
+    if out.len() != size {
+        return Err(invalid(format!(
+            "Expected {size} uncompressed bytes, got {}",
+            out.len()
+        )));
+    }
+
+    Ok(out)
+}
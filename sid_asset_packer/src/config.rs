@@ -0,0 +1,348 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Deserialize;
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asset::{SidColorSpace, SidPixelFormat},
+    compression::SidCompression,
+    error::{SidToolError, SidToolResult},
+    naming::SidCaseFolding,
+    resource::{
+        SidEndianness, SidInputCollisionPolicy, SidOutputFormat, SidOverwritePolicy,
+        DEFAULT_RESOURCES_PATH,
+    },
+    texture::{SidMipmapFilter, SidTextureEncoding},
+};
+
+pub static CONFIG_FILE_NAME: &str = "sid_tools.toml";
+
+/// Converter settings, overriding the command line defaults.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SidConverterConfig {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub recursive: Option<bool>,
+    /// Walk through symbolic links to files and folders, instead of skipping
+    /// them.
+    pub follow_symlinks: Option<bool>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub include_layers: Option<Vec<String>>,
+    pub exclude_layers: Option<Vec<String>>,
+    pub split_layers: Option<bool>,
+    /// Name of the animations, where `{sheet}` and `{tag}` are replaced by the
+    /// names of the sheet and of the tag.
+    pub animation_name: Option<String>,
+    pub animation_case: Option<SidNameCase>,
+    pub dedup_frames: Option<bool>,
+    /// Default pivot of the frames, unless a slice gives them one.
+    pub pivot_anchor: Option<SidPivotAnchor>,
+    /// Prefixes removed from the tag names in animation names.
+    pub strip_tag_prefixes: Option<Vec<String>>,
+    /// Convert the numbered PNG images of the input folders, e.g.
+    /// `run_000.png`, into a sprite sheet and animation per sequence.
+    pub sequences: Option<bool>,
+    /// Duration of the frames of image sequences and TexturePacker sheets in
+    /// milliseconds, unless their sidecar sets one.
+    pub sequence_frame_duration: Option<u16>,
+    /// Export native Aseprite files with the Aseprite executable.
+    pub aseprite_cli: Option<bool>,
+    /// Aseprite executable, relative to the configuration file unless it is a
+    /// bare command name looked up in the `PATH`.
+    pub aseprite_path: Option<PathBuf>,
+    pub overrides: Vec<SidConverterOverride>,
+}
+
+/// Case of the sheet and tag names in animation names.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SidNameCase {
+    /// Names are left as is.
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    /// Lowercase words separated by underscores, e.g. `run_fast`.
+    Snake,
+    /// Lowercase words separated by hyphens, e.g. `run-fast`.
+    Kebab,
+}
+
+/// Point of the frames they are drawn around, e.g. their feet.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SidPivotAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// Converter settings of the sheets under `path`, relative to the input folder.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SidConverterOverride {
+    pub path: PathBuf,
+    pub include_layers: Option<Vec<String>>,
+    pub exclude_layers: Option<Vec<String>>,
+    pub split_layers: Option<bool>,
+}
+
+/// A folder, or a list of folders, e.g. `input = ["shared", "assets"]`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SidPaths {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
+}
+
+impl SidPaths {
+    pub fn paths(&self) -> &[PathBuf] {
+        match self {
+            SidPaths::One(path) => std::slice::from_ref(path),
+            SidPaths::Many(paths) => paths,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SidPackConfig {
+    /// Assets folders, merged in order.
+    pub input: Option<SidPaths>,
+    /// Which asset is packed when several input folders define it.
+    pub input_collisions: Option<SidInputCollisionPolicy>,
+    pub output: Option<PathBuf>,
+    /// Walk through symbolic links to files and folders, instead of skipping
+    /// them.
+    pub follow_symlinks: Option<bool>,
+    pub format: Option<SidOutputFormat>,
+    pub overwrite: Option<SidOverwritePolicy>,
+    pub compression: Option<SidCompression>,
+    pub endianness: Option<SidEndianness>,
+    pub mipmaps: Option<SidMipmapFilter>,
+    pub texture_encoding: Option<SidTextureEncoding>,
+    pub color_space: Option<SidColorSpace>,
+    pub pixel_format: Option<SidPixelFormat>,
+    pub max_texture_size: Option<u32>,
+    pub power_of_two: Option<bool>,
+    pub color_key: Option<[u8; 3]>,
+    pub jobs: Option<usize>,
+    pub allow_collisions: Option<bool>,
+    pub deterministic: Option<bool>,
+    pub content_hash_names: Option<bool>,
+    /// Only pack the assets with one of these tags.
+    pub only_tags: Vec<String>,
+    /// Leave out the assets with one of these tags.
+    pub exclude_tags: Vec<String>,
+    pub overrides: Vec<SidPackOverride>,
+    /// Split the resources into packages, e.g. for DLCs, written as
+    /// `[[pack.packages]]` tables.
+    pub packages: Vec<SidPackage>,
+    /// Package of the assets of no other package [default: base].
+    pub default_package: Option<String>,
+    /// Id overrides file [default: id_overrides.toml, if any].
+    pub id_overrides: Option<PathBuf>,
+}
+
+/// Pack settings of the assets under `path`, relative to the input folder.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SidPackOverride {
+    pub path: PathBuf,
+    pub compression: Option<SidCompression>,
+    pub texture_encoding: Option<SidTextureEncoding>,
+    pub color_space: Option<SidColorSpace>,
+    pub pixel_format: Option<SidPixelFormat>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SidAtlasConfig {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub name: Option<String>,
+    pub max_size: Option<u32>,
+    pub padding: Option<u32>,
+    pub extrude: Option<u32>,
+    pub trim: Option<bool>,
+}
+
+/// Package of the assets under one of `paths`, relative to the input folder,
+/// or with one of `tags`. The first matching package of the list wins.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SidPackage {
+    pub name: String,
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Pack settings of a platform, e.g. `[targets.switch]`, over the ones of the
+/// [pack] section. Its resources go to a folder named after it in the pack
+/// output folder, unless it sets its own.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SidTargetConfig {
+    pub output: Option<PathBuf>,
+    pub format: Option<SidOutputFormat>,
+    pub compression: Option<SidCompression>,
+    pub endianness: Option<SidEndianness>,
+    pub mipmaps: Option<SidMipmapFilter>,
+    pub texture_encoding: Option<SidTextureEncoding>,
+    pub color_space: Option<SidColorSpace>,
+    pub pixel_format: Option<SidPixelFormat>,
+    pub max_texture_size: Option<u32>,
+    pub power_of_two: Option<bool>,
+    /// Replace the ones of the [pack] section, e.g. to leave out the `debug`
+    /// assets of shipping builds.
+    pub only_tags: Option<Vec<String>>,
+    pub exclude_tags: Option<Vec<String>>,
+    /// Applied after the overrides of the [pack] section.
+    pub overrides: Vec<SidPackOverride>,
+}
+
+/// Rules of the asset names, shared by the converter and the packer.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SidNamesConfig {
+    pub max_length: Option<usize>,
+    pub case_folding: Option<SidCaseFolding>,
+    /// Characters allowed on top of ASCII letters, digits, underscores and
+    /// slashes.
+    pub extra_characters: Option<String>,
+}
+
+/// Project configuration shared by every tool, read from `sid_tools.toml` (or
+/// a JSON file with the same layout). Relative paths are relative to the
+/// folder of the configuration file.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SidToolsConfig {
+    pub converter: SidConverterConfig,
+    pub pack: SidPackConfig,
+    pub atlas: SidAtlasConfig,
+    pub names: SidNamesConfig,
+    /// Platforms to pack for, by name.
+    pub targets: BTreeMap<String, SidTargetConfig>,
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+impl SidToolsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> SidToolResult<Self> {
+        let path = path.as_ref();
+
+        let contents = fs::read_to_string(path).map_err(|error| {
+            SidToolError::IO(format!("Unable to read configuration {:?}: {error}", path))
+        })?;
+
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+        let mut config: Self = if is_json {
+            serde_json::from_str(&contents).map_err(|error| error.to_string())
+        } else {
+            toml::from_str(&contents).map_err(|error| error.to_string())
+        }
+        .map_err(|error| {
+            SidToolError::Malformed(format!("Invalid configuration {:?}: {error}", path))
+        })?;
+
+        config.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+
+    /// Loads `path` if given, or else the closest `sid_tools.toml` from the
+    /// current folder and its parents. Returns an empty configuration if there
+    /// is none.
+    pub fn load(path: Option<&Path>) -> SidToolResult<Self> {
+        if let Some(path) = path {
+            return Self::from_path(path);
+        }
+
+        let current_dir = env::current_dir()?;
+
+        for folder in current_dir.ancestors() {
+            let path = folder.join(CONFIG_FILE_NAME);
+
+            if path.is_file() {
+                return Self::from_path(path);
+            }
+        }
+
+        Ok(Self::new())
+    }
+
+    /// Pack settings of the target called `name`: the ones of its section,
+    /// over the ones of the [pack] section.
+    pub fn pack_for_target(&self, name: &str) -> SidToolResult<SidPackConfig> {
+        let target = self.targets.get(name).ok_or_else(|| {
+            let names: Vec<_> = self.targets.keys().collect();
+
+            SidToolError::Malformed(format!(
+                "Unknown target {:?}, the configuration defines {:?}",
+                name, names
+            ))
+        })?;
+
+        let mut config = self.pack.clone();
+
+        config.output = match &target.output {
+            Some(output) => Some(output.clone()),
+            None => Some(
+                config
+                    .output
+                    .unwrap_or_else(|| PathBuf::from(DEFAULT_RESOURCES_PATH))
+                    .join(name),
+            ),
+        };
+
+        config.format = target.format.or(config.format);
+        config.compression = target.compression.or(config.compression);
+        config.endianness = target.endianness.or(config.endianness);
+        config.mipmaps = target.mipmaps.or(config.mipmaps);
+        config.texture_encoding = target.texture_encoding.or(config.texture_encoding);
+        config.color_space = target.color_space.or(config.color_space);
+        config.pixel_format = target.pixel_format.or(config.pixel_format);
+        config.max_texture_size = target.max_texture_size.or(config.max_texture_size);
+        config.power_of_two = target.power_of_two.or(config.power_of_two);
+        config.overrides.extend(target.overrides.iter().cloned());
+
+        if let Some(only_tags) = &target.only_tags {
+            config.only_tags = only_tags.clone();
+        }
+
+        if let Some(exclude_tags) = &target.exclude_tags {
+            config.exclude_tags = exclude_tags.clone();
+        }
+
+        Ok(config)
+    }
+
+    /// Makes a path of the configuration file usable from the current folder.
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        self.root.join(path)
+    }
+}
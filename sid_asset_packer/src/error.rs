@@ -0,0 +1,100 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{fmt, io, path::PathBuf};
+
+use crate::{
+    asset::SidAssetError, graph::SidDanglingReference, id_registry::SidIdCollision,
+    resource::SidAssetSerializationError,
+};
+
+#[derive(Debug)]
+pub struct SidAssetFailure {
+    pub path: PathBuf,
+    pub error: SidToolError,
+}
+
+/// Error returned by the pipeline entry points of every tool.
+#[derive(Debug)]
+pub enum SidToolError {
+    UnsupportedFormat(String),
+    Malformed(String),
+    IO(String),
+    /// Two or more assets map to the same resource id.
+    IdCollisions(Vec<SidIdCollision>),
+    /// Some assets refer to assets that do not exist.
+    DanglingReferences(Vec<SidDanglingReference>),
+    /// Some assets could not be processed; the others were.
+    AssetFailures(Vec<SidAssetFailure>),
+}
+
+impl fmt::Display for SidToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SidToolError::UnsupportedFormat(error) => {
+                write!(f, "Unsupported format error: {error}")
+            }
+            SidToolError::Malformed(error) => write!(f, "Malformed error: {error}"),
+            SidToolError::IO(error) => write!(f, "I/O error: {error}"),
+            SidToolError::IdCollisions(collisions) => {
+                write!(f, "{} id collision(s) found:", collisions.len())?;
+
+                for collision in collisions {
+                    write!(f, "\n  {collision}")?;
+                }
+
+                Ok(())
+            }
+            SidToolError::DanglingReferences(references) => {
+                write!(f, "{} dangling reference(s) found:", references.len())?;
+
+                for reference in references {
+                    write!(f, "\n  {reference}")?;
+                }
+
+                Ok(())
+            }
+            SidToolError::AssetFailures(failures) => {
+                write!(f, "{} asset(s) failed:", failures.len())?;
+
+                for failure in failures {
+                    write!(f, "\n  {:?}: {}", failure.path, failure.error)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SidToolError {}
+
+impl From<io::Error> for SidToolError {
+    fn from(error: io::Error) -> Self {
+        SidToolError::IO(error.to_string())
+    }
+}
+
+impl From<SidAssetError> for SidToolError {
+    fn from(error: SidAssetError) -> Self {
+        match error {
+            SidAssetError::Malformed(error) => SidToolError::Malformed(error),
+            SidAssetError::IO(error) => SidToolError::IO(error),
+        }
+    }
+}
+
+impl From<SidAssetSerializationError> for SidToolError {
+    fn from(error: SidAssetSerializationError) -> Self {
+        match error {
+            SidAssetSerializationError::UnsupportedFormat(error) => {
+                SidToolError::UnsupportedFormat(error)
+            }
+            SidAssetSerializationError::Malformed(error) => SidToolError::Malformed(error),
+            SidAssetSerializationError::IO(error) => SidToolError::IO(error),
+        }
+    }
+}
+
+pub type SidToolResult<T> = Result<T, SidToolError>;
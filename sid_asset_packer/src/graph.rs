@@ -0,0 +1,293 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Serialize;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    error::{SidToolError, SidToolResult},
+    id_registry::SidIdOwner,
+    resource::SidAssetEntry,
+    schema::SidAssetReference,
+    sid,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidGraphFormat {
+    /// Graphviz DOT.
+    Dot,
+    Json,
+}
+
+/// Reference to an asset that does not exist.
+#[derive(Debug)]
+pub struct SidDanglingReference {
+    pub owner: SidIdOwner,
+    pub reference: SidAssetReference,
+}
+
+impl fmt::Display for SidDanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} references unknown {} asset {:?}",
+            self.owner, self.reference.field, self.reference.namespace, self.reference.name
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct SidGraphJson<'a> {
+    assets: Vec<SidGraphAssetJson<'a>>,
+    references: Vec<SidGraphReferenceJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct SidGraphAssetJson<'a> {
+    namespace: &'static str,
+    name: &'a str,
+    id: sid::SidStringId,
+    path: &'a Path,
+    orphan: bool,
+}
+
+#[derive(Serialize)]
+struct SidGraphReferenceJson<'a> {
+    from_namespace: &'static str,
+    from_name: &'a str,
+    field: &'static str,
+    to_namespace: &'static str,
+    to_name: &'a str,
+    dangling: bool,
+}
+
+/// Assets linked by the names they reference (e.g. animations to their
+/// definition, definitions to their sprite sheet).
+#[derive(Debug)]
+pub struct SidAssetGraph {
+    assets: Vec<SidAssetEntry>,
+    indices: HashMap<(&'static str, String), usize>,
+}
+
+impl SidAssetGraph {
+    pub fn new() -> Self {
+        Self {
+            assets: vec![],
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Adds an asset. References resolve to the first asset added with a
+    /// given namespace and name.
+    pub fn add(&mut self, entry: SidAssetEntry) {
+        self.indices
+            .entry((entry.namespace, entry.name.clone()))
+            .or_insert(self.assets.len());
+
+        self.assets.push(entry);
+    }
+
+    pub fn assets(&self) -> &[SidAssetEntry] {
+        &self.assets
+    }
+
+    pub fn find(&self, namespace: &'static str, name: &str) -> Option<&SidAssetEntry> {
+        self.indices
+            .get(&(namespace, name.to_string()))
+            .map(|&index| &self.assets[index])
+    }
+
+    fn contains(&self, reference: &SidAssetReference) -> bool {
+        self.find(reference.namespace, &reference.name).is_some()
+    }
+
+    /// Assets no other asset references, and which reference none.
+    pub fn orphans(&self) -> Vec<&SidAssetEntry> {
+        let referenced: HashSet<(&'static str, &str)> = self
+            .assets
+            .iter()
+            .flat_map(|entry| &entry.references)
+            .map(|reference| (reference.namespace, reference.name.as_str()))
+            .collect();
+
+        self.assets
+            .iter()
+            .filter(|entry| {
+                entry.references.is_empty()
+                    && !referenced.contains(&(entry.namespace, entry.name.as_str()))
+            })
+            .collect()
+    }
+
+    /// Every reference to a missing asset, sorted by the path of the asset
+    /// holding it.
+    pub fn dangling_references(&self) -> Vec<SidDanglingReference> {
+        let mut assets: Vec<&SidAssetEntry> = self.assets.iter().collect();
+        assets.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assets
+            .into_iter()
+            .flat_map(|entry| {
+                entry
+                    .references
+                    .iter()
+                    .filter(|reference| !self.contains(reference))
+                    .map(|reference| SidDanglingReference {
+                        owner: SidIdOwner {
+                            namespace: entry.namespace,
+                            name: entry.name.clone(),
+                            path: entry.path.clone(),
+                        },
+                        reference: reference.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Graphviz DOT description of the graph. Orphaned assets are dashed, and
+    /// missing assets are drawn in red.
+    pub fn to_dot(&self) -> String {
+        let orphans: HashSet<(&'static str, &str)> = self
+            .orphans()
+            .into_iter()
+            .map(|entry| (entry.namespace, entry.name.as_str()))
+            .collect();
+
+        let mut assets: Vec<&SidAssetEntry> = self.assets.iter().collect();
+        assets.sort_by(|a, b| (a.namespace, &a.name).cmp(&(b.namespace, &b.name)));
+
+        let mut dot = String::from("digraph assets {\n    rankdir=LR;\n    node [shape=box];\n");
+
+        for entry in &assets {
+            let style = if orphans.contains(&(entry.namespace, entry.name.as_str())) {
+                ", style=dashed"
+            } else {
+                ""
+            };
+
+            let _ = writeln!(
+                dot,
+                "    {} [label={}{style}];",
+                dot_node_id(entry.namespace, &entry.name),
+                dot_string(&format!("{}\n{}", entry.namespace, entry.name))
+            );
+        }
+
+        let mut missing = HashSet::new();
+
+        for entry in &assets {
+            for reference in &entry.references {
+                let to = dot_node_id(reference.namespace, &reference.name);
+
+                if !self.contains(reference) && missing.insert(to.clone()) {
+                    let _ = writeln!(
+                        dot,
+                        "    {to} [label={}, color=red, fontcolor=red];",
+                        dot_string(&format!("{}\n{}", reference.namespace, reference.name))
+                    );
+                }
+
+                let _ = writeln!(
+                    dot,
+                    "    {} -> {to} [label={}];",
+                    dot_node_id(entry.namespace, &entry.name),
+                    dot_string(reference.field)
+                );
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> SidToolResult<String> {
+        let orphans: HashSet<(&'static str, &str)> = self
+            .orphans()
+            .into_iter()
+            .map(|entry| (entry.namespace, entry.name.as_str()))
+            .collect();
+
+        let graph = SidGraphJson {
+            assets: self
+                .assets
+                .iter()
+                .map(|entry| SidGraphAssetJson {
+                    namespace: entry.namespace,
+                    name: &entry.name,
+                    id: entry.id,
+                    path: &entry.path,
+                    orphan: orphans.contains(&(entry.namespace, entry.name.as_str())),
+                })
+                .collect(),
+            references: self
+                .assets
+                .iter()
+                .flat_map(|entry| {
+                    entry
+                        .references
+                        .iter()
+                        .map(move |reference| SidGraphReferenceJson {
+                            from_namespace: entry.namespace,
+                            from_name: &entry.name,
+                            field: reference.field,
+                            to_namespace: reference.namespace,
+                            to_name: &reference.name,
+                            dangling: !self.contains(reference),
+                        })
+                })
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&graph)
+            .map_err(|error| SidToolError::Malformed(error.to_string()))
+    }
+
+    /// Writes the graph to `path`, or to the standard output if none is given.
+    pub fn write(&self, format: SidGraphFormat, path: Option<&Path>) -> SidToolResult<()> {
+        let text = match format {
+            SidGraphFormat::Dot => self.to_dot(),
+            SidGraphFormat::Json => self.to_json()? + "\n",
+        };
+
+        match path {
+            Some(path) => fs::write(path, text).map_err(|error| {
+                SidToolError::IO(format!("Unable to write graph {:?}: {error}", path))
+            }),
+            None => {
+                let mut stdout = io::stdout().lock();
+                write!(stdout, "{text}")?;
+                Ok(())
+            }
+        }
+    }
+}
+
+fn dot_node_id(namespace: &str, name: &str) -> String {
+    dot_string(&format!("{namespace}/{name}"))
+}
+
+fn dot_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
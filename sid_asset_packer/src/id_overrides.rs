@@ -0,0 +1,235 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::{
+    de::{self, Unexpected, Visitor},
+    Deserialize, Deserializer,
+};
+use xxhash_rust::xxh3::Xxh3;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::Path,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::{
+    error::{SidToolError, SidToolResult},
+    sid::{self, SidStringId},
+};
+
+/// File of the id overrides, looked up next to the configuration file.
+pub static ID_OVERRIDES_FILE_NAME: &str = "id_overrides.toml";
+
+// Consulted by every id generation of the tools, so that they all agree.
+static ID_OVERRIDES: RwLock<Option<SidIdOverrides>> = RwLock::new(None);
+
+/// Layout of an id overrides file, e.g.:
+///
+/// ```toml
+/// # Ids no asset may take, e.g. the ones of removed assets.
+/// reserved = [1143714660]
+///
+/// # Ids of assets by namespace and name, e.g. to keep the id of a renamed one.
+/// [ids.sprite_sheets]
+/// player = 3977000791
+/// ```
+///
+/// Ids may also be written as decimal or `0x` hexadecimal strings, which TOML
+/// integers cannot hold past 2^63 with the `wide-ids` feature, e.g.
+/// `player = "0xed0c3f8a9b5a1c27"`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct SidIdOverridesFile {
+    ids: BTreeMap<String, BTreeMap<String, SidOverrideId>>,
+    reserved: Vec<SidOverrideId>,
+}
+
+/// Id of an id overrides file, from a TOML integer or string.
+#[derive(Debug, Clone, Copy)]
+struct SidOverrideId(SidStringId);
+
+impl<'de> Deserialize<'de> for SidOverrideId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdVisitor;
+
+        impl Visitor<'_> for IdVisitor {
+            type Value = SidOverrideId;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a {}-bit id, as an integer or a decimal or 0x hexadecimal string",
+                    sid::STRING_ID_BITS
+                )
+            }
+
+            fn visit_i64<E: de::Error>(self, id: i64) -> Result<Self::Value, E> {
+                SidStringId::try_from(id)
+                    .map(SidOverrideId)
+                    .map_err(|_| E::invalid_value(Unexpected::Signed(id), &self))
+            }
+
+            fn visit_u64<E: de::Error>(self, id: u64) -> Result<Self::Value, E> {
+                SidStringId::try_from(id)
+                    .map(SidOverrideId)
+                    .map_err(|_| E::invalid_value(Unexpected::Unsigned(id), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, id: &str) -> Result<Self::Value, E> {
+                let parsed = match id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")) {
+                    Some(hex) => SidStringId::from_str_radix(hex, 16),
+                    None => id.parse(),
+                };
+
+                parsed
+                    .map(SidOverrideId)
+                    .map_err(|_| E::invalid_value(Unexpected::Str(id), &self))
+            }
+        }
+
+        deserializer.deserialize_any(IdVisitor)
+    }
+}
+
+/// Ids given to assets instead of the ones generated from their names, and ids
+/// no other asset may take.
+#[derive(Debug, Default, Clone)]
+pub struct SidIdOverrides {
+    ids: HashMap<(String, String), SidStringId>,
+    /// Owner of every id taken, by namespace and name, or none if reserved.
+    owners: HashMap<SidStringId, Option<(String, String)>>,
+}
+
+impl SidIdOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> SidToolResult<Self> {
+        let path = path.as_ref();
+
+        let contents = fs::read_to_string(path).map_err(|error| {
+            SidToolError::IO(format!("Unable to read id overrides {:?}: {error}", path))
+        })?;
+
+        let file: SidIdOverridesFile = toml::from_str(&contents).map_err(|error| {
+            SidToolError::Malformed(format!("Invalid id overrides {:?}: {error}", path))
+        })?;
+
+        let mut overrides = Self::new();
+
+        for SidOverrideId(id) in file.reserved {
+            overrides.take(id, None, path)?;
+        }
+
+        for (namespace, ids) in file.ids {
+            for (name, SidOverrideId(id)) in ids {
+                overrides.take(id, Some((namespace.clone(), name.clone())), path)?;
+                overrides.ids.insert((namespace.clone(), name), id);
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    fn take(
+        &mut self,
+        id: SidStringId,
+        owner: Option<(String, String)>,
+        path: &Path,
+    ) -> SidToolResult<()> {
+        if self.owners.insert(id, owner).is_some() {
+            return Err(SidToolError::Malformed(format!(
+                "Id {id} is listed twice in id overrides {:?}",
+                path
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn find(&self, namespace: &str, name: &str) -> Option<SidStringId> {
+        self.ids
+            .get(&(namespace.to_string(), name.to_string()))
+            .copied()
+    }
+
+    /// Hash of the ids taken, and of their owners, which changes whenever an
+    /// asset may get a different id.
+    pub fn fingerprint(&self) -> u64 {
+        let mut owners: Vec<_> = self.owners.iter().collect();
+        owners.sort();
+
+        let mut hasher = Xxh3::new();
+
+        for (id, owner) in owners {
+            hasher.update(&id.to_le_bytes());
+
+            if let Some((namespace, name)) = owner {
+                hasher.update(namespace.as_bytes());
+                hasher.update(b"/");
+                hasher.update(name.as_bytes());
+            }
+
+            hasher.update(b"\n");
+        }
+
+        hasher.digest()
+    }
+
+    /// Describes why the asset called `name` in `namespace` may not have `id`,
+    /// if it may not: the id is reserved, or given to another asset.
+    pub fn check(&self, namespace: &str, name: &str, id: SidStringId) -> Result<(), String> {
+        match self.owners.get(&id) {
+            None => Ok(()),
+            Some(Some((owner_namespace, owner_name)))
+                if owner_namespace == namespace && owner_name == name =>
+            {
+                Ok(())
+            }
+            Some(Some((owner_namespace, owner_name))) => Err(format!(
+                "id {id} is given to {owner_namespace}/{owner_name} by the id overrides"
+            )),
+            Some(None) => Err(format!("id {id} is reserved by the id overrides")),
+        }
+    }
+}
+
+/// Makes every id generation use `overrides`.
+pub fn set_id_overrides(overrides: SidIdOverrides) {
+    *ID_OVERRIDES.write().unwrap_or_else(PoisonError::into_inner) = Some(overrides);
+}
+
+/// Id given to the asset called `name` in `namespace` by the id overrides in
+/// use, if any.
+pub fn find_id(namespace: &str, name: &str) -> Option<SidStringId> {
+    ID_OVERRIDES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .and_then(|overrides| overrides.find(namespace, name))
+}
+
+/// Fingerprint of the id overrides in use, like `SidIdOverrides::fingerprint`.
+pub fn fingerprint() -> u64 {
+    ID_OVERRIDES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map_or_else(
+            || SidIdOverrides::new().fingerprint(),
+            SidIdOverrides::fingerprint,
+        )
+}
+
+/// Checks `id` against the id overrides in use, like `SidIdOverrides::check`.
+pub fn check_id(namespace: &str, name: &str, id: SidStringId) -> Result<(), String> {
+    ID_OVERRIDES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map_or(Ok(()), |overrides| overrides.check(namespace, name, id))
+}
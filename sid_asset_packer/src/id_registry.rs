@@ -0,0 +1,82 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use crate::sid::SidStringId;
+
+#[derive(Debug, Clone)]
+pub struct SidIdOwner {
+    pub namespace: &'static str,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for SidIdOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} ({:?})", self.namespace, self.name, self.path)
+    }
+}
+
+#[derive(Debug)]
+pub struct SidIdCollision {
+    pub id: SidStringId,
+    pub first: SidIdOwner,
+    pub second: SidIdOwner,
+}
+
+impl fmt::Display for SidIdCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first.namespace == self.second.namespace && self.first.name == self.second.name {
+            write!(
+                f,
+                "Duplicate asset {} also defined by {:?} (id {})",
+                self.first, self.second.path, self.id
+            )
+        } else {
+            write!(
+                f,
+                "Id collision: {} and {} both map to id {}",
+                self.first, self.second, self.id
+            )
+        }
+    }
+}
+
+/// Keeps track of every resource id generated during a run, so that two assets
+/// never silently write to the same resource file.
+#[derive(Debug)]
+pub struct SidIdRegistry {
+    owners: BTreeMap<SidStringId, SidIdOwner>,
+}
+
+impl SidIdRegistry {
+    pub fn new() -> Self {
+        Self {
+            owners: BTreeMap::new(),
+        }
+    }
+
+    pub fn register(
+        &mut self,
+        id: SidStringId,
+        owner: SidIdOwner,
+    ) -> Result<(), Box<SidIdCollision>> {
+        match self.owners.get(&id) {
+            Some(first) => Err(Box::new(SidIdCollision {
+                id,
+                first: first.clone(),
+                second: owner,
+            })),
+            None => {
+                self.owners.insert(id, owner);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn owner(&self, id: SidStringId) -> Option<&SidIdOwner> {
+        self.owners.get(&id)
+    }
+}
@@ -0,0 +1,716 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{fmt, fs, path::Path};
+
+use crate::{
+    asset::{SidBlendMode, SidBoneTimelineProperty, SidCurveInterpolation},
+    error::{SidToolError, SidToolResult},
+    manifest::SidManifest,
+    pak::PAK_MAGIC,
+    resource::{
+        read::{
+            decode_pak, read_resource, SidDecodedCurveData, SidDecodedImage,
+            SidDecodedPrefabComponent, SidDecodedResource,
+        },
+        MESH_TEXTURE_SPRITE_SHEET,
+    },
+    sid::{
+        sid_audio_format, sid_color_space, sid_shader_format, sid_shader_stage, sid_texture_format,
+    },
+};
+
+impl fmt::Display for SidDecodedImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  size:     {}x{}", self.width, self.height)?;
+
+        if let Some(base_level) = self
+            .levels
+            .first()
+            .filter(|level| (level.width, level.height) != (self.width, self.height))
+        {
+            writeln!(f, "  padded:   {}x{}", base_level.width, base_level.height)?;
+        }
+
+        writeln!(f, "  channels: {}", self.channel_count)?;
+        writeln!(
+            f,
+            "  format:   {} ({})",
+            format_name(self.format),
+            self.format
+        )?;
+        writeln!(
+            f,
+            "  color:    {} ({})",
+            color_space_name(self.color_space),
+            self.color_space
+        )?;
+
+        if self.palette_id != 0 {
+            writeln!(f, "  palette:  {}", self.palette_id)?;
+        }
+
+        writeln!(f, "  texture:  {} byte(s)", self.texture().len())?;
+
+        if self.levels.len() > 1 {
+            writeln!(f, "  mipmaps:  {} level(s)", self.levels.len() - 1)?;
+
+            for (i, level) in self.levels.iter().enumerate().skip(1) {
+                writeln!(
+                    f,
+                    "    [{i}] {}x{}, {} byte(s)",
+                    level.width,
+                    level.height,
+                    level.data.len()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn format_name(format: i32) -> &'static str {
+    match format {
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGB8 as i32 => "RGB8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGBA8 as i32 => "RGBA8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_R8 as i32 => "R8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_BC1 as i32 => "BC1",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_BC3 as i32 => "BC3",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_BC7 as i32 => "BC7",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_ETC2_RGB8 as i32 => "ETC2 RGB8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_ETC2_RGBA8 as i32 => "ETC2 RGBA8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_ASTC_4X4 as i32 => "ASTC 4x4",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_INDEXED8 as i32 => "indexed8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_L8 as i32 => "L8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_LA8 as i32 => "LA8",
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGBA16 as i32 => "RGBA16",
+        _ => "unknown",
+    }
+}
+
+fn color_space_name(color_space: i32) -> &'static str {
+    match color_space {
+        x if x == sid_color_space::SID_COLOR_SPACE_SRGB as i32 => "sRGB",
+        x if x == sid_color_space::SID_COLOR_SPACE_LINEAR as i32 => "linear",
+        _ => "unknown",
+    }
+}
+
+fn audio_format_name(format: i32) -> &'static str {
+    match format {
+        x if x == sid_audio_format::SID_AUDIO_FORMAT_PCM16 as i32 => "PCM16",
+        x if x == sid_audio_format::SID_AUDIO_FORMAT_OGG_VORBIS as i32 => "Ogg Vorbis",
+        _ => "unknown",
+    }
+}
+
+fn bone_property_name(property: u8) -> &'static str {
+    match SidBoneTimelineProperty::from_raw(property) {
+        Some(SidBoneTimelineProperty::Rotate) => "rotate",
+        Some(SidBoneTimelineProperty::Translate) => "translate",
+        Some(SidBoneTimelineProperty::Scale) => "scale",
+        None => "unknown",
+    }
+}
+
+fn curve_interpolation_name(interpolation: u8) -> &'static str {
+    match SidCurveInterpolation::from_raw(interpolation) {
+        Some(SidCurveInterpolation::Stepped) => "stepped",
+        Some(SidCurveInterpolation::Linear) => "linear",
+        Some(SidCurveInterpolation::CatmullRom) => "Catmull-Rom",
+        None => "unknown",
+    }
+}
+
+fn blend_mode_name(blend_mode: u8) -> &'static str {
+    match SidBlendMode::from_raw(blend_mode) {
+        Some(SidBlendMode::Opaque) => "opaque",
+        Some(SidBlendMode::Alpha) => "alpha",
+        Some(SidBlendMode::Premultiplied) => "premultiplied",
+        Some(SidBlendMode::Additive) => "additive",
+        Some(SidBlendMode::Multiply) => "multiply",
+        None => "unknown",
+    }
+}
+
+fn shader_format_name(format: i32) -> &'static str {
+    match format {
+        x if x == sid_shader_format::SID_SHADER_FORMAT_GLSL as i32 => "GLSL",
+        x if x == sid_shader_format::SID_SHADER_FORMAT_SPIRV as i32 => "SPIR-V",
+        _ => "unknown",
+    }
+}
+
+fn shader_stage_name(stage: i32) -> &'static str {
+    match stage {
+        x if x == sid_shader_stage::SID_SHADER_STAGE_VERTEX as i32 => "vertex",
+        x if x == sid_shader_stage::SID_SHADER_STAGE_FRAGMENT as i32 => "fragment",
+        _ => "unknown",
+    }
+}
+
+impl fmt::Display for SidDecodedResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SidDecodedResource::SpriteSheet(sheet) => {
+                writeln!(f, "Sprite sheet {}", sheet.id)?;
+
+                if let Some((x, y)) = sheet.pivot {
+                    writeln!(f, "  pivot:  {x},{y}")?;
+                }
+
+                if let Some(id) = sheet.normal_map_id {
+                    writeln!(f, "  normal map:   texture {id}")?;
+                }
+
+                if let Some(id) = sheet.emissive_map_id {
+                    writeln!(f, "  emissive map: texture {id}")?;
+                }
+
+                write!(f, "{}", sheet.image)
+            }
+            SidDecodedResource::Texture(texture) => {
+                writeln!(f, "Texture {}", texture.id)?;
+                write!(f, "{}", texture.image)
+            }
+            SidDecodedResource::Palette(palette) => {
+                writeln!(f, "Palette {}", palette.id)?;
+                writeln!(f, "  colors: {}", palette.colors.len())?;
+
+                for (i, colors) in palette.colors.chunks(8).enumerate() {
+                    let colors: Vec<_> = colors
+                        .iter()
+                        .map(|[r, g, b, a]| format!("#{r:02x}{g:02x}{b:02x}{a:02x}"))
+                        .collect();
+
+                    writeln!(f, "    [{:>3}] {}", i * 8, colors.join(" "))?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::AnimationDef(def) => {
+                writeln!(f, "Animation def {}", def.id)?;
+                writeln!(f, "  sheet:  {}", def.sheet_id)?;
+
+                for (i, page_id) in def.page_ids.iter().enumerate() {
+                    writeln!(f, "  page {}: {page_id}", i + 1)?;
+                }
+                writeln!(
+                    f,
+                    "  frames: {} ({} region(s))",
+                    def.frames.len(),
+                    def.region_count()
+                )?;
+                writeln!(
+                    f,
+                    "  {:>5} {:>6} {:>4} {:>5} {:>5} {:>5} {:>5} {:>8} {:>8} {:>9} {:>7} {:>9}",
+                    "#",
+                    "region",
+                    "page",
+                    "x",
+                    "y",
+                    "w",
+                    "h",
+                    "duration",
+                    "offset",
+                    "source",
+                    "rotated",
+                    "pivot"
+                )?;
+
+                for (i, frame) in def.frames.iter().enumerate() {
+                    writeln!(
+                        f,
+                        "  {:>5} {:>6} {:>4} {:>5} {:>5} {:>5} {:>5} {:>8} {:>8} {:>9} {:>7} {:>9}",
+                        i,
+                        frame.region,
+                        frame.page,
+                        frame.x,
+                        frame.y,
+                        frame.width,
+                        frame.height,
+                        frame.duration,
+                        format!("{},{}", frame.offset_x, frame.offset_y),
+                        format!("{}x{}", frame.source_width, frame.source_height),
+                        frame.rotated,
+                        frame
+                            .pivot
+                            .map_or_else(|| "-".to_string(), |(x, y)| format!("{x},{y}"))
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Animation(animation) => {
+                writeln!(f, "Animation {}", animation.id)?;
+                writeln!(f, "  def:       {}", animation.def_id)?;
+                writeln!(f, "  offset:    {}", animation.offset)?;
+                writeln!(f, "  length:    {}", animation.length)?;
+                writeln!(f, "  direction: {}", animation.direction)?;
+                writeln!(f, "  loop:      {}", animation.looping)?;
+                writeln!(f, "  speed:     {}", animation.speed_scale)?;
+
+                for (frame, name) in &animation.events {
+                    writeln!(f, "  event:     {name:?} at frame {frame}")?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Slice(slice) => {
+                writeln!(f, "Slice {}", slice.id)?;
+                writeln!(f, "  sheet: {}", slice.sheet_id)?;
+                writeln!(f, "  keys:  {}", slice.keys.len())?;
+
+                for key in &slice.keys {
+                    let bounds = &key.bounds;
+                    write!(
+                        f,
+                        "  frame {:>5}: bounds {},{} {}x{}",
+                        key.frame, bounds.x, bounds.y, bounds.width, bounds.height
+                    )?;
+
+                    if let Some(center) = &key.center {
+                        write!(
+                            f,
+                            ", center {},{} {}x{}",
+                            center.x, center.y, center.width, center.height
+                        )?;
+                    }
+
+                    if let Some((x, y)) = key.pivot {
+                        write!(f, ", pivot {x},{y}")?;
+                    }
+
+                    writeln!(f)?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Character(character) => {
+                writeln!(f, "Character {}", character.id)?;
+                writeln!(f, "  parts: {}", character.parts.len())?;
+
+                for part in &character.parts {
+                    writeln!(
+                        f,
+                        "  {:?}: sheet {}, def {}, {} animation(s)",
+                        part.name,
+                        part.sheet_id,
+                        part.def_id,
+                        part.animation_ids.len()
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Scene(scene) => {
+                writeln!(f, "Scene {}", scene.id)?;
+                writeln!(f, "  entities: {}", scene.entities.len())?;
+
+                for entity in &scene.entities {
+                    let [x, y, rotation, scale_x, scale_y] = entity.transform;
+
+                    writeln!(
+                        f,
+                        "    {}: prefab {}, at {x},{y}, rotation {rotation}, scale {scale_x}x{scale_y}, layer {}, {} override(s)",
+                        entity.name.as_deref().map_or("-".to_string(), |name| format!("{name:?}")),
+                        entity.prefab_id,
+                        entity.layer,
+                        entity.overrides.len()
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Skeleton(skeleton) => {
+                writeln!(f, "Skeleton {}", skeleton.id)?;
+                writeln!(f, "  bones:       {}", skeleton.bones.len())?;
+
+                for bone in &skeleton.bones {
+                    let [x, y, rotation, scale_x, scale_y] = bone.transform;
+                    let parent = bone
+                        .parent
+                        .and_then(|parent| skeleton.bones.get(parent as usize))
+                        .map_or("-", |parent| &parent.name);
+
+                    writeln!(
+                        f,
+                        "    {:?}: parent {parent}, length {}, at {x},{y}, rotation {rotation}, scale {scale_x}x{scale_y}",
+                        bone.name, bone.length
+                    )?;
+                }
+
+                writeln!(f, "  slots:       {}", skeleton.slots.len())?;
+
+                for slot in &skeleton.slots {
+                    let [r, g, b, a] = slot.color;
+
+                    writeln!(
+                        f,
+                        "    {:?}: bone {}, attachment {}, color #{r:02x}{g:02x}{b:02x}{a:02x}",
+                        slot.name,
+                        slot.bone,
+                        slot.attachment.as_deref().unwrap_or("-")
+                    )?;
+                }
+
+                writeln!(f, "  attachments: {}", skeleton.attachments.len())?;
+
+                for attachment in &skeleton.attachments {
+                    writeln!(
+                        f,
+                        "    {:?}: slot {}, region {:?}, {}x{}",
+                        attachment.name,
+                        attachment.slot,
+                        attachment.region,
+                        attachment.width,
+                        attachment.height
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::SkeletalAnimation(animation) => {
+                writeln!(f, "Skeletal animation {}", animation.id)?;
+                writeln!(f, "  skeleton: {}", animation.skeleton_id)?;
+                writeln!(f, "  duration: {}s", animation.duration)?;
+
+                for timeline in &animation.bones {
+                    writeln!(
+                        f,
+                        "  bone {:?}: {}, {} key(s)",
+                        timeline.bone,
+                        bone_property_name(timeline.property),
+                        timeline.keys.len()
+                    )?;
+                }
+
+                for timeline in &animation.slots {
+                    writeln!(
+                        f,
+                        "  slot {:?}: attachment, {} key(s)",
+                        timeline.slot,
+                        timeline.keys.len()
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::NineSlice(nine_slice) => {
+                let region = &nine_slice.region;
+                let [left, top, right, bottom] = nine_slice.insets;
+
+                writeln!(f, "Nine-slice {}", nine_slice.id)?;
+                writeln!(f, "  sheet:  {}", nine_slice.sheet_id)?;
+                writeln!(
+                    f,
+                    "  region: {},{} {}x{}",
+                    region.x, region.y, region.width, region.height
+                )?;
+                writeln!(
+                    f,
+                    "  insets: left {left}, top {top}, right {right}, bottom {bottom}"
+                )
+            }
+            SidDecodedResource::Audio(audio) => {
+                writeln!(f, "Audio {}", audio.id)?;
+                writeln!(f, "  sample rate: {} Hz", audio.sample_rate)?;
+                writeln!(f, "  channels:    {}", audio.channel_count)?;
+                writeln!(
+                    f,
+                    "  format:      {} ({})",
+                    audio_format_name(audio.format),
+                    audio.format
+                )?;
+                writeln!(f, "  payload:     {} byte(s)", audio.payload.len())
+            }
+            SidDecodedResource::Font(font) => {
+                writeln!(f, "Font {}", font.id)?;
+                writeln!(f, "  size:     {} px", font.size)?;
+                writeln!(
+                    f,
+                    "  metrics:  ascent {}, descent {}, line gap {}",
+                    font.ascent, font.descent, font.line_gap
+                )?;
+                writeln!(
+                    f,
+                    "  atlas:    {}x{} {} ({} byte(s))",
+                    font.atlas_width,
+                    font.atlas_height,
+                    format_name(font.format),
+                    font.texture.len()
+                )?;
+                writeln!(f, "  glyphs:   {}", font.glyphs.len())?;
+                writeln!(
+                    f,
+                    "  {:>9} {:>5} {:>5} {:>5} {:>5} {:>9} {:>8}",
+                    "char", "x", "y", "w", "h", "offset", "advance"
+                )?;
+
+                for glyph in &font.glyphs {
+                    let character = match char::from_u32(glyph.character) {
+                        Some(character) if !character.is_control() => format!("{character:?}"),
+                        _ => format!("U+{:04X}", glyph.character),
+                    };
+
+                    writeln!(
+                        f,
+                        "  {:>9} {:>5} {:>5} {:>5} {:>5} {:>9} {:>8.2}",
+                        character,
+                        glyph.x,
+                        glyph.y,
+                        glyph.width,
+                        glyph.height,
+                        format!("{},{}", glyph.offset_x, glyph.offset_y),
+                        glyph.advance
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::BitmapFont(font) => {
+                writeln!(f, "Bitmap font {}", font.id)?;
+                writeln!(f, "  size:        {} px", font.size)?;
+                writeln!(f, "  line height: {}", font.line_height)?;
+                writeln!(f, "  base:        {}", font.base)?;
+                writeln!(f, "  glyphs:      {}", font.glyphs.len())?;
+                writeln!(f, "  kernings:    {}", font.kernings.len())?;
+
+                for (i, page_id) in font.page_ids.iter().enumerate() {
+                    writeln!(f, "  page {i}:      texture {page_id}")?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Mesh(mesh) => {
+                writeln!(f, "Mesh {}", mesh.id)?;
+                writeln!(f, "  vertices:  {}", mesh.vertices.len())?;
+                writeln!(
+                    f,
+                    "  indices:   {} ({}-bit)",
+                    mesh.indices.len(),
+                    mesh.index_size as u32 * 8
+                )?;
+
+                for (i, submesh) in mesh.submeshes.iter().enumerate() {
+                    write!(
+                        f,
+                        "  submesh {i}: {} indices from {}",
+                        submesh.index_count, submesh.first_index
+                    )?;
+
+                    match submesh.texture {
+                        Some((MESH_TEXTURE_SPRITE_SHEET, id)) => {
+                            writeln!(f, ", sprite sheet {id}")?
+                        }
+                        Some((_, id)) => writeln!(f, ", texture {id}")?,
+                        None => writeln!(f)?,
+                    }
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Curve(curve) => {
+                writeln!(f, "Curve {}", curve.id)?;
+                writeln!(
+                    f,
+                    "  interpolation: {}",
+                    curve_interpolation_name(curve.interpolation)
+                )?;
+                writeln!(f, "  values:        {} per key", curve.dimension)?;
+
+                match &curve.data {
+                    SidDecodedCurveData::Keyed(keys) => {
+                        writeln!(f, "  keys:          {}", keys.len())?;
+
+                        for (time, values) in keys {
+                            writeln!(f, "    {time:.3}s: {values:?}")?;
+                        }
+                    }
+                    SidDecodedCurveData::Sampled {
+                        sample_rate,
+                        start_time,
+                        samples,
+                    } => writeln!(
+                        f,
+                        "  samples:       {} at {sample_rate}/s from {start_time:.3}s",
+                        samples.len()
+                    )?,
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Tileset(tileset) => {
+                writeln!(f, "Tileset {}", tileset.id)?;
+                writeln!(f, "  sheet:   {}", tileset.sheet_id)?;
+                writeln!(
+                    f,
+                    "  tiles:   {} of {}x{}, {} column(s)",
+                    tileset.tile_count, tileset.tile_width, tileset.tile_height, tileset.columns
+                )?;
+                writeln!(f, "  spacing: {}", tileset.spacing)?;
+                writeln!(f, "  margin:  {}", tileset.margin)
+            }
+            SidDecodedResource::Tilemap(tilemap) => {
+                writeln!(f, "Tilemap {}", tilemap.id)?;
+                writeln!(
+                    f,
+                    "  size:     {}x{} tile(s) of {}x{}",
+                    tilemap.width, tilemap.height, tilemap.tile_width, tilemap.tile_height
+                )?;
+                writeln!(f, "  tilesets: {}", tilemap.tilesets.len())?;
+
+                for (first_gid, tileset_id) in &tilemap.tilesets {
+                    writeln!(f, "    first gid {first_gid:>5}: tileset {tileset_id}")?;
+                }
+
+                writeln!(f, "  layers:   {}", tilemap.layers.len())?;
+
+                for (i, layer) in tilemap.layers.iter().enumerate() {
+                    let used_count = layer.tiles.iter().filter(|&&tile| tile != 0).count();
+
+                    writeln!(
+                        f,
+                        "    layer {i}: {used_count} non-empty tile(s){}",
+                        if layer.visible { "" } else { ", hidden" }
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Shader(shader) => {
+                writeln!(f, "Shader {}", shader.id)?;
+                writeln!(
+                    f,
+                    "  format: {} ({})",
+                    shader_format_name(shader.format),
+                    shader.format
+                )?;
+                writeln!(f, "  stages: {}", shader.stages.len())?;
+
+                for stage in &shader.stages {
+                    writeln!(
+                        f,
+                        "    {:<8}: {} byte(s)",
+                        shader_stage_name(stage.stage),
+                        stage.code.len()
+                    )?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Material(material) => {
+                writeln!(f, "Material {}", material.id)?;
+                writeln!(f, "  shader:     {}", material.shader_id)?;
+                writeln!(f, "  blend mode: {}", blend_mode_name(material.blend_mode))?;
+
+                for (sampler, texture_id) in &material.textures {
+                    writeln!(f, "  texture {sampler:?}: {texture_id}")?;
+                }
+
+                for (uniform, components) in &material.uniforms {
+                    writeln!(f, "  uniform {uniform:?}: {components:?}")?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Blob(blob) => {
+                writeln!(f, "Blob {}", blob.id)?;
+                writeln!(f, "  size: {} byte(s)", blob.data.len())
+            }
+            SidDecodedResource::StringTable(table) => {
+                writeln!(f, "String table {}", table.id)?;
+                writeln!(f, "  keys:    {}", table.key_ids.len())?;
+                writeln!(f, "  locales: {}", table.locales.len())?;
+
+                for (locale, strings) in &table.locales {
+                    let size: usize = strings.iter().map(String::len).sum();
+                    writeln!(f, "    {locale}: {size} byte(s) of text")?;
+                }
+
+                Ok(())
+            }
+            SidDecodedResource::Prefab(prefab) => {
+                writeln!(f, "Prefab {}", prefab.id)?;
+                writeln!(f, "  components: {}", prefab.components.len())?;
+
+                for component in &prefab.components {
+                    match component {
+                        SidDecodedPrefabComponent::Sprite { sheet_id } => {
+                            writeln!(f, "    sprite:    sheet {sheet_id}")?
+                        }
+                        SidDecodedPrefabComponent::Animation { animation_id } => {
+                            writeln!(f, "    animation: {animation_id}")?
+                        }
+                        SidDecodedPrefabComponent::Collider {
+                            x,
+                            y,
+                            width,
+                            height,
+                            is_trigger,
+                        } => writeln!(
+                            f,
+                            "    collider:  {width}x{height} at ({x}, {y}){}",
+                            if *is_trigger { ", trigger" } else { "" }
+                        )?,
+                        SidDecodedPrefabComponent::Custom { values } => {
+                            writeln!(f, "    custom:    {} value(s)", values.len())?;
+
+                            for (key, value) in values {
+                                writeln!(f, "      {key} = {value:?}")?;
+                            }
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Decodes a loose resource, or every resource of a .sidpak archive, and
+/// returns a readable description of their contents.
+pub fn inspect<P: AsRef<Path>>(path: P) -> SidToolResult<String> {
+    let path = path.as_ref();
+
+    let data = fs::read(path).map_err(|error| {
+        SidToolError::IO(format!("Unable to read resource {:?}: {error}", path))
+    })?;
+
+    // Names are only known from the manifest written next to the resources.
+    let manifest = path
+        .parent()
+        .and_then(|folder| SidManifest::load(folder).ok());
+
+    if !data.starts_with(PAK_MAGIC) {
+        let resource = read_resource(&data)?;
+        return Ok(describe(&resource, manifest.as_ref()));
+    }
+
+    let (version, resources) = decode_pak(&data)?;
+    let mut out = format!("Pak version {version}, {} resource(s)\n", resources.len());
+
+    for resource in &resources {
+        out.push('\n');
+        out.push_str(&describe(resource, manifest.as_ref()));
+    }
+
+    Ok(out)
+}
+
+fn describe(resource: &SidDecodedResource, manifest: Option<&SidManifest>) -> String {
+    let mut out = resource.to_string();
+
+    if let Some(entry) = manifest.and_then(|manifest| manifest.find(resource.id())) {
+        out.push_str(&format!(
+            "  name:   {}\n  source: {}\n",
+            entry.name,
+            entry.source_path.display()
+        ));
+    }
+
+    out
+}
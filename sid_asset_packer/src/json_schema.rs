@@ -0,0 +1,482 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAnimationDirection, SidAnimationEvent,
+        SidAnimationFrameAsset, SidAnimationFrameDims, SidAnimationFramePos, SidAnimationFrameTrim,
+        SidColorSpace, SidPackedAsset, SidPixelFormat, SidSlicePivot, SidSpriteSheetAsset,
+    },
+    error::{SidToolError, SidToolResult},
+    sid::{
+        SidAnimationFrameCoord, SidAnimationFrameDim, SidAnimationFrameDuration,
+        SidAnimationFrameIndex, SidAnimationPageIndex, SidSliceCoord, SidSpriteSheetDim,
+    },
+};
+
+// The schemas are written by hand next to each other, but the defaults and the
+// enum values they hold are serialized from the types themselves, and a field
+// serialized by a type but missing from its schema fails the generation.
+
+pub static DEFAULT_JSON_SCHEMAS_PATH: &str = "./schemas";
+
+/// Extension of the JSON Schema files, e.g. `sprite_sheets.schema.json`.
+pub static JSON_SCHEMA_EXTENSION: &str = "schema.json";
+
+static JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Type written in asset JSON files, described by a JSON Schema.
+pub trait SidJsonSchema {
+    fn json_schema() -> Value;
+}
+
+macro_rules! integer_json_schema {
+    ($($type:ty),* $(,)?) => {
+        $(impl SidJsonSchema for $type {
+            fn json_schema() -> Value {
+                json!({
+                    "type": "integer",
+                    "minimum": <$type>::MIN,
+                    "maximum": <$type>::MAX,
+                })
+            }
+        })*
+    };
+}
+
+integer_json_schema!(u8, u16, u32, u64, i32);
+
+impl SidJsonSchema for f32 {
+    fn json_schema() -> Value {
+        json!({ "type": "number" })
+    }
+}
+
+impl SidJsonSchema for bool {
+    fn json_schema() -> Value {
+        json!({ "type": "boolean" })
+    }
+}
+
+impl SidJsonSchema for String {
+    fn json_schema() -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl SidJsonSchema for PathBuf {
+    fn json_schema() -> Value {
+        json!({
+            "type": "string",
+            "description": "Path relative to the asset, with forward slashes.",
+        })
+    }
+}
+
+impl<T: SidJsonSchema> SidJsonSchema for Option<T> {
+    fn json_schema() -> Value {
+        json!({ "anyOf": [T::json_schema(), { "type": "null" }] })
+    }
+}
+
+impl<T: SidJsonSchema> SidJsonSchema for Vec<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+impl<T: SidJsonSchema, const N: usize> SidJsonSchema for [T; N] {
+    fn json_schema() -> Value {
+        json!({
+            "type": "array",
+            "items": T::json_schema(),
+            "minItems": N,
+            "maxItems": N,
+        })
+    }
+}
+
+/// Property of an object: its name, schema, description, and whether it must
+/// be set.
+type SidJsonField = (&'static str, Value, &'static str, bool);
+
+fn field<T: SidJsonSchema>(name: &'static str, description: &'static str) -> SidJsonField {
+    (name, T::json_schema(), description, true)
+}
+
+/// Property the asset may leave out, taking its default value.
+fn optional_field<T: SidJsonSchema>(name: &'static str, description: &'static str) -> SidJsonField {
+    (name, T::json_schema(), description, false)
+}
+
+/// Schema of an object holding `fields`. `defaults` is the object as
+/// serialized once built with its default values, whose fields must all be
+/// described.
+fn object<T: Serialize>(description: &str, fields: Vec<SidJsonField>, defaults: T) -> Value {
+    let defaults = serde_json::to_value(defaults).expect("Unable to serialize defaults");
+    let mut properties = Map::new();
+    let mut required = vec![];
+
+    for (name, mut schema, field_description, is_required) in fields {
+        if !field_description.is_empty() {
+            schema["description"] = json!(field_description);
+        }
+
+        if is_required {
+            required.push(name);
+        } else if let Some(default) = defaults.get(name) {
+            schema["default"] = default.clone();
+        }
+
+        properties.insert(name.to_string(), schema);
+    }
+
+    if let Some(defaults) = defaults.as_object() {
+        for name in defaults.keys() {
+            assert!(
+                properties.contains_key(name),
+                "Field {name:?} of {description:?} is missing from its schema"
+            );
+        }
+    }
+
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Schema of an enum serialized as one of the names of `variants`.
+fn string_enum<T: Serialize>(description: &str, variants: &[T]) -> Value {
+    let names: Vec<Value> = variants
+        .iter()
+        .map(|variant| serde_json::to_value(variant).expect("Unable to serialize variant"))
+        .collect();
+
+    json!({
+        "type": "string",
+        "description": description,
+        "enum": names,
+    })
+}
+
+impl SidJsonSchema for SidColorSpace {
+    fn json_schema() -> Value {
+        string_enum(
+            "How the color channels of an image are encoded. Alpha is always linear.",
+            &[SidColorSpace::Srgb, SidColorSpace::Linear],
+        )
+    }
+}
+
+impl SidJsonSchema for SidPixelFormat {
+    fn json_schema() -> Value {
+        string_enum(
+            "Pixel layout a raw image is packed with.",
+            &[
+                SidPixelFormat::Source,
+                SidPixelFormat::L8,
+                SidPixelFormat::La8,
+                SidPixelFormat::Rgb8,
+                SidPixelFormat::Rgba8,
+                SidPixelFormat::Rgba16,
+            ],
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationDirection {
+    fn json_schema() -> Value {
+        string_enum(
+            "Order the frames of an animation are played in.",
+            &[
+                SidAnimationDirection::Forward,
+                SidAnimationDirection::Reverse,
+                SidAnimationDirection::PingPong,
+                SidAnimationDirection::PingPongReverse,
+            ],
+        )
+    }
+}
+
+impl SidJsonSchema for SidSlicePivot {
+    fn json_schema() -> Value {
+        object(
+            "Point in pixels from the top-left corner.",
+            vec![
+                field::<SidSliceCoord>("x", ""),
+                field::<SidSliceCoord>("y", ""),
+            ],
+            SidSlicePivot::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationFramePos {
+    fn json_schema() -> Value {
+        object(
+            "Position in pixels.",
+            vec![
+                field::<SidAnimationFrameCoord>("x", ""),
+                field::<SidAnimationFrameCoord>("y", ""),
+            ],
+            SidAnimationFramePos::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationFrameDims {
+    fn json_schema() -> Value {
+        object(
+            "Size in pixels.",
+            vec![
+                field::<SidAnimationFrameDim>("width", ""),
+                field::<SidAnimationFrameDim>("height", ""),
+            ],
+            SidAnimationFrameDims::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationFrameTrim {
+    fn json_schema() -> Value {
+        object(
+            "Transparent borders removed from a frame when it was exported.",
+            vec![
+                field::<SidAnimationFramePos>(
+                    "offset",
+                    "Position of the trimmed frame within the original one.",
+                ),
+                field::<SidAnimationFrameDims>(
+                    "source_dims",
+                    "Size of the original frame, before trimming.",
+                ),
+            ],
+            SidAnimationFrameTrim::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationFrameAsset {
+    fn json_schema() -> Value {
+        object(
+            "Frame of an animation definition.",
+            vec![
+                field::<SidAnimationFramePos>("pos", "Position of the frame in the sheet."),
+                field::<SidAnimationFrameDims>("dims", "Size of the frame in the sheet."),
+                field::<SidAnimationFrameDuration>(
+                    "duration",
+                    "Duration of the frame, in milliseconds.",
+                ),
+                optional_field::<Option<SidAnimationFrameTrim>>("trim", ""),
+                optional_field::<bool>(
+                    "rotated",
+                    "Whether the frame is stored rotated by 90 degrees clockwise in the sheet, \
+                     in which case dims is the unrotated size.",
+                ),
+                optional_field::<Option<SidSlicePivot>>(
+                    "pivot",
+                    "Point the frame is drawn around, in pixels from the top-left corner of \
+                     its source size, replacing the pivot of the sprite sheet.",
+                ),
+                optional_field::<SidAnimationPageIndex>(
+                    "page",
+                    "Sheet holding the frame: 0 for the sheet of its animation definition, \
+                     or else the index of the sheet in its pages, plus one.",
+                ),
+            ],
+            SidAnimationFrameAsset::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationEvent {
+    fn json_schema() -> Value {
+        object(
+            "Named event of an animation, e.g. a footstep.",
+            vec![
+                field::<SidAnimationFrameIndex>(
+                    "frame",
+                    "Frame of the event, relative to the first frame of the animation.",
+                ),
+                field::<String>("name", ""),
+            ],
+            SidAnimationEvent::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidSpriteSheetAsset {
+    fn json_schema() -> Value {
+        object(
+            "Sprite sheet: an image holding the frames of animations.",
+            vec![
+                field::<String>("name", ""),
+                field::<PathBuf>("image_path", "Image of the sheet."),
+                field::<SidSpriteSheetDim>("width", "Width of the image, in pixels."),
+                field::<SidSpriteSheetDim>("height", "Height of the image, in pixels."),
+                field::<String>("format", "Format of the image, e.g. RGBA8888."),
+                optional_field::<SidColorSpace>("color_space", "Color space of the image."),
+                optional_field::<SidPixelFormat>(
+                    "pixel_format",
+                    "Pixel layout of the packed image.",
+                ),
+                optional_field::<Option<String>>(
+                    "palette",
+                    "Name of the shared palette the image is mapped to, packing it as 8-bit \
+                     indices.",
+                ),
+                optional_field::<Option<[u8; 3]>>(
+                    "color_key",
+                    "Color made transparent, e.g. magenta in legacy art, as red, green and \
+                     blue values.",
+                ),
+                optional_field::<Option<SidSlicePivot>>(
+                    "pivot",
+                    "Default pivot of the frames, in pixels from their top-left corner.",
+                ),
+                optional_field::<Option<String>>(
+                    "atlas_group",
+                    "Atlas the sheet is packed into by the atlas command, instead of the \
+                     default one.",
+                ),
+                optional_field::<Option<String>>(
+                    "normal_map",
+                    "Name of the texture holding the normals of the image, with the same \
+                     layout, for lit rendering.",
+                ),
+                optional_field::<Option<String>>(
+                    "emissive_map",
+                    "Name of the texture holding the light emitted by the image, with the \
+                     same layout.",
+                ),
+            ],
+            SidSpriteSheetAsset::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationDefAsset {
+    fn json_schema() -> Value {
+        object(
+            "Animation definition: the frames the animations of a sprite sheet pick from.",
+            vec![
+                field::<SidAnimationFrameIndex>("frame_count", "Number of frames."),
+                field::<Vec<SidAnimationFrameAsset>>("frames", ""),
+                field::<String>("name", ""),
+                field::<String>("sheet_name", "Name of the sprite sheet holding the frames."),
+                optional_field::<Vec<String>>(
+                    "pages",
+                    "Sheets holding the frames that did not fit in sheet_name, e.g. once split \
+                     into atlas pages, referenced by the page of the frames.",
+                ),
+            ],
+            SidAnimationDefAsset::new(),
+        )
+    }
+}
+
+impl SidJsonSchema for SidAnimationAsset {
+    fn json_schema() -> Value {
+        object(
+            "Animation: a range of the frames of an animation definition.",
+            vec![
+                field::<SidAnimationFrameIndex>(
+                    "offset",
+                    "Index of the first frame in the animation definition.",
+                ),
+                field::<SidAnimationFrameIndex>("length", "Number of frames."),
+                field::<String>("name", ""),
+                field::<String>("def_name", "Name of the animation definition."),
+                optional_field::<SidAnimationDirection>("direction", ""),
+                optional_field::<bool>(
+                    "loop",
+                    "Play the animation again once it ends, instead of holding its last frame.",
+                ),
+                optional_field::<f32>(
+                    "speed_scale",
+                    "Default playback speed, 1 being the speed of the frame durations.",
+                ),
+                optional_field::<Vec<SidAnimationEvent>>(
+                    "events",
+                    "Events fired when the animation reaches their frame, sorted by frame.",
+                ),
+            ],
+            SidAnimationAsset::new(),
+        )
+    }
+}
+
+/// Complete schema of the asset files of the `T` namespace, with the fields
+/// every asset may have.
+fn asset_json_schema<T: SidPackedAsset + SidJsonSchema>() -> Value {
+    let mut schema = T::json_schema();
+    schema["$schema"] = json!(JSON_SCHEMA_DIALECT);
+    schema["title"] = json!(T::namespace());
+    schema["properties"]["tags"] = json!({
+        "type": "array",
+        "items": { "type": "string" },
+        "description": "Tags deciding whether the asset is packed, e.g. demo or debug.",
+    });
+
+    schema
+}
+
+/// Schemas of the asset files, by namespace.
+pub fn generate_json_schemas() -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            SidSpriteSheetAsset::namespace(),
+            asset_json_schema::<SidSpriteSheetAsset>(),
+        ),
+        (
+            SidAnimationDefAsset::namespace(),
+            asset_json_schema::<SidAnimationDefAsset>(),
+        ),
+        (
+            SidAnimationAsset::namespace(),
+            asset_json_schema::<SidAnimationAsset>(),
+        ),
+    ]
+}
+
+/// Writes the schema of every namespace to `folder_path`, e.g.
+/// `sprite_sheets.schema.json`.
+pub fn write_json_schemas(folder_path: &Path) -> SidToolResult<Vec<PathBuf>> {
+    fs::create_dir_all(folder_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Unable to create folder at path {:?}: {error}",
+            folder_path
+        ))
+    })?;
+
+    let mut paths = vec![];
+
+    for (namespace, schema) in generate_json_schemas() {
+        let path = folder_path.join(format!("{namespace}.{JSON_SCHEMA_EXTENSION}"));
+        let mut contents = serde_json::to_string_pretty(&schema).map_err(|error| {
+            SidToolError::Malformed(format!("Unable to write schema of {namespace}: {error}"))
+        })?;
+        contents.push('\n');
+
+        fs::write(&path, contents).map_err(|error| {
+            SidToolError::IO(format!("Unable to write schema {:?}: {error}", path))
+        })?;
+
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
@@ -2,7 +2,46 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
+#![allow(clippy::new_without_default)]
+
+pub mod animation_export;
 pub mod asset;
-pub(crate) mod log;
+pub mod atlas;
+#[cfg(feature = "browse")]
+pub mod browse;
+pub mod c_header;
+pub mod cache;
+pub mod clean;
+pub mod compression;
+pub mod config;
+pub mod error;
+pub mod graph;
+pub mod id_overrides;
+pub mod id_registry;
+pub mod inspect;
+pub mod json_schema;
+pub mod log;
+pub mod manifest;
+pub mod naming;
+pub mod packer;
+pub mod pak;
+pub mod palette;
+pub mod patch;
+pub mod portable_path;
+pub mod preview;
+pub mod progress;
+pub mod registry;
+pub mod report;
 pub mod resource;
+pub mod schema;
+#[cfg(feature = "serve")]
+pub mod serve;
 pub mod sid;
+pub mod string_table;
+pub mod tags;
+pub mod texture;
+pub mod timings;
+pub mod unpack;
+pub mod verify;
+pub mod walk;
+pub mod watch;
@@ -2,45 +2,293 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! sid_debug {
-    ($($arg:tt)*) => (println!("[DEBUG] {}", format_args!($($arg)*)));
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock, PoisonError, RwLock,
+    },
+};
+
+pub use ::log::{Level, LevelFilter};
+
+use crate::{
+    error::{SidToolError, SidToolResult},
+    progress,
+};
+
+static ERROR_COUNT: AtomicUsize = AtomicUsize::new(0);
+static WARNING_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// The sid_*! macros go through the `log` facade, so that the messages of the
+// tools and of the crates they use end up in the same place, and so that
+// applications using the tools as a library can plug their own logger.
+static LOGGER: SidLogger = SidLogger {
+    state: RwLock::new(None),
+};
+
+static LOGGER_INSTALLED: OnceLock<bool> = OnceLock::new();
+
+/// Prefix of the targets of the tools, i.e. of their module paths.
+const TOOL_TARGET_PREFIX: &str = "sid_";
+
+/// Filtering and outputs of the tools logger.
+#[derive(Debug, Clone)]
+pub struct SidLogConfig {
+    /// Most verbose level printed by the tools.
+    pub level: LevelFilter,
+    /// Levels replacing `level` for a target and its submodules, e.g.
+    /// `sid_asset_packer::texture`. The longest matching target wins.
+    pub filters: Vec<(String, LevelFilter)>,
+    /// File every printed message is appended to as well.
+    pub file: Option<PathBuf>,
 }
 
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! sid_info {
-    ($($arg:tt)*) => (println!("[INFO] {}", format_args!($($arg)*)));
+impl SidLogConfig {
+    /// Errors only, or every message in debug builds.
+    pub fn new() -> Self {
+        Self {
+            level: default_level(),
+            filters: vec![],
+            file: None,
+        }
+    }
 }
 
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! sid_warning {
-    ($($arg:tt)*) => (println!("[WARNING] {}", format_args!($($arg)*)));
+/// Parses a filter of the form `target=level`, or a bare `level`, applying to
+/// every target.
+pub fn parse_filter(filter: &str) -> SidToolResult<(String, LevelFilter)> {
+    let (target, level) = match filter.split_once('=') {
+        Some((target, level)) => (target.trim(), level),
+        None => ("", filter),
+    };
+
+    Ok((target.to_string(), parse_level(level)?))
+}
+
+/// Parses a level name, e.g. `warning` or `debug`.
+pub fn parse_level(level: &str) -> SidToolResult<LevelFilter> {
+    let level = level.trim();
+
+    if level.eq_ignore_ascii_case("warning") {
+        return Ok(LevelFilter::Warn);
+    }
+
+    LevelFilter::from_str(level)
+        .map_err(|_| SidToolError::Malformed(format!("Unknown log level {:?}", level)))
+}
+
+fn default_level() -> LevelFilter {
+    if cfg!(debug_assertions) {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Error
+    }
+}
+
+struct SidLoggerState {
+    config: SidLogConfig,
+    file: Option<Mutex<File>>,
+}
+
+impl SidLoggerState {
+    fn new(config: SidLogConfig) -> SidToolResult<Self> {
+        let file = match &config.file {
+            Some(path) => {
+                let file = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|error| {
+                        SidToolError::IO(format!("Unable to open log file {:?}: {error}", path))
+                    })?;
+
+                Some(Mutex::new(file))
+            }
+            None => None,
+        };
+
+        Ok(Self { config, file })
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let filter = self
+            .config
+            .filters
+            .iter()
+            .filter(|(filter_target, _)| {
+                filter_target.is_empty()
+                    || target == filter_target
+                    || target
+                        .strip_prefix(filter_target.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(filter_target, _)| filter_target.len());
+
+        match filter {
+            Some((_, level)) => *level,
+            // Other crates only get to print their warnings and errors, unless
+            // a filter says otherwise.
+            None if !target.starts_with(TOOL_TARGET_PREFIX) => {
+                self.config.level.min(LevelFilter::Warn)
+            }
+            None => self.config.level,
+        }
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.config
+            .filters
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.config.level, Ord::max)
+    }
+}
+
+struct SidLogger {
+    state: RwLock<Option<SidLoggerState>>,
+}
+
+impl SidLogger {
+    fn with_state<R>(&self, f: impl FnOnce(&SidLoggerState) -> R) -> R {
+        let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+
+        match state.as_ref() {
+            Some(state) => f(state),
+            None => f(&SidLoggerState {
+                config: SidLogConfig::new(),
+                file: None,
+            }),
+        }
+    }
+}
+
+impl ::log::Log for SidLogger {
+    fn enabled(&self, metadata: &::log::Metadata) -> bool {
+        self.with_state(|state| metadata.level() <= state.level_for(metadata.target()))
+    }
+
+    fn log(&self, record: &::log::Record) {
+        self.with_state(|state| {
+            if record.level() > state.level_for(record.target()) {
+                return;
+            }
+
+            let label = level_label(record.level());
+
+            progress::suspend(|| {
+                if record.level() == Level::Error {
+                    eprintln!("[{label}] {}", record.args());
+                } else {
+                    println!("[{label}] {}", record.args());
+                }
+            });
+
+            if let Some(file) = &state.file {
+                let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+                let _ = writeln!(file, "[{label}] {}: {}", record.target(), record.args());
+            }
+        });
+    }
+
+    fn flush(&self) {
+        self.with_state(|state| {
+            if let Some(file) = &state.file {
+                let _ = file.lock().unwrap_or_else(PoisonError::into_inner).flush();
+            }
+        });
+    }
+}
+
+fn level_label(level: Level) -> &'static str {
+    match level {
+        Level::Error => "ERROR",
+        Level::Warn => "WARNING",
+        Level::Info => "INFO",
+        Level::Debug => "DEBUG",
+        Level::Trace => "TRACE",
+    }
+}
+
+// Installs the tools logger, unless the application already set its own.
+fn install_logger() -> bool {
+    *LOGGER_INSTALLED.get_or_init(|| {
+        let installed = ::log::set_logger(&LOGGER).is_ok();
+
+        if installed {
+            ::log::set_max_level(LOGGER.with_state(SidLoggerState::max_level));
+        }
+
+        installed
+    })
+}
+
+/// Makes the tools logger print the messages allowed by `config`.
+pub fn init(config: SidLogConfig) -> SidToolResult<()> {
+    let state = SidLoggerState::new(config)?;
+    let max_level = state.max_level();
+
+    *LOGGER.state.write().unwrap_or_else(PoisonError::into_inner) = Some(state);
+
+    if install_logger() {
+        ::log::set_max_level(max_level);
+    }
+
+    Ok(())
+}
+
+/// Logs a message of `target`, the module path of the caller. Prefer the
+/// sid_*! macros.
+pub fn log(level: Level, target: &str, args: fmt::Arguments) {
+    install_logger();
+    ::log::log!(target: target, level, "{}", args);
+}
+
+// Warnings are counted even when they are not printed.
+pub fn record_warning() {
+    WARNING_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_error() {
+    ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn warning_count() -> usize {
+    WARNING_COUNT.load(Ordering::Relaxed)
+}
+
+pub fn error_count() -> usize {
+    ERROR_COUNT.load(Ordering::Relaxed)
 }
 
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! sid_debug {
-    ($($arg:tt)*) => {{}};
+    ($($arg:tt)*) => ($crate::log::log($crate::log::Level::Debug, module_path!(), format_args!($($arg)*)));
 }
 
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! sid_info {
-    ($($arg:tt)*) => {{}};
+    ($($arg:tt)*) => ($crate::log::log($crate::log::Level::Info, module_path!(), format_args!($($arg)*)));
 }
 
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! sid_warning {
-    ($($arg:tt)*) => {{}};
+    ($($arg:tt)*) => {{
+        $crate::log::record_warning();
+        $crate::log::log($crate::log::Level::Warn, module_path!(), format_args!($($arg)*))
+    }};
 }
 
 #[macro_export]
 macro_rules! sid_error {
-    ($($arg:tt)*) => (eprintln!("[ERROR] {}", format_args!($($arg)*)));
+    ($($arg:tt)*) => {{
+        $crate::log::record_error();
+        $crate::log::log($crate::log::Level::Error, module_path!(), format_args!($($arg)*))
+    }};
 }
 
 #[macro_export]
@@ -2,31 +2,1093 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
 use sid_asset_packer::{
-    asset,
-    resource::{self, from_assets_to_resources},
+    animation_export,
+    asset::{self, SidColorSpace, SidPixelFormat},
+    atlas::{build_atlases, SidAtlasOptions},
+    c_header, clean,
+    compression::SidCompression,
+    config::SidToolsConfig,
+    error::{SidToolError, SidToolResult},
+    graph::SidGraphFormat,
+    id_overrides::{self, SidIdOverrides},
+    inspect, json_schema,
+    log::{self, LevelFilter, SidLogConfig},
+    naming::SidNameRules,
+    packer::SidPacker,
+    palette::{build_palette, SidPaletteOptions},
+    patch, preview,
+    report::SidReport,
+    resource::{
+        self, SidEndianness, SidInputCollisionPolicy, SidOutputFormat, SidOverwritePolicy,
+        SidPackOptions,
+    },
+    sid, sid_error, sid_info,
+    texture::{self, SidMipmapFilter, SidTextureEncoding},
+    timings, unpack, verify, walk, watch,
 };
 
-use std::{env, path::PathBuf};
+#[cfg(feature = "browse")]
+use sid_asset_packer::browse;
+#[cfg(feature = "serve")]
+use sid_asset_packer::serve::{self, SidReloadServer};
+
+use std::{
+    mem,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Instant,
+};
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+#[derive(Parser)]
+#[command(version, about = "Packs Space it, Dummy! assets into binary resources")]
+struct Cli {
+    /// Print debug and info messages, same as --log-level debug.
+    #[arg(short, long, global = true, conflicts_with = "log_level")]
+    verbose: bool,
 
-    let assets_input_path = if args.len() > 1 {
-        PathBuf::from(&args[1])
-    } else {
-        let mut assets_input_path = PathBuf::from(".");
-        assets_input_path.push(asset::DEFAULT_ASSETS_PATH);
-        assets_input_path
-    };
+    /// Most verbose level of the printed messages [default: error, or debug in debug builds].
+    #[arg(long, value_enum, global = true)]
+    log_level: Option<LogLevel>,
+
+    /// Level of the messages of a module and its submodules, e.g. sid_asset_packer::texture=debug.
+    #[arg(long, value_name = "TARGET=LEVEL", global = true, value_delimiter = ',', value_parser = log::parse_filter)]
+    log_filter: Vec<(String, LevelFilter)>,
+
+    /// File the printed messages are appended to as well, with their module.
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Write a report of the run, with the status of every asset.
+    #[arg(long, value_enum, global = true)]
+    report: Option<ReportFormat>,
+
+    /// File the report is written to, instead of the standard output.
+    #[arg(long, global = true, requires = "report")]
+    report_output: Option<PathBuf>,
+
+    /// Project configuration file, instead of the closest sid_tools.toml.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Walk through symbolic links (and junctions) to files and folders, instead of skipping them.
+    #[arg(long, global = true)]
+    follow_symlinks: bool,
+
+    /// File of the ids fixed or reserved instead of generated [default: id_overrides.toml next to the configuration file, if any].
+    #[arg(long, global = true)]
+    id_overrides: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Pack every asset from the input folders into the output folder.
+    Pack(PackArgs),
+    /// Check every asset from the input folder against its schema and references, without writing resources.
+    Validate(InputArgs),
+    /// List every asset from the input folder with its resource id.
+    List(InputArgs),
+    /// Export the references between the assets of the input folder.
+    Graph(GraphArgs),
+    /// Browse the assets of the input folder and their resources in the terminal, searching by name or id.
+    #[cfg(feature = "browse")]
+    Browse(BrowseArgs),
+    /// Merge every sprite sheet of the input folder into shared atlas pages.
+    Atlas(AtlasArgs),
+    /// Quantize the sprite sheets and textures opting in to a palette into it.
+    Palette(PaletteArgs),
+    /// Decode a packed resource or .sidpak archive and print its contents.
+    Inspect(InspectArgs),
+    /// Render the frames of an animation, asset or packed resource, into an animated GIF or APNG with their durations.
+    ExportGif(ExportGifArgs),
+    /// Rebuild the assets a resources folder was packed from.
+    Unpack(UnpackArgs),
+    /// Delete the resource files of a resources folder missing from its manifest.
+    Clean(CleanArgs),
+    /// Write a patch with the resources added or changed between two builds, and the removed ones.
+    Diff(DiffArgs),
+    /// Check the checksums of the resources of a resources folder, and their manifest entries.
+    Verify(VerifyArgs),
+    /// Write a C header describing the layout of the resources, for the engine reader.
+    Header(HeaderArgs),
+    /// Write JSON Schemas of the assets, for editors to validate and complete hand-written ones.
+    Schema(SchemaArgs),
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// Assets folder, containing one subfolder per namespace [default: the first input folder of the pack, or assets].
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct GraphArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Format of the graph [default: dot].
+    #[arg(long, value_enum)]
+    format: Option<GraphFormat>,
+
+    /// File the graph is written to, instead of the standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct InspectArgs {
+    /// Loose resource file or .sidpak archive.
+    path: PathBuf,
+
+    /// Draw the image of a sprite sheet or a texture in the terminal, or play the frames of an animation once.
+    #[arg(long)]
+    preview: bool,
+
+    /// Resource of the .sidpak archive to preview.
+    #[arg(long, requires = "preview")]
+    id: Option<sid::SidStringId>,
+
+    /// Width of the preview, in characters [default: 64].
+    #[arg(long, requires = "preview")]
+    preview_width: Option<u32>,
+}
+
+#[derive(Args)]
+struct ExportGifArgs {
+    /// Animation or animation definition, as a JSON asset, a loose resource file or a .sidpak archive.
+    path: PathBuf,
+
+    /// Resource of the .sidpak archive to export.
+    #[arg(long)]
+    id: Option<sid::SidStringId>,
+
+    /// Animated image written, as an APNG if its extension is .png or .apng, or else as a GIF.
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Times the frames are scaled up, without filtering [default: 1].
+    #[arg(long)]
+    scale: Option<u32>,
+}
+
+#[derive(Args)]
+struct UnpackArgs {
+    /// Resources folder, with loose resources or a .sidpak archive [default: resources].
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Assets folder the rebuilt assets are written to.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+#[cfg(feature = "browse")]
+#[derive(Args)]
+struct BrowseArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Resources folder, with loose resources or a .sidpak archive [default: resources].
+    #[arg(short, long)]
+    resources: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct CleanArgs {
+    /// Resources folder, with its manifest [default: resources].
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Print the stale resource files without deleting them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// Resources folder, with loose resources or a .sidpak archive [default: resources].
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct HeaderArgs {
+    /// File the header is written to, instead of the standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SchemaArgs {
+    /// Folder the schemas are written to, one per namespace [default: schemas].
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Resources folder or .sidpak archive of the previous build.
+    old: PathBuf,
+
+    /// Resources folder or .sidpak archive of the new build.
+    new: PathBuf,
+
+    /// Folder the patch is written to.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// One file per resource, named after its id.
+    Loose,
+    /// A single .sidpak archive in the output folder.
+    Pak,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum InputCollisionPolicy {
+    /// Fail, listing every asset defined by several folders.
+    Error,
+    /// Pack the asset of the first folder defining it.
+    FirstWins,
+    /// Pack the asset of the last folder defining it.
+    LastWins,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    /// Graphviz DOT.
+    Dot,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogLevel {
+    Off,
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warning => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Endianness {
+    /// Little endian.
+    Le,
+    /// Big endian.
+    Be,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MipmapFilter {
+    /// Average of every 2x2 block of the previous level.
+    Box,
+    /// Lanczos resampling of the base level, sharper but slower.
+    Lanczos,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TextureEncoding {
+    /// Uncompressed pixels.
+    Raw,
+    /// BC1, opaque (desktop).
+    Bc1,
+    /// BC3, with alpha (desktop).
+    Bc3,
+    /// BC7, with alpha (desktop).
+    Bc7,
+    /// ETC2 RGB8 or RGBA8 (mobile).
+    Etc2,
+    /// ASTC 4x4 (mobile).
+    Astc,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorSpace {
+    /// Gamma-encoded colors.
+    Srgb,
+    /// Linear colors.
+    Linear,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PixelFormat {
+    /// The layout of each image if supported, or else RGBA16 or RGBA8.
+    Source,
+    /// 8-bit grayscale.
+    L8,
+    /// 8-bit grayscale with alpha.
+    La8,
+    Rgb8,
+    Rgba8,
+    Rgba16,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Compression {
+    None,
+    Lz4,
+    Zstd,
+}
+
+#[derive(Args)]
+struct PackArgs {
+    /// Assets folder, containing one subfolder per namespace, repeated to merge several folders in order [default: assets].
+    #[arg(short, long)]
+    input: Vec<PathBuf>,
+
+    /// Platform of the [targets.<NAME>] section of the configuration file to pack for, repeated to pack for several.
+    #[arg(long, value_name = "NAME")]
+    target: Vec<String>,
+
+    /// Only pack the assets with one of these tags, e.g. demo.
+    #[arg(long, value_delimiter = ',', value_name = "TAGS")]
+    only_tags: Vec<String>,
+
+    /// Leave out the assets with one of these tags, e.g. debug.
+    #[arg(long, value_delimiter = ',', value_name = "TAGS")]
+    exclude_tags: Vec<String>,
+
+    /// Which asset is packed when several input folders define it [default: error].
+    #[arg(long, value_enum)]
+    input_collisions: Option<InputCollisionPolicy>,
+
+    /// Resources folder the packed files are written to [default: resources].
+    #[arg(short, long, conflicts_with = "target")]
+    output: Option<PathBuf>,
+
+    /// How resources are laid out in the output folder [default: loose].
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Delete the resources, pak, manifest and build cache of the output folder before packing.
+    #[arg(long, conflicts_with_all = ["no_overwrite", "merge"])]
+    clean_output: bool,
+
+    /// Fail if any asset maps to the id of a resource already in the output folder.
+    #[arg(long, conflicts_with = "merge")]
+    no_overwrite: bool,
+
+    /// Keep the resources of the output folder that are not repacked, in the manifest and the pak.
+    #[arg(long)]
+    merge: bool,
+
+    /// Compression applied to every resource [default: none].
+    #[arg(long, value_enum)]
+    compress: Option<Compression>,
+
+    /// Byte order of the resources, matching the target platform [default: le].
+    #[arg(long, value_enum)]
+    endianness: Option<Endianness>,
+
+    /// Generate the mip chain of sprite sheets and textures, with the given filter [default: box].
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "box")]
+    mipmaps: Option<MipmapFilter>,
+
+    /// GPU format of the packed sprite sheets and textures [default: raw].
+    #[arg(long, value_enum)]
+    texture_encoding: Option<TextureEncoding>,
+
+    /// Convert sprite sheets and textures to this color space [default: the one of each asset].
+    #[arg(long, value_enum)]
+    color_space: Option<ColorSpace>,
+
+    /// Convert raw sprite sheets and textures to this pixel layout [default: the one of each asset].
+    #[arg(long, value_enum)]
+    pixel_format: Option<PixelFormat>,
+
+    /// Largest width and height of the packed sprite sheets and textures, larger ones failing to pack.
+    #[arg(long)]
+    max_texture_size: Option<u32>,
+
+    /// Pad the packed sprite sheets and textures up to power-of-two sizes.
+    #[arg(long)]
+    power_of_two: bool,
+
+    /// Color made transparent in the sprite sheets and textures without a color key of their own, e.g. FF00FF.
+    #[arg(long, value_name = "RRGGBB", value_parser = texture::parse_color_key)]
+    color_key: Option<[u8; 3]>,
+
+    /// Number of assets packed in parallel (0 uses one job per logical CPU) [default: 0].
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    /// Repack every asset, ignoring the build cache.
+    #[arg(short, long)]
+    force: bool,
+
+    /// Only warn when two assets map to the same resource id.
+    #[arg(long)]
+    allow_collisions: bool,
+
+    /// Pack every asset and print the resources that would be written, without writing anything.
+    #[arg(long, conflicts_with = "watch")]
+    dry_run: bool,
+
+    /// Write byte-identical resources for identical assets, without the build cache.
+    #[arg(long)]
+    deterministic: bool,
+
+    /// Name loose resources after their id and the hash of their contents.
+    #[arg(long)]
+    content_hash_names: bool,
+
+    /// Keep running and repack assets whenever the input folder changes.
+    #[arg(short, long)]
+    watch: bool,
+
+    /// Print neither the progress of the pack nor its summary.
+    #[arg(long)]
+    no_progress: bool,
+
+    /// Print the time every asset spent parsing, decoding, encoding and writing, slowest first.
+    #[arg(long)]
+    timings: bool,
+
+    /// While watching, notify the engine clients connected to this address of the repacked resources [default: 127.0.0.1:7878].
+    #[cfg(feature = "serve")]
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        num_args = 0..=1,
+        default_missing_value = serve::DEFAULT_SERVE_ADDRESS,
+        requires = "watch"
+    )]
+    serve: Option<String>,
+}
+
+#[derive(Args)]
+struct AtlasArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Assets folder the atlases and the updated assets are written to.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Name of the atlas pages, suffixed by their atlas group, if any, and their index [default: atlas].
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Maximum width and height of an atlas page, in pixels, sheets over it being split [default: [pack] max_texture_size, or 2048].
+    #[arg(long)]
+    max_size: Option<u32>,
+
+    /// Empty pixels between two packed frames [default: 1].
+    #[arg(long)]
+    padding: Option<u32>,
 
-    let resources_output_path = if args.len() > 2 {
-        PathBuf::from(&args[2])
+    /// Pixels the edges of the packed frames are repeated over, against bleeding when sampling with filtering [default: 0].
+    #[arg(long)]
+    extrude: Option<u32>,
+
+    /// Crop the frames to their opaque pixels, to save atlas space.
+    #[arg(long)]
+    trim: bool,
+}
+
+#[derive(Args)]
+struct PaletteArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Assets folder the palette is written to [default: the input folder].
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Name of the palette, as referenced by sprite sheets and textures [default: palette].
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Maximum number of colors of the palette [default: 256].
+    #[arg(long)]
+    colors: Option<usize>,
+}
+
+impl From<GraphFormat> for SidGraphFormat {
+    fn from(format: GraphFormat) -> Self {
+        match format {
+            GraphFormat::Dot => SidGraphFormat::Dot,
+            GraphFormat::Json => SidGraphFormat::Json,
+        }
+    }
+}
+
+impl From<OutputFormat> for SidOutputFormat {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Loose => SidOutputFormat::Loose,
+            OutputFormat::Pak => SidOutputFormat::Pak,
+        }
+    }
+}
+
+impl From<InputCollisionPolicy> for SidInputCollisionPolicy {
+    fn from(policy: InputCollisionPolicy) -> Self {
+        match policy {
+            InputCollisionPolicy::Error => SidInputCollisionPolicy::Error,
+            InputCollisionPolicy::FirstWins => SidInputCollisionPolicy::FirstWins,
+            InputCollisionPolicy::LastWins => SidInputCollisionPolicy::LastWins,
+        }
+    }
+}
+
+impl From<Compression> for SidCompression {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::None => SidCompression::None,
+            Compression::Lz4 => SidCompression::Lz4,
+            Compression::Zstd => SidCompression::Zstd,
+        }
+    }
+}
+
+impl From<MipmapFilter> for SidMipmapFilter {
+    fn from(filter: MipmapFilter) -> Self {
+        match filter {
+            MipmapFilter::Box => SidMipmapFilter::Box,
+            MipmapFilter::Lanczos => SidMipmapFilter::Lanczos,
+        }
+    }
+}
+
+impl From<TextureEncoding> for SidTextureEncoding {
+    fn from(encoding: TextureEncoding) -> Self {
+        match encoding {
+            TextureEncoding::Raw => SidTextureEncoding::Raw,
+            TextureEncoding::Bc1 => SidTextureEncoding::Bc1,
+            TextureEncoding::Bc3 => SidTextureEncoding::Bc3,
+            TextureEncoding::Bc7 => SidTextureEncoding::Bc7,
+            TextureEncoding::Etc2 => SidTextureEncoding::Etc2,
+            TextureEncoding::Astc => SidTextureEncoding::Astc,
+        }
+    }
+}
+
+impl From<ColorSpace> for SidColorSpace {
+    fn from(color_space: ColorSpace) -> Self {
+        match color_space {
+            ColorSpace::Srgb => SidColorSpace::Srgb,
+            ColorSpace::Linear => SidColorSpace::Linear,
+        }
+    }
+}
+
+impl From<PixelFormat> for SidPixelFormat {
+    fn from(format: PixelFormat) -> Self {
+        match format {
+            PixelFormat::Source => SidPixelFormat::Source,
+            PixelFormat::L8 => SidPixelFormat::L8,
+            PixelFormat::La8 => SidPixelFormat::La8,
+            PixelFormat::Rgb8 => SidPixelFormat::Rgb8,
+            PixelFormat::Rgba8 => SidPixelFormat::Rgba8,
+            PixelFormat::Rgba16 => SidPixelFormat::Rgba16,
+        }
+    }
+}
+
+impl From<Endianness> for SidEndianness {
+    fn from(endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Le => SidEndianness::Little,
+            Endianness::Be => SidEndianness::Big,
+        }
+    }
+}
+
+/// Picks the path given on the command line, or else the one from the
+/// configuration file, or else `default`.
+fn resolve_path(
+    config: &SidToolsConfig,
+    arg: Option<PathBuf>,
+    config_path: Option<&PathBuf>,
+    default: &str,
+) -> PathBuf {
+    arg.or_else(|| config_path.map(|path| config.resolve(path)))
+        .unwrap_or_else(|| PathBuf::from(default))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let mut log_config = SidLogConfig::new();
+
+    if cli.verbose {
+        log_config.level = LevelFilter::Debug;
+    } else if let Some(level) = cli.log_level {
+        log_config.level = level.into();
+    }
+
+    log_config.filters = cli.log_filter;
+    log_config.file = cli.log_file;
+
+    if let Err(error) = log::init(log_config) {
+        sid_error!("{error}");
+        return ExitCode::FAILURE;
+    }
+
+    let start = Instant::now();
+    let mut report = SidReport::new();
+
+    let result = SidToolsConfig::load(cli.config.as_deref()).and_then(|config| {
+        walk::set_follow_symlinks(
+            cli.follow_symlinks || config.pack.follow_symlinks.unwrap_or(false),
+        );
+        load_id_overrides(cli.id_overrides, &config)?;
+        run(cli.command, &config, &mut report)
+    });
+
+    if let Err(error) = result {
+        sid_error!("{error}");
+    }
+
+    sid_info!(
+        "{} error(s), {} warning(s).",
+        log::error_count(),
+        log::warning_count()
+    );
+
+    if let Some(ReportFormat::Json) = cli.report {
+        report.finish(start.elapsed());
+
+        if let Err(error) = report.write_json(cli.report_output.as_deref()) {
+            sid_error!("{error}");
+        }
+    }
+
+    // Errors logged without stopping the run fail it as well.
+    if log::error_count() > 0 {
+        ExitCode::FAILURE
     } else {
-        let mut resources_output_path = PathBuf::from(".");
-        resources_output_path.push(resource::DEFAULT_RESOURCES_PATH);
-        resources_output_path
-    };
+        ExitCode::SUCCESS
+    }
+}
+
+/// Assets folder of the commands going through a single one: the first input
+/// folder of the pack, by default.
+fn assets_input_path(config: &SidToolsConfig, args: InputArgs) -> PathBuf {
+    resolve_path(
+        config,
+        args.input,
+        config
+            .pack
+            .input
+            .as_ref()
+            .and_then(|input| input.paths().first()),
+        asset::DEFAULT_ASSETS_PATH,
+    )
+}
+
+/// Makes every id generation use the id overrides at `path`, or else the ones
+/// of the configuration file, if any.
+fn load_id_overrides(path: Option<PathBuf>, config: &SidToolsConfig) -> SidToolResult<()> {
+    let path = path
+        .or_else(|| {
+            config
+                .pack
+                .id_overrides
+                .as_ref()
+                .map(|path| config.resolve(path))
+        })
+        .or_else(|| {
+            let path = config.resolve(Path::new(id_overrides::ID_OVERRIDES_FILE_NAME));
+            path.is_file().then_some(path)
+        });
+
+    if let Some(path) = path {
+        id_overrides::set_id_overrides(SidIdOverrides::from_path(&path)?);
+        sid_info!("Using the id overrides of {:?}.", path);
+    }
+
+    Ok(())
+}
+
+/// Applies the pack options given on the command line over the ones of the
+/// configuration file.
+fn apply_pack_args(options: &mut SidPackOptions, args: &PackArgs) {
+    options.force = args.force;
+    options.dry_run = args.dry_run;
+    options.deterministic |= args.deterministic;
+    options.content_hash_names |= args.content_hash_names;
+    options.allow_collisions |= args.allow_collisions;
+    options.progress = !args.no_progress;
+    options.timings = args.timings;
+
+    if let Some(jobs) = args.jobs {
+        options.jobs = jobs;
+    }
+
+    if let Some(format) = args.format {
+        options.output_format = format.into();
+    }
+
+    if let Some(input_collisions) = args.input_collisions {
+        options.input_collisions = input_collisions.into();
+    }
+
+    if !args.only_tags.is_empty() {
+        options.tags.only = args.only_tags.clone();
+    }
+
+    if !args.exclude_tags.is_empty() {
+        options.tags.exclude = args.exclude_tags.clone();
+    }
+
+    if args.clean_output {
+        options.overwrite = SidOverwritePolicy::Clean;
+    } else if args.no_overwrite {
+        options.overwrite = SidOverwritePolicy::NoOverwrite;
+    } else if args.merge {
+        options.overwrite = SidOverwritePolicy::Merge;
+    }
+
+    if let Some(compression) = args.compress {
+        options.compression = compression.into();
+    }
+
+    if let Some(endianness) = args.endianness {
+        options.endianness = endianness.into();
+    }
+
+    if let Some(mipmaps) = args.mipmaps {
+        options.texture.mipmaps = Some(mipmaps.into());
+    }
+
+    if let Some(encoding) = args.texture_encoding {
+        options.texture.encoding = encoding.into();
+    }
+
+    if let Some(color_space) = args.color_space {
+        options.texture.color_space = Some(color_space.into());
+    }
+
+    if let Some(pixel_format) = args.pixel_format {
+        options.texture.pixel_format = Some(pixel_format.into());
+    }
+
+    if let Some(max_size) = args.max_texture_size {
+        options.texture.max_size = Some(max_size);
+    }
+
+    options.texture.power_of_two |= args.power_of_two;
+
+    if let Some(color_key) = args.color_key {
+        options.texture.color_key = Some(color_key);
+    }
+}
+
+/// Packs with every packer of `packers`, one per target of `targets`, if any.
+/// A failing target does not stop the others.
+fn pack_targets(
+    packers: &[SidPacker],
+    targets: &[String],
+    report: &mut SidReport,
+) -> SidToolResult<()> {
+    let mut result = Ok(());
+
+    for (index, packer) in packers.iter().enumerate() {
+        if let Some(target) = targets.get(index) {
+            sid_info!("Packing target {:?}.", target);
+        }
+
+        if let Err(error) = packer.pack_with_report(report) {
+            if let Err(previous_error) = mem::replace(&mut result, Err(error)) {
+                sid_error!("{previous_error}");
+            }
+        }
+    }
+
+    result
+}
+
+fn run(command: Command, config: &SidToolsConfig, report: &mut SidReport) -> SidToolResult<()> {
+    let pack_config = &config.pack;
+
+    match command {
+        Command::Pack(args) => {
+            let mut packers = if args.target.is_empty() {
+                vec![SidPacker::new(config)]
+            } else {
+                args.target
+                    .iter()
+                    .map(|target| SidPacker::for_target(config, target))
+                    .collect::<SidToolResult<Vec<_>>>()?
+            };
+
+            for packer in &mut packers {
+                if !args.input.is_empty() {
+                    packer.assets_input_paths = args.input.clone();
+                }
+
+                if let Some(output) = &args.output {
+                    packer.resources_output_path = output.clone();
+                }
+
+                apply_pack_args(&mut packer.options, &args);
+            }
+
+            let start = Instant::now();
+            let result = pack_targets(&packers, &args.target, report);
+
+            if !args.no_progress {
+                eprintln!("{}", report.summary(start.elapsed()));
+            }
+
+            if args.timings {
+                timings::print_timings(report);
+            }
+
+            if !args.watch {
+                return result;
+            }
+
+            if let Err(error) = result {
+                sid_error!("{error}");
+            }
+
+            // Later runs only repack what changed, thanks to the build cache,
+            // over the resources of the first one.
+            for packer in &mut packers {
+                packer.options.force = false;
+
+                if packer.options.overwrite != SidOverwritePolicy::Merge {
+                    packer.options.overwrite = SidOverwritePolicy::Overwrite;
+                }
+            }
+
+            #[cfg(feature = "serve")]
+            let server = args
+                .serve
+                .as_deref()
+                .map(SidReloadServer::bind)
+                .transpose()?;
+
+            // Targets only differ by their outputs.
+            let assets_input_paths = packers[0].assets_input_paths.clone();
+
+            let resources_output_paths: Vec<PathBuf> = packers
+                .iter()
+                .map(|packer| packer.resources_output_path.clone())
+                .collect();
+
+            watch::watch_all(&assets_input_paths, &resources_output_paths, || {
+                let start = Instant::now();
+                let mut run_report = SidReport::new();
+
+                if let Err(error) = pack_targets(&packers, &args.target, &mut run_report) {
+                    sid_error!("{error}");
+                }
+
+                if !args.no_progress {
+                    eprintln!("{}", run_report.summary(start.elapsed()));
+                }
+
+                if args.timings {
+                    timings::print_timings(&run_report);
+                }
+
+                #[cfg(feature = "serve")]
+                if let Some(server) = &server {
+                    server.notify(&serve::updated_resource_ids(&run_report));
+                }
+            })
+            .map_err(|error| {
+                SidToolError::IO(format!("Unable to watch {:?}: {error}", assets_input_paths))
+            })?;
+        }
+        Command::Validate(args) => {
+            resource::validate_assets_with_names(
+                assets_input_path(config, args),
+                &SidNameRules::from_config(&config.names),
+            )?;
+            sid_info!("All assets are valid.");
+        }
+        Command::List(args) => {
+            let (entries, failures) = resource::list_assets(assets_input_path(config, args))?;
+
+            for entry in entries {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    entry.namespace,
+                    entry.id,
+                    entry.name,
+                    entry.path.display()
+                );
+            }
+
+            if !failures.is_empty() {
+                return Err(SidToolError::AssetFailures(failures));
+            }
+        }
+        Command::Graph(args) => {
+            let graph = resource::build_asset_graph(assets_input_path(config, args.input))?;
+            let format = args.format.map_or(SidGraphFormat::Dot, Into::into);
+            graph.write(format, args.output.as_deref())?;
+        }
+        #[cfg(feature = "browse")]
+        Command::Browse(args) => {
+            let resources_path = resolve_path(
+                config,
+                args.resources,
+                pack_config.output.as_ref(),
+                resource::DEFAULT_RESOURCES_PATH,
+            );
+
+            browse::browse(assets_input_path(config, args.input), resources_path)?;
+        }
+        Command::Atlas(args) => {
+            let atlas_config = &config.atlas;
+
+            let assets_input_path = match (&args.input.input, &atlas_config.input) {
+                (None, Some(path)) => config.resolve(path),
+                _ => assets_input_path(config, args.input),
+            };
+
+            let assets_output_path = args
+                .output
+                .or_else(|| {
+                    atlas_config
+                        .output
+                        .as_ref()
+                        .map(|path| config.resolve(path))
+                })
+                .ok_or_else(|| {
+                    SidToolError::Malformed(
+                        "No atlas output folder, set --output or [atlas] output".to_string(),
+                    )
+                })?;
+
+            let mut options = SidAtlasOptions::new();
+
+            if let Some(name) = args.name.or_else(|| atlas_config.name.clone()) {
+                options.name = name;
+            }
+
+            if let Some(max_size) = args
+                .max_size
+                .or(atlas_config.max_size)
+                .or(config.pack.max_texture_size)
+            {
+                options.max_size = max_size;
+            }
+
+            if let Some(padding) = args.padding.or(atlas_config.padding) {
+                options.padding = padding;
+            }
+
+            if let Some(extrude) = args.extrude.or(atlas_config.extrude) {
+                options.extrude = extrude;
+            }
+
+            options.trim = args.trim || atlas_config.trim.unwrap_or(false);
+
+            build_atlases(&assets_input_path, &assets_output_path, &options)?;
+        }
+        Command::Palette(args) => {
+            let assets_input_path = assets_input_path(config, args.input);
+            let assets_output_path = args.output.unwrap_or_else(|| assets_input_path.clone());
+
+            let mut options = SidPaletteOptions::new();
+
+            if let Some(name) = args.name {
+                options.name = name;
+            }
+
+            if let Some(color_count) = args.colors {
+                options.color_count = color_count;
+            }
+
+            build_palette(&assets_input_path, &assets_output_path, &options)?;
+        }
+        Command::Inspect(args) => {
+            print!("{}", inspect::inspect(&args.path)?);
+
+            if args.preview {
+                let columns = args.preview_width.unwrap_or(preview::DEFAULT_PREVIEW_WIDTH);
+                preview::preview(&args.path, args.id, columns)?;
+            }
+        }
+        Command::ExportGif(args) => {
+            animation_export::export_animation(
+                &args.path,
+                args.id,
+                &args.output,
+                args.scale.unwrap_or(1),
+            )?;
+        }
+        Command::Unpack(args) => {
+            let resources_input_path = resolve_path(
+                config,
+                args.input,
+                pack_config.output.as_ref(),
+                resource::DEFAULT_RESOURCES_PATH,
+            );
+
+            unpack::from_resources_to_assets(resources_input_path, &args.output)?;
+        }
+        Command::Clean(args) => {
+            let resources_path = resolve_path(
+                config,
+                args.input,
+                pack_config.output.as_ref(),
+                resource::DEFAULT_RESOURCES_PATH,
+            );
+
+            clean::clean_resources(resources_path, args.dry_run)?;
+        }
+        Command::Diff(args) => {
+            patch::diff_resources(&args.old, &args.new, &args.output)?;
+        }
+        Command::Verify(args) => {
+            let resources_path = resolve_path(
+                config,
+                args.input,
+                pack_config.output.as_ref(),
+                resource::DEFAULT_RESOURCES_PATH,
+            );
+
+            verify::verify_resources(resources_path)?;
+            sid_info!("All resources are valid.");
+        }
+        Command::Header(args) => {
+            c_header::write_c_header(args.output.as_deref())?;
+        }
+        Command::Schema(args) => {
+            let output = args
+                .output
+                .unwrap_or_else(|| PathBuf::from(json_schema::DEFAULT_JSON_SCHEMAS_PATH));
+
+            for path in json_schema::write_json_schemas(&output)? {
+                sid_info!("Wrote schema {:?}.", path);
+            }
+        }
+    }
 
-    from_assets_to_resources(assets_input_path, resources_output_path);
+    Ok(())
 }
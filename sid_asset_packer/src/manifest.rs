@@ -0,0 +1,98 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{resource::SidResourceType, sid};
+
+pub static MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SidManifestEntry {
+    pub id: sid::SidStringId,
+    pub name: String,
+    pub resource_type: SidResourceType,
+    /// Size of the packed resource, in bytes.
+    pub size: u64,
+    pub source_path: PathBuf,
+    /// XXH3 hash of the packed resource, as a hexadecimal string.
+    pub hash: String,
+    /// Name of the loose resource file, when packed with content-hash names.
+    /// Defaults to the id otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+impl SidManifestEntry {
+    pub fn file_name(&self) -> String {
+        self.file_name
+            .clone()
+            .unwrap_or_else(|| self.id.to_string())
+    }
+}
+
+fn default_id_bits() -> u32 {
+    32
+}
+
+/// Maps the resources of a pack to the assets they were built from.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidManifest {
+    pub version: String,
+    /// Width of the resource ids, in bits.
+    #[serde(default = "default_id_bits")]
+    pub id_bits: u32,
+    pub entries: Vec<SidManifestEntry>,
+}
+
+impl SidManifest {
+    pub fn new() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            id_bits: sid::STRING_ID_BITS,
+            entries: vec![],
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(resources_path: P) -> io::Result<Self> {
+        let file = File::open(resources_path.as_ref().join(MANIFEST_FILE_NAME))?;
+        let manifest: Self = serde_json::from_reader(file)?;
+
+        if manifest.id_bits != sid::STRING_ID_BITS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Manifest with {}-bit ids, but the tools use {}-bit ones",
+                    manifest.id_bits,
+                    sid::STRING_ID_BITS
+                ),
+            ));
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn save<P: AsRef<Path>>(&mut self, resources_path: P) -> io::Result<()> {
+        // Colliding ids are ordered by source path, so that saving is stable.
+        self.entries
+            .sort_by(|a, b| (a.id, &a.source_path).cmp(&(b.id, &b.source_path)));
+
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(resources_path.as_ref().join(MANIFEST_FILE_NAME), json)
+    }
+
+    pub fn find(&self, id: sid::SidStringId) -> Option<&SidManifestEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+}
+
+pub(crate) fn format_hash(hash: u64) -> String {
+    format!("{hash:016x}")
+}
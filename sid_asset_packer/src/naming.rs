@@ -0,0 +1,119 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Deserialize;
+
+use crate::config::SidNamesConfig;
+
+pub const DEFAULT_MAX_NAME_LENGTH: usize = 128;
+pub static DEFAULT_EXTRA_NAME_CHARACTERS: &str = "-.";
+
+/// How the case of asset names is handled. Ids are case-sensitive, so names
+/// differing only by case would silently be packed as different resources.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SidCaseFolding {
+    /// Names keep their case, but two names of a namespace differing only by
+    /// case are rejected.
+    #[default]
+    Keep,
+    /// Names are lowercased when converted, and rejected when they hold an
+    /// uppercase letter.
+    Lower,
+}
+
+/// Rules the names of the assets follow, since they become resource ids and
+/// file names. The converter normalizes the names it builds and rejects the
+/// ones still breaking the rules, the packer rejects them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SidNameRules {
+    /// Maximum number of characters of a name.
+    pub max_length: usize,
+    pub case_folding: SidCaseFolding,
+    /// Characters allowed on top of ASCII letters, digits, underscores and
+    /// the slashes separating subfolders.
+    pub extra_characters: String,
+}
+
+impl SidNameRules {
+    pub fn new() -> Self {
+        Self {
+            max_length: DEFAULT_MAX_NAME_LENGTH,
+            case_folding: SidCaseFolding::Keep,
+            extra_characters: DEFAULT_EXTRA_NAME_CHARACTERS.to_string(),
+        }
+    }
+
+    /// Rules set in the [names] section of a configuration file, defaulting
+    /// to the ones of `new()`.
+    pub fn from_config(config: &SidNamesConfig) -> Self {
+        let mut rules = Self::new();
+
+        if let Some(max_length) = config.max_length {
+            rules.max_length = max_length;
+        }
+
+        if let Some(case_folding) = config.case_folding {
+            rules.case_folding = case_folding;
+        }
+
+        if let Some(extra_characters) = &config.extra_characters {
+            rules.extra_characters = extra_characters.clone();
+        }
+
+        rules
+    }
+
+    pub fn is_allowed(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_' || c == '/' || self.extra_characters.contains(c)
+    }
+
+    /// Trims `name`, replaces its whitespace by underscores and folds its
+    /// case. The result may still break the rules, e.g. by being too long.
+    pub fn normalize(&self, name: &str) -> String {
+        let name = name.split_whitespace().collect::<Vec<_>>().join("_");
+
+        match self.case_folding {
+            SidCaseFolding::Keep => name,
+            SidCaseFolding::Lower => name.to_lowercase(),
+        }
+    }
+
+    /// Describes how `name` breaks the rules, if it does. Empty names are
+    /// left to the schema of their asset.
+    pub fn check(&self, name: &str) -> Result<(), String> {
+        let length = name.chars().count();
+
+        if length > self.max_length {
+            return Err(format!(
+                "name is {length} characters long, at most {} are allowed",
+                self.max_length
+            ));
+        }
+
+        if let Some(c) = name.chars().find(|&c| !self.is_allowed(c)) {
+            return Err(format!(
+                "character {c:?} is not allowed, only ASCII letters, digits and \"_/{}\" are",
+                self.extra_characters
+            ));
+        }
+
+        if !name.is_empty() && name.split('/').any(str::is_empty) {
+            return Err("name must not start or end with a slash, nor hold empty folders".into());
+        }
+
+        if self.case_folding == SidCaseFolding::Lower && name.chars().any(char::is_uppercase) {
+            return Err("name must be lowercase".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Normalizes `name`, and describes how it still breaks the rules, if it
+    /// does.
+    pub fn apply(&self, name: &str) -> Result<String, String> {
+        let name = self.normalize(name);
+        self.check(&name).map(|_| name)
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    asset::DEFAULT_ASSETS_PATH,
+    config::{SidPackConfig, SidToolsConfig},
+    error::SidToolResult,
+    manifest::SidManifestEntry,
+    naming::SidNameRules,
+    report::SidReport,
+    resource::{self, SidPackOptions, DEFAULT_RESOURCES_PATH},
+};
+
+/// Packs folders of assets into resources, the way the `pack` command does,
+/// e.g. `SidPacker::new(&config).pack()?`.
+#[derive(Debug)]
+pub struct SidPacker {
+    /// Assets folders, merged in order.
+    pub assets_input_paths: Vec<PathBuf>,
+    pub resources_output_path: PathBuf,
+    pub options: SidPackOptions,
+}
+
+impl SidPacker {
+    /// Packer using the folders and the options of the [pack] section of
+    /// `config`, or the defaults of the `pack` command.
+    pub fn new(config: &SidToolsConfig) -> Self {
+        Self::with_pack_config(config, &config.pack)
+    }
+
+    /// Packer of the target called `name`, using the settings of its section
+    /// of `config` over the ones of the [pack] section.
+    pub fn for_target(config: &SidToolsConfig, name: &str) -> SidToolResult<Self> {
+        Ok(Self::with_pack_config(
+            config,
+            &config.pack_for_target(name)?,
+        ))
+    }
+
+    fn with_pack_config(config: &SidToolsConfig, pack_config: &SidPackConfig) -> Self {
+        let resolve = |path: Option<&PathBuf>, default: &str| {
+            path.map(|path| config.resolve(path))
+                .unwrap_or_else(|| PathBuf::from(default))
+        };
+
+        let mut options = SidPackOptions::from_config(pack_config);
+        options.names = SidNameRules::from_config(&config.names);
+
+        let assets_input_paths = match &pack_config.input {
+            Some(input) => input
+                .paths()
+                .iter()
+                .map(|path| config.resolve(path))
+                .collect(),
+            None => vec![PathBuf::from(DEFAULT_ASSETS_PATH)],
+        };
+
+        Self {
+            assets_input_paths,
+            resources_output_path: resolve(pack_config.output.as_ref(), DEFAULT_RESOURCES_PATH),
+            options,
+        }
+    }
+
+    pub fn with_paths<P1: AsRef<Path>, P2: AsRef<Path>>(
+        assets_input_path: P1,
+        resources_output_path: P2,
+        options: SidPackOptions,
+    ) -> Self {
+        Self {
+            assets_input_paths: vec![assets_input_path.as_ref().to_path_buf()],
+            resources_output_path: resources_output_path.as_ref().to_path_buf(),
+            options,
+        }
+    }
+
+    /// Packs every asset, and returns the status of each of them.
+    pub fn pack(&self) -> SidToolResult<SidReport> {
+        let mut report = SidReport::new();
+        self.pack_with_report(&mut report)?;
+        Ok(report)
+    }
+
+    /// Packs every asset, adding the status of each of them to `report`, even
+    /// when some fail.
+    pub fn pack_with_report(&self, report: &mut SidReport) -> SidToolResult<()> {
+        resource::from_asset_folders_to_resources_with_report(
+            &self.assets_input_paths,
+            &self.resources_output_path,
+            &self.options,
+            report,
+        )
+    }
+
+    /// Packs the asset at `asset_input_path` alone, into a loose resource. The
+    /// build cache and the manifest are left untouched.
+    pub fn pack_one<P: AsRef<Path>>(&self, asset_input_path: P) -> SidToolResult<SidManifestEntry> {
+        let asset_input_path = asset_input_path.as_ref();
+
+        // The asset belongs to the first folder it is in.
+        let assets_input_path = self
+            .assets_input_paths
+            .iter()
+            .find(|path| asset_input_path.starts_with(path))
+            .or(self.assets_input_paths.first())
+            .map(PathBuf::as_path)
+            .unwrap_or(Path::new(DEFAULT_ASSETS_PATH));
+
+        resource::pack_asset(
+            asset_input_path,
+            assets_input_path,
+            &self.resources_output_path,
+            &self.options,
+        )
+    }
+}
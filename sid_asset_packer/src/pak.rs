@@ -0,0 +1,200 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use memmap2::MmapMut;
+
+use std::{fs::OpenOptions, io, path::Path};
+
+use crate::{
+    error::{SidToolError, SidToolResult},
+    resource::SidResourceType,
+    sid,
+};
+
+// Layout of a .sidpak file (little endian):
+// - header: magic (4 bytes), version (u32), entry count (u32), id width in
+//   bits (u32, 0 for 32-bit ids).
+// - table, sorted by id: id (u32), type (u32), offset (u64), size (u64). With
+//   64-bit ids: id (u64), type (u32), reserved (u32), offset (u64), size (u64).
+// - payloads, each starting at an offset aligned to PAK_ALIGNMENT bytes.
+pub static PAK_MAGIC: &[u8; 4] = b"SPAK";
+pub const PAK_VERSION: u32 = 1;
+pub const PAK_ALIGNMENT: u64 = 16;
+pub const PAK_HEADER_SIZE: u64 = 16;
+pub const PAK_TABLE_ENTRY_SIZE: u64 = if sid::STRING_ID_BITS == 64 { 32 } else { 24 };
+const PAK_ID_SIZE: usize = size_of::<sid::SidStringId>();
+pub static DEFAULT_PAK_FILE_NAME: &str = "resources.sidpak";
+
+#[derive(Debug)]
+pub struct SidPakEntry {
+    pub id: sid::SidStringId,
+    pub resource_type: SidResourceType,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SidPakWriter {
+    entries: Vec<SidPakEntry>,
+}
+
+fn align(offset: u64) -> u64 {
+    offset.div_ceil(PAK_ALIGNMENT) * PAK_ALIGNMENT
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn read_id(data: &[u8], offset: usize) -> Option<sid::SidStringId> {
+    Some(sid::SidStringId::from_le_bytes(
+        data.get(offset..offset + PAK_ID_SIZE)?.try_into().ok()?,
+    ))
+}
+
+/// Reads the version and the entries of a pak, leaving their payloads as
+/// stored (possibly compressed).
+pub fn read_pak(data: &[u8]) -> SidToolResult<(u32, Vec<SidPakEntry>)> {
+    if !data.starts_with(PAK_MAGIC) {
+        return Err(SidToolError::UnsupportedFormat(
+            "Missing pak magic".to_string(),
+        ));
+    }
+
+    let truncated = || SidToolError::Malformed("Truncated pak table".to_string());
+    let version = read_u32(data, 4).ok_or_else(truncated)?;
+    let entry_count = read_u32(data, 8).ok_or_else(truncated)?;
+
+    let id_bits = match read_u32(data, 12).ok_or_else(truncated)? {
+        0 => 32,
+        id_bits => id_bits,
+    };
+
+    if id_bits != sid::STRING_ID_BITS {
+        return Err(SidToolError::UnsupportedFormat(format!(
+            "Pak with {id_bits}-bit ids, but the tools use {}-bit ones",
+            sid::STRING_ID_BITS
+        )));
+    }
+
+    let mut entries = Vec::with_capacity((entry_count as usize).min(data.len()));
+
+    for i in 0..entry_count as usize {
+        let position = PAK_HEADER_SIZE as usize + i * PAK_TABLE_ENTRY_SIZE as usize;
+        let end = position + PAK_TABLE_ENTRY_SIZE as usize;
+        let id = read_id(data, position).ok_or_else(truncated)?;
+        let raw_type = read_u32(data, position + PAK_ID_SIZE).ok_or_else(truncated)?;
+        let offset = read_u64(data, end - 16).ok_or_else(truncated)? as usize;
+        let size = read_u64(data, end - 8).ok_or_else(truncated)? as usize;
+
+        let resource_type = SidResourceType::from_raw(raw_type).ok_or_else(|| {
+            SidToolError::UnsupportedFormat(format!("Unknown type {raw_type} for resource {id}"))
+        })?;
+
+        let payload = offset
+            .checked_add(size)
+            .and_then(|end| data.get(offset..end))
+            .ok_or_else(|| {
+                SidToolError::Malformed(format!("Resource {id} is out of the pak bounds"))
+            })?;
+
+        entries.push(SidPakEntry {
+            id,
+            resource_type,
+            data: payload.to_vec(),
+        });
+    }
+
+    Ok((version, entries))
+}
+
+impl SidPakWriter {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    pub fn add(&mut self, entry: SidPakEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the pak through a memory map of the output file, sized up front
+    /// from the offsets of every payload.
+    pub fn write_to_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.entries.sort_by_key(|entry| entry.id);
+
+        let entry_count = u32::try_from(self.entries.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Too many pak entries"))?;
+
+        // Paks with 32-bit ids keep the layout they had before 64-bit ones.
+        let id_bits = if sid::STRING_ID_BITS == 32 {
+            0
+        } else {
+            sid::STRING_ID_BITS
+        };
+
+        let table_size = PAK_TABLE_ENTRY_SIZE * self.entries.len() as u64;
+        let mut offset = align(PAK_HEADER_SIZE + table_size);
+        let mut offsets = Vec::with_capacity(self.entries.len());
+
+        // The file ends with the last payload, without any padding.
+        let mut file_size = PAK_HEADER_SIZE + table_size;
+
+        for entry in &self.entries {
+            offsets.push(offset);
+            file_size = offset + entry.data.len() as u64;
+            offset = align(file_size);
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        file.set_len(file_size)?;
+
+        // SAFETY: the file was just created and sized by this function, and
+        // is dropped along with the map.
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+
+        map[0..4].copy_from_slice(PAK_MAGIC);
+        map[4..8].copy_from_slice(&PAK_VERSION.to_le_bytes());
+        map[8..12].copy_from_slice(&entry_count.to_le_bytes());
+        map[12..16].copy_from_slice(&id_bits.to_le_bytes());
+
+        for (i, (entry, offset)) in self.entries.iter().zip(&offsets).enumerate() {
+            let position = (PAK_HEADER_SIZE + i as u64 * PAK_TABLE_ENTRY_SIZE) as usize;
+            let size = entry.data.len() as u64;
+            let end = position + PAK_TABLE_ENTRY_SIZE as usize;
+
+            map[position..position + PAK_ID_SIZE].copy_from_slice(&entry.id.to_le_bytes());
+            map[position + PAK_ID_SIZE..position + PAK_ID_SIZE + 4]
+                .copy_from_slice(&(entry.resource_type as u32).to_le_bytes());
+            map[end - 16..end - 8].copy_from_slice(&offset.to_le_bytes());
+            map[end - 8..end].copy_from_slice(&size.to_le_bytes());
+
+            // Padding bytes are already zero, the file being freshly sized.
+            let offset = *offset as usize;
+            map[offset..offset + entry.data.len()].copy_from_slice(&entry.data);
+        }
+
+        map.flush()
+    }
+}
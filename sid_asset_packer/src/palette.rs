@@ -0,0 +1,159 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use image::RgbaImage;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asset::{SidAssetError, SidPackedAsset, SidPaletteAsset, SidSpriteSheetAsset, SidTextureAsset},
+    error::{SidToolError, SidToolResult},
+    resource::SidAssetProcessor,
+    sid_info,
+    texture::{self, MAX_PALETTE_COLOR_COUNT},
+};
+
+pub static DEFAULT_PALETTE_NAME: &str = "palette";
+
+#[derive(Debug)]
+pub struct SidPaletteOptions {
+    /// Name of the palette, which sprite sheets and textures opt in to.
+    pub name: String,
+    /// Maximum number of colors of the palette.
+    pub color_count: usize,
+}
+
+impl SidPaletteOptions {
+    pub fn new() -> Self {
+        Self {
+            name: DEFAULT_PALETTE_NAME.to_string(),
+            color_count: MAX_PALETTE_COLOR_COUNT,
+        }
+    }
+}
+
+/// Appends the pixels of every asset of type `T` using the palette `name`.
+fn collect_pixels<T: SidAssetProcessor, P: AsRef<Path>>(
+    assets_input_path: P,
+    name: &str,
+    image_path: impl Fn(&T, &Path) -> SidToolResult<Option<PathBuf>>,
+    pixels: &mut Vec<u8>,
+) -> SidToolResult<usize> {
+    let folder = assets_input_path.as_ref().join(T::namespace());
+    let mut image_count = 0;
+
+    if !folder.is_dir() {
+        return Ok(image_count);
+    }
+
+    for path in T::compatible_entries(&folder) {
+        let asset = T::load_asset(&path)?;
+
+        let Some(image_path) = image_path(&asset, &path)? else {
+            continue;
+        };
+
+        let image = image::open(&image_path).map_err(|error| {
+            SidToolError::IO(format!("Unable to open image {:?}: {error}", image_path))
+        })?;
+
+        pixels.extend_from_slice(image.to_rgba8().as_raw());
+        image_count += 1;
+    }
+
+    if image_count > 0 {
+        sid_info!(
+            "Found {image_count} {} using palette {:?}.",
+            T::namespace(),
+            name
+        );
+    }
+
+    Ok(image_count)
+}
+
+/// Quantizes the sprite sheets and textures of `assets_input_path` opting in
+/// to the palette `options.name` into a shared palette, written to
+/// `assets_output_path` as a palette asset and its image.
+pub fn build_palette<P1: AsRef<Path>, P2: AsRef<Path>>(
+    assets_input_path: P1,
+    assets_output_path: P2,
+    options: &SidPaletteOptions,
+) -> SidToolResult<()> {
+    if options.color_count == 0 || options.color_count > MAX_PALETTE_COLOR_COUNT {
+        return Err(SidToolError::Malformed(format!(
+            "Invalid palette color count: {} (expected 1 to {MAX_PALETTE_COLOR_COUNT})",
+            options.color_count
+        )));
+    }
+
+    let assets_input_path = assets_input_path.as_ref();
+    let assets_output_path = assets_output_path.as_ref();
+    let name = options.name.as_str();
+
+    let mut pixels = vec![];
+
+    let sheet_count = collect_pixels::<SidSpriteSheetAsset, _>(
+        assets_input_path,
+        name,
+        |sheet, path| match sheet.palette.as_deref() {
+            Some(palette) if palette == name => Ok(Some(sheet.texture_path(path)?)),
+            _ => Ok(None),
+        },
+        &mut pixels,
+    )?;
+
+    let texture_count = collect_pixels::<SidTextureAsset, _>(
+        assets_input_path,
+        name,
+        |texture, path| match texture.palette.as_deref() {
+            Some(palette) if palette == name => Ok(Some(texture.texture_path(path)?)),
+            _ => Ok(None),
+        },
+        &mut pixels,
+    )?;
+
+    if sheet_count + texture_count == 0 {
+        return Err(SidToolError::Malformed(format!(
+            "No sprite sheet or texture uses palette {:?}",
+            name
+        )));
+    }
+
+    let colors = texture::quantize(&pixels, options.color_count);
+
+    let palettes_path = assets_output_path.join(SidPaletteAsset::namespace());
+
+    fs::create_dir_all(&palettes_path).map_err(|error| {
+        SidAssetError::IO(format!(
+            "Unable to create folder at path {:?}: {error}",
+            palettes_path
+        ))
+    })?;
+
+    // One pixel per color, in palette order.
+    let image = RgbaImage::from_raw(colors.len() as u32, 1, colors.concat())
+        .expect("Palette image should match its size");
+
+    let image_name = format!("{name}.png");
+    let image_path = palettes_path.join(&image_name);
+
+    image.save(&image_path).map_err(|error| {
+        SidAssetError::IO(format!("Unable to save palette {:?}: {error}", image_path))
+    })?;
+
+    let palette = SidPaletteAsset::with_data(name.to_string(), image_name.into(), image_path);
+    palette.write_to_folder(assets_output_path)?;
+
+    sid_info!(
+        "Generated palette {:?} with {} color(s).",
+        name,
+        colors.len()
+    );
+
+    Ok(())
+}
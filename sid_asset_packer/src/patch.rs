@@ -0,0 +1,232 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    compression,
+    error::{SidToolError, SidToolResult},
+    manifest::SidManifest,
+    pak::{self, SidPakEntry, SidPakWriter, DEFAULT_PAK_FILE_NAME},
+    resource::{self, SidResourceHeader, SidResourceType},
+    sid, sid_info,
+};
+
+pub static PATCH_FILE_NAME: &str = "patch.json";
+pub static PATCH_PAK_FILE_NAME: &str = "patch.sidpak";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidPatchEntry {
+    pub id: sid::SidStringId,
+    /// Name from the manifest of the build, if any.
+    pub name: Option<String>,
+    pub resource_type: SidResourceType,
+    /// Size of the packed resource, in bytes.
+    pub size: u64,
+}
+
+/// Differences between two resource builds. The added and changed resources
+/// are shipped in the patch pak, next to this description.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SidPatch {
+    pub version: String,
+    pub added: Vec<SidPatchEntry>,
+    pub changed: Vec<SidPatchEntry>,
+    pub removed: Vec<SidPatchEntry>,
+}
+
+struct SidResourceBuild {
+    entries: BTreeMap<sid::SidStringId, SidPakEntry>,
+    manifest: Option<SidManifest>,
+}
+
+impl SidResourceBuild {
+    /// Loads a .sidpak archive, or a resources folder with loose resources or
+    /// a .sidpak archive.
+    fn load(path: &Path) -> SidToolResult<Self> {
+        if path.is_file() {
+            let manifest = path
+                .parent()
+                .and_then(|folder| SidManifest::load(folder).ok());
+
+            return Ok(Self {
+                entries: Self::load_pak(path)?,
+                manifest,
+            });
+        }
+
+        let manifest = SidManifest::load(path).ok();
+        let pak_path = path.join(DEFAULT_PAK_FILE_NAME);
+
+        let entries = if pak_path.is_file() {
+            Self::load_pak(&pak_path)?
+        } else {
+            Self::load_loose(path, manifest.as_ref())?
+        };
+
+        Ok(Self { entries, manifest })
+    }
+
+    fn load_pak(path: &Path) -> SidToolResult<BTreeMap<sid::SidStringId, SidPakEntry>> {
+        let data = fs::read(path)
+            .map_err(|error| SidToolError::IO(format!("Unable to read {:?}: {error}", path)))?;
+
+        let (_, entries) = pak::read_pak(&data)?;
+
+        Ok(entries.into_iter().map(|entry| (entry.id, entry)).collect())
+    }
+
+    fn load_loose(
+        path: &Path,
+        manifest: Option<&SidManifest>,
+    ) -> SidToolResult<BTreeMap<sid::SidStringId, SidPakEntry>> {
+        // Stale files left by older builds are not part of this one.
+        let file_names: Option<HashSet<String>> = manifest.map(|manifest| {
+            manifest
+                .entries
+                .iter()
+                .map(|entry| entry.file_name())
+                .collect()
+        });
+
+        let folder_entries = fs::read_dir(path).map_err(|error| {
+            SidToolError::IO(format!("Failed to read directory {:?}: {error}", path))
+        })?;
+
+        let mut entries = BTreeMap::new();
+
+        for folder_entry in folder_entries.flatten() {
+            let file_path = folder_entry.path();
+
+            let Some(file_name) = file_path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let Some(id) = resource::resource_file_id(file_name) else {
+                continue;
+            };
+
+            if file_names
+                .as_ref()
+                .is_some_and(|file_names| !file_names.contains(file_name))
+            {
+                continue;
+            }
+
+            let data = fs::read(&file_path).map_err(|error| {
+                SidToolError::IO(format!("Unable to read resource {:?}: {error}", file_path))
+            })?;
+
+            let resource_type =
+                SidResourceHeader::check(&compression::decompress(&data)?)?.resource_type;
+
+            entries.insert(
+                id,
+                SidPakEntry {
+                    id,
+                    resource_type,
+                    data,
+                },
+            );
+        }
+
+        Ok(entries)
+    }
+
+    fn patch_entry(&self, entry: &SidPakEntry) -> SidPatchEntry {
+        SidPatchEntry {
+            id: entry.id,
+            name: self
+                .manifest
+                .as_ref()
+                .and_then(|manifest| manifest.find(entry.id))
+                .map(|manifest_entry| manifest_entry.name.clone()),
+            resource_type: entry.resource_type,
+            size: entry.data.len() as u64,
+        }
+    }
+}
+
+/// Compares two resource builds, each a resources folder or a .sidpak
+/// archive, and writes the resources added or changed by the new one to
+/// `patch_output_path`, along with the list of removed resources.
+pub fn diff_resources<P1: AsRef<Path>, P2: AsRef<Path>, P3: AsRef<Path>>(
+    old_resources_path: P1,
+    new_resources_path: P2,
+    patch_output_path: P3,
+) -> SidToolResult<SidPatch> {
+    let old_build = SidResourceBuild::load(old_resources_path.as_ref())?;
+    let new_build = SidResourceBuild::load(new_resources_path.as_ref())?;
+    let patch_output_path = patch_output_path.as_ref();
+
+    let mut patch = SidPatch {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        added: vec![],
+        changed: vec![],
+        removed: vec![],
+    };
+
+    let mut pak = SidPakWriter::new();
+
+    for (id, entry) in &new_build.entries {
+        match old_build.entries.get(id) {
+            Some(old_entry)
+                if old_entry.resource_type == entry.resource_type
+                    && old_entry.data == entry.data =>
+            {
+                continue;
+            }
+            Some(_) => patch.changed.push(new_build.patch_entry(entry)),
+            None => patch.added.push(new_build.patch_entry(entry)),
+        }
+
+        pak.add(SidPakEntry {
+            id: *id,
+            resource_type: entry.resource_type,
+            data: entry.data.clone(),
+        });
+    }
+
+    for (id, entry) in &old_build.entries {
+        if !new_build.entries.contains_key(id) {
+            patch.removed.push(old_build.patch_entry(entry));
+        }
+    }
+
+    fs::create_dir_all(patch_output_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Unable to create folder at path {:?}: {error}",
+            patch_output_path
+        ))
+    })?;
+
+    let pak_path = patch_output_path.join(PATCH_PAK_FILE_NAME);
+
+    pak.write_to_file(&pak_path)
+        .map_err(|error| SidToolError::IO(format!("Unable to write {:?}: {error}", pak_path)))?;
+
+    let patch_path = patch_output_path.join(PATCH_FILE_NAME);
+
+    let json = serde_json::to_string_pretty(&patch)
+        .map_err(|error| SidToolError::Malformed(error.to_string()))?;
+
+    fs::write(&patch_path, json)
+        .map_err(|error| SidToolError::IO(format!("Unable to write {:?}: {error}", patch_path)))?;
+
+    sid_info!(
+        "Wrote a patch of {} added, {} changed and {} removed resource(s) to {:?}.",
+        patch.added.len(),
+        patch.changed.len(),
+        patch.removed.len(),
+        patch_output_path
+    );
+
+    Ok(patch)
+}
@@ -0,0 +1,69 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::{ser::Error as _, Deserialize, Deserializer, Serializer};
+
+use std::path::{Path, PathBuf};
+
+// Paths of asset files are stored with forward slashes, so that assets written
+// on Windows pack anywhere. Fields use `#[serde(with = "portable_path")]`.
+
+/// `path` with forward slashes, if it is valid UTF-8.
+pub fn to_portable(path: &Path) -> Option<String> {
+    path.to_str().map(|path| path.replace('\\', "/"))
+}
+
+/// Path of the platform from a stored path, with either kind of slashes.
+/// Backslashes are always separators, even on UNIX.
+pub fn from_portable(path: &str) -> PathBuf {
+    PathBuf::from(path.replace('\\', "/"))
+}
+
+pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    let path = to_portable(path)
+        .ok_or_else(|| S::Error::custom("path contains invalid UTF-8 characters"))?;
+
+    serializer.serialize_str(&path)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+    let path = String::deserialize(deserializer)?;
+    Ok(from_portable(&path))
+}
+
+/// Same as the parent module, for maps of paths, e.g. by locale.
+pub mod map {
+    use serde::{ser::Error as _, Deserialize, Deserializer, Serializer};
+
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use super::{from_portable, to_portable};
+
+    pub fn serialize<S: Serializer>(
+        paths: &BTreeMap<String, PathBuf>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut portable_paths = BTreeMap::new();
+
+        for (key, path) in paths {
+            let path = to_portable(path)
+                .ok_or_else(|| S::Error::custom("path contains invalid UTF-8 characters"))?;
+
+            portable_paths.insert(key, path);
+        }
+
+        serializer.collect_map(portable_paths)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, PathBuf>, D::Error> {
+        let paths = BTreeMap::<String, String>::deserialize(deserializer)?;
+
+        Ok(paths
+            .into_iter()
+            .map(|(key, path)| (key, from_portable(&path)))
+            .collect())
+    }
+}
@@ -0,0 +1,548 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use image::RgbaImage;
+
+use crate::{
+    animation_export::SidAnimationFrames,
+    asset::SidAnimationDirection,
+    error::{SidToolError, SidToolResult},
+    pak::PAK_MAGIC,
+    resource::{
+        self,
+        read::{
+            decode_pak, read_resource, SidDecodedAnimation, SidDecodedAnimationDef,
+            SidDecodedFrame, SidDecodedImage, SidDecodedResource,
+        },
+    },
+    sid::{self, sid_texture_format, SidAnimationFrameDuration},
+};
+
+// Previews are drawn with half blocks, each character holding two pixels on
+// top of each other: the top one as the foreground color of '▀', and the
+// bottom one as the background color, in 24-bit ANSI colors. Pixels less than
+// half opaque are left to the background of the terminal.
+
+/// Width of the previews, in characters, unless set otherwise.
+pub const DEFAULT_PREVIEW_WIDTH: u32 = 64;
+
+type SidRgba = [u8; 4];
+
+/// Image converted to 8-bit RGBA, for drawing.
+struct SidPreviewImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<SidRgba>,
+}
+
+impl SidPreviewImage {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0; 4]; width as usize * height as usize],
+        }
+    }
+
+    /// Pixel at (`x`, `y`), transparent out of the image.
+    fn get(&self, x: u32, y: u32) -> SidRgba {
+        if x >= self.width || y >= self.height {
+            return [0; 4];
+        }
+
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    fn set(&mut self, x: u32, y: u32, pixel: SidRgba) {
+        self.pixels[y as usize * self.width as usize + x as usize] = pixel;
+    }
+
+    /// Draws the image scaled down to fit `columns` characters, on a canvas
+    /// of `width` by `height` pixels, so that the frames of an animation take
+    /// as many lines.
+    fn render(&self, width: u32, height: u32, columns: u32) -> String {
+        let scale = width.div_ceil(columns.max(1)).max(1);
+        let mut out = String::new();
+
+        for row in (0..height.div_ceil(scale)).step_by(2) {
+            for column in 0..width.div_ceil(scale) {
+                let top = self.get(column * scale, row * scale);
+                let bottom = if (row + 1) * scale < height {
+                    self.get(column * scale, (row + 1) * scale)
+                } else {
+                    [0; 4]
+                };
+
+                match (is_visible(top), is_visible(bottom)) {
+                    (false, false) => out.push_str("\x1b[0m "),
+                    (true, false) => write!(out, "\x1b[0m\x1b[{}m▀", foreground(top)).unwrap(),
+                    (false, true) => write!(out, "\x1b[0m\x1b[{}m▄", foreground(bottom)).unwrap(),
+                    (true, true) => {
+                        write!(out, "\x1b[{};{}m▀", foreground(top), background(bottom)).unwrap()
+                    }
+                }
+            }
+
+            out.push_str("\x1b[0m\n");
+        }
+
+        out
+    }
+}
+
+fn is_visible(pixel: SidRgba) -> bool {
+    pixel[3] >= 128
+}
+
+fn foreground(pixel: SidRgba) -> String {
+    format!("38;2;{};{};{}", pixel[0], pixel[1], pixel[2])
+}
+
+fn background(pixel: SidRgba) -> String {
+    format!("48;2;{};{};{}", pixel[0], pixel[1], pixel[2])
+}
+
+/// Size in bytes of a pixel of a raw format, or none for block-compressed
+/// ones, which cannot be previewed.
+fn pixel_size(format: i32) -> Option<usize> {
+    match format {
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_R8 as i32 => Some(1),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_L8 as i32 => Some(1),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_INDEXED8 as i32 => Some(1),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_LA8 as i32 => Some(2),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGB8 as i32 => Some(3),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGBA8 as i32 => Some(4),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGBA16 as i32 => Some(8),
+        _ => None,
+    }
+}
+
+/// Resources the previewed one refers to, e.g. the sprite sheet of an
+/// animation definition: the other resources of its .sidpak archive, or the
+/// other resource files of its folder.
+enum SidPreviewSource {
+    Pak(Vec<SidDecodedResource>),
+    Folder(PathBuf),
+}
+
+impl SidPreviewSource {
+    fn take(&mut self, id: sid::SidStringId) -> SidToolResult<SidDecodedResource> {
+        let not_found = || SidToolError::Malformed(format!("Resource {id} not found to preview"));
+
+        match self {
+            SidPreviewSource::Pak(resources) => {
+                let index = resources
+                    .iter()
+                    .position(|resource| resource.id() == id)
+                    .ok_or_else(not_found)?;
+
+                Ok(resources.swap_remove(index))
+            }
+            SidPreviewSource::Folder(folder) => {
+                let entries = fs::read_dir(&*folder).map_err(|error| {
+                    SidToolError::IO(format!("Failed to read directory {:?}: {error}", folder))
+                })?;
+
+                let path = entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .find(|path| {
+                        path.file_name()
+                            .and_then(|file_name| file_name.to_str())
+                            .and_then(resource::resource_file_id)
+                            == Some(id)
+                    })
+                    .ok_or_else(not_found)?;
+
+                let data = fs::read(&path).map_err(|error| {
+                    SidToolError::IO(format!("Unable to read resource {:?}: {error}", path))
+                })?;
+
+                read_resource(&data)
+            }
+        }
+    }
+}
+
+struct SidPreviewContext {
+    source: SidPreviewSource,
+    palettes: HashMap<sid::SidPaletteId, Vec<SidRgba>>,
+    sheets: HashMap<sid::SidSpriteSheetId, SidPreviewImage>,
+}
+
+impl SidPreviewContext {
+    fn new(source: SidPreviewSource) -> Self {
+        Self {
+            source,
+            palettes: HashMap::new(),
+            sheets: HashMap::new(),
+        }
+    }
+
+    fn palette(&mut self, id: sid::SidPaletteId) -> SidToolResult<&[SidRgba]> {
+        if !self.palettes.contains_key(&id) {
+            let SidDecodedResource::Palette(palette) = self.source.take(id)? else {
+                return Err(SidToolError::Malformed(format!(
+                    "Resource {id} is not a palette"
+                )));
+            };
+
+            self.palettes.insert(id, palette.colors);
+        }
+
+        Ok(&self.palettes[&id])
+    }
+
+    /// Base level of `image`, in 8-bit RGBA.
+    fn decode_image(&mut self, image: &SidDecodedImage) -> SidToolResult<SidPreviewImage> {
+        let format = image.format;
+        let pixel_size = pixel_size(format).ok_or_else(|| {
+            SidToolError::UnsupportedFormat(format!(
+                "Unable to preview an image packed in format {format}, only raw formats can be previewed"
+            ))
+        })?;
+
+        let level = &image.levels[0];
+        let row_size = level.width as usize * pixel_size;
+
+        if image.width > level.width
+            || image.height > level.height
+            || level.data.len() < row_size * level.height as usize
+        {
+            return Err(SidToolError::Malformed(format!(
+                "Image of {}x{} pixel(s) with a base level of {} byte(s)",
+                image.width,
+                image.height,
+                level.data.len()
+            )));
+        }
+
+        let palette = if format == sid_texture_format::SID_TEXTURE_FORMAT_INDEXED8 as i32 {
+            self.palette(image.palette_id)?.to_vec()
+        } else {
+            vec![]
+        };
+
+        let mut out = SidPreviewImage::new(image.width, image.height);
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let start = y as usize * row_size + x as usize * pixel_size;
+                let texel = &level.data[start..start + pixel_size];
+
+                // 16-bit channels are little endian, their high byte last.
+                let pixel = match pixel_size {
+                    1 if !palette.is_empty() => {
+                        palette.get(texel[0] as usize).copied().unwrap_or([0; 4])
+                    }
+                    1 => [texel[0], texel[0], texel[0], 255],
+                    2 => [texel[0], texel[0], texel[0], texel[1]],
+                    3 => [texel[0], texel[1], texel[2], 255],
+                    4 => [texel[0], texel[1], texel[2], texel[3]],
+                    _ => [texel[1], texel[3], texel[5], texel[7]],
+                };
+
+                out.set(x, y, pixel);
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn sheet(&mut self, id: sid::SidSpriteSheetId) -> SidToolResult<&SidPreviewImage> {
+        if !self.sheets.contains_key(&id) {
+            let SidDecodedResource::SpriteSheet(sheet) = self.source.take(id)? else {
+                return Err(SidToolError::Malformed(format!(
+                    "Resource {id} is not a sprite sheet"
+                )));
+            };
+
+            let image = self.decode_image(&sheet.image)?;
+            self.sheets.insert(id, image);
+        }
+
+        Ok(&self.sheets[&id])
+    }
+
+    /// Frame of `def` at its source size, trimmed borders included.
+    fn frame_image(
+        &mut self,
+        def: &SidDecodedAnimationDef,
+        frame: &SidDecodedFrame,
+    ) -> SidToolResult<SidPreviewImage> {
+        let sheet_id = match frame.page {
+            0 => def.sheet_id,
+            page => *def
+                .page_ids
+                .get(page as usize - 1)
+                .ok_or_else(|| SidToolError::Malformed(format!("Frame on unknown page {page}")))?,
+        };
+
+        let sheet = self.sheet(sheet_id)?;
+        let (width, height) = (frame.width as u32, frame.height as u32);
+        let (sheet_width, sheet_height) = if frame.rotated {
+            (height, width)
+        } else {
+            (width, height)
+        };
+
+        if frame.x as u32 + sheet_width > sheet.width
+            || frame.y as u32 + sheet_height > sheet.height
+        {
+            return Err(SidToolError::Malformed(format!(
+                "Frame at ({}, {}) is out of the bounds of its {}x{} sheet",
+                frame.x, frame.y, sheet.width, sheet.height
+            )));
+        }
+
+        let mut out = SidPreviewImage::new(frame.source_width as u32, frame.source_height as u32);
+
+        for y in 0..height {
+            for x in 0..width {
+                // Rotated frames are stored turned clockwise.
+                let pixel = if frame.rotated {
+                    sheet.get(frame.x as u32 + height - 1 - y, frame.y as u32 + x)
+                } else {
+                    sheet.get(frame.x as u32 + x, frame.y as u32 + y)
+                };
+
+                let (out_x, out_y) = (frame.offset_x as u32 + x, frame.offset_y as u32 + y);
+
+                if out_x < out.width && out_y < out.height {
+                    out.set(out_x, out_y, pixel);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// Indices of the frames an animation definition of `frame_count` frames
+/// plays, once, from `offset` and for `length` frames, in `direction`.
+pub(crate) fn frame_order(
+    frame_count: usize,
+    offset: usize,
+    length: usize,
+    direction: SidAnimationDirection,
+) -> SidToolResult<Vec<usize>> {
+    if offset + length > frame_count {
+        return Err(SidToolError::Malformed(format!(
+            "Frames {offset} to {} out of the {frame_count} of the animation definition",
+            offset + length
+        )));
+    }
+
+    let forward: Vec<usize> = (offset..offset + length).collect();
+    let reverse: Vec<usize> = forward.iter().rev().copied().collect();
+
+    Ok(match direction {
+        SidAnimationDirection::Forward => forward,
+        SidAnimationDirection::Reverse => reverse,
+        SidAnimationDirection::PingPong => [&forward[..], &reverse[1.min(length)..]].concat(),
+        SidAnimationDirection::PingPongReverse => {
+            [&reverse[..], &forward[1.min(length)..]].concat()
+        }
+    })
+}
+
+/// Images of `frames` of `def`, with their durations.
+fn frame_images(
+    context: &mut SidPreviewContext,
+    def: &SidDecodedAnimationDef,
+    frames: &[usize],
+) -> SidToolResult<Vec<(SidPreviewImage, SidAnimationFrameDuration)>> {
+    let mut images = Vec::with_capacity(frames.len());
+
+    for &index in frames {
+        let frame = &def.frames[index];
+        images.push((context.frame_image(def, frame)?, frame.duration));
+    }
+
+    Ok(images)
+}
+
+/// Steps through `frames` of `def` in the terminal, each for its duration,
+/// drawing every frame over the previous one.
+fn play(
+    context: &mut SidPreviewContext,
+    def: &SidDecodedAnimationDef,
+    frames: &[usize],
+    columns: u32,
+) -> SidToolResult<()> {
+    let images = frame_images(context, def, frames)?;
+    let width = images
+        .iter()
+        .map(|(image, _)| image.width)
+        .max()
+        .unwrap_or(0);
+    let height = images
+        .iter()
+        .map(|(image, _)| image.height)
+        .max()
+        .unwrap_or(0);
+    let mut stdout = io::stdout().lock();
+    let mut line_count = 0;
+
+    for (image, duration) in &images {
+        if line_count > 0 {
+            write!(stdout, "\x1b[{line_count}A")?;
+        }
+
+        let text = image.render(width, height, columns);
+        line_count = text.lines().count();
+        stdout.write_all(text.as_bytes())?;
+        stdout.flush()?;
+
+        thread::sleep(Duration::from_millis(*duration as u64));
+    }
+
+    Ok(())
+}
+
+/// Reads the resource at `path`, or the one of id `id` if it is a .sidpak
+/// archive, along with the resources it may refer to.
+fn open_resource(
+    path: &Path,
+    id: Option<sid::SidStringId>,
+) -> SidToolResult<(SidDecodedResource, SidPreviewContext)> {
+    let data = fs::read(path).map_err(|error| {
+        SidToolError::IO(format!("Unable to read resource {:?}: {error}", path))
+    })?;
+
+    let (resource, source) = if data.starts_with(PAK_MAGIC) {
+        let id = id.ok_or_else(|| {
+            SidToolError::Malformed("Set the id of the resource of the archive".to_string())
+        })?;
+
+        let mut source = SidPreviewSource::Pak(decode_pak(&data)?.1);
+        (source.take(id)?, source)
+    } else {
+        let folder = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        (read_resource(&data)?, SidPreviewSource::Folder(folder))
+    };
+
+    Ok((resource, SidPreviewContext::new(source)))
+}
+
+/// Animation definition `animation` plays, and the indices of its frames in
+/// play order.
+fn animation_frame_order(
+    context: &mut SidPreviewContext,
+    animation: &SidDecodedAnimation,
+) -> SidToolResult<(SidDecodedAnimationDef, Vec<usize>)> {
+    let SidDecodedResource::AnimationDef(def) = context.source.take(animation.def_id)? else {
+        return Err(SidToolError::Malformed(format!(
+            "Resource {} is not an animation definition",
+            animation.def_id
+        )));
+    };
+
+    let direction = SidAnimationDirection::from_raw(animation.direction)
+        .unwrap_or(SidAnimationDirection::Forward);
+    let frames = frame_order(
+        def.frames.len(),
+        animation.offset as usize,
+        animation.length as usize,
+        direction,
+    )?;
+
+    Ok((def, frames))
+}
+
+/// Draws a preview of a resource in the terminal, scaled down to fit
+/// `columns` characters: the image of a sprite sheet or a texture, or the
+/// frames of an animation definition or an animation, played once. `id`
+/// picks the resource of a .sidpak archive.
+pub fn preview<P: AsRef<Path>>(
+    path: P,
+    id: Option<sid::SidStringId>,
+    columns: u32,
+) -> SidToolResult<()> {
+    let (resource, mut context) = open_resource(path.as_ref(), id)?;
+
+    match resource {
+        SidDecodedResource::SpriteSheet(sheet) => {
+            let image = context.decode_image(&sheet.image)?;
+            print!("{}", image.render(image.width, image.height, columns));
+        }
+        SidDecodedResource::Texture(texture) => {
+            let image = context.decode_image(&texture.image)?;
+            print!("{}", image.render(image.width, image.height, columns));
+        }
+        SidDecodedResource::AnimationDef(def) => {
+            let frames = frame_order(
+                def.frames.len(),
+                0,
+                def.frames.len(),
+                SidAnimationDirection::Forward,
+            )?;
+            play(&mut context, &def, &frames, columns)?;
+        }
+        SidDecodedResource::Animation(animation) => {
+            let (def, frames) = animation_frame_order(&mut context, &animation)?;
+            play(&mut context, &def, &frames, columns)?;
+        }
+        resource => {
+            return Err(SidToolError::UnsupportedFormat(format!(
+                "Unable to preview a {:?} resource, only images and animations can be previewed",
+                resource.resource_type()
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+/// Frames of the animation definition or animation resource at `path`, or of
+/// id `id` if it is a .sidpak archive, in play order.
+pub(crate) fn animation_frames(
+    path: &Path,
+    id: Option<sid::SidStringId>,
+) -> SidToolResult<SidAnimationFrames> {
+    let (resource, mut context) = open_resource(path, id)?;
+
+    let (def, frames, looping, speed_scale) = match resource {
+        SidDecodedResource::AnimationDef(def) => {
+            let frames = frame_order(
+                def.frames.len(),
+                0,
+                def.frames.len(),
+                SidAnimationDirection::Forward,
+            )?;
+            (def, frames, true, 1.0)
+        }
+        SidDecodedResource::Animation(animation) => {
+            let (def, frames) = animation_frame_order(&mut context, &animation)?;
+            (def, frames, animation.looping, animation.speed_scale)
+        }
+        resource => {
+            return Err(SidToolError::UnsupportedFormat(format!(
+                "Unable to export a {:?} resource, only animations can be exported",
+                resource.resource_type()
+            )))
+        }
+    };
+
+    let mut out = SidAnimationFrames::new(looping, speed_scale);
+
+    for (image, duration) in frame_images(&mut context, &def, &frames)? {
+        let pixels = image.pixels.into_iter().flatten().collect();
+        let image = RgbaImage::from_raw(image.width, image.height, pixels)
+            .expect("Frame image should match its size");
+        out.push(image, duration);
+    }
+
+    Ok(out)
+}
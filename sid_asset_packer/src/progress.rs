@@ -0,0 +1,119 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use std::{
+    io::{self, IsTerminal},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// Delay between two progress lines when the standard error is not a
+/// terminal, e.g. in CI logs.
+const PLAIN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Bar being drawn, if any, hidden while log messages are printed.
+static ACTIVE_BAR: RwLock<Option<ProgressBar>> = RwLock::new(None);
+
+struct SidProgressState {
+    total: usize,
+    done: AtomicUsize,
+    bar: Option<ProgressBar>,
+    last_line: Mutex<Instant>,
+}
+
+/// Progress of a pack, drawn on the standard error: a bar on terminals, or a
+/// line every few seconds otherwise. Clones share the same progress.
+#[derive(Clone)]
+pub struct SidProgress {
+    state: Option<Arc<SidProgressState>>,
+}
+
+impl SidProgress {
+    /// Progress that shows nothing.
+    pub fn hidden() -> Self {
+        Self { state: None }
+    }
+
+    pub fn new(total: usize) -> Self {
+        let bar = if io::stderr().is_terminal() {
+            let bar =
+                ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stderr());
+
+            if let Ok(style) =
+                ProgressStyle::with_template("{bar:40} {pos}/{len} [{elapsed_precise}] {wide_msg}")
+            {
+                bar.set_style(style);
+            }
+
+            *ACTIVE_BAR.write().unwrap_or_else(PoisonError::into_inner) = Some(bar.clone());
+            Some(bar)
+        } else {
+            None
+        };
+
+        Self {
+            state: Some(Arc::new(SidProgressState {
+                total,
+                done: AtomicUsize::new(0),
+                bar,
+                last_line: Mutex::new(Instant::now()),
+            })),
+        }
+    }
+
+    /// Records that the asset at `path` is done, whether it was packed, skipped
+    /// or failed.
+    pub fn inc(&self, path: &Path) {
+        let Some(state) = &self.state else {
+            return;
+        };
+
+        let done = state.done.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if let Some(bar) = &state.bar {
+            bar.set_message(path.display().to_string());
+            bar.inc(1);
+            return;
+        }
+
+        let mut last_line = state
+            .last_line
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if last_line.elapsed() >= PLAIN_INTERVAL || done == state.total {
+            eprintln!("Processed {done}/{} asset(s).", state.total);
+            *last_line = Instant::now();
+        }
+    }
+
+    /// Removes the bar, if any.
+    pub fn finish(&self) {
+        let Some(bar) = self.state.as_ref().and_then(|state| state.bar.as_ref()) else {
+            return;
+        };
+
+        bar.finish_and_clear();
+        *ACTIVE_BAR.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+/// Runs `f`, which prints to the terminal, with the progress bar hidden.
+pub(crate) fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    let bar = ACTIVE_BAR
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+
+    match bar {
+        Some(bar) => bar.suspend(f),
+        None => f(),
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{
+    ffi::OsStr,
+    fmt,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{OnceLock, PoisonError, RwLock, RwLockReadGuard},
+};
+
+use crate::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAudioAsset, SidBitmapFontAsset, SidBlobAsset,
+        SidCharacterAsset, SidCurveAsset, SidFontAsset, SidMaterialAsset, SidMeshAsset,
+        SidNineSliceAsset, SidPaletteAsset, SidPrefabAsset, SidSceneAsset, SidShaderAsset,
+        SidSkeletalAnimationAsset, SidSkeletonAsset, SidSliceAsset, SidSpriteSheetAsset,
+        SidStringTableAsset, SidTextureAsset, SidTilemapAsset, SidTilesetAsset,
+    },
+    error::SidAssetFailure,
+    resource::{
+        SidAssetEntry, SidAssetProcessor, SidAssetSerializationResult, SidPackContext,
+        SidProcessSettings, SidProcessedAsset, SidValidationContext,
+    },
+};
+
+/// Object-safe view of a `SidAssetProcessor`, as stored in the registry.
+pub trait SidDynAssetProcessor: Send + Sync {
+    /// Name of the assets subfolder holding the assets of this processor.
+    fn namespace(&self) -> &'static str;
+
+    fn process_assets(&self, assets_input_path: PathBuf, context: &mut SidPackContext);
+
+    fn process_asset(
+        &self,
+        asset_input_path: &Path,
+        settings: SidProcessSettings,
+    ) -> SidAssetSerializationResult<SidProcessedAsset>;
+
+    fn validate_assets(&self, assets_input_path: PathBuf, context: &mut SidValidationContext);
+
+    /// Number of assets this processor would pack from its namespace folder.
+    fn count_assets(&self, assets_input_path: PathBuf) -> usize;
+
+    fn list_assets(
+        &self,
+        assets_input_path: PathBuf,
+        entries: &mut Vec<SidAssetEntry>,
+        failures: &mut Vec<SidAssetFailure>,
+    );
+}
+
+struct SidProcessorHandle<T>(PhantomData<fn() -> T>);
+
+impl<T: SidAssetProcessor + 'static> SidDynAssetProcessor for SidProcessorHandle<T> {
+    fn namespace(&self) -> &'static str {
+        T::namespace()
+    }
+
+    fn process_assets(&self, assets_input_path: PathBuf, context: &mut SidPackContext) {
+        T::process_assets(assets_input_path, context);
+    }
+
+    fn process_asset(
+        &self,
+        asset_input_path: &Path,
+        settings: SidProcessSettings,
+    ) -> SidAssetSerializationResult<SidProcessedAsset> {
+        T::process_asset(asset_input_path, settings)
+    }
+
+    fn validate_assets(&self, assets_input_path: PathBuf, context: &mut SidValidationContext) {
+        T::validate_assets(assets_input_path, context);
+    }
+
+    fn count_assets(&self, assets_input_path: PathBuf) -> usize {
+        T::compatible_entries(assets_input_path).len()
+    }
+
+    fn list_assets(
+        &self,
+        assets_input_path: PathBuf,
+        entries: &mut Vec<SidAssetEntry>,
+        failures: &mut Vec<SidAssetFailure>,
+    ) {
+        T::list_assets(assets_input_path, entries, failures);
+    }
+}
+
+/// Asset processors, by namespace.
+pub struct SidProcessorRegistry {
+    processors: Vec<Box<dyn SidDynAssetProcessor>>,
+}
+
+impl fmt::Debug for SidProcessorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.processors
+                    .iter()
+                    .map(|processor| processor.namespace()),
+            )
+            .finish()
+    }
+}
+
+impl SidProcessorRegistry {
+    /// Registry of the built-in asset types.
+    pub fn new() -> Self {
+        let mut registry = Self { processors: vec![] };
+        registry.register::<SidSpriteSheetAsset>();
+        registry.register::<SidAnimationDefAsset>();
+        registry.register::<SidAnimationAsset>();
+        registry.register::<SidSliceAsset>();
+        registry.register::<SidAudioAsset>();
+        registry.register::<SidFontAsset>();
+        registry.register::<SidTilesetAsset>();
+        registry.register::<SidTilemapAsset>();
+        registry.register::<SidShaderAsset>();
+        registry.register::<SidBlobAsset>();
+        registry.register::<SidPrefabAsset>();
+        registry.register::<SidStringTableAsset>();
+        registry.register::<SidTextureAsset>();
+        registry.register::<SidPaletteAsset>();
+        registry.register::<SidNineSliceAsset>();
+        registry.register::<SidCharacterAsset>();
+        registry.register::<SidSkeletonAsset>();
+        registry.register::<SidSkeletalAnimationAsset>();
+        registry.register::<SidBitmapFontAsset>();
+        registry.register::<SidMeshAsset>();
+        registry.register::<SidCurveAsset>();
+        registry.register::<SidMaterialAsset>();
+        registry.register::<SidSceneAsset>();
+        registry
+    }
+
+    /// Registers the processor of `T`, replacing the one of its namespace if
+    /// any.
+    pub fn register<T: SidAssetProcessor + 'static>(&mut self) {
+        self.processors
+            .retain(|processor| processor.namespace() != T::namespace());
+        self.processors
+            .push(Box::new(SidProcessorHandle::<T>(PhantomData)));
+    }
+
+    /// Processor of the assets found in the namespace folder `folder_name`.
+    pub fn find(&self, folder_name: &OsStr) -> Option<&dyn SidDynAssetProcessor> {
+        let folder_name = folder_name.to_str()?;
+
+        self.processors
+            .iter()
+            .find(|processor| processor.namespace() == folder_name)
+            .map(AsRef::as_ref)
+    }
+
+    pub fn namespaces(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.processors
+            .iter()
+            .map(|processor| processor.namespace())
+    }
+}
+
+fn registry() -> &'static RwLock<SidProcessorRegistry> {
+    static REGISTRY: OnceLock<RwLock<SidProcessorRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(SidProcessorRegistry::new()))
+}
+
+/// Makes every tool entry point process the assets of `T`, found in the
+/// namespace folder of `T`. Registering a built-in namespace again replaces
+/// its processor.
+pub fn register_processor<T: SidAssetProcessor + 'static>() {
+    registry()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register::<T>();
+}
+
+pub(crate) fn processors() -> RwLockReadGuard<'static, SidProcessorRegistry> {
+    registry().read().unwrap_or_else(PoisonError::into_inner)
+}
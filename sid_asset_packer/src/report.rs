@@ -0,0 +1,155 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Serialize;
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    error::{SidToolError, SidToolResult},
+    log,
+    sid::SidStringId,
+    timings::SidStageTimings,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidAssetStatus {
+    Ok,
+    /// The asset did not change since the last run.
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SidAssetReport {
+    pub path: PathBuf,
+    pub status: SidAssetStatus,
+    pub duration_ms: f64,
+    /// Ids of the resources or assets written from this input.
+    pub ids: Vec<SidStringId>,
+    /// Size of the resources written from this input, before any archive.
+    pub bytes_written: u64,
+    /// Time spent in each stage of the pipeline, when collected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<SidStageTimings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SidAssetReport {
+    pub fn new(path: PathBuf, status: SidAssetStatus, duration: Duration) -> Self {
+        SidAssetReport {
+            path,
+            status,
+            duration_ms: to_ms(duration),
+            ids: vec![],
+            bytes_written: 0,
+            timings: None,
+            error: None,
+        }
+    }
+}
+
+/// Summary of a tool run, written with `--report`.
+#[derive(Debug, Serialize)]
+pub struct SidReport {
+    pub success: bool,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub duration_ms: f64,
+    pub assets: Vec<SidAssetReport>,
+}
+
+impl SidReport {
+    pub fn new() -> Self {
+        SidReport {
+            success: true,
+            error_count: 0,
+            warning_count: 0,
+            duration_ms: 0.0,
+            assets: vec![],
+        }
+    }
+
+    pub fn add(&mut self, asset: SidAssetReport) {
+        self.assets.push(asset);
+    }
+
+    /// Fills the run totals from the log counters.
+    pub fn finish(&mut self, duration: Duration) {
+        self.error_count = log::error_count();
+        self.warning_count = log::warning_count();
+        self.success = self.error_count == 0;
+        self.duration_ms = to_ms(duration);
+        self.assets.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    /// One-line summary of the assets of a pack, e.g. "3 asset(s) packed, 1
+    /// skipped, 0 failed: 12.5 KiB written in 0.42s."
+    pub fn summary(&self, duration: Duration) -> String {
+        let count = |status| {
+            self.assets
+                .iter()
+                .filter(|asset| asset.status == status)
+                .count()
+        };
+
+        let bytes_written: u64 = self.assets.iter().map(|asset| asset.bytes_written).sum();
+
+        format!(
+            "{} asset(s) packed, {} skipped, {} failed: {} written in {:.2}s.",
+            count(SidAssetStatus::Ok),
+            count(SidAssetStatus::Skipped),
+            count(SidAssetStatus::Failed),
+            format_size(bytes_written),
+            duration.as_secs_f64()
+        )
+    }
+
+    /// Writes the report as JSON to `path`, or to the standard output if none
+    /// is given.
+    pub fn write_json(&self, path: Option<&Path>) -> SidToolResult<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|error| SidToolError::Malformed(error.to_string()))?;
+
+        match path {
+            Some(path) => fs::write(path, json).map_err(|error| {
+                SidToolError::IO(format!("Unable to write report {:?}: {error}", path))
+            }),
+            None => {
+                let mut stdout = io::stdout().lock();
+                writeln!(stdout, "{json}")?;
+                Ok(())
+            }
+        }
+    }
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
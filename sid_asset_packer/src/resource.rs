@@ -2,337 +2,4698 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
+pub mod read;
+
 use image::GenericImageView;
-use serde::Deserialize;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
 
 use std::{
+    any::Any,
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::OsStr,
+    fmt,
     fs::{self, File},
-    io::Write,
+    io::{self, BufWriter, Write},
+    mem,
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
 };
 
 use crate::{
-    asset::{SidAnimationAsset, SidAnimationDefAsset, SidPackedAsset, SidSpriteSheetAsset},
-    sid::{self, sid_texture_format},
-    sid_error, sid_warning,
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAudioAsset, SidBitmapFontAsset, SidBlobAsset,
+        SidBoneTransform, SidCharacterAsset, SidColorSpace, SidCurveAsset, SidFontAsset,
+        SidKeyCurve, SidMaterialAsset, SidMeshAsset, SidMeshTexture, SidNineSliceAsset,
+        SidPackedAsset, SidPaletteAsset, SidPixelFormat, SidPrefabAsset, SidPrefabComponentAsset,
+        SidSceneAsset, SidShaderAsset, SidShaderTarget, SidSkeletalAnimationAsset,
+        SidSkeletonAsset, SidSliceAsset, SidSlicePivot, SidSliceRect, SidSpriteSheetAsset,
+        SidStringTableAsset, SidTextureAsset, SidTilemapAsset, SidTilesetAsset,
+    },
+    atlas::SidSkylinePacker,
+    cache::{self, SidBuildCache, SidCacheEntry},
+    clean,
+    compression::{self, SidCompression},
+    config::{SidPackConfig, SidPackOverride, SidPackage},
+    error::{SidAssetFailure, SidToolError, SidToolResult},
+    graph::SidAssetGraph,
+    id_overrides,
+    id_registry::{SidIdCollision, SidIdOwner, SidIdRegistry},
+    manifest::{self, SidManifest, SidManifestEntry},
+    naming::SidNameRules,
+    pak::{self, SidPakEntry, SidPakWriter, DEFAULT_PAK_FILE_NAME},
+    progress::SidProgress,
+    registry::{self, SidDynAssetProcessor},
+    report::{SidAssetReport, SidAssetStatus, SidReport},
+    schema::{SidAssetReference, SidAssetSchema},
+    sid::{
+        self, sid_audio_format, sid_color_space, sid_prefab_component_type, sid_shader_format,
+        sid_shader_stage, sid_texture_format,
+    },
+    sid_debug, sid_error, sid_info, sid_warning,
+    string_table::{self, SidLocaleStrings},
+    tags::{self, SidTagFilter},
+    texture::{self, SidPalette, SidTextureEncoding, SidTextureOptions},
+    timings::{self, SidStage, SidTimedWriter},
+    walk::{self, SidEntryKind, SidIgnoreRules, SidVisitedFolders, IGNORE_FILE_NAME},
 };
 
 #[derive(Debug)]
 pub enum SidAssetSerializationError {
     UnsupportedFormat(String),
+    Malformed(String),
     IO(String),
 }
 
+impl fmt::Display for SidAssetSerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SidAssetSerializationError::UnsupportedFormat(error) => {
+                write!(f, "Unsupported format error: {error}")
+            }
+            SidAssetSerializationError::Malformed(error) => write!(f, "Malformed error: {error}"),
+            SidAssetSerializationError::IO(error) => write!(f, "I/O error: {error}"),
+        }
+    }
+}
+
 pub static DEFAULT_RESOURCES_PATH: &str = "./resources";
+/// Package of the assets assigned to none, when the pack is split into
+/// packages.
+pub static DEFAULT_PACKAGE_NAME: &str = "base";
 
 pub type SidAssetSerializationResult<T> = Result<T, SidAssetSerializationError>;
 
-pub trait SerializeSidAsset {
-    fn write_resource<P1: AsRef<Path>, P2: AsRef<Path>>(
-        &self,
-        in_path: P1,
-        out_folder: P2,
-    ) -> SidAssetSerializationResult<()>;
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidResourceType {
+    SpriteSheet = 1,
+    AnimationDef = 2,
+    Animation = 3,
+    Slice = 4,
+    Audio = 5,
+    Font = 6,
+    Tileset = 7,
+    Tilemap = 8,
+    Shader = 9,
+    Blob = 10,
+    Prefab = 11,
+    StringTable = 12,
+    Texture = 13,
+    Palette = 14,
+    NineSlice = 15,
+    Character = 16,
+    Skeleton = 17,
+    SkeletalAnimation = 18,
+    BitmapFont = 19,
+    Mesh = 20,
+    Curve = 21,
+    Material = 22,
+    Scene = 23,
 }
 
-trait SidResourceWrite: Write {
-    fn write_packed(&mut self, data: &[u8]) -> SidAssetSerializationResult<()> {
-        match self.write_all(data) {
-            Ok(img) => img,
-            Err(err) => return Err(SidAssetSerializationError::IO(err.to_string())),
+impl SidResourceType {
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        match raw {
+            1 => Some(SidResourceType::SpriteSheet),
+            2 => Some(SidResourceType::AnimationDef),
+            3 => Some(SidResourceType::Animation),
+            4 => Some(SidResourceType::Slice),
+            5 => Some(SidResourceType::Audio),
+            6 => Some(SidResourceType::Font),
+            7 => Some(SidResourceType::Tileset),
+            8 => Some(SidResourceType::Tilemap),
+            9 => Some(SidResourceType::Shader),
+            10 => Some(SidResourceType::Blob),
+            11 => Some(SidResourceType::Prefab),
+            12 => Some(SidResourceType::StringTable),
+            13 => Some(SidResourceType::Texture),
+            14 => Some(SidResourceType::Palette),
+            15 => Some(SidResourceType::NineSlice),
+            16 => Some(SidResourceType::Character),
+            17 => Some(SidResourceType::Skeleton),
+            18 => Some(SidResourceType::SkeletalAnimation),
+            19 => Some(SidResourceType::BitmapFont),
+            20 => Some(SidResourceType::Mesh),
+            21 => Some(SidResourceType::Curve),
+            22 => Some(SidResourceType::Material),
+            23 => Some(SidResourceType::Scene),
+            _ => None,
+        }
+    }
+}
+
+// Every resource starts with this header: magic (4 bytes), format version
+// (u32), resource type (u32). The version must be bumped whenever the layout of
+// any resource changes. Numbers use the byte order of the target platform,
+// which readers detect from the version. Every resource ends with the XXH3
+// hash (u64) of all the bytes before it, so that corrupted or truncated files
+// are detected when loaded.
+pub static RESOURCE_MAGIC: &[u8; 4] = b"SIDR";
+pub const RESOURCE_FORMAT_VERSION: u32 = 12;
+pub const RESOURCE_HEADER_SIZE: usize = 12;
+pub const RESOURCE_CHECKSUM_SIZE: usize = 8;
+/// Bit of the resource type set in the header of resources with 64-bit ids.
+pub const RESOURCE_WIDE_IDS_FLAG: u32 = 1 << 31;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SidResourceHeader {
+    pub version: u32,
+    pub resource_type: SidResourceType,
+    pub endianness: SidEndianness,
+}
+
+impl SidResourceHeader {
+    pub fn new(resource_type: SidResourceType, endianness: SidEndianness) -> Self {
+        Self {
+            version: RESOURCE_FORMAT_VERSION,
+            resource_type,
+            endianness,
+        }
+    }
+
+    pub fn write<W: SidResourceWrite>(&self, out: &mut W) -> SidAssetSerializationResult<()> {
+        out.write_packed(RESOURCE_MAGIC)?;
+        out.write_number(self.version)?;
+
+        let id_flag = if sid::STRING_ID_BITS == 64 {
+            RESOURCE_WIDE_IDS_FLAG
+        } else {
+            0
         };
 
-        Ok(())
+        out.write_number(self.resource_type as u32 | id_flag)
     }
-}
 
-impl SidResourceWrite for File {}
+    /// Reads the header of an uncompressed resource, and checks that this
+    /// version of the tools can read it.
+    pub fn check(data: &[u8]) -> SidAssetSerializationResult<Self> {
+        if data.len() < RESOURCE_HEADER_SIZE || &data[..4] != RESOURCE_MAGIC {
+            return Err(SidAssetSerializationError::UnsupportedFormat(
+                "Not a resource (missing magic)".to_string(),
+            ));
+        }
 
-impl SerializeSidAsset for SidSpriteSheetAsset {
-    fn write_resource<P1: AsRef<Path>, P2: AsRef<Path>>(
-        &self,
-        in_path: P1,
-        out_folder: P2,
-    ) -> SidAssetSerializationResult<()> {
-        let id = sid::generate_sprite_sheet_id(&self.name);
-        let path = out_folder.as_ref().join(id.to_string());
+        let raw_version = data[4..8].try_into().unwrap();
+        let raw_type = data[8..12].try_into().unwrap();
 
-        let mut texture_path = match in_path.as_ref().parent() {
-            Some(texture_path) => PathBuf::from(texture_path),
-            None => {
-                return Err(SidAssetSerializationError::IO(format!(
-                    "Unable to retrieve texture path"
+        let (version, raw_type, endianness) =
+            if u32::from_le_bytes(raw_version) == RESOURCE_FORMAT_VERSION {
+                (
+                    RESOURCE_FORMAT_VERSION,
+                    u32::from_le_bytes(raw_type),
+                    SidEndianness::Little,
+                )
+            } else if u32::from_be_bytes(raw_version) == RESOURCE_FORMAT_VERSION {
+                (
+                    RESOURCE_FORMAT_VERSION,
+                    u32::from_be_bytes(raw_type),
+                    SidEndianness::Big,
+                )
+            } else {
+                return Err(SidAssetSerializationError::UnsupportedFormat(format!(
+                    "Unsupported resource format version {} (expected {RESOURCE_FORMAT_VERSION})",
+                    u32::from_le_bytes(raw_version)
                 )));
-            }
+            };
+
+        let id_bits = if raw_type & RESOURCE_WIDE_IDS_FLAG != 0 {
+            64
+        } else {
+            32
         };
 
-        texture_path.push(&self.image_path);
+        if id_bits != sid::STRING_ID_BITS {
+            return Err(SidAssetSerializationError::UnsupportedFormat(format!(
+                "Resource with {id_bits}-bit ids, but the tools use {}-bit ones",
+                sid::STRING_ID_BITS
+            )));
+        }
 
-        let texture = match image::open(texture_path) {
-            Ok(texture) => texture,
-            Err(error) => return Err(SidAssetSerializationError::IO(error.to_string())),
+        let raw_type = raw_type & !RESOURCE_WIDE_IDS_FLAG;
+
+        let resource_type = SidResourceType::from_raw(raw_type).ok_or_else(|| {
+            SidAssetSerializationError::UnsupportedFormat(format!(
+                "Unknown resource type {raw_type}"
+            ))
+        })?;
+
+        Ok(Self {
+            version,
+            resource_type,
+            endianness,
+        })
+    }
+
+    /// Checks the checksum ending an uncompressed resource, and returns the
+    /// resource without it.
+    pub fn check_checksum<'a>(&self, data: &'a [u8]) -> SidAssetSerializationResult<&'a [u8]> {
+        if data.len() < RESOURCE_HEADER_SIZE + RESOURCE_CHECKSUM_SIZE {
+            return Err(SidAssetSerializationError::Malformed(
+                "Truncated resource (missing checksum)".to_string(),
+            ));
+        }
+
+        let (contents, raw_checksum) = data.split_at(data.len() - RESOURCE_CHECKSUM_SIZE);
+        let raw_checksum = raw_checksum.try_into().unwrap();
+
+        let checksum = match self.endianness {
+            SidEndianness::Little => u64::from_le_bytes(raw_checksum),
+            SidEndianness::Big => u64::from_be_bytes(raw_checksum),
         };
 
-        let (width, height) = texture.dimensions();
-        let channel_count = texture.color().channel_count();
+        let expected = xxh3_64(contents);
 
-        let format = match texture.color() {
-            image::ColorType::Rgb8 => sid_texture_format::SID_TEXTURE_FORMAT_RGB8,
-            image::ColorType::Rgba8 => sid_texture_format::SID_TEXTURE_FORMAT_RGBA8,
-            format => {
-                return Err(SidAssetSerializationError::UnsupportedFormat(format!(
-                    "Unknown or unsupported format: {:?}",
-                    format
-                )))
+        if checksum != expected {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Checksum mismatch ({checksum:016x}, expected {expected:016x}), the resource is corrupted or truncated"
+            )));
+        }
+
+        Ok(contents)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidOutputFormat {
+    /// One file per resource, named after its id.
+    Loose,
+    /// A single .sidpak archive holding every resource.
+    Pak,
+}
+
+/// What becomes of the resources already in the output folder.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidOverwritePolicy {
+    /// Packed resources replace the existing ones with the same ids, and the
+    /// manifest only lists the packed resources.
+    #[default]
+    Overwrite,
+    /// The resources, pak, manifest and build cache of the output folder are
+    /// deleted before packing.
+    Clean,
+    /// The pack fails if any asset maps to the id of an existing resource.
+    NoOverwrite,
+    /// Packed resources replace the existing ones with the same ids, and the
+    /// others are kept, in the manifest and in the pak.
+    Merge,
+}
+
+impl fmt::Display for SidOverwritePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SidOverwritePolicy::Overwrite => write!(f, "overwriting existing resources"),
+            SidOverwritePolicy::Clean => write!(f, "cleaning the output folder first"),
+            SidOverwritePolicy::NoOverwrite => {
+                write!(f, "refusing to overwrite existing resources")
             }
-        };
+            SidOverwritePolicy::Merge => write!(f, "merging with existing resources"),
+        }
+    }
+}
 
-        let raw_format = format as i32;
+/// What happens when several input folders hold an asset with the same name.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SidInputCollisionPolicy {
+    /// The pack fails, listing every asset defined by several folders.
+    #[default]
+    Error,
+    /// The asset of the first folder listed is packed, the others skipped.
+    FirstWins,
+    /// The asset of the last folder listed is packed, e.g. so that the assets
+    /// of a game override the shared ones listed before.
+    LastWins,
+}
 
-        let mut file = match File::create(&path) {
-            Ok(file) => file,
-            Err(error) => return Err(SidAssetSerializationError::IO(error.to_string())),
+#[derive(Debug)]
+pub struct SidPackOptions {
+    /// Number of worker threads; 0 uses one per logical CPU.
+    pub jobs: usize,
+    /// Repack every asset, even the ones the build cache reports unchanged.
+    pub force: bool,
+    /// Only warn when two assets map to the same resource id.
+    pub allow_collisions: bool,
+    /// Pack every asset in memory and log the resources that would be
+    /// written, without touching the output folder.
+    pub dry_run: bool,
+    /// Write byte-identical output for identical inputs: the build cache,
+    /// which records modification times, is neither read nor written, and
+    /// manifest source paths are relative to the assets folder.
+    pub deterministic: bool,
+    /// Name loose resources `<id>_<hash>` after the XXH3 hash of their
+    /// contents, so that changed resources get new file names. The files of
+    /// their previous names are removed.
+    pub content_hash_names: bool,
+    /// Draw the progress of the pack on the standard error.
+    pub progress: bool,
+    /// Record the time every asset spends in each stage of the pipeline.
+    pub timings: bool,
+    pub output_format: SidOutputFormat,
+    /// What becomes of the resources already in the output folder.
+    pub overwrite: SidOverwritePolicy,
+    pub compression: SidCompression,
+    /// Byte order of the resources, matching the target platform.
+    pub endianness: SidEndianness,
+    /// Settings of the packed sprite sheets and textures.
+    pub texture: SidTextureOptions,
+    /// Settings of the assets under a given folder or file, relative to the
+    /// assets folder. The last matching override wins.
+    pub overrides: Vec<SidPackOverride>,
+    /// Rules the asset names must follow.
+    pub names: SidNameRules,
+    /// Which asset is packed when several input folders define it.
+    pub input_collisions: SidInputCollisionPolicy,
+    /// Tags of the assets to pack, or to leave out.
+    pub tags: SidTagFilter,
+    /// Packages the assets are split into, each written to a folder of the
+    /// output folder named after it, with its own manifest. None writes every
+    /// asset to the output folder.
+    pub packages: Vec<SidPackage>,
+    /// Package of the assets of none of `packages`.
+    pub default_package: String,
+    /// Backend generating the ids of the pack, e.g. a `SidTestBackend` in
+    /// tests. None keeps the one in use, sid_lib by default.
+    pub backend: Option<Arc<dyn sid::SidBackend>>,
+}
+
+impl SidPackOptions {
+    pub fn new() -> Self {
+        Self {
+            jobs: 0,
+            force: false,
+            allow_collisions: false,
+            dry_run: false,
+            deterministic: false,
+            content_hash_names: false,
+            progress: false,
+            timings: false,
+            output_format: SidOutputFormat::Loose,
+            overwrite: SidOverwritePolicy::Overwrite,
+            compression: SidCompression::None,
+            endianness: SidEndianness::Little,
+            texture: SidTextureOptions::new(),
+            overrides: vec![],
+            names: SidNameRules::new(),
+            input_collisions: SidInputCollisionPolicy::Error,
+            tags: SidTagFilter::new(),
+            packages: vec![],
+            default_package: DEFAULT_PACKAGE_NAME.to_string(),
+            backend: None,
+        }
+    }
+}
+
+/// Settings an asset is packed with, opaque outside of this crate.
+#[derive(Clone, Copy)]
+pub struct SidProcessSettings<'a> {
+    assets_input_path: &'a Path,
+    resources_output_path: &'a Path,
+    // Unused when writing a pak, which is always rebuilt from scratch.
+    previous_cache: Option<&'a SidBuildCache>,
+    compression: SidCompression,
+    endianness: SidEndianness,
+    texture: SidTextureOptions,
+    overrides: &'a [SidPackOverride],
+    content_hash_names: bool,
+    timings: bool,
+    backend: Option<&'a Arc<dyn sid::SidBackend>>,
+}
+
+impl SidProcessSettings<'_> {
+    /// Applies the overrides matching the asset at `asset_input_path`.
+    fn for_asset(&self, asset_input_path: &Path) -> Self {
+        let mut settings = *self;
+
+        let Ok(relative_path) = asset_input_path.strip_prefix(self.assets_input_path) else {
+            return settings;
         };
 
-        file.write_packed(&id.to_le_bytes())?;
-        file.write_packed(&width.to_le_bytes())?;
-        file.write_packed(&height.to_le_bytes())?;
-        file.write_packed(&channel_count.to_le_bytes())?;
-        file.write_packed(&raw_format.to_le_bytes())?;
+        for asset_override in self.overrides {
+            if !relative_path.starts_with(&asset_override.path) {
+                continue;
+            }
+
+            if let Some(compression) = asset_override.compression {
+                settings.compression = compression;
+            }
 
-        let texture_size =
-            (width as sid::UIndex) * (height as sid::UIndex) * channel_count as sid::UIndex;
+            if let Some(encoding) = asset_override.texture_encoding {
+                settings.texture.encoding = encoding;
+            }
 
-        file.write_packed(&texture_size.to_le_bytes())?;
-        file.write_packed(&texture.as_bytes())?;
+            if let Some(color_space) = asset_override.color_space {
+                settings.texture.color_space = Some(color_space);
+            }
 
-        Ok(())
+            if let Some(pixel_format) = asset_override.pixel_format {
+                settings.texture.pixel_format = Some(pixel_format);
+            }
+        }
+
+        settings
     }
 }
 
-impl SerializeSidAsset for SidAnimationDefAsset {
-    fn write_resource<P1: AsRef<Path>, P2: AsRef<Path>>(
-        &self,
-        _: P1,
-        out_folder: P2,
-    ) -> SidAssetSerializationResult<()> {
-        let id = sid::generate_animation_def_id(&self.name);
-        let sheet_id = sid::generate_sprite_sheet_id(&self.sheet_name);
-        let path = out_folder.as_ref().join(id.to_string());
+impl SidPackOptions {
+    /// Options set in the [pack] section of a configuration file, defaulting
+    /// to the ones of `new()`.
+    pub fn from_config(config: &SidPackConfig) -> Self {
+        let mut options = Self::new();
+        options.allow_collisions = config.allow_collisions.unwrap_or(false);
+        options.deterministic = config.deterministic.unwrap_or(false);
+        options.content_hash_names = config.content_hash_names.unwrap_or(false);
+        options.overrides = config.overrides.clone();
+        options.tags.only = config.only_tags.clone();
+        options.tags.exclude = config.exclude_tags.clone();
+        options.packages = config.packages.clone();
 
-        let mut file = match File::create(&path) {
-            Ok(file) => file,
-            Err(err) => return Err(SidAssetSerializationError::IO(err.to_string())),
-        };
+        if let Some(default_package) = &config.default_package {
+            options.default_package = default_package.clone();
+        }
 
-        file.write_packed(&id.to_le_bytes())?;
-        file.write_packed(&sheet_id.to_le_bytes())?;
-        file.write_packed(&self.frame_count.to_le_bytes())?;
+        if let Some(jobs) = config.jobs {
+            options.jobs = jobs;
+        }
 
-        for frame in &self.frames {
-            file.write_packed(&frame.pos.x.to_le_bytes())?;
-            file.write_packed(&frame.pos.y.to_le_bytes())?;
+        if let Some(format) = config.format {
+            options.output_format = format;
+        }
+
+        if let Some(overwrite) = config.overwrite {
+            options.overwrite = overwrite;
+        }
 
-            file.write_packed(&frame.dims.width.to_le_bytes())?;
-            file.write_packed(&frame.dims.height.to_le_bytes())?;
+        if let Some(input_collisions) = config.input_collisions {
+            options.input_collisions = input_collisions;
+        }
 
-            file.write_packed(&frame.duration.to_le_bytes())?;
+        if let Some(compression) = config.compression {
+            options.compression = compression;
         }
 
-        Ok(())
+        if let Some(endianness) = config.endianness {
+            options.endianness = endianness;
+        }
+
+        if let Some(encoding) = config.texture_encoding {
+            options.texture.encoding = encoding;
+        }
+
+        options.texture.mipmaps = config.mipmaps;
+        options.texture.color_space = config.color_space;
+        options.texture.pixel_format = config.pixel_format;
+        options.texture.max_size = config.max_texture_size;
+        options.texture.power_of_two = config.power_of_two.unwrap_or(false);
+        options.texture.color_key = config.color_key;
+        options
+    }
+
+    fn process_settings<'a>(
+        &'a self,
+        assets_input_path: &'a Path,
+        resources_output_path: &'a Path,
+        previous_cache: Option<&'a SidBuildCache>,
+    ) -> SidProcessSettings<'a> {
+        SidProcessSettings {
+            assets_input_path,
+            resources_output_path,
+            previous_cache,
+            compression: self.compression,
+            endianness: self.endianness,
+            texture: self.texture,
+            overrides: &self.overrides,
+            content_hash_names: self.content_hash_names,
+            timings: self.timings,
+            backend: self.backend.as_ref(),
+        }
+    }
+
+    /// Describes the options changing the contents of the resources, so that
+    /// the build cache can tell when they must all be rebuilt. Resources refer
+    /// to each other by id, so the ids must not change either.
+    fn cache_key(&self) -> String {
+        let backend = {
+            let _backend = sid::scope_backend(self.backend.as_ref());
+            sid::backend_fingerprint()
+        };
+
+        format!(
+            "compression={:?};endianness={:?};texture={:?};overrides={:?};content_hash_names={};id_overrides={:016x};backend={backend:016x};format={RESOURCE_FORMAT_VERSION}",
+            self.compression,
+            self.endianness,
+            self.texture,
+            self.overrides,
+            self.content_hash_names,
+            id_overrides::fingerprint()
+        )
     }
 }
 
-impl SerializeSidAsset for SidAnimationAsset {
+/// State of a pack, opaque outside of this crate.
+pub struct SidPackContext<'a> {
+    settings: SidProcessSettings<'a>,
+    cache: SidBuildCache,
+    pak: Option<SidPakWriter>,
+    failures: Vec<SidAssetFailure>,
+    skipped_count: usize,
+    reports: Vec<SidAssetReport>,
+    manifest: SidManifest,
+    progress: SidProgress,
+    /// Assets left out of the pack, filtered out by their tags or overridden
+    /// by the ones of another input folder.
+    excluded_paths: HashSet<PathBuf>,
+}
+
+/// State of a validation, opaque outside of this crate.
+pub struct SidValidationContext {
+    /// Every asset that could be loaded, linked by its references.
+    graph: SidAssetGraph,
+    failures: Vec<SidAssetFailure>,
+}
+
+/// Packed asset, opaque outside of this crate.
+pub struct SidProcessedAsset {
+    manifest_entry: SidManifestEntry,
+    cache_entry: Option<SidCacheEntry>,
+    pak_entry: Option<SidPakEntry>,
+    skipped: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SidAssetEntry {
+    pub namespace: &'static str,
+    pub name: String,
+    pub id: sid::SidStringId,
+    pub path: PathBuf,
+    /// Other assets this asset refers to by name.
+    pub references: Vec<SidAssetReference>,
+}
+
+pub trait SerializeSidAsset {
+    fn resource_id(&self) -> sid::SidStringId;
+
+    fn resource_path<P: AsRef<Path>>(&self, out_folder: P) -> PathBuf {
+        out_folder.as_ref().join(self.resource_id().to_string())
+    }
+
+    /// Files the resource is built from, used to detect unchanged assets.
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        Ok(vec![in_path.as_ref().to_path_buf()])
+    }
+
+    fn resource_type(&self) -> SidResourceType;
+
+    /// Writes the resource contents, after the common header.
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()>;
+
+    fn serialize_resource<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        SidResourceHeader::new(self.resource_type(), out.endianness()).write(out)?;
+        self.serialize_payload(in_path, out)
+    }
+
     fn write_resource<P1: AsRef<Path>, P2: AsRef<Path>>(
         &self,
-        _: P1,
+        in_path: P1,
         out_folder: P2,
+        endianness: SidEndianness,
+        texture: SidTextureOptions,
     ) -> SidAssetSerializationResult<()> {
-        let id = sid::generate_animation_id(&self.name);
-        let def_id = sid::generate_animation_def_id(&self.def_name);
-        let path = out_folder.as_ref().join(id.to_string());
+        let path = self.resource_path(out_folder);
 
-        let mut file = match File::create(&path) {
+        let file = match timings::measure(SidStage::Write, || File::create(&path)) {
             Ok(file) => file,
-            Err(err) => return Err(SidAssetSerializationError::IO(err.to_string())),
+            Err(error) => return Err(SidAssetSerializationError::IO(error.to_string())),
         };
 
-        file.write_packed(&id.to_le_bytes())?;
-        file.write_packed(&def_id.to_le_bytes())?;
-        file.write_packed(&self.offset.to_le_bytes())?;
-        file.write_packed(&self.length.to_le_bytes())?;
+        // Resources are streamed to disk, so writes are timed as they happen.
+        timings::measure(SidStage::Encode, || {
+            let mut out = SidResourceWriter::with_texture_options(
+                SidTimedWriter::new(file),
+                endianness,
+                texture,
+            );
 
-        Ok(())
+            self.serialize_resource(in_path, &mut out)?;
+            out.finish().map(|_| ())
+        })
     }
 }
 
-trait SidAssetProcessor: SidPackedAsset
-where
-    for<'de> Self: Deserialize<'de>,
-    Self: Sized + SidPackedAsset + SerializeSidAsset,
-{
-    fn compatible(entry: &fs::DirEntry) -> bool {
-        let file_type = match entry.file_type() {
-            Ok(file_type) => file_type,
-            Err(_) => {
-                return false;
-            }
-        };
-
-        if !file_type.is_file() {
-            return false;
-        }
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Deserialize)]
+pub enum SidEndianness {
+    #[default]
+    #[serde(rename = "le")]
+    Little,
+    #[serde(rename = "be")]
+    Big,
+}
 
-        let path = entry.path();
+pub trait SidPackedNumber: Copy {
+    fn to_bytes(self, endianness: SidEndianness) -> Vec<u8>;
+}
 
-        let extension = match path.extension() {
-            Some(extension) => extension,
-            None => {
-                return false;
+macro_rules! impl_packed_number {
+    ($($number:ty),*) => {
+        $(impl SidPackedNumber for $number {
+            fn to_bytes(self, endianness: SidEndianness) -> Vec<u8> {
+                match endianness {
+                    SidEndianness::Little => self.to_le_bytes().to_vec(),
+                    SidEndianness::Big => self.to_be_bytes().to_vec(),
+                }
             }
-        };
+        })*
+    };
+}
 
-        let extension = match extension.to_str() {
-            Some(extension) => extension,
-            None => {
-                return false;
-            }
-        };
+impl_packed_number!(u8, u16, u32, u64, i32, f32);
 
-        return Self::extension_compatible(extension);
-    }
+pub trait SidResourceWrite: Write {
+    /// Byte order of the numbers written with `write_number`.
+    fn endianness(&self) -> SidEndianness;
 
-    fn assets_folder<P: AsRef<Path>>(folder_name: P) -> bool {
-        let folder_name = match folder_name.as_ref().to_str() {
-            Some(folder_name) => folder_name,
-            None => {
-                return false;
-            }
+    /// Settings of the sprite sheets and textures written.
+    fn texture_options(&self) -> SidTextureOptions;
+
+    fn write_packed(&mut self, data: &[u8]) -> SidAssetSerializationResult<()> {
+        match self.write_all(data) {
+            Ok(img) => img,
+            Err(err) => return Err(SidAssetSerializationError::IO(err.to_string())),
         };
 
-        Self::namespace() == folder_name
+        Ok(())
     }
 
-    fn process_asset<P1: AsRef<Path>, P2: AsRef<Path>>(
-        asset_input_path: P1,
-        resources_output_path: P2,
-    ) {
-        let file = File::open(&asset_input_path).unwrap();
-        let asset: Self = serde_json::from_reader(file).unwrap();
-        asset
-            .write_resource(asset_input_path, resources_output_path)
-            .unwrap();
+    fn write_number<N: SidPackedNumber>(&mut self, value: N) -> SidAssetSerializationResult<()> {
+        let bytes = value.to_bytes(self.endianness());
+        self.write_packed(&bytes)
     }
 
-    fn extension_compatible(extension: &str) -> bool;
+    /// Writes the UTF-8 bytes of `value`, prefixed by their length as a u16.
+    fn write_string(&mut self, value: &str) -> SidAssetSerializationResult<()> {
+        let length: u16 = value.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "String too long to be packed ({} bytes): {:?}",
+                value.len(),
+                value
+            ))
+        })?;
 
-    fn process_assets<P1: AsRef<Path>, P2: AsRef<Path>>(
-        assets_input_path: P1,
-        resources_output_path: P2,
-    ) {
-        let entries = match fs::read_dir(assets_input_path) {
-            Ok(entries) => entries,
-            Err(error) => {
-                sid_error!("Error retrieving directory entries: {error}");
-                return;
-            }
-        };
+        self.write_number(length)?;
+        self.write_packed(value.as_bytes())
+    }
+}
 
-        for entry in entries.flatten() {
-            if !Self::compatible(&entry) {
-                continue;
-            }
+/// Writes resources in the byte order of the target platform. Writes are
+/// buffered, so `finish` must be called once the resource is written: it also
+/// appends the checksum of everything written.
+pub struct SidResourceWriter<W: Write> {
+    inner: BufWriter<W>,
+    endianness: SidEndianness,
+    texture: SidTextureOptions,
+    checksum: Xxh3,
+}
+
+impl<W: Write> SidResourceWriter<W> {
+    pub fn new(inner: W, endianness: SidEndianness) -> Self {
+        Self::with_texture_options(inner, endianness, SidTextureOptions::new())
+    }
 
-            Self::process_asset(entry.path(), &resources_output_path);
+    pub fn with_texture_options(
+        inner: W,
+        endianness: SidEndianness,
+        texture: SidTextureOptions,
+    ) -> Self {
+        Self {
+            inner: BufWriter::new(inner),
+            endianness,
+            texture,
+            checksum: Xxh3::new(),
         }
     }
+
+    /// Writes the checksum, flushes the buffered writes, and returns the
+    /// underlying writer.
+    pub fn finish(mut self) -> SidAssetSerializationResult<W> {
+        let checksum = self.checksum.digest();
+        self.write_number(checksum)?;
+
+        self.inner
+            .into_inner()
+            .map_err(|error| SidAssetSerializationError::IO(error.error().to_string()))
+    }
 }
 
-impl SidAssetProcessor for SidSpriteSheetAsset {
-    fn extension_compatible(extension: &str) -> bool {
-        extension == "json"
+impl<W: Write> Write for SidResourceWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.checksum.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
     }
 }
 
-impl SidAssetProcessor for SidAnimationDefAsset {
-    fn extension_compatible(extension: &str) -> bool {
-        extension == "json"
+impl<W: Write> SidResourceWrite for SidResourceWriter<W> {
+    fn endianness(&self) -> SidEndianness {
+        self.endianness
+    }
+
+    fn texture_options(&self) -> SidTextureOptions {
+        self.texture
     }
 }
 
-impl SidAssetProcessor for SidAnimationAsset {
-    fn extension_compatible(extension: &str) -> bool {
-        extension == "json"
+impl SidSpriteSheetAsset {
+    pub(crate) fn texture_path<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<PathBuf> {
+        let mut texture_path = match in_path.as_ref().parent() {
+            Some(texture_path) => PathBuf::from(texture_path),
+            None => {
+                return Err(SidAssetSerializationError::IO(
+                    "Unable to retrieve texture path".to_string(),
+                ));
+            }
+        };
+
+        texture_path.push(&self.image_path);
+        Ok(texture_path)
     }
 }
 
-pub fn from_assets_to_resources<P: AsRef<Path>>(assets_input_path: P, resources_output_path: P) {
-    fs::create_dir_all(&resources_output_path).unwrap_or_else(|error| {
-        sid_error!(
-            "Unable to create folder at path {:?}: {}",
-            assets_input_path.as_ref(),
-            error
-        );
+fn open_texture(texture_path: &Path) -> SidAssetSerializationResult<image::DynamicImage> {
+    timings::measure(SidStage::Decode, || image::open(texture_path))
+        .map_err(|error| SidAssetSerializationError::IO(error.to_string()))
+}
 
-        return;
-    });
+/// Finds the palette asset named `name`, in the assets folder the asset at
+/// `in_path` belongs to. Returns the paths of its JSON file and image.
+fn find_palette(in_path: &Path, name: &str) -> SidAssetSerializationResult<(PathBuf, PathBuf)> {
+    let palettes_path = assets_folder(in_path)
+        .map(|assets_path| assets_path.join(SidPaletteAsset::namespace()))
+        .ok_or_else(|| {
+            SidAssetSerializationError::IO("Unable to retrieve palettes folder".to_string())
+        })?;
 
-    let entries = fs::read_dir(&assets_input_path)
-        .unwrap_or_else(|error| panic!("Failed to read directory: {error}"));
+    for path in SidPaletteAsset::compatible_entries(&palettes_path) {
+        let palette = SidPaletteAsset::load_asset(&path)?;
 
-    for entry in entries {
-        if let Err(error) = entry {
-            sid_error!("Error iterating over directory entry: {error}");
-            continue;
+        if palette.name == name {
+            let image_path = palette.image_path(&path)?;
+            return Ok((path, image_path));
         }
+    }
 
-        let path = match entry {
-            Ok(entry) => entry.path(),
-            Err(err) => {
-                sid_error!("Error while processing an entry: {err}");
-                continue;
-            }
-        };
+    Err(SidAssetSerializationError::Malformed(format!(
+        "Unknown palette {:?}",
+        name
+    )))
+}
 
-        let metadata = match fs::metadata(path.clone()) {
-            Ok(metadata) => metadata,
-            Err(err) => {
-                sid_error!("Error while processing an entry: {err}");
-                continue;
-            }
-        };
+/// Assets folder the asset at `in_path` belongs to, i.e. the parent of the
+/// namespace folder it is in, directly or in a subfolder. The closest folder
+/// holding a palettes folder wins, so that palettes are found from any depth.
+fn assets_folder(in_path: &Path) -> Option<&Path> {
+    in_path
+        .ancestors()
+        .skip(2)
+        .find(|folder| folder.join(SidPaletteAsset::namespace()).is_dir())
+        .or_else(|| in_path.parent().and_then(Path::parent))
+}
 
-        if !metadata.is_dir() {
-            sid_warning!("Ignoring entry (a folder is expected): {:?}", path.to_str());
-            continue;
+fn load_palette(in_path: &Path, name: &str) -> SidAssetSerializationResult<SidPalette> {
+    let (_, image_path) = find_palette(in_path, name)?;
+    SidPalette::from_image(sid::generate_palette_id(name), &open_texture(&image_path)?)
+}
+
+/// Files of the palette an image is mapped to, if any.
+fn palette_source_paths(
+    in_path: &Path,
+    name: Option<&str>,
+) -> SidAssetSerializationResult<Vec<PathBuf>> {
+    match name {
+        Some(name) => {
+            let (path, image_path) = find_palette(in_path, name)?;
+            Ok(vec![path, image_path])
         }
+        None => Ok(vec![]),
+    }
+}
 
-        let folder_name = match std::path::Path::new(&path).file_name() {
-            Some(folder_name) => folder_name,
-            None => {
-                sid_error!(
-                    "Unknown error while processing an entry: {:?}",
-                    path.to_str()
-                );
-                continue;
-            }
-        };
+/// Writes the texture block shared by sprite sheets and textures: size of the
+/// image, channel count, format, color space (i32), palette id (0 unless
+/// indexed), mip count (u8), then the width (u32), height (u32), size (u64) and
+/// pixels (or compressed blocks, or indices) of every mip level, starting with
+/// the base one. The base level is larger than the image when padded to a
+/// power-of-two size, the image being at its top-left corner.
+fn write_texture<W: SidResourceWrite>(
+    texture: image::DynamicImage,
+    source_color_space: SidColorSpace,
+    source_pixel_format: SidPixelFormat,
+    palette: Option<&SidPalette>,
+    out: &mut W,
+) -> SidAssetSerializationResult<()> {
+    let texture_options = out.texture_options();
 
-        match folder_name {
-            folder_name if SidSpriteSheetAsset::assets_folder(folder_name) => {
-                SidSpriteSheetAsset::process_assets(path, &resources_output_path);
-            }
-            folder_name if SidAnimationDefAsset::assets_folder(folder_name) => {
-                SidAnimationDefAsset::process_assets(path, &resources_output_path);
-            }
-            folder_name if SidAnimationAsset::assets_folder(folder_name) => {
-                SidAnimationAsset::process_assets(path, &resources_output_path);
-            }
-            _ => sid_warning!(
-                "Ignoring entry (unknown or unsupported namespace): {:?}",
-                path
-            ),
+    let pixel_format = texture_options.pixel_format.unwrap_or(source_pixel_format);
+
+    let mut texture = texture::convert_pixel_format(texture, pixel_format);
+    let (width, height) = texture.dimensions();
+    let channel_count = texture.color().channel_count();
+
+    let format = match texture.color() {
+        image::ColorType::L8 => sid_texture_format::SID_TEXTURE_FORMAT_L8,
+        image::ColorType::La8 => sid_texture_format::SID_TEXTURE_FORMAT_LA8,
+        image::ColorType::Rgb8 => sid_texture_format::SID_TEXTURE_FORMAT_RGB8,
+        image::ColorType::Rgba8 => sid_texture_format::SID_TEXTURE_FORMAT_RGBA8,
+        image::ColorType::Rgba16 => sid_texture_format::SID_TEXTURE_FORMAT_RGBA16,
+        format => {
+            return Err(SidAssetSerializationError::UnsupportedFormat(format!(
+                "Unknown or unsupported format: {:?}",
+                format
+            )))
         }
+    };
+
+    // Palette colors are matched as they are, in the color space of the image.
+    let color_space = match palette {
+        Some(_) => source_color_space,
+        None => texture_options.color_space.unwrap_or(source_color_space),
+    };
+
+    texture::convert_color_space(&mut texture, source_color_space, color_space);
+
+    if texture_options.power_of_two {
+        texture = texture::pad_to_power_of_two(texture);
     }
+
+    let mipmaps = match texture_options.mipmaps {
+        Some(filter) => texture::generate_mipmaps(&texture, filter),
+        None => vec![],
+    };
+
+    let levels: Vec<&image::DynamicImage> = std::iter::once(&texture).chain(&mipmaps).collect();
+
+    // Indexed textures are never block-compressed.
+    let (format, level_data): (_, Vec<Cow<[u8]>>) = match (palette, texture_options.encoding) {
+        (Some(palette), _) => (
+            sid_texture_format::SID_TEXTURE_FORMAT_INDEXED8,
+            levels
+                .iter()
+                .map(|level| Cow::from(palette.index_pixels(level)))
+                .collect(),
+        ),
+        (None, SidTextureEncoding::Raw) => (
+            format,
+            levels
+                .iter()
+                .map(|level| level_bytes(level, out.endianness()))
+                .collect(),
+        ),
+        (None, encoding) => {
+            let (format, level_data) = texture::encode_blocks(&levels, encoding, color_space)?;
+            (format, level_data.into_iter().map(Cow::from).collect())
+        }
+    };
+
+    let channel_count = if palette.is_some() { 1 } else { channel_count };
+    let palette_id = palette.map_or(0, |palette| palette.id);
+
+    let raw_format = format as i32;
+
+    let raw_color_space = match color_space {
+        SidColorSpace::Srgb => sid_color_space::SID_COLOR_SPACE_SRGB,
+        SidColorSpace::Linear => sid_color_space::SID_COLOR_SPACE_LINEAR,
+    } as i32;
+
+    out.write_number(width)?;
+    out.write_number(height)?;
+    out.write_number(channel_count)?;
+    out.write_number(raw_format)?;
+    out.write_number(raw_color_space)?;
+    out.write_number(palette_id)?;
+    out.write_number((mipmaps.len() + 1) as u8)?;
+
+    for (level, data) in levels.iter().zip(&level_data) {
+        let (level_width, level_height) = level.dimensions();
+
+        out.write_number(level_width)?;
+        out.write_number(level_height)?;
+        out.write_number(data.len() as sid::UIndex)?;
+        out.write_packed(data)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the texture block of the image at `texture_path`, its pixels of
+/// color `color_key` (or of the global one) made transparent, mapped to
+/// `palette` if any.
+fn write_texture_file<W: SidResourceWrite>(
+    texture_path: &Path,
+    source_color_space: SidColorSpace,
+    source_pixel_format: SidPixelFormat,
+    color_key: Option<[u8; 3]>,
+    palette: Option<&SidPalette>,
+    out: &mut W,
+) -> SidAssetSerializationResult<()> {
+    if let Some(max_size) = out.texture_options().max_size {
+        let (width, height) = image::image_dimensions(texture_path)
+            .map_err(|error| SidAssetSerializationError::IO(error.to_string()))?;
+
+        if width > max_size || height > max_size {
+            return Err(SidAssetSerializationError::UnsupportedFormat(format!(
+                "Image {:?} of {width}x{height} pixels is over the maximum texture size of {max_size}, split it into atlas pages",
+                texture_path
+            )));
+        }
+    }
+
+    let color_key = color_key.or(out.texture_options().color_key);
+
+    if palette.is_none()
+        && color_key.is_none()
+        && write_png_texture_rows(texture_path, source_color_space, source_pixel_format, out)?
+    {
+        return Ok(());
+    }
+
+    let mut texture = open_texture(texture_path)?;
+
+    if let Some(color_key) = color_key {
+        texture = texture::apply_color_key(texture, color_key);
+    }
+
+    write_texture(
+        texture,
+        source_color_space,
+        source_pixel_format,
+        palette,
+        out,
+    )
+}
+
+/// Writes the texture block of a PNG image row by row, so that large images
+/// are never decoded whole. Only images written as they are qualify: raw,
+/// without mip levels, and left in their pixel format and color space.
+/// Returns `false`, having written nothing, for the others.
+fn write_png_texture_rows<W: SidResourceWrite>(
+    texture_path: &Path,
+    source_color_space: SidColorSpace,
+    source_pixel_format: SidPixelFormat,
+    out: &mut W,
+) -> SidAssetSerializationResult<bool> {
+    let is_png = texture_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+
+    let texture_options = out.texture_options();
+
+    if !is_png
+        || texture_options.encoding != SidTextureEncoding::Raw
+        || texture_options.mipmaps.is_some()
+        || texture_options.power_of_two
+        || texture_options
+            .color_space
+            .is_some_and(|color_space| color_space != source_color_space)
+    {
+        return Ok(false);
+    }
+
+    let decode_error = |error: png::DecodingError| {
+        SidAssetSerializationError::IO(format!(
+            "Unable to decode PNG file {:?}: {error}",
+            texture_path
+        ))
+    };
+
+    let file = File::open(texture_path).map_err(|error| {
+        SidAssetSerializationError::IO(format!(
+            "Unable to open texture {:?}: {error}",
+            texture_path
+        ))
+    })?;
+
+    // Expands palettes, transparency chunks and low bit depths, the way the
+    // image crate does.
+    let mut decoder = png::Decoder::new(io::BufReader::new(file));
+    decoder.set_transformations(png::Transformations::EXPAND);
+
+    let mut reader =
+        timings::measure(SidStage::Decode, || decoder.read_info()).map_err(decode_error)?;
+
+    if reader.info().interlaced {
+        return Ok(false);
+    }
+
+    let (format, pixel_format, channel_count, is_16_bit) = match reader.output_color_type() {
+        (png::ColorType::Grayscale, png::BitDepth::Eight) => (
+            sid_texture_format::SID_TEXTURE_FORMAT_L8,
+            SidPixelFormat::L8,
+            1u8,
+            false,
+        ),
+        (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => (
+            sid_texture_format::SID_TEXTURE_FORMAT_LA8,
+            SidPixelFormat::La8,
+            2,
+            false,
+        ),
+        (png::ColorType::Rgb, png::BitDepth::Eight) => (
+            sid_texture_format::SID_TEXTURE_FORMAT_RGB8,
+            SidPixelFormat::Rgb8,
+            3,
+            false,
+        ),
+        (png::ColorType::Rgba, png::BitDepth::Eight) => (
+            sid_texture_format::SID_TEXTURE_FORMAT_RGBA8,
+            SidPixelFormat::Rgba8,
+            4,
+            false,
+        ),
+        (png::ColorType::Rgba, png::BitDepth::Sixteen) => (
+            sid_texture_format::SID_TEXTURE_FORMAT_RGBA16,
+            SidPixelFormat::Rgba16,
+            4,
+            true,
+        ),
+        // Converted from their layout, which needs the whole image.
+        _ => return Ok(false),
+    };
+
+    let target_pixel_format = texture_options.pixel_format.unwrap_or(source_pixel_format);
+
+    if target_pixel_format != SidPixelFormat::Source && target_pixel_format != pixel_format {
+        return Ok(false);
+    }
+
+    let (width, height) = (reader.info().width, reader.info().height);
+    let channel_size = if is_16_bit { 2 } else { 1 };
+    let size =
+        width as sid::UIndex * height as sid::UIndex * channel_count as sid::UIndex * channel_size;
+
+    let raw_color_space = match source_color_space {
+        SidColorSpace::Srgb => sid_color_space::SID_COLOR_SPACE_SRGB,
+        SidColorSpace::Linear => sid_color_space::SID_COLOR_SPACE_LINEAR,
+    } as i32;
+
+    out.write_number(width)?;
+    out.write_number(height)?;
+    out.write_number(channel_count)?;
+    out.write_number(format as i32)?;
+    out.write_number(raw_color_space)?;
+    out.write_number(0 as sid::SidPaletteId)?;
+    out.write_number(1u8)?;
+
+    out.write_number(width)?;
+    out.write_number(height)?;
+    out.write_number(size)?;
+
+    // PNG stores 16-bit channels big-endian.
+    let swap_bytes = is_16_bit && out.endianness() == SidEndianness::Little;
+    let mut swapped_row = vec![];
+
+    while let Some(row) =
+        timings::measure(SidStage::Decode, || reader.next_row()).map_err(decode_error)?
+    {
+        if swap_bytes {
+            swapped_row.clear();
+            swapped_row.extend(
+                row.data()
+                    .chunks_exact(2)
+                    .flat_map(|channel| [channel[1], channel[0]]),
+            );
+
+            out.write_packed(&swapped_row)?;
+        } else {
+            out.write_packed(row.data())?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Pixels of a raw level, with 16-bit channels in the byte order of the
+/// resource.
+fn level_bytes(level: &image::DynamicImage, endianness: SidEndianness) -> Cow<'_, [u8]> {
+    match level {
+        image::DynamicImage::ImageRgba16(buffer) => Cow::from(
+            buffer
+                .iter()
+                .flat_map(|channel| channel.to_bytes(endianness))
+                .collect::<Vec<u8>>(),
+        ),
+        _ => Cow::from(level.as_bytes()),
+    }
+}
+
+impl SerializeSidAsset for SidSpriteSheetAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_sprite_sheet_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let texture_path = self.texture_path(&in_path)?;
+        let mut paths = vec![in_path.as_ref().to_path_buf(), texture_path];
+        paths.extend(palette_source_paths(
+            in_path.as_ref(),
+            self.palette.as_deref(),
+        )?);
+
+        Ok(paths)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::SpriteSheet
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let texture_path = self.texture_path(&in_path)?;
+
+        let palette = match &self.palette {
+            Some(name) => Some(load_palette(in_path.as_ref(), name)?),
+            None => None,
+        };
+
+        out.write_number(id)?;
+        write_pivot(self.pivot.as_ref(), out)?;
+        write_texture_ref(self.normal_map.as_deref(), out)?;
+        write_texture_ref(self.emissive_map.as_deref(), out)?;
+        write_texture_file(
+            &texture_path,
+            self.color_space,
+            self.pixel_format,
+            self.color_key,
+            palette.as_ref(),
+            out,
+        )
+    }
+}
+
+/// Writes a presence flag followed by the coordinates of `pivot`, zeros if
+/// there is none, so that pivots always take the same size.
+fn write_pivot<W: SidResourceWrite>(
+    pivot: Option<&SidSlicePivot>,
+    out: &mut W,
+) -> SidAssetSerializationResult<()> {
+    let empty_pivot = SidSlicePivot::new();
+
+    out.write_packed(&[pivot.is_some() as u8])?;
+    out.write_number(pivot.unwrap_or(&empty_pivot).x)?;
+    out.write_number(pivot.unwrap_or(&empty_pivot).y)?;
+
+    Ok(())
+}
+
+/// Writes a presence flag followed by the id of the texture `name`, zero if
+/// there is none.
+fn write_texture_ref<W: SidResourceWrite>(
+    name: Option<&str>,
+    out: &mut W,
+) -> SidAssetSerializationResult<()> {
+    out.write_packed(&[name.is_some() as u8])?;
+    out.write_number(name.map(sid::generate_texture_id).unwrap_or_default())?;
+
+    Ok(())
+}
+
+impl SidTextureAsset {
+    pub(crate) fn texture_path<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<PathBuf> {
+        match in_path.as_ref().parent() {
+            Some(folder) => Ok(folder.join(&self.image_path)),
+            None => Err(SidAssetSerializationError::IO(
+                "Unable to retrieve texture path".to_string(),
+            )),
+        }
+    }
+}
+
+impl SerializeSidAsset for SidTextureAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_texture_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let texture_path = self.texture_path(&in_path)?;
+        let mut paths = vec![in_path.as_ref().to_path_buf(), texture_path];
+        paths.extend(palette_source_paths(
+            in_path.as_ref(),
+            self.palette.as_deref(),
+        )?);
+
+        Ok(paths)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Texture
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let texture_path = self.texture_path(&in_path)?;
+
+        let palette = match &self.palette {
+            Some(name) => Some(load_palette(in_path.as_ref(), name)?),
+            None => None,
+        };
+
+        out.write_number(id)?;
+        write_texture_file(
+            &texture_path,
+            self.color_space,
+            self.pixel_format,
+            self.color_key,
+            palette.as_ref(),
+            out,
+        )
+    }
+}
+
+impl SidPaletteAsset {
+    pub(crate) fn image_path<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<PathBuf> {
+        match in_path.as_ref().parent() {
+            Some(folder) => Ok(folder.join(&self.image_path)),
+            None => Err(SidAssetSerializationError::IO(
+                "Unable to retrieve palette image path".to_string(),
+            )),
+        }
+    }
+}
+
+impl SerializeSidAsset for SidPaletteAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_palette_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let image_path = self.image_path(&in_path)?;
+        Ok(vec![in_path.as_ref().to_path_buf(), image_path])
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Palette
+    }
+
+    /// Writes the id, the color count (u16), then every color as RGBA8.
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let image_path = self.image_path(in_path)?;
+        let palette = SidPalette::from_image(id, &open_texture(&image_path)?)?;
+
+        out.write_number(id)?;
+        out.write_number(palette.colors.len() as u16)?;
+
+        for color in &palette.colors {
+            out.write_packed(color)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidAnimationDefAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_animation_def_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::AnimationDef
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let sheet_id = sid::generate_sprite_sheet_id(&self.sheet_name);
+
+        // Frames covering the same region, e.g. held frames, are written once
+        // and referenced by index, along with the duration of each frame.
+        let mut regions = vec![];
+        let mut region_indices = HashMap::new();
+        let mut frame_regions = Vec::with_capacity(self.frames.len());
+
+        for frame in &self.frames {
+            let (offset_x, offset_y) = match &frame.trim {
+                Some(trim) => (trim.offset.x, trim.offset.y),
+                None => (0, 0),
+            };
+
+            let source_dims = frame.source_dims();
+
+            let region = (
+                frame.pos.x,
+                frame.pos.y,
+                frame.dims.width,
+                frame.dims.height,
+                offset_x,
+                offset_y,
+                source_dims.width,
+                source_dims.height,
+                frame.rotated,
+                frame.page,
+            );
+
+            let region_index = *region_indices.entry(region).or_insert_with(|| {
+                regions.push(region);
+                regions.len() - 1
+            });
+
+            frame_regions.push(region_index as sid::SidAnimationFrameIndex);
+        }
+
+        out.write_number(id)?;
+        out.write_number(sheet_id)?;
+        out.write_number(self.pages.len() as sid::SidAnimationPageIndex)?;
+
+        for page in &self.pages {
+            out.write_number(sid::generate_sprite_sheet_id(page))?;
+        }
+
+        out.write_number(self.frame_count)?;
+        out.write_number(regions.len() as sid::SidAnimationFrameIndex)?;
+
+        for (x, y, width, height, offset_x, offset_y, source_width, source_height, rotated, page) in
+            regions
+        {
+            out.write_number(x)?;
+            out.write_number(y)?;
+
+            out.write_number(width)?;
+            out.write_number(height)?;
+
+            out.write_number(offset_x)?;
+            out.write_number(offset_y)?;
+
+            out.write_number(source_width)?;
+            out.write_number(source_height)?;
+
+            out.write_packed(&[rotated as u8])?;
+            out.write_number(page)?;
+        }
+
+        for (frame, region_index) in self.frames.iter().zip(frame_regions) {
+            out.write_number(region_index)?;
+            out.write_number(frame.duration)?;
+            write_pivot(frame.pivot.as_ref(), out)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidAnimationAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_animation_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Animation
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let def_id = sid::generate_animation_def_id(&self.def_name);
+
+        out.write_number(id)?;
+        out.write_number(def_id)?;
+        out.write_number(self.offset)?;
+        out.write_number(self.length)?;
+        out.write_packed(&[self.direction as u8, self.looping as u8])?;
+        out.write_number(self.speed_scale)?;
+
+        let event_count: u16 = self.events.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many events in animation {:?}: {}",
+                self.name,
+                self.events.len()
+            ))
+        })?;
+
+        out.write_number(event_count)?;
+
+        for event in &self.events {
+            out.write_number(event.frame)?;
+            out.write_string(&event.name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidSliceAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_slice_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Slice
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let sheet_id = sid::generate_sprite_sheet_id(&self.sheet_name);
+
+        let key_count: u16 = self.keys.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!("Too many keys in slice {:?}", self.name))
+        })?;
+
+        out.write_number(id)?;
+        out.write_number(sheet_id)?;
+        out.write_number(key_count)?;
+
+        // Every key has the same size: missing centers and pivots are written
+        // as zeros, with a flag telling whether they are set.
+        for key in &self.keys {
+            let empty_rect = SidSliceRect::new();
+            let center = key.center.as_ref().unwrap_or(&empty_rect);
+            let empty_pivot = SidSlicePivot::new();
+            let pivot = key.pivot.as_ref().unwrap_or(&empty_pivot);
+            let flags = key.center.is_some() as u8 | (key.pivot.is_some() as u8) << 1;
+
+            out.write_number(key.frame)?;
+            out.write_packed(&[flags])?;
+
+            for rect in [&key.bounds, center] {
+                out.write_number(rect.x)?;
+                out.write_number(rect.y)?;
+                out.write_number(rect.width)?;
+                out.write_number(rect.height)?;
+            }
+
+            out.write_number(pivot.x)?;
+            out.write_number(pivot.y)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidNineSliceAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_nine_slice_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::NineSlice
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let sheet_id = sid::generate_sprite_sheet_id(&self.sheet_name);
+
+        out.write_number(id)?;
+        out.write_number(sheet_id)?;
+
+        out.write_number(self.region.x)?;
+        out.write_number(self.region.y)?;
+        out.write_number(self.region.width)?;
+        out.write_number(self.region.height)?;
+
+        out.write_number(self.insets.left)?;
+        out.write_number(self.insets.top)?;
+        out.write_number(self.insets.right)?;
+        out.write_number(self.insets.bottom)?;
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidCharacterAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_character_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Character
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let part_count: u16 = self.parts.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many parts in character {:?}",
+                self.name
+            ))
+        })?;
+
+        out.write_number(self.resource_id())?;
+        out.write_number(part_count)?;
+
+        for part in &self.parts {
+            let animation_count: u16 = part.animations.len().try_into().map_err(|_| {
+                SidAssetSerializationError::Malformed(format!(
+                    "Too many animations in part {:?} of character {:?}",
+                    part.name, self.name
+                ))
+            })?;
+
+            out.write_string(&part.name)?;
+            out.write_number(sid::generate_sprite_sheet_id(&part.sheet_name))?;
+            out.write_number(sid::generate_animation_def_id(&part.def_name))?;
+            out.write_number(animation_count)?;
+
+            for animation in &part.animations {
+                out.write_number(sid::generate_animation_id(animation))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub const KEY_CURVE_LINEAR: u8 = 0;
+pub const KEY_CURVE_STEPPED: u8 = 1;
+pub const KEY_CURVE_BEZIER: u8 = 2;
+
+fn write_bone_transform<W: SidResourceWrite>(
+    transform: &SidBoneTransform,
+    out: &mut W,
+) -> SidAssetSerializationResult<()> {
+    out.write_number(transform.x)?;
+    out.write_number(transform.y)?;
+    out.write_number(transform.rotation)?;
+    out.write_number(transform.scale_x)?;
+    out.write_number(transform.scale_y)?;
+
+    Ok(())
+}
+
+impl SidSkeletonAsset {
+    /// Index of the bone, slot or attachment `name` in `names`, as packed.
+    fn index_of<'a>(
+        &self,
+        mut names: impl Iterator<Item = &'a str>,
+        kind: &str,
+        name: &str,
+    ) -> SidAssetSerializationResult<u16> {
+        names
+            .position(|other| other == name)
+            .map(|index| index as u16)
+            .ok_or_else(|| {
+                SidAssetSerializationError::Malformed(format!(
+                    "Unknown {kind} {:?} in skeleton {:?}",
+                    name, self.name
+                ))
+            })
+    }
+}
+
+impl SerializeSidAsset for SidSkeletonAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_skeleton_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Skeleton
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let too_many = |kind: &str| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many {kind} in skeleton {:?}",
+                self.name
+            ))
+        };
+
+        let bone_count: u16 = self.bones.len().try_into().map_err(|_| too_many("bones"))?;
+        let slot_count: u16 = self.slots.len().try_into().map_err(|_| too_many("slots"))?;
+        let attachment_count: u16 = self
+            .attachments
+            .len()
+            .try_into()
+            .map_err(|_| too_many("attachments"))?;
+
+        out.write_number(self.resource_id())?;
+        out.write_number(bone_count)?;
+
+        for (i, bone) in self.bones.iter().enumerate() {
+            // Parents come first, so that the engine poses bones in order.
+            let parent = match &bone.parent {
+                Some(parent) => {
+                    let names = self.bones[..i].iter().map(|bone| bone.name.as_str());
+                    Some(self.index_of(names, "parent bone", parent)?)
+                }
+                None => None,
+            };
+
+            out.write_string(&bone.name)?;
+            out.write_number(parent.is_some() as u8)?;
+            out.write_number(parent.unwrap_or(0))?;
+            out.write_number(bone.length)?;
+            write_bone_transform(&bone.transform, out)?;
+        }
+
+        out.write_number(slot_count)?;
+
+        for slot in &self.slots {
+            let bone_names = self.bones.iter().map(|bone| bone.name.as_str());
+
+            out.write_string(&slot.name)?;
+            out.write_number(self.index_of(bone_names, "bone", &slot.bone)?)?;
+            out.write_number(slot.attachment.is_some() as u8)?;
+            out.write_string(slot.attachment.as_deref().unwrap_or_default())?;
+            out.write_packed(&slot.color)?;
+        }
+
+        out.write_number(attachment_count)?;
+
+        for attachment in &self.attachments {
+            let slot_names = self.slots.iter().map(|slot| slot.name.as_str());
+
+            out.write_number(self.index_of(slot_names, "slot", &attachment.slot)?)?;
+            out.write_string(&attachment.name)?;
+            out.write_string(&attachment.region)?;
+            write_bone_transform(&attachment.transform, out)?;
+            out.write_number(attachment.width)?;
+            out.write_number(attachment.height)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidSkeletalAnimationAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_skeletal_animation_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::SkeletalAnimation
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let too_many = |kind: &str| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many {kind} in skeletal animation {:?}",
+                self.name
+            ))
+        };
+
+        let bone_timeline_count: u16 = self
+            .bones
+            .len()
+            .try_into()
+            .map_err(|_| too_many("bone timelines"))?;
+        let slot_timeline_count: u16 = self
+            .slots
+            .len()
+            .try_into()
+            .map_err(|_| too_many("slot timelines"))?;
+
+        out.write_number(self.resource_id())?;
+        out.write_number(sid::generate_skeleton_id(&self.skeleton_name))?;
+        out.write_number(self.duration)?;
+        out.write_number(bone_timeline_count)?;
+
+        // Bones and slots are referenced by name, the skeleton not being read
+        // when packing its animations.
+        for timeline in &self.bones {
+            let key_count: u16 = timeline
+                .keys
+                .len()
+                .try_into()
+                .map_err(|_| too_many("keys"))?;
+
+            out.write_string(&timeline.bone)?;
+            out.write_number(timeline.property as u8)?;
+            out.write_number(key_count)?;
+
+            for key in &timeline.keys {
+                out.write_number(key.time)?;
+                out.write_number(key.values[0])?;
+                out.write_number(key.values[1])?;
+
+                match key.curve {
+                    SidKeyCurve::Linear => out.write_number(KEY_CURVE_LINEAR)?,
+                    SidKeyCurve::Stepped => out.write_number(KEY_CURVE_STEPPED)?,
+                    SidKeyCurve::Bezier { points } => {
+                        out.write_number(KEY_CURVE_BEZIER)?;
+
+                        for point in points {
+                            out.write_number(point)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        out.write_number(slot_timeline_count)?;
+
+        for timeline in &self.slots {
+            let key_count: u16 = timeline
+                .keys
+                .len()
+                .try_into()
+                .map_err(|_| too_many("keys"))?;
+
+            out.write_string(&timeline.slot)?;
+            out.write_number(key_count)?;
+
+            for key in &timeline.keys {
+                out.write_number(key.time)?;
+                out.write_number(key.attachment.is_some() as u8)?;
+                out.write_string(key.attachment.as_deref().unwrap_or_default())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SidAudioAsset {
+    pub(crate) fn audio_path<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<PathBuf> {
+        match in_path.as_ref().parent() {
+            Some(folder) => Ok(folder.join(&self.audio_path)),
+            None => Err(SidAssetSerializationError::IO(
+                "Unable to retrieve audio path".to_string(),
+            )),
+        }
+    }
+}
+
+/// Decodes a WAV file into interleaved 16-bit samples, whatever its sample
+/// format.
+fn decode_wav(path: &Path) -> SidAssetSerializationResult<(hound::WavSpec, Vec<i16>)> {
+    let decode_error = |error: hound::Error| {
+        SidAssetSerializationError::IO(format!("Unable to decode WAV file {:?}: {error}", path))
+    };
+
+    let mut reader = hound::WavReader::open(path).map_err(decode_error)?;
+    let spec = reader.spec();
+
+    let samples: Result<Vec<i16>, _> = match (spec.sample_format, spec.bits_per_sample) {
+        (hound::SampleFormat::Float, 32) => reader
+            .samples::<f32>()
+            .map(|sample| sample.map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+            .collect(),
+        (hound::SampleFormat::Int, bits @ 1..=16) => reader
+            .samples::<i32>()
+            .map(|sample| sample.map(|sample| (sample << (16 - bits)) as i16))
+            .collect(),
+        (hound::SampleFormat::Int, bits @ 17..=32) => reader
+            .samples::<i32>()
+            .map(|sample| sample.map(|sample| (sample >> (bits - 16)) as i16))
+            .collect(),
+        (format, bits) => {
+            return Err(SidAssetSerializationError::UnsupportedFormat(format!(
+                "Unsupported WAV sample format {:?} ({bits} bits) in {:?}",
+                format, path
+            )))
+        }
+    };
+
+    Ok((spec, samples.map_err(decode_error)?))
+}
+
+/// Reads the sample rate and channel count of an Ogg Vorbis stream.
+fn read_ogg_vorbis_header(
+    data: &[u8],
+    path: &Path,
+) -> SidAssetSerializationResult<(sid::SidAudioSampleRate, sid::SidAudioChannelCount)> {
+    let reader =
+        lewton::inside_ogg::OggStreamReader::new(std::io::Cursor::new(data)).map_err(|error| {
+            SidAssetSerializationError::UnsupportedFormat(format!(
+                "Unable to read Ogg Vorbis file {:?}: {error}",
+                path
+            ))
+        })?;
+
+    Ok((
+        reader.ident_hdr.audio_sample_rate,
+        reader.ident_hdr.audio_channels as sid::SidAudioChannelCount,
+    ))
+}
+
+impl SerializeSidAsset for SidAudioAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_audio_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let audio_path = self.audio_path(&in_path)?;
+        Ok(vec![in_path.as_ref().to_path_buf(), audio_path])
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Audio
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let audio_path = self.audio_path(in_path)?;
+
+        let extension = audio_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+
+        // WAV files are decoded so that the engine can play them as-is, while
+        // Ogg Vorbis streams are kept compressed and decoded at runtime.
+        let (sample_rate, channel_count, format, payload) = match extension.as_deref() {
+            Some("wav") => {
+                let (spec, samples) =
+                    timings::measure(SidStage::Decode, || decode_wav(&audio_path))?;
+                let endianness = out.endianness();
+
+                let payload = samples
+                    .iter()
+                    .flat_map(|sample| match endianness {
+                        SidEndianness::Little => sample.to_le_bytes(),
+                        SidEndianness::Big => sample.to_be_bytes(),
+                    })
+                    .collect();
+
+                (
+                    spec.sample_rate,
+                    spec.channels,
+                    sid_audio_format::SID_AUDIO_FORMAT_PCM16,
+                    payload,
+                )
+            }
+            Some("ogg") => {
+                let data = fs::read(&audio_path).map_err(|error| {
+                    SidAssetSerializationError::IO(format!(
+                        "Unable to read audio file {:?}: {error}",
+                        audio_path
+                    ))
+                })?;
+
+                let (sample_rate, channel_count) = read_ogg_vorbis_header(&data, &audio_path)?;
+
+                (
+                    sample_rate,
+                    channel_count,
+                    sid_audio_format::SID_AUDIO_FORMAT_OGG_VORBIS,
+                    data,
+                )
+            }
+            _ => {
+                return Err(SidAssetSerializationError::UnsupportedFormat(format!(
+                    "Unknown or unsupported audio file: {:?}",
+                    audio_path
+                )))
+            }
+        };
+
+        out.write_number(id)?;
+        out.write_number(sample_rate)?;
+        out.write_number(channel_count)?;
+        out.write_number(format as i32)?;
+        out.write_number(payload.len() as u64)?;
+        out.write_packed(&payload)?;
+
+        Ok(())
+    }
+}
+
+pub const MAX_FONT_ATLAS_SIZE: u32 = 4096;
+
+struct SidRasterizedGlyph {
+    character: char,
+    metrics: fontdue::Metrics,
+    bitmap: Vec<u8>,
+    x: u32,
+    y: u32,
+}
+
+impl SidFontAsset {
+    pub(crate) fn font_path<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<PathBuf> {
+        match in_path.as_ref().parent() {
+            Some(folder) => Ok(folder.join(&self.font_path)),
+            None => Err(SidAssetSerializationError::IO(
+                "Unable to retrieve font path".to_string(),
+            )),
+        }
+    }
+
+    fn character_set(&self) -> Vec<char> {
+        let mut characters: Vec<char> = if self.characters.is_empty() {
+            (' '..='~').collect()
+        } else {
+            self.characters.chars().collect()
+        };
+
+        characters.sort_unstable();
+        characters.dedup();
+        characters
+    }
+}
+
+/// Places every glyph in the smallest square power-of-two atlas fitting them
+/// all. Returns the atlas size.
+fn pack_glyphs(
+    glyphs: &mut [SidRasterizedGlyph],
+    padding: u32,
+    font_name: &str,
+) -> SidAssetSerializationResult<u32> {
+    let mut order: Vec<usize> = (0..glyphs.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(glyphs[i].metrics.height));
+
+    let mut size = 64;
+
+    'sizes: while size <= MAX_FONT_ATLAS_SIZE {
+        let mut packer = SidSkylinePacker::new(size, size);
+
+        for &i in &order {
+            let metrics = &glyphs[i].metrics;
+
+            // Blank glyphs, such as spaces, only have metrics.
+            if metrics.width == 0 || metrics.height == 0 {
+                continue;
+            }
+
+            let width = metrics.width as u32 + padding;
+            let height = metrics.height as u32 + padding;
+
+            match packer.insert(width, height) {
+                Some((x, y)) => {
+                    glyphs[i].x = x;
+                    glyphs[i].y = y;
+                }
+                None => {
+                    size *= 2;
+                    continue 'sizes;
+                }
+            }
+        }
+
+        return Ok(size);
+    }
+
+    Err(SidAssetSerializationError::Malformed(format!(
+        "Glyphs of font {:?} do not fit in a {MAX_FONT_ATLAS_SIZE}x{MAX_FONT_ATLAS_SIZE} atlas",
+        font_name
+    )))
+}
+
+impl SerializeSidAsset for SidFontAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_font_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let font_path = self.font_path(&in_path)?;
+        Ok(vec![in_path.as_ref().to_path_buf(), font_path])
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Font
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let font_path = self.font_path(in_path)?;
+
+        if self.size <= 0.0 {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Invalid size {} for font {:?}",
+                self.size, self.name
+            )));
+        }
+
+        let data = fs::read(&font_path).map_err(|error| {
+            SidAssetSerializationError::IO(format!(
+                "Unable to read font file {:?}: {error}",
+                font_path
+            ))
+        })?;
+
+        let font =
+            fontdue::Font::from_bytes(data, fontdue::FontSettings::default()).map_err(|error| {
+                SidAssetSerializationError::UnsupportedFormat(format!(
+                    "Unable to parse font file {:?}: {error}",
+                    font_path
+                ))
+            })?;
+
+        let line_metrics = font.horizontal_line_metrics(self.size).ok_or_else(|| {
+            SidAssetSerializationError::UnsupportedFormat(format!(
+                "No horizontal line metrics in font file {:?}",
+                font_path
+            ))
+        })?;
+
+        let mut glyphs = vec![];
+
+        for character in self.character_set() {
+            if font.lookup_glyph_index(character) == 0 {
+                sid_warning!(
+                    "Ignoring character {:?}, missing from font file {:?}",
+                    character,
+                    font_path
+                );
+                continue;
+            }
+
+            let (metrics, bitmap) = font.rasterize(character, self.size);
+
+            glyphs.push(SidRasterizedGlyph {
+                character,
+                metrics,
+                bitmap,
+                x: 0,
+                y: 0,
+            });
+        }
+
+        let atlas_size = pack_glyphs(&mut glyphs, self.padding, &self.name)?;
+        let mut texture = vec![0; atlas_size as usize * atlas_size as usize];
+
+        for glyph in &glyphs {
+            for (row, line) in glyph
+                .bitmap
+                .chunks_exact(glyph.metrics.width.max(1))
+                .enumerate()
+            {
+                let start = (glyph.y as usize + row) * atlas_size as usize + glyph.x as usize;
+                texture[start..start + line.len()].copy_from_slice(line);
+            }
+        }
+
+        let glyph_count: u32 = glyphs.len() as u32;
+
+        out.write_number(id)?;
+        out.write_number(self.size)?;
+        out.write_number(line_metrics.ascent)?;
+        out.write_number(line_metrics.descent)?;
+        out.write_number(line_metrics.line_gap)?;
+        out.write_number(glyph_count)?;
+
+        for glyph in &glyphs {
+            out.write_number(glyph.character as u32)?;
+            out.write_number(glyph.x as sid::SidFontGlyphCoord)?;
+            out.write_number(glyph.y as sid::SidFontGlyphCoord)?;
+            out.write_number(glyph.metrics.width as sid::SidFontGlyphDim)?;
+            out.write_number(glyph.metrics.height as sid::SidFontGlyphDim)?;
+            out.write_number(glyph.metrics.xmin)?;
+            out.write_number(glyph.metrics.ymin)?;
+            out.write_number(glyph.metrics.advance_width)?;
+        }
+
+        // Coverage of every glyph, as a single channel texture.
+        out.write_number(atlas_size)?;
+        out.write_number(atlas_size)?;
+        out.write_number(1u8)?;
+        out.write_number(sid_texture_format::SID_TEXTURE_FORMAT_R8 as i32)?;
+        out.write_number(texture.len() as u64)?;
+        out.write_packed(&texture)?;
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidBitmapFontAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_bitmap_font_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::BitmapFont
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let page_count: u16 = self.pages.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many pages in bitmap font {:?}",
+                self.name
+            ))
+        })?;
+
+        if let Some(glyph) = self
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.page as usize >= self.pages.len())
+        {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Glyph {} of bitmap font {:?} is on page {}, out of {page_count} page(s)",
+                glyph.character, self.name, glyph.page
+            )));
+        }
+
+        out.write_number(self.resource_id())?;
+        out.write_number(self.size)?;
+        out.write_number(self.line_height)?;
+        out.write_number(self.base)?;
+        out.write_number(page_count)?;
+
+        for page in &self.pages {
+            out.write_number(sid::generate_texture_id(page))?;
+        }
+
+        // Sorted, so that the engine can look glyphs up by binary search.
+        let mut glyphs: Vec<_> = self.glyphs.iter().collect();
+        glyphs.sort_by_key(|glyph| glyph.character);
+
+        out.write_number(glyphs.len() as u32)?;
+
+        for glyph in glyphs {
+            out.write_number(glyph.character)?;
+            out.write_number(glyph.x)?;
+            out.write_number(glyph.y)?;
+            out.write_number(glyph.width)?;
+            out.write_number(glyph.height)?;
+            out.write_number(glyph.x_offset)?;
+            out.write_number(glyph.y_offset)?;
+            out.write_number(glyph.x_advance)?;
+            out.write_number(glyph.page)?;
+        }
+
+        let mut kernings: Vec<_> = self.kernings.iter().collect();
+        kernings.sort_by_key(|kerning| (kerning.first, kerning.second));
+
+        out.write_number(kernings.len() as u32)?;
+
+        for kerning in kernings {
+            out.write_number(kerning.first)?;
+            out.write_number(kerning.second)?;
+            out.write_number(kerning.amount)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Bytes of a packed vertex: position, normal and texture coordinates.
+pub const MESH_VERTEX_STRIDE: u16 = 32;
+
+pub const MESH_TEXTURE_NONE: u8 = 0;
+pub const MESH_TEXTURE_TEXTURE: u8 = 1;
+pub const MESH_TEXTURE_SPRITE_SHEET: u8 = 2;
+
+impl SerializeSidAsset for SidMeshAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_mesh_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Mesh
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        if let Some(index) = self
+            .indices
+            .iter()
+            .find(|&&index| index as usize >= self.vertices.len())
+        {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Index {index} of mesh {:?} is out of its {} vertices",
+                self.name,
+                self.vertices.len()
+            )));
+        }
+
+        if let Some(submesh) = self.submeshes.iter().find(|submesh| {
+            submesh.first_index as usize + submesh.index_count as usize > self.indices.len()
+        }) {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Submesh at index {} of mesh {:?} is out of its {} indices",
+                submesh.first_index,
+                self.name,
+                self.indices.len()
+            )));
+        }
+
+        let submesh_count: u16 = self.submeshes.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many submeshes in mesh {:?}",
+                self.name
+            ))
+        })?;
+
+        out.write_number(self.resource_id())?;
+        out.write_number(self.vertices.len() as u32)?;
+        out.write_number(MESH_VERTEX_STRIDE)?;
+
+        for vertex in &self.vertices {
+            for value in vertex
+                .position
+                .iter()
+                .chain(&vertex.normal)
+                .chain(&vertex.uv)
+            {
+                out.write_number(*value)?;
+            }
+        }
+
+        // Indices are 16-bit whenever they fit, as most props are small.
+        let index_size: u8 = if self.vertices.len() <= u16::MAX as usize + 1 {
+            2
+        } else {
+            4
+        };
+
+        out.write_number(self.indices.len() as u32)?;
+        out.write_number(index_size)?;
+
+        for &index in &self.indices {
+            match index_size {
+                2 => out.write_number(index as u16)?,
+                _ => out.write_number(index)?,
+            }
+        }
+
+        out.write_number(submesh_count)?;
+
+        for submesh in &self.submeshes {
+            out.write_number(submesh.first_index)?;
+            out.write_number(submesh.index_count)?;
+
+            match &submesh.texture {
+                None => out.write_number(MESH_TEXTURE_NONE)?,
+                Some(SidMeshTexture::Texture(name)) => {
+                    out.write_number(MESH_TEXTURE_TEXTURE)?;
+                    out.write_number(sid::generate_texture_id(name))?;
+                }
+                Some(SidMeshTexture::SpriteSheet(name)) => {
+                    out.write_number(MESH_TEXTURE_SPRITE_SHEET)?;
+                    out.write_number(sid::generate_sprite_sheet_id(name))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub const CURVE_ENCODING_KEYED: u8 = 0;
+pub const CURVE_ENCODING_SAMPLED: u8 = 1;
+
+impl SerializeSidAsset for SidCurveAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_curve_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Curve
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Curve {:?} has no keys",
+                self.name
+            )));
+        };
+
+        let dimension = first.values.len();
+
+        if let Some(key) = self.keys.iter().find(|key| key.values.len() != dimension) {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Key at {} of curve {:?} has {} value(s) instead of {dimension}",
+                key.time,
+                self.name,
+                key.values.len()
+            )));
+        }
+
+        let dimension: u8 = dimension.try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many values per key in curve {:?}",
+                self.name
+            ))
+        })?;
+
+        out.write_number(self.resource_id())?;
+        out.write_number(self.interpolation as u8)?;
+        out.write_number(dimension)?;
+
+        let Some(sample_rate) = self.sample_rate else {
+            out.write_number(CURVE_ENCODING_KEYED)?;
+            out.write_number(self.keys.len() as u32)?;
+
+            for key in &self.keys {
+                out.write_number(key.time)?;
+
+                for &value in &key.values {
+                    out.write_number(value)?;
+                }
+            }
+
+            return Ok(());
+        };
+
+        if !sample_rate.is_finite() || sample_rate <= 0.0 {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Invalid sample rate {sample_rate} of curve {:?}",
+                self.name
+            )));
+        }
+
+        // Samples start at the first key, and the last one is taken at the
+        // last key or just after it.
+        let sample_count = ((last.time - first.time) * sample_rate).ceil() as u32 + 1;
+
+        out.write_number(CURVE_ENCODING_SAMPLED)?;
+        out.write_number(sample_rate)?;
+        out.write_number(first.time)?;
+        out.write_number(sample_count)?;
+
+        for i in 0..sample_count {
+            for value in self.evaluate(first.time + i as f32 / sample_rate) {
+                out.write_number(value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidTilesetAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_tileset_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Tileset
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let sheet_id = sid::generate_sprite_sheet_id(&self.sheet_name);
+
+        out.write_number(id)?;
+        out.write_number(sheet_id)?;
+        out.write_number(self.tile_width)?;
+        out.write_number(self.tile_height)?;
+        out.write_number(self.tile_count)?;
+        out.write_number(self.columns)?;
+        out.write_number(self.spacing)?;
+        out.write_number(self.margin)?;
+
+        Ok(())
+    }
+}
+
+pub const TILEMAP_LAYER_FLAG_VISIBLE: u8 = 1;
+
+impl SerializeSidAsset for SidTilemapAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_tilemap_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Tilemap
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let too_many = |what: &str| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many {what} in tilemap {:?}",
+                self.name
+            ))
+        };
+
+        let tileset_count: u16 = self
+            .tilesets
+            .len()
+            .try_into()
+            .map_err(|_| too_many("tilesets"))?;
+        let layer_count: u16 = self
+            .layers
+            .len()
+            .try_into()
+            .map_err(|_| too_many("layers"))?;
+
+        out.write_number(id)?;
+        out.write_number(self.width)?;
+        out.write_number(self.height)?;
+        out.write_number(self.tile_width)?;
+        out.write_number(self.tile_height)?;
+        out.write_number(tileset_count)?;
+
+        for tileset in &self.tilesets {
+            out.write_number(tileset.first_gid)?;
+            out.write_number(sid::generate_tileset_id(&tileset.tileset_name))?;
+        }
+
+        out.write_number(layer_count)?;
+        let tile_count = self.width as usize * self.height as usize;
+
+        // Layer names are not packed, layers keep their order instead. Tiles
+        // take 2 bytes when every id of the layer fits, 4 otherwise.
+        for layer in &self.layers {
+            if layer.tiles.len() != tile_count {
+                return Err(SidAssetSerializationError::Malformed(format!(
+                    "Layer {:?} of tilemap {:?} has {} tile(s), expected {tile_count}",
+                    layer.name,
+                    self.name,
+                    layer.tiles.len()
+                )));
+            }
+
+            let flags = if layer.visible {
+                TILEMAP_LAYER_FLAG_VISIBLE
+            } else {
+                0
+            };
+
+            let is_compact = layer.tiles.iter().all(|&tile| tile <= u16::MAX as u32);
+
+            out.write_packed(&[flags])?;
+            out.write_packed(&[if is_compact { 2 } else { 4 }])?;
+
+            for &tile in &layer.tiles {
+                if is_compact {
+                    out.write_number(tile as u16)?;
+                } else {
+                    out.write_number(tile)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SidShaderAsset {
+    fn stage_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<[(sid_shader_stage, PathBuf); 2]> {
+        match in_path.as_ref().parent() {
+            Some(folder) => Ok([
+                (
+                    sid_shader_stage::SID_SHADER_STAGE_VERTEX,
+                    folder.join(&self.vertex_path),
+                ),
+                (
+                    sid_shader_stage::SID_SHADER_STAGE_FRAGMENT,
+                    folder.join(&self.fragment_path),
+                ),
+            ]),
+            None => Err(SidAssetSerializationError::IO(
+                "Unable to retrieve shader paths".to_string(),
+            )),
+        }
+    }
+}
+
+/// Parses and validates a GLSL stage, so that errors point at the source file.
+fn compile_glsl(
+    source: &str,
+    stage: naga::ShaderStage,
+    path: &Path,
+) -> SidAssetSerializationResult<(naga::Module, naga::valid::ModuleInfo)> {
+    let path_name = path.to_string_lossy();
+    let invalid_shader = |error: String| {
+        SidAssetSerializationError::Malformed(format!("Invalid shader {:?}:\n{error}", path))
+    };
+
+    let module = naga::front::glsl::Frontend::default()
+        .parse(&naga::front::glsl::Options::from(stage), source)
+        .map_err(|error| invalid_shader(error.emit_to_string_with_path(source, &path_name)))?;
+
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|error| invalid_shader(error.emit_to_string_with_path(source, &path_name)))?;
+
+    Ok((module, info))
+}
+
+impl SerializeSidAsset for SidShaderAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_shader_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let mut paths = vec![in_path.as_ref().to_path_buf()];
+        paths.extend(
+            self.stage_paths(&in_path)?
+                .into_iter()
+                .map(|(_, path)| path),
+        );
+        Ok(paths)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Shader
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let stages = self.stage_paths(in_path)?;
+
+        let format = match self.target {
+            SidShaderTarget::Glsl => sid_shader_format::SID_SHADER_FORMAT_GLSL,
+            SidShaderTarget::Spirv => sid_shader_format::SID_SHADER_FORMAT_SPIRV,
+        };
+
+        out.write_number(id)?;
+        out.write_number(format as i32)?;
+        out.write_number(stages.len() as u16)?;
+
+        for (stage, path) in stages {
+            let source = fs::read_to_string(&path).map_err(|error| {
+                SidAssetSerializationError::IO(format!(
+                    "Unable to read shader file {:?}: {error}",
+                    path
+                ))
+            })?;
+
+            let naga_stage = match stage {
+                sid_shader_stage::SID_SHADER_STAGE_FRAGMENT => naga::ShaderStage::Fragment,
+                _ => naga::ShaderStage::Vertex,
+            };
+
+            out.write_number(stage as i32)?;
+
+            match self.target {
+                SidShaderTarget::Glsl => {
+                    if self.validate {
+                        compile_glsl(&source, naga_stage, &path)?;
+                    }
+
+                    out.write_number(source.len() as u64)?;
+                    out.write_packed(source.as_bytes())?;
+                }
+                SidShaderTarget::Spirv => {
+                    let (module, info) = compile_glsl(&source, naga_stage, &path)?;
+
+                    let pipeline_options = naga::back::spv::PipelineOptions {
+                        shader_stage: naga_stage,
+                        entry_point: "main".to_string(),
+                    };
+
+                    let words = naga::back::spv::write_vec(
+                        &module,
+                        &info,
+                        &naga::back::spv::Options::default(),
+                        Some(&pipeline_options),
+                    )
+                    .map_err(|error| {
+                        SidAssetSerializationError::Malformed(format!(
+                            "Unable to compile shader {:?} to SPIR-V: {error}",
+                            path
+                        ))
+                    })?;
+
+                    // SPIR-V is a stream of words, which follow the byte order of
+                    // the target like any other number.
+                    out.write_number((words.len() * 4) as u64)?;
+
+                    for word in words {
+                        out.write_number(word)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidMaterialAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_material_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Material
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let texture_count: u16 = self.textures.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many textures in material {:?}",
+                self.name
+            ))
+        })?;
+
+        let uniform_count: u16 = self.uniforms.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many uniforms in material {:?}",
+                self.name
+            ))
+        })?;
+
+        out.write_number(self.resource_id())?;
+        out.write_number(sid::generate_shader_id(&self.shader_name))?;
+        out.write_number(self.blend_mode as u8)?;
+        out.write_number(texture_count)?;
+
+        for (sampler, texture) in &self.textures {
+            out.write_string(sampler)?;
+            out.write_number(sid::generate_texture_id(texture))?;
+        }
+
+        out.write_number(uniform_count)?;
+
+        for (uniform, value) in &self.uniforms {
+            let components = value.components();
+
+            out.write_string(uniform)?;
+            out.write_number(components.len() as u8)?;
+
+            for &component in components {
+                out.write_number(component)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SidBlobAsset {
+    pub(crate) fn blob_path<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<PathBuf> {
+        match in_path.as_ref().parent() {
+            Some(folder) => Ok(folder.join(&self.blob_path)),
+            None => Err(SidAssetSerializationError::IO(
+                "Unable to retrieve blob path".to_string(),
+            )),
+        }
+    }
+}
+
+impl SerializeSidAsset for SidBlobAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_blob_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let blob_path = self.blob_path(&in_path)?;
+        Ok(vec![in_path.as_ref().to_path_buf(), blob_path])
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Blob
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let blob_path = self.blob_path(in_path)?;
+
+        let data = fs::read(&blob_path).map_err(|error| {
+            SidAssetSerializationError::IO(format!(
+                "Unable to read blob file {:?}: {error}",
+                blob_path
+            ))
+        })?;
+
+        // The data is opaque, so it keeps its byte order whatever the target.
+        out.write_number(id)?;
+        out.write_number(data.len() as u64)?;
+        out.write_packed(&data)?;
+
+        Ok(())
+    }
+}
+
+/// Writes the components of a prefab, or the ones overriding them.
+fn write_prefab_components<W: SidResourceWrite>(
+    components: &[SidPrefabComponentAsset],
+    owner: &str,
+    out: &mut W,
+) -> SidAssetSerializationResult<()> {
+    let component_count: u16 = components.len().try_into().map_err(|_| {
+        SidAssetSerializationError::Malformed(format!("Too many components in {owner}"))
+    })?;
+
+    out.write_number(component_count)?;
+
+    for component in components {
+        match component {
+            SidPrefabComponentAsset::Sprite { sheet_name } => {
+                out.write_number(
+                    sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_SPRITE as i32,
+                )?;
+                out.write_number(sid::generate_sprite_sheet_id(sheet_name))?;
+            }
+            SidPrefabComponentAsset::Animation { animation_name } => {
+                out.write_number(
+                    sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_ANIMATION as i32,
+                )?;
+                out.write_number(sid::generate_animation_id(animation_name))?;
+            }
+            SidPrefabComponentAsset::Collider {
+                x,
+                y,
+                width,
+                height,
+                is_trigger,
+            } => {
+                out.write_number(
+                    sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_COLLIDER as i32,
+                )?;
+                out.write_number(*x)?;
+                out.write_number(*y)?;
+                out.write_number(*width)?;
+                out.write_number(*height)?;
+                out.write_number(*is_trigger as u8)?;
+            }
+            SidPrefabComponentAsset::Custom { values } => {
+                let value_count: u16 = values.len().try_into().map_err(|_| {
+                    SidAssetSerializationError::Malformed(format!(
+                        "Too many custom values in {owner}"
+                    ))
+                })?;
+
+                out.write_number(
+                    sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_CUSTOM as i32,
+                )?;
+                out.write_number(value_count)?;
+
+                for (key, value) in values {
+                    out.write_string(key)?;
+                    out.write_string(value)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl SerializeSidAsset for SidPrefabAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_prefab_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Prefab
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        out.write_number(self.resource_id())?;
+        write_prefab_components(&self.components, &format!("prefab {:?}", self.name), out)?;
+
+        Ok(())
+    }
+}
+
+impl SerializeSidAsset for SidSceneAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_scene_id(&self.name)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::Scene
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        _: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let entity_count: u32 = self.entities.len().try_into().map_err(|_| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many entities in scene {:?}",
+                self.name
+            ))
+        })?;
+
+        out.write_number(self.resource_id())?;
+        out.write_number(entity_count)?;
+
+        for (index, entity) in self.entities.iter().enumerate() {
+            out.write_number(entity.name.is_some() as u8)?;
+            out.write_string(entity.name.as_deref().unwrap_or_default())?;
+            out.write_number(sid::generate_prefab_id(&entity.prefab_name))?;
+            write_bone_transform(&entity.transform, out)?;
+            out.write_number(entity.layer)?;
+            write_prefab_components(
+                &entity.overrides,
+                &format!("entity {index} of scene {:?}", self.name),
+                out,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SidStringTableAsset {
+    /// Reads the strings of every locale, checking that they define the same
+    /// keys.
+    pub(crate) fn load_locales<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<BTreeMap<String, SidLocaleStrings>> {
+        let folder = in_path.as_ref().parent().ok_or_else(|| {
+            SidAssetSerializationError::IO("Unable to retrieve string table path".to_string())
+        })?;
+
+        if self.locales.is_empty() {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "String table {:?} has no locale",
+                self.name
+            )));
+        }
+
+        let locales = self
+            .locales
+            .iter()
+            .map(|(locale, path)| {
+                string_table::read_locale_strings(&folder.join(path))
+                    .map(|strings| (locale.clone(), strings))
+            })
+            .collect::<SidAssetSerializationResult<_>>()?;
+
+        string_table::check_key_sets(&self.name, &locales)?;
+        Ok(locales)
+    }
+}
+
+impl SerializeSidAsset for SidStringTableAsset {
+    fn resource_id(&self) -> sid::SidStringId {
+        sid::generate_string_table_id(&self.name)
+    }
+
+    fn source_paths<P: AsRef<Path>>(
+        &self,
+        in_path: P,
+    ) -> SidAssetSerializationResult<Vec<PathBuf>> {
+        let in_path = in_path.as_ref();
+        let folder = in_path.parent().unwrap_or(Path::new(""));
+
+        let mut paths = vec![in_path.to_path_buf()];
+        paths.extend(self.locales.values().map(|path| folder.join(path)));
+        Ok(paths)
+    }
+
+    fn resource_type(&self) -> SidResourceType {
+        SidResourceType::StringTable
+    }
+
+    fn serialize_payload<P: AsRef<Path>, W: SidResourceWrite>(
+        &self,
+        in_path: P,
+        out: &mut W,
+    ) -> SidAssetSerializationResult<()> {
+        let id = self.resource_id();
+        let locales = self.load_locales(in_path)?;
+
+        // Every locale has the same keys, which are sorted by hash so that the
+        // engine can binary search them.
+        let mut keys: Vec<_> = locales
+            .values()
+            .next()
+            .map(|strings| {
+                strings
+                    .keys()
+                    .map(|key| (sid::generate_string_key_id(key), key))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        keys.sort();
+
+        if let Some(pair) = keys.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Keys {:?} and {:?} of string table {:?} have the same id {}",
+                pair[0].1, pair[1].1, self.name, pair[0].0
+            )));
+        }
+
+        let too_many = |what: &str| {
+            SidAssetSerializationError::Malformed(format!(
+                "Too many {what} in string table {:?}",
+                self.name
+            ))
+        };
+
+        let locale_count: u16 = locales.len().try_into().map_err(|_| too_many("locales"))?;
+        let key_count: u32 = keys.len().try_into().map_err(|_| too_many("keys"))?;
+
+        out.write_number(id)?;
+        out.write_number(locale_count)?;
+        out.write_number(key_count)?;
+
+        for (key_id, _) in &keys {
+            out.write_number(*key_id)?;
+        }
+
+        // Strings are null-terminated, and found through their offset in the
+        // data of their locale.
+        for (locale, strings) in &locales {
+            let mut offsets = Vec::with_capacity(keys.len());
+            let mut data = vec![];
+
+            for (_, key) in &keys {
+                offsets.push(data.len());
+                data.extend_from_slice(strings[*key].as_bytes());
+                data.push(0);
+            }
+
+            let data_size: u32 = data.len().try_into().map_err(|_| too_many("strings"))?;
+
+            out.write_string(locale)?;
+
+            for offset in offsets {
+                out.write_number(offset as u32)?;
+            }
+
+            out.write_number(data_size)?;
+            out.write_packed(&data)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads, checks and packs the assets of a namespace folder. Implementing
+/// `extension_compatible` is enough, other types then register their
+/// processor with `registry::register_processor`.
+pub trait SidAssetProcessor: SidPackedAsset
+where
+    for<'de> Self: Deserialize<'de>,
+    Self: Sized + SidPackedAsset + SerializeSidAsset + SidAssetSchema,
+{
+    /// Whether the file at `path` may be an asset of this type.
+    fn compatible(path: &Path) -> bool {
+        let extension = match path.extension() {
+            Some(extension) => extension,
+            None => {
+                return false;
+            }
+        };
+
+        let extension = match extension.to_str() {
+            Some(extension) => extension,
+            None => {
+                return false;
+            }
+        };
+
+        Self::extension_compatible(extension)
+    }
+
+    fn load_asset<P: AsRef<Path>>(asset_input_path: P) -> SidAssetSerializationResult<Self> {
+        let asset_input_path = asset_input_path.as_ref();
+
+        let file = File::open(asset_input_path).map_err(|error| {
+            SidAssetSerializationError::IO(format!(
+                "Unable to open asset {:?}: {error}",
+                asset_input_path
+            ))
+        })?;
+
+        serde_json::from_reader(file).map_err(|error| {
+            SidAssetSerializationError::Malformed(format!(
+                "Unable to parse asset {:?}: {error}",
+                asset_input_path
+            ))
+        })
+    }
+
+    /// Assets of the namespace folder `assets_input_path`, and of its
+    /// subfolders. Links are only followed if `walk::follows_symlinks()`, and
+    /// the entries matching a `.sidignore` file are skipped.
+    fn compatible_entries<P: AsRef<Path>>(assets_input_path: P) -> Vec<PathBuf> {
+        let mut paths = vec![];
+        let mut folders = vec![assets_input_path.as_ref().to_path_buf()];
+        let mut visited_folders = SidVisitedFolders::new();
+        let mut ignore_rules = SidIgnoreRules::for_root(assets_input_path.as_ref());
+
+        while let Some(folder) = folders.pop() {
+            if !visited_folders.visit(&folder) {
+                continue;
+            }
+
+            ignore_rules.load(&folder);
+
+            let entries = match fs::read_dir(&folder) {
+                Ok(entries) => entries,
+                Err(error) => {
+                    sid_error!("Error retrieving directory entries: {error}");
+                    continue;
+                }
+            };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+
+                match walk::entry_kind(&entry) {
+                    Some(SidEntryKind::Folder) if !ignore_rules.is_ignored(&path, true) => {
+                        folders.push(path)
+                    }
+                    Some(SidEntryKind::File)
+                        if Self::compatible(&path) && !ignore_rules.is_ignored(&path, false) =>
+                    {
+                        paths.push(path)
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        paths.sort();
+        paths
+    }
+
+    fn encode_resource<P: AsRef<Path>>(
+        asset: &Self,
+        asset_input_path: P,
+        settings: SidProcessSettings,
+    ) -> SidAssetSerializationResult<Vec<u8>> {
+        timings::measure(SidStage::Encode, || {
+            let mut out = SidResourceWriter::with_texture_options(
+                vec![],
+                settings.endianness,
+                settings.texture,
+            );
+            asset.serialize_resource(asset_input_path, &mut out)?;
+
+            compression::compress(&out.finish()?, settings.compression)
+                .map_err(|error| SidAssetSerializationError::IO(error.to_string()))
+        })
+    }
+
+    fn process_asset<P: AsRef<Path>>(
+        asset_input_path: P,
+        settings: SidProcessSettings,
+    ) -> SidAssetSerializationResult<SidProcessedAsset> {
+        // Assets are packed on worker threads, which do not share the scope of
+        // the pack.
+        let _backend = sid::scope_backend(settings.backend);
+        let asset_input_path = asset_input_path.as_ref();
+        let asset = timings::measure(SidStage::Parse, || Self::load_asset(asset_input_path))?;
+
+        let previous_cache = match settings.previous_cache {
+            Some(previous_cache) => previous_cache,
+            None => {
+                let data = Self::encode_resource(&asset, asset_input_path, settings)?;
+
+                let manifest_entry = Self::manifest_entry(
+                    &asset,
+                    asset_input_path,
+                    data.len() as u64,
+                    xxh3_64(&data),
+                );
+
+                return Ok(SidProcessedAsset {
+                    manifest_entry,
+                    cache_entry: None,
+                    pak_entry: Some(SidPakEntry {
+                        id: asset.resource_id(),
+                        resource_type: asset.resource_type(),
+                        data,
+                    }),
+                    skipped: false,
+                });
+            }
+        };
+
+        let source_paths = asset.source_paths(asset_input_path)?;
+        let id_path = asset.resource_path(settings.resources_output_path);
+
+        // Content-hash names are only known once written, the cache keeps them.
+        let resource_path = match previous_cache.file_name(asset_input_path) {
+            Some(file_name) if settings.content_hash_names => {
+                settings.resources_output_path.join(file_name)
+            }
+            _ => id_path.clone(),
+        };
+
+        let (mut cache_entry, fresh) = previous_cache
+            .check(
+                asset_input_path,
+                &source_paths,
+                &resource_path,
+                asset.resource_id(),
+            )
+            .map_err(|error| SidAssetSerializationError::IO(error.to_string()))?;
+
+        if !fresh {
+            if settings.compression == SidCompression::None {
+                asset.write_resource(
+                    asset_input_path,
+                    settings.resources_output_path,
+                    settings.endianness,
+                    settings.texture,
+                )?;
+            } else {
+                let data = Self::encode_resource(&asset, asset_input_path, settings)?;
+
+                timings::measure(SidStage::Write, || fs::write(&id_path, data))
+                    .map_err(|error| SidAssetSerializationError::IO(error.to_string()))?;
+            }
+        }
+
+        let resource_path = if fresh { resource_path } else { id_path };
+
+        let io_error = |error: std::io::Error| {
+            SidAssetSerializationError::IO(format!(
+                "Unable to read resource {:?}: {error}",
+                resource_path
+            ))
+        };
+
+        let size = fs::metadata(&resource_path).map_err(io_error)?.len();
+        let hash = cache::hash_file(&resource_path).map_err(io_error)?;
+        let mut manifest_entry = Self::manifest_entry(&asset, asset_input_path, size, hash);
+
+        if settings.content_hash_names {
+            let file_name = hashed_resource_file_name(asset.resource_id(), hash);
+            let hashed_path = settings.resources_output_path.join(&file_name);
+
+            if resource_path != hashed_path {
+                fs::rename(&resource_path, &hashed_path).map_err(|error| {
+                    SidAssetSerializationError::IO(format!(
+                        "Unable to rename resource {:?} to {:?}: {error}",
+                        resource_path, hashed_path
+                    ))
+                })?;
+            }
+
+            cache_entry.file_name = Some(file_name.clone());
+            manifest_entry.file_name = Some(file_name);
+        }
+
+        Ok(SidProcessedAsset {
+            manifest_entry,
+            cache_entry: Some(cache_entry),
+            pak_entry: None,
+            skipped: fresh,
+        })
+    }
+
+    fn manifest_entry(
+        asset: &Self,
+        asset_input_path: &Path,
+        size: u64,
+        hash: u64,
+    ) -> SidManifestEntry {
+        SidManifestEntry {
+            id: asset.resource_id(),
+            name: asset.name().to_string(),
+            resource_type: asset.resource_type(),
+            size,
+            source_path: asset_input_path.to_path_buf(),
+            hash: manifest::format_hash(hash),
+            file_name: None,
+        }
+    }
+
+    fn extension_compatible(extension: &str) -> bool;
+
+    fn process_assets<P: AsRef<Path>>(assets_input_path: P, context: &mut SidPackContext) {
+        let settings = context.settings;
+        let progress = &context.progress;
+
+        let excluded_paths = &context.excluded_paths;
+
+        let results: Vec<_> = Self::compatible_entries(assets_input_path)
+            .into_par_iter()
+            .filter(|path| !excluded_paths.contains(path))
+            .map(|path| {
+                let start = Instant::now();
+
+                if settings.timings {
+                    timings::start();
+                }
+
+                // A panic, e.g. in a decoding library, only fails its asset.
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    Self::process_asset(&path, settings.for_asset(&path))
+                }))
+                .unwrap_or_else(|payload| {
+                    Err(SidAssetSerializationError::Malformed(format!(
+                        "Packing panicked: {}",
+                        panic_message(payload.as_ref())
+                    )))
+                });
+
+                let stage_timings = timings::finish();
+                progress.inc(&path);
+                (path, result, start.elapsed(), stage_timings)
+            })
+            .collect();
+
+        for (path, result, duration, stage_timings) in results {
+            match result {
+                Ok(processed) => {
+                    let status = if processed.skipped {
+                        context.skipped_count += 1;
+                        SidAssetStatus::Skipped
+                    } else {
+                        SidAssetStatus::Ok
+                    };
+
+                    let mut report = SidAssetReport::new(path.clone(), status, duration);
+                    report.ids.push(processed.manifest_entry.id);
+                    report.timings = stage_timings;
+
+                    if !processed.skipped {
+                        report.bytes_written = processed.manifest_entry.size;
+                    }
+
+                    context.reports.push(report);
+                    context.manifest.entries.push(processed.manifest_entry);
+
+                    if let Some(cache_entry) = processed.cache_entry {
+                        context.cache.entries.insert(path, cache_entry);
+                    }
+
+                    if let (Some(pak), Some(pak_entry)) = (&mut context.pak, processed.pak_entry) {
+                        pak.add(pak_entry);
+                    }
+                }
+                Err(error) => {
+                    let mut report =
+                        SidAssetReport::new(path.clone(), SidAssetStatus::Failed, duration);
+                    report.error = Some(error.to_string());
+                    report.timings = stage_timings;
+                    context.reports.push(report);
+
+                    context.failures.push(SidAssetFailure {
+                        path,
+                        error: error.into(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn validate_assets<P: AsRef<Path>>(assets_input_path: P, context: &mut SidValidationContext) {
+        for path in Self::compatible_entries(assets_input_path) {
+            let asset = match Self::load_asset(&path) {
+                Ok(asset) => asset,
+                Err(error) => {
+                    context.failures.push(SidAssetFailure {
+                        path,
+                        error: error.into(),
+                    });
+                    continue;
+                }
+            };
+
+            for violation in asset.violations(&path) {
+                context.failures.push(SidAssetFailure {
+                    path: path.clone(),
+                    error: SidToolError::Malformed(violation),
+                });
+            }
+
+            context.graph.add(SidAssetEntry {
+                namespace: Self::namespace(),
+                name: asset.name().to_string(),
+                id: asset.resource_id(),
+                references: asset.references(),
+                path,
+            });
+        }
+    }
+
+    fn list_assets<P: AsRef<Path>>(
+        assets_input_path: P,
+        entries: &mut Vec<SidAssetEntry>,
+        failures: &mut Vec<SidAssetFailure>,
+    ) {
+        for path in Self::compatible_entries(assets_input_path) {
+            let asset = match Self::load_asset(&path) {
+                Ok(asset) => asset,
+                Err(error) => {
+                    failures.push(SidAssetFailure {
+                        path,
+                        error: error.into(),
+                    });
+                    continue;
+                }
+            };
+
+            entries.push(SidAssetEntry {
+                namespace: Self::namespace(),
+                name: asset.name().to_string(),
+                id: asset.resource_id(),
+                references: asset.references(),
+                path,
+            });
+        }
+    }
+}
+
+impl SidAssetProcessor for SidSpriteSheetAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidAnimationDefAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidAnimationAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidSliceAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidAudioAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidFontAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidBitmapFontAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidMeshAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidCurveAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidTilesetAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidTilemapAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidShaderAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidMaterialAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidBlobAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidPrefabAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidSceneAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidTextureAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidPaletteAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidStringTableAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidNineSliceAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidCharacterAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidSkeletonAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+impl SidAssetProcessor for SidSkeletalAnimationAsset {
+    fn extension_compatible(extension: &str) -> bool {
+        extension == "json"
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown error"
+    }
+}
+
+/// Name of a loose resource packed with content-hash names.
+pub fn hashed_resource_file_name(id: sid::SidStringId, hash: u64) -> String {
+    format!("{id}_{}", manifest::format_hash(hash))
+}
+
+/// Id of the loose resource named `file_name`, either `<id>` or
+/// `<id>_<hash>`, if it is one.
+pub fn resource_file_id(file_name: &str) -> Option<sid::SidStringId> {
+    let id = match file_name.split_once('_') {
+        Some((id, hash))
+            if hash.len() == 16 && hash.bytes().all(|byte| byte.is_ascii_hexdigit()) =>
+        {
+            id
+        }
+        Some(_) => return None,
+        None => file_name,
+    };
+
+    id.parse().ok()
+}
+
+/// Calls `callback` with every namespace folder of `assets_input_path` and the
+/// processor registered for it.
+fn for_each_processor_folder<P, F>(assets_input_path: P, mut callback: F) -> SidToolResult<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(&dyn SidDynAssetProcessor, PathBuf),
+{
+    let processors = registry::processors();
+
+    for_each_namespace_folder(assets_input_path, |folder_name, path| {
+        match processors.find(folder_name) {
+            Some(processor) => callback(processor, path),
+            None => sid_warning!(
+                "Ignoring entry (unknown or unsupported namespace): {:?}",
+                path
+            ),
+        }
+    })
+}
+
+/// Number of assets a pack of `assets_input_path` goes through.
+fn count_assets(assets_input_path: &Path) -> SidToolResult<usize> {
+    let processors = registry::processors();
+    let mut count = 0;
+
+    for_each_namespace_folder(assets_input_path, |folder_name, path| {
+        if let Some(processor) = processors.find(folder_name) {
+            count += processor.count_assets(path);
+        }
+    })?;
+
+    Ok(count)
+}
+
+fn for_each_namespace_folder<P, F>(assets_input_path: P, mut callback: F) -> SidToolResult<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(&OsStr, PathBuf),
+{
+    let entries = fs::read_dir(&assets_input_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Failed to read directory {:?}: {error}",
+            assets_input_path.as_ref()
+        ))
+    })?;
+
+    let mut ignore_rules = SidIgnoreRules::for_root(assets_input_path.as_ref());
+    ignore_rules.load(assets_input_path.as_ref());
+
+    let mut paths = vec![];
+
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push((entry.path(), walk::entry_kind(&entry))),
+            Err(error) => sid_error!("Error iterating over directory entry: {error}"),
+        }
+    }
+
+    // Directory iteration order is platform-specific.
+    paths.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (path, kind) in paths {
+        match kind {
+            Some(SidEntryKind::Folder) if ignore_rules.is_ignored(&path, true) => continue,
+            Some(SidEntryKind::Folder) => {}
+            Some(SidEntryKind::File)
+                if path.ends_with(IGNORE_FILE_NAME) || ignore_rules.is_ignored(&path, false) =>
+            {
+                continue
+            }
+            Some(SidEntryKind::File) => {
+                sid_warning!("Ignoring entry (a folder is expected): {:?}", path.to_str());
+                continue;
+            }
+            // Skipped links are logged by `entry_kind`.
+            None => continue,
+        }
+
+        let folder_name = match std::path::Path::new(&path).file_name() {
+            Some(folder_name) => folder_name.to_os_string(),
+            None => {
+                sid_error!(
+                    "Unknown error while processing an entry: {:?}",
+                    path.to_str()
+                );
+                continue;
+            }
+        };
+
+        callback(&folder_name, path);
+    }
+
+    Ok(())
+}
+
+/// Packs the single asset at `asset_input_path`, found in a namespace folder
+/// of `assets_input_path`, into a loose resource of `resources_output_path`.
+/// The build cache and the manifest are left untouched.
+pub fn pack_asset<P1: AsRef<Path>, P2: AsRef<Path>, P3: AsRef<Path>>(
+    asset_input_path: P1,
+    assets_input_path: P2,
+    resources_output_path: P3,
+    options: &SidPackOptions,
+) -> SidToolResult<SidManifestEntry> {
+    let _backend = sid::scope_backend(options.backend.as_ref());
+
+    let asset_input_path = asset_input_path.as_ref();
+    let resources_output_path = resources_output_path.as_ref();
+
+    fs::create_dir_all(resources_output_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Unable to create folder at path {:?}: {error}",
+            resources_output_path
+        ))
+    })?;
+
+    // Assets may be in a subfolder of their namespace folder.
+    let folder_name = asset_input_path
+        .strip_prefix(assets_input_path.as_ref())
+        .ok()
+        .and_then(|relative_path| relative_path.parent()?.iter().next())
+        .or_else(|| {
+            asset_input_path
+                .parent()
+                .and_then(|folder| folder.file_name())
+        })
+        .ok_or_else(|| {
+            SidToolError::Malformed(format!(
+                "Asset {:?} is not in a namespace folder",
+                asset_input_path
+            ))
+        })?;
+
+    // An empty cache rebuilds the resource, and keeps it loose.
+    let previous_cache = SidBuildCache::new(options.cache_key());
+    let settings = options
+        .process_settings(
+            assets_input_path.as_ref(),
+            resources_output_path,
+            Some(&previous_cache),
+        )
+        .for_asset(asset_input_path);
+
+    let processors = registry::processors();
+
+    let processor = processors.find(folder_name).ok_or_else(|| {
+        SidToolError::UnsupportedFormat(format!(
+            "Unknown or unsupported namespace {:?}",
+            folder_name
+        ))
+    })?;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        processor.process_asset(asset_input_path, settings)
+    }))
+    .unwrap_or_else(|payload| {
+        Err(SidAssetSerializationError::Malformed(format!(
+            "Packing panicked: {}",
+            panic_message(payload.as_ref())
+        )))
+    });
+
+    Ok(result?.manifest_entry)
+}
+
+pub fn from_assets_to_resources<P: AsRef<Path>>(
+    assets_input_path: P,
+    resources_output_path: P,
+) -> SidToolResult<()> {
+    from_assets_to_resources_with_options(
+        assets_input_path,
+        resources_output_path,
+        &SidPackOptions::new(),
+    )
+}
+
+pub fn from_assets_to_resources_with_options<P: AsRef<Path>>(
+    assets_input_path: P,
+    resources_output_path: P,
+    options: &SidPackOptions,
+) -> SidToolResult<()> {
+    from_assets_to_resources_with_report(
+        assets_input_path,
+        resources_output_path,
+        options,
+        &mut SidReport::new(),
+    )
+}
+
+/// Packs every asset of `assets_input_path` into `resources_output_path`.
+/// Assets failing to pack do not stop the others, but are all reported in a
+/// `SidToolError::AssetFailures` error. The status of every asset is added to
+/// `report`.
+pub fn from_assets_to_resources_with_report<P: AsRef<Path>>(
+    assets_input_path: P,
+    resources_output_path: P,
+    options: &SidPackOptions,
+    report: &mut SidReport,
+) -> SidToolResult<()> {
+    from_asset_folders_to_resources_with_report(
+        std::slice::from_ref(&assets_input_path),
+        resources_output_path,
+        options,
+        report,
+    )
+}
+
+/// Packs the assets of every folder of `assets_input_paths` together, like
+/// `from_assets_to_resources_with_report`. Assets with the same name in
+/// several folders are handled according to `options.input_collisions`.
+pub fn from_asset_folders_to_resources_with_report<P1: AsRef<Path>, P2: AsRef<Path>>(
+    assets_input_paths: &[P1],
+    resources_output_path: P2,
+    options: &SidPackOptions,
+    report: &mut SidReport,
+) -> SidToolResult<()> {
+    let _backend = sid::scope_backend(options.backend.as_ref());
+
+    let assets_input_paths: Vec<&Path> = assets_input_paths.iter().map(AsRef::as_ref).collect();
+    let resources_output_path = resources_output_path.as_ref();
+
+    let mut folder_entries = vec![];
+    let mut excluded_paths = HashSet::new();
+    let mut failed_paths = vec![];
+
+    for assets_input_path in &assets_input_paths {
+        // Assets that cannot be listed fail again once packed, and are
+        // reported then.
+        let (mut entries, failures) = list_assets(assets_input_path)?;
+        failed_paths.extend(failures.into_iter().map(|failure| failure.path));
+
+        // Filtered out assets do not override the ones of other folders.
+        if !options.tags.is_empty() {
+            filter_by_tags(&mut entries, &options.tags, &mut excluded_paths)?;
+        }
+
+        folder_entries.push(entries);
+    }
+
+    let (entries, shadowed_paths) =
+        resolve_input_collisions(folder_entries, options.input_collisions)?;
+
+    excluded_paths.extend(shadowed_paths);
+
+    // Names become ids and file names, so they are checked before anything
+    // is written as well.
+    check_asset_names(&entries, &options.names)?;
+
+    // Generated ids may take the ones the id overrides reserve.
+    check_id_overrides(&entries)?;
+
+    // Resource files are named after their ids, so collisions are detected
+    // before anything is written.
+    let collisions = entry_collisions(&entries);
+
+    if !collisions.is_empty() {
+        // Which of the colliding assets wins depends on the worker threads.
+        if !options.allow_collisions || options.deterministic {
+            return Err(SidToolError::IdCollisions(collisions));
+        }
+
+        for collision in &collisions {
+            sid_warning!("{collision}");
+        }
+    }
+
+    // A resource referring to a missing one would only fail once loaded.
+    let mut graph = SidAssetGraph::new();
+
+    for entry in entries.iter().cloned() {
+        graph.add(entry);
+    }
+
+    let dangling_references = graph.dangling_references();
+
+    if !dangling_references.is_empty() {
+        return Err(SidToolError::DanglingReferences(dangling_references));
+    }
+
+    if options.packages.is_empty() {
+        return write_resources(
+            &assets_input_paths,
+            resources_output_path,
+            &entries,
+            excluded_paths,
+            options,
+            report,
+        );
+    }
+
+    let packages = assign_packages(&assets_input_paths, &entries, &failed_paths, options)?;
+    let mut failures = vec![];
+
+    for package in package_names(options)? {
+        let (package_entries, other_entries): (Vec<_>, Vec<_>) = entries
+            .iter()
+            .cloned()
+            .partition(|entry| packages.get(&entry.path) == Some(&package));
+
+        let mut package_excluded_paths = excluded_paths.clone();
+        package_excluded_paths.extend(other_entries.into_iter().map(|entry| entry.path));
+        package_excluded_paths.extend(
+            failed_paths
+                .iter()
+                .filter(|path| packages.get(*path) != Some(&package))
+                .cloned(),
+        );
+
+        sid_info!(
+            "Packing {} asset(s) to package {:?}.",
+            package_entries.len(),
+            package
+        );
+
+        // References may cross packages, e.g. from a DLC to the base game, so
+        // they were checked over every package at once.
+        let result = write_resources(
+            &assets_input_paths,
+            &resources_output_path.join(package),
+            &package_entries,
+            package_excluded_paths,
+            options,
+            report,
+        );
+
+        match result {
+            Ok(()) => {}
+            // A failing asset does not stop the other packages.
+            Err(SidToolError::AssetFailures(package_failures)) => failures.extend(package_failures),
+            Err(error) => return Err(error),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
+
+/// Writes the resources of `entries` to `resources_output_path`, every asset
+/// of `assets_input_paths` but the ones of `excluded_paths` being packed.
+fn write_resources(
+    assets_input_paths: &[&Path],
+    resources_output_path: &Path,
+    entries: &[SidAssetEntry],
+    excluded_paths: HashSet<PathBuf>,
+    options: &SidPackOptions,
+    report: &mut SidReport,
+) -> SidToolResult<()> {
+    if !options.dry_run {
+        fs::create_dir_all(resources_output_path).map_err(|error| {
+            SidToolError::IO(format!(
+                "Unable to create folder at path {:?}: {error}",
+                resources_output_path
+            ))
+        })?;
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.jobs)
+        .build()
+        .map_err(|error| {
+            SidToolError::IO(format!("Unable to create the worker thread pool: {error}"))
+        })?;
+
+    sid_info!(
+        "Packing to {:?}, {}.",
+        resources_output_path,
+        options.overwrite
+    );
+
+    match options.overwrite {
+        SidOverwritePolicy::Clean => clean::wipe_resources(resources_output_path, options.dry_run)?,
+        SidOverwritePolicy::NoOverwrite => check_no_overwrite(entries, resources_output_path)?,
+        SidOverwritePolicy::Overwrite | SidOverwritePolicy::Merge => {}
+    }
+
+    // Forcing a repack starts from an empty cache, so that every asset is
+    // considered changed but the new fingerprints are still recorded.
+    let previous_cache = if options.force || options.deterministic {
+        SidBuildCache::new(options.cache_key())
+    } else {
+        SidBuildCache::load(resources_output_path, options.cache_key())
+    };
+
+    let is_pak = options.output_format == SidOutputFormat::Pak;
+
+    // Dry runs encode every resource in memory, the way paks are built.
+    let is_in_memory = is_pak || options.dry_run;
+
+    let mut context = SidPackContext {
+        settings: options.process_settings(
+            assets_input_paths.first().copied().unwrap_or(Path::new("")),
+            resources_output_path,
+            if is_in_memory {
+                None
+            } else {
+                Some(&previous_cache)
+            },
+        ),
+        cache: SidBuildCache::new(options.cache_key()),
+        pak: if is_pak && !options.dry_run {
+            Some(SidPakWriter::new())
+        } else {
+            None
+        },
+        failures: vec![],
+        skipped_count: 0,
+        reports: vec![],
+        manifest: SidManifest::new(),
+        progress: if options.progress {
+            let mut count = 0;
+
+            for assets_input_path in assets_input_paths {
+                count += count_assets(assets_input_path)?;
+            }
+
+            SidProgress::new(count.saturating_sub(excluded_paths.len()))
+        } else {
+            SidProgress::hidden()
+        },
+        excluded_paths,
+    };
+
+    let result: SidToolResult<()> = pool.install(|| {
+        // The pool runs this on one of its threads.
+        let _backend = sid::scope_backend(options.backend.as_ref());
+
+        for assets_input_path in assets_input_paths {
+            // Overrides are relative to the folder of their asset.
+            context.settings.assets_input_path = assets_input_path;
+
+            for_each_processor_folder(assets_input_path, |processor, path| {
+                processor.process_assets(path, &mut context);
+            })?;
+        }
+
+        Ok(())
+    });
+
+    context.progress.finish();
+    result?;
+
+    for asset_report in context.reports.drain(..) {
+        report.add(asset_report);
+    }
+
+    if context.skipped_count > 0 {
+        sid_info!("Skipped {} unchanged asset(s).", context.skipped_count);
+    }
+
+    if options.dry_run {
+        log_dry_run(&mut context.manifest, resources_output_path, is_pak);
+    } else {
+        // Changed resources get new content-hash names, the previous ones go.
+        if options.content_hash_names && !is_pak {
+            clean::remove_replaced_resources(resources_output_path, &context.manifest)?;
+        }
+
+        if options.overwrite == SidOverwritePolicy::Merge {
+            merge_previous_resources(
+                resources_output_path,
+                &mut context.manifest,
+                context.pak.as_mut(),
+            )?;
+        }
+
+        if let Some(pak) = &mut context.pak {
+            let pak_path = resources_output_path.join(DEFAULT_PAK_FILE_NAME);
+
+            pak.write_to_file(&pak_path).map_err(|error| {
+                SidToolError::IO(format!("Unable to write {:?}: {error}", pak_path))
+            })?;
+
+            sid_info!("Wrote {} resource(s) to {:?}.", pak.len(), pak_path);
+        } else if !options.deterministic {
+            if let Err(error) = context.cache.save(resources_output_path) {
+                sid_error!("Unable to save the build cache: {error}");
+            }
+        }
+
+        if options.deterministic {
+            for entry in &mut context.manifest.entries {
+                let relative_path = assets_input_paths
+                    .iter()
+                    .find_map(|path| entry.source_path.strip_prefix(path).ok());
+
+                if let Some(relative_path) = relative_path {
+                    entry.source_path = relative_path.to_path_buf();
+                }
+            }
+        }
+
+        if let Err(error) = context.manifest.save(resources_output_path) {
+            sid_error!("Unable to save the manifest: {error}");
+        }
+    }
+
+    let mut failures = context.failures;
+
+    // Workers finish in any order, so sort failures to keep the output stable.
+    failures.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
+
+/// Describes every asset of `entries` whose name breaks `names`, or only
+/// differs by case from the name of another asset of its namespace.
+fn name_failures(entries: &[SidAssetEntry], names: &SidNameRules) -> Vec<SidAssetFailure> {
+    let mut entries: Vec<_> = entries.iter().collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut folded_names: HashMap<(&str, String), &SidAssetEntry> = HashMap::new();
+    let mut failures = vec![];
+
+    for entry in entries {
+        if let Err(violation) = names.check(&entry.name) {
+            failures.push(SidAssetFailure {
+                path: entry.path.clone(),
+                error: SidToolError::Malformed(format!(
+                    "Invalid name {:?}: {violation}",
+                    entry.name
+                )),
+            });
+            continue;
+        }
+
+        // Equal names are id collisions, reported on their own.
+        let key = (entry.namespace, entry.name.to_lowercase());
+
+        match folded_names.get(&key) {
+            Some(other) if other.name != entry.name => failures.push(SidAssetFailure {
+                path: entry.path.clone(),
+                error: SidToolError::Malformed(format!(
+                    "Name {:?} only differs by case from {:?} of {:?}",
+                    entry.name, other.name, other.path
+                )),
+            }),
+            Some(_) => {}
+            None => {
+                folded_names.insert(key, entry);
+            }
+        }
+    }
+
+    failures
+}
+
+/// Names of the packages of a pack: the ones of `options.packages`, then the
+/// default one.
+fn package_names(options: &SidPackOptions) -> SidToolResult<Vec<&str>> {
+    let mut names: Vec<&str> = vec![];
+
+    let packages = options.packages.iter().map(|package| package.name.as_str());
+
+    for name in packages.chain([options.default_package.as_str()]) {
+        // Packages are folders of the output folder.
+        let is_valid =
+            !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\']);
+
+        if !is_valid {
+            return Err(SidToolError::Malformed(format!(
+                "Invalid package name {:?}",
+                name
+            )));
+        }
+
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    Ok(names)
+}
+
+/// Package of every asset of `entries`, by path: the first package of
+/// `options.packages` it is under a path or has a tag of, or else the default
+/// one. The assets of `failed_paths`, whose tags cannot be read, only go by
+/// their paths.
+fn assign_packages<'a>(
+    assets_input_paths: &[&Path],
+    entries: &[SidAssetEntry],
+    failed_paths: &[PathBuf],
+    options: &'a SidPackOptions,
+) -> SidToolResult<HashMap<PathBuf, &'a str>> {
+    let has_tags = options
+        .packages
+        .iter()
+        .any(|package| !package.tags.is_empty());
+
+    let mut packages = HashMap::new();
+    let mut failures = vec![];
+
+    let paths = entries
+        .iter()
+        .map(|entry| (&entry.path, true))
+        .chain(failed_paths.iter().map(|path| (path, false)));
+
+    for (asset_path, is_loadable) in paths {
+        let tags = if has_tags && is_loadable {
+            match tags::read_tags(asset_path) {
+                Ok(tags) => tags,
+                Err(error) => {
+                    failures.push(SidAssetFailure {
+                        path: asset_path.clone(),
+                        error,
+                    });
+                    continue;
+                }
+            }
+        } else {
+            vec![]
+        };
+
+        // Paths are relative to the input folder of the asset.
+        let relative_path = assets_input_paths
+            .iter()
+            .find_map(|path| asset_path.strip_prefix(path).ok());
+
+        let package = options
+            .packages
+            .iter()
+            .find(|package| {
+                relative_path.is_some_and(|relative_path| {
+                    package
+                        .paths
+                        .iter()
+                        .any(|path| relative_path.starts_with(path))
+                }) || package.tags.iter().any(|tag| tags.contains(tag))
+            })
+            .map(|package| package.name.as_str())
+            .unwrap_or(&options.default_package);
+
+        packages.insert(asset_path.clone(), package);
+    }
+
+    if failures.is_empty() {
+        Ok(packages)
+    } else {
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
+
+/// Leaves out the assets of `entries` that `filter` rejects, adding their
+/// paths to `excluded_paths`.
+fn filter_by_tags(
+    entries: &mut Vec<SidAssetEntry>,
+    filter: &SidTagFilter,
+    excluded_paths: &mut HashSet<PathBuf>,
+) -> SidToolResult<()> {
+    let mut failures = vec![];
+    let count = entries.len();
+
+    entries.retain(|entry| match tags::read_tags(&entry.path) {
+        Ok(tags) if filter.matches(&tags) => true,
+        Ok(_) => {
+            sid_debug!("Skipping {:?}, filtered out by its tags.", entry.path);
+            excluded_paths.insert(entry.path.clone());
+            false
+        }
+        Err(error) => {
+            failures.push(SidAssetFailure {
+                path: entry.path.clone(),
+                error,
+            });
+            true
+        }
+    });
+
+    if !failures.is_empty() {
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        return Err(SidToolError::AssetFailures(failures));
+    }
+
+    if entries.len() < count {
+        sid_info!(
+            "Skipped {} asset(s) filtered out by their tags.",
+            count - entries.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Keeps a single asset of every name defined in several input folders,
+/// `folder_entries` holding the assets of each folder in order. Returns the
+/// assets kept, and the paths of the ones skipped. Assets with the same name
+/// in a single folder are id collisions, reported on their own.
+fn resolve_input_collisions(
+    folder_entries: Vec<Vec<SidAssetEntry>>,
+    policy: SidInputCollisionPolicy,
+) -> SidToolResult<(Vec<SidAssetEntry>, HashSet<PathBuf>)> {
+    let owner = |entry: &SidAssetEntry| SidIdOwner {
+        namespace: entry.namespace,
+        name: entry.name.clone(),
+        path: entry.path.clone(),
+    };
+
+    let mut entries: Vec<SidAssetEntry> = vec![];
+    let mut indices: HashMap<(&str, String), (usize, usize)> = HashMap::new();
+    let mut shadowed_paths = HashSet::new();
+    let mut collisions = vec![];
+
+    for (folder_index, mut folder_entries) in folder_entries.into_iter().enumerate() {
+        folder_entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        for entry in folder_entries {
+            let key = (entry.namespace, entry.name.clone());
+
+            let index = match indices.get(&key) {
+                Some(&(other_folder_index, index)) if other_folder_index != folder_index => index,
+                Some(_) => {
+                    entries.push(entry);
+                    continue;
+                }
+                None => {
+                    indices.insert(key, (folder_index, entries.len()));
+                    entries.push(entry);
+                    continue;
+                }
+            };
+
+            match policy {
+                SidInputCollisionPolicy::Error => collisions.push(SidIdCollision {
+                    id: entry.id,
+                    first: owner(&entries[index]),
+                    second: owner(&entry),
+                }),
+                SidInputCollisionPolicy::FirstWins => {
+                    sid_debug!(
+                        "Skipping {:?}, overridden by {:?}.",
+                        entry.path,
+                        entries[index].path
+                    );
+                    shadowed_paths.insert(entry.path);
+                }
+                SidInputCollisionPolicy::LastWins => {
+                    sid_debug!(
+                        "Skipping {:?}, overridden by {:?}.",
+                        entries[index].path,
+                        entry.path
+                    );
+                    let shadowed_entry = mem::replace(&mut entries[index], entry);
+                    shadowed_paths.insert(shadowed_entry.path);
+                    indices.insert(key, (folder_index, index));
+                }
+            }
+        }
+    }
+
+    if !collisions.is_empty() {
+        return Err(SidToolError::IdCollisions(collisions));
+    }
+
+    if !shadowed_paths.is_empty() {
+        sid_info!(
+            "Skipped {} asset(s) overridden by another input folder.",
+            shadowed_paths.len()
+        );
+    }
+
+    Ok((entries, shadowed_paths))
+}
+
+/// Fails every asset of `entries` whose name breaks `names`, before anything
+/// is written.
+fn check_asset_names(entries: &[SidAssetEntry], names: &SidNameRules) -> SidToolResult<()> {
+    let failures = name_failures(entries, names);
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
+
+/// Fails every asset of `entries` whose id the id overrides reserve or give to
+/// another asset, before anything is written.
+fn check_id_overrides(entries: &[SidAssetEntry]) -> SidToolResult<()> {
+    let mut failures: Vec<SidAssetFailure> = entries
+        .iter()
+        .filter_map(|entry| {
+            let violation = id_overrides::check_id(entry.namespace, &entry.name, entry.id).err()?;
+
+            Some(SidAssetFailure {
+                path: entry.path.clone(),
+                error: SidToolError::Malformed(format!(
+                    "Invalid id of {:?}: {violation}",
+                    entry.name
+                )),
+            })
+        })
+        .collect();
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    failures.sort_by(|a, b| a.path.cmp(&b.path));
+    Err(SidToolError::AssetFailures(failures))
+}
+
+/// Ids of the resources already in `resources_output_path`, loose or in its
+/// pak.
+fn existing_resource_ids(resources_output_path: &Path) -> SidToolResult<HashSet<sid::SidStringId>> {
+    let mut ids = HashSet::new();
+
+    let Ok(entries) = fs::read_dir(resources_output_path) else {
+        return Ok(ids);
+    };
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+
+        if let Some(id) = file_name.to_str().and_then(resource_file_id) {
+            ids.insert(id);
+        }
+    }
+
+    let pak_path = resources_output_path.join(DEFAULT_PAK_FILE_NAME);
+
+    if pak_path.is_file() {
+        let data = fs::read(&pak_path)
+            .map_err(|error| SidToolError::IO(format!("Unable to read {:?}: {error}", pak_path)))?;
+
+        ids.extend(pak::read_pak(&data)?.1.iter().map(|entry| entry.id));
+    }
+
+    Ok(ids)
+}
+
+/// Fails every asset of `entries` mapping to the id of a resource already in
+/// `resources_output_path`, before anything is written.
+fn check_no_overwrite(
+    entries: &[SidAssetEntry],
+    resources_output_path: &Path,
+) -> SidToolResult<()> {
+    let existing_ids = existing_resource_ids(resources_output_path)?;
+
+    let mut failures: Vec<SidAssetFailure> = entries
+        .iter()
+        .filter(|entry| existing_ids.contains(&entry.id))
+        .map(|entry| SidAssetFailure {
+            error: SidToolError::IO(format!(
+                "Resource {} already exists in {:?}, and overwriting is disabled",
+                entry.id, resources_output_path
+            )),
+            path: entry.path.clone(),
+        })
+        .collect();
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    failures.sort_by(|a, b| a.path.cmp(&b.path));
+    Err(SidToolError::AssetFailures(failures))
+}
+
+/// Keeps the resources of the previous pack of `resources_output_path` that
+/// this one did not replace, in the pak being written, if any, and in
+/// `manifest`.
+fn merge_previous_resources(
+    resources_output_path: &Path,
+    manifest: &mut SidManifest,
+    pak: Option<&mut SidPakWriter>,
+) -> SidToolResult<()> {
+    let Ok(previous_manifest) = SidManifest::load(resources_output_path) else {
+        return Ok(());
+    };
+
+    let packed_ids: HashSet<_> = manifest.entries.iter().map(|entry| entry.id).collect();
+    let is_pak = pak.is_some();
+    let mut pak_ids = HashSet::new();
+
+    if let Some(pak) = pak {
+        let pak_path = resources_output_path.join(DEFAULT_PAK_FILE_NAME);
+
+        if pak_path.is_file() {
+            let data = fs::read(&pak_path).map_err(|error| {
+                SidToolError::IO(format!("Unable to read {:?}: {error}", pak_path))
+            })?;
+
+            for entry in pak::read_pak(&data)?.1 {
+                if !packed_ids.contains(&entry.id) {
+                    pak_ids.insert(entry.id);
+                    pak.add(entry);
+                }
+            }
+        }
+    }
+
+    let mut kept_count = 0;
+
+    for entry in previous_manifest.entries {
+        let is_kept = !packed_ids.contains(&entry.id)
+            && if is_pak {
+                pak_ids.contains(&entry.id)
+            } else {
+                resources_output_path.join(entry.file_name()).is_file()
+            };
+
+        if is_kept {
+            manifest.entries.push(entry);
+            kept_count += 1;
+        }
+    }
+
+    sid_info!(
+        "Kept {kept_count} existing resource(s) of {:?}.",
+        resources_output_path
+    );
+
+    Ok(())
+}
+
+/// Logs the resources a dry run would have written, by id.
+fn log_dry_run(manifest: &mut SidManifest, resources_output_path: &Path, is_pak: bool) {
+    manifest.entries.sort_by_key(|entry| entry.id);
+
+    for entry in &manifest.entries {
+        sid_info!(
+            "Would write {:?} {:?} ({}): {} byte(s) from {:?}",
+            entry.resource_type,
+            entry.name,
+            entry.id,
+            entry.size,
+            entry.source_path
+        );
+    }
+
+    let size: u64 = manifest.entries.iter().map(|entry| entry.size).sum();
+
+    let destination = if is_pak {
+        resources_output_path.join(DEFAULT_PAK_FILE_NAME)
+    } else {
+        resources_output_path.to_path_buf()
+    };
+
+    sid_info!(
+        "Dry run: would write {} resource(s) ({size} byte(s)) to {:?}.",
+        manifest.entries.len(),
+        destination
+    );
+}
+
+/// Lists the assets that can be loaded. The others have no id nor references,
+/// the pack reports them later.
+fn list_loadable_assets<P: AsRef<Path>>(assets_input_path: P) -> SidToolResult<Vec<SidAssetEntry>> {
+    let (entries, _) = list_assets(assets_input_path)?;
+    Ok(entries)
+}
+
+pub fn find_id_collisions<P: AsRef<Path>>(
+    assets_input_path: P,
+) -> SidToolResult<Vec<SidIdCollision>> {
+    Ok(entry_collisions(&list_loadable_assets(assets_input_path)?))
+}
+
+fn entry_collisions(entries: &[SidAssetEntry]) -> Vec<SidIdCollision> {
+    let mut entries: Vec<_> = entries.iter().collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut registry = SidIdRegistry::new();
+    let mut collisions = vec![];
+
+    for entry in entries {
+        let owner = SidIdOwner {
+            namespace: entry.namespace,
+            name: entry.name.clone(),
+            path: entry.path.clone(),
+        };
+
+        if let Err(collision) = registry.register(entry.id, owner) {
+            collisions.push(*collision);
+        }
+    }
+
+    collisions
+}
+
+/// Links the assets of `assets_input_path` by the names they reference.
+pub fn build_asset_graph<P: AsRef<Path>>(assets_input_path: P) -> SidToolResult<SidAssetGraph> {
+    let mut graph = SidAssetGraph::new();
+
+    for entry in list_loadable_assets(assets_input_path)? {
+        graph.add(entry);
+    }
+
+    Ok(graph)
+}
+
+/// Checks every asset of `assets_input_path` against its schema, and that the
+/// assets it references exist. Every violation is reported in a
+/// `SidToolError::AssetFailures` error.
+pub fn validate_assets<P: AsRef<Path>>(assets_input_path: P) -> SidToolResult<()> {
+    validate_assets_with_names(assets_input_path, &SidNameRules::new())
+}
+
+/// Validates the assets of `assets_input_path` like `validate_assets`, with
+/// their names checked against `names`.
+pub fn validate_assets_with_names<P: AsRef<Path>>(
+    assets_input_path: P,
+    names: &SidNameRules,
+) -> SidToolResult<()> {
+    let mut context = SidValidationContext {
+        graph: SidAssetGraph::new(),
+        failures: vec![],
+    };
+
+    for_each_processor_folder(&assets_input_path, |processor, path| {
+        processor.validate_assets(path, &mut context);
+    })?;
+
+    let name_failures = name_failures(context.graph.assets(), names);
+    context.failures.extend(name_failures);
+
+    for dangling in context.graph.dangling_references() {
+        let reference = dangling.reference;
+
+        context.failures.push(SidAssetFailure {
+            path: dangling.owner.path,
+            error: SidToolError::Malformed(format!(
+                "{} references unknown {} asset {:?}",
+                reference.field, reference.namespace, reference.name
+            )),
+        });
+    }
+
+    let mut failures = context.failures;
+    failures.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
+
+/// Lists the assets of `assets_input_path`. Returns the ones that can be
+/// loaded, and the failures of the others.
+pub fn list_assets<P: AsRef<Path>>(
+    assets_input_path: P,
+) -> SidToolResult<(Vec<SidAssetEntry>, Vec<SidAssetFailure>)> {
+    let mut entries = vec![];
+    let mut failures = vec![];
+
+    for_each_processor_folder(&assets_input_path, |processor, path| {
+        processor.list_assets(path, &mut entries, &mut failures);
+    })?;
+
+    failures.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((entries, failures))
 }
@@ -0,0 +1,1597 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Readers parsing packed resources back into structs, for the inspector, the
+// unpacker, round-trip checks and the tooling of the engine.
+
+use crate::{
+    compression,
+    error::{SidToolError, SidToolResult},
+    pak,
+    resource::{
+        SidEndianness, SidResourceHeader, SidResourceType, CURVE_ENCODING_KEYED,
+        CURVE_ENCODING_SAMPLED, KEY_CURVE_BEZIER, MESH_TEXTURE_NONE, MESH_VERTEX_STRIDE,
+        RESOURCE_HEADER_SIZE, TILEMAP_LAYER_FLAG_VISIBLE,
+    },
+    sid::{
+        self, sid_audio_format, sid_prefab_component_type, sid_shader_format, sid_texture_format,
+    },
+};
+
+/// Cursor over the bytes of a packed resource.
+pub struct SidResourceReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    endianness: SidEndianness,
+}
+
+impl<'a> SidResourceReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self::with_endianness(data, SidEndianness::Little)
+    }
+
+    pub fn with_endianness(data: &'a [u8], endianness: SidEndianness) -> Self {
+        Self {
+            data,
+            position: 0,
+            endianness,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    /// Capacity to reserve for `count` items read next, bounded by the bytes
+    /// left, so that corrupted counts can not exhaust the memory.
+    fn capacity(&self, count: usize) -> usize {
+        count.min(self.remaining())
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> SidToolResult<&'a [u8]> {
+        if self.remaining() < count {
+            return Err(SidToolError::Malformed(format!(
+                "Unexpected end of resource at byte {} (expected {count} more byte(s))",
+                self.position
+            )));
+        }
+
+        let bytes = &self.data[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> SidToolResult<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    pub fn read_u8(&mut self) -> SidToolResult<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_u16(&mut self) -> SidToolResult<u16> {
+        let bytes = self.read_array()?;
+
+        Ok(match self.endianness {
+            SidEndianness::Little => u16::from_le_bytes(bytes),
+            SidEndianness::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    pub fn read_u32(&mut self) -> SidToolResult<u32> {
+        let bytes = self.read_array()?;
+
+        Ok(match self.endianness {
+            SidEndianness::Little => u32::from_le_bytes(bytes),
+            SidEndianness::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads a string id, as wide as the ids of the tools.
+    pub fn read_id(&mut self) -> SidToolResult<sid::SidStringId> {
+        let bytes = self.read_array()?;
+
+        Ok(match self.endianness {
+            SidEndianness::Little => sid::SidStringId::from_le_bytes(bytes),
+            SidEndianness::Big => sid::SidStringId::from_be_bytes(bytes),
+        })
+    }
+
+    pub fn read_i32(&mut self) -> SidToolResult<i32> {
+        let bytes = self.read_array()?;
+
+        Ok(match self.endianness {
+            SidEndianness::Little => i32::from_le_bytes(bytes),
+            SidEndianness::Big => i32::from_be_bytes(bytes),
+        })
+    }
+
+    pub fn read_f32(&mut self) -> SidToolResult<f32> {
+        let bytes = self.read_array()?;
+
+        Ok(match self.endianness {
+            SidEndianness::Little => f32::from_le_bytes(bytes),
+            SidEndianness::Big => f32::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads a string written with a u16 length prefix.
+    pub fn read_string(&mut self) -> SidToolResult<String> {
+        let length = self.read_u16()? as usize;
+        let position = self.position;
+
+        String::from_utf8(self.read_bytes(length)?.to_vec()).map_err(|_| {
+            SidToolError::Malformed(format!("Invalid UTF-8 string at byte {position}"))
+        })
+    }
+
+    pub fn read_u64(&mut self) -> SidToolResult<u64> {
+        let bytes = self.read_array()?;
+
+        Ok(match self.endianness {
+            SidEndianness::Little => u64::from_le_bytes(bytes),
+            SidEndianness::Big => u64::from_be_bytes(bytes),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct SidDecodedMipLevel {
+    pub width: sid::SidSpriteSheetDim,
+    pub height: sid::SidSpriteSheetDim,
+    /// Pixels, with 16-bit channels in little endian.
+    pub data: Vec<u8>,
+}
+
+/// Pixels of a sprite sheet or a texture, with their mip chain if any.
+#[derive(Debug)]
+pub struct SidDecodedImage {
+    pub width: sid::SidSpriteSheetDim,
+    pub height: sid::SidSpriteSheetDim,
+    pub channel_count: u8,
+    pub format: i32,
+    pub color_space: i32,
+    /// Palette the indices point to, or 0 if the texture is not indexed.
+    pub palette_id: sid::SidPaletteId,
+    /// Mip levels, starting with the base one.
+    pub levels: Vec<SidDecodedMipLevel>,
+}
+
+impl SidDecodedImage {
+    fn read(reader: &mut SidResourceReader) -> SidToolResult<Self> {
+        let width = reader.read_u32()?;
+        let height = reader.read_u32()?;
+        let channel_count = reader.read_u8()?;
+        let format = reader.read_i32()?;
+        let color_space = reader.read_i32()?;
+        let palette_id = reader.read_id()?;
+        let level_count = reader.read_u8()?;
+
+        if level_count == 0 {
+            return Err(SidToolError::Malformed(
+                "Texture without any mip level".to_string(),
+            ));
+        }
+
+        let is_rgba16 = format == sid_texture_format::SID_TEXTURE_FORMAT_RGBA16 as i32;
+        let mut levels = Vec::with_capacity(reader.capacity(level_count as usize));
+
+        for _ in 0..level_count {
+            let width = reader.read_u32()?;
+            let height = reader.read_u32()?;
+            let size = reader.read_u64()? as usize;
+            let mut data = reader.read_bytes(size)?.to_vec();
+
+            if is_rgba16 && reader.endianness == SidEndianness::Big {
+                data.chunks_exact_mut(2)
+                    .for_each(|channel| channel.swap(0, 1));
+            }
+
+            levels.push(SidDecodedMipLevel {
+                width,
+                height,
+                data,
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            channel_count,
+            format,
+            color_space,
+            palette_id,
+            levels,
+        })
+    }
+
+    /// Pixels of the base level.
+    pub fn texture(&self) -> &[u8] {
+        &self.levels[0].data
+    }
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSpriteSheet {
+    pub id: sid::SidSpriteSheetId,
+    pub pivot: Option<(sid::SidSliceCoord, sid::SidSliceCoord)>,
+    pub normal_map_id: Option<sid::SidTextureId>,
+    pub emissive_map_id: Option<sid::SidTextureId>,
+    pub image: SidDecodedImage,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedTexture {
+    pub id: sid::SidTextureId,
+    pub image: SidDecodedImage,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedPalette {
+    pub id: sid::SidPaletteId,
+    pub colors: Vec<[u8; 4]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SidDecodedFrame {
+    /// Index of the region record of the frame, shared by the frames covering
+    /// the same region.
+    pub region: sid::SidAnimationFrameIndex,
+    pub x: sid::SidAnimationFrameCoord,
+    pub y: sid::SidAnimationFrameCoord,
+    pub width: sid::SidAnimationFrameDim,
+    pub height: sid::SidAnimationFrameDim,
+    pub duration: sid::SidAnimationFrameDuration,
+    pub offset_x: sid::SidAnimationFrameCoord,
+    pub offset_y: sid::SidAnimationFrameCoord,
+    pub source_width: sid::SidAnimationFrameDim,
+    pub source_height: sid::SidAnimationFrameDim,
+    pub rotated: bool,
+    pub pivot: Option<(sid::SidSliceCoord, sid::SidSliceCoord)>,
+    pub page: sid::SidAnimationPageIndex,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedAnimationDef {
+    pub id: sid::SidAnimationDefId,
+    pub sheet_id: sid::SidSpriteSheetId,
+    /// Sheets of the pages after the first one.
+    pub page_ids: Vec<sid::SidSpriteSheetId>,
+    pub frames: Vec<SidDecodedFrame>,
+}
+
+impl SidDecodedAnimationDef {
+    /// Number of distinct regions the frames cover.
+    pub fn region_count(&self) -> usize {
+        self.frames
+            .iter()
+            .map(|frame| frame.region as usize + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug)]
+pub struct SidDecodedAnimation {
+    pub id: sid::SidAnimationId,
+    pub def_id: sid::SidAnimationDefId,
+    pub offset: sid::SidAnimationFrameIndex,
+    pub length: sid::SidAnimationFrameIndex,
+    pub direction: u8,
+    pub looping: bool,
+    pub speed_scale: f32,
+    /// Frame and name of every event.
+    pub events: Vec<(sid::SidAnimationFrameIndex, String)>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSliceRect {
+    pub x: sid::SidSliceCoord,
+    pub y: sid::SidSliceCoord,
+    pub width: sid::SidSliceDim,
+    pub height: sid::SidSliceDim,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSliceKey {
+    pub frame: sid::SidAnimationFrameIndex,
+    pub bounds: SidDecodedSliceRect,
+    pub center: Option<SidDecodedSliceRect>,
+    pub pivot: Option<(sid::SidSliceCoord, sid::SidSliceCoord)>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSlice {
+    pub id: sid::SidSliceId,
+    pub sheet_id: sid::SidSpriteSheetId,
+    pub keys: Vec<SidDecodedSliceKey>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedNineSlice {
+    pub id: sid::SidNineSliceId,
+    pub sheet_id: sid::SidSpriteSheetId,
+    pub region: SidDecodedSliceRect,
+    /// Left, top, right and bottom borders.
+    pub insets: [sid::SidSliceDim; 4],
+}
+
+#[derive(Debug)]
+pub struct SidDecodedCharacterPart {
+    pub name: String,
+    pub sheet_id: sid::SidSpriteSheetId,
+    pub def_id: sid::SidAnimationDefId,
+    pub animation_ids: Vec<sid::SidAnimationId>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedCharacter {
+    pub id: sid::SidCharacterId,
+    pub parts: Vec<SidDecodedCharacterPart>,
+}
+
+/// Position, rotation, and x and y scales.
+pub type SidDecodedBoneTransform = [f32; 5];
+
+#[derive(Debug)]
+pub struct SidDecodedSkeletonBone {
+    pub name: String,
+    /// Index of the parent bone, none for the root bone.
+    pub parent: Option<u16>,
+    pub length: f32,
+    pub transform: SidDecodedBoneTransform,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSkeletonSlot {
+    pub name: String,
+    pub bone: u16,
+    pub attachment: Option<String>,
+    pub color: [u8; 4],
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSkeletonAttachment {
+    pub slot: u16,
+    pub name: String,
+    pub region: String,
+    pub transform: SidDecodedBoneTransform,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSkeleton {
+    pub id: sid::SidSkeletonId,
+    pub bones: Vec<SidDecodedSkeletonBone>,
+    pub slots: Vec<SidDecodedSkeletonSlot>,
+    pub attachments: Vec<SidDecodedSkeletonAttachment>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedBoneKey {
+    pub time: f32,
+    pub values: [f32; 2],
+    /// Curve type, and the control points of Bézier curves.
+    pub curve: u8,
+    pub curve_points: Option<[f32; 4]>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedBoneTimeline {
+    pub bone: String,
+    pub property: u8,
+    pub keys: Vec<SidDecodedBoneKey>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSlotTimeline {
+    pub slot: String,
+    /// Time and attachment of every key.
+    pub keys: Vec<(f32, Option<String>)>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSkeletalAnimation {
+    pub id: sid::SidSkeletalAnimationId,
+    pub skeleton_id: sid::SidSkeletonId,
+    pub duration: f32,
+    pub bones: Vec<SidDecodedBoneTimeline>,
+    pub slots: Vec<SidDecodedSlotTimeline>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedAudio {
+    pub id: sid::SidAudioId,
+    pub sample_rate: sid::SidAudioSampleRate,
+    pub channel_count: sid::SidAudioChannelCount,
+    pub format: i32,
+    /// Little endian samples, whatever the resource byte order, or the Ogg
+    /// Vorbis stream.
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedGlyph {
+    pub character: u32,
+    pub x: sid::SidFontGlyphCoord,
+    pub y: sid::SidFontGlyphCoord,
+    pub width: sid::SidFontGlyphDim,
+    pub height: sid::SidFontGlyphDim,
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub advance: f32,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedFont {
+    pub id: sid::SidFontId,
+    pub size: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+    pub glyphs: Vec<SidDecodedGlyph>,
+    pub atlas_width: u32,
+    pub atlas_height: u32,
+    pub channel_count: u8,
+    pub format: i32,
+    pub texture: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedBitmapFontGlyph {
+    pub character: u32,
+    pub x: sid::SidFontGlyphCoord,
+    pub y: sid::SidFontGlyphCoord,
+    pub width: sid::SidFontGlyphDim,
+    pub height: sid::SidFontGlyphDim,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub x_advance: i32,
+    pub page: u8,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedBitmapFont {
+    pub id: sid::SidBitmapFontId,
+    pub size: f32,
+    pub line_height: u32,
+    pub base: u32,
+    pub page_ids: Vec<sid::SidTextureId>,
+    pub glyphs: Vec<SidDecodedBitmapFontGlyph>,
+    /// First and second characters of every kerning pair, and its amount.
+    pub kernings: Vec<(u32, u32, i32)>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedMeshSubmesh {
+    pub first_index: u32,
+    pub index_count: u32,
+    /// `MESH_TEXTURE_*` kind of the texture, and its id if any.
+    pub texture: Option<(u8, sid::SidStringId)>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedMesh {
+    pub id: sid::SidMeshId,
+    /// Positions, normals and texture coordinates of every vertex.
+    pub vertices: Vec<[f32; 8]>,
+    pub indices: Vec<u32>,
+    /// Bytes of every index as packed.
+    pub index_size: u8,
+    pub submeshes: Vec<SidDecodedMeshSubmesh>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SidDecodedCurveData {
+    /// Time and values of every key.
+    Keyed(Vec<(f32, Vec<f32>)>),
+    Sampled {
+        sample_rate: f32,
+        /// Time of the first sample.
+        start_time: f32,
+        samples: Vec<Vec<f32>>,
+    },
+}
+
+#[derive(Debug)]
+pub struct SidDecodedCurve {
+    pub id: sid::SidCurveId,
+    pub interpolation: u8,
+    /// Values per key or sample.
+    pub dimension: u8,
+    pub data: SidDecodedCurveData,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedTileset {
+    pub id: sid::SidTilesetId,
+    pub sheet_id: sid::SidSpriteSheetId,
+    pub tile_width: sid::SidTileDim,
+    pub tile_height: sid::SidTileDim,
+    pub tile_count: u32,
+    pub columns: u32,
+    pub spacing: u32,
+    pub margin: u32,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedTilemapLayer {
+    pub visible: bool,
+    pub tiles: Vec<sid::SidTileGid>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedTilemap {
+    pub id: sid::SidTilemapId,
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: sid::SidTileDim,
+    pub tile_height: sid::SidTileDim,
+    /// First global tile id and tileset id of every tileset.
+    pub tilesets: Vec<(sid::SidTileGid, sid::SidTilesetId)>,
+    pub layers: Vec<SidDecodedTilemapLayer>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedShaderStage {
+    pub stage: i32,
+    /// GLSL source, or SPIR-V words in little endian, whatever the resource
+    /// byte order.
+    pub code: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedShader {
+    pub id: sid::SidShaderId,
+    pub format: i32,
+    pub stages: Vec<SidDecodedShaderStage>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedMaterial {
+    pub id: sid::SidMaterialId,
+    pub shader_id: sid::SidShaderId,
+    pub blend_mode: u8,
+    /// Sampler names and the ids of their textures.
+    pub textures: Vec<(String, sid::SidTextureId)>,
+    /// Uniform names and the components of their values.
+    pub uniforms: Vec<(String, Vec<f32>)>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedBlob {
+    pub id: sid::SidBlobId,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum SidDecodedPrefabComponent {
+    Sprite {
+        sheet_id: sid::SidSpriteSheetId,
+    },
+    Animation {
+        animation_id: sid::SidAnimationId,
+    },
+    Collider {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        is_trigger: bool,
+    },
+    Custom {
+        values: Vec<(String, String)>,
+    },
+}
+
+#[derive(Debug)]
+pub struct SidDecodedPrefab {
+    pub id: sid::SidPrefabId,
+    pub components: Vec<SidDecodedPrefabComponent>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedSceneEntity {
+    pub name: Option<String>,
+    pub prefab_id: sid::SidPrefabId,
+    pub transform: SidDecodedBoneTransform,
+    pub layer: i32,
+    pub overrides: Vec<SidDecodedPrefabComponent>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedScene {
+    pub id: sid::SidSceneId,
+    pub entities: Vec<SidDecodedSceneEntity>,
+}
+
+#[derive(Debug)]
+pub struct SidDecodedStringTable {
+    pub id: sid::SidStringTableId,
+    pub key_ids: Vec<sid::SidStringKeyId>,
+    /// Locale name, and its strings in the order of `key_ids`.
+    pub locales: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Debug)]
+pub enum SidDecodedResource {
+    SpriteSheet(SidDecodedSpriteSheet),
+    AnimationDef(SidDecodedAnimationDef),
+    Animation(SidDecodedAnimation),
+    Slice(SidDecodedSlice),
+    Audio(SidDecodedAudio),
+    Font(SidDecodedFont),
+    Tileset(SidDecodedTileset),
+    Tilemap(SidDecodedTilemap),
+    Shader(SidDecodedShader),
+    Blob(SidDecodedBlob),
+    Prefab(SidDecodedPrefab),
+    StringTable(SidDecodedStringTable),
+    Texture(SidDecodedTexture),
+    Palette(SidDecodedPalette),
+    NineSlice(SidDecodedNineSlice),
+    Character(SidDecodedCharacter),
+    Skeleton(SidDecodedSkeleton),
+    SkeletalAnimation(SidDecodedSkeletalAnimation),
+    BitmapFont(SidDecodedBitmapFont),
+    Mesh(SidDecodedMesh),
+    Curve(SidDecodedCurve),
+    Material(SidDecodedMaterial),
+    Scene(SidDecodedScene),
+}
+
+impl SidDecodedResource {
+    pub fn id(&self) -> sid::SidStringId {
+        match self {
+            SidDecodedResource::SpriteSheet(resource) => resource.id,
+            SidDecodedResource::AnimationDef(resource) => resource.id,
+            SidDecodedResource::Animation(resource) => resource.id,
+            SidDecodedResource::Slice(resource) => resource.id,
+            SidDecodedResource::Audio(resource) => resource.id,
+            SidDecodedResource::Font(resource) => resource.id,
+            SidDecodedResource::Tileset(resource) => resource.id,
+            SidDecodedResource::Tilemap(resource) => resource.id,
+            SidDecodedResource::Shader(resource) => resource.id,
+            SidDecodedResource::Blob(resource) => resource.id,
+            SidDecodedResource::Prefab(resource) => resource.id,
+            SidDecodedResource::StringTable(resource) => resource.id,
+            SidDecodedResource::Texture(resource) => resource.id,
+            SidDecodedResource::Palette(resource) => resource.id,
+            SidDecodedResource::NineSlice(resource) => resource.id,
+            SidDecodedResource::Character(resource) => resource.id,
+            SidDecodedResource::Skeleton(resource) => resource.id,
+            SidDecodedResource::SkeletalAnimation(resource) => resource.id,
+            SidDecodedResource::BitmapFont(resource) => resource.id,
+            SidDecodedResource::Mesh(resource) => resource.id,
+            SidDecodedResource::Curve(resource) => resource.id,
+            SidDecodedResource::Material(resource) => resource.id,
+            SidDecodedResource::Scene(resource) => resource.id,
+        }
+    }
+
+    pub fn resource_type(&self) -> SidResourceType {
+        match self {
+            SidDecodedResource::SpriteSheet(_) => SidResourceType::SpriteSheet,
+            SidDecodedResource::AnimationDef(_) => SidResourceType::AnimationDef,
+            SidDecodedResource::Animation(_) => SidResourceType::Animation,
+            SidDecodedResource::Slice(_) => SidResourceType::Slice,
+            SidDecodedResource::Audio(_) => SidResourceType::Audio,
+            SidDecodedResource::Font(_) => SidResourceType::Font,
+            SidDecodedResource::Tileset(_) => SidResourceType::Tileset,
+            SidDecodedResource::Tilemap(_) => SidResourceType::Tilemap,
+            SidDecodedResource::Shader(_) => SidResourceType::Shader,
+            SidDecodedResource::Blob(_) => SidResourceType::Blob,
+            SidDecodedResource::Prefab(_) => SidResourceType::Prefab,
+            SidDecodedResource::StringTable(_) => SidResourceType::StringTable,
+            SidDecodedResource::Texture(_) => SidResourceType::Texture,
+            SidDecodedResource::Palette(_) => SidResourceType::Palette,
+            SidDecodedResource::NineSlice(_) => SidResourceType::NineSlice,
+            SidDecodedResource::Character(_) => SidResourceType::Character,
+            SidDecodedResource::Skeleton(_) => SidResourceType::Skeleton,
+            SidDecodedResource::SkeletalAnimation(_) => SidResourceType::SkeletalAnimation,
+            SidDecodedResource::BitmapFont(_) => SidResourceType::BitmapFont,
+            SidDecodedResource::Mesh(_) => SidResourceType::Mesh,
+            SidDecodedResource::Curve(_) => SidResourceType::Curve,
+            SidDecodedResource::Material(_) => SidResourceType::Material,
+            SidDecodedResource::Scene(_) => SidResourceType::Scene,
+        }
+    }
+}
+
+/// Decodes an uncompressed resource, after checking its header and checksum.
+pub fn decode_resource(data: &[u8]) -> SidToolResult<SidDecodedResource> {
+    let header = SidResourceHeader::check(data)?;
+    let data = header.check_checksum(data)?;
+    let resource_type = header.resource_type;
+    let mut reader = SidResourceReader::with_endianness(data, header.endianness);
+    reader.read_bytes(RESOURCE_HEADER_SIZE)?;
+
+    let resource = match resource_type {
+        SidResourceType::SpriteSheet => {
+            let id = reader.read_id()?;
+            let pivot = read_pivot(&mut reader)?;
+            let normal_map_id = read_texture_ref(&mut reader)?;
+            let emissive_map_id = read_texture_ref(&mut reader)?;
+            let image = SidDecodedImage::read(&mut reader)?;
+
+            SidDecodedResource::SpriteSheet(SidDecodedSpriteSheet {
+                id,
+                pivot,
+                normal_map_id,
+                emissive_map_id,
+                image,
+            })
+        }
+        SidResourceType::Texture => {
+            let id = reader.read_id()?;
+            let image = SidDecodedImage::read(&mut reader)?;
+
+            SidDecodedResource::Texture(SidDecodedTexture { id, image })
+        }
+        SidResourceType::Palette => {
+            let id = reader.read_id()?;
+            let color_count = reader.read_u16()?;
+            let mut colors = Vec::with_capacity(reader.capacity(color_count as usize));
+
+            for _ in 0..color_count {
+                let color = reader.read_bytes(4)?;
+                colors.push([color[0], color[1], color[2], color[3]]);
+            }
+
+            SidDecodedResource::Palette(SidDecodedPalette { id, colors })
+        }
+        SidResourceType::AnimationDef => {
+            let id = reader.read_id()?;
+            let sheet_id = reader.read_id()?;
+            let page_count = reader.read_u16()?;
+            let mut page_ids = Vec::with_capacity(reader.capacity(page_count as usize));
+
+            for _ in 0..page_count {
+                page_ids.push(reader.read_id()?);
+            }
+
+            let frame_count = reader.read_u16()?;
+            let region_count = reader.read_u16()?;
+            let mut regions = Vec::with_capacity(reader.capacity(region_count as usize));
+
+            for region in 0..region_count {
+                regions.push(SidDecodedFrame {
+                    region,
+                    x: reader.read_u16()?,
+                    y: reader.read_u16()?,
+                    width: reader.read_u16()?,
+                    height: reader.read_u16()?,
+                    duration: 0,
+                    offset_x: reader.read_u16()?,
+                    offset_y: reader.read_u16()?,
+                    source_width: reader.read_u16()?,
+                    source_height: reader.read_u16()?,
+                    rotated: reader.read_u8()? != 0,
+                    pivot: None,
+                    page: reader.read_u16()?,
+                });
+            }
+
+            let mut frames = Vec::with_capacity(reader.capacity(frame_count as usize));
+
+            for _ in 0..frame_count {
+                let region = reader.read_u16()?;
+                let duration = reader.read_u16()?;
+                let pivot = read_pivot(&mut reader)?;
+
+                let region = regions.get(region as usize).ok_or_else(|| {
+                    SidToolError::Malformed(format!(
+                        "Frame region {region} out of {region_count} region(s)"
+                    ))
+                })?;
+
+                frames.push(SidDecodedFrame {
+                    duration,
+                    pivot,
+                    ..region.clone()
+                });
+            }
+
+            SidDecodedResource::AnimationDef(SidDecodedAnimationDef {
+                id,
+                sheet_id,
+                page_ids,
+                frames,
+            })
+        }
+        SidResourceType::Animation => {
+            let id = reader.read_id()?;
+            let def_id = reader.read_id()?;
+            let offset = reader.read_u16()?;
+            let length = reader.read_u16()?;
+            let direction = reader.read_u8()?;
+            let looping = reader.read_u8()? != 0;
+            let speed_scale = reader.read_f32()?;
+            let event_count = reader.read_u16()?;
+            let mut events = Vec::with_capacity(reader.capacity(event_count as usize));
+
+            for _ in 0..event_count {
+                events.push((reader.read_u16()?, reader.read_string()?));
+            }
+
+            SidDecodedResource::Animation(SidDecodedAnimation {
+                id,
+                def_id,
+                offset,
+                length,
+                direction,
+                looping,
+                speed_scale,
+                events,
+            })
+        }
+        SidResourceType::Slice => {
+            let id = reader.read_id()?;
+            let sheet_id = reader.read_id()?;
+            let key_count = reader.read_u16()?;
+            let mut keys = Vec::with_capacity(reader.capacity(key_count as usize));
+
+            for _ in 0..key_count {
+                let frame = reader.read_u16()?;
+                let flags = reader.read_u8()?;
+                let bounds = read_slice_rect(&mut reader)?;
+                let center = read_slice_rect(&mut reader)?;
+                let pivot = (reader.read_i32()?, reader.read_i32()?);
+
+                keys.push(SidDecodedSliceKey {
+                    frame,
+                    bounds,
+                    center: (flags & 1 != 0).then_some(center),
+                    pivot: (flags & 2 != 0).then_some(pivot),
+                });
+            }
+
+            SidDecodedResource::Slice(SidDecodedSlice { id, sheet_id, keys })
+        }
+        SidResourceType::Character => {
+            let id = reader.read_id()?;
+            let part_count = reader.read_u16()?;
+            let mut parts = Vec::with_capacity(reader.capacity(part_count as usize));
+
+            for _ in 0..part_count {
+                let name = reader.read_string()?;
+                let sheet_id = reader.read_id()?;
+                let def_id = reader.read_id()?;
+                let animation_count = reader.read_u16()?;
+                let mut animation_ids =
+                    Vec::with_capacity(reader.capacity(animation_count as usize));
+
+                for _ in 0..animation_count {
+                    animation_ids.push(reader.read_id()?);
+                }
+
+                parts.push(SidDecodedCharacterPart {
+                    name,
+                    sheet_id,
+                    def_id,
+                    animation_ids,
+                });
+            }
+
+            SidDecodedResource::Character(SidDecodedCharacter { id, parts })
+        }
+        SidResourceType::Skeleton => {
+            let id = reader.read_id()?;
+            let bone_count = reader.read_u16()?;
+            let mut bones = Vec::with_capacity(reader.capacity(bone_count as usize));
+
+            for _ in 0..bone_count {
+                let name = reader.read_string()?;
+                let has_parent = reader.read_u8()? != 0;
+                let parent = reader.read_u16()?;
+
+                bones.push(SidDecodedSkeletonBone {
+                    name,
+                    parent: has_parent.then_some(parent),
+                    length: reader.read_f32()?,
+                    transform: read_bone_transform(&mut reader)?,
+                });
+            }
+
+            let slot_count = reader.read_u16()?;
+            let mut slots = Vec::with_capacity(reader.capacity(slot_count as usize));
+
+            for _ in 0..slot_count {
+                let name = reader.read_string()?;
+                let bone = reader.read_u16()?;
+                let attachment = read_optional_string(&mut reader)?;
+                let color = reader.read_array()?;
+
+                slots.push(SidDecodedSkeletonSlot {
+                    name,
+                    bone,
+                    attachment,
+                    color,
+                });
+            }
+
+            let attachment_count = reader.read_u16()?;
+            let mut attachments = Vec::with_capacity(reader.capacity(attachment_count as usize));
+
+            for _ in 0..attachment_count {
+                attachments.push(SidDecodedSkeletonAttachment {
+                    slot: reader.read_u16()?,
+                    name: reader.read_string()?,
+                    region: reader.read_string()?,
+                    transform: read_bone_transform(&mut reader)?,
+                    width: reader.read_f32()?,
+                    height: reader.read_f32()?,
+                });
+            }
+
+            SidDecodedResource::Skeleton(SidDecodedSkeleton {
+                id,
+                bones,
+                slots,
+                attachments,
+            })
+        }
+        SidResourceType::SkeletalAnimation => {
+            let id = reader.read_id()?;
+            let skeleton_id = reader.read_id()?;
+            let duration = reader.read_f32()?;
+            let bone_timeline_count = reader.read_u16()?;
+            let mut bones = Vec::with_capacity(reader.capacity(bone_timeline_count as usize));
+
+            for _ in 0..bone_timeline_count {
+                let bone = reader.read_string()?;
+                let property = reader.read_u8()?;
+                let key_count = reader.read_u16()?;
+                let mut keys = Vec::with_capacity(reader.capacity(key_count as usize));
+
+                for _ in 0..key_count {
+                    let time = reader.read_f32()?;
+                    let values = [reader.read_f32()?, reader.read_f32()?];
+                    let curve = reader.read_u8()?;
+
+                    let curve_points = match curve {
+                        KEY_CURVE_BEZIER => Some([
+                            reader.read_f32()?,
+                            reader.read_f32()?,
+                            reader.read_f32()?,
+                            reader.read_f32()?,
+                        ]),
+                        _ => None,
+                    };
+
+                    keys.push(SidDecodedBoneKey {
+                        time,
+                        values,
+                        curve,
+                        curve_points,
+                    });
+                }
+
+                bones.push(SidDecodedBoneTimeline {
+                    bone,
+                    property,
+                    keys,
+                });
+            }
+
+            let slot_timeline_count = reader.read_u16()?;
+            let mut slots = Vec::with_capacity(reader.capacity(slot_timeline_count as usize));
+
+            for _ in 0..slot_timeline_count {
+                let slot = reader.read_string()?;
+                let key_count = reader.read_u16()?;
+                let mut keys = Vec::with_capacity(reader.capacity(key_count as usize));
+
+                for _ in 0..key_count {
+                    keys.push((reader.read_f32()?, read_optional_string(&mut reader)?));
+                }
+
+                slots.push(SidDecodedSlotTimeline { slot, keys });
+            }
+
+            SidDecodedResource::SkeletalAnimation(SidDecodedSkeletalAnimation {
+                id,
+                skeleton_id,
+                duration,
+                bones,
+                slots,
+            })
+        }
+        SidResourceType::NineSlice => SidDecodedResource::NineSlice(SidDecodedNineSlice {
+            id: reader.read_id()?,
+            sheet_id: reader.read_id()?,
+            region: read_slice_rect(&mut reader)?,
+            insets: [
+                reader.read_u32()?,
+                reader.read_u32()?,
+                reader.read_u32()?,
+                reader.read_u32()?,
+            ],
+        }),
+        SidResourceType::Audio => {
+            let id = reader.read_id()?;
+            let sample_rate = reader.read_u32()?;
+            let channel_count = reader.read_u16()?;
+            let format = reader.read_i32()?;
+            let payload_size = reader.read_u64()? as usize;
+            let mut payload = reader.read_bytes(payload_size)?.to_vec();
+
+            let is_pcm16 = format == sid_audio_format::SID_AUDIO_FORMAT_PCM16 as i32;
+
+            if is_pcm16 && header.endianness == SidEndianness::Big {
+                payload
+                    .chunks_exact_mut(2)
+                    .for_each(|sample| sample.swap(0, 1));
+            }
+
+            SidDecodedResource::Audio(SidDecodedAudio {
+                id,
+                sample_rate,
+                channel_count,
+                format,
+                payload,
+            })
+        }
+        SidResourceType::Font => {
+            let id = reader.read_id()?;
+            let size = reader.read_f32()?;
+            let ascent = reader.read_f32()?;
+            let descent = reader.read_f32()?;
+            let line_gap = reader.read_f32()?;
+            let glyph_count = reader.read_u32()?;
+            let mut glyphs = Vec::with_capacity(glyph_count.min(u16::MAX as u32) as usize);
+
+            for _ in 0..glyph_count {
+                glyphs.push(SidDecodedGlyph {
+                    character: reader.read_u32()?,
+                    x: reader.read_u32()?,
+                    y: reader.read_u32()?,
+                    width: reader.read_u32()?,
+                    height: reader.read_u32()?,
+                    offset_x: reader.read_i32()?,
+                    offset_y: reader.read_i32()?,
+                    advance: reader.read_f32()?,
+                });
+            }
+
+            let atlas_width = reader.read_u32()?;
+            let atlas_height = reader.read_u32()?;
+            let channel_count = reader.read_u8()?;
+            let format = reader.read_i32()?;
+            let texture_size = reader.read_u64()? as usize;
+            let texture = reader.read_bytes(texture_size)?.to_vec();
+
+            SidDecodedResource::Font(SidDecodedFont {
+                id,
+                size,
+                ascent,
+                descent,
+                line_gap,
+                glyphs,
+                atlas_width,
+                atlas_height,
+                channel_count,
+                format,
+                texture,
+            })
+        }
+        SidResourceType::BitmapFont => {
+            let id = reader.read_id()?;
+            let size = reader.read_f32()?;
+            let line_height = reader.read_u32()?;
+            let base = reader.read_u32()?;
+            let page_count = reader.read_u16()?;
+            let mut page_ids = Vec::with_capacity(reader.capacity(page_count as usize));
+
+            for _ in 0..page_count {
+                page_ids.push(reader.read_id()?);
+            }
+
+            let glyph_count = reader.read_u32()?;
+            let mut glyphs = Vec::with_capacity(reader.capacity(glyph_count as usize));
+
+            for _ in 0..glyph_count {
+                glyphs.push(SidDecodedBitmapFontGlyph {
+                    character: reader.read_u32()?,
+                    x: reader.read_u32()?,
+                    y: reader.read_u32()?,
+                    width: reader.read_u32()?,
+                    height: reader.read_u32()?,
+                    x_offset: reader.read_i32()?,
+                    y_offset: reader.read_i32()?,
+                    x_advance: reader.read_i32()?,
+                    page: reader.read_u8()?,
+                });
+            }
+
+            let kerning_count = reader.read_u32()?;
+            let mut kernings = Vec::with_capacity(reader.capacity(kerning_count as usize));
+
+            for _ in 0..kerning_count {
+                kernings.push((reader.read_u32()?, reader.read_u32()?, reader.read_i32()?));
+            }
+
+            SidDecodedResource::BitmapFont(SidDecodedBitmapFont {
+                id,
+                size,
+                line_height,
+                base,
+                page_ids,
+                glyphs,
+                kernings,
+            })
+        }
+        SidResourceType::Mesh => {
+            let id = reader.read_id()?;
+            let vertex_count = reader.read_u32()?;
+            let stride = reader.read_u16()?;
+
+            if stride != MESH_VERTEX_STRIDE {
+                return Err(SidToolError::Malformed(format!(
+                    "Unsupported vertex stride {stride} of mesh {id}"
+                )));
+            }
+
+            let mut vertices = Vec::with_capacity(reader.capacity(vertex_count as usize));
+
+            for _ in 0..vertex_count {
+                let mut vertex = [0.0; 8];
+
+                for value in &mut vertex {
+                    *value = reader.read_f32()?;
+                }
+
+                vertices.push(vertex);
+            }
+
+            let index_count = reader.read_u32()?;
+            let index_size = reader.read_u8()?;
+            let mut indices = Vec::with_capacity(reader.capacity(index_count as usize));
+
+            for _ in 0..index_count {
+                indices.push(match index_size {
+                    2 => reader.read_u16()? as u32,
+                    4 => reader.read_u32()?,
+                    size => {
+                        return Err(SidToolError::Malformed(format!(
+                            "Unsupported index size {size} of mesh {id}"
+                        )))
+                    }
+                });
+            }
+
+            let submesh_count = reader.read_u16()?;
+            let mut submeshes = Vec::with_capacity(reader.capacity(submesh_count as usize));
+
+            for _ in 0..submesh_count {
+                let first_index = reader.read_u32()?;
+                let index_count = reader.read_u32()?;
+
+                let texture = match reader.read_u8()? {
+                    MESH_TEXTURE_NONE => None,
+                    kind => Some((kind, reader.read_id()?)),
+                };
+
+                submeshes.push(SidDecodedMeshSubmesh {
+                    first_index,
+                    index_count,
+                    texture,
+                });
+            }
+
+            SidDecodedResource::Mesh(SidDecodedMesh {
+                id,
+                vertices,
+                indices,
+                index_size,
+                submeshes,
+            })
+        }
+        SidResourceType::Curve => {
+            let id = reader.read_id()?;
+            let interpolation = reader.read_u8()?;
+            let dimension = reader.read_u8()?;
+
+            let data = match reader.read_u8()? {
+                CURVE_ENCODING_KEYED => {
+                    let key_count = reader.read_u32()?;
+                    let mut keys = Vec::with_capacity(reader.capacity(key_count as usize));
+
+                    for _ in 0..key_count {
+                        let time = reader.read_f32()?;
+                        keys.push((time, read_curve_values(&mut reader, dimension)?));
+                    }
+
+                    SidDecodedCurveData::Keyed(keys)
+                }
+                CURVE_ENCODING_SAMPLED => {
+                    let sample_rate = reader.read_f32()?;
+                    let start_time = reader.read_f32()?;
+                    let sample_count = reader.read_u32()?;
+                    let mut samples = Vec::with_capacity(reader.capacity(sample_count as usize));
+
+                    for _ in 0..sample_count {
+                        samples.push(read_curve_values(&mut reader, dimension)?);
+                    }
+
+                    SidDecodedCurveData::Sampled {
+                        sample_rate,
+                        start_time,
+                        samples,
+                    }
+                }
+                encoding => {
+                    return Err(SidToolError::Malformed(format!(
+                        "Unknown encoding {encoding} of curve {id}"
+                    )))
+                }
+            };
+
+            SidDecodedResource::Curve(SidDecodedCurve {
+                id,
+                interpolation,
+                dimension,
+                data,
+            })
+        }
+        SidResourceType::Tileset => SidDecodedResource::Tileset(SidDecodedTileset {
+            id: reader.read_id()?,
+            sheet_id: reader.read_id()?,
+            tile_width: reader.read_u32()?,
+            tile_height: reader.read_u32()?,
+            tile_count: reader.read_u32()?,
+            columns: reader.read_u32()?,
+            spacing: reader.read_u32()?,
+            margin: reader.read_u32()?,
+        }),
+        SidResourceType::Tilemap => {
+            let id = reader.read_id()?;
+            let width = reader.read_u32()?;
+            let height = reader.read_u32()?;
+            let tile_width = reader.read_u32()?;
+            let tile_height = reader.read_u32()?;
+            let tileset_count = reader.read_u16()?;
+            let mut tilesets = Vec::with_capacity(reader.capacity(tileset_count as usize));
+
+            for _ in 0..tileset_count {
+                tilesets.push((reader.read_u32()?, reader.read_id()?));
+            }
+
+            let layer_count = reader.read_u16()?;
+            let tile_count = width as usize * height as usize;
+            let mut layers = Vec::with_capacity(reader.capacity(layer_count as usize));
+
+            for _ in 0..layer_count {
+                let flags = reader.read_u8()?;
+                let tile_size = reader.read_u8()?;
+                let mut tiles = Vec::with_capacity(reader.capacity(tile_count));
+
+                for _ in 0..tile_count {
+                    tiles.push(match tile_size {
+                        2 => reader.read_u16()? as sid::SidTileGid,
+                        4 => reader.read_u32()?,
+                        _ => {
+                            return Err(SidToolError::Malformed(format!(
+                                "Invalid tile size {tile_size} in tilemap {id}"
+                            )))
+                        }
+                    });
+                }
+
+                layers.push(SidDecodedTilemapLayer {
+                    visible: flags & TILEMAP_LAYER_FLAG_VISIBLE != 0,
+                    tiles,
+                });
+            }
+
+            SidDecodedResource::Tilemap(SidDecodedTilemap {
+                id,
+                width,
+                height,
+                tile_width,
+                tile_height,
+                tilesets,
+                layers,
+            })
+        }
+        SidResourceType::Shader => {
+            let id = reader.read_id()?;
+            let format = reader.read_i32()?;
+            let stage_count = reader.read_u16()?;
+            let is_spirv = format == sid_shader_format::SID_SHADER_FORMAT_SPIRV as i32;
+            let mut stages = Vec::with_capacity(reader.capacity(stage_count as usize));
+
+            for _ in 0..stage_count {
+                let stage = reader.read_i32()?;
+                let code_size = reader.read_u64()? as usize;
+                let mut code = reader.read_bytes(code_size)?.to_vec();
+
+                if is_spirv && header.endianness == SidEndianness::Big {
+                    code.chunks_exact_mut(4).for_each(|word| word.reverse());
+                }
+
+                stages.push(SidDecodedShaderStage { stage, code });
+            }
+
+            SidDecodedResource::Shader(SidDecodedShader { id, format, stages })
+        }
+        SidResourceType::Material => {
+            let id = reader.read_id()?;
+            let shader_id = reader.read_id()?;
+            let blend_mode = reader.read_u8()?;
+            let texture_count = reader.read_u16()?;
+            let mut textures = Vec::with_capacity(reader.capacity(texture_count as usize));
+
+            for _ in 0..texture_count {
+                textures.push((reader.read_string()?, reader.read_id()?));
+            }
+
+            let uniform_count = reader.read_u16()?;
+            let mut uniforms = Vec::with_capacity(reader.capacity(uniform_count as usize));
+
+            for _ in 0..uniform_count {
+                let uniform = reader.read_string()?;
+                let component_count = reader.read_u8()?;
+                let components = (0..component_count)
+                    .map(|_| reader.read_f32())
+                    .collect::<SidToolResult<_>>()?;
+
+                uniforms.push((uniform, components));
+            }
+
+            SidDecodedResource::Material(SidDecodedMaterial {
+                id,
+                shader_id,
+                blend_mode,
+                textures,
+                uniforms,
+            })
+        }
+        SidResourceType::Blob => {
+            let id = reader.read_id()?;
+            let size = reader.read_u64()? as usize;
+            let data = reader.read_bytes(size)?.to_vec();
+
+            SidDecodedResource::Blob(SidDecodedBlob { id, data })
+        }
+        SidResourceType::Prefab => {
+            let id = reader.read_id()?;
+            let components = read_prefab_components(&mut reader)?;
+            SidDecodedResource::Prefab(SidDecodedPrefab { id, components })
+        }
+        SidResourceType::Scene => {
+            let id = reader.read_id()?;
+            let entity_count = reader.read_u32()? as usize;
+            let mut entities = Vec::with_capacity(reader.capacity(entity_count));
+
+            for _ in 0..entity_count {
+                entities.push(SidDecodedSceneEntity {
+                    name: read_optional_string(&mut reader)?,
+                    prefab_id: reader.read_id()?,
+                    transform: read_bone_transform(&mut reader)?,
+                    layer: reader.read_i32()?,
+                    overrides: read_prefab_components(&mut reader)?,
+                });
+            }
+
+            SidDecodedResource::Scene(SidDecodedScene { id, entities })
+        }
+        SidResourceType::StringTable => {
+            let id = reader.read_id()?;
+            let locale_count = reader.read_u16()?;
+            let key_count = reader.read_u32()? as usize;
+
+            let key_ids = (0..key_count)
+                .map(|_| reader.read_id())
+                .collect::<SidToolResult<Vec<_>>>()?;
+
+            let mut locales = Vec::with_capacity(reader.capacity(locale_count as usize));
+
+            for _ in 0..locale_count {
+                let locale = reader.read_string()?;
+
+                let offsets = (0..key_count)
+                    .map(|_| reader.read_u32())
+                    .collect::<SidToolResult<Vec<_>>>()?;
+
+                let data_size = reader.read_u32()? as usize;
+                let data = reader.read_bytes(data_size)?;
+
+                let strings = offsets
+                    .iter()
+                    .map(|&offset| {
+                        let string = data
+                            .get(offset as usize..)
+                            .and_then(|data| data.split(|&byte| byte == 0).next())
+                            .ok_or_else(|| {
+                                SidToolError::Malformed(format!(
+                                    "Invalid string offset {offset} in locale {:?}",
+                                    locale
+                                ))
+                            })?;
+
+                        String::from_utf8(string.to_vec()).map_err(|_| {
+                            SidToolError::Malformed(format!(
+                                "Invalid UTF-8 string at offset {offset} in locale {:?}",
+                                locale
+                            ))
+                        })
+                    })
+                    .collect::<SidToolResult<Vec<_>>>()?;
+
+                locales.push((locale, strings));
+            }
+
+            SidDecodedResource::StringTable(SidDecodedStringTable {
+                id,
+                key_ids,
+                locales,
+            })
+        }
+    };
+
+    if reader.remaining() > 0 {
+        return Err(SidToolError::Malformed(format!(
+            "{} unexpected trailing byte(s) in {:?} resource",
+            reader.remaining(),
+            resource_type
+        )));
+    }
+
+    Ok(resource)
+}
+
+fn read_slice_rect(reader: &mut SidResourceReader) -> SidToolResult<SidDecodedSliceRect> {
+    Ok(SidDecodedSliceRect {
+        x: reader.read_i32()?,
+        y: reader.read_i32()?,
+        width: reader.read_u32()?,
+        height: reader.read_u32()?,
+    })
+}
+
+fn read_pivot(
+    reader: &mut SidResourceReader,
+) -> SidToolResult<Option<(sid::SidSliceCoord, sid::SidSliceCoord)>> {
+    let has_pivot = reader.read_u8()? != 0;
+    let pivot = (reader.read_i32()?, reader.read_i32()?);
+    Ok(has_pivot.then_some(pivot))
+}
+
+fn read_texture_ref(reader: &mut SidResourceReader) -> SidToolResult<Option<sid::SidTextureId>> {
+    let has_texture = reader.read_u8()? != 0;
+    let id = reader.read_id()?;
+    Ok(has_texture.then_some(id))
+}
+
+fn read_bone_transform(reader: &mut SidResourceReader) -> SidToolResult<SidDecodedBoneTransform> {
+    Ok([
+        reader.read_f32()?,
+        reader.read_f32()?,
+        reader.read_f32()?,
+        reader.read_f32()?,
+        reader.read_f32()?,
+    ])
+}
+
+fn read_optional_string(reader: &mut SidResourceReader) -> SidToolResult<Option<String>> {
+    let has_string = reader.read_u8()? != 0;
+    let string = reader.read_string()?;
+    Ok(has_string.then_some(string))
+}
+
+fn read_curve_values(reader: &mut SidResourceReader, dimension: u8) -> SidToolResult<Vec<f32>> {
+    (0..dimension).map(|_| reader.read_f32()).collect()
+}
+
+fn read_prefab_components(
+    reader: &mut SidResourceReader,
+) -> SidToolResult<Vec<SidDecodedPrefabComponent>> {
+    let component_count = reader.read_u16()?;
+    let mut components = Vec::with_capacity(reader.capacity(component_count as usize));
+
+    for _ in 0..component_count {
+        let component = match reader.read_i32()? {
+            x if x == sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_SPRITE as i32 => {
+                SidDecodedPrefabComponent::Sprite {
+                    sheet_id: reader.read_id()?,
+                }
+            }
+            x if x == sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_ANIMATION as i32 => {
+                SidDecodedPrefabComponent::Animation {
+                    animation_id: reader.read_id()?,
+                }
+            }
+            x if x == sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_COLLIDER as i32 => {
+                SidDecodedPrefabComponent::Collider {
+                    x: reader.read_f32()?,
+                    y: reader.read_f32()?,
+                    width: reader.read_f32()?,
+                    height: reader.read_f32()?,
+                    is_trigger: reader.read_u8()? != 0,
+                }
+            }
+            x if x == sid_prefab_component_type::SID_PREFAB_COMPONENT_TYPE_CUSTOM as i32 => {
+                let value_count = reader.read_u16()?;
+                let mut values = Vec::with_capacity(reader.capacity(value_count as usize));
+
+                for _ in 0..value_count {
+                    values.push((reader.read_string()?, reader.read_string()?));
+                }
+
+                SidDecodedPrefabComponent::Custom { values }
+            }
+            component_type => {
+                return Err(SidToolError::Malformed(format!(
+                    "Unknown prefab component type {component_type}"
+                )))
+            }
+        };
+
+        components.push(component);
+    }
+
+    Ok(components)
+}
+
+/// Decodes every resource of a .sidpak archive. Also returns the pak version.
+pub fn decode_pak(data: &[u8]) -> SidToolResult<(u32, Vec<SidDecodedResource>)> {
+    let (version, entries) = pak::read_pak(data)?;
+    let mut resources = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let resource = read_resource(&entry.data)?;
+
+        if resource.resource_type() != entry.resource_type {
+            return Err(SidToolError::Malformed(format!(
+                "Resource {} is a {:?} in the pak table, but a {:?} in its header",
+                entry.id,
+                entry.resource_type,
+                resource.resource_type()
+            )));
+        }
+
+        resources.push(resource);
+    }
+
+    Ok((version, resources))
+}
+
+/// Decodes a resource as stored, compressed or not.
+pub fn read_resource(data: &[u8]) -> SidToolResult<SidDecodedResource> {
+    decode_resource(&compression::decompress(data)?)
+}
+
+fn unexpected_type(resource: &SidDecodedResource, expected: SidResourceType) -> SidToolError {
+    SidToolError::UnsupportedFormat(format!(
+        "Expected a {:?} resource, got a {:?} one",
+        expected,
+        resource.resource_type()
+    ))
+}
+
+/// Decodes a sprite sheet resource as stored, compressed or not.
+pub fn read_sprite_sheet(data: &[u8]) -> SidToolResult<SidDecodedSpriteSheet> {
+    match read_resource(data)? {
+        SidDecodedResource::SpriteSheet(sheet) => Ok(sheet),
+        resource => Err(unexpected_type(&resource, SidResourceType::SpriteSheet)),
+    }
+}
+
+/// Decodes an animation def resource as stored, compressed or not.
+pub fn read_animation_def(data: &[u8]) -> SidToolResult<SidDecodedAnimationDef> {
+    match read_resource(data)? {
+        SidDecodedResource::AnimationDef(def) => Ok(def),
+        resource => Err(unexpected_type(&resource, SidResourceType::AnimationDef)),
+    }
+}
+
+/// Decodes an animation resource as stored, compressed or not.
+pub fn read_animation(data: &[u8]) -> SidToolResult<SidDecodedAnimation> {
+    match read_resource(data)? {
+        SidDecodedResource::Animation(animation) => Ok(animation),
+        resource => Err(unexpected_type(&resource, SidResourceType::Animation)),
+    }
+}
@@ -0,0 +1,948 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::path::Path;
+
+use crate::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAudioAsset, SidBitmapFontAsset, SidBlobAsset,
+        SidCharacterAsset, SidCurveAsset, SidFontAsset, SidMaterialAsset, SidMeshAsset,
+        SidMeshTexture, SidNineSliceAsset, SidPackedAsset, SidPaletteAsset, SidPrefabAsset,
+        SidPrefabComponentAsset, SidSceneAsset, SidShaderAsset, SidSkeletalAnimationAsset,
+        SidSkeletonAsset, SidSliceAsset, SidSliceRect, SidSpriteSheetAsset, SidStringTableAsset,
+        SidTextureAsset, SidTilemapAsset, SidTilesetAsset,
+    },
+    resource::SidAssetSerializationError,
+};
+
+/// Name of another asset an asset depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SidAssetReference {
+    /// Field holding the reference, as spelled in the asset JSON.
+    pub field: &'static str,
+    /// Namespace of the referenced asset.
+    pub namespace: &'static str,
+    pub name: String,
+}
+
+impl SidAssetReference {
+    pub fn new(field: &'static str, namespace: &'static str, name: &str) -> Self {
+        Self {
+            field,
+            namespace,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Checks of the values of an asset, beyond what its JSON layout enforces.
+pub trait SidAssetSchema {
+    /// Describes every invalid value of the asset loaded from `asset_input_path`.
+    fn violations(&self, asset_input_path: &Path) -> Vec<String>;
+
+    /// Other assets this asset refers to by name.
+    fn references(&self) -> Vec<SidAssetReference> {
+        vec![]
+    }
+}
+
+fn check_name(name: &str, violations: &mut Vec<String>) {
+    if name.trim().is_empty() {
+        violations.push("name must not be empty".to_string());
+    }
+}
+
+fn check_path(field: &str, path: &Path, violations: &mut Vec<String>) {
+    if path.as_os_str().is_empty() {
+        violations.push(format!("{field} must not be empty"));
+    }
+}
+
+fn check_rect(field: &str, rect: &SidSliceRect, violations: &mut Vec<String>) {
+    if rect.width == 0 || rect.height == 0 {
+        violations.push(format!(
+            "{field} must have a non-zero size, got {}x{}",
+            rect.width, rect.height
+        ));
+    }
+}
+
+impl SidAssetSchema for SidSpriteSheetAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_path("image_path", &self.image_path, &mut violations);
+
+        if self.width == 0 || self.height == 0 {
+            violations.push(format!(
+                "width and height must not be zero, got {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        let mut references: Vec<_> = self
+            .palette
+            .iter()
+            .map(|palette| SidAssetReference::new("palette", SidPaletteAsset::namespace(), palette))
+            .collect();
+
+        for (field, texture) in [
+            ("normal_map", &self.normal_map),
+            ("emissive_map", &self.emissive_map),
+        ] {
+            if let Some(texture) = texture {
+                references.push(SidAssetReference::new(
+                    field,
+                    SidTextureAsset::namespace(),
+                    texture,
+                ));
+            }
+        }
+
+        references
+    }
+}
+
+impl SidAssetSchema for SidAnimationDefAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.frames.is_empty() {
+            violations.push("frames must not be empty".to_string());
+        }
+
+        if self.frame_count as usize != self.frames.len() {
+            violations.push(format!(
+                "frame_count is {} but there are {} frame(s)",
+                self.frame_count,
+                self.frames.len()
+            ));
+        }
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            if frame.dims.width == 0 || frame.dims.height == 0 {
+                violations.push(format!(
+                    "frames[{i}] must have a non-zero size, got {}x{}",
+                    frame.dims.width, frame.dims.height
+                ));
+            }
+
+            if frame.duration == 0 {
+                violations.push(format!("frames[{i}] must have a non-zero duration"));
+            }
+
+            if frame.page as usize > self.pages.len() {
+                violations.push(format!(
+                    "frames[{i}] is on page {} but there are {} extra page(s)",
+                    frame.page,
+                    self.pages.len()
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        let mut references = vec![SidAssetReference::new(
+            "sheet_name",
+            SidSpriteSheetAsset::namespace(),
+            &self.sheet_name,
+        )];
+
+        for page in &self.pages {
+            references.push(SidAssetReference::new(
+                "pages",
+                SidSpriteSheetAsset::namespace(),
+                page,
+            ));
+        }
+
+        references
+    }
+}
+
+impl SidAssetSchema for SidAnimationAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.length == 0 {
+            violations.push("length must not be zero".to_string());
+        }
+
+        if !self.speed_scale.is_finite() || self.speed_scale <= 0.0 {
+            violations.push(format!(
+                "speed_scale must be positive, got {}",
+                self.speed_scale
+            ));
+        }
+
+        for (i, event) in self.events.iter().enumerate() {
+            if event.name.trim().is_empty() {
+                violations.push(format!("events[{i}].name must not be empty"));
+            }
+
+            if event.frame >= self.length {
+                violations.push(format!(
+                    "events[{i}].frame must be lower than the length {}, got {}",
+                    self.length, event.frame
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        vec![SidAssetReference::new(
+            "def_name",
+            SidAnimationDefAsset::namespace(),
+            &self.def_name,
+        )]
+    }
+}
+
+impl SidAssetSchema for SidSliceAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.keys.is_empty() {
+            violations.push("keys must not be empty".to_string());
+        }
+
+        for (i, key) in self.keys.iter().enumerate() {
+            check_rect(&format!("keys[{i}].bounds"), &key.bounds, &mut violations);
+
+            if let Some(center) = &key.center {
+                check_rect(&format!("keys[{i}].center"), center, &mut violations);
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        vec![SidAssetReference::new(
+            "sheet_name",
+            SidSpriteSheetAsset::namespace(),
+            &self.sheet_name,
+        )]
+    }
+}
+
+impl SidAssetSchema for SidNineSliceAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_rect("region", &self.region, &mut violations);
+
+        let insets = &self.insets;
+
+        if u64::from(insets.left) + u64::from(insets.right) > u64::from(self.region.width) {
+            violations.push(format!(
+                "insets.left and insets.right must fit in the region width {}, got {} and {}",
+                self.region.width, insets.left, insets.right
+            ));
+        }
+
+        if u64::from(insets.top) + u64::from(insets.bottom) > u64::from(self.region.height) {
+            violations.push(format!(
+                "insets.top and insets.bottom must fit in the region height {}, got {} and {}",
+                self.region.height, insets.top, insets.bottom
+            ));
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        vec![SidAssetReference::new(
+            "sheet_name",
+            SidSpriteSheetAsset::namespace(),
+            &self.sheet_name,
+        )]
+    }
+}
+
+impl SidAssetSchema for SidCharacterAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.parts.is_empty() {
+            violations.push("parts must not be empty".to_string());
+        }
+
+        for (i, part) in self.parts.iter().enumerate() {
+            if part.name.trim().is_empty() {
+                violations.push(format!("parts[{i}].name must not be empty"));
+            }
+
+            if self.parts[..i].iter().any(|other| other.name == part.name) {
+                violations.push(format!(
+                    "parts[{i}].name must be unique, {:?} is used twice",
+                    part.name
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        self.parts
+            .iter()
+            .flat_map(|part| {
+                [
+                    SidAssetReference::new(
+                        "sheet_name",
+                        SidSpriteSheetAsset::namespace(),
+                        &part.sheet_name,
+                    ),
+                    SidAssetReference::new(
+                        "def_name",
+                        SidAnimationDefAsset::namespace(),
+                        &part.def_name,
+                    ),
+                ]
+                .into_iter()
+                .chain(part.animations.iter().map(|animation| {
+                    SidAssetReference::new("animations", SidAnimationAsset::namespace(), animation)
+                }))
+            })
+            .collect()
+    }
+}
+
+impl SidAssetSchema for SidSkeletonAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.bones.is_empty() {
+            violations.push("bones must not be empty".to_string());
+        }
+
+        for (i, bone) in self.bones.iter().enumerate() {
+            let previous_bones = &self.bones[..i];
+
+            if previous_bones.iter().any(|other| other.name == bone.name) {
+                violations.push(format!(
+                    "bones[{i}].name must be unique, {:?} is used twice",
+                    bone.name
+                ));
+            }
+
+            if let Some(parent) = &bone.parent {
+                if !previous_bones.iter().any(|other| &other.name == parent) {
+                    violations.push(format!(
+                        "bones[{i}].parent must name a bone before it, got {:?}",
+                        parent
+                    ));
+                }
+            }
+        }
+
+        for (i, slot) in self.slots.iter().enumerate() {
+            if self.slots[..i].iter().any(|other| other.name == slot.name) {
+                violations.push(format!(
+                    "slots[{i}].name must be unique, {:?} is used twice",
+                    slot.name
+                ));
+            }
+
+            if !self.bones.iter().any(|bone| bone.name == slot.bone) {
+                violations.push(format!(
+                    "slots[{i}].bone must name a bone, got {:?}",
+                    slot.bone
+                ));
+            }
+
+            if let Some(attachment) = &slot.attachment {
+                if !self
+                    .attachments
+                    .iter()
+                    .any(|other| other.slot == slot.name && &other.name == attachment)
+                {
+                    violations.push(format!(
+                        "slots[{i}].attachment must name an attachment of the slot, got {:?}",
+                        attachment
+                    ));
+                }
+            }
+        }
+
+        for (i, attachment) in self.attachments.iter().enumerate() {
+            if !self.slots.iter().any(|slot| slot.name == attachment.slot) {
+                violations.push(format!(
+                    "attachments[{i}].slot must name a slot, got {:?}",
+                    attachment.slot
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+impl SidAssetSchema for SidSkeletalAnimationAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if !self.duration.is_finite() || self.duration < 0.0 {
+            violations.push(format!(
+                "duration must not be negative, got {}",
+                self.duration
+            ));
+        }
+
+        let bone_key_times = self.bones.iter().enumerate().map(|(i, timeline)| {
+            (
+                format!("bones[{i}]"),
+                timeline.keys.iter().map(|key| key.time).collect::<Vec<_>>(),
+            )
+        });
+
+        let slot_key_times = self.slots.iter().enumerate().map(|(i, timeline)| {
+            (
+                format!("slots[{i}]"),
+                timeline.keys.iter().map(|key| key.time).collect(),
+            )
+        });
+
+        for (field, times) in bone_key_times.chain(slot_key_times) {
+            if times.windows(2).any(|times| times[0] > times[1]) {
+                violations.push(format!("{field}.keys must be sorted by time"));
+            }
+
+            if let Some(time) = times
+                .iter()
+                .find(|&&time| !(0.0..=self.duration).contains(&time))
+            {
+                violations.push(format!(
+                    "{field}.keys must be within the duration, got a key at {time}"
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        vec![SidAssetReference::new(
+            "skeleton_name",
+            SidSkeletonAsset::namespace(),
+            &self.skeleton_name,
+        )]
+    }
+}
+
+impl SidAssetSchema for SidAudioAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_path("audio_path", &self.audio_path, &mut violations);
+        violations
+    }
+}
+
+impl SidAssetSchema for SidFontAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_path("font_path", &self.font_path, &mut violations);
+
+        if !self.size.is_finite() || self.size <= 0.0 {
+            violations.push(format!("size must be positive, got {}", self.size));
+        }
+
+        violations
+    }
+}
+
+impl SidAssetSchema for SidBitmapFontAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if !self.size.is_finite() || self.size <= 0.0 {
+            violations.push(format!("size must be positive, got {}", self.size));
+        }
+
+        if self.pages.is_empty() {
+            violations.push("pages must not be empty".to_string());
+        }
+
+        if self.glyphs.is_empty() {
+            violations.push("glyphs must not be empty".to_string());
+        }
+
+        for (i, glyph) in self.glyphs.iter().enumerate() {
+            if self.glyphs[..i]
+                .iter()
+                .any(|other| other.character == glyph.character)
+            {
+                violations.push(format!(
+                    "glyphs[{i}].character must be unique, {} is used twice",
+                    glyph.character
+                ));
+            }
+
+            if glyph.page as usize >= self.pages.len() {
+                violations.push(format!(
+                    "glyphs[{i}].page must be less than the page count {}, got {}",
+                    self.pages.len(),
+                    glyph.page
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        self.pages
+            .iter()
+            .map(|page| SidAssetReference::new("pages", SidTextureAsset::namespace(), page))
+            .collect()
+    }
+}
+
+impl SidAssetSchema for SidMeshAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.vertices.is_empty() {
+            violations.push("vertices must not be empty".to_string());
+        }
+
+        if !self.indices.len().is_multiple_of(3) {
+            violations.push(format!(
+                "indices must hold three indices per triangle, got {}",
+                self.indices.len()
+            ));
+        }
+
+        if let Some(index) = self
+            .indices
+            .iter()
+            .find(|&&index| index as usize >= self.vertices.len())
+        {
+            violations.push(format!(
+                "indices must be less than the vertex count {}, got {index}",
+                self.vertices.len()
+            ));
+        }
+
+        if self.submeshes.is_empty() {
+            violations.push("submeshes must not be empty".to_string());
+        }
+
+        for (i, submesh) in self.submeshes.iter().enumerate() {
+            if submesh.first_index as usize + submesh.index_count as usize > self.indices.len() {
+                violations.push(format!(
+                    "submeshes[{i}] must be within the {} indices, got {} from {}",
+                    self.indices.len(),
+                    submesh.index_count,
+                    submesh.first_index
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        self.submeshes
+            .iter()
+            .filter_map(|submesh| match &submesh.texture {
+                Some(SidMeshTexture::Texture(name)) => Some(SidAssetReference::new(
+                    "texture",
+                    SidTextureAsset::namespace(),
+                    name,
+                )),
+                Some(SidMeshTexture::SpriteSheet(name)) => Some(SidAssetReference::new(
+                    "texture",
+                    SidSpriteSheetAsset::namespace(),
+                    name,
+                )),
+                None => None,
+            })
+            .collect()
+    }
+}
+
+impl SidAssetSchema for SidCurveAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        let Some(first) = self.keys.first() else {
+            violations.push("keys must not be empty".to_string());
+            return violations;
+        };
+
+        if first.values.is_empty() || first.values.len() > u8::MAX as usize {
+            violations.push(format!(
+                "keys[0].values must hold between 1 and {} values, got {}",
+                u8::MAX,
+                first.values.len()
+            ));
+        }
+
+        for (i, key) in self.keys.iter().enumerate() {
+            if !key.time.is_finite() {
+                violations.push(format!("keys[{i}].time must be finite, got {}", key.time));
+            }
+
+            if key.values.len() != first.values.len() {
+                violations.push(format!(
+                    "keys[{i}].values must hold as many values as the first key, got {}",
+                    key.values.len()
+                ));
+            }
+        }
+
+        if self.keys.windows(2).any(|keys| keys[0].time > keys[1].time) {
+            violations.push("keys must be sorted by time".to_string());
+        }
+
+        if let Some(sample_rate) = self.sample_rate {
+            if !sample_rate.is_finite() || sample_rate <= 0.0 {
+                violations.push(format!("sample_rate must be positive, got {sample_rate}"));
+            }
+        }
+
+        violations
+    }
+}
+
+impl SidAssetSchema for SidTilesetAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.tile_width == 0 || self.tile_height == 0 {
+            violations.push(format!(
+                "tile_width and tile_height must not be zero, got {}x{}",
+                self.tile_width, self.tile_height
+            ));
+        }
+
+        if self.tile_count == 0 {
+            violations.push("tile_count must not be zero".to_string());
+        }
+
+        if self.columns == 0 {
+            violations.push("columns must not be zero".to_string());
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        vec![SidAssetReference::new(
+            "sheet_name",
+            SidSpriteSheetAsset::namespace(),
+            &self.sheet_name,
+        )]
+    }
+}
+
+impl SidAssetSchema for SidTilemapAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.width == 0 || self.height == 0 {
+            violations.push(format!(
+                "width and height must not be zero, got {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        if self.tile_width == 0 || self.tile_height == 0 {
+            violations.push(format!(
+                "tile_width and tile_height must not be zero, got {}x{}",
+                self.tile_width, self.tile_height
+            ));
+        }
+
+        for (i, tileset) in self.tilesets.iter().enumerate() {
+            if tileset.first_gid == 0 {
+                violations.push(format!("tilesets[{i}].first_gid must not be zero"));
+            }
+        }
+
+        let tile_count = self.width as usize * self.height as usize;
+
+        for layer in &self.layers {
+            if layer.tiles.len() != tile_count {
+                violations.push(format!(
+                    "layer {:?} has {} tile(s), expected {tile_count} ({}x{})",
+                    layer.name,
+                    layer.tiles.len(),
+                    self.width,
+                    self.height
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        self.tilesets
+            .iter()
+            .map(|tileset| {
+                SidAssetReference::new(
+                    "tileset_name",
+                    SidTilesetAsset::namespace(),
+                    &tileset.tileset_name,
+                )
+            })
+            .collect()
+    }
+}
+
+impl SidAssetSchema for SidShaderAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_path("vertex_path", &self.vertex_path, &mut violations);
+        check_path("fragment_path", &self.fragment_path, &mut violations);
+        violations
+    }
+}
+
+impl SidAssetSchema for SidMaterialAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        if self.shader_name.trim().is_empty() {
+            violations.push("shader_name must not be empty".to_string());
+        }
+
+        for (sampler, texture) in &self.textures {
+            if sampler.trim().is_empty() {
+                violations.push("textures must not have an empty sampler name".to_string());
+            }
+
+            if texture.trim().is_empty() {
+                violations.push(format!("textures[{sampler:?}] must not be empty"));
+            }
+        }
+
+        for (uniform, value) in &self.uniforms {
+            if uniform.trim().is_empty() {
+                violations.push("uniforms must not have an empty name".to_string());
+            }
+
+            if value
+                .components()
+                .iter()
+                .any(|component| !component.is_finite())
+            {
+                violations.push(format!(
+                    "uniforms[{uniform:?}] must be finite, got {:?}",
+                    value.components()
+                ));
+            }
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        let shader = SidAssetReference::new(
+            "shader_name",
+            SidShaderAsset::namespace(),
+            &self.shader_name,
+        );
+
+        let textures = self.textures.values().map(|texture| {
+            SidAssetReference::new("textures", SidTextureAsset::namespace(), texture)
+        });
+
+        std::iter::once(shader).chain(textures).collect()
+    }
+}
+
+impl SidAssetSchema for SidBlobAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_path("blob_path", &self.blob_path, &mut violations);
+        violations
+    }
+}
+
+fn check_prefab_components(
+    field: &str,
+    components: &[SidPrefabComponentAsset],
+    violations: &mut Vec<String>,
+) {
+    for (i, component) in components.iter().enumerate() {
+        if let SidPrefabComponentAsset::Collider { width, height, .. } = component {
+            let is_valid = |value: &f32| value.is_finite() && *value > 0.0;
+
+            if !is_valid(width) || !is_valid(height) {
+                violations.push(format!(
+                    "{field}[{i}] collider must have a positive size, got {width}x{height}"
+                ));
+            }
+        }
+    }
+}
+
+fn prefab_component_reference(component: &SidPrefabComponentAsset) -> Option<SidAssetReference> {
+    match component {
+        SidPrefabComponentAsset::Sprite { sheet_name } => Some(SidAssetReference::new(
+            "sheet_name",
+            SidSpriteSheetAsset::namespace(),
+            sheet_name,
+        )),
+        SidPrefabComponentAsset::Animation { animation_name } => Some(SidAssetReference::new(
+            "animation_name",
+            SidAnimationAsset::namespace(),
+            animation_name,
+        )),
+        _ => None,
+    }
+}
+
+impl SidAssetSchema for SidPrefabAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_prefab_components("components", &self.components, &mut violations);
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        self.components
+            .iter()
+            .filter_map(prefab_component_reference)
+            .collect()
+    }
+}
+
+impl SidAssetSchema for SidSceneAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        for (i, entity) in self.entities.iter().enumerate() {
+            if let Some(name) = &entity.name {
+                if self.entities[..i]
+                    .iter()
+                    .any(|other| other.name.as_ref() == Some(name))
+                {
+                    violations.push(format!(
+                        "entities[{i}].name must be unique, {:?} is used twice",
+                        name
+                    ));
+                }
+            }
+
+            if entity.prefab_name.trim().is_empty() {
+                violations.push(format!("entities[{i}].prefab_name must not be empty"));
+            }
+
+            let transform = &entity.transform;
+
+            if [
+                transform.x,
+                transform.y,
+                transform.rotation,
+                transform.scale_x,
+                transform.scale_y,
+            ]
+            .iter()
+            .any(|value| !value.is_finite())
+            {
+                violations.push(format!("entities[{i}] transform must be finite"));
+            }
+
+            check_prefab_components(
+                &format!("entities[{i}].overrides"),
+                &entity.overrides,
+                &mut violations,
+            );
+        }
+
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        self.entities
+            .iter()
+            .flat_map(|entity| {
+                std::iter::once(SidAssetReference::new(
+                    "prefab_name",
+                    SidPrefabAsset::namespace(),
+                    &entity.prefab_name,
+                ))
+                .chain(
+                    entity
+                        .overrides
+                        .iter()
+                        .filter_map(prefab_component_reference),
+                )
+            })
+            .collect()
+    }
+}
+
+impl SidAssetSchema for SidStringTableAsset {
+    fn violations(&self, asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+
+        // Also checks that every locale defines the same keys.
+        match self.load_locales(asset_input_path) {
+            Ok(_) => {}
+            Err(SidAssetSerializationError::UnsupportedFormat(error))
+            | Err(SidAssetSerializationError::Malformed(error))
+            | Err(SidAssetSerializationError::IO(error)) => violations.push(error),
+        }
+
+        violations
+    }
+}
+
+impl SidAssetSchema for SidTextureAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_path("image_path", &self.image_path, &mut violations);
+        violations
+    }
+
+    fn references(&self) -> Vec<SidAssetReference> {
+        self.palette
+            .iter()
+            .map(|palette| SidAssetReference::new("palette", SidPaletteAsset::namespace(), palette))
+            .collect()
+    }
+}
+
+impl SidAssetSchema for SidPaletteAsset {
+    fn violations(&self, _asset_input_path: &Path) -> Vec<String> {
+        let mut violations = vec![];
+        check_name(&self.name, &mut violations);
+        check_path("image_path", &self.image_path, &mut violations);
+        violations
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use std::{
+    fmt::Write as _,
+    io::Write,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    error::{SidToolError, SidToolResult},
+    report::{SidAssetStatus, SidReport},
+    sid::SidStringId,
+    sid_error, sid_info,
+};
+
+// Protocol: engine clients connect over TCP and only read. After every repack
+// updating resources, each client receives a line of text made of `reload`
+// followed by the ids of the updated resources, e.g. "reload 1234 5678\n".
+pub static DEFAULT_SERVE_ADDRESS: &str = "127.0.0.1:7878";
+
+/// Clients not reading their notifications are dropped after this long.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub struct SidReloadServer {
+    address: SocketAddr,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl SidReloadServer {
+    /// Listens for clients on `address`, accepting them in the background.
+    pub fn bind<A: ToSocketAddrs + std::fmt::Debug>(address: A) -> SidToolResult<Self> {
+        let listener = TcpListener::bind(&address).map_err(|error| {
+            SidToolError::IO(format!("Unable to listen on {:?}: {error}", address))
+        })?;
+
+        let address = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(vec![]));
+        let accepted_clients = Arc::clone(&clients);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        sid_error!("Unable to accept a client: {error}");
+                        continue;
+                    }
+                };
+
+                if let Err(error) = stream.set_write_timeout(Some(WRITE_TIMEOUT)) {
+                    sid_error!("Unable to configure a client: {error}");
+                    continue;
+                }
+
+                if let Ok(peer_address) = stream.peer_addr() {
+                    sid_info!("Client connected from {peer_address}.");
+                }
+
+                accepted_clients
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(stream);
+            }
+        });
+
+        sid_info!("Serving resource updates on {address}.");
+        Ok(Self { address, clients })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Sends the ids of the updated resources to every client. Clients that
+    /// disconnected are dropped.
+    pub fn notify(&self, ids: &[SidStringId]) {
+        if ids.is_empty() {
+            return;
+        }
+
+        let mut line = String::from("reload");
+
+        for id in ids {
+            let _ = write!(line, " {id}");
+        }
+
+        line.push('\n');
+
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+
+        sid_info!(
+            "Notified {} client(s) of {} updated resource(s).",
+            clients.len(),
+            ids.len()
+        );
+    }
+}
+
+/// Ids of the resources a run packed, leaving out the unchanged ones.
+pub fn updated_resource_ids(report: &SidReport) -> Vec<SidStringId> {
+    report
+        .assets
+        .iter()
+        .filter(|asset| asset.status == SidAssetStatus::Ok)
+        .flat_map(|asset| asset.ids.iter().copied())
+        .collect()
+}
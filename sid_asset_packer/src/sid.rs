@@ -2,10 +2,27 @@
 // Use of this source code is governed by the MIT
 // license that can be found in the LICENSE file.
 
-use std::{ffi::CStr, ffi::CString};
+use std::{
+    cell::RefCell,
+    ffi::CStr,
+    ffi::CString,
+    marker::PhantomData,
+    sync::{Arc, OnceLock, PoisonError, RwLock},
+};
+
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::id_overrides;
 
 pub type SChar = i8;
+
+#[cfg(not(feature = "wide-ids"))]
 pub type SidStringId = u32;
+#[cfg(feature = "wide-ids")]
+pub type SidStringId = u64;
+
+/// Width of the string ids, in bits.
+pub const STRING_ID_BITS: u32 = SidStringId::BITS;
 
 pub type SidSpriteSheetId = SidStringId;
 pub type SidSpriteSheetDim = u32;
@@ -16,9 +33,62 @@ pub type SidAnimationFrameDim = u16;
 pub type SidAnimationFrameCoord = u16;
 pub type SidAnimationFrameIndex = u16;
 pub type SidAnimationFrameDuration = u16;
+pub type SidAnimationPageIndex = u16;
+
+pub type SidSliceId = SidStringId;
+pub type SidSliceCoord = i32;
+pub type SidSliceDim = u32;
+
+pub type SidAudioId = SidStringId;
+pub type SidAudioSampleRate = u32;
+pub type SidAudioChannelCount = u16;
+
+pub type SidFontId = SidStringId;
+pub type SidFontGlyphCoord = u32;
+pub type SidFontGlyphDim = u32;
+
+pub type SidTilesetId = SidStringId;
+pub type SidTilemapId = SidStringId;
+pub type SidTileDim = u32;
+/// Global tile id of a map: 0 is an empty cell, and the 3 highest bits are the
+/// Tiled flip flags.
+pub type SidTileGid = u32;
+
+pub type SidShaderId = SidStringId;
+
+pub type SidBlobId = SidStringId;
+
+pub type SidPrefabId = SidStringId;
+
+pub type SidStringTableId = SidStringId;
+
+pub type SidStringKeyId = SidStringId;
+
+pub type SidTextureId = SidStringId;
+
+pub type SidPaletteId = SidStringId;
+
+pub type SidNineSliceId = SidStringId;
+
+pub type SidCharacterId = SidStringId;
+
+pub type SidSkeletonId = SidStringId;
+
+pub type SidSkeletalAnimationId = SidStringId;
+
+pub type SidBitmapFontId = SidStringId;
+
+pub type SidMeshId = SidStringId;
+
+pub type SidCurveId = SidStringId;
+
+pub type SidMaterialId = SidStringId;
+
+pub type SidSceneId = SidStringId;
 
 pub type UIndex = u64;
 
+#[cfg(not(feature = "pure-rust"))]
 #[link(name = "sid_lib", kind = "static")]
 extern "C" {
     fn sid_get_animation_namespace() -> *const SChar;
@@ -28,6 +98,48 @@ extern "C" {
     fn sid_generate_animation_id(name: *const SChar) -> SidAnimationId;
     fn sid_get_sprite_sheet_namespace() -> *const SChar;
     fn sid_generate_sprite_sheet_id(name: *const SChar) -> SidSpriteSheetId;
+    fn sid_get_slice_namespace() -> *const SChar;
+    fn sid_generate_slice_id(name: *const SChar) -> SidSliceId;
+    fn sid_get_audio_namespace() -> *const SChar;
+    fn sid_generate_audio_id(name: *const SChar) -> SidAudioId;
+    fn sid_get_font_namespace() -> *const SChar;
+    fn sid_generate_font_id(name: *const SChar) -> SidFontId;
+    fn sid_get_tileset_namespace() -> *const SChar;
+    fn sid_generate_tileset_id(name: *const SChar) -> SidTilesetId;
+    fn sid_get_tilemap_namespace() -> *const SChar;
+    fn sid_generate_tilemap_id(name: *const SChar) -> SidTilemapId;
+    fn sid_get_shader_namespace() -> *const SChar;
+    fn sid_generate_shader_id(name: *const SChar) -> SidShaderId;
+    fn sid_get_blob_namespace() -> *const SChar;
+    fn sid_generate_blob_id(name: *const SChar) -> SidBlobId;
+    fn sid_get_prefab_namespace() -> *const SChar;
+    fn sid_generate_prefab_id(name: *const SChar) -> SidPrefabId;
+    fn sid_get_string_table_namespace() -> *const SChar;
+    fn sid_generate_string_table_id(name: *const SChar) -> SidStringTableId;
+    fn sid_get_string_key_namespace() -> *const SChar;
+    fn sid_generate_string_key_id(name: *const SChar) -> SidStringKeyId;
+    fn sid_get_texture_namespace() -> *const SChar;
+    fn sid_generate_texture_id(name: *const SChar) -> SidTextureId;
+    fn sid_get_palette_namespace() -> *const SChar;
+    fn sid_generate_palette_id(name: *const SChar) -> SidPaletteId;
+    fn sid_get_nine_slice_namespace() -> *const SChar;
+    fn sid_generate_nine_slice_id(name: *const SChar) -> SidNineSliceId;
+    fn sid_get_character_namespace() -> *const SChar;
+    fn sid_generate_character_id(name: *const SChar) -> SidCharacterId;
+    fn sid_get_skeleton_namespace() -> *const SChar;
+    fn sid_generate_skeleton_id(name: *const SChar) -> SidSkeletonId;
+    fn sid_get_skeletal_animation_namespace() -> *const SChar;
+    fn sid_generate_skeletal_animation_id(name: *const SChar) -> SidSkeletalAnimationId;
+    fn sid_get_bitmap_font_namespace() -> *const SChar;
+    fn sid_generate_bitmap_font_id(name: *const SChar) -> SidBitmapFontId;
+    fn sid_get_mesh_namespace() -> *const SChar;
+    fn sid_generate_mesh_id(name: *const SChar) -> SidMeshId;
+    fn sid_get_curve_namespace() -> *const SChar;
+    fn sid_generate_curve_id(name: *const SChar) -> SidCurveId;
+    fn sid_get_material_namespace() -> *const SChar;
+    fn sid_generate_material_id(name: *const SChar) -> SidMaterialId;
+    fn sid_get_scene_namespace() -> *const SChar;
+    fn sid_generate_scene_id(name: *const SChar) -> SidSceneId;
 }
 
 #[allow(non_camel_case_types)]
@@ -38,59 +150,902 @@ pub enum sid_texture_format {
     SID_TEXTURE_FORMAT_UNKNOWN,
     SID_TEXTURE_FORMAT_RGB8,
     SID_TEXTURE_FORMAT_RGBA8,
+    SID_TEXTURE_FORMAT_R8,
+    SID_TEXTURE_FORMAT_BC1,
+    SID_TEXTURE_FORMAT_BC3,
+    SID_TEXTURE_FORMAT_BC7,
+    SID_TEXTURE_FORMAT_ETC2_RGB8,
+    SID_TEXTURE_FORMAT_ETC2_RGBA8,
+    SID_TEXTURE_FORMAT_ASTC_4X4,
+    /// 8-bit indices into a palette resource.
+    SID_TEXTURE_FORMAT_INDEXED8,
+    /// 8-bit grayscale.
+    SID_TEXTURE_FORMAT_L8,
+    /// 8-bit grayscale with alpha.
+    SID_TEXTURE_FORMAT_LA8,
+    /// 16-bit channels, in the byte order of the resource.
+    SID_TEXTURE_FORMAT_RGBA16,
+}
+
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+#[repr(i32)]
+pub enum sid_color_space {
+    SID_COLOR_SPACE_UNKNOWN,
+    SID_COLOR_SPACE_SRGB,
+    SID_COLOR_SPACE_LINEAR,
 }
 
-macro_rules! sid_namespace {
-    ($fn:ident) => {{
-        static mut NAMESPACE: Option<&'static str> = None;
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+#[repr(i32)]
+pub enum sid_audio_format {
+    SID_AUDIO_FORMAT_UNKNOWN,
+    /// Interleaved signed 16-bit samples.
+    SID_AUDIO_FORMAT_PCM16,
+    /// Ogg Vorbis stream, decoded by the engine.
+    SID_AUDIO_FORMAT_OGG_VORBIS,
+}
+
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+#[repr(i32)]
+pub enum sid_shader_stage {
+    SID_SHADER_STAGE_UNKNOWN,
+    SID_SHADER_STAGE_VERTEX,
+    SID_SHADER_STAGE_FRAGMENT,
+}
+
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+#[repr(i32)]
+pub enum sid_shader_format {
+    SID_SHADER_FORMAT_UNKNOWN,
+    /// GLSL source, compiled by the engine.
+    SID_SHADER_FORMAT_GLSL,
+    /// SPIR-V module, as a stream of 32-bit words.
+    SID_SHADER_FORMAT_SPIRV,
+}
+
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+#[repr(i32)]
+pub enum sid_prefab_component_type {
+    SID_PREFAB_COMPONENT_TYPE_UNKNOWN,
+    SID_PREFAB_COMPONENT_TYPE_SPRITE,
+    SID_PREFAB_COMPONENT_TYPE_ANIMATION,
+    SID_PREFAB_COMPONENT_TYPE_COLLIDER,
+    SID_PREFAB_COMPONENT_TYPE_CUSTOM,
+}
+
+// Rust implementation of the sid_lib functions, for platforms and setups where
+// the static library is not available. The values below must stay in sync with
+// the engine, otherwise the packed resources will not be found at runtime.
+#[cfg(feature = "pure-rust")]
+mod fallback {
+    use super::{
+        SChar, SidAnimationDefId, SidAnimationId, SidAudioId, SidBitmapFontId, SidBlobId,
+        SidCharacterId, SidCurveId, SidFontId, SidMaterialId, SidMeshId, SidNineSliceId,
+        SidPaletteId, SidPrefabId, SidSceneId, SidShaderId, SidSkeletalAnimationId, SidSkeletonId,
+        SidSliceId, SidSpriteSheetId, SidStringId, SidStringKeyId, SidStringTableId, SidTextureId,
+        SidTilemapId, SidTilesetId,
+    };
+
+    use std::ffi::CStr;
+
+    const ANIMATION_NAMESPACE: &CStr = c"animations";
+    const ANIMATION_DEF_NAMESPACE: &CStr = c"animation_defs";
+    const SPRITE_SHEET_NAMESPACE: &CStr = c"sprite_sheets";
+    const SLICE_NAMESPACE: &CStr = c"slices";
+    const AUDIO_NAMESPACE: &CStr = c"audio";
+    const FONT_NAMESPACE: &CStr = c"fonts";
+    const TILESET_NAMESPACE: &CStr = c"tilesets";
+    const TILEMAP_NAMESPACE: &CStr = c"tilemaps";
+    const SHADER_NAMESPACE: &CStr = c"shaders";
+    const BLOB_NAMESPACE: &CStr = c"blobs";
+    const PREFAB_NAMESPACE: &CStr = c"prefabs";
+    const STRING_TABLE_NAMESPACE: &CStr = c"string_tables";
+    const STRING_KEY_NAMESPACE: &CStr = c"string_keys";
+    const TEXTURE_NAMESPACE: &CStr = c"textures";
+    const PALETTE_NAMESPACE: &CStr = c"palettes";
+    const NINE_SLICE_NAMESPACE: &CStr = c"nine_slices";
+    const CHARACTER_NAMESPACE: &CStr = c"characters";
+    const SKELETON_NAMESPACE: &CStr = c"skeletons";
+    const SKELETAL_ANIMATION_NAMESPACE: &CStr = c"skeletal_animations";
+    const BITMAP_FONT_NAMESPACE: &CStr = c"bitmap_fonts";
+    const MESH_NAMESPACE: &CStr = c"meshes";
+    const CURVE_NAMESPACE: &CStr = c"curves";
+    const MATERIAL_NAMESPACE: &CStr = c"materials";
+    const SCENE_NAMESPACE: &CStr = c"scenes";
+    const MAX_ANIMATION_FRAME_COUNT: u16 = 256;
+
+    // CRC-32 (IEEE 802.3) of "<namespace>/<name>", so that assets sharing a
+    // name across namespaces (e.g. a sheet and its animation def) get distinct
+    // ids.
+    #[cfg(not(feature = "wide-ids"))]
+    unsafe fn generate_string_id(namespace: &CStr, name: *const SChar) -> SidStringId {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(namespace.to_bytes());
+        hasher.update(b"/");
+        hasher.update(CStr::from_ptr(name).to_bytes());
+        hasher.finalize()
+    }
+
+    // XXH3 (64-bit) of "<namespace>/<name>".
+    #[cfg(feature = "wide-ids")]
+    unsafe fn generate_string_id(namespace: &CStr, name: *const SChar) -> SidStringId {
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        hasher.update(namespace.to_bytes());
+        hasher.update(b"/");
+        hasher.update(CStr::from_ptr(name).to_bytes());
+        hasher.digest()
+    }
+
+    pub unsafe fn sid_get_animation_namespace() -> *const SChar {
+        ANIMATION_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_get_animation_def_namespace() -> *const SChar {
+        ANIMATION_DEF_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_get_max_animation_frame_count() -> u16 {
+        MAX_ANIMATION_FRAME_COUNT
+    }
+
+    pub unsafe fn sid_generate_animation_def_id(name: *const SChar) -> SidAnimationDefId {
+        generate_string_id(ANIMATION_DEF_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_generate_animation_id(name: *const SChar) -> SidAnimationId {
+        generate_string_id(ANIMATION_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_sprite_sheet_namespace() -> *const SChar {
+        SPRITE_SHEET_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_sprite_sheet_id(name: *const SChar) -> SidSpriteSheetId {
+        generate_string_id(SPRITE_SHEET_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_slice_namespace() -> *const SChar {
+        SLICE_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_slice_id(name: *const SChar) -> SidSliceId {
+        generate_string_id(SLICE_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_audio_namespace() -> *const SChar {
+        AUDIO_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_audio_id(name: *const SChar) -> SidAudioId {
+        generate_string_id(AUDIO_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_font_namespace() -> *const SChar {
+        FONT_NAMESPACE.as_ptr()
+    }
 
+    pub unsafe fn sid_generate_font_id(name: *const SChar) -> SidFontId {
+        generate_string_id(FONT_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_tileset_namespace() -> *const SChar {
+        TILESET_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_tileset_id(name: *const SChar) -> SidTilesetId {
+        generate_string_id(TILESET_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_tilemap_namespace() -> *const SChar {
+        TILEMAP_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_tilemap_id(name: *const SChar) -> SidTilemapId {
+        generate_string_id(TILEMAP_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_shader_namespace() -> *const SChar {
+        SHADER_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_shader_id(name: *const SChar) -> SidShaderId {
+        generate_string_id(SHADER_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_blob_namespace() -> *const SChar {
+        BLOB_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_blob_id(name: *const SChar) -> SidBlobId {
+        generate_string_id(BLOB_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_prefab_namespace() -> *const SChar {
+        PREFAB_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_prefab_id(name: *const SChar) -> SidPrefabId {
+        generate_string_id(PREFAB_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_string_table_namespace() -> *const SChar {
+        STRING_TABLE_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_string_table_id(name: *const SChar) -> SidStringTableId {
+        generate_string_id(STRING_TABLE_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_string_key_namespace() -> *const SChar {
+        STRING_KEY_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_string_key_id(name: *const SChar) -> SidStringKeyId {
+        generate_string_id(STRING_KEY_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_texture_namespace() -> *const SChar {
+        TEXTURE_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_texture_id(name: *const SChar) -> SidTextureId {
+        generate_string_id(TEXTURE_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_palette_namespace() -> *const SChar {
+        PALETTE_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_palette_id(name: *const SChar) -> SidPaletteId {
+        generate_string_id(PALETTE_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_nine_slice_namespace() -> *const SChar {
+        NINE_SLICE_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_nine_slice_id(name: *const SChar) -> SidNineSliceId {
+        generate_string_id(NINE_SLICE_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_character_namespace() -> *const SChar {
+        CHARACTER_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_character_id(name: *const SChar) -> SidCharacterId {
+        generate_string_id(CHARACTER_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_skeleton_namespace() -> *const SChar {
+        SKELETON_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_skeleton_id(name: *const SChar) -> SidSkeletonId {
+        generate_string_id(SKELETON_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_skeletal_animation_namespace() -> *const SChar {
+        SKELETAL_ANIMATION_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_skeletal_animation_id(name: *const SChar) -> SidSkeletalAnimationId {
+        generate_string_id(SKELETAL_ANIMATION_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_bitmap_font_namespace() -> *const SChar {
+        BITMAP_FONT_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_bitmap_font_id(name: *const SChar) -> SidBitmapFontId {
+        generate_string_id(BITMAP_FONT_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_mesh_namespace() -> *const SChar {
+        MESH_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_mesh_id(name: *const SChar) -> SidMeshId {
+        generate_string_id(MESH_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_curve_namespace() -> *const SChar {
+        CURVE_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_curve_id(name: *const SChar) -> SidCurveId {
+        generate_string_id(CURVE_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_material_namespace() -> *const SChar {
+        MATERIAL_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_material_id(name: *const SChar) -> SidMaterialId {
+        generate_string_id(MATERIAL_NAMESPACE, name)
+    }
+
+    pub unsafe fn sid_get_scene_namespace() -> *const SChar {
+        SCENE_NAMESPACE.as_ptr()
+    }
+
+    pub unsafe fn sid_generate_scene_id(name: *const SChar) -> SidSceneId {
+        generate_string_id(SCENE_NAMESPACE, name)
+    }
+}
+
+#[cfg(feature = "pure-rust")]
+use fallback::*;
+
+/// Kind of asset a string id is generated for, each with its own namespace.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SidIdKind {
+    Animation,
+    AnimationDef,
+    SpriteSheet,
+    Slice,
+    Audio,
+    Font,
+    Tileset,
+    Tilemap,
+    Shader,
+    Blob,
+    Prefab,
+    StringTable,
+    StringKey,
+    Texture,
+    Palette,
+    NineSlice,
+    Character,
+    Skeleton,
+    SkeletalAnimation,
+    BitmapFont,
+    Mesh,
+    Curve,
+    Material,
+    Scene,
+}
+
+impl SidIdKind {
+    pub const ALL: [SidIdKind; 24] = [
+        SidIdKind::Animation,
+        SidIdKind::AnimationDef,
+        SidIdKind::SpriteSheet,
+        SidIdKind::Slice,
+        SidIdKind::Audio,
+        SidIdKind::Font,
+        SidIdKind::Tileset,
+        SidIdKind::Tilemap,
+        SidIdKind::Shader,
+        SidIdKind::Blob,
+        SidIdKind::Prefab,
+        SidIdKind::StringTable,
+        SidIdKind::StringKey,
+        SidIdKind::Texture,
+        SidIdKind::Palette,
+        SidIdKind::NineSlice,
+        SidIdKind::Character,
+        SidIdKind::Skeleton,
+        SidIdKind::SkeletalAnimation,
+        SidIdKind::BitmapFont,
+        SidIdKind::Mesh,
+        SidIdKind::Curve,
+        SidIdKind::Material,
+        SidIdKind::Scene,
+    ];
+}
+
+/// Namespaces of the string ids, read once from the backend and shared by
+/// every thread afterwards.
+#[derive(Debug, Clone)]
+pub struct SidNamespaces {
+    pub animation: &'static str,
+    pub animation_def: &'static str,
+    pub sprite_sheet: &'static str,
+    pub slice: &'static str,
+    pub audio: &'static str,
+    pub font: &'static str,
+    pub tileset: &'static str,
+    pub tilemap: &'static str,
+    pub shader: &'static str,
+    pub blob: &'static str,
+    pub prefab: &'static str,
+    pub string_table: &'static str,
+    pub string_key: &'static str,
+    pub texture: &'static str,
+    pub palette: &'static str,
+    pub nine_slice: &'static str,
+    pub character: &'static str,
+    pub skeleton: &'static str,
+    pub skeletal_animation: &'static str,
+    pub bitmap_font: &'static str,
+    pub mesh: &'static str,
+    pub curve: &'static str,
+    pub material: &'static str,
+    pub scene: &'static str,
+}
+
+impl SidNamespaces {
+    pub fn get(&self, kind: SidIdKind) -> &'static str {
+        match kind {
+            SidIdKind::Animation => self.animation,
+            SidIdKind::AnimationDef => self.animation_def,
+            SidIdKind::SpriteSheet => self.sprite_sheet,
+            SidIdKind::Slice => self.slice,
+            SidIdKind::Audio => self.audio,
+            SidIdKind::Font => self.font,
+            SidIdKind::Tileset => self.tileset,
+            SidIdKind::Tilemap => self.tilemap,
+            SidIdKind::Shader => self.shader,
+            SidIdKind::Blob => self.blob,
+            SidIdKind::Prefab => self.prefab,
+            SidIdKind::StringTable => self.string_table,
+            SidIdKind::StringKey => self.string_key,
+            SidIdKind::Texture => self.texture,
+            SidIdKind::Palette => self.palette,
+            SidIdKind::NineSlice => self.nine_slice,
+            SidIdKind::Character => self.character,
+            SidIdKind::Skeleton => self.skeleton,
+            SidIdKind::SkeletalAnimation => self.skeletal_animation,
+            SidIdKind::BitmapFont => self.bitmap_font,
+            SidIdKind::Mesh => self.mesh,
+            SidIdKind::Curve => self.curve,
+            SidIdKind::Material => self.material,
+            SidIdKind::Scene => self.scene,
+        }
+    }
+}
+
+/// Source of the namespaces and the string ids, sid_lib by default. Tests can
+/// swap it for a `SidTestBackend`, so that they run without the library.
+pub trait SidBackend: Send + Sync + std::fmt::Debug {
+    fn namespaces(&self) -> &SidNamespaces;
+
+    /// Id of the asset called `name`, before the id overrides.
+    fn generate_id(&self, kind: SidIdKind, name: &str) -> SidStringId;
+
+    fn max_animation_frame_count(&self) -> SidAnimationFrameIndex;
+}
+
+// The namespaces of sid_lib are static strings, never freed.
+unsafe fn to_namespace(namespace: *const SChar) -> &'static str {
+    CStr::from_ptr(namespace)
+        .to_str()
+        .expect("Invalid UTF-8 in value")
+}
+
+/// Backend calling sid_lib, or its Rust implementation with the `pure-rust`
+/// feature.
+#[derive(Debug, Default)]
+pub struct SidLibBackend {
+    namespaces: OnceLock<SidNamespaces>,
+}
+
+impl SidLibBackend {
+    pub const fn new() -> Self {
+        Self {
+            namespaces: OnceLock::new(),
+        }
+    }
+
+    fn load_namespaces() -> SidNamespaces {
         unsafe {
-            if let Some(namespace) = NAMESPACE {
-                namespace
-            } else {
-                let namespace = $fn();
-                let namespace = CStr::from_ptr(namespace);
-                let namespace = namespace.to_str().expect("Invalid UTF-8 in value");
-                NAMESPACE = Some(namespace);
-                namespace
+            SidNamespaces {
+                animation: to_namespace(sid_get_animation_namespace()),
+                animation_def: to_namespace(sid_get_animation_def_namespace()),
+                sprite_sheet: to_namespace(sid_get_sprite_sheet_namespace()),
+                slice: to_namespace(sid_get_slice_namespace()),
+                audio: to_namespace(sid_get_audio_namespace()),
+                font: to_namespace(sid_get_font_namespace()),
+                tileset: to_namespace(sid_get_tileset_namespace()),
+                tilemap: to_namespace(sid_get_tilemap_namespace()),
+                shader: to_namespace(sid_get_shader_namespace()),
+                blob: to_namespace(sid_get_blob_namespace()),
+                prefab: to_namespace(sid_get_prefab_namespace()),
+                string_table: to_namespace(sid_get_string_table_namespace()),
+                string_key: to_namespace(sid_get_string_key_namespace()),
+                texture: to_namespace(sid_get_texture_namespace()),
+                palette: to_namespace(sid_get_palette_namespace()),
+                nine_slice: to_namespace(sid_get_nine_slice_namespace()),
+                character: to_namespace(sid_get_character_namespace()),
+                skeleton: to_namespace(sid_get_skeleton_namespace()),
+                skeletal_animation: to_namespace(sid_get_skeletal_animation_namespace()),
+                bitmap_font: to_namespace(sid_get_bitmap_font_namespace()),
+                mesh: to_namespace(sid_get_mesh_namespace()),
+                curve: to_namespace(sid_get_curve_namespace()),
+                material: to_namespace(sid_get_material_namespace()),
+                scene: to_namespace(sid_get_scene_namespace()),
             }
         }
-    }};
+    }
 }
 
-macro_rules! generate_string_id {
-    ($name:expr, $id_generator:ident) => {{
-        let name = CString::new($name).expect("Failed to create CString");
+impl SidBackend for SidLibBackend {
+    fn namespaces(&self) -> &SidNamespaces {
+        self.namespaces.get_or_init(Self::load_namespaces)
+    }
+
+    fn generate_id(&self, kind: SidIdKind, name: &str) -> SidStringId {
+        let name = CString::new(name).expect("Failed to create CString");
         let name: *const SChar = name.as_ptr();
-        unsafe { $id_generator(name) }
-    }};
+
+        unsafe {
+            match kind {
+                SidIdKind::Animation => sid_generate_animation_id(name),
+                SidIdKind::AnimationDef => sid_generate_animation_def_id(name),
+                SidIdKind::SpriteSheet => sid_generate_sprite_sheet_id(name),
+                SidIdKind::Slice => sid_generate_slice_id(name),
+                SidIdKind::Audio => sid_generate_audio_id(name),
+                SidIdKind::Font => sid_generate_font_id(name),
+                SidIdKind::Tileset => sid_generate_tileset_id(name),
+                SidIdKind::Tilemap => sid_generate_tilemap_id(name),
+                SidIdKind::Shader => sid_generate_shader_id(name),
+                SidIdKind::Blob => sid_generate_blob_id(name),
+                SidIdKind::Prefab => sid_generate_prefab_id(name),
+                SidIdKind::StringTable => sid_generate_string_table_id(name),
+                SidIdKind::StringKey => sid_generate_string_key_id(name),
+                SidIdKind::Texture => sid_generate_texture_id(name),
+                SidIdKind::Palette => sid_generate_palette_id(name),
+                SidIdKind::NineSlice => sid_generate_nine_slice_id(name),
+                SidIdKind::Character => sid_generate_character_id(name),
+                SidIdKind::Skeleton => sid_generate_skeleton_id(name),
+                SidIdKind::SkeletalAnimation => sid_generate_skeletal_animation_id(name),
+                SidIdKind::BitmapFont => sid_generate_bitmap_font_id(name),
+                SidIdKind::Mesh => sid_generate_mesh_id(name),
+                SidIdKind::Curve => sid_generate_curve_id(name),
+                SidIdKind::Material => sid_generate_material_id(name),
+                SidIdKind::Scene => sid_generate_scene_id(name),
+            }
+        }
+    }
+
+    fn max_animation_frame_count(&self) -> SidAnimationFrameIndex {
+        unsafe { sid_get_max_animation_frame_count() }
+    }
 }
 
-// Public API below.
-pub fn get_animation_namespace() -> &'static str {
-    sid_namespace!(sid_get_animation_namespace)
+/// Deterministic backend for tests: FNV-1a of "<namespace>/<name>", with the
+/// namespaces of the `pure-rust` feature. Its ids do not match the engine's.
+#[derive(Debug)]
+pub struct SidTestBackend {
+    namespaces: SidNamespaces,
 }
 
-pub fn get_animation_def_namespace() -> &'static str {
-    sid_namespace!(sid_get_animation_def_namespace)
+impl SidTestBackend {
+    pub fn new() -> Self {
+        Self {
+            namespaces: SidNamespaces {
+                animation: "animations",
+                animation_def: "animation_defs",
+                sprite_sheet: "sprite_sheets",
+                slice: "slices",
+                audio: "audio",
+                font: "fonts",
+                tileset: "tilesets",
+                tilemap: "tilemaps",
+                shader: "shaders",
+                blob: "blobs",
+                prefab: "prefabs",
+                string_table: "string_tables",
+                string_key: "string_keys",
+                texture: "textures",
+                palette: "palettes",
+                nine_slice: "nine_slices",
+                character: "characters",
+                skeleton: "skeletons",
+                skeletal_animation: "skeletal_animations",
+                bitmap_font: "bitmap_fonts",
+                mesh: "meshes",
+                curve: "curves",
+                material: "materials",
+                scene: "scenes",
+            },
+        }
+    }
 }
 
+impl SidBackend for SidTestBackend {
+    fn namespaces(&self) -> &SidNamespaces {
+        &self.namespaces
+    }
+
+    fn generate_id(&self, kind: SidIdKind, name: &str) -> SidStringId {
+        let namespace = self.namespaces.get(kind);
+        let mut hash: u64 = 0xcbf29ce484222325;
+
+        for byte in namespace.bytes().chain(Some(b'/')).chain(name.bytes()) {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+        }
+
+        hash as SidStringId
+    }
+
+    fn max_animation_frame_count(&self) -> SidAnimationFrameIndex {
+        256
+    }
+}
+
+static LIB_BACKEND: SidLibBackend = SidLibBackend::new();
+
+// Consulted by every id generation of the tools, so that they all agree.
+static BACKEND: RwLock<Option<Arc<dyn SidBackend>>> = RwLock::new(None);
+
+thread_local! {
+    // Backend of the pack running on this thread, if it has its own.
+    static SCOPED_BACKEND: RefCell<Option<Arc<dyn SidBackend>>> = const { RefCell::new(None) };
+}
+
+/// Makes every id generation use `backend`.
+pub fn set_backend(backend: Arc<dyn SidBackend>) {
+    *BACKEND.write().unwrap_or_else(PoisonError::into_inner) = Some(backend);
+}
+
+/// Backend of the current thread until dropped, restoring the previous one.
+#[must_use]
+pub struct SidBackendScope {
+    previous: Option<Option<Arc<dyn SidBackend>>>,
+    // Scopes are per thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for SidBackendScope {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            SCOPED_BACKEND.set(previous);
+        }
+    }
+}
+
+/// Makes the id generations of the current thread use `backend` until the
+/// returned scope is dropped, leaving the other threads alone. None keeps the
+/// backend in use.
+pub fn scope_backend(backend: Option<&Arc<dyn SidBackend>>) -> SidBackendScope {
+    SidBackendScope {
+        previous: backend.map(|backend| SCOPED_BACKEND.replace(Some(backend.clone()))),
+        _not_send: PhantomData,
+    }
+}
+
+/// Calls `f` with the backend in use.
+fn with_backend<T>(f: impl FnOnce(&dyn SidBackend) -> T) -> T {
+    SCOPED_BACKEND.with_borrow(|scoped| match scoped.as_deref() {
+        Some(backend) => f(backend),
+        None => match BACKEND
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_deref()
+        {
+            Some(backend) => f(backend),
+            None => f(&LIB_BACKEND),
+        },
+    })
+}
+
+/// Hash of the namespaces of the backend in use and of the ids it generates,
+/// which changes along with the ids of the assets.
+pub fn backend_fingerprint() -> u64 {
+    with_backend(|backend| {
+        let mut hasher = Xxh3::new();
+
+        for kind in SidIdKind::ALL {
+            hasher.update(backend.namespaces().get(kind).as_bytes());
+            hasher.update(&backend.generate_id(kind, "sid_fingerprint").to_le_bytes());
+        }
+
+        hasher.update(&backend.max_animation_frame_count().to_le_bytes());
+        hasher.digest()
+    })
+}
+
+fn namespace(kind: SidIdKind) -> &'static str {
+    with_backend(|backend| backend.namespaces().get(kind))
+}
+
+fn generate_string_id(kind: SidIdKind, name: &str) -> SidStringId {
+    with_backend(|backend| {
+        // Ids fixed by the id overrides are used as is.
+        id_overrides::find_id(backend.namespaces().get(kind), name)
+            .unwrap_or_else(|| backend.generate_id(kind, name))
+    })
+}
+
+// Public API below.
 pub fn get_max_animation_frame_count() -> SidAnimationFrameIndex {
-    unsafe { sid_get_max_animation_frame_count() }
+    with_backend(|backend| backend.max_animation_frame_count())
 }
 
-pub fn generate_animation_def_id(name: &str) -> SidAnimationDefId {
-    generate_string_id!(name, sid_generate_animation_def_id)
+pub fn get_animation_namespace() -> &'static str {
+    namespace(SidIdKind::Animation)
 }
 
 pub fn generate_animation_id(name: &str) -> SidAnimationId {
-    generate_string_id!(name, sid_generate_animation_id)
+    generate_string_id(SidIdKind::Animation, name)
+}
+
+pub fn get_animation_def_namespace() -> &'static str {
+    namespace(SidIdKind::AnimationDef)
+}
+
+pub fn generate_animation_def_id(name: &str) -> SidAnimationDefId {
+    generate_string_id(SidIdKind::AnimationDef, name)
 }
 
 pub fn get_sprite_sheet_namespace() -> &'static str {
-    sid_namespace!(sid_get_sprite_sheet_namespace)
+    namespace(SidIdKind::SpriteSheet)
 }
 
 pub fn generate_sprite_sheet_id(name: &str) -> SidSpriteSheetId {
-    generate_string_id!(name, sid_generate_sprite_sheet_id)
+    generate_string_id(SidIdKind::SpriteSheet, name)
+}
+
+pub fn get_slice_namespace() -> &'static str {
+    namespace(SidIdKind::Slice)
+}
+
+pub fn generate_slice_id(name: &str) -> SidSliceId {
+    generate_string_id(SidIdKind::Slice, name)
+}
+
+pub fn get_audio_namespace() -> &'static str {
+    namespace(SidIdKind::Audio)
+}
+
+pub fn generate_audio_id(name: &str) -> SidAudioId {
+    generate_string_id(SidIdKind::Audio, name)
+}
+
+pub fn get_font_namespace() -> &'static str {
+    namespace(SidIdKind::Font)
+}
+
+pub fn generate_font_id(name: &str) -> SidFontId {
+    generate_string_id(SidIdKind::Font, name)
+}
+
+pub fn get_tileset_namespace() -> &'static str {
+    namespace(SidIdKind::Tileset)
+}
+
+pub fn generate_tileset_id(name: &str) -> SidTilesetId {
+    generate_string_id(SidIdKind::Tileset, name)
+}
+
+pub fn get_tilemap_namespace() -> &'static str {
+    namespace(SidIdKind::Tilemap)
+}
+
+pub fn generate_tilemap_id(name: &str) -> SidTilemapId {
+    generate_string_id(SidIdKind::Tilemap, name)
+}
+
+pub fn get_shader_namespace() -> &'static str {
+    namespace(SidIdKind::Shader)
+}
+
+pub fn generate_shader_id(name: &str) -> SidShaderId {
+    generate_string_id(SidIdKind::Shader, name)
+}
+
+pub fn get_blob_namespace() -> &'static str {
+    namespace(SidIdKind::Blob)
+}
+
+pub fn generate_blob_id(name: &str) -> SidBlobId {
+    generate_string_id(SidIdKind::Blob, name)
+}
+
+pub fn get_prefab_namespace() -> &'static str {
+    namespace(SidIdKind::Prefab)
+}
+
+pub fn generate_prefab_id(name: &str) -> SidPrefabId {
+    generate_string_id(SidIdKind::Prefab, name)
+}
+
+pub fn get_string_table_namespace() -> &'static str {
+    namespace(SidIdKind::StringTable)
+}
+
+pub fn generate_string_table_id(name: &str) -> SidStringTableId {
+    generate_string_id(SidIdKind::StringTable, name)
+}
+
+pub fn get_string_key_namespace() -> &'static str {
+    namespace(SidIdKind::StringKey)
+}
+
+pub fn generate_string_key_id(name: &str) -> SidStringKeyId {
+    generate_string_id(SidIdKind::StringKey, name)
+}
+
+pub fn get_texture_namespace() -> &'static str {
+    namespace(SidIdKind::Texture)
+}
+
+pub fn generate_texture_id(name: &str) -> SidTextureId {
+    generate_string_id(SidIdKind::Texture, name)
+}
+
+pub fn get_palette_namespace() -> &'static str {
+    namespace(SidIdKind::Palette)
+}
+
+pub fn generate_palette_id(name: &str) -> SidPaletteId {
+    generate_string_id(SidIdKind::Palette, name)
+}
+
+pub fn get_nine_slice_namespace() -> &'static str {
+    namespace(SidIdKind::NineSlice)
+}
+
+pub fn generate_nine_slice_id(name: &str) -> SidNineSliceId {
+    generate_string_id(SidIdKind::NineSlice, name)
+}
+
+pub fn get_character_namespace() -> &'static str {
+    namespace(SidIdKind::Character)
+}
+
+pub fn generate_character_id(name: &str) -> SidCharacterId {
+    generate_string_id(SidIdKind::Character, name)
+}
+
+pub fn get_skeleton_namespace() -> &'static str {
+    namespace(SidIdKind::Skeleton)
+}
+
+pub fn generate_skeleton_id(name: &str) -> SidSkeletonId {
+    generate_string_id(SidIdKind::Skeleton, name)
+}
+
+pub fn get_skeletal_animation_namespace() -> &'static str {
+    namespace(SidIdKind::SkeletalAnimation)
+}
+
+pub fn generate_skeletal_animation_id(name: &str) -> SidSkeletalAnimationId {
+    generate_string_id(SidIdKind::SkeletalAnimation, name)
+}
+
+pub fn get_bitmap_font_namespace() -> &'static str {
+    namespace(SidIdKind::BitmapFont)
+}
+
+pub fn generate_bitmap_font_id(name: &str) -> SidBitmapFontId {
+    generate_string_id(SidIdKind::BitmapFont, name)
+}
+
+pub fn get_mesh_namespace() -> &'static str {
+    namespace(SidIdKind::Mesh)
+}
+
+pub fn generate_mesh_id(name: &str) -> SidMeshId {
+    generate_string_id(SidIdKind::Mesh, name)
+}
+
+pub fn get_curve_namespace() -> &'static str {
+    namespace(SidIdKind::Curve)
+}
+
+pub fn generate_curve_id(name: &str) -> SidCurveId {
+    generate_string_id(SidIdKind::Curve, name)
+}
+
+pub fn get_material_namespace() -> &'static str {
+    namespace(SidIdKind::Material)
+}
+
+pub fn generate_material_id(name: &str) -> SidMaterialId {
+    generate_string_id(SidIdKind::Material, name)
+}
+
+pub fn get_scene_namespace() -> &'static str {
+    namespace(SidIdKind::Scene)
+}
+
+pub fn generate_scene_id(name: &str) -> SidSceneId {
+    generate_string_id(SidIdKind::Scene, name)
 }
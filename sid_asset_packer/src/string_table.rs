@@ -0,0 +1,293 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use fluent_syntax::ast;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+use crate::resource::{SidAssetSerializationError, SidAssetSerializationResult};
+
+/// Strings of a locale, by key.
+pub type SidLocaleStrings = BTreeMap<String, String>;
+
+// Guards against message references looping on each other.
+const MAX_FLUENT_REFERENCE_DEPTH: usize = 16;
+
+/// Reads the strings of a locale from a CSV file (one `key,value` record per
+/// line, with an optional `key,value` header) or a Fluent file.
+pub fn read_locale_strings(path: &Path) -> SidAssetSerializationResult<SidLocaleStrings> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+
+    let entries = match extension.as_deref() {
+        Some("csv") => read_csv(path)?,
+        Some("ftl") => read_fluent(path)?,
+        _ => {
+            return Err(SidAssetSerializationError::UnsupportedFormat(format!(
+                "Unknown or unsupported string file: {:?}",
+                path
+            )))
+        }
+    };
+
+    let mut strings = SidLocaleStrings::new();
+
+    for (key, value) in entries {
+        if strings.contains_key(&key) {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Duplicate key {:?} in {:?}",
+                key, path
+            )));
+        }
+
+        strings.insert(key, value);
+    }
+
+    Ok(strings)
+}
+
+fn read_csv(path: &Path) -> SidAssetSerializationResult<Vec<(String, String)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(path)
+        .map_err(|error| {
+            SidAssetSerializationError::IO(format!("Unable to read {:?}: {error}", path))
+        })?;
+
+    let mut entries = vec![];
+
+    for (i, record) in reader.records().enumerate() {
+        let record = record.map_err(|error| {
+            SidAssetSerializationError::Malformed(format!("Invalid CSV file {:?}: {error}", path))
+        })?;
+
+        if record.len() != 2 {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Invalid CSV file {:?}: expected 2 fields (key, value) on record {}, got {}",
+                path,
+                i + 1,
+                record.len()
+            )));
+        }
+
+        if i == 0 && &record[0] == "key" && &record[1] == "value" {
+            continue;
+        }
+
+        entries.push((record[0].to_string(), record[1].to_string()));
+    }
+
+    Ok(entries)
+}
+
+fn read_fluent(path: &Path) -> SidAssetSerializationResult<Vec<(String, String)>> {
+    let source = fs::read_to_string(path).map_err(|error| {
+        SidAssetSerializationError::IO(format!("Unable to read {:?}: {error}", path))
+    })?;
+
+    let resource = fluent_syntax::parser::parse(source.as_str()).map_err(|(_, errors)| {
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+
+        SidAssetSerializationError::Malformed(format!(
+            "Invalid Fluent file {:?}: {}",
+            path,
+            errors.join(", ")
+        ))
+    })?;
+
+    let mut messages = HashMap::new();
+    let mut terms = HashMap::new();
+
+    for entry in &resource.body {
+        match entry {
+            ast::Entry::Message(message) => {
+                messages.insert(message.id.name, message);
+            }
+            ast::Entry::Term(term) => {
+                terms.insert(term.id.name, term);
+            }
+            _ => {}
+        }
+    }
+
+    let renderer = SidFluentRenderer {
+        path,
+        messages: &messages,
+        terms: &terms,
+    };
+
+    let mut entries = vec![];
+
+    // Attributes are packed as separate "<message>.<attribute>" keys.
+    for entry in &resource.body {
+        if let ast::Entry::Message(message) = entry {
+            if let Some(value) = &message.value {
+                entries.push((
+                    message.id.name.to_string(),
+                    renderer.render(value, message.id.name, 0)?,
+                ));
+            }
+
+            for attribute in &message.attributes {
+                let key = format!("{}.{}", message.id.name, attribute.id.name);
+                let value = renderer.render(&attribute.value, &key, 0)?;
+                entries.push((key, value));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Flattens Fluent patterns into plain strings. References to messages and
+/// terms are inlined, and variables are kept as `{$name}` for the engine to
+/// substitute. Selectors and functions are not supported.
+struct SidFluentRenderer<'a> {
+    path: &'a Path,
+    messages: &'a HashMap<&'a str, &'a ast::Message<&'a str>>,
+    terms: &'a HashMap<&'a str, &'a ast::Term<&'a str>>,
+}
+
+impl SidFluentRenderer<'_> {
+    fn unsupported(&self, key: &str, what: &str) -> SidAssetSerializationError {
+        SidAssetSerializationError::UnsupportedFormat(format!(
+            "Unsupported {what} in message {:?} of {:?}",
+            key, self.path
+        ))
+    }
+
+    fn render(
+        &self,
+        pattern: &ast::Pattern<&str>,
+        key: &str,
+        depth: usize,
+    ) -> SidAssetSerializationResult<String> {
+        if depth > MAX_FLUENT_REFERENCE_DEPTH {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "Too many nested references in message {:?} of {:?}",
+                key, self.path
+            )));
+        }
+
+        let mut out = String::new();
+
+        for element in &pattern.elements {
+            match element {
+                ast::PatternElement::TextElement { value } => out.push_str(value),
+                ast::PatternElement::Placeable { expression } => {
+                    out.push_str(&self.render_expression(expression, key, depth)?)
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn render_expression(
+        &self,
+        expression: &ast::Expression<&str>,
+        key: &str,
+        depth: usize,
+    ) -> SidAssetSerializationResult<String> {
+        let expression = match expression {
+            ast::Expression::Inline(expression) => expression,
+            ast::Expression::Select { .. } => return Err(self.unsupported(key, "selector")),
+        };
+
+        match expression {
+            ast::InlineExpression::StringLiteral { value } => Ok(value.to_string()),
+            ast::InlineExpression::NumberLiteral { value } => Ok(value.to_string()),
+            ast::InlineExpression::VariableReference { id } => Ok(format!("{{${}}}", id.name)),
+            ast::InlineExpression::MessageReference { id, attribute } => {
+                let message = self.messages.get(id.name).ok_or_else(|| {
+                    SidAssetSerializationError::Malformed(format!(
+                        "Unknown message {:?} referenced by {:?} in {:?}",
+                        id.name, key, self.path
+                    ))
+                })?;
+
+                let pattern = match attribute {
+                    Some(attribute) => message
+                        .attributes
+                        .iter()
+                        .find(|candidate| candidate.id.name == attribute.name)
+                        .map(|attribute| &attribute.value),
+                    None => message.value.as_ref(),
+                };
+
+                let pattern = pattern.ok_or_else(|| {
+                    SidAssetSerializationError::Malformed(format!(
+                        "Message {:?} referenced by {:?} in {:?} has no such value",
+                        id.name, key, self.path
+                    ))
+                })?;
+
+                self.render(pattern, key, depth + 1)
+            }
+            ast::InlineExpression::TermReference {
+                id,
+                attribute: None,
+                arguments: None,
+            } => {
+                let term = self.terms.get(id.name).ok_or_else(|| {
+                    SidAssetSerializationError::Malformed(format!(
+                        "Unknown term {:?} referenced by {:?} in {:?}",
+                        id.name, key, self.path
+                    ))
+                })?;
+
+                self.render(&term.value, key, depth + 1)
+            }
+            ast::InlineExpression::TermReference { .. } => {
+                Err(self.unsupported(key, "parameterized term"))
+            }
+            ast::InlineExpression::FunctionReference { .. } => {
+                Err(self.unsupported(key, "function"))
+            }
+            ast::InlineExpression::Placeable { expression } => {
+                self.render_expression(expression, key, depth)
+            }
+        }
+    }
+}
+
+/// Checks that every locale defines the same keys, listing the differences
+/// otherwise.
+pub fn check_key_sets(
+    table_name: &str,
+    locales: &BTreeMap<String, SidLocaleStrings>,
+) -> SidAssetSerializationResult<()> {
+    let all_keys: BTreeSet<&String> = locales
+        .values()
+        .flat_map(|strings| strings.keys())
+        .collect();
+    let mut errors = vec![];
+
+    for (locale, strings) in locales {
+        let missing: Vec<_> = all_keys
+            .iter()
+            .filter(|key| !strings.contains_key(key.as_str()))
+            .collect();
+
+        if !missing.is_empty() {
+            errors.push(format!("locale {:?} is missing {:?}", locale, missing));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SidAssetSerializationError::Malformed(format!(
+            "Locales of string table {:?} do not define the same keys: {}",
+            table_name,
+            errors.join(", ")
+        )))
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Deserialize;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{SidToolError, SidToolResult};
+
+/// Sidecar holding tags of the asset of the same name, e.g. `hero.tags.toml`
+/// for `hero.json`, so that assets written by other tools can be tagged.
+pub static TAGS_SIDECAR_EXTENSION: &str = "tags.toml";
+
+/// Tags of an asset JSON, whatever its type.
+#[derive(Deserialize, Default)]
+struct SidTaggedAsset {
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct SidTagsSidecar {
+    tags: Vec<String>,
+}
+
+pub fn sidecar_path(asset_input_path: &Path) -> PathBuf {
+    asset_input_path.with_extension(TAGS_SIDECAR_EXTENSION)
+}
+
+/// Tags of the asset at `asset_input_path`: the ones of its `tags` field, and
+/// the ones of its sidecar, if any.
+pub fn read_tags(asset_input_path: &Path) -> SidToolResult<Vec<String>> {
+    let contents = fs::read_to_string(asset_input_path).map_err(|error| {
+        SidToolError::IO(format!("Unable to read {:?}: {error}", asset_input_path))
+    })?;
+
+    let asset: SidTaggedAsset = serde_json::from_str(&contents)
+        .map_err(|error| SidToolError::Malformed(format!("Invalid tags: {error}")))?;
+
+    let mut tags = asset.tags;
+    let path = sidecar_path(asset_input_path);
+
+    if path.is_file() {
+        let contents = fs::read_to_string(&path).map_err(|error| {
+            SidToolError::IO(format!("Unable to read sidecar {:?}: {error}", path))
+        })?;
+
+        let sidecar: SidTagsSidecar = toml::from_str(&contents).map_err(|error| {
+            SidToolError::Malformed(format!("Invalid sidecar {:?}: {error}", path))
+        })?;
+
+        tags.extend(sidecar.tags);
+    }
+
+    Ok(tags)
+}
+
+/// Tags deciding which assets are packed, e.g. only the `demo` ones, or all
+/// but the `debug` ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SidTagFilter {
+    /// Assets must have one of these tags, if there are any.
+    pub only: Vec<String>,
+    /// Assets must have none of these tags.
+    pub exclude: Vec<String>,
+}
+
+impl SidTagFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether an asset with `tags` is packed.
+    pub fn matches(&self, tags: &[String]) -> bool {
+        (self.only.is_empty() || tags.iter().any(|tag| self.only.contains(tag)))
+            && !tags.iter().any(|tag| self.exclude.contains(tag))
+    }
+}
@@ -0,0 +1,587 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use color_quant::NeuQuant;
+use image::{
+    imageops::FilterType, ColorType, DynamicImage, GenericImage, GenericImageView, ImageBuffer,
+    Pixel,
+};
+use serde::Deserialize;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    asset::{SidColorSpace, SidPixelFormat},
+    error::{SidToolError, SidToolResult},
+    resource::{SidAssetSerializationError, SidAssetSerializationResult},
+    sid::{self, sid_texture_format},
+};
+
+pub const MAX_PALETTE_COLOR_COUNT: usize = 256;
+
+// Speed of the NeuQuant quantizer, from 1 (best) to 30 (fastest).
+const NEUQUANT_SAMPLE_FACTOR: i32 = 10;
+
+/// Filter used to downsample the levels of a mip chain.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidMipmapFilter {
+    /// Averages every 2x2 block of the previous level.
+    Box,
+    /// Resamples the base level with a Lanczos filter: sharper, but slower.
+    Lanczos,
+}
+
+/// Layout of the packed pixels: raw, or one of the block-compressed formats
+/// GPUs sample directly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidTextureEncoding {
+    /// Uncompressed pixels, in the layout of their pixel format.
+    #[default]
+    Raw,
+    /// Desktop, opaque only: alpha is dropped.
+    Bc1,
+    /// Desktop, with alpha.
+    Bc3,
+    /// Desktop, with alpha and a better quality than BC3.
+    Bc7,
+    /// Mobile: ETC2 RGB8, or RGBA8 when the source image has alpha.
+    Etc2,
+    /// Mobile, with alpha, using 4x4 blocks.
+    Astc,
+}
+
+/// Settings of the packed sprite sheets and textures.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SidTextureOptions {
+    /// Filter of the generated mip chain, or `None` to only pack the base level.
+    pub mipmaps: Option<SidMipmapFilter>,
+    pub encoding: SidTextureEncoding,
+    /// Color space the images are converted to, or `None` to keep the one of
+    /// their source.
+    pub color_space: Option<SidColorSpace>,
+    /// Pixel layout of the raw images, or `None` to keep the one of each asset.
+    pub pixel_format: Option<SidPixelFormat>,
+    /// Largest width and height the target GPUs can load, images over it
+    /// failing to pack.
+    pub max_size: Option<u32>,
+    /// Pad the images with transparent pixels up to power-of-two sizes, for
+    /// the GPUs needing them.
+    pub power_of_two: bool,
+    /// Color made transparent in the images without a color key of their own.
+    pub color_key: Option<[u8; 3]>,
+}
+
+impl SidTextureOptions {
+    pub fn new() -> Self {
+        Self {
+            mipmaps: None,
+            encoding: SidTextureEncoding::Raw,
+            color_space: None,
+            pixel_format: None,
+            max_size: None,
+            power_of_two: false,
+            color_key: None,
+        }
+    }
+}
+
+/// `image`, padded on its right and bottom edges with transparent pixels up to
+/// the next power-of-two width and height.
+pub fn pad_to_power_of_two(image: DynamicImage) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let (padded_width, padded_height) = (width.next_power_of_two(), height.next_power_of_two());
+
+    if (padded_width, padded_height) == (width, height) {
+        return image;
+    }
+
+    let mut padded = DynamicImage::new(padded_width, padded_height, image.color());
+
+    // Both images have the same layout and the padded one is larger.
+    let _ = padded.copy_from(&image, 0, 0);
+    padded
+}
+
+/// Parses a color key written as `RRGGBB` in hexadecimal, with or without a
+/// leading `#`.
+pub fn parse_color_key(value: &str) -> SidToolResult<[u8; 3]> {
+    let digits = value.trim().trim_start_matches('#');
+    let error = || SidToolError::Malformed(format!("Invalid color key {:?}", value));
+
+    if digits.len() != 6 || !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(error());
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16);
+    Ok([
+        channel(0).map_err(|_| error())?,
+        channel(1).map_err(|_| error())?,
+        channel(2).map_err(|_| error())?,
+    ])
+}
+
+/// `image` with an alpha channel, its pixels of color `key` made fully
+/// transparent.
+pub fn apply_color_key(image: DynamicImage, key: [u8; 3]) -> DynamicImage {
+    if image.color().bytes_per_pixel() / image.color().channel_count() > 1 {
+        let key = key.map(|channel| channel as u16 * 257);
+        let mut image = image.into_rgba16();
+
+        for pixel in image.pixels_mut() {
+            if pixel.0[..3] == key {
+                pixel.0 = [0; 4];
+            }
+        }
+
+        return DynamicImage::ImageRgba16(image);
+    }
+
+    let mut image = image.into_rgba8();
+
+    for pixel in image.pixels_mut() {
+        if pixel.0[..3] == key {
+            pixel.0 = [0; 4];
+        }
+    }
+
+    DynamicImage::ImageRgba8(image)
+}
+
+/// Converts `image` to the layout `format`.
+pub fn convert_pixel_format(image: DynamicImage, format: SidPixelFormat) -> DynamicImage {
+    match format {
+        SidPixelFormat::Source => match image.color() {
+            ColorType::L8
+            | ColorType::La8
+            | ColorType::Rgb8
+            | ColorType::Rgba8
+            | ColorType::Rgba16 => image,
+            // Keeps the precision of 16-bit and floating-point images.
+            ColorType::L16
+            | ColorType::La16
+            | ColorType::Rgb16
+            | ColorType::Rgb32F
+            | ColorType::Rgba32F => DynamicImage::ImageRgba16(image.to_rgba16()),
+            _ => DynamicImage::ImageRgba8(image.to_rgba8()),
+        },
+        SidPixelFormat::L8 => DynamicImage::ImageLuma8(image.to_luma8()),
+        SidPixelFormat::La8 => DynamicImage::ImageLumaA8(image.to_luma_alpha8()),
+        SidPixelFormat::Rgb8 => DynamicImage::ImageRgb8(image.to_rgb8()),
+        SidPixelFormat::Rgba8 => DynamicImage::ImageRgba8(image.to_rgba8()),
+        SidPixelFormat::Rgba16 => DynamicImage::ImageRgba16(image.to_rgba16()),
+    }
+}
+
+/// Converts the color channels of `image` from one color space to the other,
+/// leaving alpha untouched.
+pub fn convert_color_space(image: &mut DynamicImage, from: SidColorSpace, to: SidColorSpace) {
+    if from == to {
+        return;
+    }
+
+    let convert: fn(f32) -> f32 = match to {
+        SidColorSpace::Linear => srgb_to_linear,
+        SidColorSpace::Srgb => linear_to_srgb,
+    };
+
+    let table_8: Vec<u8> = (0..=u8::MAX)
+        .map(|i| (convert(i as f32 / 255.0) * 255.0).round() as u8)
+        .collect();
+
+    match image {
+        DynamicImage::ImageLuma8(buffer) => convert_channels(buffer, 1, 1, &table_8),
+        DynamicImage::ImageLumaA8(buffer) => convert_channels(buffer, 2, 1, &table_8),
+        DynamicImage::ImageRgb8(buffer) => convert_channels(buffer, 3, 3, &table_8),
+        DynamicImage::ImageRgba8(buffer) => convert_channels(buffer, 4, 3, &table_8),
+        DynamicImage::ImageRgba16(buffer) => {
+            let table_16: Vec<u16> = (0..=u16::MAX)
+                .map(|i| (convert(i as f32 / 65535.0) * 65535.0).round() as u16)
+                .collect();
+
+            convert_channels(buffer, 4, 3, &table_16);
+        }
+        // Other layouts are converted before packing.
+        _ => {}
+    }
+}
+
+/// Maps the first `color_channel_count` channels of every pixel through
+/// `table`.
+fn convert_channels<T: Copy + Into<usize>>(
+    pixels: &mut [T],
+    channel_count: usize,
+    color_channel_count: usize,
+    table: &[T],
+) {
+    for pixel in pixels.chunks_exact_mut(channel_count) {
+        for channel in &mut pixel[..color_channel_count] {
+            *channel = table[(*channel).into()];
+        }
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// RGBA8 colors of a palette resource, which indexed textures point to.
+#[derive(Debug)]
+pub struct SidPalette {
+    pub id: sid::SidPaletteId,
+    pub colors: Vec<[u8; 4]>,
+}
+
+impl SidPalette {
+    /// Reads the colors of a palette image in order, keeping each only once.
+    pub fn from_image(
+        id: sid::SidPaletteId,
+        image: &DynamicImage,
+    ) -> SidAssetSerializationResult<Self> {
+        let colors = unique_colors(image.to_rgba8().as_raw());
+
+        if colors.is_empty() || colors.len() > MAX_PALETTE_COLOR_COUNT {
+            return Err(SidAssetSerializationError::Malformed(format!(
+                "A palette must have between 1 and {MAX_PALETTE_COLOR_COUNT} colors, got {}",
+                colors.len()
+            )));
+        }
+
+        Ok(Self { id, colors })
+    }
+
+    /// Maps every pixel of `image` to the index of the closest palette color.
+    pub fn index_pixels(&self, image: &DynamicImage) -> Vec<u8> {
+        let mut indices: HashMap<[u8; 4], u8> = HashMap::new();
+
+        image
+            .to_rgba8()
+            .pixels()
+            .map(|pixel| {
+                let color = normalize_transparent(pixel.0);
+
+                *indices
+                    .entry(color)
+                    .or_insert_with(|| self.closest_index(color))
+            })
+            .collect()
+    }
+
+    fn closest_index(&self, color: [u8; 4]) -> u8 {
+        let distance = |other: &[u8; 4]| -> u32 {
+            color
+                .iter()
+                .zip(other)
+                .map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32)
+                .sum()
+        };
+
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, other)| distance(other))
+            .map_or(0, |(i, _)| i as u8)
+    }
+}
+
+/// Reduces RGBA8 `pixels` to at most `color_count` colors, kept as they are
+/// when there are few enough of them.
+pub fn quantize(pixels: &[u8], color_count: usize) -> Vec<[u8; 4]> {
+    let colors = unique_colors(pixels);
+
+    if colors.len() <= color_count {
+        return colors;
+    }
+
+    let pixels: Vec<u8> = pixels
+        .chunks_exact(4)
+        .flat_map(|pixel| normalize_transparent(pixel.try_into().unwrap()))
+        .collect();
+
+    // NeuQuant may converge several neurons to the same color.
+    unique_colors(&NeuQuant::new(NEUQUANT_SAMPLE_FACTOR, color_count, &pixels).color_map_rgba())
+}
+
+fn unique_colors(pixels: &[u8]) -> Vec<[u8; 4]> {
+    let mut colors = vec![];
+    let mut seen = HashSet::new();
+
+    for pixel in pixels.chunks_exact(4) {
+        let color = normalize_transparent(pixel.try_into().unwrap());
+
+        if seen.insert(color) {
+            colors.push(color);
+        }
+    }
+
+    colors
+}
+
+// Fully transparent pixels all look the same, whatever their color.
+fn normalize_transparent(color: [u8; 4]) -> [u8; 4] {
+    if color[3] == 0 {
+        [0; 4]
+    } else {
+        color
+    }
+}
+
+/// Number of levels of a full mip chain, down to 1x1.
+pub fn mip_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+/// Returns the levels of the mip chain of `image` below the base level, each
+/// half the size of the previous one.
+pub fn generate_mipmaps(image: &DynamicImage, filter: SidMipmapFilter) -> Vec<DynamicImage> {
+    let (width, height) = image.dimensions();
+    let mut levels: Vec<DynamicImage> = vec![];
+
+    for level in 1..mip_count(width, height) {
+        let mip_width = (width >> level).max(1);
+        let mip_height = (height >> level).max(1);
+
+        let mip = match filter {
+            SidMipmapFilter::Box => downsample_box(levels.last().unwrap_or(image)),
+            SidMipmapFilter::Lanczos => {
+                image.resize_exact(mip_width, mip_height, FilterType::Lanczos3)
+            }
+        };
+
+        levels.push(mip);
+    }
+
+    levels
+}
+
+/// Halves `image`, averaging every 2x2 block. With an odd size, the last row
+/// or column is folded into the previous block.
+fn downsample_box(image: &DynamicImage) -> DynamicImage {
+    match image {
+        DynamicImage::ImageLuma8(buffer) => DynamicImage::ImageLuma8(downsample_buffer(buffer)),
+        DynamicImage::ImageLumaA8(buffer) => DynamicImage::ImageLumaA8(downsample_buffer(buffer)),
+        DynamicImage::ImageRgb8(buffer) => DynamicImage::ImageRgb8(downsample_buffer(buffer)),
+        DynamicImage::ImageRgba8(buffer) => DynamicImage::ImageRgba8(downsample_buffer(buffer)),
+        DynamicImage::ImageRgba16(buffer) => DynamicImage::ImageRgba16(downsample_buffer(buffer)),
+        // Other layouts are not packed as they are, so precision matters less.
+        _ => {
+            let (width, height) = image.dimensions();
+            image.resize_exact(
+                (width / 2).max(1),
+                (height / 2).max(1),
+                FilterType::Triangle,
+            )
+        }
+    }
+}
+
+fn downsample_buffer<P>(
+    buffer: &ImageBuffer<P, Vec<P::Subpixel>>,
+) -> ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: Pixel,
+    P::Subpixel: Into<u32> + TryFrom<u32>,
+{
+    let (width, height) = buffer.dimensions();
+    let mip_width = (width / 2).max(1);
+    let mip_height = (height / 2).max(1);
+
+    let channel_count = P::CHANNEL_COUNT as usize;
+    let pixels = buffer.as_raw();
+    let mut data = Vec::with_capacity(mip_width as usize * mip_height as usize * channel_count);
+
+    let block = |i: u32, mip_size: u32, size: u32| {
+        let end = if i + 1 == mip_size { size } else { 2 * i + 2 };
+        (2 * i).min(size - 1)..end
+    };
+
+    for y in 0..mip_height {
+        let rows = block(y, mip_height, height);
+
+        for x in 0..mip_width {
+            let columns = block(x, mip_width, width);
+            let count = (rows.len() * columns.len()) as u32;
+
+            for channel in 0..channel_count {
+                let mut sum: u32 = 0;
+
+                for row in rows.clone() {
+                    for column in columns.clone() {
+                        let i = (row as usize * width as usize + column as usize) * channel_count;
+                        sum += pixels[i + channel].into();
+                    }
+                }
+
+                let average = (sum + count / 2) / count;
+
+                data.push(
+                    P::Subpixel::try_from(average)
+                        .ok()
+                        .expect("Average should fit in a channel"),
+                );
+            }
+        }
+    }
+
+    ImageBuffer::from_raw(mip_width, mip_height, data)
+        .expect("Box-filtered level should match its size")
+}
+
+/// Compresses every mip level with `encoding`, returning the resulting format
+/// and the blocks of each level. Levels go through the UASTC intermediate
+/// format of Basis Universal before being transcoded.
+#[cfg(feature = "block-compression")]
+pub fn encode_blocks(
+    levels: &[&DynamicImage],
+    encoding: SidTextureEncoding,
+    color_space: SidColorSpace,
+) -> SidAssetSerializationResult<(sid_texture_format, Vec<Vec<u8>>)> {
+    use basis_universal::{
+        BasisTextureFormat, ColorSpace, Compressor, CompressorParams, TranscodeParameters,
+        Transcoder, TranscoderTextureFormat, UASTC_QUALITY_DEFAULT,
+    };
+    use std::borrow::Cow;
+
+    let has_alpha = levels
+        .first()
+        .is_some_and(|level| level.color().has_alpha());
+
+    // The compressor only reads 8-bit RGB and RGBA pixels.
+    let levels: Vec<Cow<DynamicImage>> = levels
+        .iter()
+        .map(|level| match level {
+            DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_) => Cow::Borrowed(*level),
+            _ if has_alpha => Cow::Owned(DynamicImage::ImageRgba8(level.to_rgba8())),
+            _ => Cow::Owned(DynamicImage::ImageRgb8(level.to_rgb8())),
+        })
+        .collect();
+
+    let (format, transcoder_format) = match encoding {
+        SidTextureEncoding::Raw => {
+            return Err(SidAssetSerializationError::UnsupportedFormat(
+                "Raw textures are not block-compressed".to_string(),
+            ))
+        }
+        SidTextureEncoding::Bc1 => (
+            sid_texture_format::SID_TEXTURE_FORMAT_BC1,
+            TranscoderTextureFormat::BC1_RGB,
+        ),
+        SidTextureEncoding::Bc3 => (
+            sid_texture_format::SID_TEXTURE_FORMAT_BC3,
+            TranscoderTextureFormat::BC3_RGBA,
+        ),
+        SidTextureEncoding::Bc7 => (
+            sid_texture_format::SID_TEXTURE_FORMAT_BC7,
+            TranscoderTextureFormat::BC7_RGBA,
+        ),
+        // ETC1 blocks are valid ETC2 RGB8 blocks.
+        SidTextureEncoding::Etc2 if !has_alpha => (
+            sid_texture_format::SID_TEXTURE_FORMAT_ETC2_RGB8,
+            TranscoderTextureFormat::ETC1_RGB,
+        ),
+        SidTextureEncoding::Etc2 => (
+            sid_texture_format::SID_TEXTURE_FORMAT_ETC2_RGBA8,
+            TranscoderTextureFormat::ETC2_RGBA,
+        ),
+        SidTextureEncoding::Astc => (
+            sid_texture_format::SID_TEXTURE_FORMAT_ASTC_4X4,
+            TranscoderTextureFormat::ASTC_4x4_RGBA,
+        ),
+    };
+
+    let mut params = CompressorParams::new();
+    params.set_basis_format(BasisTextureFormat::UASTC4x4);
+    params.set_uastc_quality_level(UASTC_QUALITY_DEFAULT);
+    params.set_generate_mipmaps(false);
+    params.set_color_space(match color_space {
+        SidColorSpace::Srgb => ColorSpace::Srgb,
+        SidColorSpace::Linear => ColorSpace::Linear,
+    });
+    params.set_print_status_to_stdout(false);
+
+    // Each level is its own image, as they are already downsampled.
+    for (i, level) in levels.iter().enumerate() {
+        let (width, height) = level.dimensions();
+        params.source_image_mut(i as u32).init(
+            level.as_bytes(),
+            width,
+            height,
+            level.color().channel_count(),
+        );
+    }
+
+    let mut compressor = Compressor::default();
+
+    // SAFETY: the parameters only reference images initialized above, with
+    // their actual sizes and channel counts.
+    unsafe {
+        if !compressor.init(&params) {
+            return Err(SidAssetSerializationError::Malformed(
+                "Unable to initialize the texture compressor".to_string(),
+            ));
+        }
+
+        compressor.process().map_err(|error| {
+            SidAssetSerializationError::Malformed(format!(
+                "Unable to compress texture: {:?}",
+                error
+            ))
+        })?;
+    }
+
+    let basis_file = compressor.basis_file();
+    let mut transcoder = Transcoder::new();
+
+    transcoder.prepare_transcoding(basis_file).map_err(|_| {
+        SidAssetSerializationError::Malformed("Unable to read compressed texture".to_string())
+    })?;
+
+    let mut level_data = Vec::with_capacity(levels.len());
+
+    for i in 0..levels.len() {
+        let parameters = TranscodeParameters {
+            image_index: i as u32,
+            ..Default::default()
+        };
+
+        let data = transcoder
+            .transcode_image_level(basis_file, transcoder_format, parameters)
+            .map_err(|error| {
+                SidAssetSerializationError::Malformed(format!(
+                    "Unable to transcode texture to {:?}: {:?}",
+                    encoding, error
+                ))
+            })?;
+
+        level_data.push(data);
+    }
+
+    transcoder.end_transcoding();
+    Ok((format, level_data))
+}
+
+#[cfg(not(feature = "block-compression"))]
+pub fn encode_blocks(
+    _levels: &[&DynamicImage],
+    encoding: SidTextureEncoding,
+    _color_space: SidColorSpace,
+) -> SidAssetSerializationResult<(sid_texture_format, Vec<Vec<u8>>)> {
+    Err(SidAssetSerializationError::UnsupportedFormat(format!(
+        "Unable to encode textures as {:?}: built without the block-compression feature",
+        encoding
+    )))
+}
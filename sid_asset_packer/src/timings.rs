@@ -0,0 +1,185 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use serde::Serialize;
+
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::report::SidReport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidStage {
+    /// Reading and parsing the JSON file of the asset.
+    Parse,
+    /// Decoding the source files, e.g. images or sounds.
+    Decode,
+    /// Converting and compressing the resource.
+    Encode,
+    /// Writing the resource to disk.
+    Write,
+}
+
+/// Time an asset spent in each stage of the pipeline. Nested stages are not
+/// counted in their parent, e.g. decoding a texture while encoding a sprite
+/// sheet is only decoding.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SidStageTimings {
+    pub parse_ms: f64,
+    pub decode_ms: f64,
+    pub encode_ms: f64,
+    pub write_ms: f64,
+}
+
+impl SidStageTimings {
+    fn add(&mut self, stage: SidStage, duration: Duration) {
+        let ms = duration.as_secs_f64() * 1000.0;
+
+        match stage {
+            SidStage::Parse => self.parse_ms += ms,
+            SidStage::Decode => self.decode_ms += ms,
+            SidStage::Encode => self.encode_ms += ms,
+            SidStage::Write => self.write_ms += ms,
+        }
+    }
+
+    pub fn total_ms(&self) -> f64 {
+        self.parse_ms + self.decode_ms + self.encode_ms + self.write_ms
+    }
+}
+
+struct SidTimingsCollector {
+    timings: SidStageTimings,
+    /// Time spent in the nested stages of each running stage.
+    nested: Vec<Duration>,
+}
+
+thread_local! {
+    static COLLECTOR: RefCell<Option<SidTimingsCollector>> = const { RefCell::new(None) };
+}
+
+/// Starts collecting the stage timings of the current thread.
+pub(crate) fn start() {
+    COLLECTOR.with_borrow_mut(|collector| {
+        *collector = Some(SidTimingsCollector {
+            timings: SidStageTimings::default(),
+            nested: vec![],
+        });
+    });
+}
+
+/// Stops collecting the stage timings of the current thread, and returns
+/// them.
+pub(crate) fn finish() -> Option<SidStageTimings> {
+    COLLECTOR
+        .with_borrow_mut(Option::take)
+        .map(|collector| collector.timings)
+}
+
+/// Runs `f`, counting its duration in `stage` if timings are collected.
+pub(crate) fn measure<R>(stage: SidStage, f: impl FnOnce() -> R) -> R {
+    let is_collecting = COLLECTOR.with_borrow_mut(|collector| match collector {
+        Some(collector) => {
+            collector.nested.push(Duration::ZERO);
+            true
+        }
+        None => false,
+    });
+
+    if !is_collecting {
+        return f();
+    }
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+
+    COLLECTOR.with_borrow_mut(|collector| {
+        let Some(collector) = collector else {
+            return;
+        };
+
+        let nested = collector.nested.pop().unwrap_or_default();
+        collector.timings.add(stage, elapsed.saturating_sub(nested));
+
+        if let Some(parent_nested) = collector.nested.last_mut() {
+            *parent_nested += elapsed;
+        }
+    });
+
+    result
+}
+
+/// Writer counting the time spent writing in the `Write` stage.
+pub(crate) struct SidTimedWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> SidTimedWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> Write for SidTimedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        measure(SidStage::Write, || self.inner.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        measure(SidStage::Write, || self.inner.flush())
+    }
+}
+
+/// Prints the stage timings of every asset of `report`, slowest first,
+/// followed by the totals of the run.
+pub fn print_timings(report: &SidReport) {
+    let mut assets: Vec<_> = report
+        .assets
+        .iter()
+        .filter_map(|asset| asset.timings.map(|timings| (asset, timings)))
+        .collect();
+
+    assets.sort_by(|(a, _), (b, _)| b.duration_ms.total_cmp(&a.duration_ms));
+
+    println!("Stage timings in milliseconds, slowest asset first:");
+    println!(
+        "{:>10} {:>10} {:>10} {:>10} {:>10} {:>10}  asset",
+        "total", "parse", "decode", "encode", "write", "other"
+    );
+
+    let mut totals = SidStageTimings::default();
+    let mut total_ms = 0.0;
+
+    for (asset, timings) in &assets {
+        print_row(
+            asset.duration_ms,
+            timings,
+            &asset.path.display().to_string(),
+        );
+
+        totals.parse_ms += timings.parse_ms;
+        totals.decode_ms += timings.decode_ms;
+        totals.encode_ms += timings.encode_ms;
+        totals.write_ms += timings.write_ms;
+        total_ms += asset.duration_ms;
+    }
+
+    print_row(total_ms, &totals, &format!("({} asset(s))", assets.len()));
+}
+
+fn print_row(duration_ms: f64, timings: &SidStageTimings, label: &str) {
+    println!(
+        "{:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}  {label}",
+        duration_ms,
+        timings.parse_ms,
+        timings.decode_ms,
+        timings.encode_ms,
+        timings.write_ms,
+        (duration_ms - timings.total_ms()).max(0.0),
+    );
+}
@@ -0,0 +1,1041 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use image::{ImageBuffer, Luma, LumaA, Rgb, Rgba};
+
+use std::{fs, path::Path};
+
+use crate::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAnimationDirection, SidAnimationEvent,
+        SidAnimationFrameAsset, SidAnimationFrameDims, SidAnimationFramePos, SidAnimationFrameTrim,
+        SidAudioAsset, SidBitmapFontAsset, SidBitmapFontGlyphAsset, SidBitmapFontKerningAsset,
+        SidBlendMode, SidBlobAsset, SidBoneKeyAsset, SidBoneTimelineAsset, SidBoneTimelineProperty,
+        SidBoneTransform, SidCharacterAsset, SidCharacterPartAsset, SidColorSpace, SidCurveAsset,
+        SidCurveInterpolation, SidCurveKeyAsset, SidKeyCurve, SidMaterialAsset, SidMeshAsset,
+        SidMeshSubmeshAsset, SidMeshTexture, SidMeshVertexAsset, SidNineSliceAsset,
+        SidNineSliceInsets, SidPackedAsset, SidPaletteAsset, SidPrefabAsset,
+        SidPrefabComponentAsset, SidSceneAsset, SidSceneEntityAsset, SidShaderAsset,
+        SidSkeletalAnimationAsset, SidSkeletonAsset, SidSkeletonAttachmentAsset,
+        SidSkeletonBoneAsset, SidSkeletonSlotAsset, SidSliceAsset, SidSliceKeyAsset, SidSlicePivot,
+        SidSliceRect, SidSlotKeyAsset, SidSlotTimelineAsset, SidSpriteSheetAsset, SidTextureAsset,
+        SidTilemapAsset, SidTilemapLayerAsset, SidTilemapTilesetAsset, SidTilesetAsset,
+        SidUniformValue,
+    },
+    error::{SidAssetFailure, SidToolError, SidToolResult},
+    manifest::SidManifest,
+    pak::DEFAULT_PAK_FILE_NAME,
+    resource,
+    resource::read::{
+        self, SidDecodedAnimation, SidDecodedAnimationDef, SidDecodedAudio, SidDecodedBitmapFont,
+        SidDecodedBlob, SidDecodedBoneTransform, SidDecodedCharacter, SidDecodedCurve,
+        SidDecodedCurveData, SidDecodedImage, SidDecodedMaterial, SidDecodedMesh,
+        SidDecodedNineSlice, SidDecodedPalette, SidDecodedPrefab, SidDecodedPrefabComponent,
+        SidDecodedResource, SidDecodedScene, SidDecodedShader, SidDecodedSkeletalAnimation,
+        SidDecodedSkeleton, SidDecodedSlice, SidDecodedSliceRect, SidDecodedSpriteSheet,
+        SidDecodedTexture, SidDecodedTilemap, SidDecodedTileset,
+    },
+    sid::{
+        self, sid_audio_format, sid_color_space, sid_shader_format, sid_shader_stage,
+        sid_texture_format,
+    },
+    sid_info, sid_warning,
+};
+
+struct SidUnpackContext<'a> {
+    manifest: Option<&'a SidManifest>,
+    assets_output_path: &'a Path,
+}
+
+impl SidUnpackContext<'_> {
+    /// Resources only store ids: names come from the manifest, and default to
+    /// the ids themselves.
+    fn name(&self, id: sid::SidStringId) -> String {
+        match self.manifest.and_then(|manifest| manifest.find(id)) {
+            Some(entry) => entry.name.clone(),
+            None => id.to_string(),
+        }
+    }
+
+    fn unpack(&self, resource: &SidDecodedResource) -> SidToolResult<()> {
+        match resource {
+            SidDecodedResource::SpriteSheet(sheet) => self.unpack_sprite_sheet(sheet),
+            SidDecodedResource::Texture(texture) => self.unpack_texture(texture),
+            SidDecodedResource::Palette(palette) => self.unpack_palette(palette),
+            SidDecodedResource::AnimationDef(def) => self.unpack_animation_def(def),
+            SidDecodedResource::Animation(animation) => self.unpack_animation(animation),
+            SidDecodedResource::Slice(slice) => self.unpack_slice(slice),
+            SidDecodedResource::NineSlice(nine_slice) => self.unpack_nine_slice(nine_slice),
+            SidDecodedResource::Character(character) => self.unpack_character(character),
+            SidDecodedResource::Skeleton(skeleton) => self.unpack_skeleton(skeleton),
+            SidDecodedResource::SkeletalAnimation(animation) => {
+                self.unpack_skeletal_animation(animation)
+            }
+            SidDecodedResource::Audio(audio) => self.unpack_audio(audio),
+            SidDecodedResource::Font(font) => {
+                // Only the rasterized glyphs are packed, not the font file.
+                sid_warning!(
+                    "Skipping font {:?}, which cannot be rebuilt from its resource.",
+                    self.name(font.id)
+                );
+                Ok(())
+            }
+            SidDecodedResource::BitmapFont(font) => self.unpack_bitmap_font(font),
+            SidDecodedResource::Mesh(mesh) => self.unpack_mesh(mesh),
+            SidDecodedResource::Curve(curve) => self.unpack_curve(curve),
+            SidDecodedResource::Tileset(tileset) => self.unpack_tileset(tileset),
+            SidDecodedResource::Tilemap(tilemap) => self.unpack_tilemap(tilemap),
+            SidDecodedResource::Shader(shader) => self.unpack_shader(shader),
+            SidDecodedResource::Material(material) => self.unpack_material(material),
+            SidDecodedResource::Blob(blob) => self.unpack_blob(blob),
+            SidDecodedResource::Prefab(prefab) => self.unpack_prefab(prefab),
+            SidDecodedResource::Scene(scene) => self.unpack_scene(scene),
+            SidDecodedResource::StringTable(table) => {
+                // Only the ids of the keys are packed, not the keys themselves.
+                sid_warning!(
+                    "Skipping string table {:?}, which cannot be rebuilt from its resource.",
+                    self.name(table.id)
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn unpack_sprite_sheet(&self, sheet: &SidDecodedSpriteSheet) -> SidToolResult<()> {
+        let name = self.name(sheet.id);
+        let folder = self
+            .assets_output_path
+            .join(SidSpriteSheetAsset::namespace());
+
+        fs::create_dir_all(&folder)?;
+
+        let image_name = format!("{name}.png");
+        let image_path = folder.join(&image_name);
+        let format = save_image(&sheet.image, &image_path)?;
+
+        let mut asset = SidSpriteSheetAsset::with_data(
+            name,
+            image_name.into(),
+            image_path,
+            sheet.image.width,
+            sheet.image.height,
+            format.to_string(),
+        );
+
+        asset.color_space = color_space(&sheet.image);
+        asset.pivot = sheet.pivot.map(|(x, y)| SidSlicePivot::with_coords(x, y));
+        asset.normal_map = sheet.normal_map_id.map(|id| self.name(id));
+        asset.emissive_map = sheet.emissive_map_id.map(|id| self.name(id));
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_texture(&self, texture: &SidDecodedTexture) -> SidToolResult<()> {
+        let name = self.name(texture.id);
+        let folder = self.assets_output_path.join(SidTextureAsset::namespace());
+
+        fs::create_dir_all(&folder)?;
+
+        let image_name = format!("{name}.png");
+        let image_path = folder.join(&image_name);
+        save_image(&texture.image, &image_path)?;
+
+        let mut asset = SidTextureAsset::with_data(name, image_name.into(), image_path);
+        asset.color_space = color_space(&texture.image);
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_palette(&self, palette: &SidDecodedPalette) -> SidToolResult<()> {
+        let name = self.name(palette.id);
+        let folder = self.assets_output_path.join(SidPaletteAsset::namespace());
+
+        fs::create_dir_all(&folder)?;
+
+        let image_name = format!("{name}.png");
+        let image_path = folder.join(&image_name);
+
+        ImageBuffer::<Rgba<u8>, _>::from_raw(
+            palette.colors.len() as u32,
+            1,
+            palette.colors.concat(),
+        )
+        .ok_or_else(|| SidToolError::Malformed(format!("Invalid palette {name:?}")))?
+        .save(&image_path)
+        .map_err(|error| {
+            SidToolError::IO(format!("Unable to save image {:?}: {error}", image_path))
+        })?;
+
+        let asset = SidPaletteAsset::with_data(name, image_name.into(), image_path);
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_animation_def(&self, def: &SidDecodedAnimationDef) -> SidToolResult<()> {
+        let frames: Vec<_> = def
+            .frames
+            .iter()
+            .map(|frame| {
+                let pos = SidAnimationFramePos::with_coords(frame.x, frame.y);
+                let dims = SidAnimationFrameDims::with_width_and_height(frame.width, frame.height);
+                let mut asset = SidAnimationFrameAsset::with_data(pos, dims, frame.duration);
+                asset.rotated = frame.rotated;
+                asset.page = frame.page;
+                asset.pivot = frame.pivot.map(|(x, y)| SidSlicePivot::with_coords(x, y));
+
+                let is_trimmed = frame.offset_x != 0
+                    || frame.offset_y != 0
+                    || frame.source_width != frame.width
+                    || frame.source_height != frame.height;
+
+                if is_trimmed {
+                    asset.trim = Some(SidAnimationFrameTrim::with_data(
+                        SidAnimationFramePos::with_coords(frame.offset_x, frame.offset_y),
+                        SidAnimationFrameDims::with_width_and_height(
+                            frame.source_width,
+                            frame.source_height,
+                        ),
+                    ));
+                }
+
+                asset
+            })
+            .collect();
+
+        let mut asset = SidAnimationDefAsset::with_data(
+            frames.len() as sid::SidAnimationFrameIndex,
+            frames,
+            self.name(def.id),
+            self.name(def.sheet_id),
+        );
+        asset.pages = def.page_ids.iter().map(|id| self.name(*id)).collect();
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_animation(&self, animation: &SidDecodedAnimation) -> SidToolResult<()> {
+        let direction = SidAnimationDirection::from_raw(animation.direction).ok_or_else(|| {
+            SidToolError::Malformed(format!(
+                "Unknown direction {} for animation {}",
+                animation.direction, animation.id
+            ))
+        })?;
+
+        let mut asset = SidAnimationAsset::with_data(
+            animation.offset,
+            animation.length,
+            self.name(animation.id),
+            self.name(animation.def_id),
+            direction,
+        );
+
+        asset.looping = animation.looping;
+        asset.speed_scale = animation.speed_scale;
+        asset.events = animation
+            .events
+            .iter()
+            .map(|(frame, name)| SidAnimationEvent::with_data(*frame, name.clone()))
+            .collect();
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_slice(&self, slice: &SidDecodedSlice) -> SidToolResult<()> {
+        let to_rect = |rect: &SidDecodedSliceRect| {
+            SidSliceRect::with_coords_and_size(rect.x, rect.y, rect.width, rect.height)
+        };
+
+        let keys = slice
+            .keys
+            .iter()
+            .map(|key| {
+                SidSliceKeyAsset::with_data(
+                    key.frame,
+                    to_rect(&key.bounds),
+                    key.center.as_ref().map(to_rect),
+                    key.pivot.map(|(x, y)| SidSlicePivot::with_coords(x, y)),
+                )
+            })
+            .collect();
+
+        // User data is not packed, so it cannot be restored.
+        let asset = SidSliceAsset::with_data(
+            self.name(slice.id),
+            self.name(slice.sheet_id),
+            keys,
+            String::new(),
+        );
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_nine_slice(&self, nine_slice: &SidDecodedNineSlice) -> SidToolResult<()> {
+        let region = &nine_slice.region;
+        let [left, top, right, bottom] = nine_slice.insets;
+
+        let asset = SidNineSliceAsset::with_data(
+            self.name(nine_slice.id),
+            self.name(nine_slice.sheet_id),
+            SidSliceRect::with_coords_and_size(region.x, region.y, region.width, region.height),
+            SidNineSliceInsets::with_sizes(left, top, right, bottom),
+        );
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_character(&self, character: &SidDecodedCharacter) -> SidToolResult<()> {
+        let parts = character
+            .parts
+            .iter()
+            .map(|part| {
+                SidCharacterPartAsset::with_data(
+                    part.name.clone(),
+                    self.name(part.sheet_id),
+                    self.name(part.def_id),
+                    part.animation_ids.iter().map(|id| self.name(*id)).collect(),
+                )
+            })
+            .collect();
+
+        let asset = SidCharacterAsset::with_data(self.name(character.id), parts);
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_skeleton(&self, skeleton: &SidDecodedSkeleton) -> SidToolResult<()> {
+        let name = self.name(skeleton.id);
+
+        let bone_name = |index: u16| {
+            skeleton
+                .bones
+                .get(index as usize)
+                .map(|bone| bone.name.clone())
+                .ok_or_else(|| {
+                    SidToolError::Malformed(format!("Unknown bone {index} in skeleton {name:?}"))
+                })
+        };
+
+        let slot_name = |index: u16| {
+            skeleton
+                .slots
+                .get(index as usize)
+                .map(|slot| slot.name.clone())
+                .ok_or_else(|| {
+                    SidToolError::Malformed(format!("Unknown slot {index} in skeleton {name:?}"))
+                })
+        };
+
+        let bones = skeleton
+            .bones
+            .iter()
+            .map(|bone| {
+                Ok(SidSkeletonBoneAsset::with_data(
+                    bone.name.clone(),
+                    bone.parent.map(bone_name).transpose()?,
+                    bone.length,
+                    to_bone_transform(bone.transform),
+                ))
+            })
+            .collect::<SidToolResult<_>>()?;
+
+        let slots = skeleton
+            .slots
+            .iter()
+            .map(|slot| {
+                Ok(SidSkeletonSlotAsset::with_data(
+                    slot.name.clone(),
+                    bone_name(slot.bone)?,
+                    slot.attachment.clone(),
+                    slot.color,
+                ))
+            })
+            .collect::<SidToolResult<_>>()?;
+
+        let attachments = skeleton
+            .attachments
+            .iter()
+            .map(|attachment| {
+                Ok(SidSkeletonAttachmentAsset::with_data(
+                    slot_name(attachment.slot)?,
+                    attachment.name.clone(),
+                    attachment.region.clone(),
+                    to_bone_transform(attachment.transform),
+                    attachment.width,
+                    attachment.height,
+                ))
+            })
+            .collect::<SidToolResult<_>>()?;
+
+        let asset = SidSkeletonAsset::with_data(name.clone(), bones, slots, attachments);
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_skeletal_animation(
+        &self,
+        animation: &SidDecodedSkeletalAnimation,
+    ) -> SidToolResult<()> {
+        let bones = animation
+            .bones
+            .iter()
+            .map(|timeline| {
+                let property =
+                    SidBoneTimelineProperty::from_raw(timeline.property).ok_or_else(|| {
+                        SidToolError::Malformed(format!(
+                            "Unknown property {} for bone {:?} of skeletal animation {}",
+                            timeline.property, timeline.bone, animation.id
+                        ))
+                    })?;
+
+                let keys = timeline
+                    .keys
+                    .iter()
+                    .map(|key| {
+                        let curve = match (key.curve, key.curve_points) {
+                            (resource::KEY_CURVE_LINEAR, _) => SidKeyCurve::Linear,
+                            (resource::KEY_CURVE_STEPPED, _) => SidKeyCurve::Stepped,
+                            (_, Some(points)) => SidKeyCurve::Bezier { points },
+                            (curve, None) => {
+                                return Err(SidToolError::Malformed(format!(
+                                    "Unknown curve {curve} for bone {:?} of skeletal animation {}",
+                                    timeline.bone, animation.id
+                                )))
+                            }
+                        };
+
+                        Ok(SidBoneKeyAsset {
+                            time: key.time,
+                            values: key.values,
+                            curve,
+                        })
+                    })
+                    .collect::<SidToolResult<_>>()?;
+
+                Ok(SidBoneTimelineAsset {
+                    bone: timeline.bone.clone(),
+                    property,
+                    keys,
+                })
+            })
+            .collect::<SidToolResult<_>>()?;
+
+        let slots = animation
+            .slots
+            .iter()
+            .map(|timeline| SidSlotTimelineAsset {
+                slot: timeline.slot.clone(),
+                keys: timeline
+                    .keys
+                    .iter()
+                    .map(|(time, attachment)| SidSlotKeyAsset {
+                        time: *time,
+                        attachment: attachment.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let asset = SidSkeletalAnimationAsset::with_data(
+            self.name(animation.id),
+            self.name(animation.skeleton_id),
+            animation.duration,
+            bones,
+            slots,
+        );
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_bitmap_font(&self, font: &SidDecodedBitmapFont) -> SidToolResult<()> {
+        let glyphs = font
+            .glyphs
+            .iter()
+            .map(|glyph| SidBitmapFontGlyphAsset {
+                character: glyph.character,
+                x: glyph.x,
+                y: glyph.y,
+                width: glyph.width,
+                height: glyph.height,
+                x_offset: glyph.x_offset,
+                y_offset: glyph.y_offset,
+                x_advance: glyph.x_advance,
+                page: glyph.page,
+            })
+            .collect();
+
+        let kernings = font
+            .kernings
+            .iter()
+            .map(|&(first, second, amount)| SidBitmapFontKerningAsset {
+                first,
+                second,
+                amount,
+            })
+            .collect();
+
+        let asset = SidBitmapFontAsset::with_data(
+            self.name(font.id),
+            font.size,
+            font.line_height,
+            font.base,
+            font.page_ids.iter().map(|&id| self.name(id)).collect(),
+            glyphs,
+            kernings,
+        );
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_mesh(&self, mesh: &SidDecodedMesh) -> SidToolResult<()> {
+        let vertices = mesh
+            .vertices
+            .iter()
+            .map(|vertex| SidMeshVertexAsset {
+                position: [vertex[0], vertex[1], vertex[2]],
+                normal: [vertex[3], vertex[4], vertex[5]],
+                uv: [vertex[6], vertex[7]],
+            })
+            .collect();
+
+        let submeshes = mesh
+            .submeshes
+            .iter()
+            .map(|submesh| {
+                let texture = match submesh.texture {
+                    None => None,
+                    Some((resource::MESH_TEXTURE_TEXTURE, id)) => {
+                        Some(SidMeshTexture::Texture(self.name(id)))
+                    }
+                    Some((resource::MESH_TEXTURE_SPRITE_SHEET, id)) => {
+                        Some(SidMeshTexture::SpriteSheet(self.name(id)))
+                    }
+                    Some((kind, _)) => {
+                        return Err(SidToolError::Malformed(format!(
+                            "Unknown texture kind {kind} of mesh {}",
+                            mesh.id
+                        )))
+                    }
+                };
+
+                Ok(SidMeshSubmeshAsset {
+                    first_index: submesh.first_index,
+                    index_count: submesh.index_count,
+                    texture,
+                })
+            })
+            .collect::<SidToolResult<_>>()?;
+
+        let asset = SidMeshAsset::with_data(
+            self.name(mesh.id),
+            vertices,
+            mesh.indices.clone(),
+            submeshes,
+        );
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_curve(&self, curve: &SidDecodedCurve) -> SidToolResult<()> {
+        let interpolation =
+            SidCurveInterpolation::from_raw(curve.interpolation).ok_or_else(|| {
+                SidToolError::Malformed(format!(
+                    "Unknown interpolation {} of curve {}",
+                    curve.interpolation, curve.id
+                ))
+            })?;
+
+        // Samples become keys, which the curve is sampled at again when
+        // repacked.
+        let (keys, sample_rate) = match &curve.data {
+            SidDecodedCurveData::Keyed(keys) => (keys.clone(), None),
+            SidDecodedCurveData::Sampled {
+                sample_rate,
+                start_time,
+                samples,
+            } => (
+                samples
+                    .iter()
+                    .enumerate()
+                    .map(|(i, values)| (start_time + i as f32 / sample_rate, values.clone()))
+                    .collect(),
+                Some(*sample_rate),
+            ),
+        };
+
+        let keys = keys
+            .into_iter()
+            .map(|(time, values)| SidCurveKeyAsset { time, values })
+            .collect();
+
+        let mut asset = SidCurveAsset::with_data(self.name(curve.id), interpolation, keys);
+        asset.sample_rate = sample_rate;
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_tileset(&self, tileset: &SidDecodedTileset) -> SidToolResult<()> {
+        let mut asset = SidTilesetAsset::with_data(
+            self.name(tileset.id),
+            self.name(tileset.sheet_id),
+            tileset.tile_width,
+            tileset.tile_height,
+            tileset.tile_count,
+            tileset.columns,
+        );
+
+        asset.spacing = tileset.spacing;
+        asset.margin = tileset.margin;
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_tilemap(&self, tilemap: &SidDecodedTilemap) -> SidToolResult<()> {
+        let tilesets = tilemap
+            .tilesets
+            .iter()
+            .map(|&(first_gid, tileset_id)| {
+                SidTilemapTilesetAsset::with_data(first_gid, self.name(tileset_id))
+            })
+            .collect();
+
+        // Layer names are not packed, so they are named after their index.
+        let layers = tilemap
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                SidTilemapLayerAsset::with_data(
+                    format!("layer_{i}"),
+                    layer.visible,
+                    layer.tiles.clone(),
+                )
+            })
+            .collect();
+
+        let asset = SidTilemapAsset::with_data(
+            self.name(tilemap.id),
+            tilemap.width,
+            tilemap.height,
+            tilemap.tile_width,
+            tilemap.tile_height,
+            tilesets,
+            layers,
+        );
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_audio(&self, audio: &SidDecodedAudio) -> SidToolResult<()> {
+        let name = self.name(audio.id);
+        let folder = self.assets_output_path.join(SidAudioAsset::namespace());
+
+        fs::create_dir_all(&folder)?;
+
+        let audio_name = match audio.format {
+            x if x == sid_audio_format::SID_AUDIO_FORMAT_PCM16 as i32 => {
+                let audio_name = format!("{name}.wav");
+                write_wav(&folder.join(&audio_name), audio)?;
+                audio_name
+            }
+            x if x == sid_audio_format::SID_AUDIO_FORMAT_OGG_VORBIS as i32 => {
+                let audio_name = format!("{name}.ogg");
+                fs::write(folder.join(&audio_name), &audio.payload)?;
+                audio_name
+            }
+            format => {
+                return Err(SidToolError::UnsupportedFormat(format!(
+                    "Unsupported audio format {format} for audio {name:?}"
+                )))
+            }
+        };
+
+        let audio_path = folder.join(&audio_name);
+        let asset = SidAudioAsset::with_data(name, audio_name.into(), audio_path);
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_shader(&self, shader: &SidDecodedShader) -> SidToolResult<()> {
+        let name = self.name(shader.id);
+
+        if shader.format != sid_shader_format::SID_SHADER_FORMAT_GLSL as i32 {
+            // Compiled shaders do not keep their GLSL sources.
+            sid_warning!(
+                "Skipping shader {:?}, which cannot be rebuilt from its resource.",
+                name
+            );
+            return Ok(());
+        }
+
+        let folder = self.assets_output_path.join(SidShaderAsset::namespace());
+        fs::create_dir_all(&folder)?;
+
+        let write_stage = |stage: i32, extension: &str| {
+            let code = shader
+                .stages
+                .iter()
+                .find(|decoded| decoded.stage == stage)
+                .ok_or_else(|| {
+                    SidToolError::Malformed(format!("Missing {extension} stage in shader {name:?}"))
+                })?;
+
+            let stage_name = format!("{name}.{extension}");
+            fs::write(folder.join(&stage_name), &code.code)?;
+            Ok::<_, SidToolError>(stage_name)
+        };
+
+        let vertex_name = write_stage(sid_shader_stage::SID_SHADER_STAGE_VERTEX as i32, "vert")?;
+        let fragment_name =
+            write_stage(sid_shader_stage::SID_SHADER_STAGE_FRAGMENT as i32, "frag")?;
+
+        let asset = SidShaderAsset::with_data(
+            name.clone(),
+            vertex_name.clone().into(),
+            folder.join(vertex_name),
+            fragment_name.clone().into(),
+            folder.join(fragment_name),
+        );
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_material(&self, material: &SidDecodedMaterial) -> SidToolResult<()> {
+        let blend_mode = SidBlendMode::from_raw(material.blend_mode).ok_or_else(|| {
+            SidToolError::Malformed(format!(
+                "Unknown blend mode {} of material {}",
+                material.blend_mode, material.id
+            ))
+        })?;
+
+        let mut asset =
+            SidMaterialAsset::with_data(self.name(material.id), self.name(material.shader_id));
+        asset.blend_mode = blend_mode;
+
+        for (sampler, texture_id) in &material.textures {
+            asset
+                .textures
+                .insert(sampler.clone(), self.name(*texture_id));
+        }
+
+        for (uniform, components) in &material.uniforms {
+            let value = SidUniformValue::from_components(components).ok_or_else(|| {
+                SidToolError::Malformed(format!(
+                    "Uniform {uniform:?} of material {} has {} components",
+                    material.id,
+                    components.len()
+                ))
+            })?;
+
+            asset.uniforms.insert(uniform.clone(), value);
+        }
+
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_blob(&self, blob: &SidDecodedBlob) -> SidToolResult<()> {
+        let name = self.name(blob.id);
+        let folder = self.assets_output_path.join(SidBlobAsset::namespace());
+
+        fs::create_dir_all(&folder)?;
+
+        // The original extension is not packed.
+        let blob_name = format!("{name}.bin");
+        let blob_path = folder.join(&blob_name);
+        fs::write(&blob_path, &blob.data)?;
+
+        let asset = SidBlobAsset::with_data(name, blob_name.into(), blob_path);
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn to_prefab_component(
+        &self,
+        component: &SidDecodedPrefabComponent,
+    ) -> SidPrefabComponentAsset {
+        match component {
+            SidDecodedPrefabComponent::Sprite { sheet_id } => SidPrefabComponentAsset::Sprite {
+                sheet_name: self.name(*sheet_id),
+            },
+            SidDecodedPrefabComponent::Animation { animation_id } => {
+                SidPrefabComponentAsset::Animation {
+                    animation_name: self.name(*animation_id),
+                }
+            }
+            SidDecodedPrefabComponent::Collider {
+                x,
+                y,
+                width,
+                height,
+                is_trigger,
+            } => SidPrefabComponentAsset::Collider {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+                is_trigger: *is_trigger,
+            },
+            SidDecodedPrefabComponent::Custom { values } => SidPrefabComponentAsset::Custom {
+                values: values.iter().cloned().collect(),
+            },
+        }
+    }
+
+    fn unpack_prefab(&self, prefab: &SidDecodedPrefab) -> SidToolResult<()> {
+        let components = prefab
+            .components
+            .iter()
+            .map(|component| self.to_prefab_component(component))
+            .collect();
+
+        let asset = SidPrefabAsset::with_data(self.name(prefab.id), components);
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+
+    fn unpack_scene(&self, scene: &SidDecodedScene) -> SidToolResult<()> {
+        let entities = scene
+            .entities
+            .iter()
+            .map(|entity| SidSceneEntityAsset {
+                name: entity.name.clone(),
+                prefab_name: self.name(entity.prefab_id),
+                transform: to_bone_transform(entity.transform),
+                layer: entity.layer,
+                overrides: entity
+                    .overrides
+                    .iter()
+                    .map(|component| self.to_prefab_component(component))
+                    .collect(),
+            })
+            .collect();
+
+        let asset = SidSceneAsset::with_data(self.name(scene.id), entities);
+        Ok(asset.write_to_folder(self.assets_output_path)?)
+    }
+}
+
+fn to_bone_transform(transform: SidDecodedBoneTransform) -> SidBoneTransform {
+    let [x, y, rotation, scale_x, scale_y] = transform;
+
+    SidBoneTransform {
+        x,
+        y,
+        rotation,
+        scale_x,
+        scale_y,
+    }
+}
+
+/// Images were packed either in their own color space or converted to the
+/// target one: both are the color space of the rebuilt image.
+fn color_space(image: &SidDecodedImage) -> SidColorSpace {
+    if image.color_space == sid_color_space::SID_COLOR_SPACE_LINEAR as i32 {
+        SidColorSpace::Linear
+    } else {
+        SidColorSpace::Srgb
+    }
+}
+
+/// Pixels of the base level of `image`, without the padding the texture may
+/// have been packed with.
+fn logical_pixels(image: &SidDecodedImage) -> SidToolResult<Vec<u8>> {
+    let level = &image.levels[0];
+
+    if (level.width, level.height) == (image.width, image.height) {
+        return Ok(image.texture().to_vec());
+    }
+
+    let pixel_count = level.width as usize * level.height as usize;
+    let pixel_size = level.data.len() / pixel_count.max(1);
+    let row_size = level.width as usize * pixel_size;
+    let logical_row_size = image.width as usize * pixel_size;
+
+    if row_size == 0
+        || image.width > level.width
+        || image.height > level.height
+        || level.data.len() < row_size * level.height as usize
+    {
+        return Err(SidToolError::Malformed(format!(
+            "Image of {}x{} pixel(s) with a base level of {}x{} pixel(s) and {} byte(s)",
+            image.width,
+            image.height,
+            level.width,
+            level.height,
+            level.data.len()
+        )));
+    }
+
+    Ok(level
+        .data
+        .chunks_exact(row_size)
+        .take(image.height as usize)
+        .flat_map(|row| &row[..logical_row_size])
+        .copied()
+        .collect())
+}
+
+/// Saves decoded pixels as a PNG file, and returns the name of their format.
+fn save_image(image: &SidDecodedImage, image_path: &Path) -> SidToolResult<&'static str> {
+    let (width, height) = (image.width, image.height);
+
+    // Mip levels are generated again when packing.
+    let texture = logical_pixels(image)?;
+
+    let (result, format) = match image.format {
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_L8 as i32 => (
+            ImageBuffer::<Luma<u8>, _>::from_raw(width, height, texture)
+                .map(|image| image.save(image_path)),
+            "L8",
+        ),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_LA8 as i32 => (
+            ImageBuffer::<LumaA<u8>, _>::from_raw(width, height, texture)
+                .map(|image| image.save(image_path)),
+            "LA88",
+        ),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGB8 as i32 => (
+            ImageBuffer::<Rgb<u8>, _>::from_raw(width, height, texture)
+                .map(|image| image.save(image_path)),
+            "RGB888",
+        ),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGBA8 as i32 => (
+            ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, texture)
+                .map(|image| image.save(image_path)),
+            "RGBA8888",
+        ),
+        x if x == sid_texture_format::SID_TEXTURE_FORMAT_RGBA16 as i32 => {
+            // Decoded channels are little endian.
+            let texture: Vec<u16> = texture
+                .chunks_exact(2)
+                .map(|channel| u16::from_le_bytes([channel[0], channel[1]]))
+                .collect();
+
+            (
+                ImageBuffer::<Rgba<u16>, _>::from_raw(width, height, texture)
+                    .map(|image| image.save(image_path)),
+                "RGBA16161616",
+            )
+        }
+        format => {
+            return Err(SidToolError::UnsupportedFormat(format!(
+                "Unable to decode texture {:?} packed in format {format}, only raw formats can be unpacked",
+                image_path
+            )))
+        }
+    };
+
+    result
+        .ok_or_else(|| {
+            SidToolError::Malformed(format!("Invalid texture size for image {:?}", image_path))
+        })?
+        .map_err(|error| {
+            SidToolError::IO(format!("Unable to save image {:?}: {error}", image_path))
+        })?;
+
+    Ok(format)
+}
+
+fn write_wav(path: &Path, audio: &SidDecodedAudio) -> SidToolResult<()> {
+    let spec = hound::WavSpec {
+        channels: audio.channel_count,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let io_error =
+        |error: hound::Error| SidToolError::IO(format!("Unable to write {:?}: {error}", path));
+
+    let mut writer = hound::WavWriter::create(path, spec).map_err(io_error)?;
+
+    for sample in audio.payload.chunks_exact(2) {
+        writer
+            .write_sample(i16::from_le_bytes([sample[0], sample[1]]))
+            .map_err(io_error)?;
+    }
+
+    writer.finalize().map_err(io_error)
+}
+
+/// Rebuilds the assets of a resources folder, written either as loose files or
+/// as a .sidpak archive. Names are restored from the manifest, when present.
+pub fn from_resources_to_assets<P1: AsRef<Path>, P2: AsRef<Path>>(
+    resources_input_path: P1,
+    assets_output_path: P2,
+) -> SidToolResult<()> {
+    let resources_input_path = resources_input_path.as_ref();
+    let assets_output_path = assets_output_path.as_ref();
+
+    fs::create_dir_all(assets_output_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Unable to create folder at path {:?}: {error}",
+            assets_output_path
+        ))
+    })?;
+
+    let manifest = SidManifest::load(resources_input_path).ok();
+
+    if manifest.is_none() {
+        sid_warning!(
+            "No manifest found in {:?}, assets will be named after their ids.",
+            resources_input_path
+        );
+    }
+
+    let context = SidUnpackContext {
+        manifest: manifest.as_ref(),
+        assets_output_path,
+    };
+
+    let entries = fs::read_dir(resources_input_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Failed to read directory {:?}: {error}",
+            resources_input_path
+        ))
+    })?;
+
+    let mut failures = vec![];
+    let mut count = 0;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        let is_pak = path
+            .file_name()
+            .is_some_and(|name| name == DEFAULT_PAK_FILE_NAME);
+        let is_resource = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| resource::resource_file_id(name).is_some());
+
+        if !is_pak && !is_resource {
+            continue;
+        }
+
+        let result = fs::read(&path)
+            .map_err(SidToolError::from)
+            .and_then(|data| {
+                if is_pak {
+                    Ok(read::decode_pak(&data)?.1)
+                } else {
+                    Ok(vec![read::read_resource(&data)?])
+                }
+            })
+            .and_then(|resources| {
+                for resource in &resources {
+                    context.unpack(resource)?;
+                }
+
+                Ok(resources.len())
+            });
+
+        match result {
+            Ok(unpacked) => count += unpacked,
+            Err(error) => failures.push(SidAssetFailure { path, error }),
+        }
+    }
+
+    sid_info!("Unpacked {count} resource(s) to {:?}.", assets_output_path);
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use xxhash_rust::xxh3::xxh3_64;
+
+use std::{collections::HashSet, fs, path::Path};
+
+use crate::{
+    compression,
+    error::{SidAssetFailure, SidToolError, SidToolResult},
+    manifest::{self, SidManifest, SidManifestEntry},
+    pak::{self, DEFAULT_PAK_FILE_NAME},
+    resource::{self, SidResourceHeader},
+    sid, sid_info, sid_warning,
+};
+
+/// Checks a resource as stored, possibly compressed: its header and checksum,
+/// and its size, hash and type against its manifest entry, if any. Returns its
+/// header.
+fn verify_resource(
+    data: &[u8],
+    entry: Option<&SidManifestEntry>,
+) -> SidToolResult<SidResourceHeader> {
+    if let Some(entry) = entry {
+        if entry.size != data.len() as u64 {
+            return Err(SidToolError::Malformed(format!(
+                "Resource of {} byte(s), expected {} from the manifest",
+                data.len(),
+                entry.size
+            )));
+        }
+
+        let hash = manifest::format_hash(xxh3_64(data));
+
+        if hash != entry.hash {
+            return Err(SidToolError::Malformed(format!(
+                "Resource hash {hash}, expected {} from the manifest",
+                entry.hash
+            )));
+        }
+    }
+
+    let data = compression::decompress(data)?;
+    let header = SidResourceHeader::check(&data)?;
+    header.check_checksum(&data)?;
+
+    if let Some(entry) = entry.filter(|entry| entry.resource_type != header.resource_type) {
+        return Err(SidToolError::Malformed(format!(
+            "Resource is a {:?}, but a {:?} in the manifest",
+            header.resource_type, entry.resource_type
+        )));
+    }
+
+    Ok(header)
+}
+
+/// Checks every resource of a .sidpak archive. Returns the ids found.
+fn verify_pak(
+    pak_path: &Path,
+    manifest: Option<&SidManifest>,
+    failures: &mut Vec<SidAssetFailure>,
+) -> SidToolResult<HashSet<sid::SidStringId>> {
+    let data = fs::read(pak_path)
+        .map_err(|error| SidToolError::IO(format!("Unable to read {:?}: {error}", pak_path)))?;
+
+    let (_, entries) = pak::read_pak(&data)?;
+    let mut ids = HashSet::new();
+
+    for entry in entries {
+        ids.insert(entry.id);
+
+        let manifest_entry = manifest.and_then(|manifest| manifest.find(entry.id));
+
+        let result = verify_resource(&entry.data, manifest_entry).and_then(|header| {
+            if header.resource_type != entry.resource_type {
+                return Err(SidToolError::Malformed(format!(
+                    "Resource is a {:?}, but a {:?} in the pak table",
+                    header.resource_type, entry.resource_type
+                )));
+            }
+
+            Ok(())
+        });
+
+        if let Err(error) = result {
+            failures.push(SidAssetFailure {
+                path: pak_path.to_path_buf(),
+                error: SidToolError::Malformed(format!("Resource {}: {error}", entry.id)),
+            });
+        }
+    }
+
+    Ok(ids)
+}
+
+/// Checks every loose resource of `resources_path`. Returns the ids found.
+fn verify_loose(
+    resources_path: &Path,
+    manifest: Option<&SidManifest>,
+    failures: &mut Vec<SidAssetFailure>,
+) -> SidToolResult<HashSet<sid::SidStringId>> {
+    // Stale files left by older builds are not part of this one.
+    let file_names: Option<HashSet<String>> = manifest.map(|manifest| {
+        manifest
+            .entries
+            .iter()
+            .map(|entry| entry.file_name())
+            .collect()
+    });
+
+    let entries = fs::read_dir(resources_path).map_err(|error| {
+        SidToolError::IO(format!(
+            "Failed to read directory {:?}: {error}",
+            resources_path
+        ))
+    })?;
+
+    let mut ids = HashSet::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        let Some(id) = resource::resource_file_id(file_name) else {
+            continue;
+        };
+
+        if file_names
+            .as_ref()
+            .is_some_and(|file_names| !file_names.contains(file_name))
+        {
+            continue;
+        }
+
+        ids.insert(id);
+
+        let result = fs::read(&path)
+            .map_err(|error| SidToolError::IO(format!("Unable to read resource: {error}")))
+            .and_then(|data| {
+                verify_resource(&data, manifest.and_then(|manifest| manifest.find(id)))
+            })
+            .map(|_| ());
+
+        if let Err(error) = result {
+            failures.push(SidAssetFailure { path, error });
+        }
+    }
+
+    Ok(ids)
+}
+
+/// Checks the resources of a resources folder, written either as loose files
+/// or as a .sidpak archive: every resource must have a valid header and
+/// checksum, and match its manifest entry, if there is a manifest. Resources
+/// of the manifest missing from the folder fail as well. Every problem is
+/// reported in a `SidToolError::AssetFailures` error.
+pub fn verify_resources<P: AsRef<Path>>(resources_path: P) -> SidToolResult<()> {
+    let resources_path = resources_path.as_ref();
+    let manifest = SidManifest::load(resources_path).ok();
+
+    if manifest.is_none() {
+        sid_warning!(
+            "No manifest found in {:?}, only checksums will be verified.",
+            resources_path
+        );
+    }
+
+    let pak_path = resources_path.join(DEFAULT_PAK_FILE_NAME);
+    let mut failures = vec![];
+
+    let ids = if pak_path.is_file() {
+        verify_pak(&pak_path, manifest.as_ref(), &mut failures)?
+    } else {
+        verify_loose(resources_path, manifest.as_ref(), &mut failures)?
+    };
+
+    for entry in manifest.iter().flat_map(|manifest| &manifest.entries) {
+        if !ids.contains(&entry.id) {
+            failures.push(SidAssetFailure {
+                path: resources_path.join(entry.file_name()),
+                error: SidToolError::IO(format!(
+                    "Missing resource {:?} ({}) from the manifest",
+                    entry.name, entry.id
+                )),
+            });
+        }
+    }
+
+    sid_info!(
+        "Verified {} resource(s) in {:?}, {} failure(s).",
+        ids.len(),
+        resources_path,
+        failures.len()
+    );
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        Err(SidToolError::AssetFailures(failures))
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{self, Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    error::{SidToolError, SidToolResult},
+    sid_debug, sid_error, sid_warning,
+};
+
+/// File listing, in gitignore syntax, the files and folders of its folder that
+/// the tools skip, e.g. editor backups or work in progress.
+pub static IGNORE_FILE_NAME: &str = ".sidignore";
+
+// Shared by every directory walk of the tools, so that they all agree on links.
+static FOLLOW_SYMLINKS: AtomicBool = AtomicBool::new(false);
+
+/// Makes every directory walk follow symbolic links (and junctions on
+/// Windows) to files and folders, instead of skipping them.
+pub fn set_follow_symlinks(follow_symlinks: bool) {
+    FOLLOW_SYMLINKS.store(follow_symlinks, Ordering::Relaxed);
+}
+
+pub fn follows_symlinks() -> bool {
+    FOLLOW_SYMLINKS.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SidEntryKind {
+    File,
+    Folder,
+}
+
+/// Kind of a directory entry, or of the entry a link points to when links are
+/// followed. Skipped links, broken links and other entries have none.
+pub fn entry_kind(entry: &fs::DirEntry) -> Option<SidEntryKind> {
+    let mut file_type = entry.file_type().ok()?;
+
+    if file_type.is_symlink() {
+        if !follows_symlinks() {
+            sid_debug!("Skipping symbolic link {:?}.", entry.path());
+            return None;
+        }
+
+        file_type = match fs::metadata(entry.path()) {
+            Ok(metadata) => metadata.file_type(),
+            Err(error) => {
+                sid_warning!("Skipping broken link {:?}: {error}", entry.path());
+                return None;
+            }
+        };
+    }
+
+    if file_type.is_dir() {
+        Some(SidEntryKind::Folder)
+    } else if file_type.is_file() {
+        Some(SidEntryKind::File)
+    } else {
+        None
+    }
+}
+
+/// Folders a walk went through, so that links leading back to one of them do
+/// not make it loop forever.
+pub struct SidVisitedFolders {
+    folders: HashSet<PathBuf>,
+}
+
+impl SidVisitedFolders {
+    pub fn new() -> Self {
+        Self {
+            folders: HashSet::new(),
+        }
+    }
+
+    /// Whether the walk enters `folder` for the first time. Folders are only
+    /// told apart by their real path when links are followed, since they can
+    /// not be reached twice otherwise.
+    pub fn visit(&mut self, folder: &Path) -> bool {
+        if !follows_symlinks() {
+            return true;
+        }
+
+        let Ok(real_path) = fs::canonicalize(folder) else {
+            return true;
+        };
+
+        if self.folders.insert(real_path) {
+            return true;
+        }
+
+        sid_warning!(
+            "Skipping {:?}, a link to a folder already walked through.",
+            folder
+        );
+        false
+    }
+}
+
+struct SidIgnorePattern {
+    matcher: GlobMatcher,
+    /// Re-includes what an earlier pattern ignored, e.g. `!keep.json`.
+    is_negated: bool,
+    /// Only matches folders, e.g. `_wip/`.
+    is_folder_only: bool,
+}
+
+/// Patterns of an ignore file, matched against paths relative to its folder.
+struct SidIgnoreFile {
+    folder: PathBuf,
+    patterns: Vec<SidIgnorePattern>,
+}
+
+impl SidIgnoreFile {
+    fn parse(folder: PathBuf, contents: &str, path: &Path) -> SidToolResult<Self> {
+        let mut patterns = vec![];
+
+        for line in contents.lines() {
+            let line = line.trim_end();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (is_negated, line) = match line.strip_prefix('!') {
+                Some(line) => (true, line),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+
+            let (is_folder_only, line) = match line.strip_suffix('/') {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+
+            // Patterns holding a slash are relative to the folder of the file,
+            // the others match at any depth.
+            let glob = match line.strip_prefix('/') {
+                Some(line) => line.to_string(),
+                None if line.contains('/') => line.to_string(),
+                None => format!("**/{line}"),
+            };
+
+            let matcher = GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|error| {
+                    SidToolError::Malformed(format!(
+                        "Invalid pattern {:?} in {:?}: {error}",
+                        line, path
+                    ))
+                })?
+                .compile_matcher();
+
+            patterns.push(SidIgnorePattern {
+                matcher,
+                is_negated,
+                is_folder_only,
+            });
+        }
+
+        Ok(Self { folder, patterns })
+    }
+}
+
+/// Ignore files applying to a directory walk: the ones of the folder it starts
+/// from and of its parents, and the ones of the folders it goes through.
+pub struct SidIgnoreRules {
+    files: Vec<SidIgnoreFile>,
+}
+
+impl SidIgnoreRules {
+    pub fn new() -> Self {
+        Self { files: vec![] }
+    }
+
+    /// Rules of a walk starting from `root`, with the ignore files of its
+    /// parents, the outermost first.
+    pub fn for_root(root: &Path) -> Self {
+        let mut rules = Self::new();
+
+        let Ok(root) = path::absolute(root) else {
+            return rules;
+        };
+
+        let mut folders: Vec<&Path> = root.ancestors().skip(1).collect();
+        folders.reverse();
+
+        for folder in folders {
+            rules.load(folder);
+        }
+
+        rules
+    }
+
+    /// Adds the ignore file of `folder`, if any, over the ones added before.
+    /// Folders must be added before their subfolders.
+    pub fn load(&mut self, folder: &Path) {
+        let path = folder.join(IGNORE_FILE_NAME);
+
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return;
+        };
+
+        let Ok(folder) = path::absolute(folder) else {
+            return;
+        };
+
+        match SidIgnoreFile::parse(folder, &contents, &path) {
+            Ok(file) => self.files.push(file),
+            Err(error) => sid_error!("{error}"),
+        }
+    }
+
+    /// Whether the file or folder at `path` is ignored. The last matching
+    /// pattern wins, the ones of the innermost ignore files coming last.
+    pub fn is_ignored(&self, path: &Path, is_folder: bool) -> bool {
+        let Ok(path) = path::absolute(path) else {
+            return false;
+        };
+
+        let mut is_ignored = false;
+
+        for file in &self.files {
+            let Ok(relative_path) = path.strip_prefix(&file.folder) else {
+                continue;
+            };
+
+            for pattern in &file.patterns {
+                if pattern.is_folder_only && !is_folder {
+                    continue;
+                }
+
+                if pattern.matcher.is_match(relative_path) {
+                    is_ignored = !pattern.is_negated;
+                }
+            }
+        }
+
+        if is_ignored {
+            sid_debug!("Ignoring {:?}.", path);
+        }
+
+        is_ignored
+    }
+}
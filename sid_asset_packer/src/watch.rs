@@ -0,0 +1,85 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use crate::{sid_error, sid_info, walk};
+
+/// Editors and exporters tend to write a file in several steps, so events are
+/// gathered until the watched folder stays quiet for this long.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(250);
+
+/// Calls `on_change` every time something changes in `watched_path`, until the
+/// watcher stops. Changes happening inside `ignored_paths` (e.g. an output
+/// folder nested in the input one) are ignored to avoid feedback loops.
+pub fn watch<P: AsRef<Path>, F: FnMut()>(
+    watched_path: P,
+    ignored_paths: &[PathBuf],
+    on_change: F,
+) -> notify::Result<()> {
+    watch_all(&[watched_path], ignored_paths, on_change)
+}
+
+/// Same as `watch`, for changes in any of `watched_paths`.
+pub fn watch_all<P: AsRef<Path>, F: FnMut()>(
+    watched_paths: &[P],
+    ignored_paths: &[PathBuf],
+    mut on_change: F,
+) -> notify::Result<()> {
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let config = Config::default().with_follow_symlinks(walk::follows_symlinks());
+    let mut watcher = RecommendedWatcher::new(tx, config)?;
+
+    for watched_path in watched_paths {
+        let watched_path = fs::canonicalize(watched_path).map_err(notify::Error::io)?;
+        watcher.watch(&watched_path, RecursiveMode::Recursive)?;
+        sid_info!("Watching {:?} for changes...", watched_path);
+    }
+
+    let ignored_paths: Vec<PathBuf> = ignored_paths
+        .iter()
+        .filter_map(|path| fs::canonicalize(path).ok())
+        .collect();
+
+    let is_relevant = |event: &Event| {
+        !matches!(event.kind, EventKind::Access(_))
+            && event.paths.iter().any(|path| {
+                !ignored_paths
+                    .iter()
+                    .any(|ignored_path| path.starts_with(ignored_path))
+            })
+    };
+
+    while let Ok(result) = rx.recv() {
+        let mut changed = match result {
+            Ok(event) => is_relevant(&event),
+            Err(error) => {
+                sid_error!("Watch error: {error}");
+                false
+            }
+        };
+
+        loop {
+            match rx.recv_timeout(DEBOUNCE_DELAY) {
+                Ok(Ok(event)) => changed |= is_relevant(&event),
+                Ok(Err(error)) => sid_error!("Watch error: {error}"),
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+
+        if changed {
+            on_change();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,1839 @@
+// Copyright 2024 m4jr0. All Rights Reserved.
+// Use of this source code is governed by the MIT
+// license that can be found in the LICENSE file.
+
+// Assets of every built-in resource type, written in both byte orders then
+// read back. Assets reading files get them from a temporary folder.
+
+use image::{Rgba, RgbaImage};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use xxhash_rust::xxh3::xxh3_64;
+
+use sid_asset_packer::{
+    asset::{
+        SidAnimationAsset, SidAnimationDefAsset, SidAudioAsset, SidBitmapFontAsset, SidBlendMode,
+        SidBlobAsset, SidBoneTimelineProperty, SidCharacterAsset, SidCurveAsset,
+        SidCurveInterpolation, SidMaterialAsset, SidMeshAsset, SidNineSliceAsset, SidPaletteAsset,
+        SidPrefabAsset, SidSceneAsset, SidShaderAsset, SidSkeletalAnimationAsset, SidSkeletonAsset,
+        SidSliceAsset, SidSpriteSheetAsset, SidStringTableAsset, SidTextureAsset, SidTilemapAsset,
+        SidTilesetAsset,
+    },
+    atlas::{self, SidAtlasOptions},
+    cache::CACHE_FILE_NAME,
+    config::SidPackage,
+    error::{SidAssetFailure, SidToolError},
+    id_overrides::{self, SidIdOverrides},
+    id_registry::SidIdCollision,
+    manifest::{SidManifest, MANIFEST_FILE_NAME},
+    report::{SidAssetStatus, SidReport},
+    resource::{
+        self,
+        read::{
+            decode_resource, SidDecodedCurveData, SidDecodedPrefabComponent, SidDecodedResource,
+        },
+        SerializeSidAsset, SidEndianness, SidInputCollisionPolicy, SidOverwritePolicy,
+        SidPackOptions, SidResourceWriter, MESH_TEXTURE_SPRITE_SHEET, MESH_TEXTURE_TEXTURE,
+        RESOURCE_CHECKSUM_SIZE,
+    },
+    sid::{self, SidBackend, SidIdKind, SidLibBackend, SidNamespaces, SidTestBackend},
+    unpack, walk,
+};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Held by the tests setting the id overrides, which every pack of the
+/// process reads.
+static ID_OVERRIDES_LOCK: Mutex<()> = Mutex::new(());
+
+fn from_json<A: DeserializeOwned>(value: Value) -> A {
+    serde_json::from_value(value).expect("Invalid asset")
+}
+
+/// Writes `asset`, as if read from `in_path`, in both byte orders, and decodes
+/// the resources.
+fn round_trip<A: SerializeSidAsset>(asset: &A, in_path: &Path) -> Vec<SidDecodedResource> {
+    [SidEndianness::Little, SidEndianness::Big]
+        .into_iter()
+        .map(|endianness| {
+            let mut out = SidResourceWriter::new(vec![], endianness);
+            asset
+                .serialize_resource(in_path, &mut out)
+                .expect("Unable to write the resource");
+            let data = out.finish().expect("Unable to write the resource");
+            decode_resource(&data).expect("Unable to read the resource back")
+        })
+        .collect()
+}
+
+/// Path of an asset of the temporary folder `folder`, whose files are
+/// relative to it.
+fn asset_path(folder: &tempfile::TempDir) -> PathBuf {
+    folder.path().join("asset.json")
+}
+
+fn in_memory_path() -> &'static Path {
+    Path::new("assets/asset.json")
+}
+
+/// 2x2 image of two colors, saved as `name` in `folder`.
+fn save_image(folder: &tempfile::TempDir, name: &str) -> RgbaImage {
+    let image = RgbaImage::from_fn(2, 2, |x, _| match x {
+        0 => Rgba([255, 0, 0, 255]),
+        _ => Rgba([0, 0, 255, 255]),
+    });
+
+    image
+        .save(folder.path().join(name))
+        .expect("Unable to save the image");
+    image
+}
+
+#[test]
+fn sprite_sheets_round_trip() {
+    let folder = tempfile::tempdir().unwrap();
+    let image = save_image(&folder, "hero.png");
+
+    let asset: SidSpriteSheetAsset = from_json(json!({
+        "name": "hero",
+        "image_path": "hero.png",
+        "width": 2,
+        "height": 2,
+        "format": "RGBA8888",
+        "pivot": { "x": 1, "y": 2 },
+        "normal_map": "hero_normals",
+    }));
+
+    for resource in round_trip(&asset, &asset_path(&folder)) {
+        let SidDecodedResource::SpriteSheet(sheet) = resource else {
+            panic!("expected a sprite sheet");
+        };
+
+        assert_eq!(sheet.id, asset.resource_id());
+        assert_eq!(sheet.pivot, Some((1, 2)));
+        assert_eq!(
+            sheet.normal_map_id,
+            Some(sid::generate_texture_id("hero_normals"))
+        );
+        assert_eq!(sheet.emissive_map_id, None);
+        assert_eq!((sheet.image.width, sheet.image.height), (2, 2));
+        assert_eq!(sheet.image.channel_count, 4);
+        assert_eq!(sheet.image.texture(), image.as_raw().as_slice());
+    }
+}
+
+#[test]
+fn animation_defs_round_trip() {
+    let asset: SidAnimationDefAsset = from_json(json!({
+        "name": "hero",
+        "sheet_name": "hero",
+        "pages": ["hero_1"],
+        "frame_count": 3,
+        "frames": [
+            {
+                "pos": { "x": 0, "y": 0 },
+                "dims": { "width": 16, "height": 24 },
+                "duration": 100,
+                "trim": {
+                    "offset": { "x": 2, "y": 1 },
+                    "source_dims": { "width": 20, "height": 26 },
+                },
+                "pivot": { "x": 10, "y": 26 },
+            },
+            {
+                "pos": { "x": 16, "y": 0 },
+                "dims": { "width": 16, "height": 24 },
+                "duration": 150,
+                "rotated": true,
+                "page": 1,
+            },
+            {
+                "pos": { "x": 0, "y": 0 },
+                "dims": { "width": 16, "height": 24 },
+                "duration": 200,
+                "trim": {
+                    "offset": { "x": 2, "y": 1 },
+                    "source_dims": { "width": 20, "height": 26 },
+                },
+            },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::AnimationDef(def) = resource else {
+            panic!("expected an animation definition");
+        };
+
+        assert_eq!(def.id, asset.resource_id());
+        assert_eq!(def.sheet_id, sid::generate_sprite_sheet_id("hero"));
+        assert_eq!(def.page_ids, [sid::generate_sprite_sheet_id("hero_1")]);
+        assert_eq!(def.frames.len(), 3);
+        // Held frames share their region.
+        assert_eq!(def.region_count(), 2);
+        assert_eq!(def.frames[0].region, def.frames[2].region);
+
+        let first = &def.frames[0];
+        assert_eq!(
+            (first.x, first.y, first.width, first.height),
+            (0, 0, 16, 24)
+        );
+        assert_eq!((first.offset_x, first.offset_y), (2, 1));
+        assert_eq!((first.source_width, first.source_height), (20, 26));
+        assert_eq!(first.pivot, Some((10, 26)));
+        assert_eq!(first.duration, 100);
+        assert!(!first.rotated);
+
+        let second = &def.frames[1];
+        assert_eq!((second.x, second.y), (16, 0));
+        assert!(second.rotated);
+        assert_eq!(second.page, 1);
+        assert_eq!(second.pivot, None);
+        assert_eq!(second.duration, 150);
+
+        assert_eq!(def.frames[2].duration, 200);
+    }
+}
+
+#[test]
+fn animations_round_trip() {
+    let asset: SidAnimationAsset = from_json(json!({
+        "name": "hero_run",
+        "def_name": "hero",
+        "offset": 2,
+        "length": 4,
+        "direction": "pingpong",
+        "loop": false,
+        "speed_scale": 1.5,
+        "events": [{ "frame": 3, "name": "step" }],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Animation(animation) = resource else {
+            panic!("expected an animation");
+        };
+
+        assert_eq!(animation.id, asset.resource_id());
+        assert_eq!(animation.def_id, sid::generate_animation_def_id("hero"));
+        assert_eq!((animation.offset, animation.length), (2, 4));
+        assert_eq!(animation.direction, asset.direction as u8);
+        assert!(!animation.looping);
+        assert_eq!(animation.speed_scale, 1.5);
+        assert_eq!(animation.events, [(3, "step".to_string())]);
+    }
+}
+
+#[test]
+fn slices_round_trip() {
+    let asset: SidSliceAsset = from_json(json!({
+        "name": "hero_hitbox",
+        "sheet_name": "hero",
+        "keys": [
+            {
+                "frame": 0,
+                "bounds": { "x": 1, "y": 2, "width": 10, "height": 12 },
+                "center": { "x": 2, "y": 3, "width": 4, "height": 5 },
+                "pivot": { "x": 5, "y": 6 },
+            },
+            {
+                "frame": 2,
+                "bounds": { "x": 3, "y": 4, "width": 8, "height": 9 },
+            },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Slice(slice) = resource else {
+            panic!("expected a slice");
+        };
+
+        assert_eq!(slice.id, asset.resource_id());
+        assert_eq!(slice.sheet_id, sid::generate_sprite_sheet_id("hero"));
+        assert_eq!(slice.keys.len(), 2);
+
+        let first = &slice.keys[0];
+        assert_eq!(first.frame, 0);
+        assert_eq!(
+            (
+                first.bounds.x,
+                first.bounds.y,
+                first.bounds.width,
+                first.bounds.height
+            ),
+            (1, 2, 10, 12)
+        );
+        let center = first.center.as_ref().expect("missing center");
+        assert_eq!(
+            (center.x, center.y, center.width, center.height),
+            (2, 3, 4, 5)
+        );
+        assert_eq!(first.pivot, Some((5, 6)));
+
+        let second = &slice.keys[1];
+        assert_eq!(second.frame, 2);
+        assert!(second.center.is_none());
+        assert_eq!(second.pivot, None);
+    }
+}
+
+#[test]
+fn audio_round_trips() {
+    let folder = tempfile::tempdir().unwrap();
+    let samples = [0i16, 1000, -1000, i16::MAX];
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 22050,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(folder.path().join("jump.wav"), spec).unwrap();
+
+    for sample in samples {
+        writer.write_sample(sample).unwrap();
+    }
+
+    writer.finalize().unwrap();
+
+    let asset: SidAudioAsset = from_json(json!({
+        "name": "jump",
+        "audio_path": "jump.wav",
+    }));
+
+    let payload: Vec<u8> = samples
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect();
+
+    for resource in round_trip(&asset, &asset_path(&folder)) {
+        let SidDecodedResource::Audio(audio) = resource else {
+            panic!("expected an audio clip");
+        };
+
+        assert_eq!(audio.id, asset.resource_id());
+        assert_eq!((audio.sample_rate, audio.channel_count), (22050, 1));
+        assert_eq!(audio.payload, payload);
+    }
+}
+
+#[test]
+fn tilesets_round_trip() {
+    let asset: SidTilesetAsset = from_json(json!({
+        "name": "dungeon",
+        "sheet_name": "dungeon_tiles",
+        "tile_width": 16,
+        "tile_height": 8,
+        "tile_count": 12,
+        "columns": 4,
+        "spacing": 1,
+        "margin": 2,
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Tileset(tileset) = resource else {
+            panic!("expected a tileset");
+        };
+
+        assert_eq!(tileset.id, asset.resource_id());
+        assert_eq!(
+            tileset.sheet_id,
+            sid::generate_sprite_sheet_id("dungeon_tiles")
+        );
+        assert_eq!((tileset.tile_width, tileset.tile_height), (16, 8));
+        assert_eq!((tileset.tile_count, tileset.columns), (12, 4));
+        assert_eq!((tileset.spacing, tileset.margin), (1, 2));
+    }
+}
+
+#[test]
+fn tilemaps_round_trip() {
+    let asset: SidTilemapAsset = from_json(json!({
+        "name": "dungeon_1",
+        "width": 3,
+        "height": 2,
+        "tile_width": 16,
+        "tile_height": 16,
+        "tilesets": [
+            { "first_gid": 1, "tileset_name": "dungeon" },
+            { "first_gid": 13, "tileset_name": "props" },
+        ],
+        "layers": [
+            { "name": "ground", "tiles": [1, 2, 3, 4, 5, 6] },
+            { "name": "props", "visible": false, "tiles": [0, 13, 0, 0, 0, 14] },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Tilemap(tilemap) = resource else {
+            panic!("expected a tilemap");
+        };
+
+        assert_eq!(tilemap.id, asset.resource_id());
+        assert_eq!((tilemap.width, tilemap.height), (3, 2));
+        assert_eq!((tilemap.tile_width, tilemap.tile_height), (16, 16));
+        assert_eq!(
+            tilemap.tilesets,
+            [
+                (1, sid::generate_tileset_id("dungeon")),
+                (13, sid::generate_tileset_id("props")),
+            ]
+        );
+        assert_eq!(tilemap.layers.len(), 2);
+        assert!(tilemap.layers[0].visible);
+        assert_eq!(tilemap.layers[0].tiles, [1, 2, 3, 4, 5, 6]);
+        assert!(!tilemap.layers[1].visible);
+        assert_eq!(tilemap.layers[1].tiles, [0, 13, 0, 0, 0, 14]);
+    }
+}
+
+#[test]
+fn shaders_round_trip() {
+    let folder = tempfile::tempdir().unwrap();
+    let vertex = "#version 330 core\nvoid main() { gl_Position = vec4(0.0); }\n";
+    let fragment = "#version 330 core\nout vec4 color;\nvoid main() { color = vec4(1.0); }\n";
+    fs::write(folder.path().join("flat.vert"), vertex).unwrap();
+    fs::write(folder.path().join("flat.frag"), fragment).unwrap();
+
+    let asset: SidShaderAsset = from_json(json!({
+        "name": "flat",
+        "vertex_path": "flat.vert",
+        "fragment_path": "flat.frag",
+        "validate": false,
+    }));
+
+    for resource in round_trip(&asset, &asset_path(&folder)) {
+        let SidDecodedResource::Shader(shader) = resource else {
+            panic!("expected a shader");
+        };
+
+        assert_eq!(shader.id, asset.resource_id());
+        assert_eq!(shader.stages.len(), 2);
+        assert!(shader.stages[0].code.starts_with(vertex.as_bytes()));
+        assert!(shader.stages[1].code.starts_with(fragment.as_bytes()));
+    }
+}
+
+#[test]
+fn blobs_round_trip() {
+    let folder = tempfile::tempdir().unwrap();
+    let data = b"\x00level\xffdata".to_vec();
+    fs::write(folder.path().join("level.bin"), &data).unwrap();
+
+    let asset: SidBlobAsset = from_json(json!({
+        "name": "level",
+        "blob_path": "level.bin",
+    }));
+
+    for resource in round_trip(&asset, &asset_path(&folder)) {
+        let SidDecodedResource::Blob(blob) = resource else {
+            panic!("expected a blob");
+        };
+
+        assert_eq!(blob.id, asset.resource_id());
+        assert_eq!(blob.data, data);
+    }
+}
+
+#[test]
+fn pack_backends_only_apply_to_their_pack() {
+    let folder = tempfile::tempdir().unwrap();
+    let blobs_path = folder.path().join("assets").join(sid::get_blob_namespace());
+    fs::create_dir_all(&blobs_path).unwrap();
+    fs::write(blobs_path.join("level.bin"), b"level").unwrap();
+
+    let asset: SidBlobAsset = from_json(json!({
+        "name": "level",
+        "blob_path": "level.bin",
+    }));
+    fs::write(
+        blobs_path.join("level.json"),
+        serde_json::to_string(&asset).unwrap(),
+    )
+    .unwrap();
+
+    let backend = Arc::new(SidTestBackend::new());
+    let test_id = backend.generate_id(SidIdKind::Blob, "level");
+    let id = sid::generate_blob_id("level");
+    assert_ne!(test_id, id);
+
+    let mut options = SidPackOptions::new();
+    options.backend = Some(backend);
+    let resources_path = folder.path().join("resources");
+
+    resource::from_assets_to_resources_with_options(
+        folder.path().join("assets"),
+        resources_path.clone(),
+        &options,
+    )
+    .expect("Unable to pack the assets");
+
+    assert!(resources_path.join(test_id.to_string()).is_file());
+    assert_eq!(sid::generate_blob_id("level"), id);
+}
+
+#[test]
+fn id_override_changes_repack_the_resources_referring_to_them() {
+    let _lock = ID_OVERRIDES_LOCK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let resources_path = folder.path().join("resources");
+
+    let prefabs_path = assets_path.join(sid::get_prefab_namespace());
+    let scenes_path = assets_path.join(sid::get_scene_namespace());
+    fs::create_dir_all(&prefabs_path).unwrap();
+    fs::create_dir_all(&scenes_path).unwrap();
+
+    let prefab = json!({ "name": "cached_crate", "components": [] });
+    fs::write(prefabs_path.join("cached_crate.json"), prefab.to_string()).unwrap();
+
+    let scene = json!({
+        "name": "cached_room",
+        "entities": [{ "prefab_name": "cached_crate" }],
+    });
+    fs::write(scenes_path.join("cached_room.json"), scene.to_string()).unwrap();
+
+    let scene_prefab_id = || {
+        let data = fs::read(resources_path.join(sid::generate_scene_id("cached_room").to_string()))
+            .expect("Unable to read the scene");
+        let SidDecodedResource::Scene(scene) = decode_resource(&data).unwrap() else {
+            panic!("expected a scene");
+        };
+
+        scene.entities[0].prefab_id
+    };
+
+    let pack = || {
+        resource::from_assets_to_resources(assets_path.clone(), resources_path.clone())
+            .expect("Unable to pack the assets")
+    };
+
+    pack();
+    assert_eq!(scene_prefab_id(), sid::generate_prefab_id("cached_crate"));
+
+    // Only the id of the prefab changes, not its source nor the scene's.
+    let overrides_path = folder.path().join("id_overrides.toml");
+    let prefab_id: sid::SidStringId = 4242;
+    fs::write(
+        &overrides_path,
+        format!(
+            "[ids.{}]\ncached_crate = {prefab_id}\n",
+            sid::get_prefab_namespace()
+        ),
+    )
+    .unwrap();
+    id_overrides::set_id_overrides(SidIdOverrides::from_path(&overrides_path).unwrap());
+
+    pack();
+    assert_eq!(scene_prefab_id(), prefab_id);
+}
+
+#[test]
+fn id_overrides_accept_ids_as_strings() {
+    let folder = tempfile::tempdir().unwrap();
+    let overrides_path = folder.path().join("id_overrides.toml");
+
+    // Past the TOML integers with wide ids.
+    let hex_id = (0xed0c3f8a9b5a1c27u64 >> (64 - sid::STRING_ID_BITS)) as sid::SidStringId;
+    let decimal_id = sid::SidStringId::MAX;
+
+    fs::write(
+        &overrides_path,
+        format!(
+            "reserved = [\"{decimal_id}\", 7]\n\n[ids.sprite_sheets]\nplayer = \"{hex_id:#x}\"\nenemy = 42\n"
+        ),
+    )
+    .unwrap();
+
+    let overrides = SidIdOverrides::from_path(&overrides_path).expect("Invalid id overrides");
+    assert_eq!(overrides.find("sprite_sheets", "player"), Some(hex_id));
+    assert_eq!(overrides.find("sprite_sheets", "enemy"), Some(42));
+    assert!(overrides
+        .check("sprite_sheets", "boss", decimal_id)
+        .is_err());
+    assert!(overrides.check("sprite_sheets", "boss", 7).is_err());
+
+    fs::write(&overrides_path, "reserved = [\"0xfoo\"]\n").unwrap();
+    assert!(SidIdOverrides::from_path(&overrides_path).is_err());
+}
+
+#[test]
+fn prefabs_round_trip() {
+    let asset: SidPrefabAsset = from_json(json!({
+        "name": "bat",
+        "components": [
+            { "type": "sprite", "sheet_name": "bat" },
+            { "type": "animation", "animation_name": "bat_fly" },
+            { "type": "collider", "x": 1.0, "y": 2.0, "width": 8.0, "height": 6.0, "is_trigger": true },
+            { "type": "custom", "values": { "hp": "3", "speed": "1.5" } },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Prefab(prefab) = resource else {
+            panic!("expected a prefab");
+        };
+
+        assert_eq!(prefab.id, asset.resource_id());
+
+        let [sprite, animation, collider, custom] = &prefab.components[..] else {
+            panic!("expected four components");
+        };
+
+        assert!(matches!(
+            sprite,
+            SidDecodedPrefabComponent::Sprite { sheet_id }
+                if *sheet_id == sid::generate_sprite_sheet_id("bat")
+        ));
+        assert!(matches!(
+            animation,
+            SidDecodedPrefabComponent::Animation { animation_id }
+                if *animation_id == sid::generate_animation_id("bat_fly")
+        ));
+        assert!(matches!(
+            collider,
+            SidDecodedPrefabComponent::Collider {
+                x: 1.0,
+                y: 2.0,
+                width: 8.0,
+                height: 6.0,
+                is_trigger: true,
+            }
+        ));
+
+        let SidDecodedPrefabComponent::Custom { values } = custom else {
+            panic!("expected custom values");
+        };
+        assert_eq!(
+            values,
+            &[
+                ("hp".to_string(), "3".to_string()),
+                ("speed".to_string(), "1.5".to_string()),
+            ]
+        );
+    }
+}
+
+#[test]
+fn string_tables_round_trip() {
+    let folder = tempfile::tempdir().unwrap();
+    fs::write(
+        folder.path().join("en.csv"),
+        "key,value\ngreeting,Hello\nfarewell,Goodbye\n",
+    )
+    .unwrap();
+    fs::write(
+        folder.path().join("fr.csv"),
+        "key,value\ngreeting,Bonjour\nfarewell,Au revoir\n",
+    )
+    .unwrap();
+
+    let asset: SidStringTableAsset = from_json(json!({
+        "name": "menu",
+        "locales": { "en": "en.csv", "fr": "fr.csv" },
+    }));
+
+    for resource in round_trip(&asset, &asset_path(&folder)) {
+        let SidDecodedResource::StringTable(table) = resource else {
+            panic!("expected a string table");
+        };
+
+        assert_eq!(table.id, asset.resource_id());
+        assert_eq!(table.key_ids.len(), 2);
+
+        let greeting = table
+            .key_ids
+            .iter()
+            .position(|id| *id == sid::generate_string_key_id("greeting"))
+            .expect("missing greeting key");
+
+        let locales: Vec<(&str, &str)> = table
+            .locales
+            .iter()
+            .map(|(locale, strings)| (locale.as_str(), strings[greeting].as_str()))
+            .collect();
+        assert_eq!(locales, [("en", "Hello"), ("fr", "Bonjour")]);
+    }
+}
+
+#[test]
+fn textures_round_trip() {
+    let folder = tempfile::tempdir().unwrap();
+    let image = save_image(&folder, "crate.png");
+
+    let asset: SidTextureAsset = from_json(json!({
+        "name": "crate",
+        "image_path": "crate.png",
+    }));
+
+    for resource in round_trip(&asset, &asset_path(&folder)) {
+        let SidDecodedResource::Texture(texture) = resource else {
+            panic!("expected a texture");
+        };
+
+        assert_eq!(texture.id, asset.resource_id());
+        assert_eq!((texture.image.width, texture.image.height), (2, 2));
+        assert_eq!(texture.image.palette_id, 0);
+        assert_eq!(texture.image.texture(), image.as_raw().as_slice());
+    }
+}
+
+#[test]
+fn textures_with_a_level_smaller_than_their_image_fail_to_unpack() {
+    let folder = tempfile::tempdir().unwrap();
+    save_image(&folder, "crate.png");
+
+    let asset: SidTextureAsset = from_json(json!({
+        "name": "crate",
+        "image_path": "crate.png",
+    }));
+
+    for level_width in [0u32, 1] {
+        let mut out = SidResourceWriter::new(vec![], SidEndianness::Little);
+        asset
+            .serialize_resource(asset_path(&folder), &mut out)
+            .expect("Unable to write the resource");
+        let mut data = out.finish().expect("Unable to write the resource");
+
+        // Base level of 2x2 pixels of 4 bytes, rewritten with a valid checksum.
+        let level: Vec<u8> = [
+            &2u32.to_le_bytes()[..],
+            &2u32.to_le_bytes(),
+            &16u64.to_le_bytes(),
+        ]
+        .concat();
+        let offset = data
+            .windows(level.len())
+            .position(|bytes| bytes == level)
+            .expect("Unable to find the base level");
+        data[offset..offset + 4].copy_from_slice(&level_width.to_le_bytes());
+
+        let checksum_offset = data.len() - RESOURCE_CHECKSUM_SIZE;
+        let checksum = xxh3_64(&data[..checksum_offset]);
+        data[checksum_offset..].copy_from_slice(&checksum.to_le_bytes());
+
+        let resources = tempfile::tempdir().unwrap();
+        fs::write(
+            resources.path().join(asset.resource_id().to_string()),
+            &data,
+        )
+        .unwrap();
+
+        let error = unpack::from_resources_to_assets(resources.path(), folder.path().join("out"))
+            .expect_err("expected the texture to be rejected");
+        let SidToolError::AssetFailures(failures) = error else {
+            panic!("expected an asset failure, got {error:?}");
+        };
+
+        assert!(matches!(
+            failures[..],
+            [SidAssetFailure {
+                error: SidToolError::Malformed(_),
+                ..
+            }]
+        ));
+    }
+}
+
+#[test]
+fn palettes_round_trip() {
+    let folder = tempfile::tempdir().unwrap();
+    save_image(&folder, "palette.png");
+
+    let asset: SidPaletteAsset = from_json(json!({
+        "name": "dusk",
+        "image_path": "palette.png",
+    }));
+
+    for resource in round_trip(&asset, &asset_path(&folder)) {
+        let SidDecodedResource::Palette(palette) = resource else {
+            panic!("expected a palette");
+        };
+
+        assert_eq!(palette.id, asset.resource_id());
+        assert!(palette.colors.contains(&[255, 0, 0, 255]));
+        assert!(palette.colors.contains(&[0, 0, 255, 255]));
+    }
+}
+
+#[test]
+fn nine_slices_round_trip() {
+    let asset: SidNineSliceAsset = from_json(json!({
+        "name": "panel",
+        "sheet_name": "ui",
+        "region": { "x": 4, "y": 8, "width": 32, "height": 24 },
+        "insets": { "left": 1, "top": 2, "right": 3, "bottom": 4 },
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::NineSlice(nine_slice) = resource else {
+            panic!("expected a nine-slice");
+        };
+
+        assert_eq!(nine_slice.id, asset.resource_id());
+        assert_eq!(nine_slice.sheet_id, sid::generate_sprite_sheet_id("ui"));
+        let region = &nine_slice.region;
+        assert_eq!(
+            (region.x, region.y, region.width, region.height),
+            (4, 8, 32, 24)
+        );
+        assert_eq!(nine_slice.insets, [1, 2, 3, 4]);
+    }
+}
+
+#[test]
+fn characters_round_trip() {
+    let asset: SidCharacterAsset = from_json(json!({
+        "name": "knight",
+        "parts": [
+            {
+                "name": "body",
+                "sheet_name": "knight_body",
+                "def_name": "knight_body",
+                "animations": ["knight_body_idle", "knight_body_run"],
+            },
+            {
+                "name": "helmet",
+                "sheet_name": "knight_helmet",
+                "def_name": "knight_helmet",
+                "animations": ["knight_helmet_idle"],
+            },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Character(character) = resource else {
+            panic!("expected a character");
+        };
+
+        assert_eq!(character.id, asset.resource_id());
+        assert_eq!(character.parts.len(), 2);
+
+        let body = &character.parts[0];
+        assert_eq!(body.name, "body");
+        assert_eq!(body.sheet_id, sid::generate_sprite_sheet_id("knight_body"));
+        assert_eq!(body.def_id, sid::generate_animation_def_id("knight_body"));
+        assert_eq!(
+            body.animation_ids,
+            [
+                sid::generate_animation_id("knight_body_idle"),
+                sid::generate_animation_id("knight_body_run"),
+            ]
+        );
+        assert_eq!(character.parts[1].name, "helmet");
+    }
+}
+
+#[test]
+fn skeletons_round_trip() {
+    let asset: SidSkeletonAsset = from_json(json!({
+        "name": "villager",
+        "bones": [
+            { "name": "root" },
+            { "name": "arm", "parent": "root", "length": 12.0, "x": 3.0, "rotation": 90.0 },
+        ],
+        "slots": [
+            { "name": "arm", "bone": "arm", "attachment": "arm", "color": [255, 128, 0, 255] },
+        ],
+        "attachments": [
+            { "slot": "arm", "name": "arm", "region": "villager/arm", "width": 8.0, "height": 16.0 },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Skeleton(skeleton) = resource else {
+            panic!("expected a skeleton");
+        };
+
+        assert_eq!(skeleton.id, asset.resource_id());
+        assert_eq!(skeleton.bones.len(), 2);
+        assert_eq!(skeleton.bones[0].parent, None);
+
+        let arm = &skeleton.bones[1];
+        assert_eq!(arm.parent, Some(0));
+        assert_eq!(arm.length, 12.0);
+        assert_eq!(arm.transform, [3.0, 0.0, 90.0, 1.0, 1.0]);
+
+        let slot = &skeleton.slots[0];
+        assert_eq!(slot.bone, 1);
+        assert_eq!(slot.attachment.as_deref(), Some("arm"));
+        assert_eq!(slot.color, [255, 128, 0, 255]);
+
+        let attachment = &skeleton.attachments[0];
+        assert_eq!(attachment.slot, 0);
+        assert_eq!(attachment.region, "villager/arm");
+        assert_eq!((attachment.width, attachment.height), (8.0, 16.0));
+    }
+}
+
+#[test]
+fn skeletal_animations_round_trip() {
+    let asset: SidSkeletalAnimationAsset = from_json(json!({
+        "name": "villager_wave",
+        "skeleton_name": "villager",
+        "duration": 1.0,
+        "bones": [
+            {
+                "bone": "arm",
+                "property": "rotate",
+                "keys": [
+                    {
+                        "time": 0.0,
+                        "values": [0.0, 0.0],
+                        "curve": { "type": "bezier", "points": [0.25, 0.0, 0.75, 1.0] },
+                    },
+                    { "time": 1.0, "values": [45.0, 0.0], "curve": { "type": "stepped" } },
+                ],
+            },
+        ],
+        "slots": [
+            {
+                "slot": "arm",
+                "keys": [
+                    { "time": 0.0, "attachment": "arm" },
+                    { "time": 0.5, "attachment": null },
+                ],
+            },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::SkeletalAnimation(animation) = resource else {
+            panic!("expected a skeletal animation");
+        };
+
+        assert_eq!(animation.id, asset.resource_id());
+        assert_eq!(animation.skeleton_id, sid::generate_skeleton_id("villager"));
+        assert_eq!(animation.duration, 1.0);
+
+        let timeline = &animation.bones[0];
+        assert_eq!(timeline.bone, "arm");
+        assert_eq!(timeline.property, SidBoneTimelineProperty::Rotate as u8);
+        assert_eq!(timeline.keys[0].curve_points, Some([0.25, 0.0, 0.75, 1.0]));
+        assert_eq!(timeline.keys[1].values, [45.0, 0.0]);
+        assert_eq!(timeline.keys[1].curve_points, None);
+
+        assert_eq!(
+            animation.slots[0].keys,
+            [(0.0, Some("arm".to_string())), (0.5, None)]
+        );
+    }
+}
+
+#[test]
+fn bitmap_fonts_round_trip() {
+    let asset: SidBitmapFontAsset = from_json(json!({
+        "name": "pixel_font",
+        "size": 16.0,
+        "line_height": 18,
+        "base": 14,
+        "pages": ["pixel_font_0", "pixel_font_1"],
+        "glyphs": [
+            {
+                "character": 66, "x": 8, "y": 0, "width": 7, "height": 12,
+                "x_offset": 0, "y_offset": 2, "x_advance": 8, "page": 1,
+            },
+            {
+                "character": 65, "x": 0, "y": 0, "width": 7, "height": 12,
+                "x_offset": 0, "y_offset": 2, "x_advance": 8,
+            },
+        ],
+        "kernings": [{ "first": 65, "second": 66, "amount": -1 }],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::BitmapFont(font) = resource else {
+            panic!("expected a bitmap font");
+        };
+
+        assert_eq!(font.id, asset.resource_id());
+        assert_eq!(font.size, 16.0);
+        assert_eq!((font.line_height, font.base), (18, 14));
+        assert_eq!(
+            font.page_ids,
+            [
+                sid::generate_texture_id("pixel_font_0"),
+                sid::generate_texture_id("pixel_font_1"),
+            ]
+        );
+
+        // Glyphs are sorted by character.
+        let characters: Vec<_> = font.glyphs.iter().map(|glyph| glyph.character).collect();
+        assert_eq!(characters, [65, 66]);
+        assert_eq!(font.glyphs[1].page, 1);
+        assert_eq!(font.glyphs[1].x, 8);
+        assert_eq!(font.kernings, [(65, 66, -1)]);
+    }
+}
+
+#[test]
+fn meshes_round_trip() {
+    let asset: SidMeshAsset = from_json(json!({
+        "name": "crate",
+        "vertices": [
+            { "position": [0.0, 0.0, 0.0], "uv": [0.0, 1.0] },
+            { "position": [1.0, 0.0, 0.0], "normal": [0.0, 0.0, 1.0], "uv": [1.0, 1.0] },
+            { "position": [1.0, 1.0, 0.0], "uv": [1.0, 0.0] },
+            { "position": [0.0, 1.0, 0.0], "uv": [0.0, 0.0] },
+        ],
+        "indices": [0, 1, 2, 2, 3, 0],
+        "submeshes": [
+            {
+                "first_index": 0,
+                "index_count": 3,
+                "texture": { "type": "texture", "name": "crate_wood" },
+            },
+            {
+                "first_index": 3,
+                "index_count": 3,
+                "texture": { "type": "sprite_sheet", "name": "crate_labels" },
+            },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Mesh(mesh) = resource else {
+            panic!("expected a mesh");
+        };
+
+        assert_eq!(mesh.id, asset.resource_id());
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.vertices[1], [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+
+        // Indices of small meshes are 16-bit.
+        assert_eq!(mesh.index_size, 2);
+        assert_eq!(mesh.indices, [0, 1, 2, 2, 3, 0]);
+
+        assert_eq!(
+            mesh.submeshes[0].texture,
+            Some((MESH_TEXTURE_TEXTURE, sid::generate_texture_id("crate_wood")))
+        );
+        assert_eq!(
+            mesh.submeshes[1].texture,
+            Some((
+                MESH_TEXTURE_SPRITE_SHEET,
+                sid::generate_sprite_sheet_id("crate_labels")
+            ))
+        );
+        assert_eq!(
+            (mesh.submeshes[1].first_index, mesh.submeshes[1].index_count),
+            (3, 3)
+        );
+    }
+}
+
+#[test]
+fn curves_round_trip() {
+    let asset: SidCurveAsset = from_json(json!({
+        "name": "camera_intro",
+        "interpolation": "catmull_rom",
+        "keys": [
+            { "time": 0.0, "values": [0.0, 0.0] },
+            { "time": 1.0, "values": [10.0, 5.0] },
+            { "time": 2.5, "values": [20.0, 0.0] },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Curve(curve) = resource else {
+            panic!("expected a curve");
+        };
+
+        assert_eq!(curve.id, asset.resource_id());
+        assert_eq!(curve.interpolation, SidCurveInterpolation::CatmullRom as u8);
+        assert_eq!(curve.dimension, 2);
+        assert_eq!(
+            curve.data,
+            SidDecodedCurveData::Keyed(vec![
+                (0.0, vec![0.0, 0.0]),
+                (1.0, vec![10.0, 5.0]),
+                (2.5, vec![20.0, 0.0]),
+            ])
+        );
+    }
+}
+
+#[test]
+fn sampled_curves_round_trip() {
+    let asset: SidCurveAsset = from_json(json!({
+        "name": "button_fade",
+        "keys": [
+            { "time": 0.5, "values": [0.0] },
+            { "time": 1.0, "values": [1.0] },
+        ],
+        "sample_rate": 4.0,
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Curve(curve) = resource else {
+            panic!("expected a curve");
+        };
+
+        assert_eq!(curve.interpolation, SidCurveInterpolation::Linear as u8);
+
+        // Samples start at the first key and end on the last one.
+        assert_eq!(
+            curve.data,
+            SidDecodedCurveData::Sampled {
+                sample_rate: 4.0,
+                start_time: 0.5,
+                samples: vec![vec![0.0], vec![0.5], vec![1.0]],
+            }
+        );
+    }
+}
+
+#[test]
+fn materials_round_trip() {
+    let asset: SidMaterialAsset = from_json(json!({
+        "name": "crate_lit",
+        "shader_name": "lit",
+        "blend_mode": "additive",
+        "textures": { "u_albedo": "crate_wood", "u_normal": "crate_wood_normal" },
+        "uniforms": { "u_shininess": 8.0, "u_tint": [1.0, 0.5, 0.25, 1.0] },
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Material(material) = resource else {
+            panic!("expected a material");
+        };
+
+        assert_eq!(material.id, asset.resource_id());
+        assert_eq!(material.shader_id, sid::generate_shader_id("lit"));
+        assert_eq!(material.blend_mode, SidBlendMode::Additive as u8);
+        assert_eq!(
+            material.textures,
+            [
+                (
+                    "u_albedo".to_string(),
+                    sid::generate_texture_id("crate_wood")
+                ),
+                (
+                    "u_normal".to_string(),
+                    sid::generate_texture_id("crate_wood_normal")
+                ),
+            ]
+        );
+        assert_eq!(
+            material.uniforms,
+            [
+                ("u_shininess".to_string(), vec![8.0]),
+                ("u_tint".to_string(), vec![1.0, 0.5, 0.25, 1.0]),
+            ]
+        );
+    }
+}
+
+#[test]
+fn scenes_round_trip() {
+    let asset: SidSceneAsset = from_json(json!({
+        "name": "forest_1",
+        "entities": [
+            { "name": "spawn", "prefab_name": "player", "x": 32.0, "y": 48.0 },
+            {
+                "prefab_name": "tree",
+                "x": 128.0,
+                "y": 16.0,
+                "rotation": 90.0,
+                "scale_x": 2.0,
+                "layer": -1,
+                "overrides": [{ "type": "sprite", "sheet_name": "tree_autumn" }],
+            },
+        ],
+    }));
+
+    for resource in round_trip(&asset, in_memory_path()) {
+        let SidDecodedResource::Scene(scene) = resource else {
+            panic!("expected a scene");
+        };
+
+        assert_eq!(scene.id, asset.resource_id());
+
+        let [spawn, tree] = &scene.entities[..] else {
+            panic!("expected two entities");
+        };
+
+        assert_eq!(spawn.name.as_deref(), Some("spawn"));
+        assert_eq!(spawn.prefab_id, sid::generate_prefab_id("player"));
+        assert_eq!(spawn.transform, [32.0, 48.0, 0.0, 1.0, 1.0]);
+        assert_eq!(spawn.layer, 0);
+        assert!(spawn.overrides.is_empty());
+
+        assert_eq!(tree.name, None);
+        assert_eq!(tree.prefab_id, sid::generate_prefab_id("tree"));
+        assert_eq!(tree.transform, [128.0, 16.0, 90.0, 2.0, 1.0]);
+        assert_eq!(tree.layer, -1);
+        assert!(matches!(
+            tree.overrides[..],
+            [SidDecodedPrefabComponent::Sprite { sheet_id }]
+                if sheet_id == sid::generate_sprite_sheet_id("tree_autumn")
+        ));
+    }
+}
+
+/// Writes a blob asset for every name of `names` in `folder`, all sharing the
+/// same data file.
+fn write_blob_assets(folder: &Path, names: &[&str]) {
+    fs::create_dir_all(folder).unwrap();
+    fs::write(folder.join("data.bin"), b"data").unwrap();
+
+    for name in names {
+        let asset = json!({ "name": name, "blob_path": "data.bin" });
+        fs::write(folder.join(format!("{name}.json")), asset.to_string()).unwrap();
+    }
+}
+
+/// Paths of the assets a walk of `assets_path` finds, relative to it.
+fn listed_asset_paths(assets_path: &Path) -> Vec<PathBuf> {
+    let (entries, failures) =
+        resource::list_assets(assets_path).expect("Unable to list the assets");
+    assert!(failures.is_empty());
+
+    let mut paths: Vec<PathBuf> = entries
+        .into_iter()
+        .map(|entry| entry.path.strip_prefix(assets_path).unwrap().to_path_buf())
+        .collect();
+
+    paths.sort();
+    paths
+}
+
+#[cfg(unix)]
+#[test]
+fn walks_following_links_skip_the_folders_they_loop_back_to() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let blobs_path = assets_path.join(sid::get_blob_namespace());
+    write_blob_assets(&blobs_path, &["level"]);
+    write_blob_assets(&folder.path().join("shared"), &["music"]);
+
+    // One link leads back to the namespace folder, the other out of it.
+    std::os::unix::fs::symlink(&blobs_path, blobs_path.join("loop")).unwrap();
+    std::os::unix::fs::symlink(folder.path().join("shared"), blobs_path.join("shared")).unwrap();
+
+    walk::set_follow_symlinks(true);
+
+    assert_eq!(
+        listed_asset_paths(&assets_path),
+        [
+            Path::new(sid::get_blob_namespace()).join("level.json"),
+            Path::new(sid::get_blob_namespace()).join("shared/music.json"),
+        ]
+    );
+}
+
+#[test]
+fn walks_skip_the_entries_of_ignore_files() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let blobs_path = assets_path.join(sid::get_blob_namespace());
+    write_blob_assets(&blobs_path, &["level", "wip", "stale.old", "keep.old"]);
+    write_blob_assets(&blobs_path.join("_drafts"), &["draft"]);
+    write_blob_assets(&blobs_path.join("music"), &["theme", "theme.old"]);
+
+    // Ignore files of the parents of the namespace folders apply as well.
+    fs::write(assets_path.join(walk::IGNORE_FILE_NAME), "_drafts/\n").unwrap();
+    fs::write(
+        blobs_path.join(walk::IGNORE_FILE_NAME),
+        "# Work in progress.\n/wip.json\n*.old.json\n!keep.old.json\n",
+    )
+    .unwrap();
+    fs::write(
+        blobs_path.join("music").join(walk::IGNORE_FILE_NAME),
+        "!theme.old.json\n",
+    )
+    .unwrap();
+
+    let blobs = Path::new(sid::get_blob_namespace());
+
+    assert_eq!(
+        listed_asset_paths(&assets_path),
+        [
+            blobs.join("keep.old.json"),
+            blobs.join("level.json"),
+            blobs.join("music/theme.json"),
+            blobs.join("music/theme.old.json"),
+        ]
+    );
+}
+
+/// Writes an asset of `folder` that cannot be loaded.
+fn write_malformed_asset(folder: &Path) {
+    fs::create_dir_all(folder).unwrap();
+    fs::write(folder.join("broken.json"), "{").unwrap();
+}
+
+#[test]
+fn malformed_assets_do_not_hide_dangling_references() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let scenes_path = assets_path.join(sid::get_scene_namespace());
+    fs::create_dir_all(&scenes_path).unwrap();
+    write_malformed_asset(&assets_path.join(sid::get_blob_namespace()));
+
+    let scene = json!({
+        "name": "empty_room",
+        "entities": [{ "prefab_name": "missing_crate" }],
+    });
+    fs::write(scenes_path.join("empty_room.json"), scene.to_string()).unwrap();
+
+    let error = resource::from_assets_to_resources(assets_path, folder.path().join("resources"))
+        .expect_err("Packed a dangling reference");
+
+    let SidToolError::DanglingReferences(references) = error else {
+        panic!("expected dangling references, got {error}");
+    };
+
+    let [reference] = &references[..] else {
+        panic!("expected a single dangling reference");
+    };
+
+    assert_eq!(reference.owner.name, "empty_room");
+    assert_eq!(reference.reference.name, "missing_crate");
+}
+
+/// File names of the resources of `resources_path`, sorted.
+fn resource_file_names(resources_path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(resources_path)
+        .expect("Unable to read the resources")
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name != MANIFEST_FILE_NAME && name != CACHE_FILE_NAME)
+        .collect();
+
+    names.sort();
+    names
+}
+
+/// File names of the resources of the blobs of `names`, sorted.
+fn blob_file_names(names: &[&str]) -> Vec<String> {
+    let mut names: Vec<String> = names
+        .iter()
+        .map(|name| sid::generate_blob_id(name).to_string())
+        .collect();
+
+    names.sort();
+    names
+}
+
+/// Checks that `result` only reports the asset written by
+/// `write_malformed_asset`.
+fn assert_only_malformed_asset_failed(result: Result<(), SidToolError>) {
+    let Err(SidToolError::AssetFailures(failures)) = result else {
+        panic!("expected the malformed asset to fail");
+    };
+
+    let [SidAssetFailure { path, .. }] = &failures[..] else {
+        panic!("expected a single failure");
+    };
+
+    assert!(path.ends_with("broken.json"));
+}
+
+#[test]
+fn malformed_assets_do_not_disable_tag_filters() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let blobs_path = assets_path.join(sid::get_blob_namespace());
+    let resources_path = folder.path().join("resources");
+    write_blob_assets(&blobs_path, &["keep"]);
+    write_malformed_asset(&blobs_path);
+
+    let debug_only = json!({ "name": "debug_only", "blob_path": "data.bin", "tags": ["debug"] });
+    fs::write(blobs_path.join("debug_only.json"), debug_only.to_string()).unwrap();
+
+    let mut options = SidPackOptions::new();
+    options.tags.exclude = vec!["debug".to_string()];
+
+    assert_only_malformed_asset_failed(resource::from_assets_to_resources_with_options(
+        assets_path,
+        resources_path.clone(),
+        &options,
+    ));
+
+    assert_eq!(
+        resource_file_names(&resources_path),
+        blob_file_names(&["keep"])
+    );
+}
+
+#[test]
+fn malformed_assets_do_not_merge_packages() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let blobs_path = assets_path.join(sid::get_blob_namespace());
+    let resources_path = folder.path().join("resources");
+    write_blob_assets(&blobs_path, &["level"]);
+    write_blob_assets(&blobs_path.join("dlc"), &["bonus_level"]);
+    write_malformed_asset(&blobs_path);
+
+    let mut options = SidPackOptions::new();
+    options.packages = vec![SidPackage {
+        name: "dlc".to_string(),
+        paths: vec![Path::new(sid::get_blob_namespace()).join("dlc")],
+        tags: vec![],
+    }];
+
+    assert_only_malformed_asset_failed(resource::from_assets_to_resources_with_options(
+        assets_path,
+        resources_path.clone(),
+        &options,
+    ));
+
+    assert_eq!(
+        resource_file_names(&resources_path.join(resource::DEFAULT_PACKAGE_NAME)),
+        blob_file_names(&["level"])
+    );
+    assert_eq!(
+        resource_file_names(&resources_path.join("dlc")),
+        blob_file_names(&["bonus_level"])
+    );
+}
+
+#[test]
+fn malformed_assets_do_not_disable_input_collision_policies() {
+    let folder = tempfile::tempdir().unwrap();
+    let shared_path = folder.path().join("shared");
+    let game_path = folder.path().join("game");
+    let resources_path = folder.path().join("resources");
+    write_blob_assets(&shared_path.join(sid::get_blob_namespace()), &["level"]);
+    write_blob_assets(&game_path.join(sid::get_blob_namespace()), &["level"]);
+    write_malformed_asset(&shared_path.join(sid::get_blob_namespace()));
+
+    fs::write(
+        game_path.join(sid::get_blob_namespace()).join("data.bin"),
+        b"game data",
+    )
+    .unwrap();
+
+    let pack = |options: &SidPackOptions| {
+        resource::from_asset_folders_to_resources_with_report(
+            &[&shared_path, &game_path],
+            &resources_path,
+            options,
+            &mut SidReport::new(),
+        )
+    };
+
+    let mut options = SidPackOptions::new();
+    assert!(matches!(
+        pack(&options),
+        Err(SidToolError::IdCollisions(collisions)) if collisions.len() == 1
+    ));
+
+    options.input_collisions = SidInputCollisionPolicy::LastWins;
+    assert_only_malformed_asset_failed(pack(&options));
+
+    let data = fs::read(resources_path.join(sid::generate_blob_id("level").to_string()))
+        .expect("Unable to read the blob");
+    let SidDecodedResource::Blob(blob) = decode_resource(&data).unwrap() else {
+        panic!("expected a blob");
+    };
+
+    assert_eq!(blob.data, b"game data");
+}
+
+#[test]
+fn malformed_assets_do_not_disable_id_override_checks() {
+    let _lock = ID_OVERRIDES_LOCK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let blobs_path = assets_path.join(sid::get_blob_namespace());
+    write_blob_assets(&blobs_path, &["reserved_level"]);
+    write_malformed_asset(&blobs_path);
+
+    let overrides_path = folder.path().join("id_overrides.toml");
+    fs::write(
+        &overrides_path,
+        format!(
+            "reserved = [\"{}\"]\n",
+            sid::generate_blob_id("reserved_level")
+        ),
+    )
+    .unwrap();
+    id_overrides::set_id_overrides(SidIdOverrides::from_path(&overrides_path).unwrap());
+
+    let result = resource::from_assets_to_resources(assets_path, folder.path().join("resources"));
+
+    let Err(SidToolError::AssetFailures(failures)) = result else {
+        panic!("expected the reserved id to be rejected");
+    };
+
+    let [SidAssetFailure { path, .. }] = &failures[..] else {
+        panic!("expected a single failure");
+    };
+
+    assert!(path.ends_with("reserved_level.json"));
+}
+
+/// Backend giving `level_copy` the id of `level`.
+#[derive(Debug)]
+struct SidCollidingBackend(SidTestBackend);
+
+impl SidBackend for SidCollidingBackend {
+    fn namespaces(&self) -> &SidNamespaces {
+        self.0.namespaces()
+    }
+
+    fn generate_id(&self, kind: SidIdKind, name: &str) -> sid::SidStringId {
+        let name = if name == "level_copy" { "level" } else { name };
+        self.0.generate_id(kind, name)
+    }
+
+    fn max_animation_frame_count(&self) -> sid::SidAnimationFrameIndex {
+        self.0.max_animation_frame_count()
+    }
+}
+
+#[test]
+fn malformed_assets_do_not_hide_id_collisions() {
+    let backend: Arc<dyn SidBackend> = Arc::new(SidCollidingBackend(SidTestBackend::new()));
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let blobs_path = assets_path.join(backend.namespaces().blob);
+    write_blob_assets(&blobs_path, &["level", "level_copy"]);
+    write_malformed_asset(&blobs_path);
+
+    let assert_collision = |collisions: &[SidIdCollision]| {
+        let [collision] = collisions else {
+            panic!("expected a single id collision");
+        };
+
+        assert_eq!(collision.id, backend.generate_id(SidIdKind::Blob, "level"));
+        assert_eq!(collision.first.name, "level");
+        assert_eq!(collision.second.name, "level_copy");
+    };
+
+    {
+        let _backend = sid::scope_backend(Some(&backend));
+        assert_collision(&resource::find_id_collisions(&assets_path).unwrap());
+    }
+
+    let mut options = SidPackOptions::new();
+    options.backend = Some(backend.clone());
+
+    let result = resource::from_assets_to_resources_with_options(
+        assets_path,
+        folder.path().join("resources"),
+        &options,
+    );
+
+    let Err(SidToolError::IdCollisions(collisions)) = result else {
+        panic!("expected the id collision to fail the pack");
+    };
+
+    assert_collision(&collisions);
+}
+
+#[test]
+fn content_hash_repacks_remove_the_replaced_resources() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let blobs_path = assets_path.join(sid::get_blob_namespace());
+    let resources_path = folder.path().join("resources");
+    write_blob_assets(&blobs_path, &["level"]);
+
+    let mut options = SidPackOptions::new();
+    options.content_hash_names = true;
+
+    let pack = || {
+        resource::from_assets_to_resources_with_options(
+            assets_path.clone(),
+            resources_path.clone(),
+            &options,
+        )
+        .expect("Unable to pack the assets");
+
+        resource_file_names(&resources_path)
+    };
+
+    let [first_name] = &pack()[..] else {
+        panic!("expected a single resource");
+    };
+
+    fs::write(blobs_path.join("data.bin"), b"changed data").unwrap();
+
+    let [second_name] = &pack()[..] else {
+        panic!("expected the previous resource to be removed");
+    };
+
+    assert_ne!(first_name, second_name);
+    assert!(second_name.starts_with(&format!("{}_", sid::generate_blob_id("level"))));
+}
+
+/// Names of the resources the manifest of `resources_path` lists, sorted.
+fn manifest_names(resources_path: &Path) -> Vec<String> {
+    let manifest = SidManifest::load(resources_path).expect("Unable to load the manifest");
+    let mut names: Vec<String> = manifest
+        .entries
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect();
+
+    names.sort();
+    names
+}
+
+#[test]
+fn overwrite_policies_decide_which_resources_remain() {
+    // Names the manifest lists after the second pack, or None if it fails, and
+    // the resources then in the output folder.
+    let cases = [
+        (
+            SidOverwritePolicy::Overwrite,
+            Some(vec!["boss", "level"]),
+            vec!["boss", "level", "music"],
+        ),
+        (
+            SidOverwritePolicy::Clean,
+            Some(vec!["boss", "level"]),
+            vec!["boss", "level"],
+        ),
+        (
+            SidOverwritePolicy::NoOverwrite,
+            None,
+            vec!["level", "music"],
+        ),
+        (
+            SidOverwritePolicy::Merge,
+            Some(vec!["boss", "level", "music"]),
+            vec!["boss", "level", "music"],
+        ),
+    ];
+
+    for (policy, manifest, resources) in cases {
+        let folder = tempfile::tempdir().unwrap();
+        let blobs_path = folder.path().join("assets").join(sid::get_blob_namespace());
+        let resources_path = folder.path().join("resources");
+
+        let mut options = SidPackOptions::new();
+
+        let pack = |options: &SidPackOptions| {
+            resource::from_assets_to_resources_with_options(
+                folder.path().join("assets"),
+                resources_path.clone(),
+                options,
+            )
+        };
+
+        write_blob_assets(&blobs_path, &["level", "music"]);
+        pack(&options).expect("Unable to pack the assets");
+
+        fs::remove_file(blobs_path.join("music.json")).unwrap();
+        write_blob_assets(&blobs_path, &["boss"]);
+        options.overwrite = policy;
+
+        match &manifest {
+            Some(manifest) => {
+                pack(&options).expect("Unable to repack the assets");
+                assert_eq!(manifest_names(&resources_path), *manifest, "{policy}");
+            }
+            None => assert!(
+                matches!(pack(&options), Err(SidToolError::AssetFailures(_))),
+                "{policy}"
+            ),
+        }
+
+        assert_eq!(
+            resource_file_names(&resources_path),
+            blob_file_names(&resources),
+            "{policy}"
+        );
+    }
+}
+
+/// Backend of sid_lib with a single animation frame, so that it only differs
+/// by its fingerprint.
+#[derive(Debug)]
+struct SidSingleFrameBackend(SidLibBackend);
+
+impl SidBackend for SidSingleFrameBackend {
+    fn namespaces(&self) -> &SidNamespaces {
+        self.0.namespaces()
+    }
+
+    fn generate_id(&self, kind: SidIdKind, name: &str) -> sid::SidStringId {
+        self.0.generate_id(kind, name)
+    }
+
+    fn max_animation_frame_count(&self) -> sid::SidAnimationFrameIndex {
+        1
+    }
+}
+
+#[test]
+fn build_cache_skips_unchanged_assets_only() {
+    let folder = tempfile::tempdir().unwrap();
+    let blobs_path = folder.path().join("assets").join(sid::get_blob_namespace());
+    let resources_path = folder.path().join("resources");
+    write_blob_assets(&blobs_path, &["level"]);
+
+    let pack = |options: &SidPackOptions| {
+        let mut report = SidReport::new();
+
+        resource::from_assets_to_resources_with_report(
+            folder.path().join("assets"),
+            resources_path.clone(),
+            options,
+            &mut report,
+        )
+        .expect("Unable to pack the assets");
+
+        let [asset] = &report.assets[..] else {
+            panic!("expected a single asset");
+        };
+
+        asset.status
+    };
+
+    let mut options = SidPackOptions::new();
+    assert_eq!(pack(&options), SidAssetStatus::Ok);
+    assert_eq!(pack(&options), SidAssetStatus::Skipped);
+
+    fs::write(blobs_path.join("data.bin"), b"changed data").unwrap();
+    assert_eq!(pack(&options), SidAssetStatus::Ok);
+    assert_eq!(pack(&options), SidAssetStatus::Skipped);
+
+    // The ids and resource files stay the same, only the backend changes.
+    options.backend = Some(Arc::new(SidSingleFrameBackend(SidLibBackend::new())));
+    assert_eq!(pack(&options), SidAssetStatus::Ok);
+    assert_eq!(pack(&options), SidAssetStatus::Skipped);
+}
+
+/// Reads the resource of `id` packed to `resources_path`.
+fn read_resource(resources_path: &Path, id: sid::SidStringId) -> SidDecodedResource {
+    let data = fs::read(resources_path.join(id.to_string())).expect("Unable to read the resource");
+    decode_resource(&data).expect("Unable to decode the resource")
+}
+
+#[test]
+fn atlases_round_trip() {
+    let folder = tempfile::tempdir().unwrap();
+    let assets_path = folder.path().join("assets");
+    let atlas_path = folder.path().join("atlas");
+    let resources_path = folder.path().join("resources");
+
+    let sheets_path = assets_path.join(sid::get_sprite_sheet_namespace());
+    let defs_path = assets_path.join(sid::get_animation_def_namespace());
+    fs::create_dir_all(&sheets_path).unwrap();
+    fs::create_dir_all(&defs_path).unwrap();
+
+    // Every frame has its own color, so that its pixels can be found back.
+    let colors = [
+        Rgba([255, 0, 0, 255]),
+        Rgba([0, 255, 0, 255]),
+        Rgba([0, 0, 255, 255]),
+    ];
+
+    let hero = RgbaImage::from_fn(8, 4, |x, _| colors[(x / 4) as usize]);
+    let coin = RgbaImage::from_pixel(3, 5, colors[2]);
+
+    // Frames of every sheet, as x, y, width and height.
+    let sheets = [
+        ("hero", hero, vec![(0, 0, 4, 4), (4, 0, 4, 4)]),
+        ("coin", coin, vec![(0, 0, 3, 5)]),
+    ];
+
+    for (name, image, frames) in &sheets {
+        image.save(sheets_path.join(format!("{name}.png"))).unwrap();
+
+        let sheet = json!({
+            "name": name,
+            "image_path": format!("{name}.png"),
+            "width": image.width(),
+            "height": image.height(),
+            "format": "RGBA8888",
+        });
+        fs::write(sheets_path.join(format!("{name}.json")), sheet.to_string()).unwrap();
+
+        let frames: Vec<Value> = frames
+            .iter()
+            .map(|&(x, y, width, height)| {
+                json!({
+                    "pos": { "x": x, "y": y },
+                    "dims": { "width": width, "height": height },
+                    "duration": 100,
+                })
+            })
+            .collect();
+
+        let def = json!({
+            "name": name,
+            "sheet_name": name,
+            "frame_count": frames.len(),
+            "frames": frames,
+        });
+        fs::write(defs_path.join(format!("{name}.json")), def.to_string()).unwrap();
+    }
+
+    atlas::build_atlases(&assets_path, &atlas_path, &SidAtlasOptions::new())
+        .expect("Unable to build the atlas");
+    resource::from_assets_to_resources(atlas_path, resources_path.clone())
+        .expect("Unable to pack the atlas");
+
+    let page_id = sid::generate_sprite_sheet_id(&format!("{}_0", atlas::DEFAULT_ATLAS_NAME));
+
+    let SidDecodedResource::SpriteSheet(page) = read_resource(&resources_path, page_id) else {
+        panic!("expected a sprite sheet");
+    };
+
+    let page_width = page.image.width;
+    let page_height = page.image.height;
+    let mut rects = vec![];
+
+    for (name, image, frames) in &sheets {
+        let id = sid::generate_animation_def_id(name);
+
+        let SidDecodedResource::AnimationDef(def) = read_resource(&resources_path, id) else {
+            panic!("expected an animation definition");
+        };
+
+        assert_eq!(def.sheet_id, page_id);
+        assert!(def.page_ids.is_empty());
+        assert_eq!(def.frames.len(), frames.len());
+
+        for (frame, &(source_x, source_y, width, height)) in def.frames.iter().zip(frames) {
+            let (x, y) = (frame.x as u32, frame.y as u32);
+            assert_eq!((frame.width as u32, frame.height as u32), (width, height));
+            assert!(x + width <= page_width && y + height <= page_height);
+
+            // Frames keep their pixels once moved.
+            for dy in 0..height {
+                for dx in 0..width {
+                    let offset = (((y + dy) * page_width + x + dx) * 4) as usize;
+
+                    assert_eq!(
+                        page.image.texture()[offset..offset + 4],
+                        image.get_pixel(source_x + dx, source_y + dy).0,
+                        "{name}"
+                    );
+                }
+            }
+
+            rects.push((x, y, width, height));
+        }
+    }
+
+    for (index, &(x, y, width, height)) in rects.iter().enumerate() {
+        for &(other_x, other_y, other_width, other_height) in &rects[index + 1..] {
+            let overlaps = x < other_x + other_width
+                && other_x < x + width
+                && y < other_y + other_height
+                && other_y < y + height;
+
+            assert!(
+                !overlaps,
+                "{:?} overlaps another frame",
+                (x, y, width, height)
+            );
+        }
+    }
+}